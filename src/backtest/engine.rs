@@ -0,0 +1,430 @@
+//! Bar-by-bar strategy simulation over historical candles.
+//!
+//! Signals are evaluated at each bar close on a trailing window, exactly as the
+//! worker evaluates them live. Entries fill at the close with slippage, stops and
+//! take-profits are checked against the following bars' high/low, and funding is
+//! accrued on open positions from the candles' funding rate.
+
+use crate::backtest::error::BacktestError;
+use crate::backtest::metrics::{EquityPoint, PerformanceMetrics};
+use crate::models::indicators::Candle;
+use crate::models::signal::SignalDirection;
+use crate::models::strategy::Strategy;
+use crate::signals::engine::{SignalEngine, MIN_CANDLES};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Simulation parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestConfig {
+    /// Starting account equity (quote currency)
+    pub initial_capital: f64,
+    /// Fraction of equity committed as margin per position (0-1]
+    pub position_fraction: f64,
+    /// Leverage applied to the committed margin
+    pub leverage: f64,
+    /// Taker fee per fill in basis points
+    pub fee_bps: f64,
+    /// Adverse price slippage per market fill in basis points
+    pub slippage_bps: f64,
+    /// Number of trailing candles passed to the evaluator at each bar
+    pub lookback: usize,
+    /// Accrue hourly funding on open positions
+    pub apply_funding: bool,
+}
+
+impl Default for BacktestConfig {
+    fn default() -> Self {
+        Self {
+            initial_capital: 10_000.0,
+            position_fraction: 1.0,
+            leverage: 1.0,
+            fee_bps: 4.5,
+            slippage_bps: 1.0,
+            lookback: 250,
+            apply_funding: true,
+        }
+    }
+}
+
+impl BacktestConfig {
+    pub fn validate(&self) -> Result<(), BacktestError> {
+        if !self.initial_capital.is_finite() || self.initial_capital <= 0.0 {
+            return Err(BacktestError::InvalidConfig(
+                "initial_capital must be positive".to_string(),
+            ));
+        }
+        if !(self.position_fraction > 0.0 && self.position_fraction <= 1.0) {
+            return Err(BacktestError::InvalidConfig(
+                "position_fraction must be in (0, 1]".to_string(),
+            ));
+        }
+        if !self.leverage.is_finite() || self.leverage <= 0.0 {
+            return Err(BacktestError::InvalidConfig(
+                "leverage must be positive".to_string(),
+            ));
+        }
+        if self.fee_bps < 0.0 || self.slippage_bps < 0.0 {
+            return Err(BacktestError::InvalidConfig(
+                "fee_bps and slippage_bps must not be negative".to_string(),
+            ));
+        }
+        if self.lookback < MIN_CANDLES {
+            return Err(BacktestError::InvalidConfig(format!(
+                "lookback must be at least {}",
+                MIN_CANDLES
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Why a simulated position was closed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExitReason {
+    StopLoss,
+    TakeProfit,
+    Reversal,
+    EndOfData,
+}
+
+/// A completed round-trip trade
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestTrade {
+    pub direction: SignalDirection,
+    pub entry_time: DateTime<Utc>,
+    pub entry_price: f64,
+    pub exit_time: DateTime<Utc>,
+    pub exit_price: f64,
+    /// Position size in base units
+    pub size: f64,
+    /// Net PnL after fees and funding
+    pub pnl: f64,
+    /// Entry and exit fees paid
+    pub fees: f64,
+    /// Funding paid (positive) or received (negative)
+    pub funding: f64,
+    /// Net PnL relative to equity at entry (percentage)
+    pub return_pct: f64,
+    pub exit_reason: ExitReason,
+}
+
+/// Output of a simulation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestResult {
+    pub trades: Vec<BacktestTrade>,
+    pub equity_curve: Vec<EquityPoint>,
+    pub metrics: PerformanceMetrics,
+}
+
+/// Signals of a strategy aligned with the candles they were evaluated on
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignalSeries {
+    /// Index of the first bar with enough history for the strategy's indicators
+    pub start: usize,
+    /// `None` where the strategy produced no signal
+    pub signals: Vec<Option<BarSignal>>,
+}
+
+/// Signal emitted at a bar close, reduced to what the simulator needs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BarSignal {
+    pub direction: SignalDirection,
+    pub sl_pct: f64,
+    pub tp_pct: f64,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct OpenPosition {
+    pub(crate) direction: SignalDirection,
+    entry_time: DateTime<Utc>,
+    entry_price: f64,
+    pub(crate) size: f64,
+    stop_price: Option<f64>,
+    take_profit_price: Option<f64>,
+    fees: f64,
+    funding: f64,
+    entry_equity: f64,
+}
+
+impl OpenPosition {
+    pub(crate) fn side(&self) -> f64 {
+        if self.direction == SignalDirection::Short {
+            -1.0
+        } else {
+            1.0
+        }
+    }
+
+    pub(crate) fn unrealized_pnl(&self, price: f64) -> f64 {
+        (price - self.entry_price) * self.size * self.side()
+    }
+}
+
+/// Replays a strategy over historical candles
+pub struct Backtester {
+    config: BacktestConfig,
+}
+
+impl Backtester {
+    pub fn new(config: BacktestConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn config(&self) -> &BacktestConfig {
+        &self.config
+    }
+
+    /// Evaluate and simulate a strategy over `candles` (oldest first)
+    pub fn run(
+        &self,
+        strategy: &Strategy,
+        candles: &[Candle],
+    ) -> Result<BacktestResult, BacktestError> {
+        self.config.validate()?;
+        let series = Self::generate_signals(strategy, candles, self.config.lookback)?;
+        Ok(self.simulate(candles, &series))
+    }
+
+    /// Evaluate the strategy at every bar close on a trailing window of `lookback` candles.
+    ///
+    /// Bars before the end of the strategy's warm-up are not evaluated.
+    pub fn generate_signals(
+        strategy: &Strategy,
+        candles: &[Candle],
+        lookback: usize,
+    ) -> Result<SignalSeries, BacktestError> {
+        if candles.len() < MIN_CANDLES {
+            return Err(BacktestError::InsufficientData {
+                required: MIN_CANDLES,
+                available: candles.len(),
+            });
+        }
+
+        let lookback = lookback.max(MIN_CANDLES);
+        let first = SignalEngine::warmup(strategy) - 1;
+        let signals = (0..candles.len())
+            .map(|i| {
+                if i < first {
+                    return None;
+                }
+                let start = (i + 1).saturating_sub(lookback);
+                SignalEngine::evaluate(&candles[start..=i], strategy).map(|signal| BarSignal {
+                    direction: signal.direction,
+                    sl_pct: signal.recommended_sl_pct,
+                    tp_pct: signal.recommended_tp_pct,
+                })
+            })
+            .collect();
+
+        Ok(SignalSeries {
+            start: first,
+            signals,
+        })
+    }
+
+    /// Simulate trading a precomputed signal series aligned with `candles`,
+    /// from the series' first bar
+    pub fn simulate(&self, candles: &[Candle], series: &SignalSeries) -> BacktestResult {
+        let config = &self.config;
+        let mut equity = config.initial_capital;
+        let mut position: Option<OpenPosition> = None;
+        let mut trades = Vec::new();
+        let mut equity_curve = Vec::new();
+
+        for i in series.start..candles.len() {
+            let candle = &candles[i];
+
+            if let Some(ref mut pos) = position {
+                if i > 0 {
+                    equity -= self.accrue_funding(pos, &candles[i - 1], candle);
+                }
+
+                if let Some((fill, reason)) = self.protective_exit(pos, candle) {
+                    let pos = position.take().unwrap();
+                    trades.push(self.close(pos, fill, candle.timestamp, reason, &mut equity));
+                }
+            }
+
+            if let Some(signal) = series.signals.get(i).copied().flatten() {
+                if signal.direction != SignalDirection::Neutral {
+                    let reverse = position
+                        .as_ref()
+                        .is_some_and(|pos| pos.direction != signal.direction);
+                    if reverse {
+                        let pos = position.take().unwrap();
+                        let fill = self.market_exit_price(&pos, candle.close);
+                        trades.push(self.close(
+                            pos,
+                            fill,
+                            candle.timestamp,
+                            ExitReason::Reversal,
+                            &mut equity,
+                        ));
+                    }
+                    if position.is_none() && equity > 0.0 {
+                        let notional = equity * config.position_fraction * config.leverage;
+                        position = Some(self.open(&signal, candle, notional, &mut equity));
+                    }
+                }
+            }
+
+            let marked = equity
+                + position
+                    .as_ref()
+                    .map(|pos| pos.unrealized_pnl(candle.close))
+                    .unwrap_or(0.0);
+            equity_curve.push(EquityPoint {
+                timestamp: candle.timestamp,
+                equity: marked,
+            });
+        }
+
+        if let (Some(pos), Some(last)) = (position.take(), candles.last()) {
+            let fill = self.market_exit_price(&pos, last.close);
+            trades.push(self.close(pos, fill, last.timestamp, ExitReason::EndOfData, &mut equity));
+            if let Some(point) = equity_curve.last_mut() {
+                point.equity = equity;
+            }
+        }
+
+        let metrics = PerformanceMetrics::calculate(config.initial_capital, &equity_curve, &trades);
+        BacktestResult {
+            trades,
+            equity_curve,
+            metrics,
+        }
+    }
+
+    /// Funding paid by an open position over the bar ending at `candle`
+    pub(crate) fn accrue_funding(
+        &self,
+        pos: &mut OpenPosition,
+        previous: &Candle,
+        candle: &Candle,
+    ) -> f64 {
+        let Some(rate) = candle.funding_rate.filter(|_| self.config.apply_funding) else {
+            return 0.0;
+        };
+        let hours = (candle.timestamp - previous.timestamp).num_seconds() as f64 / 3600.0;
+        let payment = pos.size * candle.close * rate * hours.max(0.0) * pos.side();
+        pos.funding += payment;
+        payment
+    }
+
+    /// Stop-loss or take-profit fill within a bar. The stop is assumed to trigger first
+    /// when both levels are inside the bar's range; gaps fill at the open.
+    pub(crate) fn protective_exit(
+        &self,
+        pos: &OpenPosition,
+        candle: &Candle,
+    ) -> Option<(f64, ExitReason)> {
+        let long = pos.direction == SignalDirection::Long;
+
+        if let Some(stop) = pos.stop_price {
+            let hit = if long { candle.low <= stop } else { candle.high >= stop };
+            if hit {
+                let fill = if long {
+                    stop.min(candle.open)
+                } else {
+                    stop.max(candle.open)
+                };
+                return Some((self.market_exit_price(pos, fill), ExitReason::StopLoss));
+            }
+        }
+
+        if let Some(target) = pos.take_profit_price {
+            let hit = if long { candle.high >= target } else { candle.low <= target };
+            if hit {
+                let fill = if long {
+                    target.max(candle.open)
+                } else {
+                    target.min(candle.open)
+                };
+                return Some((fill, ExitReason::TakeProfit));
+            }
+        }
+
+        None
+    }
+
+    /// Market exit fill for `pos` at `price` after slippage
+    pub(crate) fn market_exit_price(&self, pos: &OpenPosition, price: f64) -> f64 {
+        self.slipped(price, -pos.side())
+    }
+
+    /// Apply slippage against a fill in direction `side` (+1 buy, -1 sell)
+    fn slipped(&self, price: f64, side: f64) -> f64 {
+        price * (1.0 + side * self.config.slippage_bps / 10_000.0)
+    }
+
+    /// Open a position of `notional` at the bar close, charging the entry fee to `equity`
+    pub(crate) fn open(
+        &self,
+        signal: &BarSignal,
+        candle: &Candle,
+        notional: f64,
+        equity: &mut f64,
+    ) -> OpenPosition {
+        let side = if signal.direction == SignalDirection::Short {
+            -1.0
+        } else {
+            1.0
+        };
+        let entry_price = self.slipped(candle.close, side);
+        let fee = notional * self.config.fee_bps / 10_000.0;
+        let entry_equity = *equity;
+        *equity -= fee;
+
+        let stop_price = (signal.sl_pct > 0.0)
+            .then(|| entry_price * (1.0 - side * signal.sl_pct / 100.0));
+        let take_profit_price = (signal.tp_pct > 0.0)
+            .then(|| entry_price * (1.0 + side * signal.tp_pct / 100.0));
+
+        OpenPosition {
+            direction: signal.direction,
+            entry_time: candle.timestamp,
+            entry_price,
+            size: notional / entry_price,
+            stop_price,
+            take_profit_price,
+            fees: fee,
+            funding: 0.0,
+            entry_equity,
+        }
+    }
+
+    /// Close `pos` at `exit_price`, crediting realized PnL net of the exit fee to `equity`
+    pub(crate) fn close(
+        &self,
+        pos: OpenPosition,
+        exit_price: f64,
+        exit_time: DateTime<Utc>,
+        exit_reason: ExitReason,
+        equity: &mut f64,
+    ) -> BacktestTrade {
+        let gross = pos.unrealized_pnl(exit_price);
+        let exit_fee = exit_price * pos.size * self.config.fee_bps / 10_000.0;
+        *equity += gross - exit_fee;
+
+        let fees = pos.fees + exit_fee;
+        let pnl = gross - fees - pos.funding;
+        BacktestTrade {
+            direction: pos.direction,
+            entry_time: pos.entry_time,
+            entry_price: pos.entry_price,
+            exit_time,
+            exit_price,
+            size: pos.size,
+            pnl,
+            fees,
+            funding: pos.funding,
+            return_pct: if pos.entry_equity > 0.0 {
+                pnl / pos.entry_equity * 100.0
+            } else {
+                0.0
+            },
+            exit_reason,
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use std::fmt;
+
+#[derive(Debug, Clone)]
+pub enum BacktestError {
+    InsufficientData { required: usize, available: usize },
+    InvalidConfig(String),
+    InvalidParameter(String),
+    /// A parallel worker panicked
+    WorkerPanicked(String),
+}
+
+impl fmt::Display for BacktestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BacktestError::InsufficientData {
+                required,
+                available,
+            } => write!(
+                f,
+                "Insufficient data: {} candles required, {} available",
+                required, available
+            ),
+            BacktestError::InvalidConfig(msg) => write!(f, "Invalid backtest config: {}", msg),
+            BacktestError::InvalidParameter(msg) => write!(f, "Invalid parameter: {}", msg),
+            BacktestError::WorkerPanicked(msg) => write!(f, "Backtest worker panicked: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for BacktestError {}
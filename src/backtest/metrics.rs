@@ -0,0 +1,172 @@
+//! Performance statistics computed from equity curves and trade lists.
+
+use crate::backtest::engine::BacktestTrade;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Profit factor reported when a run has winners but no losing trades
+pub const PROFIT_FACTOR_CAP: f64 = 100.0;
+
+const SECONDS_PER_YEAR: f64 = 365.0 * 24.0 * 60.0 * 60.0;
+
+/// Marked-to-market account equity at a bar close
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EquityPoint {
+    pub timestamp: DateTime<Utc>,
+    pub equity: f64,
+}
+
+/// Summary statistics of a simulated run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PerformanceMetrics {
+    pub initial_equity: f64,
+    pub final_equity: f64,
+    /// Net return over the run (percentage)
+    pub total_return_pct: f64,
+    /// Largest peak-to-trough equity decline (percentage, positive)
+    pub max_drawdown_pct: f64,
+    /// Annualized Sharpe ratio of bar returns (zero risk-free rate)
+    pub sharpe_ratio: f64,
+    /// Gross profit divided by gross loss, capped at `PROFIT_FACTOR_CAP`
+    pub profit_factor: f64,
+    /// Fraction of trades closed with positive PnL (0-1)
+    pub win_rate: f64,
+    pub total_trades: usize,
+}
+
+impl PerformanceMetrics {
+    /// Compute metrics for a run starting at `initial_equity`
+    pub fn calculate(
+        initial_equity: f64,
+        equity_curve: &[EquityPoint],
+        trades: &[BacktestTrade],
+    ) -> Self {
+        let final_equity = equity_curve
+            .last()
+            .map(|p| p.equity)
+            .unwrap_or(initial_equity);
+        let total_return_pct = if initial_equity > 0.0 {
+            (final_equity / initial_equity - 1.0) * 100.0
+        } else {
+            0.0
+        };
+        let winners = trades.iter().filter(|t| t.pnl > 0.0).count();
+        let win_rate = if trades.is_empty() {
+            0.0
+        } else {
+            winners as f64 / trades.len() as f64
+        };
+
+        Self {
+            initial_equity,
+            final_equity,
+            total_return_pct,
+            max_drawdown_pct: max_drawdown_pct(equity_curve.iter().map(|p| p.equity)),
+            sharpe_ratio: sharpe_ratio(equity_curve),
+            profit_factor: profit_factor(trades.iter().map(|t| t.pnl)),
+            win_rate,
+            total_trades: trades.len(),
+        }
+    }
+}
+
+/// Largest peak-to-trough decline of an equity series, as a positive percentage
+pub fn max_drawdown_pct(equity: impl IntoIterator<Item = f64>) -> f64 {
+    let mut peak = f64::NEG_INFINITY;
+    let mut max_drawdown: f64 = 0.0;
+    for value in equity {
+        peak = peak.max(value);
+        if peak > 0.0 {
+            max_drawdown = max_drawdown.max((peak - value) / peak * 100.0);
+        }
+    }
+    max_drawdown
+}
+
+/// Annualized Sharpe ratio from bar-to-bar returns of an equity curve.
+///
+/// The annualization factor is derived from the median spacing between points.
+pub fn sharpe_ratio(equity_curve: &[EquityPoint]) -> f64 {
+    if equity_curve.len() < 3 {
+        return 0.0;
+    }
+
+    let returns: Vec<f64> = equity_curve
+        .windows(2)
+        .filter(|w| w[0].equity > 0.0)
+        .map(|w| w[1].equity / w[0].equity - 1.0)
+        .collect();
+    if returns.len() < 2 {
+        return 0.0;
+    }
+
+    let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+    let variance =
+        returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (returns.len() - 1) as f64;
+    let std_dev = variance.sqrt();
+    if std_dev <= f64::EPSILON {
+        return 0.0;
+    }
+
+    let mut spacings: Vec<i64> = equity_curve
+        .windows(2)
+        .map(|w| (w[1].timestamp - w[0].timestamp).num_seconds())
+        .filter(|s| *s > 0)
+        .collect();
+    if spacings.is_empty() {
+        return 0.0;
+    }
+    spacings.sort_unstable();
+    let bar_seconds = spacings[spacings.len() / 2] as f64;
+    let periods_per_year = SECONDS_PER_YEAR / bar_seconds;
+
+    mean / std_dev * periods_per_year.sqrt()
+}
+
+/// Gross profit over gross loss of a PnL series
+pub fn profit_factor(pnls: impl IntoIterator<Item = f64>) -> f64 {
+    let (gross_profit, gross_loss) = pnls.into_iter().fold((0.0, 0.0), |(p, l), pnl| {
+        if pnl > 0.0 {
+            (p + pnl, l)
+        } else {
+            (p, l - pnl)
+        }
+    });
+
+    if gross_loss <= f64::EPSILON {
+        if gross_profit > 0.0 {
+            PROFIT_FACTOR_CAP
+        } else {
+            0.0
+        }
+    } else {
+        (gross_profit / gross_loss).min(PROFIT_FACTOR_CAP)
+    }
+}
+
+/// Pearson correlation of two equally long series; zero if either is constant
+pub fn correlation(a: &[f64], b: &[f64]) -> f64 {
+    let n = a.len().min(b.len());
+    if n < 2 {
+        return 0.0;
+    }
+    let (a, b) = (&a[..n], &b[..n]);
+    let mean_a = a.iter().sum::<f64>() / n as f64;
+    let mean_b = b.iter().sum::<f64>() / n as f64;
+
+    let mut covariance = 0.0;
+    let mut var_a = 0.0;
+    let mut var_b = 0.0;
+    for (x, y) in a.iter().zip(b) {
+        covariance += (x - mean_a) * (y - mean_b);
+        var_a += (x - mean_a).powi(2);
+        var_b += (y - mean_b).powi(2);
+    }
+
+    let denominator = (var_a * var_b).sqrt();
+    if denominator <= f64::EPSILON {
+        0.0
+    } else {
+        covariance / denominator
+    }
+}
@@ -0,0 +1,17 @@
+//! Historical strategy simulation and parameter optimization.
+
+pub mod engine;
+pub mod error;
+pub mod metrics;
+pub mod monte_carlo;
+pub mod optimizer;
+pub mod portfolio;
+pub mod walk_forward;
+
+pub use engine::*;
+pub use error::BacktestError;
+pub use metrics::*;
+pub use monte_carlo::*;
+pub use optimizer::*;
+pub use portfolio::*;
+pub use walk_forward::*;
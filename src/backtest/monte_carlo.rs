@@ -0,0 +1,267 @@
+//! Monte Carlo robustness analysis of backtest trades.
+//!
+//! Trade returns are replayed in randomized sequences to show how much of a
+//! backtest's result depends on the particular order and selection of trades.
+//! Each trade compounds equity by its `return_pct`, so position sizing scales
+//! with the simulated equity rather than the original one.
+
+use crate::backtest::engine::BacktestTrade;
+use crate::backtest::error::BacktestError;
+use crate::backtest::metrics::max_drawdown_pct;
+use crate::common::random::SeededRng;
+use serde::{Deserialize, Serialize};
+
+/// How each simulated trade sequence is drawn from the original trades
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResampleMethod {
+    /// Same trades in a random order
+    Shuffle,
+    /// Same number of trades drawn with replacement
+    Bootstrap,
+    /// Original order with each trade dropped with `skip_probability`
+    RandomSkip,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonteCarloConfig {
+    pub iterations: usize,
+    pub seed: u64,
+    /// Probability of dropping a trade in `RandomSkip`
+    pub skip_probability: f64,
+    /// Drawdown (percentage of starting equity) counted as ruin
+    pub ruin_drawdown_pct: f64,
+    /// Percentiles reported for each distribution (0-100)
+    pub percentiles: Vec<f64>,
+}
+
+impl Default for MonteCarloConfig {
+    fn default() -> Self {
+        Self {
+            iterations: 1000,
+            seed: 42,
+            skip_probability: 0.1,
+            ruin_drawdown_pct: 50.0,
+            percentiles: vec![5.0, 25.0, 50.0, 75.0, 95.0],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PercentileValue {
+    pub percentile: f64,
+    pub value: f64,
+}
+
+/// Summary of simulated values
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Distribution {
+    pub mean: f64,
+    pub min: f64,
+    pub max: f64,
+    pub percentiles: Vec<PercentileValue>,
+}
+
+impl Distribution {
+    fn from_samples(mut samples: Vec<f64>, percentiles: &[f64]) -> Self {
+        samples.sort_by(|a, b| a.total_cmp(b));
+        let mean = samples.iter().sum::<f64>() / samples.len().max(1) as f64;
+        Self {
+            mean,
+            min: samples.first().copied().unwrap_or(0.0),
+            max: samples.last().copied().unwrap_or(0.0),
+            percentiles: percentiles
+                .iter()
+                .map(|&p| PercentileValue {
+                    percentile: p,
+                    value: percentile(&samples, p),
+                })
+                .collect(),
+        }
+    }
+
+    /// Value at percentile `p`, if it was requested
+    pub fn get(&self, p: f64) -> Option<f64> {
+        self.percentiles
+            .iter()
+            .find(|v| (v.percentile - p).abs() < f64::EPSILON)
+            .map(|v| v.value)
+    }
+}
+
+/// Linearly interpolated percentile of sorted samples
+pub fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (p.clamp(0.0, 100.0) / 100.0) * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    let weight = rank - lower as f64;
+    sorted[lower] * (1.0 - weight) + sorted[upper] * weight
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonteCarloResult {
+    pub method: ResampleMethod,
+    pub iterations: usize,
+    pub final_equity: Distribution,
+    pub max_drawdown_pct: Distribution,
+    /// Fraction of simulations that hit the ruin drawdown (0-1)
+    pub risk_of_ruin: f64,
+    /// Final equity of the original trade sequence
+    pub original_final_equity: f64,
+    /// Max drawdown of the original trade sequence
+    pub original_max_drawdown_pct: f64,
+}
+
+/// Results for every resampling method
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonteCarloReport {
+    pub shuffle: MonteCarloResult,
+    pub bootstrap: MonteCarloResult,
+    pub random_skip: MonteCarloResult,
+}
+
+pub struct MonteCarlo {
+    config: MonteCarloConfig,
+}
+
+impl MonteCarlo {
+    pub fn new(config: MonteCarloConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn config(&self) -> &MonteCarloConfig {
+        &self.config
+    }
+
+    /// Run all resampling methods over `trades`
+    pub fn analyze(
+        &self,
+        trades: &[BacktestTrade],
+        initial_capital: f64,
+    ) -> Result<MonteCarloReport, BacktestError> {
+        Ok(MonteCarloReport {
+            shuffle: self.run(trades, initial_capital, ResampleMethod::Shuffle)?,
+            bootstrap: self.run(trades, initial_capital, ResampleMethod::Bootstrap)?,
+            random_skip: self.run(trades, initial_capital, ResampleMethod::RandomSkip)?,
+        })
+    }
+
+    /// Simulate `iterations` resampled trade sequences starting at `initial_capital`
+    pub fn run(
+        &self,
+        trades: &[BacktestTrade],
+        initial_capital: f64,
+        method: ResampleMethod,
+    ) -> Result<MonteCarloResult, BacktestError> {
+        self.validate(initial_capital)?;
+        if trades.is_empty() {
+            return Err(BacktestError::InsufficientData {
+                required: 1,
+                available: 0,
+            });
+        }
+
+        let returns: Vec<f64> = trades.iter().map(|t| t.return_pct / 100.0).collect();
+        let ruin_equity = initial_capital * (1.0 - self.config.ruin_drawdown_pct / 100.0);
+        let (original_final_equity, original_max_drawdown_pct, _) =
+            Self::replay(&returns, initial_capital, ruin_equity);
+
+        // Offset the seed per method so each method draws independent sequences
+        let mut rng = SeededRng::new(self.config.seed ^ method as u64);
+        let mut final_equities = Vec::with_capacity(self.config.iterations);
+        let mut drawdowns = Vec::with_capacity(self.config.iterations);
+        let mut ruined = 0usize;
+        let mut sample = Vec::with_capacity(returns.len());
+
+        for _ in 0..self.config.iterations {
+            self.resample(&returns, method, &mut rng, &mut sample);
+            let (final_equity, drawdown, hit_ruin) =
+                Self::replay(&sample, initial_capital, ruin_equity);
+            final_equities.push(final_equity);
+            drawdowns.push(drawdown);
+            if hit_ruin {
+                ruined += 1;
+            }
+        }
+
+        Ok(MonteCarloResult {
+            method,
+            iterations: self.config.iterations,
+            final_equity: Distribution::from_samples(final_equities, &self.config.percentiles),
+            max_drawdown_pct: Distribution::from_samples(drawdowns, &self.config.percentiles),
+            risk_of_ruin: ruined as f64 / self.config.iterations as f64,
+            original_final_equity,
+            original_max_drawdown_pct,
+        })
+    }
+
+    fn validate(&self, initial_capital: f64) -> Result<(), BacktestError> {
+        if self.config.iterations == 0 {
+            return Err(BacktestError::InvalidConfig(
+                "iterations must be positive".to_string(),
+            ));
+        }
+        if !(0.0..1.0).contains(&self.config.skip_probability) {
+            return Err(BacktestError::InvalidConfig(
+                "skip_probability must be in [0, 1)".to_string(),
+            ));
+        }
+        if !(self.config.ruin_drawdown_pct > 0.0 && self.config.ruin_drawdown_pct <= 100.0) {
+            return Err(BacktestError::InvalidConfig(
+                "ruin_drawdown_pct must be in (0, 100]".to_string(),
+            ));
+        }
+        if !initial_capital.is_finite() || initial_capital <= 0.0 {
+            return Err(BacktestError::InvalidConfig(
+                "initial_capital must be positive".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn resample(
+        &self,
+        returns: &[f64],
+        method: ResampleMethod,
+        rng: &mut SeededRng,
+        sample: &mut Vec<f64>,
+    ) {
+        sample.clear();
+        match method {
+            ResampleMethod::Shuffle => {
+                sample.extend_from_slice(returns);
+                // Fisher-Yates
+                for i in (1..sample.len()).rev() {
+                    sample.swap(i, rng.next_index(i + 1));
+                }
+            }
+            ResampleMethod::Bootstrap => {
+                sample.extend((0..returns.len()).map(|_| returns[rng.next_index(returns.len())]));
+            }
+            ResampleMethod::RandomSkip => {
+                sample.extend(
+                    returns
+                        .iter()
+                        .filter(|_| rng.next_f64() >= self.config.skip_probability),
+                );
+            }
+        }
+    }
+
+    /// Compound `returns` from `initial`, returning final equity, max drawdown and
+    /// whether equity fell to `ruin_equity`
+    fn replay(returns: &[f64], initial: f64, ruin_equity: f64) -> (f64, f64, bool) {
+        let mut equity = initial;
+        let mut ruined = false;
+        let path = std::iter::once(initial).chain(returns.iter().map(|r| {
+            equity = (equity * (1.0 + r)).max(0.0);
+            ruined |= equity <= ruin_equity;
+            equity
+        }));
+        let drawdown = max_drawdown_pct(path);
+        (equity, drawdown, ruined)
+    }
+}
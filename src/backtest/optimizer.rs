@@ -0,0 +1,548 @@
+//! Strategy parameter optimization.
+//!
+//! Searches rule thresholds, rule weights, aggregation thresholds and indicator
+//! parameters with grid, random or genetic search. Each candidate is scored by a
+//! full backtest; candidates are evaluated in parallel across OS threads.
+
+use crate::backtest::engine::{BacktestConfig, Backtester};
+use crate::backtest::error::BacktestError;
+use crate::backtest::metrics::PerformanceMetrics;
+use crate::common::random::SeededRng;
+use crate::models::indicators::Candle;
+use crate::models::strategy::{Rule, Strategy, StrategyConfig};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::thread;
+
+const MAX_GRID_SIZE: usize = 100_000;
+const MAX_RANGE_VALUES: usize = 10_000;
+const TOURNAMENT_SIZE: usize = 3;
+
+/// A tunable value inside a strategy configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ParameterTarget {
+    /// `condition.threshold` of a rule
+    RuleThreshold { rule_id: String },
+    /// `weight` of a rule
+    RuleWeight { rule_id: String },
+    /// `aggregation.thresholds.long_min`
+    LongMin,
+    /// `aggregation.thresholds.short_max`
+    ShortMax,
+    /// A `condition.indicator_params` entry of a rule (e.g. `period`)
+    IndicatorParam { rule_id: String, param: String },
+}
+
+impl ParameterTarget {
+    /// Stable key used in reported parameter sets
+    pub fn label(&self) -> String {
+        match self {
+            ParameterTarget::RuleThreshold { rule_id } => format!("{}.threshold", rule_id),
+            ParameterTarget::RuleWeight { rule_id } => format!("{}.weight", rule_id),
+            ParameterTarget::LongMin => "long_min".to_string(),
+            ParameterTarget::ShortMax => "short_max".to_string(),
+            ParameterTarget::IndicatorParam { rule_id, param } => {
+                format!("{}.{}", rule_id, param)
+            }
+        }
+    }
+
+    /// Write `value` into `config`
+    pub fn apply(&self, config: &mut StrategyConfig, value: f64) -> Result<(), BacktestError> {
+        match self {
+            ParameterTarget::LongMin => {
+                config.aggregation.thresholds.long_min = value.round() as i32;
+            }
+            ParameterTarget::ShortMax => {
+                config.aggregation.thresholds.short_max = value.round() as i32;
+            }
+            ParameterTarget::RuleWeight { rule_id } => {
+                find_rule_mut(&mut config.rules, rule_id)
+                    .ok_or_else(|| unknown_rule(rule_id))?
+                    .weight = Some(value);
+            }
+            ParameterTarget::RuleThreshold { rule_id } => {
+                let condition = find_rule_mut(&mut config.rules, rule_id)
+                    .ok_or_else(|| unknown_rule(rule_id))?
+                    .condition
+                    .as_mut()
+                    .ok_or_else(|| no_condition(rule_id))?;
+                condition.threshold = Some(value);
+            }
+            ParameterTarget::IndicatorParam { rule_id, param } => {
+                let condition = find_rule_mut(&mut config.rules, rule_id)
+                    .ok_or_else(|| unknown_rule(rule_id))?
+                    .condition
+                    .as_mut()
+                    .ok_or_else(|| no_condition(rule_id))?;
+                let json = if value.fract() == 0.0 {
+                    Value::from(value as i64)
+                } else {
+                    Value::from(value)
+                };
+                condition.indicator_params.insert(param.clone(), json);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn find_rule_mut<'a>(rules: &'a mut [Rule], rule_id: &str) -> Option<&'a mut Rule> {
+    for rule in rules {
+        if rule.id == rule_id {
+            return Some(rule);
+        }
+        if let Some(ref mut children) = rule.children {
+            if let Some(found) = find_rule_mut(children, rule_id) {
+                return Some(found);
+            }
+        }
+    }
+    None
+}
+
+fn unknown_rule(rule_id: &str) -> BacktestError {
+    BacktestError::InvalidParameter(format!("rule '{}' not found", rule_id))
+}
+
+fn no_condition(rule_id: &str) -> BacktestError {
+    BacktestError::InvalidParameter(format!("rule '{}' has no condition", rule_id))
+}
+
+/// Inclusive range of values searched for one parameter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParameterRange {
+    pub target: ParameterTarget,
+    pub min: f64,
+    pub max: f64,
+    pub step: f64,
+}
+
+impl ParameterRange {
+    pub fn new(target: ParameterTarget, min: f64, max: f64, step: f64) -> Self {
+        Self {
+            target,
+            min,
+            max,
+            step,
+        }
+    }
+
+    /// Discrete values `min, min + step, ...` up to and including `max`
+    pub fn values(&self) -> Result<Vec<f64>, BacktestError> {
+        if !self.min.is_finite() || !self.max.is_finite() || self.min > self.max {
+            return Err(BacktestError::InvalidParameter(format!(
+                "{}: invalid range [{}, {}]",
+                self.target.label(),
+                self.min,
+                self.max
+            )));
+        }
+        if !self.step.is_finite() || self.step <= 0.0 {
+            return Err(BacktestError::InvalidParameter(format!(
+                "{}: step must be positive",
+                self.target.label()
+            )));
+        }
+
+        // Checked before allocating: a tiny step makes the count huge or infinite
+        let steps = ((self.max - self.min) / self.step + 1e-9).floor();
+        if !steps.is_finite() || steps >= MAX_RANGE_VALUES as f64 {
+            return Err(BacktestError::InvalidParameter(format!(
+                "{}: range has more than {} values",
+                self.target.label(),
+                MAX_RANGE_VALUES
+            )));
+        }
+        let count = steps as usize + 1;
+        Ok((0..count)
+            .map(|i| {
+                let value = self.min + self.step * i as f64;
+                // Trim float noise so integral steps stay integral
+                (value * 1e9).round() / 1e9
+            })
+            .collect())
+    }
+}
+
+/// How the parameter space is explored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SearchMethod {
+    /// Every combination of range values
+    Grid,
+    /// Uniformly sampled combinations
+    Random { samples: usize },
+    /// Tournament selection, uniform crossover and per-gene mutation with elitism
+    Genetic {
+        population: usize,
+        generations: usize,
+        mutation_rate: f64,
+    },
+}
+
+/// Metric maximized by the optimizer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ObjectiveMetric {
+    Sharpe,
+    ProfitFactor,
+    TotalReturn,
+}
+
+/// Optimization objective with optional constraints
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Objective {
+    pub metric: ObjectiveMetric,
+    /// Candidates whose max drawdown exceeds this percentage are discarded
+    pub max_drawdown_pct: Option<f64>,
+    /// Candidates with fewer trades are discarded
+    pub min_trades: usize,
+}
+
+impl Objective {
+    pub fn new(metric: ObjectiveMetric) -> Self {
+        Self {
+            metric,
+            max_drawdown_pct: None,
+            min_trades: 1,
+        }
+    }
+
+    pub fn with_max_drawdown(mut self, max_drawdown_pct: f64) -> Self {
+        self.max_drawdown_pct = Some(max_drawdown_pct);
+        self
+    }
+
+    pub fn with_min_trades(mut self, min_trades: usize) -> Self {
+        self.min_trades = min_trades;
+        self
+    }
+
+    /// Score a run, or `None` if it violates a constraint
+    pub fn score(&self, metrics: &PerformanceMetrics) -> Option<f64> {
+        if metrics.total_trades < self.min_trades {
+            return None;
+        }
+        if let Some(cap) = self.max_drawdown_pct {
+            if metrics.max_drawdown_pct > cap {
+                return None;
+            }
+        }
+        let score = match self.metric {
+            ObjectiveMetric::Sharpe => metrics.sharpe_ratio,
+            ObjectiveMetric::ProfitFactor => metrics.profit_factor,
+            ObjectiveMetric::TotalReturn => metrics.total_return_pct,
+        };
+        score.is_finite().then_some(score)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OptimizerConfig {
+    pub search: SearchMethod,
+    pub objective: Objective,
+    /// Number of best candidates returned
+    pub top_n: usize,
+    /// Seed for random and genetic search
+    pub seed: u64,
+    /// Worker threads; defaults to available parallelism
+    pub threads: Option<usize>,
+    pub backtest: BacktestConfig,
+}
+
+impl Default for OptimizerConfig {
+    fn default() -> Self {
+        Self {
+            search: SearchMethod::Grid,
+            objective: Objective::new(ObjectiveMetric::Sharpe),
+            top_n: 5,
+            seed: 42,
+            threads: None,
+            backtest: BacktestConfig::default(),
+        }
+    }
+}
+
+/// A scored parameter set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OptimizationCandidate {
+    /// Parameter values keyed by `ParameterTarget::label`
+    pub parameters: BTreeMap<String, f64>,
+    /// Strategy configuration with the parameters applied
+    pub config: StrategyConfig,
+    pub metrics: PerformanceMetrics,
+    pub score: f64,
+}
+
+pub struct Optimizer {
+    config: OptimizerConfig,
+}
+
+impl Optimizer {
+    pub fn new(config: OptimizerConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn config(&self) -> &OptimizerConfig {
+        &self.config
+    }
+
+    /// Search `space` for the best configurations of `strategy` over `candles`.
+    ///
+    /// Returns up to `top_n` candidates sorted by descending score. Candidates that
+    /// violate the objective's constraints are never returned.
+    pub fn optimize(
+        &self,
+        strategy: &Strategy,
+        space: &[ParameterRange],
+        candles: &[Candle],
+    ) -> Result<Vec<OptimizationCandidate>, BacktestError> {
+        self.config.backtest.validate()?;
+        if space.is_empty() {
+            return Err(BacktestError::InvalidParameter(
+                "parameter space is empty".to_string(),
+            ));
+        }
+
+        let values = space
+            .iter()
+            .map(|range| range.values())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Fail fast on targets that don't exist in the strategy
+        let mut probe = strategy.config.clone();
+        for (range, range_values) in space.iter().zip(&values) {
+            range.target.apply(&mut probe, range_values[0])?;
+        }
+
+        let search = Search {
+            optimizer: self,
+            strategy,
+            space,
+            values: &values,
+            candles,
+        };
+
+        let mut scored = match self.config.search {
+            SearchMethod::Grid => search.grid()?,
+            SearchMethod::Random { samples } => search.random(samples)?,
+            SearchMethod::Genetic {
+                population,
+                generations,
+                mutation_rate,
+            } => search.genetic(population, generations, mutation_rate)?,
+        };
+
+        scored.sort_by(|a, b| b.score.total_cmp(&a.score));
+        scored.truncate(self.config.top_n);
+        Ok(scored)
+    }
+
+    fn thread_count(&self) -> usize {
+        self.config
+            .threads
+            .unwrap_or_else(|| thread::available_parallelism().map(|n| n.get()).unwrap_or(1))
+            .max(1)
+    }
+}
+
+type Genome = Vec<usize>;
+
+struct Search<'a> {
+    optimizer: &'a Optimizer,
+    strategy: &'a Strategy,
+    space: &'a [ParameterRange],
+    values: &'a [Vec<f64>],
+    candles: &'a [Candle],
+}
+
+impl Search<'_> {
+    fn grid(&self) -> Result<Vec<OptimizationCandidate>, BacktestError> {
+        let size = self
+            .values
+            .iter()
+            .try_fold(1usize, |acc, v| acc.checked_mul(v.len()))
+            .filter(|size| *size <= MAX_GRID_SIZE)
+            .ok_or_else(|| {
+                BacktestError::InvalidParameter(format!(
+                    "grid exceeds {} combinations; use random or genetic search",
+                    MAX_GRID_SIZE
+                ))
+            })?;
+
+        let genomes: Vec<Genome> = (0..size)
+            .map(|mut n| {
+                self.values
+                    .iter()
+                    .map(|v| {
+                        let index = n % v.len();
+                        n /= v.len();
+                        index
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Ok(self
+            .evaluate(&genomes)?
+            .into_iter()
+            .flatten()
+            .collect())
+    }
+
+    fn random(&self, samples: usize) -> Result<Vec<OptimizationCandidate>, BacktestError> {
+        let mut rng = SeededRng::new(self.optimizer.config.seed);
+        let mut genomes: Vec<Genome> = (0..samples).map(|_| self.random_genome(&mut rng)).collect();
+        genomes.sort();
+        genomes.dedup();
+
+        Ok(self.evaluate(&genomes)?.into_iter().flatten().collect())
+    }
+
+    fn genetic(
+        &self,
+        population: usize,
+        generations: usize,
+        mutation_rate: f64,
+    ) -> Result<Vec<OptimizationCandidate>, BacktestError> {
+        let population = population.max(2);
+        let elites = (population / 10).max(1);
+        let mut rng = SeededRng::new(self.optimizer.config.seed);
+        let mut cache: BTreeMap<Genome, Option<OptimizationCandidate>> = BTreeMap::new();
+
+        let mut current: Vec<Genome> = (0..population)
+            .map(|_| self.random_genome(&mut rng))
+            .collect();
+
+        for generation in 0..=generations {
+            let mut pending: Vec<Genome> = current
+                .iter()
+                .filter(|g| !cache.contains_key(*g))
+                .cloned()
+                .collect();
+            pending.sort();
+            pending.dedup();
+            let results = self.evaluate(&pending)?;
+            cache.extend(pending.into_iter().zip(results));
+
+            if generation == generations {
+                break;
+            }
+
+            // Rank the population; infeasible genomes sort last
+            let fitness = |g: &Genome| {
+                cache
+                    .get(g)
+                    .and_then(|c| c.as_ref())
+                    .map(|c| c.score)
+                    .unwrap_or(f64::NEG_INFINITY)
+            };
+            current.sort_by(|a, b| fitness(b).total_cmp(&fitness(a)));
+
+            let mut next: Vec<Genome> = current.iter().take(elites).cloned().collect();
+            while next.len() < population {
+                let a = Self::tournament(&current, &fitness, &mut rng);
+                let b = Self::tournament(&current, &fitness, &mut rng);
+                let mut child: Genome = a
+                    .iter()
+                    .zip(b)
+                    .map(|(x, y)| if rng.next_f64() < 0.5 { *x } else { *y })
+                    .collect();
+                for (gene, choices) in child.iter_mut().zip(self.values) {
+                    if rng.next_f64() < mutation_rate {
+                        *gene = rng.next_index(choices.len());
+                    }
+                }
+                next.push(child);
+            }
+            current = next;
+        }
+
+        Ok(cache.into_values().flatten().collect())
+    }
+
+    fn tournament<'g>(
+        population: &'g [Genome],
+        fitness: &impl Fn(&Genome) -> f64,
+        rng: &mut SeededRng,
+    ) -> &'g Genome {
+        let mut best = &population[rng.next_index(population.len())];
+        for _ in 1..TOURNAMENT_SIZE {
+            let contender = &population[rng.next_index(population.len())];
+            if fitness(contender) > fitness(best) {
+                best = contender;
+            }
+        }
+        best
+    }
+
+    fn random_genome(&self, rng: &mut SeededRng) -> Genome {
+        self.values.iter().map(|v| rng.next_index(v.len())).collect()
+    }
+
+    /// Backtest genomes in parallel, preserving input order
+    fn evaluate(
+        &self,
+        genomes: &[Genome],
+    ) -> Result<Vec<Option<OptimizationCandidate>>, BacktestError> {
+        if genomes.is_empty() {
+            return Ok(Vec::new());
+        }
+        let threads = self.optimizer.thread_count().min(genomes.len());
+        let chunk_size = genomes.len().div_ceil(threads);
+
+        thread::scope(|scope| {
+            let handles: Vec<_> = genomes
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|genome| self.evaluate_one(genome))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+
+            // Join every worker before reporting, or the scope re-raises the panic
+            let joined: Vec<_> = handles.into_iter().map(|handle| handle.join()).collect();
+            let mut results = Vec::with_capacity(genomes.len());
+            for chunk in joined {
+                let chunk = chunk.map_err(|panic| {
+                    let message = panic
+                        .downcast_ref::<&str>()
+                        .map(|m| m.to_string())
+                        .or_else(|| panic.downcast_ref::<String>().cloned())
+                        .unwrap_or_else(|| "unknown panic".to_string());
+                    BacktestError::WorkerPanicked(message)
+                })?;
+                results.extend(chunk);
+            }
+            Ok(results)
+        })
+    }
+
+    fn evaluate_one(&self, genome: &Genome) -> Option<OptimizationCandidate> {
+        let mut strategy = self.strategy.clone();
+        let mut parameters = BTreeMap::new();
+        for ((range, choices), index) in self.space.iter().zip(self.values).zip(genome) {
+            let value = choices[*index];
+            range.target.apply(&mut strategy.config, value).ok()?;
+            parameters.insert(range.target.label(), value);
+        }
+
+        let backtester = Backtester::new(self.optimizer.config.backtest.clone());
+        let result = backtester.run(&strategy, self.candles).ok()?;
+        let score = self.optimizer.config.objective.score(&result.metrics)?;
+
+        Some(OptimizationCandidate {
+            parameters,
+            config: strategy.config,
+            metrics: result.metrics,
+            score,
+        })
+    }
+}
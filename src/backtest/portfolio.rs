@@ -0,0 +1,397 @@
+//! Multi-strategy, multi-symbol portfolio backtesting.
+//!
+//! Every leg (a strategy trading its symbol) draws positions from one shared
+//! capital pool. Legs are stepped on a merged timeline so exits, entries and
+//! marking happen in timestamp order across symbols.
+
+use crate::backtest::engine::{
+    BacktestConfig, BacktestTrade, Backtester, ExitReason, OpenPosition, SignalSeries,
+};
+use crate::backtest::error::BacktestError;
+use crate::backtest::metrics::{correlation, EquityPoint, PerformanceMetrics};
+use crate::models::indicators::Candle;
+use crate::models::signal::SignalDirection;
+use crate::models::strategy::Strategy;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// How capital is split between legs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AllocationMethod {
+    /// Each leg receives the same share of equity
+    EqualWeight,
+    /// Shares inversely proportional to each symbol's recent return volatility
+    RiskParity { volatility_lookback: usize },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioConfig {
+    pub allocation: AllocationMethod,
+    /// Maximum simultaneously open positions across all legs
+    pub max_concurrent_positions: Option<usize>,
+    /// Cap on gross open notional divided by portfolio equity, enforced when sizing entries
+    pub max_leverage: f64,
+    /// Shared settings: `initial_capital` is the pool, `position_fraction` and
+    /// `leverage` scale each leg's allocation
+    pub backtest: BacktestConfig,
+}
+
+impl Default for PortfolioConfig {
+    fn default() -> Self {
+        Self {
+            allocation: AllocationMethod::EqualWeight,
+            max_concurrent_positions: None,
+            max_leverage: 1.0,
+            backtest: BacktestConfig::default(),
+        }
+    }
+}
+
+/// A strategy and the candles (oldest first) of the symbol it trades
+#[derive(Debug, Clone, Copy)]
+pub struct PortfolioLeg<'a> {
+    pub strategy: &'a Strategy,
+    pub candles: &'a [Candle],
+}
+
+/// Per-leg outcome within the portfolio
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyContribution {
+    pub strategy_name: String,
+    pub symbol: String,
+    pub trades: Vec<BacktestTrade>,
+    /// Net PnL of the leg's trades
+    pub total_pnl: f64,
+    /// Net PnL relative to starting capital (percentage)
+    pub contribution_pct: f64,
+    /// Fraction of the portfolio's net PnL
+    pub share_of_pnl: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioResult {
+    pub equity_curve: Vec<EquityPoint>,
+    pub metrics: PerformanceMetrics,
+    /// Ordered like the input legs
+    pub contributions: Vec<StrategyContribution>,
+    /// Pearson correlation of per-bar leg PnL, indexed like `contributions`
+    pub correlation: Vec<Vec<f64>>,
+    /// Highest gross notional over equity reached
+    pub peak_leverage: f64,
+    /// Entry signals dropped by the position limit or leverage cap
+    pub skipped_entries: usize,
+}
+
+/// Simulation state of one leg
+struct LegState<'a> {
+    leg: PortfolioLeg<'a>,
+    signals: SignalSeries,
+    /// Index of the next candle to process
+    cursor: usize,
+    position: Option<OpenPosition>,
+    last_close: Option<f64>,
+    trades: Vec<BacktestTrade>,
+    /// Realized plus unrealized PnL at the previous timeline step
+    last_pnl: f64,
+    pnl_series: Vec<f64>,
+}
+
+impl LegState<'_> {
+    fn realized(&self) -> f64 {
+        self.trades.iter().map(|t| t.pnl).sum()
+    }
+
+    fn unrealized(&self) -> f64 {
+        match (&self.position, self.last_close) {
+            (Some(pos), Some(close)) => pos.unrealized_pnl(close),
+            _ => 0.0,
+        }
+    }
+
+    fn notional(&self) -> f64 {
+        match (&self.position, self.last_close) {
+            (Some(pos), Some(close)) => pos.size * close,
+            _ => 0.0,
+        }
+    }
+
+    /// Standard deviation of close-to-close returns over the last `lookback` bars
+    fn volatility(&self, lookback: usize) -> Option<f64> {
+        let end = self.cursor;
+        let start = end.saturating_sub(lookback + 1);
+        let closes = &self.leg.candles[start..end];
+        if closes.len() < 3 {
+            return None;
+        }
+        let returns: Vec<f64> = closes
+            .windows(2)
+            .filter(|w| w[0].close > 0.0)
+            .map(|w| w[1].close / w[0].close - 1.0)
+            .collect();
+        let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+        let variance =
+            returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / returns.len() as f64;
+        let volatility = variance.sqrt();
+        (volatility > f64::EPSILON).then_some(volatility)
+    }
+}
+
+pub struct PortfolioBacktester {
+    config: PortfolioConfig,
+}
+
+impl PortfolioBacktester {
+    pub fn new(config: PortfolioConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn config(&self) -> &PortfolioConfig {
+        &self.config
+    }
+
+    /// Simulate all legs against a shared capital pool
+    pub fn run(&self, legs: &[PortfolioLeg<'_>]) -> Result<PortfolioResult, BacktestError> {
+        let config = &self.config;
+        config.backtest.validate()?;
+        if legs.is_empty() {
+            return Err(BacktestError::InvalidConfig(
+                "portfolio has no legs".to_string(),
+            ));
+        }
+        if !config.max_leverage.is_finite() || config.max_leverage <= 0.0 {
+            return Err(BacktestError::InvalidConfig(
+                "max_leverage must be positive".to_string(),
+            ));
+        }
+        if config.max_concurrent_positions == Some(0) {
+            return Err(BacktestError::InvalidConfig(
+                "max_concurrent_positions must be positive".to_string(),
+            ));
+        }
+
+        let mut states = legs
+            .iter()
+            .map(|leg| {
+                Ok(LegState {
+                    leg: *leg,
+                    signals: Backtester::generate_signals(
+                        leg.strategy,
+                        leg.candles,
+                        config.backtest.lookback,
+                    )?,
+                    cursor: 0,
+                    position: None,
+                    last_close: None,
+                    trades: Vec::new(),
+                    last_pnl: 0.0,
+                    pnl_series: Vec::new(),
+                })
+            })
+            .collect::<Result<Vec<_>, BacktestError>>()?;
+
+        let backtester = Backtester::new(config.backtest.clone());
+        let mut timeline: Vec<DateTime<Utc>> = legs
+            .iter()
+            .flat_map(|leg| leg.candles.iter().map(|c| c.timestamp))
+            .collect();
+        timeline.sort_unstable();
+        timeline.dedup();
+
+        let mut cash = config.backtest.initial_capital;
+        let mut equity_curve = Vec::new();
+        let mut peak_leverage: f64 = 0.0;
+        let mut skipped_entries = 0;
+        let mut started = false;
+
+        for timestamp in timeline {
+            // Legs with a bar closing at this timestamp
+            let active: Vec<usize> = states
+                .iter()
+                .enumerate()
+                .filter(|(_, s)| {
+                    s.leg
+                        .candles
+                        .get(s.cursor)
+                        .is_some_and(|c| c.timestamp == timestamp)
+                })
+                .map(|(i, _)| i)
+                .collect();
+
+            // Funding, stops and take-profits before any new decisions
+            for &i in &active {
+                let state = &mut states[i];
+                let candle = &state.leg.candles[state.cursor];
+                state.last_close = Some(candle.close);
+                if let Some(ref mut pos) = state.position {
+                    if state.cursor > 0 {
+                        let previous = &state.leg.candles[state.cursor - 1];
+                        cash -= backtester.accrue_funding(pos, previous, candle);
+                    }
+                    if let Some((fill, reason)) = backtester.protective_exit(pos, candle) {
+                        let pos = state.position.take().unwrap();
+                        let trade = backtester.close(pos, fill, timestamp, reason, &mut cash);
+                        state.trades.push(trade);
+                    }
+                }
+            }
+
+            // Reversals free capital before entries are sized
+            let mut entries = Vec::new();
+            for &i in &active {
+                let state = &mut states[i];
+                if state.cursor < state.signals.start {
+                    continue;
+                }
+                started = true;
+                let Some(signal) = state.signals.signals[state.cursor] else {
+                    continue;
+                };
+                if signal.direction == SignalDirection::Neutral {
+                    continue;
+                }
+                let candle = &state.leg.candles[state.cursor];
+                if let Some(pos) = state.position.take_if(|p| p.direction != signal.direction) {
+                    let fill = backtester.market_exit_price(&pos, candle.close);
+                    let trade =
+                        backtester.close(pos, fill, timestamp, ExitReason::Reversal, &mut cash);
+                    state.trades.push(trade);
+                }
+                if state.position.is_none() {
+                    entries.push((i, signal));
+                }
+            }
+
+            let weights = self.weights(&states);
+            for (i, signal) in entries {
+                let equity = cash + states.iter().map(|s| s.unrealized()).sum::<f64>();
+                let open_positions = states.iter().filter(|s| s.position.is_some()).count();
+                if equity <= 0.0
+                    || config
+                        .max_concurrent_positions
+                        .is_some_and(|max| open_positions >= max)
+                {
+                    skipped_entries += 1;
+                    continue;
+                }
+
+                let gross: f64 = states.iter().map(|s| s.notional()).sum();
+                let capacity = (config.max_leverage * equity - gross).max(0.0);
+                let desired = equity
+                    * weights[i]
+                    * config.backtest.position_fraction
+                    * config.backtest.leverage;
+                let notional = desired.min(capacity);
+                if notional <= desired * 1e-6 {
+                    skipped_entries += 1;
+                    continue;
+                }
+
+                let state = &mut states[i];
+                let candle = &state.leg.candles[state.cursor];
+                state.position = Some(backtester.open(&signal, candle, notional, &mut cash));
+            }
+
+            for &i in &active {
+                states[i].cursor += 1;
+            }
+
+            if !started {
+                continue;
+            }
+
+            let unrealized: f64 = states.iter().map(|s| s.unrealized()).sum();
+            let equity = cash + unrealized;
+            if equity > 0.0 {
+                let gross: f64 = states.iter().map(|s| s.notional()).sum();
+                peak_leverage = peak_leverage.max(gross / equity);
+            }
+            equity_curve.push(EquityPoint { timestamp, equity });
+            for state in states.iter_mut() {
+                let pnl = state.realized() + state.unrealized();
+                state.pnl_series.push(pnl - state.last_pnl);
+                state.last_pnl = pnl;
+            }
+        }
+
+        // Flatten whatever is still open at each leg's last bar
+        for state in states.iter_mut() {
+            if let (Some(pos), Some(last)) = (state.position.take(), state.leg.candles.last()) {
+                let fill = backtester.market_exit_price(&pos, last.close);
+                let trade =
+                    backtester.close(pos, fill, last.timestamp, ExitReason::EndOfData, &mut cash);
+                state.trades.push(trade);
+            }
+        }
+        if let Some(point) = equity_curve.last_mut() {
+            point.equity = cash;
+        }
+
+        let initial = config.backtest.initial_capital;
+        let all_trades: Vec<BacktestTrade> =
+            states.iter().flat_map(|s| s.trades.iter().cloned()).collect();
+        let metrics = PerformanceMetrics::calculate(initial, &equity_curve, &all_trades);
+        let total_pnl: f64 = all_trades.iter().map(|t| t.pnl).sum();
+
+        let correlation = states
+            .iter()
+            .map(|a| {
+                states
+                    .iter()
+                    .map(|b| correlation(&a.pnl_series, &b.pnl_series))
+                    .collect()
+            })
+            .collect();
+
+        let contributions = states
+            .into_iter()
+            .map(|state| {
+                let leg_pnl = state.realized();
+                StrategyContribution {
+                    strategy_name: state.leg.strategy.name.clone(),
+                    symbol: state.leg.strategy.symbol.clone(),
+                    total_pnl: leg_pnl,
+                    contribution_pct: leg_pnl / initial * 100.0,
+                    share_of_pnl: if total_pnl.abs() > f64::EPSILON {
+                        leg_pnl / total_pnl
+                    } else {
+                        0.0
+                    },
+                    trades: state.trades,
+                }
+            })
+            .collect();
+
+        Ok(PortfolioResult {
+            equity_curve,
+            metrics,
+            contributions,
+            correlation,
+            peak_leverage,
+            skipped_entries,
+        })
+    }
+
+    /// Target share of equity for each leg, summing to one
+    fn weights(&self, states: &[LegState<'_>]) -> Vec<f64> {
+        let equal = vec![1.0 / states.len() as f64; states.len()];
+        match self.config.allocation {
+            AllocationMethod::EqualWeight => equal,
+            AllocationMethod::RiskParity {
+                volatility_lookback,
+            } => {
+                let volatilities: Option<Vec<f64>> = states
+                    .iter()
+                    .map(|s| s.volatility(volatility_lookback))
+                    .collect();
+                // Fall back to equal weights until every leg has enough history
+                let Some(volatilities) = volatilities else {
+                    return equal;
+                };
+                let inverse: Vec<f64> = volatilities.iter().map(|v| 1.0 / v).collect();
+                let total: f64 = inverse.iter().sum();
+                inverse.iter().map(|v| v / total).collect()
+            }
+        }
+    }
+}
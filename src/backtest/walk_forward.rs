@@ -0,0 +1,370 @@
+//! Walk-forward validation.
+//!
+//! History is split into consecutive in-sample/out-of-sample windows. Parameters
+//! are optimized on each in-sample window and then traded, unchanged, on the
+//! following unseen window. Out-of-sample runs are chained so each fold starts
+//! with the equity the previous one finished with.
+
+use crate::backtest::engine::{BacktestTrade, Backtester};
+use crate::backtest::error::BacktestError;
+use crate::backtest::metrics::{EquityPoint, PerformanceMetrics};
+use crate::backtest::optimizer::{OptimizerConfig, Optimizer, ParameterRange};
+use crate::models::indicators::Candle;
+use crate::models::strategy::Strategy;
+use crate::signals::engine::MIN_CANDLES;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalkForwardConfig {
+    /// Bars in each in-sample (optimization) window
+    pub in_sample_bars: usize,
+    /// Bars in each out-of-sample (validation) window; windows advance by this amount
+    pub out_of_sample_bars: usize,
+    /// Grow the in-sample window from the start of history instead of rolling it
+    pub anchored: bool,
+    /// Parameters whose fold-to-fold standard deviation exceeds this fraction of
+    /// their search range are flagged as unstable
+    pub stability_threshold: f64,
+    pub optimizer: OptimizerConfig,
+}
+
+impl Default for WalkForwardConfig {
+    fn default() -> Self {
+        Self {
+            in_sample_bars: 500,
+            out_of_sample_bars: 100,
+            anchored: false,
+            stability_threshold: 0.25,
+            optimizer: OptimizerConfig::default(),
+        }
+    }
+}
+
+/// One in-sample optimization followed by an out-of-sample run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalkForwardFold {
+    pub index: usize,
+    pub in_sample_start: DateTime<Utc>,
+    pub in_sample_end: DateTime<Utc>,
+    pub out_of_sample_start: DateTime<Utc>,
+    pub out_of_sample_end: DateTime<Utc>,
+    /// Best in-sample parameters; `None` when no candidate met the objective,
+    /// in which case the fold stays flat out of sample
+    pub parameters: Option<BTreeMap<String, f64>>,
+    pub in_sample: Option<PerformanceMetrics>,
+    pub out_of_sample: Option<PerformanceMetrics>,
+    pub trades: Vec<BacktestTrade>,
+}
+
+/// In-sample vs out-of-sample value of a metric, averaged over traded folds
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MetricComparison {
+    pub in_sample: f64,
+    pub out_of_sample: f64,
+    /// Relative drop from in-sample to out-of-sample (percentage; negative means improvement)
+    pub degradation_pct: f64,
+}
+
+impl MetricComparison {
+    fn new(in_sample: f64, out_of_sample: f64) -> Self {
+        let degradation_pct = if in_sample.abs() > f64::EPSILON {
+            (in_sample - out_of_sample) / in_sample.abs() * 100.0
+        } else {
+            0.0
+        };
+        Self {
+            in_sample,
+            out_of_sample,
+            degradation_pct,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Degradation {
+    pub sharpe_ratio: MetricComparison,
+    pub profit_factor: MetricComparison,
+    /// Mean return per bar (percentage), comparable across window lengths
+    pub return_per_bar_pct: MetricComparison,
+    /// Out-of-sample over in-sample return per bar
+    pub walk_forward_efficiency: f64,
+}
+
+/// Dispersion of an optimized parameter across folds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParameterStability {
+    pub parameter: String,
+    pub values: Vec<f64>,
+    pub mean: f64,
+    pub std_dev: f64,
+    /// Standard deviation divided by the width of the searched range
+    pub normalized_dispersion: f64,
+    pub unstable: bool,
+}
+
+impl ParameterStability {
+    /// Summarize `values` chosen across folds from a search range of width `span`
+    pub fn from_values(parameter: String, values: Vec<f64>, span: f64, threshold: f64) -> Self {
+        let n = values.len().max(1) as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let std_dev = (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n).sqrt();
+        let normalized_dispersion = if span > f64::EPSILON {
+            std_dev / span
+        } else {
+            0.0
+        };
+        Self {
+            parameter,
+            values,
+            mean,
+            std_dev,
+            normalized_dispersion,
+            unstable: normalized_dispersion > threshold,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalkForwardReport {
+    pub folds: Vec<WalkForwardFold>,
+    /// Out-of-sample equity curves stitched end to end
+    pub equity_curve: Vec<EquityPoint>,
+    /// Metrics of the stitched out-of-sample run
+    pub out_of_sample_metrics: PerformanceMetrics,
+    pub degradation: Degradation,
+    pub parameter_stability: Vec<ParameterStability>,
+    /// Labels of parameters flagged as unstable
+    pub unstable_parameters: Vec<String>,
+}
+
+pub struct WalkForward {
+    config: WalkForwardConfig,
+}
+
+impl WalkForward {
+    pub fn new(config: WalkForwardConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn config(&self) -> &WalkForwardConfig {
+        &self.config
+    }
+
+    /// Fold boundaries as `(in_sample_start, in_sample_end, out_of_sample_end)` bar indices
+    pub fn fold_bounds(&self, total_bars: usize) -> Vec<(usize, usize, usize)> {
+        let is_bars = self.config.in_sample_bars;
+        let oos_bars = self.config.out_of_sample_bars;
+        if oos_bars == 0 {
+            return Vec::new();
+        }
+
+        let mut bounds = Vec::new();
+        let mut is_end = is_bars;
+        while is_end + oos_bars <= total_bars {
+            let is_start = if self.config.anchored {
+                0
+            } else {
+                is_end - is_bars
+            };
+            bounds.push((is_start, is_end, is_end + oos_bars));
+            is_end += oos_bars;
+        }
+        bounds
+    }
+
+    /// Run walk-forward validation of `strategy` over `space`
+    pub fn run(
+        &self,
+        strategy: &Strategy,
+        space: &[ParameterRange],
+        candles: &[Candle],
+    ) -> Result<WalkForwardReport, BacktestError> {
+        let config = &self.config;
+        if config.in_sample_bars < MIN_CANDLES {
+            return Err(BacktestError::InvalidConfig(format!(
+                "in_sample_bars must be at least {}",
+                MIN_CANDLES
+            )));
+        }
+        if config.out_of_sample_bars == 0 {
+            return Err(BacktestError::InvalidConfig(
+                "out_of_sample_bars must be positive".to_string(),
+            ));
+        }
+        let required = config.in_sample_bars + config.out_of_sample_bars;
+        if candles.len() < required {
+            return Err(BacktestError::InsufficientData {
+                required,
+                available: candles.len(),
+            });
+        }
+
+        let backtest = &config.optimizer.backtest;
+        let warmup = backtest.lookback.saturating_sub(1);
+        let mut optimizer_config = config.optimizer.clone();
+        optimizer_config.top_n = 1;
+        let optimizer = Optimizer::new(optimizer_config);
+
+        let mut equity = backtest.initial_capital;
+        let mut equity_curve = Vec::new();
+        let mut all_trades = Vec::new();
+        let mut folds = Vec::new();
+
+        for (index, (is_start, is_end, oos_end)) in
+            self.fold_bounds(candles.len()).into_iter().enumerate()
+        {
+            let best = optimizer
+                .optimize(strategy, space, &candles[is_start..is_end])?
+                .into_iter()
+                .next();
+
+            let mut fold = WalkForwardFold {
+                index,
+                in_sample_start: candles[is_start].timestamp,
+                in_sample_end: candles[is_end - 1].timestamp,
+                out_of_sample_start: candles[is_end].timestamp,
+                out_of_sample_end: candles[oos_end - 1].timestamp,
+                parameters: None,
+                in_sample: None,
+                out_of_sample: None,
+                trades: Vec::new(),
+            };
+
+            let traded = match best {
+                Some(candidate) if equity > 0.0 => {
+                    let mut tuned = strategy.clone();
+                    tuned.config = candidate.config;
+
+                    // Evaluate with in-sample history as warmup, but only trade unseen bars
+                    let warm_start = is_end.saturating_sub(warmup);
+                    let window = &candles[warm_start..oos_end];
+                    let mut series =
+                        Backtester::generate_signals(&tuned, window, backtest.lookback)?;
+                    series.start = series.start.max(is_end - warm_start);
+
+                    let mut oos_config = backtest.clone();
+                    oos_config.initial_capital = equity;
+                    let result = Backtester::new(oos_config).simulate(window, &series);
+
+                    fold.parameters = Some(candidate.parameters);
+                    fold.in_sample = Some(candidate.metrics);
+                    Some(result)
+                }
+                Some(candidate) => {
+                    fold.parameters = Some(candidate.parameters);
+                    fold.in_sample = Some(candidate.metrics);
+                    None
+                }
+                None => None,
+            };
+
+            match traded {
+                Some(result) if !result.equity_curve.is_empty() => {
+                    equity = result.metrics.final_equity;
+                    equity_curve.extend(result.equity_curve);
+                    all_trades.extend(result.trades.iter().cloned());
+                    fold.trades = result.trades;
+                    fold.out_of_sample = Some(result.metrics);
+                }
+                _ => {
+                    // No tradable parameters (or no signals): stay flat for the window
+                    equity_curve.extend(candles[is_end..oos_end].iter().map(|c| EquityPoint {
+                        timestamp: c.timestamp,
+                        equity,
+                    }));
+                }
+            }
+
+            folds.push(fold);
+        }
+
+        let out_of_sample_metrics =
+            PerformanceMetrics::calculate(backtest.initial_capital, &equity_curve, &all_trades);
+        let degradation = self.degradation(&folds);
+        let parameter_stability = self.parameter_stability(&folds, space);
+        let unstable_parameters = parameter_stability
+            .iter()
+            .filter(|p| p.unstable)
+            .map(|p| p.parameter.clone())
+            .collect();
+
+        Ok(WalkForwardReport {
+            folds,
+            equity_curve,
+            out_of_sample_metrics,
+            degradation,
+            parameter_stability,
+            unstable_parameters,
+        })
+    }
+
+    fn degradation(&self, folds: &[WalkForwardFold]) -> Degradation {
+        let oos_bars = self.config.out_of_sample_bars as f64;
+        let mut sharpe = (0.0, 0.0);
+        let mut profit_factor = (0.0, 0.0);
+        let mut return_per_bar = (0.0, 0.0);
+        let mut count = 0.0;
+
+        for fold in folds {
+            let (Some(is), Some(oos)) = (&fold.in_sample, &fold.out_of_sample) else {
+                continue;
+            };
+            // Anchored in-sample windows grow by one out-of-sample window per fold
+            let is_bars = if self.config.anchored {
+                self.config.in_sample_bars as f64 + oos_bars * fold.index as f64
+            } else {
+                self.config.in_sample_bars as f64
+            };
+
+            sharpe.0 += is.sharpe_ratio;
+            sharpe.1 += oos.sharpe_ratio;
+            profit_factor.0 += is.profit_factor;
+            profit_factor.1 += oos.profit_factor;
+            return_per_bar.0 += is.total_return_pct / is_bars;
+            return_per_bar.1 += oos.total_return_pct / oos_bars;
+            count += 1.0;
+        }
+
+        if count == 0.0 {
+            return Degradation::default();
+        }
+
+        let is_per_bar = return_per_bar.0 / count;
+        let oos_per_bar = return_per_bar.1 / count;
+        Degradation {
+            sharpe_ratio: MetricComparison::new(sharpe.0 / count, sharpe.1 / count),
+            profit_factor: MetricComparison::new(profit_factor.0 / count, profit_factor.1 / count),
+            return_per_bar_pct: MetricComparison::new(is_per_bar, oos_per_bar),
+            walk_forward_efficiency: if is_per_bar.abs() > f64::EPSILON {
+                oos_per_bar / is_per_bar
+            } else {
+                0.0
+            },
+        }
+    }
+
+    fn parameter_stability(
+        &self,
+        folds: &[WalkForwardFold],
+        space: &[ParameterRange],
+    ) -> Vec<ParameterStability> {
+        space
+            .iter()
+            .map(|range| {
+                let label = range.target.label();
+                let values: Vec<f64> = folds
+                    .iter()
+                    .filter_map(|f| f.parameters.as_ref()?.get(&label).copied())
+                    .collect();
+                ParameterStability::from_values(
+                    label,
+                    values,
+                    range.max - range.min,
+                    self.config.stability_threshold,
+                )
+            })
+            .collect()
+    }
+}
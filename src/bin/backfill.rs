@@ -0,0 +1,151 @@
+//! Perptrix Backfill
+//!
+//! Pages historical candles (with aligned funding rates) from Hyperliquid into QuestDB.
+//! Re-running with the same range resumes after the candles already stored from its start.
+//!
+//! Usage:
+//!   backfill --symbols BTC,ETH --intervals 1m,1h --from 2024-01-01 [--to 2024-06-01]
+//!
+//! `--symbols` falls back to the SYMBOLS env var, `--intervals` defaults to 1m and
+//! `--to` defaults to now. Dates accept RFC 3339 or YYYY-MM-DD (midnight UTC).
+
+use chrono::{DateTime, Utc};
+use dotenvy::dotenv;
+use perptrix::db::QuestDatabase;
+use perptrix::logging;
+use perptrix::services::candle_io::parse_timestamp;
+use perptrix::services::hyperliquid::{BackfillConfig, CandleBackfiller, HyperliquidRestClient};
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::info;
+
+struct BackfillArgs {
+    symbols: Vec<String>,
+    intervals: Vec<String>,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    request_interval_ms: Option<u64>,
+    skip_funding: bool,
+}
+
+fn parse_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+fn parse_date(value: &str) -> Result<DateTime<Utc>, String> {
+    parse_timestamp(value)
+        .ok_or_else(|| format!("Invalid date '{}': expected RFC 3339 or YYYY-MM-DD", value))
+}
+
+fn parse_args() -> Result<BackfillArgs, String> {
+    let mut symbols = env::var("SYMBOLS").ok().map(|s| parse_list(&s));
+    let mut intervals = vec!["1m".to_string()];
+    let mut from = None;
+    let mut to = None;
+    let mut request_interval_ms = None;
+    let mut skip_funding = false;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("Missing value for {}", arg))
+        };
+        match arg.as_str() {
+            "--symbols" => symbols = Some(parse_list(&value()?)),
+            "--intervals" => intervals = parse_list(&value()?),
+            "--from" => from = Some(parse_date(&value()?)?),
+            "--to" => to = Some(parse_date(&value()?)?),
+            "--request-interval-ms" => {
+                let raw = value()?;
+                request_interval_ms = Some(
+                    raw.parse()
+                        .map_err(|_| format!("Invalid --request-interval-ms '{}'", raw))?,
+                );
+            }
+            "--no-funding" => skip_funding = true,
+            other => return Err(format!("Unknown argument: {}", other)),
+        }
+    }
+
+    let symbols = symbols
+        .filter(|s| !s.is_empty())
+        .ok_or("No symbols given: pass --symbols or set SYMBOLS")?;
+    if intervals.is_empty() {
+        return Err("No intervals given".to_string());
+    }
+
+    Ok(BackfillArgs {
+        symbols,
+        intervals,
+        from: from.ok_or("--from is required")?,
+        to: to.unwrap_or_else(Utc::now),
+        request_interval_ms,
+        skip_funding,
+    })
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Load environment variables from .env if present
+    dotenv().ok();
+
+    // Initialize logging based on environment
+    logging::init_logging();
+
+    let args = parse_args()?;
+    info!(
+        symbols = ?args.symbols,
+        intervals = ?args.intervals,
+        from = %args.from,
+        to = %args.to,
+        "Starting Perptrix Backfill"
+    );
+
+    info!("Initializing QuestDB connection...");
+    let database = QuestDatabase::new()
+        .await
+        .map_err(|e| format!("QuestDB connection required for backfill: {}", e))?;
+    info!("QuestDB connected");
+
+    let mut config = BackfillConfig {
+        include_funding: !args.skip_funding,
+        ..BackfillConfig::default()
+    };
+    if let Some(ms) = args.request_interval_ms {
+        config.request_interval = Duration::from_millis(ms);
+    }
+
+    let backfiller = CandleBackfiller::new(
+        Arc::new(HyperliquidRestClient::new()),
+        Arc::new(database),
+    )
+    .with_config(config);
+
+    let reports = backfiller
+        .backfill_all(&args.symbols, &args.intervals, args.from, args.to)
+        .await
+        .map_err(|e| format!("Backfill failed: {}", e))?;
+
+    for report in &reports {
+        info!(
+            symbol = %report.symbol,
+            interval = %report.interval,
+            pages = report.pages,
+            stored = report.candles_stored,
+            funding_points = report.funding_points,
+            "{}/{}: stored {} candles ({} pages)",
+            report.symbol,
+            report.interval,
+            report.candles_stored,
+            report.pages
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,154 @@
+//! Perptrix Candles
+//!
+//! Moves candle series between QuestDB and CSV/Parquet files, e.g. for offline
+//! research or to seed a local environment from fixture files.
+//!
+//! Usage:
+//!   candles export --symbol BTC --interval 1h --output btc_1h.parquet [--from 2024-01-01] [--to 2024-06-01]
+//!   candles import --symbol BTC --interval 1h --input tests/fixtures/candles/btc_1h.csv
+//!
+//! The format is inferred from the file extension unless `--format csv|parquet` is given.
+//! Dates accept RFC 3339, YYYY-MM-DD (midnight UTC) or epoch milliseconds.
+
+use chrono::{DateTime, Utc};
+use dotenvy::dotenv;
+use perptrix::db::QuestDatabase;
+use perptrix::logging;
+use perptrix::services::candle_io::{self, parse_timestamp, CandleFormat};
+use std::env;
+use std::path::PathBuf;
+use tracing::info;
+
+enum Command {
+    Export,
+    Import,
+}
+
+struct CandlesArgs {
+    command: Command,
+    symbol: String,
+    interval: String,
+    path: PathBuf,
+    format: CandleFormat,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+}
+
+fn parse_date(value: &str) -> Result<DateTime<Utc>, String> {
+    parse_timestamp(value).ok_or_else(|| format!("Invalid date '{}'", value))
+}
+
+fn parse_args() -> Result<CandlesArgs, String> {
+    let mut args = env::args().skip(1);
+    let command = match args.next().as_deref() {
+        Some("export") => Command::Export,
+        Some("import") => Command::Import,
+        _ => return Err("Usage: candles <export|import> --symbol <SYMBOL> --interval <INTERVAL> (--output|--input) <PATH>".to_string()),
+    };
+
+    let mut symbol = None;
+    let mut interval = None;
+    let mut path = None;
+    let mut format = None;
+    let mut from = None;
+    let mut to = None;
+
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("Missing value for {}", arg))
+        };
+        match arg.as_str() {
+            "--symbol" => symbol = Some(value()?),
+            "--interval" => interval = Some(value()?),
+            "--output" | "--input" => path = Some(PathBuf::from(value()?)),
+            "--format" => format = Some(value()?.parse::<CandleFormat>().map_err(|e| e.to_string())?),
+            "--from" => from = Some(parse_date(&value()?)?),
+            "--to" => to = Some(parse_date(&value()?)?),
+            other => return Err(format!("Unknown argument: {}", other)),
+        }
+    }
+
+    let path = path.ok_or("--output/--input is required")?;
+    let format = format
+        .or_else(|| CandleFormat::from_path(&path))
+        .ok_or("Cannot infer format from file extension: pass --format csv|parquet")?;
+
+    Ok(CandlesArgs {
+        command,
+        symbol: symbol.ok_or("--symbol is required")?,
+        interval: interval.ok_or("--interval is required")?,
+        path,
+        format,
+        from,
+        to,
+    })
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Load environment variables from .env if present
+    dotenv().ok();
+
+    // Initialize logging based on environment
+    logging::init_logging();
+
+    let args = parse_args()?;
+
+    info!("Initializing QuestDB connection...");
+    let database = QuestDatabase::new()
+        .await
+        .map_err(|e| format!("QuestDB connection required: {}", e))?;
+    info!("QuestDB connected");
+
+    match args.command {
+        Command::Export => {
+            let data = candle_io::export_candles(
+                &database,
+                &args.symbol,
+                &args.interval,
+                args.from,
+                args.to,
+                args.format,
+            )
+            .await?;
+            std::fs::write(&args.path, &data)?;
+            info!(
+                symbol = %args.symbol,
+                interval = %args.interval,
+                path = %args.path.display(),
+                bytes = data.len(),
+                "Exported {}/{} candles to {}",
+                args.symbol,
+                args.interval,
+                args.path.display()
+            );
+        }
+        Command::Import => {
+            let data = std::fs::read(&args.path)?;
+            let report = candle_io::import_candles(
+                &database,
+                &args.symbol,
+                &args.interval,
+                &data,
+                args.format,
+            )
+            .await?;
+            info!(
+                symbol = %report.symbol,
+                interval = %report.interval,
+                rows = report.rows,
+                imported = report.imported,
+                skipped_existing = report.skipped_existing,
+                "Imported {} of {} candles for {}/{} from {}",
+                report.imported,
+                report.rows,
+                report.symbol,
+                report.interval,
+                args.path.display()
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,133 @@
+//! Perptrix Paper Trader
+//!
+//! Trades new signals on simulated per-strategy accounts using the latest prices
+//! written by the WebSocket service, and persists accounts, fills and equity
+//! snapshots to QuestDB. Signals are consumed from the `bus:signals` stream as the
+//! `paper-trader` consumer group, or polled from QuestDB when the bus is
+//! unavailable. Run as a singleton alongside the workers.
+//!
+//! Environment:
+//!   PAPER_INITIAL_CAPITAL, PAPER_POSITION_FRACTION, PAPER_LEVERAGE, PAPER_FEE_BPS,
+//!   PAPER_SLIPPAGE_BPS, PAPER_POLL_INTERVAL_SECONDS, PAPER_SNAPSHOT_INTERVAL_SECONDS
+
+use dotenvy::dotenv;
+use perptrix::cache::RedisCache;
+use perptrix::db::QuestDatabase;
+use perptrix::logging;
+use perptrix::paper::{PaperConfig, PaperTradingService};
+use perptrix::services::hyperliquid::HyperliquidMarketDataProvider;
+use perptrix::services::market_bus::{BusConsumer, BusTopic, RedisMarketBus};
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::signal;
+use tracing::{error, info, warn};
+
+fn env_f64(name: &str, default: f64) -> Result<f64, String> {
+    match env::var(name) {
+        Ok(raw) => raw
+            .parse()
+            .map_err(|_| format!("Invalid {} '{}'", name, raw)),
+        Err(_) => Ok(default),
+    }
+}
+
+fn env_secs(name: &str, default: u64) -> Result<Duration, String> {
+    match env::var(name) {
+        Ok(raw) => raw
+            .parse()
+            .map(Duration::from_secs)
+            .map_err(|_| format!("Invalid {} '{}'", name, raw)),
+        Err(_) => Ok(Duration::from_secs(default)),
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Load environment variables from .env if present
+    dotenv().ok();
+
+    // Initialize logging based on environment
+    logging::init_logging();
+
+    let defaults = PaperConfig::default();
+    let config = PaperConfig {
+        initial_capital: env_f64("PAPER_INITIAL_CAPITAL", defaults.initial_capital)?,
+        position_fraction: env_f64("PAPER_POSITION_FRACTION", defaults.position_fraction)?,
+        leverage: env_f64("PAPER_LEVERAGE", defaults.leverage)?,
+        fee_bps: env_f64("PAPER_FEE_BPS", defaults.fee_bps)?,
+        slippage_bps: env_f64("PAPER_SLIPPAGE_BPS", defaults.slippage_bps)?,
+        apply_funding: defaults.apply_funding,
+    };
+    let poll_interval = env_secs("PAPER_POLL_INTERVAL_SECONDS", 10)?;
+    let snapshot_interval = env_secs("PAPER_SNAPSHOT_INTERVAL_SECONDS", 60)?;
+
+    let env = perptrix::config::get_environment();
+    info!("Starting Perptrix Paper Trader");
+    info!(environment = %env, "Environment");
+    info!(
+        initial_capital = config.initial_capital,
+        leverage = config.leverage,
+        fee_bps = config.fee_bps,
+        slippage_bps = config.slippage_bps,
+        "Paper account settings"
+    );
+
+    info!("Initializing QuestDB connection...");
+    let database = Arc::new(
+        QuestDatabase::new()
+            .await
+            .map_err(|e| format!("QuestDB connection required for paper trading: {}", e))?,
+    );
+    info!("QuestDB connected");
+
+    // Prices come from the candles the WebSocket service writes to Redis/QuestDB
+    info!("Initializing Redis connection...");
+    let mut provider = HyperliquidMarketDataProvider::new().with_database(database.clone());
+    match RedisCache::new().await {
+        Ok(cache) => {
+            info!("Redis connected");
+            provider = provider.with_cache(Arc::new(cache));
+        }
+        Err(e) => {
+            warn!(error = %e, "Failed to connect to Redis - reading prices from QuestDB only");
+        }
+    }
+
+    let mut service = PaperTradingService::new(config, database, Arc::new(provider))?
+        .with_poll_interval(poll_interval)
+        .with_snapshot_interval(snapshot_interval);
+    match RedisMarketBus::new().await {
+        Ok(bus) => {
+            info!("Consuming signals from the market data bus");
+            let consumer = BusConsumer::new(
+                Arc::new(bus),
+                BusTopic::Signals,
+                "paper-trader",
+                &format!("paper-trader-{}", std::process::id()),
+            )
+            .with_batch_size(500);
+            service = service.with_signal_bus(consumer);
+        }
+        Err(e) => {
+            warn!(error = %e, "Failed to connect the market data bus - polling signals from QuestDB");
+        }
+    }
+    service
+        .restore()
+        .await
+        .map_err(|e| format!("Failed to restore paper accounts: {}", e))?;
+
+    info!("Paper trader started, waiting for shutdown signal...");
+    tokio::select! {
+        _ = service.run() => {
+            error!("Paper trading loop stopped");
+        }
+        _ = signal::ctrl_c() => {
+            info!("Shutting down paper trader...");
+            info!("Paper trader stopped");
+        }
+    }
+
+    Ok(())
+}
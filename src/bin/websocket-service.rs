@@ -3,19 +3,55 @@
 //! Maintains long-lived WebSocket connection to market data provider.
 //! Receives real-time updates and stores them in Redis/QuestDB.
 //! This service should run as a singleton (one instance).
+//!
+//! Set `WS_RECORD_PATH` to append every raw WebSocket event to a JSON Lines file.
+//! Set `WS_REPLAY_PATH` to feed such a recording through the provider instead of
+//! connecting to the exchange; `WS_REPLAY_SPEED` scales playback (default 1.0,
+//! 0 = as fast as possible).
+//!
+//! Symbols prefixed with `binance:` (e.g. `binance:BTCUSDT`) are streamed from
+//! Binance USDⓈ-M futures; bare symbols are Hyperliquid coins.
+//!
+//! Every closed candle is announced on the Redis `events:candle_closed` channel,
+//! which the worker follows to evaluate strategies on bar close.
+//!
+//! Candle updates and closes, mid prices and funding updates are published to
+//! the market data bus (Redis Streams `bus:*`) for other services to consume.
+//!
+//! Besides `SYMBOLS`, which are always streamed, the service subscribes to the
+//! symbols of the strategy table and unsubscribes those no strategy uses
+//! anymore. The table is re-read every `STRATEGY_RELOAD_SECONDS` (default 60,
+//! 0 = only on request) and whenever a command arrives on the Redis
+//! `events:subscriptions` channel; the active set is listed by the admin API.
+//!
+//! Stored candle history is audited for gaps whenever the Hyperliquid feed
+//! (re)connects and every `GAP_AUDIT_INTERVAL_SECONDS` (default 300, 0 audits
+//! on reconnect only), checking the last `GAP_AUDIT_LOOKBACK_BARS` bars
+//! (default 500).
+//! Missing bars are backfilled from the venue's REST API.
 
 use dotenvy::dotenv;
 use perptrix::cache::RedisCache;
 use perptrix::db::QuestDatabase;
 use perptrix::logging;
 use perptrix::metrics::Metrics;
-use perptrix::services::hyperliquid::HyperliquidMarketDataProvider;
+use perptrix::services::binance::{BinanceMarketDataProvider, BINANCE_VENUE};
+use perptrix::services::candle_events::forward_to_redis;
+use perptrix::services::candle_gaps::{CandleGapAuditor, DEFAULT_LOOKBACK_BARS};
+use perptrix::services::hyperliquid::{
+    CandleBackfiller, HyperliquidClient, HyperliquidMarketDataProvider, HyperliquidRestClient,
+    RecordingWebSocketClient, ReplayWebSocketClient, WebSocketClient,
+};
+use perptrix::services::market_bus::{forward_to_bus, MarketBus, RedisMarketBus};
+use perptrix::services::market_data::{MarketDataProvider, DEFAULT_VENUE};
+use perptrix::services::subscription_sync::SubscriptionSync;
 use perptrix::services::websocket::WebSocketService;
+use std::collections::HashMap;
 use std::env;
 use std::sync::Arc;
 use tokio::signal;
 use tokio::time::Duration;
-use tracing::{error, info, warn};
+use tracing::{info, warn};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -25,14 +61,27 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize logging based on environment
     logging::init_logging();
 
-    let symbols: Option<Vec<String>> = env::var("SYMBOLS").ok().and_then(|s| {
-        let v: Vec<String> = s.split(',').map(|s| s.trim().to_string()).collect();
-        if v.is_empty() {
-            None
-        } else {
-            Some(v)
-        }
-    });
+    let symbols: Vec<String> = env::var("SYMBOLS")
+        .map(|s| {
+            s.split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+    let strategy_reload: u64 = env::var("STRATEGY_RELOAD_SECONDS")
+        .ok()
+        .and_then(|i| i.parse().ok())
+        .unwrap_or(60);
+
+    let gap_audit_interval: u64 = env::var("GAP_AUDIT_INTERVAL_SECONDS")
+        .ok()
+        .and_then(|i| i.parse().ok())
+        .unwrap_or(300);
+    let gap_audit_lookback: usize = env::var("GAP_AUDIT_LOOKBACK_BARS")
+        .ok()
+        .and_then(|i| i.parse().ok())
+        .unwrap_or(DEFAULT_LOOKBACK_BARS);
 
     let env = perptrix::config::get_environment();
     info!("Starting Perptrix WebSocket Service");
@@ -74,17 +123,101 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     };
 
+    // Market data bus, on its own connection so publishing does not queue behind cache writes
+    let bus: Option<Arc<dyn MarketBus>> = match cache {
+        Some(_) => match RedisMarketBus::new().await {
+            Ok(bus) => Some(Arc::new(bus)),
+            Err(e) => {
+                warn!(error = %e, "Failed to connect the market data bus - events will not be published");
+                None
+            }
+        },
+        None => None,
+    };
+
     // Initialize WebSocket Service (long-lived, maintains connection)
     info!("Initializing WebSocket service...");
-    let mut ws_provider = HyperliquidMarketDataProvider::new();
+    let mut ws_client: Arc<dyn WebSocketClient> = match env::var("WS_REPLAY_PATH") {
+        Ok(path) => {
+            let speed = env::var("WS_REPLAY_SPEED")
+                .ok()
+                .and_then(|s| s.parse::<f64>().ok())
+                .unwrap_or(1.0);
+            let replay = ReplayWebSocketClient::from_file(&path)
+                .await
+                .map_err(|e| format!("Failed to load WebSocket recording: {}", e))?
+                .with_speed(speed);
+            info!(path = %path, events = replay.event_count(), speed = speed, "Replaying WebSocket recording from {}", path);
+            Arc::new(replay)
+        }
+        Err(_) => Arc::new(HyperliquidClient::new()),
+    };
+    if let Ok(path) = env::var("WS_RECORD_PATH") {
+        ws_client = Arc::new(
+            RecordingWebSocketClient::to_file(ws_client, path)
+                .await
+                .map_err(|e| format!("Failed to open WebSocket recording: {}", e))?,
+        );
+    }
+
+    let mut ws_provider = HyperliquidMarketDataProvider::with_websocket_client(ws_client);
     if let Some(ref db) = database {
         ws_provider = ws_provider.with_database(db.clone());
     }
     if let Some(ref c) = cache {
         ws_provider = ws_provider.with_cache(c.clone());
+        tokio::spawn(forward_to_redis(ws_provider.subscribe_market_events(), c.clone()));
+    }
+    if let Some(ref bus) = bus {
+        tokio::spawn(forward_to_bus(
+            ws_provider.subscribe_market_events(),
+            bus.clone(),
+            Some(metrics.clone()),
+        ));
     }
 
-    let ws_service = WebSocketService::new(ws_provider);
+    // Gap repair writes to QuestDB, so it needs the database
+    let connections = ws_provider.subscribe_connections();
+    let mut intervals = HashMap::new();
+    intervals.insert(DEFAULT_VENUE.to_string(), ws_provider.intervals().to_vec());
+    let mut auditor = database.as_ref().map(|db| {
+        let backfiller = CandleBackfiller::new(Arc::new(HyperliquidRestClient::new()), db.clone());
+        let mut auditor = CandleGapAuditor::new(db.clone())
+            .with_filler(DEFAULT_VENUE, Arc::new(backfiller))
+            .with_metrics(metrics.clone())
+            .with_lookback(gap_audit_lookback);
+        if let Some(ref c) = cache {
+            auditor = auditor
+                .with_cache(c.clone())
+                .with_empty_ranges(c.clone());
+        }
+        auditor
+    });
+
+    // Binance only connects on its first subscription, so it is always
+    // registered for symbols added at runtime
+    let mut ws_service = WebSocketService::new(ws_provider);
+    {
+        let mut binance = BinanceMarketDataProvider::new();
+        if let Some(ref db) = database {
+            binance = binance.with_database(db.clone());
+        }
+        if let Some(ref c) = cache {
+            binance = binance.with_cache(c.clone());
+            tokio::spawn(forward_to_redis(binance.subscribe_market_events(), c.clone()));
+        }
+        if let Some(ref bus) = bus {
+            tokio::spawn(forward_to_bus(
+                binance.subscribe_market_events(),
+                bus.clone(),
+                Some(metrics.clone()),
+            ));
+        }
+        let binance = Arc::new(binance);
+        intervals.insert(BINANCE_VENUE.to_string(), binance.intervals().to_vec());
+        auditor = auditor.map(|a| a.with_filler(BINANCE_VENUE, binance.clone()));
+        ws_service = ws_service.with_venue(BINANCE_VENUE, binance);
+    }
     ws_service.start().await.map_err(|e| format!("Failed to start WebSocket service: {}", e))?;
 
     // Wait for connection to establish (with timeout)
@@ -98,22 +231,48 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         metrics.websocket_connected.set(0.0);
     }
 
-    // Subscribe to symbols if provided (will queue if not connected yet)
-    if let Some(symbols) = symbols {
-        info!(symbols = ?symbols, "Subscribing to symbols: {}", symbols.join(", "));
-        for symbol in &symbols {
-            match ws_service.subscribe(symbol).await {
-                Ok(()) => {
-                    info!(symbol = %symbol, "Subscribed to {} (or queued if not connected)", symbol);
-                }
-                Err(e) => {
-                    error!(symbol = %symbol, error = %e, "Failed to subscribe to {}", symbol);
-                }
-            }
+    let auditor = auditor.map(Arc::new);
+
+    // Subscribe to the configured and strategy symbols (queued if not connected yet)
+    let registry: Arc<dyn MarketDataProvider> = Arc::new(ws_service.registry().clone());
+    let mut sync = SubscriptionSync::new(registry)
+        .with_static_symbols(&symbols)
+        .with_metrics(metrics.clone());
+    if let Some(ref db) = database {
+        sync = sync.with_source(db.clone());
+    } else {
+        warn!("No database - only SYMBOLS and manual subscriptions will be streamed");
+    }
+    if let Some(ref c) = cache {
+        sync = sync.with_store(c.clone());
+    }
+    if let Some(ref auditor) = auditor {
+        sync = sync.with_auditor(auditor.clone(), intervals);
+    }
+    let sync = Arc::new(sync);
+    match sync.reload().await {
+        Ok(report) => info!(
+            symbols = ?report.subscribed,
+            "Subscribed to {} symbols (or queued if not connected)",
+            report.subscribed.len()
+        ),
+        Err(e) => warn!(error = %e, "Failed to load strategy symbols - streaming SYMBOLS only"),
+    }
+    if let Some(ref c) = cache {
+        sync.follow(c.clone());
+    }
+    if strategy_reload > 0 {
+        sync.start(Duration::from_secs(strategy_reload));
+    }
+
+    if let Some(ref auditor) = auditor {
+        auditor.audit_on_connect(connections);
+        if gap_audit_interval > 0 {
+            auditor.start(Duration::from_secs(gap_audit_interval));
         }
+        info!(watched = auditor.watched().len(), "Candle gap audits enabled");
     } else {
-        info!("No symbols specified - WebSocket service running but not subscribed to any symbols");
-        info!("Note: Use SYMBOLS environment variable to subscribe to symbols");
+        warn!("No database - candle gaps will not be audited");
     }
 
     // Graceful shutdown
@@ -123,6 +282,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         _ = signal::ctrl_c() => {
             info!("Shutting down WebSocket service...");
             ws_service.stop().await;
+            sync.stop();
+            if let Some(ref auditor) = auditor {
+                auditor.stop();
+            }
             info!("WebSocket service stopped");
         }
     }
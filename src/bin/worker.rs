@@ -2,22 +2,48 @@
 //!
 //! Processes signal evaluation jobs from the Redis queue.
 //! Can be run as a separate process/instance from the web server.
+//!
+//! Strategies are evaluated as soon as the websocket-service announces a
+//! closed bar of their symbol. Set `EVAL_INTERVAL_SECONDS` to also evaluate on
+//! a fixed schedule, as a fallback for missed closes.
+//!
+//! Stored signals are published to the `bus:signals` stream of the market data bus.
+//! Symbols whose recent candles have gaps are not evaluated until the
+//! websocket-service repairs them.
+//!
+//! The strategy symbols are re-read every `STRATEGY_RELOAD_SECONDS` (default
+//! 60, 0 = only on request) and whenever the API announces a strategy change,
+//! so new strategies are evaluated without a restart.
 
 use dotenvy::dotenv;
 use perptrix::cache::RedisCache;
 use perptrix::core::runtime::{RuntimeConfig, SignalRuntime};
-use perptrix::core::scheduler::JobScheduler;
+use perptrix::core::scheduler::{CandleCloseTrigger, JobScheduler};
 use perptrix::db::QuestDatabase;
+use perptrix::execution::{
+    AlgoEngine, ExchangeExecutor, KillSwitch, MemoryKillSwitchStore, OrderManager, PolicyEngine,
+    PositionTracker, RiskGuardedExecutor, RiskLimits, RiskManager, ShadowExecutor,
+};
 use perptrix::jobs::context::JobContext;
-use perptrix::jobs::types::{EvaluateSignalJob, FetchCandlesJob, StoreSignalJob};
+use perptrix::jobs::types::{
+    BackfillCandlesJob, EvaluateSignalJob, ExecuteSignalJob, FetchCandlesJob, StoreSignalJob,
+};
 use perptrix::logging;
 use perptrix::metrics::Metrics;
-use perptrix::services::hyperliquid::HyperliquidMarketDataProvider;
-use perptrix::services::market_data::MarketDataProvider;
+use perptrix::services::hyperliquid::{
+    CandleBackfiller, HyperliquidExecutor, HyperliquidMarketDataProvider, HyperliquidRestClient,
+    HyperliquidUserEvents,
+};
+use perptrix::services::binance::{BinanceMarketDataProvider, BINANCE_VENUE};
+use perptrix::services::market_bus::RedisMarketBus;
+use perptrix::services::market_data::{MarketDataProvider, MarketDataRegistry};
+use perptrix::services::subscription_sync::{follow_commands, SubscriptionCommand, SymbolSource};
 use apalis_redis::RedisStorage;
 use std::env;
 use std::sync::Arc;
 use tokio::signal;
+use tokio::sync::Notify;
+use tokio::time::Duration;
 use tracing::{info, warn};
 
 #[tokio::main]
@@ -32,15 +58,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .ok()
         .and_then(|i| i.parse().ok())
         .unwrap_or(0);
+    let strategy_reload: u64 = env::var("STRATEGY_RELOAD_SECONDS")
+        .ok()
+        .and_then(|i| i.parse().ok())
+        .unwrap_or(60);
 
     let env = perptrix::config::get_environment();
     info!("Starting Perptrix Worker");
     info!(environment = %env, "Environment");
 
-    if eval_interval == 0 {
-        return Err("EVAL_INTERVAL_SECONDS must be > 0 for worker".into());
-    }
-
     // Initialize metrics
     let metrics = Arc::new(Metrics::new()?);
 
@@ -85,10 +111,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .unwrap_or_else(|| symbols.len().max(1));
     
     info!(concurrency = concurrency, "Worker concurrency: {}", concurrency);
-    info!(
-        interval = eval_interval,
-        "Signal Evaluation: every {} seconds", eval_interval
-    );
+    if eval_interval > 0 {
+        info!(
+            interval = eval_interval,
+            "Signal Evaluation: on candle close, and every {} seconds", eval_interval
+        );
+    } else {
+        info!("Signal Evaluation: on candle close");
+    }
     if symbols.is_empty() {
         warn!("No symbols to evaluate - no strategies configured");
     } else {
@@ -106,7 +136,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Ok(c) => {
             info!("Redis connected");
             metrics.cache_connected.set(1.0);
-            Some(Arc::new(c))
+            Arc::new(c)
         }
         Err(e) => {
             warn!(error = %e, "Failed to connect to Redis");
@@ -122,11 +152,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     if let Some(ref db) = database {
         read_only_provider = read_only_provider.with_database(db.clone());
     }
-    if let Some(ref c) = cache {
-        read_only_provider = read_only_provider.with_cache(c.clone());
+    read_only_provider = read_only_provider.with_cache(cache.clone());
+    let eval_candle_interval = read_only_provider.primary_interval().to_string();
+    // Binance only connects once a symbol is requested, so it is always
+    // registered for strategies added later
+    let mut binance = BinanceMarketDataProvider::new();
+    if let Some(ref db) = database {
+        binance = binance.with_database(db.clone());
     }
-    let read_only_provider: Arc<dyn MarketDataProvider + Send + Sync> =
-        Arc::new(read_only_provider);
+    binance = binance.with_cache(cache.clone());
+    let registry = MarketDataRegistry::new(Arc::new(read_only_provider))
+        .with_provider(BINANCE_VENUE, Arc::new(binance));
+    let read_only_provider: Arc<dyn MarketDataProvider + Send + Sync> = Arc::new(registry);
 
     // Initialize Apalis storage backends
     info!("Initializing Apalis Redis storage...");
@@ -137,18 +174,145 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let eval_storage: Arc<RedisStorage<EvaluateSignalJob>> =
         Arc::new(RedisStorage::new(conn.clone()));
     let store_storage: Arc<RedisStorage<StoreSignalJob>> =
+        Arc::new(RedisStorage::new(conn.clone()));
+    let backfill_storage: Arc<RedisStorage<BackfillCandlesJob>> =
+        Arc::new(RedisStorage::new(conn.clone()));
+    let execute_storage: Arc<RedisStorage<ExecuteSignalJob>> =
         Arc::new(RedisStorage::new(conn));
     info!("Apalis Redis storage initialized");
 
-    // Create job context
-    let job_context = Arc::new(JobContext::new(
-        read_only_provider,
-        database.clone(),
-        Some(metrics.clone()),
+    // Create job context (backfill jobs write directly to QuestDB)
+    let backfiller = Arc::new(CandleBackfiller::new(
+        Arc::new(HyperliquidRestClient::new()),
+        db.clone(),
     ));
+    // Empty ranges are written by the websocket-service's gap audit
+    let mut job_context =
+        JobContext::new(read_only_provider, database.clone(), Some(metrics.clone()))
+            .with_backfiller(backfiller)
+            .with_candle_interval(&eval_candle_interval)
+            .with_empty_ranges(cache.clone());
+    match RedisMarketBus::new().await {
+        Ok(bus) => job_context = job_context.with_bus(Arc::new(bus)),
+        Err(e) => warn!(error = %e, "Failed to connect the market data bus - signals will not be published"),
+    }
+
+    // Signal execution places real orders; run it on a single worker so the
+    // order manager and policy engine see every order. Shadow mode runs the
+    // same path against the real account but records orders instead of sending them.
+    let execution_mode = env::var("EXECUTION_MODE").unwrap_or_else(|_| "off".to_string());
+    let mut execution_handles = Vec::new();
+    match execution_mode.as_str() {
+        "off" => info!("Signal execution disabled"),
+        "live" | "shadow" => {
+            let shadow = execution_mode == "shadow";
+            let exchange = Arc::new(
+                HyperliquidExecutor::from_env()
+                    .map_err(|e| format!("Failed to create executor: {}", e))?,
+            );
+            let (target, kill_switch): (Arc<dyn ExchangeExecutor>, _) = if shadow {
+                info!("Shadow execution enabled, recording orders without sending them to Hyperliquid");
+                // The shared kill switch (and its flatten requests) belongs to live trading
+                (
+                    Arc::new(ShadowExecutor::new(exchange.clone())),
+                    KillSwitch::new(Arc::new(MemoryKillSwitchStore::default())),
+                )
+            } else {
+                info!("Signal execution enabled, placing orders on Hyperliquid");
+                (
+                    exchange.clone(),
+                    KillSwitch::new(cache.clone())
+                        .with_metrics(metrics.clone()),
+                )
+            };
+            let limits =
+                RiskLimits::from_env().map_err(|e| format!("Invalid risk limits: {}", e))?;
+            let mut risk =
+                RiskManager::new(limits, Arc::new(kill_switch)).with_metrics(metrics.clone());
+            if !shadow {
+                // Shared with the kill switch so a restart keeps the daily loss baseline
+                risk = risk.with_equity_store(cache.clone());
+            }
+            let risk = Arc::new(risk);
+            let guarded = Arc::new(RiskGuardedExecutor::new(target, risk));
+            let oms = Arc::new(OrderManager::new(
+                guarded.clone() as Arc<dyn ExchangeExecutor>,
+                db.clone(),
+            ));
+            let report = oms
+                .restore()
+                .await
+                .map_err(|e| format!("Failed to restore orders: {}", e))?;
+            info!(updated = report.updated, "Order state restored");
+            let engine = Arc::new(PolicyEngine::new(oms.clone()));
+            // Entries placed before a restart still need their stops managed
+            engine
+                .restore()
+                .await
+                .map_err(|e| format!("Failed to restore policy engine: {}", e))?;
+
+            // Shadow mode only tracks its own orders; the account's real orders,
+            // positions and algos belong to live trading
+            if !shadow {
+                // Fills and order updates are pushed over the user event stream;
+                // the OMS poll loop remains as a fallback
+                let user_events = Arc::new(
+                    HyperliquidUserEvents::from_env()
+                        .map_err(|e| format!("Failed to create user event stream: {}", e))?,
+                );
+                let oms_events = oms.clone();
+                let events = user_events.subscribe();
+                execution_handles.push(tokio::spawn(async move {
+                    oms_events.follow_user_events(events).await
+                }));
+
+                // Positions and PnL are built from the same stream
+                let positions = Arc::new(PositionTracker::new(
+                    user_events.user(),
+                    exchange.clone(),
+                    db.clone(),
+                ));
+                positions
+                    .restore()
+                    .await
+                    .map_err(|e| format!("Failed to restore positions: {}", e))?;
+                let positions_events = positions.clone();
+                let events = user_events.subscribe();
+                execution_handles.push(tokio::spawn(async move {
+                    positions_events.follow_user_events(events).await
+                }));
+                execution_handles.push(tokio::spawn(async move { positions.run().await }));
+                execution_handles.push(tokio::spawn(async move { user_events.run().await }));
+
+                // Algos are requested through the API and run against this OMS
+                let algos = Arc::new(AlgoEngine::new(
+                    oms.clone(),
+                    cache.clone(),
+                ));
+                let interrupted = algos
+                    .restore()
+                    .await
+                    .map_err(|e| format!("Failed to restore algos: {}", e))?;
+                info!(interrupted = interrupted, "Algo state restored");
+                execution_handles.push(tokio::spawn(async move { algos.run().await }));
+            }
+
+            let oms_task = oms.clone();
+            execution_handles.push(tokio::spawn(async move { oms_task.run().await }));
+            execution_handles.push(tokio::spawn(async move { guarded.run().await }));
+            let engine_task = engine.clone();
+            execution_handles.push(tokio::spawn(async move { engine_task.run().await }));
+            job_context = job_context.with_policy_engine(engine);
+        }
+        other => {
+            return Err(format!("Unknown EXECUTION_MODE '{}', expected off, shadow or live", other).into())
+        }
+    }
+    let job_context = Arc::new(job_context);
 
     // Initialize and start job runtime (workers)
     info!("Starting Apalis workers...");
+    let job_context_has_engine = job_context.policy_engine.is_some();
     let runtime = SignalRuntime::new(
         runtime_config.clone(),
         job_context,
@@ -156,22 +320,69 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         eval_storage.clone(),
         store_storage.clone(),
     )
-    .with_concurrency(concurrency);
+    .with_concurrency(concurrency)
+    .with_backfill_storage(backfill_storage);
+    let runtime = if job_context_has_engine {
+        runtime.with_execute_storage(execute_storage)
+    } else {
+        runtime
+    };
     let worker_handles = runtime.start_workers().await.map_err(|e| format!("Failed to start workers: {}", e))?;
 
-    // Initialize and start scheduler
-    info!("Starting job scheduler...");
-    let scheduler = JobScheduler::new(fetch_storage, symbols.clone(), eval_interval)
-        .map_err(|e| format!("Failed to create scheduler: {}", e))?;
-    scheduler.start().await.map_err(|e| format!("Failed to start scheduler: {}", e))?;
+    // Evaluate as soon as a bar closes
+    info!("Starting candle close trigger...");
+    let trigger = Arc::new(CandleCloseTrigger::new(&symbols, &eval_candle_interval));
+    trigger
+        .start(cache.clone(), fetch_storage.clone())
+        .await;
+
+    // Cron scheduling as the fallback
+    let scheduler = if eval_interval > 0 {
+        info!("Starting job scheduler...");
+        let scheduler = JobScheduler::new(fetch_storage, symbols.clone(), eval_interval)
+            .map_err(|e| format!("Failed to create scheduler: {}", e))?;
+        scheduler.start().await.map_err(|e| format!("Failed to start scheduler: {}", e))?;
+        Some(Arc::new(scheduler))
+    } else {
+        None
+    };
+
+    // Follow strategy changes without a restart
+    let reload = Arc::new(Notify::new());
+    let notify = reload.clone();
+    let reload_handles = vec![
+        follow_commands(cache.clone(), move |command| {
+            let notify = notify.clone();
+            async move {
+                if command == SubscriptionCommand::Reload {
+                    notify.notify_one();
+                }
+            }
+        }),
+        tokio::spawn(follow_strategy_symbols(
+            db.clone(),
+            symbols,
+            trigger.clone(),
+            scheduler.clone(),
+            reload,
+            Duration::from_secs(strategy_reload),
+        )),
+    ];
 
     // Graceful shutdown
     info!("Worker started, waiting for shutdown signal...");
     tokio::select! {
         _ = signal::ctrl_c() => {
             info!("Shutting down worker...");
-            scheduler.stop().await;
-            for handle in worker_handles {
+            trigger.stop().await;
+            if let Some(scheduler) = scheduler {
+                scheduler.stop().await;
+            }
+            for handle in worker_handles
+                .into_iter()
+                .chain(execution_handles)
+                .chain(reload_handles)
+            {
                 handle.abort();
             }
             info!("Worker stopped");
@@ -181,3 +392,41 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+
+/// Re-read the strategy symbols every `period` (zero: only when notified) and
+/// hand changes to the candle close trigger and the scheduler
+async fn follow_strategy_symbols(
+    source: Arc<dyn SymbolSource>,
+    mut symbols: Vec<String>,
+    trigger: Arc<CandleCloseTrigger>,
+    scheduler: Option<Arc<JobScheduler>>,
+    reload: Arc<Notify>,
+    period: Duration,
+) {
+    loop {
+        if period.is_zero() {
+            reload.notified().await;
+        } else {
+            tokio::select! {
+                _ = reload.notified() => {}
+                _ = tokio::time::sleep(period) => {}
+            }
+        }
+        let reloaded = match source.symbols().await {
+            Ok(reloaded) => reloaded,
+            Err(e) => {
+                warn!(error = %e, "Failed to reload strategy symbols");
+                continue;
+            }
+        };
+        if reloaded == symbols {
+            continue;
+        }
+        info!(symbols = ?reloaded, "Strategy symbols changed: {}", reloaded.join(", "));
+        trigger.set_symbols(&reloaded);
+        if let Some(ref scheduler) = scheduler {
+            scheduler.set_symbols(reloaded.clone()).await;
+        }
+        symbols = reloaded;
+    }
+}
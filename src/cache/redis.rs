@@ -1,16 +1,58 @@
-//! Redis cache for candles
+//! Redis cache for candles, and shared state such as the kill switch, the
+//! day's starting equity, execution algo progress, the active market data
+//! subscriptions and the candle ranges confirmed empty
 
 use crate::config;
+use crate::execution::{
+    AlgoProgress, AlgoStore, DayStartEquityStore, KillSwitchState, KillSwitchStore,
+};
 use crate::models::indicators::Candle;
+use crate::services::candle_events::{CandleClosed, CANDLE_CLOSED_CHANNEL};
+use crate::services::candle_gaps::{CandleGap, EmptyRangeStore};
+use crate::services::instruments::normalize_symbol;
+use crate::services::subscription_sync::{
+    ActiveSubscription, SubscriptionCommand, SubscriptionStore, SUBSCRIPTION_CONTROL_CHANNEL,
+};
+use async_trait::async_trait;
+use futures_util::{Stream, StreamExt};
 use redis::AsyncCommands;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
 const CANDLE_CACHE_TTL: i64 = 3600; // 1 hour in seconds
 const CACHE_KEY_PREFIX: &str = "candles";
+const KILL_SWITCH_KEY: &str = "risk:kill_switch";
+const DAY_START_EQUITY_KEY_PREFIX: &str = "risk:day_start_equity";
+/// Day start equities outlive their day long enough for late readers
+const DAY_START_EQUITY_TTL: u64 = 2 * 24 * 3600; // 2 days in seconds
+const ALGO_KEY_PREFIX: &str = "algo";
+const ALGO_INDEX_KEY: &str = "algos";
+const ALGO_TTL: i64 = 7 * 24 * 3600; // 1 week in seconds
+/// Most recent algos returned by `load_algos`
+const ALGO_LIST_LIMIT: isize = 200;
+const SUBSCRIPTIONS_KEY: &str = "subscriptions:active";
+const EMPTY_RANGES_KEY_PREFIX: &str = "candle_gaps:empty";
+/// Confirmed empty ranges of symbols that are no longer audited expire
+const EMPTY_RANGES_TTL: u64 = 7 * 24 * 3600; // 1 week in seconds
+
+fn empty_ranges_key(symbol: &str, interval: &str) -> String {
+    format!(
+        "{}:{}:{}",
+        EMPTY_RANGES_KEY_PREFIX,
+        normalize_symbol(symbol),
+        interval
+    )
+}
+
+/// Candle cache key, under the canonical symbol so every spelling shares one entry
+fn candle_key(symbol: &str, interval: &str) -> String {
+    format!("{}:{}:{}", CACHE_KEY_PREFIX, normalize_symbol(symbol), interval)
+}
 
 pub struct RedisCache {
     client: Arc<RwLock<Option<redis::aio::ConnectionManager>>>,
+    /// Opens the dedicated connections pub/sub subscriptions need
+    redis: redis::Client,
 }
 
 impl RedisCache {
@@ -32,6 +74,7 @@ impl RedisCache {
 
         Ok(Self {
             client: Arc::new(RwLock::new(Some(connection))),
+            redis: client,
         })
     }
 
@@ -44,7 +87,7 @@ impl RedisCache {
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let mut conn = self.client.write().await;
         if let Some(ref mut c) = *conn {
-            let key = format!("{}:{}:{}", CACHE_KEY_PREFIX, symbol, interval);
+            let key = candle_key(symbol, interval);
             let json = serde_json::to_string(candles).map_err(|e| {
                 Box::new(std::io::Error::new(
                     std::io::ErrorKind::InvalidData,
@@ -78,7 +121,7 @@ impl RedisCache {
     ) -> Result<Option<Vec<Candle>>, Box<dyn std::error::Error + Send + Sync>> {
         let mut conn = self.client.write().await;
         if let Some(ref mut c) = *conn {
-            let key = format!("{}:{}:{}", CACHE_KEY_PREFIX, symbol, interval);
+            let key = candle_key(symbol, interval);
             let json: Option<String> = c.get(&key).await.map_err(|e| {
                 Box::new(std::io::Error::other(format!("Failed to get cache: {}", e)))
                     as Box<dyn std::error::Error + Send + Sync>
@@ -106,7 +149,7 @@ impl RedisCache {
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let mut conn = self.client.write().await;
         if let Some(ref mut c) = *conn {
-            let key = format!("{}:{}:{}", CACHE_KEY_PREFIX, symbol, interval);
+            let key = candle_key(symbol, interval);
             c.del::<_, ()>(&key).await.map_err(|e| {
                 Box::new(std::io::Error::other(format!(
                     "Failed to delete cache: {}",
@@ -118,9 +161,372 @@ impl RedisCache {
         Ok(())
     }
 
+    /// Publish a candle close on the candle close channel
+    pub async fn publish_candle_closed(
+        &self,
+        event: &CandleClosed,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let json = serde_json::to_string(event)?;
+        let mut conn = self.client.write().await;
+        if let Some(ref mut c) = *conn {
+            c.publish::<_, _, ()>(CANDLE_CLOSED_CHANNEL, json)
+                .await
+                .map_err(|e| format!("Failed to publish candle close: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// Candle closes published from now on. The stream ends when the
+    /// subscription connection drops; malformed messages are skipped.
+    pub async fn subscribe_candle_closes(
+        &self,
+    ) -> Result<impl Stream<Item = CandleClosed> + Send, Box<dyn std::error::Error + Send + Sync>>
+    {
+        let mut pubsub = self
+            .redis
+            .get_async_pubsub()
+            .await
+            .map_err(|e| format!("Failed to open Redis subscription: {}", e))?;
+        pubsub
+            .subscribe(CANDLE_CLOSED_CHANNEL)
+            .await
+            .map_err(|e| format!("Failed to subscribe to {}: {}", CANDLE_CLOSED_CHANNEL, e))?;
+        Ok(pubsub.into_on_message().filter_map(|msg| async move {
+            let payload: String = msg.get_payload().ok()?;
+            serde_json::from_str(&payload).ok()
+        }))
+    }
+
+    /// Subscription commands published from now on. The stream ends when the
+    /// subscription connection drops; malformed messages are skipped.
+    pub async fn subscribe_subscription_commands(
+        &self,
+    ) -> Result<
+        impl Stream<Item = SubscriptionCommand> + Send,
+        Box<dyn std::error::Error + Send + Sync>,
+    > {
+        let mut pubsub = self
+            .redis
+            .get_async_pubsub()
+            .await
+            .map_err(|e| format!("Failed to open Redis subscription: {}", e))?;
+        pubsub
+            .subscribe(SUBSCRIPTION_CONTROL_CHANNEL)
+            .await
+            .map_err(|e| {
+                format!(
+                    "Failed to subscribe to {}: {}",
+                    SUBSCRIPTION_CONTROL_CHANNEL, e
+                )
+            })?;
+        Ok(pubsub.into_on_message().filter_map(|msg| async move {
+            let payload: String = msg.get_payload().ok()?;
+            serde_json::from_str(&payload).ok()
+        }))
+    }
+
     /// Check if Redis connection is available
     pub async fn is_available(&self) -> bool {
         let conn = self.client.read().await;
         conn.is_some()
     }
 }
+
+#[async_trait]
+impl KillSwitchStore for RedisCache {
+    async fn load_kill_switch(
+        &self,
+    ) -> Result<Option<KillSwitchState>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut conn = self.client.write().await;
+        let Some(ref mut c) = *conn else {
+            return Err(Box::new(std::io::Error::other("Redis connection unavailable")));
+        };
+        let json: Option<String> = c.get(KILL_SWITCH_KEY).await.map_err(|e| {
+            Box::new(std::io::Error::other(format!(
+                "Failed to get kill switch: {}",
+                e
+            ))) as Box<dyn std::error::Error + Send + Sync>
+        })?;
+
+        json.map(|json| {
+            serde_json::from_str(&json).map_err(|e| {
+                Box::new(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("Failed to deserialize kill switch: {}", e),
+                )) as Box<dyn std::error::Error + Send + Sync>
+            })
+        })
+        .transpose()
+    }
+
+    async fn save_kill_switch(
+        &self,
+        state: &KillSwitchState,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut conn = self.client.write().await;
+        let Some(ref mut c) = *conn else {
+            return Err(Box::new(std::io::Error::other("Redis connection unavailable")));
+        };
+        let json = serde_json::to_string(state).map_err(|e| {
+            Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Failed to serialize kill switch: {}", e),
+            )) as Box<dyn std::error::Error + Send + Sync>
+        })?;
+
+        // No TTL: the switch stays set until someone clears it
+        c.set::<_, _, ()>(KILL_SWITCH_KEY, &json).await.map_err(|e| {
+            Box::new(std::io::Error::other(format!(
+                "Failed to set kill switch: {}",
+                e
+            ))) as Box<dyn std::error::Error + Send + Sync>
+        })?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl DayStartEquityStore for RedisCache {
+    async fn day_start_equity(
+        &self,
+        day: chrono::NaiveDate,
+        equity: f64,
+    ) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
+        let mut conn = self.client.write().await;
+        let Some(ref mut c) = *conn else {
+            return Err(Box::new(std::io::Error::other("Redis connection unavailable")));
+        };
+        let key = format!("{}:{}", DAY_START_EQUITY_KEY_PREFIX, day);
+
+        // The first process to see the day sets its start; everyone else reads it
+        let options = redis::SetOptions::default()
+            .conditional_set(redis::ExistenceCheck::NX)
+            .with_expiration(redis::SetExpiry::EX(DAY_START_EQUITY_TTL));
+        c.set_options::<_, _, ()>(&key, equity, options)
+            .await
+            .map_err(|e| redis_error("Failed to set day start equity", e))?;
+        let stored: Option<f64> = c
+            .get(&key)
+            .await
+            .map_err(|e| redis_error("Failed to get day start equity", e))?;
+        Ok(stored.unwrap_or(equity))
+    }
+}
+
+#[async_trait]
+impl EmptyRangeStore for RedisCache {
+    async fn load_empty_ranges(
+        &self,
+        symbol: &str,
+        interval: &str,
+    ) -> Result<Vec<CandleGap>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut conn = self.client.write().await;
+        let Some(ref mut c) = *conn else {
+            return Err(Box::new(std::io::Error::other("Redis connection unavailable")));
+        };
+        let json: Option<String> = c
+            .get(empty_ranges_key(symbol, interval))
+            .await
+            .map_err(|e| redis_error("Failed to get empty candle ranges", e))?;
+        match json {
+            Some(json) => serde_json::from_str(&json).map_err(|e| {
+                Box::new(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("Failed to deserialize empty candle ranges: {}", e),
+                )) as Box<dyn std::error::Error + Send + Sync>
+            }),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    async fn save_empty_ranges(
+        &self,
+        symbol: &str,
+        interval: &str,
+        ranges: &[CandleGap],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let json = serde_json::to_string(ranges)?;
+        let mut conn = self.client.write().await;
+        let Some(ref mut c) = *conn else {
+            return Err(Box::new(std::io::Error::other("Redis connection unavailable")));
+        };
+        c.set_ex::<_, _, ()>(empty_ranges_key(symbol, interval), &json, EMPTY_RANGES_TTL)
+            .await
+            .map_err(|e| redis_error("Failed to save empty candle ranges", e))
+    }
+}
+
+fn redis_error(context: &str, e: redis::RedisError) -> Box<dyn std::error::Error + Send + Sync> {
+    Box::new(std::io::Error::other(format!("{}: {}", context, e)))
+}
+
+fn parse_algo(json: &str) -> Result<AlgoProgress, Box<dyn std::error::Error + Send + Sync>> {
+    serde_json::from_str(json).map_err(|e| {
+        Box::new(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("Failed to deserialize algo: {}", e),
+        )) as Box<dyn std::error::Error + Send + Sync>
+    })
+}
+
+#[async_trait]
+impl AlgoStore for RedisCache {
+    async fn load_algos(&self) -> Result<Vec<AlgoProgress>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut conn = self.client.write().await;
+        let Some(ref mut c) = *conn else {
+            return Err(Box::new(std::io::Error::other("Redis connection unavailable")));
+        };
+        let ids: Vec<String> = c
+            .zrevrange(ALGO_INDEX_KEY, 0, ALGO_LIST_LIMIT - 1)
+            .await
+            .map_err(|e| redis_error("Failed to list algos", e))?;
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let keys: Vec<String> = ids
+            .iter()
+            .map(|id| format!("{}:{}", ALGO_KEY_PREFIX, id))
+            .collect();
+        let values: Vec<Option<String>> = c
+            .mget(&keys)
+            .await
+            .map_err(|e| redis_error("Failed to get algos", e))?;
+        // Expired algos drop out of the index lazily
+        values.iter().flatten().map(|json| parse_algo(json)).collect()
+    }
+
+    async fn load_algo(
+        &self,
+        id: &str,
+    ) -> Result<Option<AlgoProgress>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut conn = self.client.write().await;
+        let Some(ref mut c) = *conn else {
+            return Err(Box::new(std::io::Error::other("Redis connection unavailable")));
+        };
+        let json: Option<String> = c
+            .get(format!("{}:{}", ALGO_KEY_PREFIX, id))
+            .await
+            .map_err(|e| redis_error("Failed to get algo", e))?;
+        json.map(|json| parse_algo(&json)).transpose()
+    }
+
+    async fn save_algo(
+        &self,
+        progress: &AlgoProgress,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut conn = self.client.write().await;
+        let Some(ref mut c) = *conn else {
+            return Err(Box::new(std::io::Error::other("Redis connection unavailable")));
+        };
+        let json = serde_json::to_string(progress).map_err(|e| {
+            Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Failed to serialize algo: {}", e),
+            )) as Box<dyn std::error::Error + Send + Sync>
+        })?;
+
+        let key = format!("{}:{}", ALGO_KEY_PREFIX, progress.id);
+        c.set_ex::<_, _, ()>(&key, &json, ALGO_TTL as u64)
+            .await
+            .map_err(|e| redis_error("Failed to set algo", e))?;
+        c.zadd::<_, _, _, ()>(
+            ALGO_INDEX_KEY,
+            &progress.id,
+            progress.created_at.timestamp_millis(),
+        )
+        .await
+        .map_err(|e| redis_error("Failed to index algo", e))?;
+        // Trim index entries whose algo has expired
+        let cutoff = progress.created_at.timestamp_millis() - ALGO_TTL * 1000;
+        c.zrembyscore::<_, _, _, ()>(ALGO_INDEX_KEY, "-inf", cutoff)
+            .await
+            .map_err(|e| redis_error("Failed to trim algo index", e))?;
+        Ok(())
+    }
+
+    async fn request_algo_cancel(
+        &self,
+        id: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut conn = self.client.write().await;
+        let Some(ref mut c) = *conn else {
+            return Err(Box::new(std::io::Error::other("Redis connection unavailable")));
+        };
+        c.set_ex::<_, _, ()>(
+            format!("{}:{}:cancel", ALGO_KEY_PREFIX, id),
+            "1",
+            ALGO_TTL as u64,
+        )
+        .await
+        .map_err(|e| redis_error("Failed to request algo cancellation", e))
+    }
+
+    async fn algo_cancel_requested(
+        &self,
+        id: &str,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let mut conn = self.client.write().await;
+        let Some(ref mut c) = *conn else {
+            return Err(Box::new(std::io::Error::other("Redis connection unavailable")));
+        };
+        c.exists(format!("{}:{}:cancel", ALGO_KEY_PREFIX, id))
+            .await
+            .map_err(|e| redis_error("Failed to read algo cancellation", e))
+    }
+}
+
+#[async_trait]
+impl SubscriptionStore for RedisCache {
+    async fn save_subscriptions(
+        &self,
+        active: &[ActiveSubscription],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let json = serde_json::to_string(active)?;
+        let mut conn = self.client.write().await;
+        let Some(ref mut c) = *conn else {
+            return Err(Box::new(std::io::Error::other("Redis connection unavailable")));
+        };
+        // No TTL: the websocket-service rewrites the set whenever it changes
+        c.set::<_, _, ()>(SUBSCRIPTIONS_KEY, &json)
+            .await
+            .map_err(|e| redis_error("Failed to save subscriptions", e))
+    }
+
+    async fn load_subscriptions(
+        &self,
+    ) -> Result<Vec<ActiveSubscription>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut conn = self.client.write().await;
+        let Some(ref mut c) = *conn else {
+            return Err(Box::new(std::io::Error::other("Redis connection unavailable")));
+        };
+        let json: Option<String> = c
+            .get(SUBSCRIPTIONS_KEY)
+            .await
+            .map_err(|e| redis_error("Failed to get subscriptions", e))?;
+        match json {
+            Some(json) => serde_json::from_str(&json).map_err(|e| {
+                Box::new(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("Failed to deserialize subscriptions: {}", e),
+                )) as Box<dyn std::error::Error + Send + Sync>
+            }),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    async fn send_subscription_command(
+        &self,
+        command: &SubscriptionCommand,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let json = serde_json::to_string(command)?;
+        let mut conn = self.client.write().await;
+        let Some(ref mut c) = *conn else {
+            return Err(Box::new(std::io::Error::other("Redis connection unavailable")));
+        };
+        c.publish::<_, _, ()>(SUBSCRIPTION_CONTROL_CHANNEL, json)
+            .await
+            .map_err(|e| redis_error("Failed to publish subscription command", e))
+    }
+}
@@ -1,6 +1,7 @@
 //! Shared utilities and helpers across layers.
 
 pub mod math;
+pub mod random;
 pub mod time {
     //! Time-related helpers placeholder.
 }
@@ -150,6 +150,28 @@ pub fn get_hyperliquid_rest_url() -> String {
     }
 }
 
+/// Get the Binance USDⓈ-M futures WebSocket URL based on environment
+pub fn get_binance_futures_ws_url() -> String {
+    if let Ok(url) = std::env::var("BINANCE_FUTURES_WS_URL") {
+        return url;
+    }
+    match get_environment().as_str() {
+        "sandbox" | "testnet" => "wss://stream.binancefuture.com/ws".to_string(),
+        _ => "wss://fstream.binance.com/ws".to_string(),
+    }
+}
+
+/// Get the Binance USDⓈ-M futures REST API URL based on environment
+pub fn get_binance_futures_rest_url() -> String {
+    if let Ok(url) = std::env::var("BINANCE_FUTURES_REST_URL") {
+        return url;
+    }
+    match get_environment().as_str() {
+        "sandbox" | "testnet" => "https://testnet.binancefuture.com".to_string(),
+        _ => "https://fapi.binance.com".to_string(),
+    }
+}
+
 /// Get the number of historical candles to fetch on startup
 pub fn get_historical_candle_count() -> usize {
     std::env::var("HISTORICAL_CANDLE_COUNT")
@@ -157,3 +179,32 @@ pub fn get_historical_candle_count() -> usize {
         .and_then(|s| s.parse().ok())
         .unwrap_or(200)
 }
+
+/// Get the approximate number of events kept per market data bus topic
+pub fn get_bus_max_len() -> usize {
+    std::env::var("BUS_MAX_LEN")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(100_000)
+}
+
+/// Get the bearer token required by the state-changing execution endpoints;
+/// unset leaves those endpoints disabled
+pub fn get_execution_api_token() -> Option<String> {
+    std::env::var("EXECUTION_API_TOKEN")
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// Get the browser origins allowed to call the execution endpoints
+pub fn get_execution_api_origins() -> Vec<String> {
+    std::env::var("EXECUTION_API_ALLOWED_ORIGINS")
+        .map(|s| {
+            s.split(',')
+                .map(|origin| origin.trim().trim_end_matches('/').to_string())
+                .filter(|origin| !origin.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
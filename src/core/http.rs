@@ -1,10 +1,11 @@
 //! HTTP endpoint server using Axum
 
 use axum::{
-    extract::{Path, Query, Request, State},
-    http::StatusCode,
+    body::Bytes,
+    extract::{DefaultBodyLimit, Path, Query, Request, State},
+    http::{header, StatusCode},
     middleware::Next,
-    response::{Json, Response},
+    response::{IntoResponse, Json, Response},
     routing::{delete, get, post, put},
     Router,
 };
@@ -17,13 +18,33 @@ use tower_http::{
     cors::CorsLayer,
     trace::{DefaultMakeSpan, DefaultOnRequest, DefaultOnResponse, TraceLayer},
 };
-use tracing::{error, info, Level};
+use tracing::{error, info, warn, Level};
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
 use crate::db::QuestDatabase;
+use crate::execution::{
+    shadow_exchange_name, AccountPnl, AlgoOrder, AlgoProgress, AlgoStore, KillSwitch,
+    KillSwitchState, ManagedOrder, OrderSide, TrackedPosition,
+};
 use crate::metrics::Metrics;
 use crate::models::strategy::{Strategy, StrategyConfig};
+use crate::paper::{EquitySnapshot, FillAction, PaperAccount, PaperFill, PaperPosition};
+use crate::services::candle_io::{self, CandleFormat, CandleIoError};
+use crate::services::instruments::{normalize_symbol, Instrument, InstrumentRegistry};
+use crate::services::subscription_sync::{
+    ActiveSubscription, SubscriptionCommand, SubscriptionSource, SubscriptionStore,
+};
+
+/// Maximum accepted size of an uploaded candle file; bulk loads go through the `candles` CLI
+const CANDLE_IMPORT_BODY_LIMIT: usize = 8 * 1024 * 1024;
+
+/// Default lookback of the shadow order endpoint
+const SHADOW_ORDER_DEFAULT_HOURS: i64 = 24;
+
+/// Fills and equity points returned by the per-strategy paper PnL endpoint
+const PAPER_FILL_LIMIT: usize = 100;
+const PAPER_EQUITY_LIMIT: usize = 500;
 
 #[derive(Clone)]
 pub struct AppState {
@@ -31,6 +52,60 @@ pub struct AppState {
     pub metrics: Arc<Metrics>,
     pub start_time: Arc<Instant>,
     pub database: Option<Arc<QuestDatabase>>,
+    pub kill_switch: Option<Arc<KillSwitch>>,
+    pub algos: Option<Arc<dyn AlgoStore>>,
+    /// Listed instruments, set once loaded; until then requests that take a
+    /// symbol to trade or stream are refused
+    pub instruments: Arc<RwLock<Option<Arc<InstrumentRegistry>>>>,
+    /// Market data subscriptions of the websocket-service
+    pub subscriptions: Option<Arc<dyn SubscriptionStore>>,
+    /// Credentials of the state-changing execution endpoints; without it they are disabled
+    pub execution_auth: Option<Arc<ExecutionAuth>>,
+}
+
+/// Bearer token and browser origins accepted by the execution endpoints
+#[derive(Debug, Clone)]
+pub struct ExecutionAuth {
+    pub token: String,
+    /// Requests carrying an `Origin` header must come from one of these
+    pub allowed_origins: Vec<String>,
+}
+
+impl ExecutionAuth {
+    pub fn new(token: impl Into<String>) -> Self {
+        Self {
+            token: token.into(),
+            allowed_origins: Vec::new(),
+        }
+    }
+
+    pub fn with_allowed_origins(mut self, origins: Vec<String>) -> Self {
+        self.allowed_origins = origins;
+        self
+    }
+
+    /// Build from `EXECUTION_API_TOKEN` and `EXECUTION_API_ALLOWED_ORIGINS`
+    pub fn from_env() -> Option<Self> {
+        crate::config::get_execution_api_token().map(|token| {
+            Self::new(token).with_allowed_origins(crate::config::get_execution_api_origins())
+        })
+    }
+
+    fn accepts_token(&self, token: &str) -> bool {
+        // Compare in constant time so the token cannot be guessed byte by byte
+        let (expected, given) = (self.token.as_bytes(), token.as_bytes());
+        expected.len() == given.len()
+            && expected
+                .iter()
+                .zip(given)
+                .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    }
+
+    fn accepts_origin(&self, origin: &str) -> bool {
+        let origin = origin.trim_end_matches('/');
+        self.allowed_origins.iter().any(|allowed| allowed == origin)
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, utoipa::ToSchema)]
@@ -104,7 +179,7 @@ struct StrategyQuery {
 struct CreateStrategyRequest {
     /// Strategy name
     name: String,
-    /// Trading symbol (e.g., "BTC-USD")
+    /// Trading symbol (e.g., "BTC")
     symbol: String,
     /// Strategy configuration
     config: StrategyConfig,
@@ -216,7 +291,52 @@ async fn get_strategy(
     Ok(Json(strategy.into()))
 }
 
+/// Canonical form of a requested symbol, rejecting symbols the instrument
+/// registry does not list, and every symbol while it is not loaded
+async fn canonical_symbol(state: &AppState, symbol: &str) -> Result<String, (StatusCode, String)> {
+    let instruments = state.instruments.read().await.clone().ok_or((
+        StatusCode::SERVICE_UNAVAILABLE,
+        "Instrument registry unavailable".to_string(),
+    ))?;
+    instruments
+        .normalize(symbol)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))
+}
+
+async fn validate_strategy_symbol(state: &AppState, symbol: &str) -> Result<String, StatusCode> {
+    canonical_symbol(state, symbol).await.map_err(|(status, e)| {
+        warn!(error = %e, symbol = %symbol, "Rejected strategy symbol");
+        status
+    })
+}
+
+/// Have the websocket-service and workers re-read the strategy table
+async fn announce_strategy_change(state: &AppState) {
+    if let Some(ref subscriptions) = state.subscriptions {
+        if let Err(e) = subscriptions
+            .send_subscription_command(&SubscriptionCommand::Reload)
+            .await
+        {
+            // They reload periodically anyway
+            warn!(error = %e, "Failed to announce strategy change");
+        }
+    }
+}
+
+fn validate_strategy_config(config: &StrategyConfig) -> Result<(), StatusCode> {
+    if let Some(ref policy) = config.execution {
+        policy.validate().map_err(|e| {
+            warn!(error = %e, "Rejected invalid execution policy");
+            StatusCode::BAD_REQUEST
+        })?;
+    }
+    Ok(())
+}
+
 /// Create a new strategy
+///
+/// A strategy with an execution policy places orders in `live` mode, so this
+/// requires the `EXECUTION_API_TOKEN` bearer token.
 #[utoipa::path(
     post,
     path = "/api/strategies",
@@ -224,13 +344,18 @@ async fn get_strategy(
     request_body = CreateStrategyRequest,
     responses(
         (status = 200, description = "Strategy created", body = StrategyResponse),
-        (status = 503, description = "Database unavailable")
+        (status = 400, description = "Unknown symbol or invalid execution policy"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Execution endpoints disabled or origin not allowed"),
+        (status = 503, description = "Database or instrument registry unavailable")
     )
 )]
 async fn create_strategy(
     State(state): State<AppState>,
     Json(request): Json<CreateStrategyRequest>,
 ) -> Result<Json<StrategyResponse>, StatusCode> {
+    validate_strategy_config(&request.config)?;
+    let symbol = validate_strategy_symbol(&state, &request.symbol).await?;
     let db = state
         .database
         .as_ref()
@@ -240,7 +365,7 @@ async fn create_strategy(
     let strategy = Strategy {
         id: None,
         name: request.name,
-        symbol: request.symbol,
+        symbol,
         config: request.config,
         created_at: now,
         updated_at: now,
@@ -255,11 +380,12 @@ async fn create_strategy(
         error!(error = %e, strategy_id = id, "Failed to load created strategy");
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
+    announce_strategy_change(&state).await;
 
     Ok(Json(created_strategy.into()))
 }
 
-/// Update a strategy
+/// Update a strategy. Requires the `EXECUTION_API_TOKEN` bearer token.
 #[utoipa::path(
     put,
     path = "/api/strategies/{id}",
@@ -270,8 +396,11 @@ async fn create_strategy(
     request_body = UpdateStrategyRequest,
     responses(
         (status = 200, description = "Strategy updated", body = StrategyResponse),
+        (status = 400, description = "Unknown symbol or invalid execution policy"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Execution endpoints disabled or origin not allowed"),
         (status = 404, description = "Strategy not found"),
-        (status = 503, description = "Database unavailable")
+        (status = 503, description = "Database or instrument registry unavailable")
     )
 )]
 async fn update_strategy(
@@ -279,6 +408,13 @@ async fn update_strategy(
     Path(id): Path<i64>,
     Json(request): Json<UpdateStrategyRequest>,
 ) -> Result<Json<StrategyResponse>, StatusCode> {
+    if let Some(ref config) = request.config {
+        validate_strategy_config(config)?;
+    }
+    let symbol = match request.symbol.as_deref() {
+        Some(symbol) => Some(validate_strategy_symbol(&state, symbol).await?),
+        None => None,
+    };
     let db = state
         .database
         .as_ref()
@@ -297,7 +433,7 @@ async fn update_strategy(
     if let Some(name) = request.name {
         strategy.name = name;
     }
-    if let Some(symbol) = request.symbol {
+    if let Some(symbol) = symbol {
         strategy.symbol = symbol;
     }
     if let Some(config) = request.config {
@@ -309,11 +445,12 @@ async fn update_strategy(
         error!(error = %e, strategy_id = id, "Failed to update strategy");
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
+    announce_strategy_change(&state).await;
 
     Ok(Json(strategy.into()))
 }
 
-/// Delete a strategy
+/// Delete a strategy. Requires the `EXECUTION_API_TOKEN` bearer token.
 #[utoipa::path(
     delete,
     path = "/api/strategies/{id}",
@@ -323,6 +460,8 @@ async fn update_strategy(
     ),
     responses(
         (status = 204, description = "Strategy deleted"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Execution endpoints disabled or origin not allowed"),
         (status = 404, description = "Strategy not found"),
         (status = 503, description = "Database unavailable")
     )
@@ -344,10 +483,873 @@ async fn delete_strategy(
             StatusCode::INTERNAL_SERVER_ERROR
         }
     })?;
+    announce_strategy_change(&state).await;
 
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Instruments known to the instrument registry, with their trading constraints
+#[utoipa::path(
+    get,
+    path = "/api/instruments",
+    tag = "Instruments",
+    responses(
+        (status = 200, description = "Instruments sorted by symbol", body = Vec<Instrument>),
+        (status = 503, description = "Instrument registry unavailable")
+    )
+)]
+async fn list_instruments(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<Instrument>>, StatusCode> {
+    let instruments = state
+        .instruments
+        .read()
+        .await
+        .clone()
+        .ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+
+    Ok(Json(instruments.instruments()))
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams, utoipa::ToSchema)]
+#[into_params(parameter_in = Query)]
+struct CandleExportQuery {
+    /// Trading symbol (e.g., "BTC")
+    symbol: String,
+    /// Candle interval (e.g., "1m", "1h")
+    interval: String,
+    /// Output format (defaults to csv)
+    format: Option<CandleFormat>,
+    /// Inclusive start of the range (RFC 3339)
+    start: Option<chrono::DateTime<chrono::Utc>>,
+    /// Inclusive end of the range (RFC 3339)
+    end: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams, utoipa::ToSchema)]
+#[into_params(parameter_in = Query)]
+struct CandleImportQuery {
+    /// Trading symbol (e.g., "BTC")
+    symbol: String,
+    /// Candle interval (e.g., "1m", "1h")
+    interval: String,
+    /// Format of the request body (defaults to csv)
+    format: Option<CandleFormat>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+struct CandleImportResponse {
+    /// Trading symbol
+    symbol: String,
+    /// Candle interval
+    interval: String,
+    /// Valid candles read from the file
+    rows: usize,
+    /// Candles written to the database
+    imported: usize,
+    /// Candles skipped because their timestamp was already stored
+    skipped_existing: usize,
+    /// Timestamp of the oldest candle in the file
+    first: Option<chrono::DateTime<chrono::Utc>>,
+    /// Timestamp of the newest candle in the file
+    last: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl From<candle_io::CandleImportReport> for CandleImportResponse {
+    fn from(report: candle_io::CandleImportReport) -> Self {
+        Self {
+            symbol: report.symbol,
+            interval: report.interval,
+            rows: report.rows,
+            imported: report.imported,
+            skipped_existing: report.skipped_existing,
+            first: report.first,
+            last: report.last,
+        }
+    }
+}
+
+/// Export stored candles as CSV or Parquet
+#[utoipa::path(
+    get,
+    path = "/api/candles/export",
+    tag = "Candles",
+    params(CandleExportQuery),
+    responses(
+        (status = 200, description = "Candle file", content_type = "application/octet-stream"),
+        (status = 503, description = "Database unavailable")
+    )
+)]
+async fn export_candles(
+    State(state): State<AppState>,
+    Query(params): Query<CandleExportQuery>,
+) -> Result<Response, StatusCode> {
+    let db = state
+        .database
+        .as_ref()
+        .ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+
+    let format = params.format.unwrap_or(CandleFormat::Csv);
+    let symbol = normalize_symbol(&params.symbol);
+    let data = candle_io::export_candles(
+        db.as_ref(),
+        &symbol,
+        &params.interval,
+        params.start,
+        params.end,
+        format,
+    )
+    .await
+    .map_err(|e| {
+        error!(error = %e, symbol = %params.symbol, interval = %params.interval, "Failed to export candles");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let filename = format!(
+        "{}_{}.{}",
+        symbol,
+        params.interval,
+        format.extension()
+    );
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ],
+        data,
+    )
+        .into_response())
+}
+
+/// Import candles from a CSV or Parquet file
+///
+/// The file is validated before anything is written; candles whose timestamp is
+/// already stored are skipped. Files are limited to 8 MB; use the `candles` CLI
+/// for bulk loads. Requires the `EXECUTION_API_TOKEN` bearer token.
+#[utoipa::path(
+    post,
+    path = "/api/candles/import",
+    tag = "Candles",
+    params(CandleImportQuery),
+    request_body(content = Vec<u8>, description = "CSV or Parquet file contents", content_type = "application/octet-stream"),
+    responses(
+        (status = 200, description = "Candles imported", body = CandleImportResponse),
+        (status = 400, description = "Invalid candle file"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Execution endpoints disabled or origin not allowed"),
+        (status = 413, description = "File larger than 8 MB"),
+        (status = 503, description = "Database unavailable")
+    )
+)]
+async fn import_candles(
+    State(state): State<AppState>,
+    Query(params): Query<CandleImportQuery>,
+    body: Bytes,
+) -> Result<Json<CandleImportResponse>, (StatusCode, String)> {
+    let db = state.database.as_ref().ok_or((
+        StatusCode::SERVICE_UNAVAILABLE,
+        "Database unavailable".to_string(),
+    ))?;
+
+    let format = params.format.unwrap_or(CandleFormat::Csv);
+    let symbol = normalize_symbol(&params.symbol);
+    let report = candle_io::import_candles(
+        db.as_ref(),
+        &symbol,
+        &params.interval,
+        &body,
+        format,
+    )
+    .await
+    .map_err(|e| match e {
+        CandleIoError::Storage(_) => {
+            error!(error = %e, symbol = %params.symbol, interval = %params.interval, "Failed to import candles");
+            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+        }
+        _ => {
+            warn!(error = %e, symbol = %params.symbol, interval = %params.interval, "Rejected candle import");
+            (StatusCode::BAD_REQUEST, e.to_string())
+        }
+    })?;
+
+    info!(
+        symbol = %report.symbol,
+        interval = %report.interval,
+        imported = report.imported,
+        "Imported {} candles for {}/{}",
+        report.imported,
+        report.symbol,
+        report.interval
+    );
+    Ok(Json(report.into()))
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+struct PaperPnlResponse {
+    /// Strategy ID
+    strategy_id: i64,
+    /// Trading symbol
+    symbol: String,
+    /// Starting equity of the paper account
+    initial_capital: f64,
+    /// Marked-to-market equity
+    equity: f64,
+    /// Equity change since the account was opened
+    total_pnl: f64,
+    /// Total PnL relative to initial capital (percentage)
+    return_pct: f64,
+    /// Net PnL of closed trades after fees and funding
+    realized_pnl: f64,
+    /// PnL of the open position at the latest mark
+    unrealized_pnl: f64,
+    /// Fees paid
+    fees_paid: f64,
+    /// Funding paid (positive) or received (negative)
+    funding_paid: f64,
+    /// Closed round trips
+    trades: usize,
+    /// Open position, if any
+    position: Option<PaperPosition>,
+    /// Latest mark price
+    mark_price: Option<f64>,
+    /// Last account update
+    updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<PaperAccount> for PaperPnlResponse {
+    fn from(account: PaperAccount) -> Self {
+        Self {
+            strategy_id: account.strategy_id,
+            equity: account.equity(),
+            total_pnl: account.total_pnl(),
+            return_pct: account.return_pct(),
+            unrealized_pnl: account.unrealized_pnl(),
+            symbol: account.symbol,
+            initial_capital: account.initial_capital,
+            realized_pnl: account.realized_pnl,
+            fees_paid: account.fees_paid,
+            funding_paid: account.funding_paid,
+            trades: account.trades,
+            position: account.position,
+            mark_price: account.mark_price,
+            updated_at: account.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+struct PaperStrategyPnlResponse {
+    /// Account summary
+    pnl: PaperPnlResponse,
+    /// Most recent fills, newest first
+    fills: Vec<PaperFill>,
+    /// Recent equity snapshots, oldest first
+    equity_curve: Vec<EquitySnapshot>,
+}
+
+/// Paper trading PnL of every strategy
+#[utoipa::path(
+    get,
+    path = "/api/paper/pnl",
+    tag = "Paper Trading",
+    responses(
+        (status = 200, description = "Paper PnL per strategy", body = Vec<PaperPnlResponse>),
+        (status = 503, description = "Database unavailable")
+    )
+)]
+async fn list_paper_pnl(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<PaperPnlResponse>>, StatusCode> {
+    let db = state
+        .database
+        .as_ref()
+        .ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+
+    let accounts = db.get_paper_accounts(None).await.map_err(|e| {
+        error!(error = %e, "Failed to load paper accounts");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(accounts.into_iter().map(Into::into).collect()))
+}
+
+/// Paper trading PnL, recent fills and equity curve of a strategy
+#[utoipa::path(
+    get,
+    path = "/api/paper/pnl/{strategy_id}",
+    tag = "Paper Trading",
+    params(
+        ("strategy_id" = i64, Path, description = "Strategy ID")
+    ),
+    responses(
+        (status = 200, description = "Paper PnL of the strategy", body = PaperStrategyPnlResponse),
+        (status = 404, description = "Strategy has no paper account"),
+        (status = 503, description = "Database unavailable")
+    )
+)]
+async fn get_paper_pnl(
+    State(state): State<AppState>,
+    Path(strategy_id): Path<i64>,
+) -> Result<Json<PaperStrategyPnlResponse>, StatusCode> {
+    let db = state
+        .database
+        .as_ref()
+        .ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+
+    let account = db
+        .get_paper_accounts(Some(strategy_id))
+        .await
+        .map_err(|e| {
+            error!(error = %e, strategy_id = strategy_id, "Failed to load paper account");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .into_iter()
+        .next()
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let fills = db
+        .get_paper_fills(strategy_id, PAPER_FILL_LIMIT)
+        .await
+        .map_err(|e| {
+            error!(error = %e, strategy_id = strategy_id, "Failed to load paper fills");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    let equity_curve = db
+        .get_paper_equity(strategy_id, PAPER_EQUITY_LIMIT)
+        .await
+        .map_err(|e| {
+            error!(error = %e, strategy_id = strategy_id, "Failed to load paper equity");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(PaperStrategyPnlResponse {
+        pnl: account.into(),
+        fills,
+        equity_curve,
+    }))
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams, utoipa::ToSchema)]
+#[into_params(parameter_in = Query)]
+struct PositionQuery {
+    /// Filter by account address
+    account: Option<String>,
+    /// Also return flat positions (default false)
+    #[serde(default)]
+    include_closed: bool,
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams, utoipa::ToSchema)]
+#[into_params(parameter_in = Query)]
+struct PnlQuery {
+    /// Filter by account address
+    account: Option<String>,
+}
+
+/// Latest tracked positions of the live trading accounts
+#[utoipa::path(
+    get,
+    path = "/api/positions",
+    tag = "Positions",
+    params(PositionQuery),
+    responses(
+        (status = 200, description = "Positions per account and symbol", body = Vec<TrackedPosition>),
+        (status = 503, description = "Database unavailable")
+    )
+)]
+async fn list_positions(
+    State(state): State<AppState>,
+    Query(params): Query<PositionQuery>,
+) -> Result<Json<Vec<TrackedPosition>>, StatusCode> {
+    let db = state
+        .database
+        .as_ref()
+        .ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+
+    let account = params.account.map(|a| a.to_lowercase());
+    let positions = db.get_positions(account.as_deref()).await.map_err(|e| {
+        error!(error = %e, "Failed to load positions");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(
+        positions
+            .into_iter()
+            .filter(|p| params.include_closed || p.is_open())
+            .collect(),
+    ))
+}
+
+/// Realized, unrealized, fee and funding PnL per live trading account
+#[utoipa::path(
+    get,
+    path = "/api/pnl",
+    tag = "Positions",
+    params(PnlQuery),
+    responses(
+        (status = 200, description = "PnL per account", body = Vec<AccountPnl>),
+        (status = 503, description = "Database unavailable")
+    )
+)]
+async fn list_pnl(
+    State(state): State<AppState>,
+    Query(params): Query<PnlQuery>,
+) -> Result<Json<Vec<AccountPnl>>, StatusCode> {
+    let db = state
+        .database
+        .as_ref()
+        .ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+
+    let account = params.account.map(|a| a.to_lowercase());
+    let positions = db.get_positions(account.as_deref()).await.map_err(|e| {
+        error!(error = %e, "Failed to load positions");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(AccountPnl::from_positions(&positions)))
+}
+
+/// Request an execution algo
+///
+/// The algo is queued and started by the trading process, which reports its
+/// progress back through the same store. Requires the `EXECUTION_API_TOKEN`
+/// bearer token.
+#[utoipa::path(
+    post,
+    path = "/api/algos",
+    tag = "Algos",
+    request_body = AlgoOrder,
+    responses(
+        (status = 202, description = "Algo queued", body = AlgoProgress),
+        (status = 400, description = "Invalid algo order"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Execution endpoints disabled or origin not allowed"),
+        (status = 503, description = "Algo store or instrument registry unavailable")
+    )
+)]
+async fn submit_algo(
+    State(state): State<AppState>,
+    Json(mut order): Json<AlgoOrder>,
+) -> Result<(StatusCode, Json<AlgoProgress>), (StatusCode, String)> {
+    let algos = state.algos.as_ref().ok_or((
+        StatusCode::SERVICE_UNAVAILABLE,
+        "Algo store unavailable".to_string(),
+    ))?;
+    order.symbol = canonical_symbol(&state, &order.symbol).await?;
+    order
+        .validate()
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+
+    let progress = AlgoProgress::new(order, chrono::Utc::now());
+    algos.save_algo(&progress).await.map_err(|e| {
+        error!(error = %e, "Failed to save algo");
+        (StatusCode::SERVICE_UNAVAILABLE, e.to_string())
+    })?;
+
+    info!(
+        algo_id = %progress.id,
+        symbol = %progress.order.symbol,
+        "Algo {} requested via API",
+        progress.id
+    );
+    Ok((StatusCode::ACCEPTED, Json(progress)))
+}
+
+/// Recent execution algos, newest first
+#[utoipa::path(
+    get,
+    path = "/api/algos",
+    tag = "Algos",
+    responses(
+        (status = 200, description = "Algos and their progress", body = Vec<AlgoProgress>),
+        (status = 503, description = "Algo store unavailable")
+    )
+)]
+async fn list_algos(State(state): State<AppState>) -> Result<Json<Vec<AlgoProgress>>, StatusCode> {
+    let algos = state
+        .algos
+        .as_ref()
+        .ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+
+    let progress = algos.load_algos().await.map_err(|e| {
+        error!(error = %e, "Failed to load algos");
+        StatusCode::SERVICE_UNAVAILABLE
+    })?;
+    Ok(Json(progress))
+}
+
+/// Progress of a single execution algo
+#[utoipa::path(
+    get,
+    path = "/api/algos/{id}",
+    tag = "Algos",
+    params(
+        ("id" = String, Path, description = "Algo id")
+    ),
+    responses(
+        (status = 200, description = "Algo progress", body = AlgoProgress),
+        (status = 404, description = "Algo not found"),
+        (status = 503, description = "Algo store unavailable")
+    )
+)]
+async fn get_algo(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<AlgoProgress>, StatusCode> {
+    let algos = state
+        .algos
+        .as_ref()
+        .ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+
+    let progress = algos.load_algo(&id).await.map_err(|e| {
+        error!(error = %e, "Failed to load algo");
+        StatusCode::SERVICE_UNAVAILABLE
+    })?;
+    progress.map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+/// Cancel an execution algo
+///
+/// The trading process stops the algo and cancels its open child orders on its
+/// next tick. Requires the `EXECUTION_API_TOKEN` bearer token.
+#[utoipa::path(
+    post,
+    path = "/api/algos/{id}/cancel",
+    tag = "Algos",
+    params(
+        ("id" = String, Path, description = "Algo id")
+    ),
+    responses(
+        (status = 202, description = "Cancellation requested", body = AlgoProgress),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Execution endpoints disabled or origin not allowed"),
+        (status = 404, description = "Algo not found"),
+        (status = 409, description = "Algo already finished"),
+        (status = 503, description = "Algo store unavailable")
+    )
+)]
+async fn cancel_algo(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<(StatusCode, Json<AlgoProgress>), StatusCode> {
+    let algos = state
+        .algos
+        .as_ref()
+        .ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+
+    let progress = algos
+        .load_algo(&id)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to load algo");
+            StatusCode::SERVICE_UNAVAILABLE
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    if progress.state.is_terminal() {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    algos.request_algo_cancel(&id).await.map_err(|e| {
+        error!(error = %e, "Failed to request algo cancellation");
+        StatusCode::SERVICE_UNAVAILABLE
+    })?;
+    info!(algo_id = %id, "Algo {} cancellation requested via API", id);
+    Ok((StatusCode::ACCEPTED, Json(progress)))
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams, utoipa::ToSchema)]
+#[into_params(parameter_in = Query)]
+struct ShadowOrderQuery {
+    /// Exchange the shadow executor wraps (default hyperliquid)
+    exchange: Option<String>,
+    /// Filter by strategy ID
+    strategy_id: Option<i64>,
+    /// Orders created in the last N hours (default 24)
+    hours: Option<i64>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+struct ShadowOrderResponse {
+    client_order_id: String,
+    /// Strategy that caused the order, if any
+    strategy_id: Option<i64>,
+    /// Signal that caused the order (its timestamp in milliseconds)
+    signal_id: Option<i64>,
+    symbol: String,
+    side: OrderSide,
+    size: f64,
+    /// Limit price, or worst acceptable price for market and trigger orders
+    price: f64,
+    #[schema(value_type = Object)]
+    order_type: serde_json::Value,
+    reduce_only: bool,
+    /// Lifecycle state the order would have reached
+    state: String,
+    /// Assumed fill of marketable orders at the touch
+    filled_size: f64,
+    avg_fill_price: Option<f64>,
+    reject_reason: Option<String>,
+    created_at: chrono::DateTime<chrono::Utc>,
+    updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<ManagedOrder> for ShadowOrderResponse {
+    fn from(order: ManagedOrder) -> Self {
+        Self {
+            strategy_id: order.origin.strategy_id,
+            signal_id: order.origin.signal_id,
+            symbol: order.request.symbol,
+            side: order.request.side,
+            size: order.request.size,
+            price: order.request.price,
+            order_type: serde_json::to_value(order.request.order_type).unwrap_or_default(),
+            reduce_only: order.request.reduce_only,
+            state: order.state.as_str().to_string(),
+            client_order_id: order.client_order_id,
+            filled_size: order.filled_size,
+            avg_fill_price: order.avg_fill_price,
+            reject_reason: order.reject_reason,
+            created_at: order.created_at,
+            updated_at: order.updated_at,
+        }
+    }
+}
+
+/// Orders recorded by shadow execution instead of being sent, newest first
+///
+/// Compare them with the orders actually placed on the account and with the
+/// paper trading results of the same signals.
+#[utoipa::path(
+    get,
+    path = "/api/shadow/orders",
+    tag = "Shadow Trading",
+    params(ShadowOrderQuery),
+    responses(
+        (status = 200, description = "Shadow orders", body = Vec<ShadowOrderResponse>),
+        (status = 400, description = "Invalid lookback"),
+        (status = 503, description = "Database unavailable")
+    )
+)]
+async fn list_shadow_orders(
+    State(state): State<AppState>,
+    Query(params): Query<ShadowOrderQuery>,
+) -> Result<Json<Vec<ShadowOrderResponse>>, StatusCode> {
+    let db = state
+        .database
+        .as_ref()
+        .ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+
+    let hours = params.hours.unwrap_or(SHADOW_ORDER_DEFAULT_HOURS);
+    if hours <= 0 {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let exchange = shadow_exchange_name(params.exchange.as_deref().unwrap_or("hyperliquid"));
+    let since = chrono::Utc::now() - chrono::Duration::hours(hours);
+    let orders = db
+        .get_orders_since(since, Some(&exchange))
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to load shadow orders");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(
+        orders
+            .into_iter()
+            .rev()
+            .filter(|o| params.strategy_id.is_none() || o.origin.strategy_id == params.strategy_id)
+            .map(ShadowOrderResponse::from)
+            .collect(),
+    ))
+}
+
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+struct KillSwitchRequest {
+    /// Block (true) or allow (false) new orders
+    active: bool,
+    /// Why the switch was flipped
+    reason: Option<String>,
+    /// Also cancel open orders and close all positions (only when activating)
+    #[serde(default)]
+    flatten: bool,
+}
+
+/// Current kill switch state
+#[utoipa::path(
+    get,
+    path = "/api/risk/kill-switch",
+    tag = "Risk",
+    responses(
+        (status = 200, description = "Kill switch state", body = KillSwitchState),
+        (status = 503, description = "Kill switch store unavailable")
+    )
+)]
+async fn get_kill_switch(
+    State(state): State<AppState>,
+) -> Result<Json<KillSwitchState>, StatusCode> {
+    let kill_switch = state
+        .kill_switch
+        .as_ref()
+        .ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+
+    Ok(Json(kill_switch.state().await))
+}
+
+/// Activate or clear the global kill switch
+///
+/// While active, every executor refuses new orders. With `flatten`, the trading
+/// process also cancels open orders and closes all positions. Requires the
+/// `EXECUTION_API_TOKEN` bearer token.
+#[utoipa::path(
+    post,
+    path = "/api/risk/kill-switch",
+    tag = "Risk",
+    request_body = KillSwitchRequest,
+    responses(
+        (status = 200, description = "Kill switch updated", body = KillSwitchState),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Execution endpoints disabled or origin not allowed"),
+        (status = 503, description = "Kill switch store unavailable")
+    )
+)]
+async fn set_kill_switch(
+    State(state): State<AppState>,
+    Json(request): Json<KillSwitchRequest>,
+) -> Result<Json<KillSwitchState>, StatusCode> {
+    let kill_switch = state
+        .kill_switch
+        .as_ref()
+        .ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+
+    let result = if request.active {
+        let reason = request
+            .reason
+            .unwrap_or_else(|| "Activated via API".to_string());
+        kill_switch.activate(reason, request.flatten).await
+    } else {
+        kill_switch.deactivate().await
+    };
+    let updated = result.map_err(|e| {
+        error!(error = %e, "Failed to update kill switch");
+        StatusCode::SERVICE_UNAVAILABLE
+    })?;
+
+    warn!(
+        active = updated.active,
+        flatten = updated.flatten_requested,
+        reason = ?updated.reason,
+        "Kill switch {} via API",
+        if updated.active { "activated" } else { "cleared" }
+    );
+    Ok(Json(updated))
+}
+
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+struct SubscribeRequest {
+    /// Symbol to stream, in any supported spelling (e.g., "BTC-PERP", "binance:BTCUSDT")
+    symbol: String,
+}
+
+/// Symbols the websocket-service streams
+#[utoipa::path(
+    get,
+    path = "/api/admin/subscriptions",
+    tag = "Admin",
+    responses(
+        (status = 200, description = "Active subscriptions sorted by symbol", body = Vec<ActiveSubscription>),
+        (status = 503, description = "Subscription store unavailable")
+    )
+)]
+async fn list_subscriptions(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<ActiveSubscription>>, StatusCode> {
+    let subscriptions = state
+        .subscriptions
+        .as_ref()
+        .ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+
+    let active = subscriptions.load_subscriptions().await.map_err(|e| {
+        error!(error = %e, "Failed to load subscriptions");
+        StatusCode::SERVICE_UNAVAILABLE
+    })?;
+    Ok(Json(active))
+}
+
+/// Stream a symbol even if no strategy uses it
+///
+/// The websocket-service subscribes on receiving the command; the symbol is
+/// listed once it did. Requires the `EXECUTION_API_TOKEN` bearer token.
+#[utoipa::path(
+    post,
+    path = "/api/admin/subscriptions",
+    tag = "Admin",
+    request_body = SubscribeRequest,
+    responses(
+        (status = 202, description = "Subscription requested", body = SubscriptionCommand),
+        (status = 400, description = "Unknown symbol"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Execution endpoints disabled or origin not allowed"),
+        (status = 503, description = "Subscription store or instrument registry unavailable")
+    )
+)]
+async fn subscribe_symbol(
+    State(state): State<AppState>,
+    Json(request): Json<SubscribeRequest>,
+) -> Result<(StatusCode, Json<SubscriptionCommand>), StatusCode> {
+    let symbol = validate_strategy_symbol(&state, &request.symbol).await?;
+    send_subscription_command(&state, SubscriptionCommand::Subscribe { symbol }).await
+}
+
+/// Stop streaming a symbol subscribed through the admin API
+///
+/// Symbols used by a strategy or configured in `SYMBOLS` stay subscribed.
+/// Requires the `EXECUTION_API_TOKEN` bearer token.
+#[utoipa::path(
+    delete,
+    path = "/api/admin/subscriptions/{symbol}",
+    tag = "Admin",
+    params(
+        ("symbol" = String, Path, description = "Subscribed symbol")
+    ),
+    responses(
+        (status = 202, description = "Unsubscription requested", body = SubscriptionCommand),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Execution endpoints disabled or origin not allowed"),
+        (status = 503, description = "Subscription store unavailable")
+    )
+)]
+async fn unsubscribe_symbol(
+    State(state): State<AppState>,
+    Path(symbol): Path<String>,
+) -> Result<(StatusCode, Json<SubscriptionCommand>), StatusCode> {
+    // Delisted symbols can still be unsubscribed
+    let symbol = normalize_symbol(&symbol);
+    send_subscription_command(&state, SubscriptionCommand::Unsubscribe { symbol }).await
+}
+
+async fn send_subscription_command(
+    state: &AppState,
+    command: SubscriptionCommand,
+) -> Result<(StatusCode, Json<SubscriptionCommand>), StatusCode> {
+    let subscriptions = state
+        .subscriptions
+        .as_ref()
+        .ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+
+    subscriptions
+        .send_subscription_command(&command)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to send subscription command");
+            StatusCode::SERVICE_UNAVAILABLE
+        })?;
+    info!(command = ?command, "Subscription command sent via API");
+    Ok((StatusCode::ACCEPTED, Json(command)))
+}
+
 #[derive(OpenApi)]
 #[openapi(
     paths(
@@ -357,7 +1359,24 @@ async fn delete_strategy(
         get_strategy,
         create_strategy,
         update_strategy,
-        delete_strategy
+        delete_strategy,
+        list_instruments,
+        export_candles,
+        import_candles,
+        list_paper_pnl,
+        get_paper_pnl,
+        list_positions,
+        list_pnl,
+        list_shadow_orders,
+        submit_algo,
+        list_algos,
+        get_algo,
+        cancel_algo,
+        get_kill_switch,
+        set_kill_switch,
+        list_subscriptions,
+        subscribe_symbol,
+        unsubscribe_symbol
     ),
     components(schemas(
         HealthResponse,
@@ -366,6 +1385,36 @@ async fn delete_strategy(
         UpdateStrategyRequest,
         StrategyConfig,
         StrategyQuery,
+        Instrument,
+        CandleExportQuery,
+        CandleImportQuery,
+        CandleImportResponse,
+        CandleFormat,
+        PaperPnlResponse,
+        PaperStrategyPnlResponse,
+        PaperPosition,
+        PaperFill,
+        FillAction,
+        EquitySnapshot,
+        PositionQuery,
+        PnlQuery,
+        TrackedPosition,
+        crate::execution::AppliedFill,
+        AccountPnl,
+        ShadowOrderQuery,
+        ShadowOrderResponse,
+        AlgoOrder,
+        AlgoProgress,
+        OrderSide,
+        crate::execution::AlgoParams,
+        crate::execution::AlgoState,
+        KillSwitchState,
+        KillSwitchRequest,
+        ActiveSubscription,
+        SubscriptionSource,
+        SubscriptionCommand,
+        SubscribeRequest,
+        crate::models::signal::SignalDirection,
         crate::models::strategy::Rule,
         crate::models::strategy::RuleType,
         crate::models::strategy::Condition,
@@ -374,12 +1423,24 @@ async fn delete_strategy(
         crate::models::strategy::LogicalOperator,
         crate::models::strategy::AggregationConfig,
         crate::models::strategy::AggregationMethod,
-        crate::models::strategy::SignalThresholds
+        crate::models::strategy::SignalThresholds,
+        crate::execution::policy::ExecutionPolicy,
+        crate::execution::policy::OppositeSignalAction,
+        crate::execution::policy::EntryOrderType,
+        crate::execution::policy::BracketGrouping
     )),
     tags(
         (name = "Health", description = "Health check endpoints"),
         (name = "Metrics", description = "Metrics endpoints"),
-        (name = "Strategies", description = "Strategy management endpoints")
+        (name = "Strategies", description = "Strategy management endpoints"),
+        (name = "Instruments", description = "Instrument metadata and symbol normalization"),
+        (name = "Candles", description = "Candle import and export endpoints"),
+        (name = "Paper Trading", description = "Simulated trading results of live signals"),
+        (name = "Positions", description = "Live positions and PnL"),
+        (name = "Shadow Trading", description = "Orders recorded by shadow execution instead of being sent"),
+        (name = "Algos", description = "TWAP, iceberg and chase-limit execution algorithms"),
+        (name = "Risk", description = "Pre-trade risk controls"),
+        (name = "Admin", description = "Market data subscriptions of the websocket-service")
     ),
     info(
         title = "Perptrix API",
@@ -437,7 +1498,51 @@ async fn metrics_middleware(
     response
 }
 
+/// Middleware guarding the state-changing execution endpoints.
+///
+/// They stay disabled until a token is configured, and then require it as a
+/// bearer token. Browsers are only let through from the allowed origins, since
+/// the permissive CORS of the read-only endpoints would otherwise apply.
+async fn require_execution_auth(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(auth) = state.execution_auth.as_ref() else {
+        warn!(path = %request.uri().path(), "Rejected execution request: EXECUTION_API_TOKEN is not set");
+        return (StatusCode::FORBIDDEN, "Execution endpoints are disabled").into_response();
+    };
+
+    if let Some(origin) = request.headers().get(header::ORIGIN) {
+        let allowed = origin.to_str().is_ok_and(|origin| auth.accepts_origin(origin));
+        if !allowed {
+            warn!(path = %request.uri().path(), origin = ?origin, "Rejected execution request from a disallowed origin");
+            return (StatusCode::FORBIDDEN, "Origin not allowed").into_response();
+        }
+    }
+
+    let token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    if !token.is_some_and(|token| auth.accepts_token(token.trim())) {
+        warn!(path = %request.uri().path(), "Rejected execution request without a valid token");
+        return (
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Bearer")],
+            "Missing or invalid bearer token",
+        )
+            .into_response();
+    }
+
+    next.run(request).await
+}
+
 pub fn create_router(state: AppState) -> Router {
+    let execution_auth =
+        || axum::middleware::from_fn_with_state(state.clone(), require_execution_auth);
+
     Router::new()
         .merge(
             SwaggerUi::new("/docs")
@@ -445,11 +1550,52 @@ pub fn create_router(state: AppState) -> Router {
         )
         .route("/health", get(health_check))
         .route("/metrics", get(metrics_handler))
-        .route("/api/strategies", get(list_strategies))
-        .route("/api/strategies", post(create_strategy))
-        .route("/api/strategies/{id}", get(get_strategy))
-        .route("/api/strategies/{id}", put(update_strategy))
-        .route("/api/strategies/{id}", delete(delete_strategy))
+        .route(
+            "/api/strategies",
+            get(list_strategies).merge(post(create_strategy).route_layer(execution_auth())),
+        )
+        .route(
+            "/api/strategies/{id}",
+            get(get_strategy).merge(
+                put(update_strategy)
+                    .delete(delete_strategy)
+                    .route_layer(execution_auth()),
+            ),
+        )
+        .route("/api/instruments", get(list_instruments))
+        .route("/api/candles/export", get(export_candles))
+        .route(
+            "/api/candles/import",
+            post(import_candles)
+                .layer(DefaultBodyLimit::max(CANDLE_IMPORT_BODY_LIMIT))
+                .route_layer(execution_auth()),
+        )
+        .route("/api/paper/pnl", get(list_paper_pnl))
+        .route("/api/paper/pnl/{strategy_id}", get(get_paper_pnl))
+        .route("/api/positions", get(list_positions))
+        .route("/api/pnl", get(list_pnl))
+        .route("/api/shadow/orders", get(list_shadow_orders))
+        .route(
+            "/api/algos",
+            get(list_algos).merge(post(submit_algo).route_layer(execution_auth())),
+        )
+        .route("/api/algos/{id}", get(get_algo))
+        .route(
+            "/api/algos/{id}/cancel",
+            post(cancel_algo).route_layer(execution_auth()),
+        )
+        .route(
+            "/api/risk/kill-switch",
+            get(get_kill_switch).merge(post(set_kill_switch).route_layer(execution_auth())),
+        )
+        .route(
+            "/api/admin/subscriptions",
+            get(list_subscriptions).merge(post(subscribe_symbol).route_layer(execution_auth())),
+        )
+        .route(
+            "/api/admin/subscriptions/{symbol}",
+            delete(unsubscribe_symbol).route_layer(execution_auth()),
+        )
         .layer(
             ServiceBuilder::new()
                 .layer(
@@ -467,6 +1613,28 @@ pub fn create_router(state: AppState) -> Router {
         .with_state(state)
 }
 
+/// Load the instrument registry into `slot`, retrying until it succeeds
+async fn load_instruments(slot: Arc<RwLock<Option<Arc<InstrumentRegistry>>>>) {
+    let mut delay = std::time::Duration::from_secs(5);
+    loop {
+        match InstrumentRegistry::load().await {
+            Ok(registry) => {
+                info!(
+                    instruments = registry.instruments().len(),
+                    "Instrument registry loaded"
+                );
+                *slot.write().await = Some(Arc::new(registry));
+                return;
+            }
+            Err(e) => {
+                warn!(error = %e, retry_in_secs = delay.as_secs(), "Failed to load instrument metadata - strategy, algo and subscription requests are refused until it loads");
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(std::time::Duration::from_secs(300));
+            }
+        }
+    }
+}
+
 pub async fn start_server(port: u16) -> Result<(), Box<dyn std::error::Error>> {
     let metrics = Arc::new(Metrics::new()?);
     let start_time = Arc::new(Instant::now());
@@ -483,11 +1651,45 @@ pub async fn start_server(port: u16) -> Result<(), Box<dyn std::error::Error>> {
         }
     };
     
+    // The kill switch, algos and subscriptions are shared with the other processes through Redis
+    let (kill_switch, algos, subscriptions) = match crate::cache::RedisCache::new().await {
+        Ok(cache) => {
+            info!("Redis connected for API server");
+            let cache = Arc::new(cache);
+            let kill_switch = KillSwitch::new(cache.clone()).with_metrics(metrics.clone());
+            kill_switch.state().await;
+            (
+                Some(Arc::new(kill_switch)),
+                Some(cache.clone() as Arc<dyn AlgoStore>),
+                Some(cache as Arc<dyn SubscriptionStore>),
+            )
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to connect to Redis for API server - kill switch, algo and subscription endpoints will be unavailable");
+            (None, None, None)
+        }
+    };
+
+    // Strategy symbols are validated against the listed instruments
+    let instruments = Arc::new(RwLock::new(None));
+    tokio::spawn(load_instruments(instruments.clone()));
+
+    // State-changing execution endpoints stay disabled without a token
+    let execution_auth = ExecutionAuth::from_env();
+    if execution_auth.is_none() {
+        warn!("EXECUTION_API_TOKEN is not set - strategy changes, candle imports, kill switch changes, algo requests and subscription changes are disabled");
+    }
+
     let state = AppState {
         health: Arc::new(RwLock::new(HealthStatus::default())),
         metrics: metrics.clone(),
         start_time: start_time.clone(),
         database,
+        kill_switch,
+        algos,
+        instruments,
+        subscriptions,
+        execution_auth: execution_auth.map(Arc::new),
     };
     let app = create_router(state);
     let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{}", port)).await?;
@@ -2,7 +2,9 @@
 
 use crate::jobs::context::JobContext;
 use crate::jobs::handlers;
-use crate::jobs::types::{EvaluateSignalJob, FetchCandlesJob, StoreSignalJob};
+use crate::jobs::types::{
+    BackfillCandlesJob, EvaluateSignalJob, ExecuteSignalJob, FetchCandlesJob, StoreSignalJob,
+};
 use apalis::prelude::*;
 use apalis_redis::RedisStorage;
 use std::sync::Arc;
@@ -19,7 +21,7 @@ impl Default for RuntimeConfig {
     fn default() -> Self {
         Self {
             evaluation_interval_seconds: 60,
-            symbols: vec!["BTC-PERP".to_string()],
+            symbols: vec!["BTC".to_string()],
         }
     }
 }
@@ -31,6 +33,8 @@ pub struct SignalRuntime {
     fetch_storage: Arc<RedisStorage<FetchCandlesJob>>,
     eval_storage: Arc<RedisStorage<EvaluateSignalJob>>,
     store_storage: Arc<RedisStorage<StoreSignalJob>>,
+    backfill_storage: Option<Arc<RedisStorage<BackfillCandlesJob>>>,
+    execute_storage: Option<Arc<RedisStorage<ExecuteSignalJob>>>,
     concurrency: usize,
 }
 
@@ -50,10 +54,31 @@ impl SignalRuntime {
             fetch_storage,
             eval_storage,
             store_storage,
+            backfill_storage: None,
+            execute_storage: None,
             concurrency,
         }
     }
 
+    /// Also run a worker for BackfillCandlesJob
+    pub fn with_backfill_storage(
+        mut self,
+        backfill_storage: Arc<RedisStorage<BackfillCandlesJob>>,
+    ) -> Self {
+        self.backfill_storage = Some(backfill_storage);
+        self
+    }
+
+    /// Enqueue ExecuteSignalJob for every stored signal, and run its worker
+    /// when the job context has a policy engine
+    pub fn with_execute_storage(
+        mut self,
+        execute_storage: Arc<RedisStorage<ExecuteSignalJob>>,
+    ) -> Self {
+        self.execute_storage = Some(execute_storage);
+        self
+    }
+
     /// Set custom concurrency (default is number of symbols)
     pub fn with_concurrency(mut self, concurrency: usize) -> Self {
         self.concurrency = concurrency;
@@ -107,9 +132,11 @@ impl SignalRuntime {
         // Worker for StoreSignalJob
         let store_storage_worker = (*self.store_storage).clone();
         let job_context_store = self.job_context.clone();
+        let execute_storage = self.execute_storage.as_ref().map(|s| (**s).clone());
         let store_handle = tokio::spawn(async move {
             let worker = WorkerBuilder::new("store-signal-worker")
                 .data(job_context_store.clone())
+                .data(execute_storage)
                 .backend(store_storage_worker)
                 .build_fn(handlers::handle_store_signal);
 
@@ -118,6 +145,40 @@ impl SignalRuntime {
         });
         handles.push(store_handle);
 
+        // Worker for BackfillCandlesJob (only when backfill is enabled)
+        if let Some(ref backfill_storage) = self.backfill_storage {
+            let backfill_storage_worker = (**backfill_storage).clone();
+            let job_context_backfill = self.job_context.clone();
+            let backfill_handle = tokio::spawn(async move {
+                let worker = WorkerBuilder::new("backfill-candles-worker")
+                    .data(job_context_backfill.clone())
+                    .backend(backfill_storage_worker)
+                    .build_fn(handlers::handle_backfill_candles);
+
+                info!("SignalRuntime: BackfillCandlesJob worker started");
+                worker.run().await;
+            });
+            handles.push(backfill_handle);
+        }
+
+        // Worker for ExecuteSignalJob (only when signal execution is enabled)
+        if let Some(ref execute_storage) = self.execute_storage {
+            if self.job_context.policy_engine.is_some() {
+                let execute_storage_worker = (**execute_storage).clone();
+                let job_context_execute = self.job_context.clone();
+                let execute_handle = tokio::spawn(async move {
+                    let worker = WorkerBuilder::new("execute-signal-worker")
+                        .data(job_context_execute.clone())
+                        .backend(execute_storage_worker)
+                        .build_fn(handlers::handle_execute_signal);
+
+                    info!("SignalRuntime: ExecuteSignalJob worker started");
+                    worker.run().await;
+                });
+                handles.push(execute_handle);
+            }
+        }
+
         info!("SignalRuntime: all workers started");
         Ok(handles)
     }
@@ -1,18 +1,25 @@
-//! Cron-based scheduler for enqueuing signal evaluation jobs
+//! Schedulers enqueuing signal evaluation jobs: on candle close, and on a
+//! cron schedule as the fallback
 
+use crate::cache::RedisCache;
 use crate::jobs::types::FetchCandlesJob;
+use crate::services::candle_events::CandleClosed;
+use crate::services::instruments::normalize_symbol;
 use apalis::prelude::*;
 use apalis_redis::RedisStorage;
 use cron::Schedule;
+use futures_util::StreamExt;
+use std::collections::HashSet;
 use std::str::FromStr;
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 /// Scheduler that periodically enqueues FetchCandlesJob for each symbol
 pub struct JobScheduler {
     storage: Arc<RedisStorage<FetchCandlesJob>>,
-    symbols: Vec<String>,
+    /// Replaced when the strategies change
+    symbols: Arc<RwLock<Vec<String>>>,
     schedule: Schedule,
     handle: Arc<RwLock<Option<tokio::task::JoinHandle<()>>>>,
 }
@@ -62,7 +69,7 @@ impl JobScheduler {
 
         Ok(Self {
             storage,
-            symbols,
+            symbols: Arc::new(RwLock::new(symbols)),
             schedule,
             handle: Arc::new(RwLock::new(None)),
         })
@@ -93,6 +100,7 @@ impl JobScheduler {
                     continue;
                 }
 
+                let symbols = symbols.read().await.clone();
                 info!(
                     symbol_count = symbols.len(),
                     "JobScheduler: cron tick, enqueuing FetchCandlesJob for {} symbols",
@@ -131,6 +139,11 @@ impl JobScheduler {
         Ok(())
     }
 
+    /// Evaluate `symbols` from the next tick on
+    pub async fn set_symbols(&self, symbols: Vec<String>) {
+        *self.symbols.write().await = symbols;
+    }
+
     /// Stop the scheduler
     pub async fn stop(&self) {
         let mut handle = self.handle.write().await;
@@ -146,3 +159,87 @@ impl JobScheduler {
         handle.is_some()
     }
 }
+
+/// Enqueues FetchCandlesJob for a symbol as soon as a bar of the evaluation
+/// interval closes, from the candle closes the websocket-service publishes
+pub struct CandleCloseTrigger {
+    /// Replaced when the strategies change
+    symbols: std::sync::RwLock<HashSet<String>>,
+    interval: String,
+    handle: Arc<RwLock<Option<tokio::task::JoinHandle<()>>>>,
+}
+
+impl CandleCloseTrigger {
+    /// Create a trigger for the strategies' `symbols`, evaluated on `interval` candles
+    pub fn new(symbols: &[String], interval: &str) -> Self {
+        Self {
+            symbols: std::sync::RwLock::new(symbols.iter().map(|s| normalize_symbol(s)).collect()),
+            interval: interval.to_string(),
+            handle: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// Job evaluating the strategies that depend on the closed bar, if any
+    pub fn job_for(&self, event: &CandleClosed) -> Option<FetchCandlesJob> {
+        let symbol = normalize_symbol(&event.symbol);
+        (event.interval == self.interval && self.symbols.read().unwrap().contains(&symbol))
+            .then_some(FetchCandlesJob { symbol })
+    }
+
+    /// Evaluate the strategies of `symbols` from now on
+    pub fn set_symbols(&self, symbols: &[String]) {
+        *self.symbols.write().unwrap() = symbols.iter().map(|s| normalize_symbol(s)).collect();
+    }
+
+    /// Start following candle closes, resubscribing if the subscription drops
+    pub async fn start(
+        self: &Arc<Self>,
+        cache: Arc<RedisCache>,
+        storage: Arc<RedisStorage<FetchCandlesJob>>,
+    ) {
+        let trigger = self.clone();
+        let handle = tokio::spawn(async move {
+            info!(interval = %trigger.interval, "CandleCloseTrigger: following {} candle closes", trigger.interval);
+            loop {
+                match cache.subscribe_candle_closes().await {
+                    Ok(events) => {
+                        let mut events = Box::pin(events);
+                        while let Some(event) = events.next().await {
+                            trigger.enqueue(&storage, &event).await;
+                        }
+                        warn!("CandleCloseTrigger: candle close subscription ended, resubscribing");
+                    }
+                    Err(e) => {
+                        error!(error = %e, "CandleCloseTrigger: failed to subscribe to candle closes");
+                    }
+                }
+                tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+            }
+        });
+        *self.handle.write().await = Some(handle);
+    }
+
+    async fn enqueue(&self, storage: &RedisStorage<FetchCandlesJob>, event: &CandleClosed) {
+        let Some(job) = self.job_for(event) else {
+            return;
+        };
+        let symbol = job.symbol.clone();
+        let mut storage = storage.clone();
+        match storage.push(job).await {
+            Ok(_) => {
+                debug!(symbol = %symbol, timestamp = %event.timestamp, "CandleCloseTrigger: enqueued FetchCandlesJob for {}", symbol);
+            }
+            Err(e) => {
+                error!(symbol = %symbol, error = %e, "CandleCloseTrigger: failed to enqueue FetchCandlesJob for {}", symbol);
+            }
+        }
+    }
+
+    /// Stop following candle closes
+    pub async fn stop(&self) {
+        if let Some(h) = self.handle.write().await.take() {
+            h.abort();
+            info!("CandleCloseTrigger: stopped");
+        }
+    }
+}
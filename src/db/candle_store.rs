@@ -0,0 +1,96 @@
+//! Storage abstraction for candle series
+
+use crate::db::QuestDatabase;
+use crate::models::indicators::Candle;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+
+/// Persistent candle storage used by backfill and import/export
+#[async_trait]
+pub trait CandleStore: Send + Sync {
+    /// Timestamp of the most recent stored candle, if any
+    async fn latest_candle_timestamp(
+        &self,
+        symbol: &str,
+        interval: &str,
+    ) -> Result<Option<DateTime<Utc>>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Store candles in bulk
+    async fn store_candles_batch(
+        &self,
+        symbol: &str,
+        interval: &str,
+        candles: &[Candle],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Candles within an optional inclusive time range, oldest first
+    async fn candles_in_range(
+        &self,
+        symbol: &str,
+        interval: &str,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Candle>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Store the candles whose timestamps are not stored yet, returning how
+    /// many were stored; repairs of partly stored ranges write no bar twice
+    async fn store_missing_candles(
+        &self,
+        symbol: &str,
+        interval: &str,
+        candles: &[Candle],
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        let (Some(first), Some(last)) = (
+            candles.iter().map(|c| c.timestamp).min(),
+            candles.iter().map(|c| c.timestamp).max(),
+        ) else {
+            return Ok(0);
+        };
+        let stored: HashSet<DateTime<Utc>> = self
+            .candles_in_range(symbol, interval, Some(first), Some(last))
+            .await?
+            .into_iter()
+            .map(|c| c.timestamp)
+            .collect();
+        let missing: Vec<Candle> = candles
+            .iter()
+            .filter(|c| !stored.contains(&c.timestamp))
+            .cloned()
+            .collect();
+        if !missing.is_empty() {
+            self.store_candles_batch(symbol, interval, &missing).await?;
+        }
+        Ok(missing.len())
+    }
+}
+
+#[async_trait]
+impl CandleStore for QuestDatabase {
+    async fn latest_candle_timestamp(
+        &self,
+        symbol: &str,
+        interval: &str,
+    ) -> Result<Option<DateTime<Utc>>, Box<dyn std::error::Error + Send + Sync>> {
+        self.get_latest_candle_timestamp(symbol, interval).await
+    }
+
+    async fn store_candles_batch(
+        &self,
+        symbol: &str,
+        interval: &str,
+        candles: &[Candle],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        QuestDatabase::store_candles_batch(self, symbol, interval, candles).await
+    }
+
+    async fn candles_in_range(
+        &self,
+        symbol: &str,
+        interval: &str,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Candle>, Box<dyn std::error::Error + Send + Sync>> {
+        self.get_candles_range(symbol, interval, start, end).await
+    }
+}
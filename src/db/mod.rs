@@ -1,5 +1,13 @@
+pub mod candle_store;
+pub mod order_store;
+pub mod paper_store;
+pub mod position_store;
 pub mod questdb;
 
+pub use candle_store::CandleStore;
+pub use order_store::OrderStore;
+pub use paper_store::PaperStore;
+pub use position_store::PositionStore;
 pub use questdb::QuestDatabase;
 
 // Type alias for backward compatibility
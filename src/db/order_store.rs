@@ -0,0 +1,50 @@
+//! Storage abstraction for the order management system
+
+use crate::db::QuestDatabase;
+use crate::execution::{ManagedOrder, OrderFill};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+/// Persistence used by the order manager
+#[async_trait]
+pub trait OrderStore: Send + Sync {
+    /// Latest state of every order created at or after `since`
+    async fn load_orders(
+        &self,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<ManagedOrder>, Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn save_order(
+        &self,
+        order: &ManagedOrder,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn record_fill(
+        &self,
+        fill: &OrderFill,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+}
+
+#[async_trait]
+impl OrderStore for QuestDatabase {
+    async fn load_orders(
+        &self,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<ManagedOrder>, Box<dyn std::error::Error + Send + Sync>> {
+        self.get_orders_since(since, None).await
+    }
+
+    async fn save_order(
+        &self,
+        order: &ManagedOrder,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.store_order(order).await
+    }
+
+    async fn record_fill(
+        &self,
+        fill: &OrderFill,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.store_order_fill(fill).await
+    }
+}
@@ -0,0 +1,76 @@
+//! Storage abstraction for paper trading
+
+use crate::db::QuestDatabase;
+use crate::models::signal::StoredSignal;
+use crate::paper::{EquitySnapshot, PaperAccount, PaperFill};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+/// Signal source and persistence used by the paper trading service
+#[async_trait]
+pub trait PaperStore: Send + Sync {
+    /// Signals stored at or after `since`, oldest first
+    async fn signals_since(
+        &self,
+        since: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<StoredSignal>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Latest persisted state of every paper account
+    async fn load_accounts(
+        &self,
+    ) -> Result<Vec<PaperAccount>, Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn save_account(
+        &self,
+        account: &PaperAccount,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn record_fill(
+        &self,
+        fill: &PaperFill,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn record_snapshot(
+        &self,
+        snapshot: &EquitySnapshot,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+}
+
+#[async_trait]
+impl PaperStore for QuestDatabase {
+    async fn signals_since(
+        &self,
+        since: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<StoredSignal>, Box<dyn std::error::Error + Send + Sync>> {
+        self.get_signals_since(since, limit).await
+    }
+
+    async fn load_accounts(
+        &self,
+    ) -> Result<Vec<PaperAccount>, Box<dyn std::error::Error + Send + Sync>> {
+        self.get_paper_accounts(None).await
+    }
+
+    async fn save_account(
+        &self,
+        account: &PaperAccount,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.store_paper_account(account).await
+    }
+
+    async fn record_fill(
+        &self,
+        fill: &PaperFill,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.store_paper_fill(fill).await
+    }
+
+    async fn record_snapshot(
+        &self,
+        snapshot: &EquitySnapshot,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.store_paper_equity(snapshot).await
+    }
+}
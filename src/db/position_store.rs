@@ -0,0 +1,37 @@
+//! Storage abstraction for the position tracker
+
+use crate::db::QuestDatabase;
+use crate::execution::TrackedPosition;
+use async_trait::async_trait;
+
+/// Persistence used by the position tracker
+#[async_trait]
+pub trait PositionStore: Send + Sync {
+    /// Latest snapshot of every position of an account
+    async fn load_positions(
+        &self,
+        account: &str,
+    ) -> Result<Vec<TrackedPosition>, Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn save_position(
+        &self,
+        position: &TrackedPosition,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+}
+
+#[async_trait]
+impl PositionStore for QuestDatabase {
+    async fn load_positions(
+        &self,
+        account: &str,
+    ) -> Result<Vec<TrackedPosition>, Box<dyn std::error::Error + Send + Sync>> {
+        self.get_positions(Some(account)).await
+    }
+
+    async fn save_position(
+        &self,
+        position: &TrackedPosition,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.store_position(position).await
+    }
+}
@@ -1,15 +1,52 @@
 //! QuestDB database operations for candles and signals
 
 use crate::config;
+use crate::execution::{ManagedOrder, OrderFill, OrderSide, TrackedPosition};
 use crate::models::indicators::Candle;
-use crate::models::signal::{SignalDirection, SignalOutput};
+use crate::models::signal::{SignalDirection, SignalOutput, SignalReason, StoredSignal};
 use crate::models::strategy::Strategy;
+use crate::paper::{EquitySnapshot, FillAction, PaperAccount, PaperFill};
+use crate::services::instruments::normalize_symbol;
 use chrono::{DateTime, Utc};
 use serde_json;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tokio_postgres::{Client, NoTls};
 
+/// Rows per multi-row candle INSERT (14 bind parameters per row)
+const CANDLE_INSERT_BATCH_SIZE: usize = 500;
+
+/// Columns written for every candle, in bind parameter order
+const CANDLE_INSERT_COLUMNS: &str = "timestamp, symbol, interval, open, high, low, close, volume, open_interest, funding_rate, mark_price, oracle_price, premium, day_notional_volume";
+const CANDLE_INSERT_PARAMS: usize = 14;
+
+/// Columns read back into a `Candle`, see `candle_from_row`
+const CANDLE_SELECT_COLUMNS: &str = "timestamp, open, high, low, close, volume, open_interest, funding_rate, mark_price, oracle_price, premium, day_notional_volume";
+
+/// Asset context columns added after the candles table was first released
+const CANDLE_CONTEXT_COLUMNS: [&str; 4] =
+    ["mark_price", "oracle_price", "premium", "day_notional_volume"];
+
+fn candle_from_row(row: &tokio_postgres::Row) -> Candle {
+    let timestamp_naive: chrono::NaiveDateTime = row.get(0);
+    Candle {
+        open: row.get(1),
+        high: row.get(2),
+        low: row.get(3),
+        close: row.get(4),
+        volume: row.get(5),
+        timestamp: DateTime::from_naive_utc_and_offset(timestamp_naive, Utc),
+        open_interest: row.get(6),
+        funding_rate: row.get(7),
+        mark_price: row.get(8),
+        oracle_price: row.get(9),
+        premium: row.get(10),
+        day_notional_volume: row.get(11),
+        // Only closed bars are persisted
+        is_closed: true,
+    }
+}
+
 pub struct QuestDatabase {
     client: Arc<RwLock<Option<Client>>>,
 }
@@ -60,8 +97,13 @@ impl QuestDatabase {
                     close DOUBLE,
                     volume DOUBLE,
                     open_interest DOUBLE,
-                    funding_rate DOUBLE
-                ) TIMESTAMP(timestamp) PARTITION BY DAY",
+                    funding_rate DOUBLE,
+                    mark_price DOUBLE,
+                    oracle_price DOUBLE,
+                    premium DOUBLE,
+                    day_notional_volume DOUBLE
+                ) TIMESTAMP(timestamp) PARTITION BY DAY WAL
+                DEDUP UPSERT KEYS(timestamp, symbol, interval)",
                 &[],
             )
             .await
@@ -72,6 +114,38 @@ impl QuestDatabase {
                 ))) as Box<dyn std::error::Error + Send + Sync>
             })?;
 
+            // A bar written twice replaces the earlier row; tables created
+            // before deduplication get it enabled here
+            if let Err(e) = c
+                .execute(
+                    "ALTER TABLE candles DEDUP ENABLE UPSERT KEYS(timestamp, symbol, interval)",
+                    &[],
+                )
+                .await
+            {
+                // Only WAL tables can deduplicate: ALTER TABLE candles SET TYPE WAL
+                // and restart QuestDB to convert an older table
+                tracing::warn!(error = %e, "Failed to enable candle deduplication");
+            }
+
+            // Tables created before asset contexts were stored lack these columns
+            for column in CANDLE_CONTEXT_COLUMNS {
+                c.execute(
+                    &format!(
+                        "ALTER TABLE candles ADD COLUMN IF NOT EXISTS {} DOUBLE",
+                        column
+                    ),
+                    &[],
+                )
+                .await
+                .map_err(|e| {
+                    Box::new(std::io::Error::other(format!(
+                        "Failed to add candles column {}: {}",
+                        column, e
+                    ))) as Box<dyn std::error::Error + Send + Sync>
+                })?;
+            }
+
             // Create strategies table
             c.execute(
                 "CREATE TABLE IF NOT EXISTS strategies (
@@ -115,6 +189,141 @@ impl QuestDatabase {
                     e
                 ))) as Box<dyn std::error::Error + Send + Sync>
             })?;
+
+            // Paper trading: account state is appended on change, latest row wins
+            c.execute(
+                "CREATE TABLE IF NOT EXISTS paper_accounts (
+                    timestamp TIMESTAMP,
+                    strategy_id LONG,
+                    symbol SYMBOL,
+                    equity DOUBLE,
+                    state_json STRING
+                ) TIMESTAMP(timestamp) PARTITION BY DAY",
+                &[],
+            )
+            .await
+            .map_err(|e| {
+                Box::new(std::io::Error::other(format!(
+                    "Failed to create paper_accounts table: {}",
+                    e
+                ))) as Box<dyn std::error::Error + Send + Sync>
+            })?;
+
+            c.execute(
+                "CREATE TABLE IF NOT EXISTS paper_fills (
+                    timestamp TIMESTAMP,
+                    strategy_id LONG,
+                    symbol SYMBOL,
+                    direction SYMBOL,
+                    action SYMBOL,
+                    price DOUBLE,
+                    size DOUBLE,
+                    fee DOUBLE,
+                    realized_pnl DOUBLE
+                ) TIMESTAMP(timestamp) PARTITION BY DAY",
+                &[],
+            )
+            .await
+            .map_err(|e| {
+                Box::new(std::io::Error::other(format!(
+                    "Failed to create paper_fills table: {}",
+                    e
+                ))) as Box<dyn std::error::Error + Send + Sync>
+            })?;
+
+            c.execute(
+                "CREATE TABLE IF NOT EXISTS paper_equity (
+                    timestamp TIMESTAMP,
+                    strategy_id LONG,
+                    symbol SYMBOL,
+                    equity DOUBLE,
+                    realized_pnl DOUBLE,
+                    unrealized_pnl DOUBLE,
+                    fees_paid DOUBLE,
+                    funding_paid DOUBLE,
+                    position_size DOUBLE
+                ) TIMESTAMP(timestamp) PARTITION BY DAY",
+                &[],
+            )
+            .await
+            .map_err(|e| {
+                Box::new(std::io::Error::other(format!(
+                    "Failed to create paper_equity table: {}",
+                    e
+                ))) as Box<dyn std::error::Error + Send + Sync>
+            })?;
+
+            // Orders: state is appended on every transition, latest row per client order id wins
+            c.execute(
+                "CREATE TABLE IF NOT EXISTS orders (
+                    timestamp TIMESTAMP,
+                    created_at TIMESTAMP,
+                    client_order_id STRING,
+                    exchange SYMBOL,
+                    symbol SYMBOL,
+                    strategy_id LONG,
+                    signal_id LONG,
+                    state SYMBOL,
+                    state_json STRING
+                ) TIMESTAMP(timestamp) PARTITION BY DAY",
+                &[],
+            )
+            .await
+            .map_err(|e| {
+                Box::new(std::io::Error::other(format!(
+                    "Failed to create orders table: {}",
+                    e
+                ))) as Box<dyn std::error::Error + Send + Sync>
+            })?;
+
+            c.execute(
+                "CREATE TABLE IF NOT EXISTS order_fills (
+                    timestamp TIMESTAMP,
+                    client_order_id STRING,
+                    exchange_order_id LONG,
+                    trade_id LONG,
+                    strategy_id LONG,
+                    signal_id LONG,
+                    symbol SYMBOL,
+                    side SYMBOL,
+                    price DOUBLE,
+                    size DOUBLE,
+                    fee DOUBLE
+                ) TIMESTAMP(timestamp) PARTITION BY DAY",
+                &[],
+            )
+            .await
+            .map_err(|e| {
+                Box::new(std::io::Error::other(format!(
+                    "Failed to create order_fills table: {}",
+                    e
+                ))) as Box<dyn std::error::Error + Send + Sync>
+            })?;
+
+            // Positions: a snapshot is appended on every change, latest row per account and symbol wins
+            c.execute(
+                "CREATE TABLE IF NOT EXISTS positions (
+                    timestamp TIMESTAMP,
+                    account SYMBOL,
+                    symbol SYMBOL,
+                    size DOUBLE,
+                    entry_price DOUBLE,
+                    mark_price DOUBLE,
+                    realized_pnl DOUBLE,
+                    unrealized_pnl DOUBLE,
+                    fees_paid DOUBLE,
+                    funding_paid DOUBLE,
+                    state_json STRING
+                ) TIMESTAMP(timestamp) PARTITION BY DAY",
+                &[],
+            )
+            .await
+            .map_err(|e| {
+                Box::new(std::io::Error::other(format!(
+                    "Failed to create positions table: {}",
+                    e
+                ))) as Box<dyn std::error::Error + Send + Sync>
+            })?;
         }
 
         Ok(())
@@ -133,8 +342,11 @@ impl QuestDatabase {
             let timestamp_naive = candle.timestamp.naive_utc();
 
             c.execute(
-                "INSERT INTO candles (timestamp, symbol, interval, open, high, low, close, volume, open_interest, funding_rate)
-                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+                &format!(
+                    "INSERT INTO candles ({})
+                     VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)",
+                    CANDLE_INSERT_COLUMNS
+                ),
                 &[
                     &timestamp_naive,
                     &symbol,
@@ -144,8 +356,12 @@ impl QuestDatabase {
                     &candle.low,
                     &candle.close,
                     &candle.volume,
-                    &candle.open_interest.unwrap_or(0.0),
-                    &candle.funding_rate.unwrap_or(0.0),
+                    &candle.open_interest,
+                    &candle.funding_rate,
+                    &candle.mark_price,
+                    &candle.oracle_price,
+                    &candle.premium,
+                    &candle.day_notional_volume,
                 ],
             )
             .await
@@ -158,23 +374,89 @@ impl QuestDatabase {
         Ok(())
     }
 
-    /// Store multiple candles in a batch
+    /// Store multiple candles in a batch using multi-row inserts
     pub async fn store_candles_batch(
         &self,
         symbol: &str,
         interval: &str,
         candles: &[Candle],
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // For now, just store candles one by one
-        // TODO: Optimize with batch insert when QuestDB supports it better
-        for candle in candles {
-            if let Err(e) = self.store_candle(symbol, interval, candle).await {
-                tracing::warn!(symbol = %symbol, interval = %interval, error = %e, "Failed to store candle in batch");
+        let client = self.client.read().await;
+        if let Some(ref c) = *client {
+            for chunk in candles.chunks(CANDLE_INSERT_BATCH_SIZE) {
+                let timestamps: Vec<_> = chunk
+                    .iter()
+                    .map(|candle| candle.timestamp.naive_utc())
+                    .collect();
+
+                let mut query = format!("INSERT INTO candles ({}) VALUES ", CANDLE_INSERT_COLUMNS);
+                let mut params: Vec<&(dyn tokio_postgres::types::ToSql + Sync)> =
+                    Vec::with_capacity(chunk.len() * CANDLE_INSERT_PARAMS);
+                for (i, (candle, timestamp)) in chunk.iter().zip(&timestamps).enumerate() {
+                    if i > 0 {
+                        query.push_str(", ");
+                    }
+                    let base = i * CANDLE_INSERT_PARAMS;
+                    let placeholders: Vec<String> = (1..=CANDLE_INSERT_PARAMS)
+                        .map(|n| format!("${}", base + n))
+                        .collect();
+                    query.push_str(&format!("({})", placeholders.join(", ")));
+                    params.push(timestamp);
+                    params.push(&symbol);
+                    params.push(&interval);
+                    params.push(&candle.open);
+                    params.push(&candle.high);
+                    params.push(&candle.low);
+                    params.push(&candle.close);
+                    params.push(&candle.volume);
+                    params.push(&candle.open_interest);
+                    params.push(&candle.funding_rate);
+                    params.push(&candle.mark_price);
+                    params.push(&candle.oracle_price);
+                    params.push(&candle.premium);
+                    params.push(&candle.day_notional_volume);
+                }
+
+                c.execute(query.as_str(), &params).await.map_err(|e| {
+                    Box::new(std::io::Error::other(format!(
+                        "Failed to store candle batch: {}",
+                        e
+                    ))) as Box<dyn std::error::Error + Send + Sync>
+                })?;
             }
         }
+
         Ok(())
     }
 
+    /// Get the timestamp of the most recent stored candle for a symbol and interval
+    pub async fn get_latest_candle_timestamp(
+        &self,
+        symbol: &str,
+        interval: &str,
+    ) -> Result<Option<DateTime<Utc>>, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.client.read().await;
+        if let Some(ref c) = *client {
+            let row = c
+                .query_opt(
+                    "SELECT max(timestamp) FROM candles WHERE symbol = $1 AND interval = $2",
+                    &[&symbol, &interval],
+                )
+                .await
+                .map_err(|e| {
+                    Box::new(std::io::Error::other(format!(
+                        "Failed to query latest candle timestamp: {}",
+                        e
+                    ))) as Box<dyn std::error::Error + Send + Sync>
+                })?;
+
+            let timestamp: Option<chrono::NaiveDateTime> = row.and_then(|r| r.get(0));
+            Ok(timestamp.map(|ts| DateTime::from_naive_utc_and_offset(ts, Utc)))
+        } else {
+            Ok(None)
+        }
+    }
+
     /// Get candles for a symbol and interval, ordered by timestamp
     pub async fn get_candles(
         &self,
@@ -186,19 +468,21 @@ impl QuestDatabase {
         if let Some(ref c) = *client {
             let query = if let Some(limit) = limit {
                 format!(
-                    "SELECT timestamp, open, high, low, close, volume, open_interest, funding_rate
+                    "SELECT {}
                      FROM candles
                      WHERE symbol = $1 AND interval = $2
                      ORDER BY timestamp DESC
                      LIMIT {}",
-                    limit
+                    CANDLE_SELECT_COLUMNS, limit
                 )
             } else {
-                "SELECT timestamp, open, high, low, close, volume, open_interest, funding_rate
-                 FROM candles
-                 WHERE symbol = $1 AND interval = $2
-                 ORDER BY timestamp DESC"
-                    .to_string()
+                format!(
+                    "SELECT {}
+                     FROM candles
+                     WHERE symbol = $1 AND interval = $2
+                     ORDER BY timestamp DESC",
+                    CANDLE_SELECT_COLUMNS
+                )
             };
 
             let rows = c.query(&query, &[&symbol, &interval]).await.map_err(|e| {
@@ -208,28 +492,7 @@ impl QuestDatabase {
                 ))) as Box<dyn std::error::Error + Send + Sync>
             })?;
 
-            let mut candles = Vec::new();
-            for row in rows {
-                let timestamp_naive: chrono::NaiveDateTime = row.get(0);
-                let timestamp = DateTime::from_naive_utc_and_offset(timestamp_naive, Utc);
-                let open: f64 = row.get(1);
-                let high: f64 = row.get(2);
-                let low: f64 = row.get(3);
-                let close: f64 = row.get(4);
-                let volume: f64 = row.get(5);
-                let open_interest: Option<f64> = row.get(6);
-                let funding_rate: Option<f64> = row.get(7);
-
-                let mut candle = Candle::new(open, high, low, close, volume, timestamp);
-                if let Some(oi) = open_interest {
-                    candle = candle.with_open_interest(oi);
-                }
-                if let Some(fr) = funding_rate {
-                    candle = candle.with_funding_rate(fr);
-                }
-
-                candles.push(candle);
-            }
+            let mut candles: Vec<Candle> = rows.iter().map(candle_from_row).collect();
 
             // Reverse to get oldest first
             candles.reverse();
@@ -240,6 +503,51 @@ impl QuestDatabase {
         }
     }
 
+    /// Get candles for a symbol and interval within an optional inclusive time range, oldest first
+    pub async fn get_candles_range(
+        &self,
+        symbol: &str,
+        interval: &str,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Candle>, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.client.read().await;
+        if let Some(ref c) = *client {
+            let start_naive = start.map(|ts| ts.naive_utc());
+            let end_naive = end.map(|ts| ts.naive_utc());
+            let mut query = format!(
+                "SELECT {}
+                 FROM candles
+                 WHERE symbol = $1 AND interval = $2",
+                CANDLE_SELECT_COLUMNS
+            );
+            let mut params: Vec<&(dyn tokio_postgres::types::ToSql + Sync)> =
+                vec![&symbol, &interval];
+            if let Some(ref ts) = start_naive {
+                params.push(ts);
+                query.push_str(&format!(" AND timestamp >= ${}", params.len()));
+            }
+            if let Some(ref ts) = end_naive {
+                params.push(ts);
+                query.push_str(&format!(" AND timestamp <= ${}", params.len()));
+            }
+            query.push_str(" ORDER BY timestamp ASC");
+
+            let rows = c.query(query.as_str(), &params).await.map_err(|e| {
+                Box::new(std::io::Error::other(format!(
+                    "Failed to query candles: {}",
+                    e
+                ))) as Box<dyn std::error::Error + Send + Sync>
+            })?;
+
+            let candles = rows.iter().map(candle_from_row).collect();
+
+            Ok(candles)
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
     /// Store a signal in QuestDB
     pub async fn store_signal(
         &self,
@@ -248,11 +556,7 @@ impl QuestDatabase {
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let client = self.client.read().await;
         if let Some(ref c) = *client {
-            let direction_str = match signal.direction {
-                SignalDirection::Long => "Long",
-                SignalDirection::Short => "Short",
-                SignalDirection::Neutral => "Neutral",
-            };
+            let direction_str = direction_name(signal.direction);
 
             let reasons_json = serde_json::to_string(&signal.reasons).map_err(|e| {
                 Box::new(std::io::Error::new(
@@ -343,44 +647,519 @@ impl QuestDatabase {
                 ))) as Box<dyn std::error::Error + Send + Sync>
             })?;
 
-            let mut signals = Vec::new();
+            rows.iter().map(signal_from_row).collect()
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    /// Get signals stored at or after `since` with their strategy, oldest first
+    pub async fn get_signals_since(
+        &self,
+        since: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<StoredSignal>, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.client.read().await;
+        if let Some(ref c) = *client {
+            let query = format!(
+                "SELECT symbol, direction, confidence, sl_pct, tp_pct, price, timestamp, reasons_json, strategy_id
+                 FROM signals
+                 WHERE timestamp >= $1
+                 ORDER BY timestamp ASC
+                 LIMIT {}",
+                limit
+            );
+            let rows = c
+                .query(&query, &[&since.naive_utc()])
+                .await
+                .map_err(|e| {
+                    Box::new(std::io::Error::other(format!(
+                        "Failed to query signals: {}",
+                        e
+                    ))) as Box<dyn std::error::Error + Send + Sync>
+                })?;
+
+            rows.iter()
+                .map(|row| {
+                    Ok(StoredSignal {
+                        strategy_id: row.get(8),
+                        signal: signal_from_row(row)?,
+                    })
+                })
+                .collect()
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    /// Append the current state of a paper account
+    pub async fn store_paper_account(
+        &self,
+        account: &PaperAccount,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.client.read().await;
+        if let Some(ref c) = *client {
+            let state_json = serde_json::to_string(account).map_err(|e| {
+                Box::new(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("Failed to serialize paper account: {}", e),
+                )) as Box<dyn std::error::Error + Send + Sync>
+            })?;
+
+            c.execute(
+                "INSERT INTO paper_accounts (timestamp, strategy_id, symbol, equity, state_json)
+                 VALUES ($1, $2, $3, $4, $5)",
+                &[
+                    &account.updated_at.naive_utc(),
+                    &account.strategy_id,
+                    &account.symbol,
+                    &account.equity(),
+                    &state_json,
+                ],
+            )
+            .await
+            .map_err(|e| {
+                Box::new(std::io::Error::other(format!(
+                    "Failed to store paper account: {}",
+                    e
+                ))) as Box<dyn std::error::Error + Send + Sync>
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Latest state of every paper account, or of a single strategy's account
+    pub async fn get_paper_accounts(
+        &self,
+        strategy_id: Option<i64>,
+    ) -> Result<Vec<PaperAccount>, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.client.read().await;
+        if let Some(ref c) = *client {
+            let rows = if let Some(id) = strategy_id {
+                c.query(
+                    "SELECT state_json FROM paper_accounts WHERE strategy_id = $1 ORDER BY timestamp DESC LIMIT 1",
+                    &[&id],
+                )
+                .await
+            } else {
+                c.query(
+                    "SELECT state_json FROM paper_accounts ORDER BY timestamp DESC",
+                    &[],
+                )
+                .await
+            }
+            .map_err(|e| {
+                Box::new(std::io::Error::other(format!(
+                    "Failed to query paper accounts: {}",
+                    e
+                ))) as Box<dyn std::error::Error + Send + Sync>
+            })?;
+
+            let mut accounts: Vec<PaperAccount> = Vec::new();
             for row in rows {
-                let symbol: String = row.get(0);
-                let direction_str: String = row.get(1);
-                let direction = match direction_str.as_str() {
-                    "Long" => SignalDirection::Long,
-                    "Short" => SignalDirection::Short,
-                    _ => SignalDirection::Neutral,
-                };
-                let confidence: f64 = row.get(2);
-                let sl_pct: f64 = row.get(3);
-                let tp_pct: f64 = row.get(4);
-                let price: f64 = row.get(5);
-                let timestamp_naive: chrono::NaiveDateTime = row.get(6);
-                let timestamp = DateTime::from_naive_utc_and_offset(timestamp_naive, Utc);
-                let reasons_json: String = row.get(7);
-
-                let reasons: Vec<crate::models::signal::SignalReason> =
-                    serde_json::from_str(&reasons_json).map_err(|e| {
-                        Box::new(std::io::Error::new(
-                            std::io::ErrorKind::InvalidData,
-                            format!("Failed to deserialize reasons: {}", e),
-                        )) as Box<dyn std::error::Error + Send + Sync>
-                    })?;
+                let state_json: String = row.get(0);
+                let account: PaperAccount = serde_json::from_str(&state_json).map_err(|e| {
+                    Box::new(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("Failed to deserialize paper account: {}", e),
+                    )) as Box<dyn std::error::Error + Send + Sync>
+                })?;
+                // Rows are newest first, so the first row per strategy is its current state
+                if !accounts.iter().any(|a| a.strategy_id == account.strategy_id) {
+                    accounts.push(account);
+                }
+            }
+            accounts.sort_by_key(|a| a.strategy_id);
+            Ok(accounts)
+        } else {
+            Ok(Vec::new())
+        }
+    }
 
-                signals.push(SignalOutput {
-                    symbol,
-                    direction,
-                    confidence,
-                    recommended_sl_pct: sl_pct,
-                    recommended_tp_pct: tp_pct,
-                    price,
-                    timestamp,
-                    reasons,
-                });
+    /// Store a simulated fill
+    pub async fn store_paper_fill(
+        &self,
+        fill: &PaperFill,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.client.read().await;
+        if let Some(ref c) = *client {
+            c.execute(
+                "INSERT INTO paper_fills (timestamp, strategy_id, symbol, direction, action, price, size, fee, realized_pnl)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+                &[
+                    &fill.timestamp.naive_utc(),
+                    &fill.strategy_id,
+                    &fill.symbol,
+                    &direction_name(fill.direction),
+                    &fill.action.as_str(),
+                    &fill.price,
+                    &fill.size,
+                    &fill.fee,
+                    &fill.realized_pnl,
+                ],
+            )
+            .await
+            .map_err(|e| {
+                Box::new(std::io::Error::other(format!(
+                    "Failed to store paper fill: {}",
+                    e
+                ))) as Box<dyn std::error::Error + Send + Sync>
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Most recent fills of a paper account, newest first
+    pub async fn get_paper_fills(
+        &self,
+        strategy_id: i64,
+        limit: usize,
+    ) -> Result<Vec<PaperFill>, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.client.read().await;
+        if let Some(ref c) = *client {
+            let query = format!(
+                "SELECT timestamp, strategy_id, symbol, direction, action, price, size, fee, realized_pnl
+                 FROM paper_fills
+                 WHERE strategy_id = $1
+                 ORDER BY timestamp DESC
+                 LIMIT {}",
+                limit
+            );
+            let rows = c.query(&query, &[&strategy_id]).await.map_err(|e| {
+                Box::new(std::io::Error::other(format!(
+                    "Failed to query paper fills: {}",
+                    e
+                ))) as Box<dyn std::error::Error + Send + Sync>
+            })?;
+
+            Ok(rows
+                .iter()
+                .map(|row| {
+                    let timestamp: chrono::NaiveDateTime = row.get(0);
+                    let direction: String = row.get(3);
+                    let action: String = row.get(4);
+                    PaperFill {
+                        timestamp: DateTime::from_naive_utc_and_offset(timestamp, Utc),
+                        strategy_id: row.get(1),
+                        symbol: row.get(2),
+                        direction: parse_direction(&direction),
+                        action: if action == "Open" {
+                            FillAction::Open
+                        } else {
+                            FillAction::Close
+                        },
+                        price: row.get(5),
+                        size: row.get(6),
+                        fee: row.get(7),
+                        realized_pnl: row.get(8),
+                    }
+                })
+                .collect())
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    /// Store a paper account equity snapshot
+    pub async fn store_paper_equity(
+        &self,
+        snapshot: &EquitySnapshot,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.client.read().await;
+        if let Some(ref c) = *client {
+            c.execute(
+                "INSERT INTO paper_equity (timestamp, strategy_id, symbol, equity, realized_pnl, unrealized_pnl, fees_paid, funding_paid, position_size)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+                &[
+                    &snapshot.timestamp.naive_utc(),
+                    &snapshot.strategy_id,
+                    &snapshot.symbol,
+                    &snapshot.equity,
+                    &snapshot.realized_pnl,
+                    &snapshot.unrealized_pnl,
+                    &snapshot.fees_paid,
+                    &snapshot.funding_paid,
+                    &snapshot.position_size,
+                ],
+            )
+            .await
+            .map_err(|e| {
+                Box::new(std::io::Error::other(format!(
+                    "Failed to store paper equity snapshot: {}",
+                    e
+                ))) as Box<dyn std::error::Error + Send + Sync>
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Most recent equity snapshots of a paper account, oldest first
+    pub async fn get_paper_equity(
+        &self,
+        strategy_id: i64,
+        limit: usize,
+    ) -> Result<Vec<EquitySnapshot>, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.client.read().await;
+        if let Some(ref c) = *client {
+            let query = format!(
+                "SELECT timestamp, strategy_id, symbol, equity, realized_pnl, unrealized_pnl, fees_paid, funding_paid, position_size
+                 FROM paper_equity
+                 WHERE strategy_id = $1
+                 ORDER BY timestamp DESC
+                 LIMIT {}",
+                limit
+            );
+            let rows = c.query(&query, &[&strategy_id]).await.map_err(|e| {
+                Box::new(std::io::Error::other(format!(
+                    "Failed to query paper equity: {}",
+                    e
+                ))) as Box<dyn std::error::Error + Send + Sync>
+            })?;
+
+            let mut snapshots: Vec<EquitySnapshot> = rows
+                .iter()
+                .map(|row| {
+                    let timestamp: chrono::NaiveDateTime = row.get(0);
+                    EquitySnapshot {
+                        timestamp: DateTime::from_naive_utc_and_offset(timestamp, Utc),
+                        strategy_id: row.get(1),
+                        symbol: row.get(2),
+                        equity: row.get(3),
+                        realized_pnl: row.get(4),
+                        unrealized_pnl: row.get(5),
+                        fees_paid: row.get(6),
+                        funding_paid: row.get(7),
+                        position_size: row.get(8),
+                    }
+                })
+                .collect();
+            snapshots.reverse();
+            Ok(snapshots)
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    /// Append the current state of a managed order
+    pub async fn store_order(
+        &self,
+        order: &ManagedOrder,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.client.read().await;
+        if let Some(ref c) = *client {
+            let state_json = serde_json::to_string(order).map_err(|e| {
+                Box::new(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("Failed to serialize order: {}", e),
+                )) as Box<dyn std::error::Error + Send + Sync>
+            })?;
+
+            c.execute(
+                "INSERT INTO orders (timestamp, created_at, client_order_id, exchange, symbol, strategy_id, signal_id, state, state_json)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+                &[
+                    &order.updated_at.naive_utc(),
+                    &order.created_at.naive_utc(),
+                    &order.client_order_id,
+                    &order.exchange,
+                    &order.request.symbol,
+                    &order.origin.strategy_id,
+                    &order.origin.signal_id,
+                    &order.state.as_str(),
+                    &state_json,
+                ],
+            )
+            .await
+            .map_err(|e| {
+                Box::new(std::io::Error::other(format!("Failed to store order: {}", e)))
+                    as Box<dyn std::error::Error + Send + Sync>
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Latest state of every order created at or after `since`, optionally
+    /// only those placed on `exchange`
+    pub async fn get_orders_since(
+        &self,
+        since: DateTime<Utc>,
+        exchange: Option<&str>,
+    ) -> Result<Vec<ManagedOrder>, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.client.read().await;
+        if let Some(ref c) = *client {
+            let rows = if let Some(exchange) = exchange {
+                c.query(
+                    "SELECT state_json FROM orders WHERE created_at >= $1 AND exchange = $2 ORDER BY timestamp DESC",
+                    &[&since.naive_utc(), &exchange],
+                )
+                .await
+            } else {
+                c.query(
+                    "SELECT state_json FROM orders WHERE created_at >= $1 ORDER BY timestamp DESC",
+                    &[&since.naive_utc()],
+                )
+                .await
             }
+            .map_err(|e| {
+                Box::new(std::io::Error::other(format!("Failed to query orders: {}", e)))
+                    as Box<dyn std::error::Error + Send + Sync>
+            })?;
+
+            let mut seen = std::collections::HashSet::new();
+            let mut orders: Vec<ManagedOrder> = Vec::new();
+            for row in rows {
+                let state_json: String = row.get(0);
+                let order: ManagedOrder = serde_json::from_str(&state_json).map_err(|e| {
+                    Box::new(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("Failed to deserialize order: {}", e),
+                    )) as Box<dyn std::error::Error + Send + Sync>
+                })?;
+                // Rows are newest first, so the first row per order is its current state
+                if seen.insert(order.client_order_id.clone()) {
+                    orders.push(order);
+                }
+            }
+            orders.sort_by_key(|o| o.created_at);
+            Ok(orders)
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    /// Store an exchange fill of a managed order
+    pub async fn store_order_fill(
+        &self,
+        fill: &OrderFill,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.client.read().await;
+        if let Some(ref c) = *client {
+            let side = match fill.fill.side {
+                OrderSide::Buy => "Buy",
+                OrderSide::Sell => "Sell",
+            };
+            c.execute(
+                "INSERT INTO order_fills (timestamp, client_order_id, exchange_order_id, trade_id, strategy_id, signal_id, symbol, side, price, size, fee)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
+                &[
+                    &fill.fill.timestamp.naive_utc(),
+                    &fill.client_order_id,
+                    &(fill.fill.order_id as i64),
+                    &(fill.fill.trade_id as i64),
+                    &fill.origin.strategy_id,
+                    &fill.origin.signal_id,
+                    &fill.fill.symbol,
+                    &side,
+                    &fill.fill.price,
+                    &fill.fill.size,
+                    &fill.fill.fee,
+                ],
+            )
+            .await
+            .map_err(|e| {
+                Box::new(std::io::Error::other(format!(
+                    "Failed to store order fill: {}",
+                    e
+                ))) as Box<dyn std::error::Error + Send + Sync>
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Append a snapshot of a tracked position
+    pub async fn store_position(
+        &self,
+        position: &TrackedPosition,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.client.read().await;
+        if let Some(ref c) = *client {
+            let state_json = serde_json::to_string(position).map_err(|e| {
+                Box::new(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("Failed to serialize position: {}", e),
+                )) as Box<dyn std::error::Error + Send + Sync>
+            })?;
+
+            c.execute(
+                "INSERT INTO positions (timestamp, account, symbol, size, entry_price, mark_price, realized_pnl, unrealized_pnl, fees_paid, funding_paid, state_json)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
+                &[
+                    &position.updated_at.naive_utc(),
+                    &position.account,
+                    &position.symbol,
+                    &position.size,
+                    &position.entry_price,
+                    &position.mark_price,
+                    &position.realized_pnl,
+                    &position.unrealized_pnl,
+                    &position.fees_paid,
+                    &position.funding_paid,
+                    &state_json,
+                ],
+            )
+            .await
+            .map_err(|e| {
+                Box::new(std::io::Error::other(format!(
+                    "Failed to store position: {}",
+                    e
+                ))) as Box<dyn std::error::Error + Send + Sync>
+            })?;
+        }
+
+        Ok(())
+    }
 
-            Ok(signals)
+    /// Latest snapshot of every position, or of a single account's positions
+    pub async fn get_positions(
+        &self,
+        account: Option<&str>,
+    ) -> Result<Vec<TrackedPosition>, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.client.read().await;
+        if let Some(ref c) = *client {
+            let rows = if let Some(account) = account {
+                c.query(
+                    "SELECT state_json FROM positions WHERE account = $1 ORDER BY timestamp DESC",
+                    &[&account],
+                )
+                .await
+            } else {
+                c.query(
+                    "SELECT state_json FROM positions ORDER BY timestamp DESC",
+                    &[],
+                )
+                .await
+            }
+            .map_err(|e| {
+                Box::new(std::io::Error::other(format!(
+                    "Failed to query positions: {}",
+                    e
+                ))) as Box<dyn std::error::Error + Send + Sync>
+            })?;
+
+            let mut positions: Vec<TrackedPosition> = Vec::new();
+            for row in rows {
+                let state_json: String = row.get(0);
+                let position: TrackedPosition = serde_json::from_str(&state_json).map_err(|e| {
+                    Box::new(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("Failed to deserialize position: {}", e),
+                    )) as Box<dyn std::error::Error + Send + Sync>
+                })?;
+                // Rows are newest first, so the first row per account and symbol is current
+                if !positions
+                    .iter()
+                    .any(|p| p.account == position.account && p.symbol == position.symbol)
+                {
+                    positions.push(position);
+                }
+            }
+            positions.sort_by(|a, b| (&a.account, &a.symbol).cmp(&(&b.account, &b.symbol)));
+            Ok(positions)
         } else {
             Ok(Vec::new())
         }
@@ -472,6 +1251,7 @@ impl QuestDatabase {
             let id: i64 = row.get(0);
             let name: String = row.get(1);
             let symbol: String = row.get(2);
+            let symbol = normalize_symbol(&symbol);
             let created_at_naive: chrono::NaiveDateTime = row.get(3);
             let updated_at_naive: chrono::NaiveDateTime = row.get(4);
             let config_json: String = row.get(5);
@@ -503,41 +1283,41 @@ impl QuestDatabase {
         }
     }
 
-    /// Get all strategies, optionally filtered by symbol
+    /// Get all strategies, optionally filtered by symbol.
+    ///
+    /// Symbols are compared and returned in canonical form, so strategies
+    /// stored under another spelling (e.g. `BTC-PERP`) still match `BTC`.
     pub async fn get_strategies(
         &self,
         symbol: Option<&str>,
     ) -> Result<Vec<Strategy>, Box<dyn std::error::Error + Send + Sync>> {
         let client = self.client.read().await;
         if let Some(ref c) = *client {
-            let query = if let Some(_sym) = symbol {
-                "SELECT id, name, symbol, created_at, updated_at, config_json
-                 FROM strategies
-                 WHERE symbol = $1
-                 ORDER BY created_at DESC"
-            } else {
-                "SELECT id, name, symbol, created_at, updated_at, config_json
-                 FROM strategies
-                 ORDER BY created_at DESC"
-            };
-
-            let rows = if let Some(sym) = symbol {
-                c.query(query, &[&sym]).await
-            } else {
-                c.query(query, &[]).await
-            }
-            .map_err(|e| {
-                Box::new(std::io::Error::other(format!(
-                    "Failed to query strategies: {}",
-                    e
-                ))) as Box<dyn std::error::Error + Send + Sync>
-            })?;
+            let symbol = symbol.map(normalize_symbol);
+            let rows = c
+                .query(
+                    "SELECT id, name, symbol, created_at, updated_at, config_json
+                     FROM strategies
+                     ORDER BY created_at DESC",
+                    &[],
+                )
+                .await
+                .map_err(|e| {
+                    Box::new(std::io::Error::other(format!(
+                        "Failed to query strategies: {}",
+                        e
+                    ))) as Box<dyn std::error::Error + Send + Sync>
+                })?;
 
             let mut strategies = Vec::new();
             for row in rows {
+                let strategy_symbol: String = row.get(2);
+                let strategy_symbol = normalize_symbol(&strategy_symbol);
+                if symbol.as_ref().is_some_and(|s| *s != strategy_symbol) {
+                    continue;
+                }
                 let id: i64 = row.get(0);
                 let name: String = row.get(1);
-                let symbol: String = row.get(2);
                 let created_at_naive: chrono::NaiveDateTime = row.get(3);
                 let updated_at_naive: chrono::NaiveDateTime = row.get(4);
                 let config_json: String = row.get(5);
@@ -556,7 +1336,7 @@ impl QuestDatabase {
                 strategies.push(Strategy {
                     id: Some(id),
                     name,
-                    symbol,
+                    symbol: strategy_symbol,
                     config,
                     created_at,
                     updated_at,
@@ -656,3 +1436,47 @@ impl QuestDatabase {
         }
     }
 }
+
+fn direction_name(direction: SignalDirection) -> &'static str {
+    match direction {
+        SignalDirection::Long => "Long",
+        SignalDirection::Short => "Short",
+        SignalDirection::Neutral => "Neutral",
+    }
+}
+
+fn parse_direction(value: &str) -> SignalDirection {
+    match value {
+        "Long" => SignalDirection::Long,
+        "Short" => SignalDirection::Short,
+        _ => SignalDirection::Neutral,
+    }
+}
+
+/// Decode a signals row selected as
+/// `symbol, direction, confidence, sl_pct, tp_pct, price, timestamp, reasons_json`
+fn signal_from_row(
+    row: &tokio_postgres::Row,
+) -> Result<SignalOutput, Box<dyn std::error::Error + Send + Sync>> {
+    let direction_str: String = row.get(1);
+    let timestamp_naive: chrono::NaiveDateTime = row.get(6);
+    let reasons_json: String = row.get(7);
+
+    let reasons: Vec<SignalReason> = serde_json::from_str(&reasons_json).map_err(|e| {
+        Box::new(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("Failed to deserialize reasons: {}", e),
+        )) as Box<dyn std::error::Error + Send + Sync>
+    })?;
+
+    Ok(SignalOutput {
+        symbol: row.get(0),
+        direction: parse_direction(&direction_str),
+        confidence: row.get(2),
+        recommended_sl_pct: row.get(3),
+        recommended_tp_pct: row.get(4),
+        price: row.get(5),
+        timestamp: DateTime::from_naive_utc_and_offset(timestamp_naive, Utc),
+        reasons,
+    })
+}
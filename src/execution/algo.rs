@@ -0,0 +1,759 @@
+//! Execution algorithms
+//!
+//! Work a large order through smaller child orders placed via the OMS:
+//! - TWAP: equal slices at even intervals over a duration, each an IOC limit
+//!   at most `max_slippage` through the far touch. A slice that does not fill
+//!   is made up by the next one.
+//! - Iceberg: one visible clip at a time, resting at the limit price.
+//! - Chase limit: a post-only order at the touch, re-priced whenever the touch
+//!   moves, until it fills or times out.
+//!
+//! Algos are requested and cancelled through an [`AlgoStore`] (Redis in
+//! production) so the API server can control the algos run by the trading
+//! process. The [`AlgoEngine`] there picks up pending algos, supervises one
+//! task per algo and writes its progress back to the store.
+
+use crate::execution::error::ExecutionError;
+use crate::execution::oms::OrderManager;
+use crate::execution::order::{OrderRequest, OrderSide, OrderType, TimeInForce};
+use crate::execution::state::{new_client_order_id, ManagedOrder, OrderOrigin};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Mutex, RwLock};
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+use utoipa::ToSchema;
+
+/// Remaining sizes below this fraction of the algo size count as filled
+const SIZE_TOLERANCE: f64 = 1e-9;
+
+fn default_max_slippage() -> f64 {
+    0.005
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type")]
+pub enum AlgoParams {
+    Twap {
+        duration_seconds: u64,
+        slices: u32,
+        /// Furthest a slice may trade through the far touch, as a fraction
+        #[serde(default = "default_max_slippage")]
+        max_slippage: f64,
+    },
+    Iceberg {
+        /// Size shown on the book at a time
+        clip_size: f64,
+        limit_price: f64,
+    },
+    ChaseLimit {
+        timeout_seconds: u64,
+        /// Never chase beyond this price
+        #[serde(default)]
+        limit_price: Option<f64>,
+    },
+}
+
+/// Parent order worked by an algo
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct AlgoOrder {
+    pub symbol: String,
+    pub side: OrderSide,
+    /// Total size in base units
+    pub size: f64,
+    #[serde(default)]
+    pub reduce_only: bool,
+    pub params: AlgoParams,
+}
+
+impl AlgoOrder {
+    pub fn validate(&self) -> Result<(), ExecutionError> {
+        let invalid = |reason: &str| Err(ExecutionError::InvalidOrder(reason.to_string()));
+        if self.symbol.is_empty() {
+            return invalid("symbol must not be empty");
+        }
+        if !self.size.is_finite() || self.size <= 0.0 {
+            return invalid("size must be positive");
+        }
+        match self.params {
+            AlgoParams::Twap {
+                duration_seconds,
+                slices,
+                max_slippage,
+            } => {
+                if duration_seconds == 0 || slices == 0 {
+                    return invalid("duration_seconds and slices must be positive");
+                }
+                if !(0.0..1.0).contains(&max_slippage) {
+                    return invalid("max_slippage must be in [0, 1)");
+                }
+            }
+            AlgoParams::Iceberg {
+                clip_size,
+                limit_price,
+            } => {
+                if !clip_size.is_finite() || clip_size <= 0.0 || clip_size > self.size {
+                    return invalid("clip_size must be positive and at most size");
+                }
+                if !limit_price.is_finite() || limit_price <= 0.0 {
+                    return invalid("limit_price must be positive");
+                }
+            }
+            AlgoParams::ChaseLimit {
+                timeout_seconds,
+                limit_price,
+            } => {
+                if timeout_seconds == 0 {
+                    return invalid("timeout_seconds must be positive");
+                }
+                if limit_price.is_some_and(|p| !p.is_finite() || p <= 0.0) {
+                    return invalid("limit_price must be positive");
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum AlgoState {
+    /// Requested, not yet picked up by the trading process
+    Pending,
+    Running,
+    Completed,
+    /// Schedule or timeout ran out before the full size filled
+    Expired,
+    Cancelled,
+    Failed,
+}
+
+impl AlgoState {
+    pub fn is_terminal(&self) -> bool {
+        !matches!(self, AlgoState::Pending | AlgoState::Running)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct AlgoProgress {
+    pub id: String,
+    pub order: AlgoOrder,
+    pub state: AlgoState,
+    pub filled_size: f64,
+    /// Average price of the filled size. Child fills without a reported
+    /// average count at their limit price.
+    pub avg_fill_price: Option<f64>,
+    /// Client order ids of the child orders, oldest first
+    pub child_orders: Vec<String>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl AlgoProgress {
+    pub fn new(order: AlgoOrder, now: DateTime<Utc>) -> Self {
+        Self {
+            id: new_client_order_id(),
+            order,
+            state: AlgoState::Pending,
+            filled_size: 0.0,
+            avg_fill_price: None,
+            child_orders: Vec::new(),
+            error: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn remaining_size(&self) -> f64 {
+        (self.order.size - self.filled_size).max(0.0)
+    }
+
+    pub fn is_filled(&self) -> bool {
+        self.remaining_size() <= self.order.size * SIZE_TOLERANCE
+    }
+}
+
+/// Shared storage for algo progress and cancellation requests
+#[async_trait]
+pub trait AlgoStore: Send + Sync {
+    /// Recent algos, newest first
+    async fn load_algos(
+        &self,
+    ) -> Result<Vec<AlgoProgress>, Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn load_algo(
+        &self,
+        id: &str,
+    ) -> Result<Option<AlgoProgress>, Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn save_algo(
+        &self,
+        progress: &AlgoProgress,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Kept apart from the progress record, which the running algo overwrites
+    async fn request_algo_cancel(
+        &self,
+        id: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn algo_cancel_requested(
+        &self,
+        id: &str,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// Process-local store, for tests and single-process setups
+#[derive(Default)]
+pub struct MemoryAlgoStore {
+    algos: RwLock<HashMap<String, AlgoProgress>>,
+    cancel_requests: RwLock<Vec<String>>,
+}
+
+#[async_trait]
+impl AlgoStore for MemoryAlgoStore {
+    async fn load_algos(
+        &self,
+    ) -> Result<Vec<AlgoProgress>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut algos: Vec<AlgoProgress> = self.algos.read().await.values().cloned().collect();
+        algos.sort_by_key(|a| std::cmp::Reverse(a.created_at));
+        Ok(algos)
+    }
+
+    async fn load_algo(
+        &self,
+        id: &str,
+    ) -> Result<Option<AlgoProgress>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.algos.read().await.get(id).cloned())
+    }
+
+    async fn save_algo(
+        &self,
+        progress: &AlgoProgress,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.algos
+            .write()
+            .await
+            .insert(progress.id.clone(), progress.clone());
+        Ok(())
+    }
+
+    async fn request_algo_cancel(
+        &self,
+        id: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.cancel_requests.write().await.push(id.to_string());
+        Ok(())
+    }
+
+    async fn algo_cancel_requested(
+        &self,
+        id: &str,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.cancel_requests.read().await.iter().any(|r| r == id))
+    }
+}
+
+/// Runs requested algos in the process that owns the OMS
+pub struct AlgoEngine {
+    runner: AlgoRunner,
+    tasks: Mutex<HashMap<String, JoinHandle<()>>>,
+    poll_interval: Duration,
+}
+
+impl AlgoEngine {
+    pub fn new(oms: Arc<OrderManager>, store: Arc<dyn AlgoStore>) -> Self {
+        Self {
+            runner: AlgoRunner {
+                oms,
+                store,
+                tick: Duration::from_secs(1),
+            },
+            tasks: Mutex::new(HashMap::new()),
+            poll_interval: Duration::from_secs(1),
+        }
+    }
+
+    /// How often running algos check their child orders, the book and cancellation
+    pub fn with_tick_interval(mut self, tick: Duration) -> Self {
+        self.runner.tick = tick;
+        self
+    }
+
+    /// How often the store is checked for new algo requests
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Validate and queue an algo; it starts on the next poll
+    pub async fn submit(&self, order: AlgoOrder) -> Result<AlgoProgress, ExecutionError> {
+        order.validate()?;
+        let progress = AlgoProgress::new(order, Utc::now());
+        self.runner
+            .store
+            .save_algo(&progress)
+            .await
+            .map_err(|e| ExecutionError::Storage(e.to_string()))?;
+        Ok(progress)
+    }
+
+    pub async fn cancel(&self, id: &str) -> Result<(), ExecutionError> {
+        self.runner
+            .store
+            .request_algo_cancel(id)
+            .await
+            .map_err(|e| ExecutionError::Storage(e.to_string()))
+    }
+
+    pub async fn progress(&self, id: &str) -> Result<Option<AlgoProgress>, ExecutionError> {
+        self.runner
+            .store
+            .load_algo(id)
+            .await
+            .map_err(|e| ExecutionError::Storage(e.to_string()))
+    }
+
+    /// Fail algos left running by a previous process and cancel their child
+    /// orders. Call after the OMS has restored its orders.
+    pub async fn restore(&self) -> Result<usize, ExecutionError> {
+        let algos = self
+            .runner
+            .store
+            .load_algos()
+            .await
+            .map_err(|e| ExecutionError::Storage(e.to_string()))?;
+        let mut interrupted = 0;
+        for mut progress in algos.into_iter().filter(|a| a.state == AlgoState::Running) {
+            self.runner.cancel_children(&progress).await;
+            self.runner.sync(&mut progress).await;
+            progress.state = AlgoState::Failed;
+            progress.error = Some("Interrupted by a restart".to_string());
+            self.runner.save(&mut progress).await;
+            interrupted += 1;
+        }
+        if interrupted > 0 {
+            warn!(
+                algos = interrupted,
+                "Failed {} algos interrupted by a restart", interrupted
+            );
+        }
+        Ok(interrupted)
+    }
+
+    /// Start every pending algo, returning the ids started
+    pub async fn start_pending(&self) -> Result<Vec<String>, ExecutionError> {
+        let algos = self
+            .runner
+            .store
+            .load_algos()
+            .await
+            .map_err(|e| ExecutionError::Storage(e.to_string()))?;
+        let mut tasks = self.tasks.lock().await;
+        tasks.retain(|_, task| !task.is_finished());
+
+        let mut started = Vec::new();
+        for progress in algos.into_iter().filter(|a| a.state == AlgoState::Pending) {
+            if tasks.contains_key(&progress.id) {
+                continue;
+            }
+            let id = progress.id.clone();
+            tasks.insert(id.clone(), self.supervise(progress));
+            started.push(id);
+        }
+        Ok(started)
+    }
+
+    /// Start pending algos until the task is cancelled
+    pub async fn run(&self) {
+        loop {
+            if let Err(e) = self.start_pending().await {
+                error!(error = %e, "Failed to start pending algos");
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+
+    /// Run the algo on its own task and mark it failed if that task panics
+    fn supervise(&self, progress: AlgoProgress) -> JoinHandle<()> {
+        let runner = self.runner.clone();
+        let id = progress.id.clone();
+        tokio::spawn(async move {
+            let task = tokio::spawn(runner.clone().execute(progress));
+            if let Err(e) = task.await {
+                error!(algo_id = %id, error = %e, "Algo {} task failed", id);
+                if let Ok(Some(mut progress)) = runner.store.load_algo(&id).await {
+                    runner.cancel_children(&progress).await;
+                    runner.sync(&mut progress).await;
+                    progress.state = AlgoState::Failed;
+                    progress.error = Some(format!("Algo task failed: {}", e));
+                    runner.save(&mut progress).await;
+                }
+            }
+        })
+    }
+}
+
+#[derive(Clone)]
+struct AlgoRunner {
+    oms: Arc<OrderManager>,
+    store: Arc<dyn AlgoStore>,
+    tick: Duration,
+}
+
+impl AlgoRunner {
+    async fn execute(self, mut progress: AlgoProgress) {
+        if self.cancel_requested(&progress.id).await {
+            progress.state = AlgoState::Cancelled;
+            self.save(&mut progress).await;
+            return;
+        }
+
+        progress.state = AlgoState::Running;
+        self.save(&mut progress).await;
+        info!(
+            algo_id = %progress.id,
+            symbol = %progress.order.symbol,
+            size = progress.order.size,
+            "Algo {} started",
+            progress.id
+        );
+
+        let result = match progress.order.params.clone() {
+            AlgoParams::Twap {
+                duration_seconds,
+                slices,
+                max_slippage,
+            } => {
+                self.twap(&mut progress, duration_seconds, slices, max_slippage)
+                    .await
+            }
+            AlgoParams::Iceberg {
+                clip_size,
+                limit_price,
+            } => self.iceberg(&mut progress, clip_size, limit_price).await,
+            AlgoParams::ChaseLimit {
+                timeout_seconds,
+                limit_price,
+            } => {
+                self.chase(&mut progress, timeout_seconds, limit_price)
+                    .await
+            }
+        };
+
+        self.cancel_children(&progress).await;
+        self.sync(&mut progress).await;
+        match result {
+            Ok(state) => progress.state = state,
+            Err(e) => {
+                progress.state = AlgoState::Failed;
+                progress.error = Some(e.to_string());
+            }
+        }
+        self.save(&mut progress).await;
+        info!(
+            algo_id = %progress.id,
+            state = ?progress.state,
+            filled = progress.filled_size,
+            "Algo {} finished {:?} with {} filled",
+            progress.id,
+            progress.state,
+            progress.filled_size
+        );
+    }
+
+    async fn twap(
+        &self,
+        progress: &mut AlgoProgress,
+        duration_seconds: u64,
+        slices: u32,
+        max_slippage: f64,
+    ) -> Result<AlgoState, ExecutionError> {
+        let start = tokio::time::Instant::now();
+        let interval = Duration::from_secs(duration_seconds) / slices;
+        let side = progress.order.side;
+
+        for slice in 0..slices {
+            if !self.wait_until(progress, start + interval * slice).await {
+                return Ok(AlgoState::Cancelled);
+            }
+            self.sync(progress).await;
+            let target = progress.order.size * f64::from(slice + 1) / f64::from(slices);
+            let size = target - progress.filled_size;
+            if size <= progress.order.size * SIZE_TOLERANCE {
+                continue;
+            }
+
+            let book = match self
+                .oms
+                .executor()
+                .top_of_book(&progress.order.symbol)
+                .await
+            {
+                Ok(book) => book,
+                Err(e) => {
+                    warn!(algo_id = %progress.id, error = %e, "Skipping TWAP slice without a book");
+                    continue;
+                }
+            };
+            let offset = match side {
+                OrderSide::Buy => 1.0 + max_slippage,
+                OrderSide::Sell => 1.0 - max_slippage,
+            };
+            let request = OrderRequest::limit(
+                &progress.order.symbol,
+                side,
+                size,
+                book.far_touch(side) * offset,
+            )
+            .with_order_type(OrderType::Limit(TimeInForce::Ioc));
+            match self.place(progress, request).await {
+                Ok(_) => {}
+                Err(e @ ExecutionError::RiskRejected(_)) => return Err(e),
+                // An IOC that finds no liquidity is rejected; the next slice catches up
+                Err(e) => {
+                    warn!(algo_id = %progress.id, error = %e, "TWAP slice {} not filled", slice + 1)
+                }
+            }
+            self.sync(progress).await;
+            self.save(progress).await;
+        }
+
+        Ok(self.final_state(progress).await)
+    }
+
+    async fn iceberg(
+        &self,
+        progress: &mut AlgoProgress,
+        clip_size: f64,
+        limit_price: f64,
+    ) -> Result<AlgoState, ExecutionError> {
+        let mut clip: Option<ManagedOrder> = None;
+        loop {
+            if self.cancel_requested(&progress.id).await {
+                return Ok(AlgoState::Cancelled);
+            }
+            if let Some(order) = clip.as_ref() {
+                clip = Some(self.refresh(order).await);
+            }
+            self.sync(progress).await;
+            if progress.is_filled() {
+                return Ok(AlgoState::Completed);
+            }
+
+            if clip.as_ref().is_none_or(|o| o.state.is_terminal()) {
+                let size = clip_size.min(progress.remaining_size());
+                let request = OrderRequest::limit(
+                    &progress.order.symbol,
+                    progress.order.side,
+                    size,
+                    limit_price,
+                );
+                match self.place(progress, request).await {
+                    Ok(order) => clip = Some(order),
+                    Err(e @ ExecutionError::RiskRejected(_)) => return Err(e),
+                    Err(e) => warn!(algo_id = %progress.id, error = %e, "Iceberg clip not placed"),
+                }
+                self.save(progress).await;
+            }
+            tokio::time::sleep(self.tick).await;
+        }
+    }
+
+    async fn chase(
+        &self,
+        progress: &mut AlgoProgress,
+        timeout_seconds: u64,
+        limit_price: Option<f64>,
+    ) -> Result<AlgoState, ExecutionError> {
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(timeout_seconds);
+        let side = progress.order.side;
+        let mut working: Option<ManagedOrder> = None;
+        loop {
+            if self.cancel_requested(&progress.id).await {
+                return Ok(AlgoState::Cancelled);
+            }
+            if let Some(order) = working.as_ref() {
+                working = Some(self.refresh(order).await);
+            }
+            self.sync(progress).await;
+            if progress.is_filled() {
+                return Ok(AlgoState::Completed);
+            }
+            if tokio::time::Instant::now() >= deadline {
+                return Ok(AlgoState::Expired);
+            }
+
+            let book = match self
+                .oms
+                .executor()
+                .top_of_book(&progress.order.symbol)
+                .await
+            {
+                Ok(book) => Some(book),
+                Err(e) => {
+                    warn!(algo_id = %progress.id, error = %e, "No book to chase");
+                    None
+                }
+            };
+            if let Some(book) = book {
+                let touch = book.touch(side);
+                let price = match (side, limit_price) {
+                    (OrderSide::Buy, Some(limit)) => touch.min(limit),
+                    (OrderSide::Sell, Some(limit)) => touch.max(limit),
+                    (_, None) => touch,
+                };
+
+                let live = working.as_ref().filter(|o| !o.state.is_terminal());
+                let replace = match live {
+                    Some(order) if order.request.price == price => false,
+                    Some(order) => match self.oms.cancel(&order.client_order_id).await {
+                        Ok(_) => true,
+                        Err(e) => {
+                            // Most likely filled in the meantime; the next refresh tells
+                            warn!(algo_id = %progress.id, error = %e, "Failed to cancel chase order");
+                            false
+                        }
+                    },
+                    None => true,
+                };
+                if replace {
+                    // Pick up fills made before the cancel
+                    if let Some(order) = working.as_ref() {
+                        self.refresh(order).await;
+                    }
+                    self.sync(progress).await;
+                    let request = OrderRequest::limit(
+                        &progress.order.symbol,
+                        side,
+                        progress.remaining_size(),
+                        price,
+                    )
+                    .with_order_type(OrderType::Limit(TimeInForce::Alo));
+                    match self.place(progress, request).await {
+                        Ok(order) => working = Some(order),
+                        Err(e @ ExecutionError::RiskRejected(_)) => return Err(e),
+                        // Post-only orders are rejected when the touch moved through them
+                        Err(e) => {
+                            warn!(algo_id = %progress.id, error = %e, "Chase order not placed");
+                            working = None;
+                        }
+                    }
+                    self.save(progress).await;
+                }
+            }
+
+            let now = tokio::time::Instant::now();
+            tokio::time::sleep(self.tick.min(deadline.saturating_duration_since(now))).await;
+        }
+    }
+
+    /// Sleep until `until`, returning false if the algo was cancelled meanwhile
+    async fn wait_until(&self, progress: &AlgoProgress, until: tokio::time::Instant) -> bool {
+        loop {
+            if self.cancel_requested(&progress.id).await {
+                return false;
+            }
+            let now = tokio::time::Instant::now();
+            if now >= until {
+                return true;
+            }
+            tokio::time::sleep(self.tick.min(until - now)).await;
+        }
+    }
+
+    async fn final_state(&self, progress: &mut AlgoProgress) -> AlgoState {
+        for id in &progress.child_orders {
+            if let Err(e) = self.oms.refresh(id).await {
+                warn!(algo_id = %progress.id, error = %e, "Failed to refresh child order {}", id);
+            }
+        }
+        self.sync(progress).await;
+        if progress.is_filled() {
+            AlgoState::Completed
+        } else {
+            AlgoState::Expired
+        }
+    }
+
+    async fn place(
+        &self,
+        progress: &mut AlgoProgress,
+        request: OrderRequest,
+    ) -> Result<ManagedOrder, ExecutionError> {
+        let client_order_id = new_client_order_id();
+        // Recorded before submission so the child is tracked whatever the outcome
+        progress.child_orders.push(client_order_id.clone());
+        let request = request
+            .with_reduce_only(progress.order.reduce_only)
+            .with_client_order_id(client_order_id);
+        self.oms.submit(request, OrderOrigin::manual()).await
+    }
+
+    async fn refresh(&self, order: &ManagedOrder) -> ManagedOrder {
+        match self.oms.refresh(&order.client_order_id).await {
+            Ok(order) => order,
+            Err(e) => {
+                warn!(client_order_id = %order.client_order_id, error = %e, "Failed to refresh order {}", order.client_order_id);
+                self.oms
+                    .order(&order.client_order_id)
+                    .await
+                    .unwrap_or_else(|| order.clone())
+            }
+        }
+    }
+
+    /// Cancel child orders that are still open
+    async fn cancel_children(&self, progress: &AlgoProgress) {
+        for id in &progress.child_orders {
+            let Some(order) = self.oms.order(id).await else {
+                continue;
+            };
+            if order.state.is_terminal() {
+                continue;
+            }
+            let order = self.refresh(&order).await;
+            if order.state.is_terminal() {
+                continue;
+            }
+            if let Err(e) = self.oms.cancel(id).await {
+                warn!(algo_id = %progress.id, error = %e, "Failed to cancel child order {}", id);
+            }
+        }
+    }
+
+    /// Recompute filled size and average price from the child orders
+    async fn sync(&self, progress: &mut AlgoProgress) {
+        let mut filled = 0.0;
+        let mut notional = 0.0;
+        for id in &progress.child_orders {
+            if let Some(order) = self.oms.order(id).await {
+                filled += order.filled_size;
+                notional += order.filled_size * order.avg_fill_price.unwrap_or(order.request.price);
+            }
+        }
+        progress.filled_size = filled;
+        progress.avg_fill_price = (filled > 0.0).then(|| notional / filled);
+    }
+
+    async fn save(&self, progress: &mut AlgoProgress) {
+        progress.updated_at = Utc::now();
+        if let Err(e) = self.store.save_algo(progress).await {
+            error!(algo_id = %progress.id, error = %e, "Failed to save algo progress");
+        }
+    }
+
+    async fn cancel_requested(&self, id: &str) -> bool {
+        match self.store.algo_cancel_requested(id).await {
+            Ok(requested) => requested,
+            Err(e) => {
+                warn!(algo_id = %id, error = %e, "Failed to read algo cancellation");
+                false
+            }
+        }
+    }
+}
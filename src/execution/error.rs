@@ -0,0 +1,43 @@
+use crate::execution::risk::RiskRejection;
+use std::fmt;
+
+#[derive(Debug, Clone)]
+pub enum ExecutionError {
+    /// The order is malformed or violates exchange precision rules
+    InvalidOrder(String),
+    UnknownSymbol(String),
+    /// The exchange accepted the request but rejected the action
+    Rejected(String),
+    /// Credentials are missing or cannot be used for signing
+    Signing(String),
+    /// Network or HTTP failure
+    Transport(String),
+    /// The exchange response could not be interpreted
+    InvalidResponse(String),
+    /// An order state transition that the lifecycle does not allow
+    InvalidTransition(String),
+    /// Persisting or loading order state failed
+    Storage(String),
+    /// Blocked by a pre-trade risk check before reaching the exchange
+    RiskRejected(RiskRejection),
+}
+
+impl fmt::Display for ExecutionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExecutionError::InvalidOrder(msg) => write!(f, "Invalid order: {}", msg),
+            ExecutionError::UnknownSymbol(symbol) => write!(f, "Unknown symbol: {}", symbol),
+            ExecutionError::Rejected(msg) => write!(f, "Rejected by exchange: {}", msg),
+            ExecutionError::Signing(msg) => write!(f, "Signing error: {}", msg),
+            ExecutionError::Transport(msg) => write!(f, "Transport error: {}", msg),
+            ExecutionError::InvalidResponse(msg) => write!(f, "Invalid exchange response: {}", msg),
+            ExecutionError::InvalidTransition(msg) => {
+                write!(f, "Invalid order transition: {}", msg)
+            }
+            ExecutionError::Storage(msg) => write!(f, "Order storage error: {}", msg),
+            ExecutionError::RiskRejected(reason) => write!(f, "Rejected by risk check: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for ExecutionError {}
@@ -0,0 +1,28 @@
+//! Account events pushed by the exchange
+//!
+//! Exchange adapters with a user data stream publish [`UserEvent`]s on a
+//! broadcast channel; the OMS and other account consumers subscribe to it
+//! instead of polling. Polling stays in place as the fallback when a
+//! consumer falls behind or the stream disconnects.
+
+use crate::execution::order::{ExchangeFill, ExchangeOrderReport, FundingPayment};
+use crate::execution::risk::AccountSnapshot;
+use tokio::sync::broadcast;
+
+/// Events buffered per subscriber before it starts lagging
+pub const USER_EVENT_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UserEvent {
+    /// Order status change
+    OrderUpdate(ExchangeOrderReport),
+    /// Fill of one of the account's orders; replayed fills keep their trade id
+    Fill(ExchangeFill),
+    Funding(FundingPayment),
+    /// Positions and equity
+    Account(AccountSnapshot),
+}
+
+pub fn user_event_channel() -> (broadcast::Sender<UserEvent>, broadcast::Receiver<UserEvent>) {
+    broadcast::channel(USER_EVENT_CAPACITY)
+}
@@ -0,0 +1,72 @@
+//! Interface implemented by exchange execution adapters
+
+use crate::execution::error::ExecutionError;
+use crate::execution::order::{
+    ExchangeFill, ExchangeOrderReport, ExchangePosition, OpenOrder, OrderAck, OrderGrouping,
+    OrderRequest, TopOfBook,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+/// Places and manages orders on a single exchange account
+#[async_trait]
+pub trait ExchangeExecutor: Send + Sync {
+    /// Short exchange identifier, e.g. "hyperliquid"
+    fn name(&self) -> &str;
+
+    async fn place_order(&self, order: &OrderRequest) -> Result<OrderAck, ExecutionError>;
+
+    /// Place several orders in one request, returning one result per order.
+    ///
+    /// Exchanges without native grouping place the orders one by one.
+    async fn place_orders(
+        &self,
+        orders: &[OrderRequest],
+        _grouping: OrderGrouping,
+    ) -> Result<Vec<Result<OrderAck, ExecutionError>>, ExecutionError> {
+        let mut results = Vec::with_capacity(orders.len());
+        for order in orders {
+            results.push(self.place_order(order).await);
+        }
+        Ok(results)
+    }
+
+    async fn cancel_order(&self, symbol: &str, order_id: u64) -> Result<(), ExecutionError>;
+
+    /// Replace the price, size or type of a resting order
+    async fn modify_order(
+        &self,
+        order_id: u64,
+        order: &OrderRequest,
+    ) -> Result<OrderAck, ExecutionError>;
+
+    async fn open_orders(&self) -> Result<Vec<OpenOrder>, ExecutionError>;
+
+    /// Current state of an order by client order id, `None` if the exchange does not know it
+    async fn order_status(
+        &self,
+        client_order_id: &str,
+    ) -> Result<Option<ExchangeOrderReport>, ExecutionError>;
+
+    /// Account fills at or after `since`, oldest first
+    async fn fills_since(&self, since: DateTime<Utc>) -> Result<Vec<ExchangeFill>, ExecutionError>;
+
+    async fn positions(&self) -> Result<Vec<ExchangePosition>, ExecutionError>;
+
+    /// Total account value including unrealized PnL, in quote currency
+    async fn account_equity(&self) -> Result<f64, ExecutionError>;
+
+    /// Current mid price of a symbol's order book
+    async fn mid_price(&self, symbol: &str) -> Result<f64, ExecutionError>;
+
+    /// Best bid and ask of a symbol's order book
+    async fn top_of_book(&self, symbol: &str) -> Result<TopOfBook, ExecutionError>;
+
+    /// Set the account leverage for a symbol
+    async fn set_leverage(
+        &self,
+        symbol: &str,
+        leverage: u32,
+        cross_margin: bool,
+    ) -> Result<(), ExecutionError>;
+}
@@ -0,0 +1,198 @@
+//! Global kill switch shared by every process that places orders
+//!
+//! The switch lives in a [`KillSwitchStore`] (Redis in production) so the API
+//! server can flip it for the trading processes. While active, new orders are
+//! refused; a flatten request is carried out once by the process that owns the
+//! executor. Until the store has been read once, an unreadable store counts as
+//! an active switch, so a process cannot start trading past a halt it never saw.
+
+use crate::metrics::Metrics;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::warn;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct KillSwitchState {
+    pub active: bool,
+    pub reason: Option<String>,
+    /// Positions should be closed and open orders cancelled
+    pub flatten_requested: bool,
+    /// When the last flatten request was carried out
+    pub flattened_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Default for KillSwitchState {
+    fn default() -> Self {
+        Self {
+            active: false,
+            reason: None,
+            flatten_requested: false,
+            flattened_at: None,
+            updated_at: DateTime::<Utc>::UNIX_EPOCH,
+        }
+    }
+}
+
+/// Shared storage for the kill switch state
+#[async_trait]
+pub trait KillSwitchStore: Send + Sync {
+    /// Stored state, `None` if the switch was never set
+    async fn load_kill_switch(
+        &self,
+    ) -> Result<Option<KillSwitchState>, Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn save_kill_switch(
+        &self,
+        state: &KillSwitchState,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// Process-local store, for tests and single-process setups
+#[derive(Default)]
+pub struct MemoryKillSwitchStore {
+    state: RwLock<Option<KillSwitchState>>,
+}
+
+#[async_trait]
+impl KillSwitchStore for MemoryKillSwitchStore {
+    async fn load_kill_switch(
+        &self,
+    ) -> Result<Option<KillSwitchState>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.state.read().await.clone())
+    }
+
+    async fn save_kill_switch(
+        &self,
+        state: &KillSwitchState,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        *self.state.write().await = Some(state.clone());
+        Ok(())
+    }
+}
+
+pub struct KillSwitch {
+    store: Arc<dyn KillSwitchStore>,
+    /// Last state read from the store, used when the store is unreachable;
+    /// `None` until the first successful read
+    last_known: RwLock<Option<KillSwitchState>>,
+    metrics: Option<Arc<Metrics>>,
+}
+
+impl KillSwitch {
+    pub fn new(store: Arc<dyn KillSwitchStore>) -> Self {
+        Self {
+            store,
+            last_known: RwLock::new(None),
+            metrics: None,
+        }
+    }
+
+    /// Keep the `kill_switch_active` gauge in sync with the state
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Current state, falling back to the last known state if the store fails.
+    ///
+    /// Fails closed: if the store has never been read successfully, the switch
+    /// is reported active.
+    pub async fn state(&self) -> KillSwitchState {
+        match self.store.load_kill_switch().await {
+            Ok(state) => {
+                let state = state.unwrap_or_default();
+                self.remember(&state).await;
+                state
+            }
+            Err(e) => match self.last_known.read().await.clone() {
+                Some(state) => {
+                    warn!(error = %e, active = state.active, "Failed to read kill switch, using last known state");
+                    state
+                }
+                None => {
+                    warn!(error = %e, "Failed to read kill switch and no state is known, treating it as active");
+                    KillSwitchState {
+                        active: true,
+                        reason: Some(format!("kill switch state unavailable: {}", e)),
+                        ..KillSwitchState::default()
+                    }
+                }
+            },
+        }
+    }
+
+    pub async fn is_active(&self) -> bool {
+        self.state().await.active
+    }
+
+    /// Block new orders, optionally requesting that all positions be closed
+    pub async fn activate(
+        &self,
+        reason: impl Into<String>,
+        flatten: bool,
+    ) -> Result<KillSwitchState, Box<dyn std::error::Error + Send + Sync>> {
+        let current = self.state().await;
+        let state = KillSwitchState {
+            active: true,
+            reason: Some(reason.into()),
+            flatten_requested: flatten || (current.active && current.flatten_requested),
+            flattened_at: current.flattened_at,
+            updated_at: Utc::now(),
+        };
+        self.save(&state).await?;
+        Ok(state)
+    }
+
+    /// Allow new orders again and drop any pending flatten request
+    pub async fn deactivate(
+        &self,
+    ) -> Result<KillSwitchState, Box<dyn std::error::Error + Send + Sync>> {
+        let current = self.state().await;
+        let state = KillSwitchState {
+            active: false,
+            reason: None,
+            flatten_requested: false,
+            flattened_at: current.flattened_at,
+            updated_at: Utc::now(),
+        };
+        self.save(&state).await?;
+        Ok(state)
+    }
+
+    /// Record that the pending flatten request was carried out
+    pub async fn mark_flattened(
+        &self,
+    ) -> Result<KillSwitchState, Box<dyn std::error::Error + Send + Sync>> {
+        let now = Utc::now();
+        let state = KillSwitchState {
+            flatten_requested: false,
+            flattened_at: Some(now),
+            updated_at: now,
+            ..self.state().await
+        };
+        self.save(&state).await?;
+        Ok(state)
+    }
+
+    async fn save(
+        &self,
+        state: &KillSwitchState,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.store.save_kill_switch(state).await?;
+        self.remember(state).await;
+        Ok(())
+    }
+
+    async fn remember(&self, state: &KillSwitchState) {
+        if let Some(metrics) = &self.metrics {
+            metrics
+                .kill_switch_active
+                .set(if state.active { 1.0 } else { 0.0 });
+        }
+        *self.last_known.write().await = Some(state.clone());
+    }
+}
@@ -0,0 +1,42 @@
+//! Exchange execution: order model, the executor interface implemented by
+//! exchange adapters, pre-trade risk checks, a shadow executor that records
+//! orders instead of sending them, the order management system on top of
+//! them, execution algorithms working large orders through it, the policy
+//! engine that turns signals into orders and the position and PnL tracker.
+
+pub mod algo;
+pub mod error;
+pub mod events;
+pub mod executor;
+pub mod kill_switch;
+pub mod oms;
+pub mod order;
+pub mod policy;
+pub mod policy_engine;
+pub mod positions;
+pub mod risk;
+pub mod shadow;
+pub mod state;
+
+pub use algo::{
+    AlgoEngine, AlgoOrder, AlgoParams, AlgoProgress, AlgoState, AlgoStore, MemoryAlgoStore,
+};
+pub use error::ExecutionError;
+pub use events::{user_event_channel, UserEvent};
+pub use executor::ExchangeExecutor;
+pub use kill_switch::{KillSwitch, KillSwitchState, KillSwitchStore, MemoryKillSwitchStore};
+pub use oms::{OrderManager, ReconcileReport};
+pub use order::*;
+pub use policy::{
+    BracketGrouping, EntryOrderType, ExecutionPolicy, OppositeSignalAction, SignalAction,
+};
+pub use policy_engine::{signal_client_order_id, PolicyEngine, SignalExecution};
+pub use positions::{
+    estimate_liquidation_price, AccountPnl, AppliedFill, PositionTracker, TrackedPosition,
+};
+pub use risk::{
+    AccountSnapshot, DayStartEquityStore, FlattenReport, MemoryDayStartEquityStore,
+    RiskGuardedExecutor, RiskLimits, RiskManager, RiskRejection,
+};
+pub use shadow::{shadow_exchange_name, ShadowExecutor};
+pub use state::{new_client_order_id, EntryPlan, ManagedOrder, OrderFill, OrderOrigin, OrderState};
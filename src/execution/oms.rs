@@ -0,0 +1,753 @@
+//! Order management system
+//!
+//! Tracks every order placed through an [`ExchangeExecutor`] from creation to
+//! a terminal state, persists each transition and fill, and reconciles local
+//! state against the exchange on startup and periodically. Client order ids
+//! make submission idempotent: an id that is already tracked is never sent twice.
+//! Order updates and fills from a user event stream are applied as they arrive.
+
+use crate::db::OrderStore;
+use crate::execution::error::ExecutionError;
+use crate::execution::events::UserEvent;
+use crate::execution::executor::ExchangeExecutor;
+use crate::execution::order::{
+    ExchangeFill, ExchangeOrderReport, ExchangeOrderState, OrderAck, OrderGrouping, OrderRequest,
+    OrderStatus,
+};
+use crate::execution::state::{EntryPlan, ManagedOrder, OrderFill, OrderOrigin, OrderState};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, Mutex, RwLock};
+use tracing::{error, info, warn};
+
+/// How far back orders are restored on startup
+const RESTORE_WINDOW_DAYS: i64 = 7;
+
+/// Overlap between consecutive fill queries; replayed fills are deduplicated
+const FILL_SYNC_OVERLAP_SECONDS: i64 = 60;
+
+/// Outcome of one reconciliation pass
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReconcileReport {
+    /// Non-terminal orders compared against the exchange
+    pub checked: usize,
+    /// Orders whose state changed
+    pub updated: usize,
+    /// New fills applied
+    pub fills: usize,
+    /// Open exchange orders that the OMS does not track
+    pub untracked: Vec<u64>,
+}
+
+pub struct OrderManager {
+    executor: Arc<dyn ExchangeExecutor>,
+    store: Arc<dyn OrderStore>,
+    orders: RwLock<HashMap<String, ManagedOrder>>,
+    /// Serializes submissions so concurrent calls with the same id cannot both send
+    submit_lock: Mutex<()>,
+    last_fill_sync: RwLock<Option<DateTime<Utc>>>,
+    reconcile_interval: Duration,
+    unknown_order_grace: Duration,
+}
+
+impl OrderManager {
+    pub fn new(executor: Arc<dyn ExchangeExecutor>, store: Arc<dyn OrderStore>) -> Self {
+        Self {
+            executor,
+            store,
+            orders: RwLock::new(HashMap::new()),
+            submit_lock: Mutex::new(()),
+            last_fill_sync: RwLock::new(None),
+            reconcile_interval: Duration::from_secs(30),
+            unknown_order_grace: Duration::from_secs(60),
+        }
+    }
+
+    pub fn with_reconcile_interval(mut self, interval: Duration) -> Self {
+        self.reconcile_interval = interval;
+        self
+    }
+
+    /// How long a submitted order may be unknown to the exchange before it is
+    /// considered rejected
+    pub fn with_unknown_order_grace(mut self, grace: Duration) -> Self {
+        self.unknown_order_grace = grace;
+        self
+    }
+
+    pub fn executor(&self) -> &Arc<dyn ExchangeExecutor> {
+        &self.executor
+    }
+
+    /// Load recent orders placed through this executor's exchange from the
+    /// store and reconcile them with the exchange
+    pub async fn restore(&self) -> Result<ReconcileReport, ExecutionError> {
+        let since = Utc::now() - ChronoDuration::days(RESTORE_WINDOW_DAYS);
+        let mut restored = self
+            .store
+            .load_orders(since)
+            .await
+            .map_err(|e| ExecutionError::Storage(e.to_string()))?;
+        // Shadow and live orders share the store
+        restored.retain(|o| o.exchange == self.executor.name());
+
+        let count = restored.len();
+        {
+            let mut orders = self.orders.write().await;
+            for order in restored {
+                orders.insert(order.client_order_id.clone(), order);
+            }
+        }
+        info!(orders = count, "Restored {} orders", count);
+        self.reconcile().await
+    }
+
+    pub async fn order(&self, client_order_id: &str) -> Option<ManagedOrder> {
+        self.orders.read().await.get(client_order_id).cloned()
+    }
+
+    /// All tracked orders, oldest first
+    pub async fn orders(&self) -> Vec<ManagedOrder> {
+        let mut orders: Vec<ManagedOrder> = self.orders.read().await.values().cloned().collect();
+        orders.sort_by_key(|o| o.created_at);
+        orders
+    }
+
+    /// Tracked orders that have not reached a terminal state, oldest first
+    pub async fn open_orders(&self) -> Vec<ManagedOrder> {
+        let mut orders: Vec<ManagedOrder> = self
+            .orders
+            .read()
+            .await
+            .values()
+            .filter(|o| !o.state.is_terminal())
+            .cloned()
+            .collect();
+        orders.sort_by_key(|o| o.created_at);
+        orders
+    }
+
+    /// Submit an order, or return the tracked order if its client order id is known.
+    ///
+    /// The order is persisted before it is sent. Exchange rejections are recorded
+    /// and returned as errors; on transport errors the order stays `Submitted`
+    /// until reconciliation finds out what happened to it.
+    pub async fn submit(
+        &self,
+        request: OrderRequest,
+        origin: OrderOrigin,
+    ) -> Result<ManagedOrder, ExecutionError> {
+        self.submit_order(request, origin, None).await
+    }
+
+    /// Submit an entry with the plan the policy engine follows once it is
+    /// placed; the plan is persisted with the order before it is sent
+    pub async fn submit_entry(
+        &self,
+        request: OrderRequest,
+        origin: OrderOrigin,
+        plan: EntryPlan,
+    ) -> Result<ManagedOrder, ExecutionError> {
+        self.submit_order(request, origin, Some(plan)).await
+    }
+
+    async fn submit_order(
+        &self,
+        request: OrderRequest,
+        origin: OrderOrigin,
+        entry_plan: Option<EntryPlan>,
+    ) -> Result<ManagedOrder, ExecutionError> {
+        let _guard = self.submit_lock.lock().await;
+        if let Some(existing) = request.client_order_id.as_ref().map(|id| id.to_lowercase()) {
+            if let Some(order) = self.order(&existing).await {
+                info!(client_order_id = %existing, state = order.state.as_str(), "Order {} already tracked, not resubmitting", existing);
+                return Ok(order);
+            }
+        }
+
+        let now = Utc::now();
+        let mut request = request;
+        request.client_order_id = request.client_order_id.map(|id| id.to_lowercase());
+        let mut order = ManagedOrder::new(self.executor.name(), request, origin, now);
+        order.entry_plan = entry_plan;
+        self.persist(&order).await?;
+
+        order.transition(OrderState::Submitted, Utc::now())?;
+        self.persist(&order).await?;
+
+        let result = self.executor.place_order(&order.request).await;
+        let outcome = Self::apply_ack(&mut order, result);
+        self.persist(&order).await?;
+        outcome.map(|_| order)
+    }
+
+    /// Submit orders that the exchange should place together, such as an entry
+    /// with its stop loss and take profit.
+    ///
+    /// Returns the orders in request order. Known client order ids are
+    /// returned as tracked; if every id is known nothing is sent. Per-order
+    /// rejections are recorded on the returned orders rather than returned as
+    /// errors. On transport errors the orders stay `Submitted` until
+    /// reconciliation finds out what happened to them.
+    pub async fn submit_group(
+        &self,
+        requests: Vec<OrderRequest>,
+        grouping: OrderGrouping,
+        origin: OrderOrigin,
+    ) -> Result<Vec<ManagedOrder>, ExecutionError> {
+        self.submit_orders(requests, grouping, origin, None).await
+    }
+
+    /// [`submit_group`](Self::submit_group) for an entry, the first request,
+    /// with its stop loss and take profit; the plan is persisted with the entry
+    pub async fn submit_entry_group(
+        &self,
+        requests: Vec<OrderRequest>,
+        grouping: OrderGrouping,
+        origin: OrderOrigin,
+        plan: EntryPlan,
+    ) -> Result<Vec<ManagedOrder>, ExecutionError> {
+        self.submit_orders(requests, grouping, origin, Some(plan))
+            .await
+    }
+
+    async fn submit_orders(
+        &self,
+        requests: Vec<OrderRequest>,
+        grouping: OrderGrouping,
+        origin: OrderOrigin,
+        entry_plan: Option<EntryPlan>,
+    ) -> Result<Vec<ManagedOrder>, ExecutionError> {
+        let _guard = self.submit_lock.lock().await;
+        let mut tracked = Vec::with_capacity(requests.len());
+        for request in &requests {
+            let existing = match request.client_order_id.as_ref() {
+                Some(id) => self.order(&id.to_lowercase()).await,
+                None => None,
+            };
+            tracked.push(existing);
+        }
+        if !tracked.is_empty() && tracked.iter().all(|o| o.is_some()) {
+            info!(
+                orders = tracked.len(),
+                "Order group already tracked, not resubmitting"
+            );
+            return Ok(tracked.into_iter().flatten().collect());
+        }
+        if tracked.iter().any(|o| o.is_some()) {
+            return Err(ExecutionError::InvalidOrder(
+                "Order group is partially tracked".to_string(),
+            ));
+        }
+
+        let mut orders: Vec<ManagedOrder> = Vec::with_capacity(requests.len());
+        let mut entry_plan = entry_plan;
+        for mut request in requests {
+            request.client_order_id = request.client_order_id.map(|id| id.to_lowercase());
+            let mut order =
+                ManagedOrder::new(self.executor.name(), request, origin, Utc::now());
+            order.entry_plan = entry_plan.take();
+            if grouping == OrderGrouping::NormalTpsl {
+                order.parent_order_id = orders.first().map(|o| o.client_order_id.clone());
+            }
+            self.persist(&order).await?;
+            order.transition(OrderState::Submitted, Utc::now())?;
+            self.persist(&order).await?;
+            orders.push(order);
+        }
+
+        let wire: Vec<OrderRequest> = orders.iter().map(|o| o.request.clone()).collect();
+        let results = match self.executor.place_orders(&wire, grouping).await {
+            Ok(results) => results,
+            Err(e) => {
+                warn!(error = %e, orders = orders.len(), "Order group submission outcome unknown");
+                return Err(e);
+            }
+        };
+        if results.len() != orders.len() {
+            warn!(
+                orders = orders.len(),
+                results = results.len(),
+                "Order group returned a result count that does not match its orders"
+            );
+        }
+        for (order, result) in orders.iter_mut().zip(results) {
+            // Rejections are recorded on the order itself
+            let _ = Self::apply_ack(order, result);
+            self.persist(order).await?;
+        }
+        Ok(orders)
+    }
+
+    /// Replace the price, size or type of a tracked resting order
+    pub async fn modify(
+        &self,
+        client_order_id: &str,
+        request: OrderRequest,
+    ) -> Result<ManagedOrder, ExecutionError> {
+        let order = self.tracked(client_order_id).await?;
+        if order.state.is_terminal() {
+            return Err(ExecutionError::InvalidTransition(format!(
+                "order {} is {}",
+                client_order_id,
+                order.state.as_str()
+            )));
+        }
+        let exchange_order_id = order.exchange_order_id.ok_or_else(|| {
+            ExecutionError::InvalidTransition(format!(
+                "order {} has no exchange order id yet",
+                client_order_id
+            ))
+        })?;
+
+        let mut request = request;
+        request.client_order_id = order.request.client_order_id.clone();
+        let ack = self
+            .executor
+            .modify_order(exchange_order_id, &request)
+            .await?;
+        info!(client_order_id = %client_order_id, order_id = ack.order_id, size = request.size, "Modified order {}", client_order_id);
+        self.update(client_order_id, |order| {
+            order.request = request;
+            // Modifying a trigger order can give it a new exchange id
+            order.exchange_order_id = ack.order_id.or(order.exchange_order_id);
+            order.updated_at = Utc::now();
+            Ok(())
+        })
+        .await
+    }
+
+    /// Cancel a tracked order
+    pub async fn cancel(&self, client_order_id: &str) -> Result<ManagedOrder, ExecutionError> {
+        let order = self.order(client_order_id).await.ok_or_else(|| {
+            ExecutionError::InvalidOrder(format!("Unknown client order id {}", client_order_id))
+        })?;
+        if order.state.is_terminal() {
+            return Ok(order);
+        }
+        let exchange_order_id = order.exchange_order_id.ok_or_else(|| {
+            ExecutionError::InvalidTransition(format!(
+                "order {} has no exchange order id yet",
+                client_order_id
+            ))
+        })?;
+
+        self.executor
+            .cancel_order(&order.request.symbol, exchange_order_id)
+            .await?;
+        info!(client_order_id = %client_order_id, order_id = exchange_order_id, "Cancelled order {}", client_order_id);
+        self.update(client_order_id, |order| {
+            order.cancel(Utc::now());
+            Ok(())
+        })
+        .await
+    }
+
+    /// Query the exchange for a single tracked order and apply its state
+    pub async fn refresh(&self, client_order_id: &str) -> Result<ManagedOrder, ExecutionError> {
+        if let Some(report) = self.executor.order_status(client_order_id).await? {
+            if let Some(order) = self.apply_order_report(&report).await? {
+                return Ok(order);
+            }
+        }
+        self.tracked(client_order_id).await
+    }
+
+    /// Apply a fill reported by the exchange to the order it belongs to.
+    ///
+    /// Returns the updated order, or `None` for fills of untracked orders and
+    /// fills that were already applied.
+    pub async fn apply_fill(
+        &self,
+        fill: &ExchangeFill,
+    ) -> Result<Option<ManagedOrder>, ExecutionError> {
+        let order = {
+            let mut orders = self.orders.write().await;
+            let Some(order) = Self::find_mut(&mut orders, fill) else {
+                return Ok(None);
+            };
+            if !order.apply_fill(fill) {
+                return Ok(None);
+            }
+            order.clone()
+        };
+
+        let record = OrderFill {
+            client_order_id: order.client_order_id.clone(),
+            origin: order.origin,
+            fill: fill.clone(),
+        };
+        self.store
+            .record_fill(&record)
+            .await
+            .map_err(|e| ExecutionError::Storage(e.to_string()))?;
+        self.persist(&order).await?;
+        Ok(Some(order))
+    }
+
+    /// Apply an order status pushed by the exchange.
+    ///
+    /// Returns the updated order, or `None` if the order is not tracked.
+    pub async fn apply_order_report(
+        &self,
+        report: &ExchangeOrderReport,
+    ) -> Result<Option<ManagedOrder>, ExecutionError> {
+        let client_order_id = {
+            let orders = self.orders.read().await;
+            Self::find_key(&orders, report.client_order_id.as_deref(), report.order_id)
+        };
+        let Some(client_order_id) = client_order_id else {
+            return Ok(None);
+        };
+        let order = self
+            .update(&client_order_id, |order| {
+                Self::sync_with_report(order, report, Utc::now())
+            })
+            .await?;
+        Ok(Some(order))
+    }
+
+    /// Apply an order update or fill from the exchange's user event stream.
+    ///
+    /// Other events are ignored and return `None`.
+    pub async fn apply_user_event(
+        &self,
+        event: &UserEvent,
+    ) -> Result<Option<ManagedOrder>, ExecutionError> {
+        match event {
+            UserEvent::OrderUpdate(report) => self.apply_order_report(report).await,
+            UserEvent::Fill(fill) => self.apply_fill(fill).await,
+            UserEvent::Funding(_) | UserEvent::Account(_) => Ok(None),
+        }
+    }
+
+    /// Apply user events until the channel closes, reconciling if events were missed
+    pub async fn follow_user_events(&self, mut events: broadcast::Receiver<UserEvent>) {
+        loop {
+            match events.recv().await {
+                Ok(event) => {
+                    if let Err(e) = self.apply_user_event(&event).await {
+                        error!(error = %e, "Failed to apply user event");
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    warn!(missed = missed, "Missed {} user events, reconciling", missed);
+                    if let Err(e) = self.reconcile().await {
+                        error!(error = %e, "Order reconciliation failed");
+                    }
+                }
+                Err(broadcast::error::RecvError::Closed) => {
+                    info!("User event stream closed");
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Bring tracked orders in line with the exchange: apply missed fills,
+    /// pick up acknowledgements, cancellations and rejections, and report
+    /// open exchange orders the OMS does not know about
+    pub async fn reconcile(&self) -> Result<ReconcileReport, ExecutionError> {
+        let started = Utc::now();
+        let mut report = ReconcileReport::default();
+        let before: HashMap<String, (OrderState, f64)> = self
+            .orders
+            .read()
+            .await
+            .values()
+            .filter(|o| !o.state.is_terminal())
+            .map(|o| (o.client_order_id.clone(), (o.state, o.filled_size)))
+            .collect();
+
+        // Fills first, so state checks below see up-to-date filled sizes
+        let since = self.fill_sync_start().await;
+        if let Some(since) = since {
+            for fill in self.executor.fills_since(since).await? {
+                if self.apply_fill(&fill).await?.is_some() {
+                    report.fills += 1;
+                }
+            }
+        }
+        *self.last_fill_sync.write().await =
+            Some(started - ChronoDuration::seconds(FILL_SYNC_OVERLAP_SECONDS));
+
+        let exchange_open = self.executor.open_orders().await?;
+        let open_by_id: HashMap<u64, f64> =
+            exchange_open.iter().map(|o| (o.order_id, o.size)).collect();
+        let open_by_cloid: HashMap<String, u64> = exchange_open
+            .iter()
+            .filter_map(|o| Some((o.client_order_id.as_ref()?.to_lowercase(), o.order_id)))
+            .collect();
+
+        report.checked = before.len();
+        let pending = self.open_orders().await;
+        let mut tracked_ids: HashSet<u64> = HashSet::new();
+        for order in pending {
+            let exchange_id = order
+                .exchange_order_id
+                .or_else(|| open_by_cloid.get(&order.client_order_id).copied());
+            if let Some(id) = exchange_id {
+                tracked_ids.insert(id);
+            }
+
+            match exchange_id.and_then(|id| open_by_id.get(&id).map(|size| (id, *size))) {
+                Some((id, remaining)) => {
+                    self.update(&order.client_order_id, |order| {
+                        let now = Utc::now();
+                        order.exchange_order_id = Some(id);
+                        if order.state.is_pending() {
+                            order.transition(OrderState::Acknowledged, now)?;
+                        }
+                        let filled = order.request.size - remaining;
+                        if filled > 0.0 {
+                            order.sync_filled_size(filled, None, now);
+                        }
+                        Ok(())
+                    })
+                    .await?
+                }
+                None => match self.executor.order_status(&order.client_order_id).await? {
+                    Some(status) => {
+                        tracked_ids.insert(status.order_id);
+                        self.update(&order.client_order_id, |order| {
+                            Self::sync_with_report(order, &status, Utc::now())
+                        })
+                        .await?
+                    }
+                    None => {
+                        let age = (Utc::now() - order.created_at).to_std().unwrap_or_default();
+                        if age < self.unknown_order_grace || self.is_waiting(&order).await {
+                            continue;
+                        }
+                        warn!(client_order_id = %order.client_order_id, "Order {} unknown to the exchange, marking rejected", order.client_order_id);
+                        self.update(&order.client_order_id, |order| {
+                            order.reject("Not found on exchange", Utc::now());
+                            Ok(())
+                        })
+                        .await?
+                    }
+                },
+            };
+        }
+        {
+            let orders = self.orders.read().await;
+            report.updated = before
+                .iter()
+                .filter(|(id, (state, filled))| {
+                    orders
+                        .get(*id)
+                        .is_some_and(|o| o.state != *state || o.filled_size != *filled)
+                })
+                .count();
+        }
+
+        // Terminal orders can still have exchange ids that are reported open
+        // briefly (e.g. a cancel racing a fill); only flag ids the OMS never saw
+        let known_ids: HashSet<u64> = self
+            .orders
+            .read()
+            .await
+            .values()
+            .filter_map(|o| o.exchange_order_id)
+            .collect();
+        report.untracked = exchange_open
+            .iter()
+            .map(|o| o.order_id)
+            .filter(|id| !tracked_ids.contains(id) && !known_ids.contains(id))
+            .collect();
+        if !report.untracked.is_empty() {
+            warn!(orders = ?report.untracked, "{} open exchange orders are not tracked by the OMS", report.untracked.len());
+        }
+
+        Ok(report)
+    }
+
+    /// Reconcile until the task is cancelled
+    pub async fn run(&self) {
+        loop {
+            tokio::time::sleep(self.reconcile_interval).await;
+            match self.reconcile().await {
+                Ok(report) if report.updated > 0 || report.fills > 0 => {
+                    info!(
+                        checked = report.checked,
+                        updated = report.updated,
+                        fills = report.fills,
+                        "Reconciled orders: {} updated, {} fills",
+                        report.updated,
+                        report.fills
+                    );
+                }
+                Ok(_) => {}
+                Err(e) => error!(error = %e, "Order reconciliation failed"),
+            }
+        }
+    }
+
+    async fn tracked(&self, client_order_id: &str) -> Result<ManagedOrder, ExecutionError> {
+        self.order(client_order_id).await.ok_or_else(|| {
+            ExecutionError::InvalidOrder(format!("Unknown client order id {}", client_order_id))
+        })
+    }
+
+    /// Whether a grouped TP/SL leg is still waiting for its entry, so the
+    /// exchange does not know it yet
+    async fn is_waiting(&self, order: &ManagedOrder) -> bool {
+        let Some(parent) = order.parent_order_id.as_ref() else {
+            return false;
+        };
+        order.exchange_order_id.is_none()
+            && self
+                .orders
+                .read()
+                .await
+                .get(parent)
+                .is_some_and(|entry| !entry.state.is_terminal())
+    }
+
+    /// Start of the next fill query: the previous sync, or the oldest order
+    /// still waiting for fill records
+    async fn fill_sync_start(&self) -> Option<DateTime<Utc>> {
+        let oldest = self
+            .orders
+            .read()
+            .await
+            .values()
+            .filter(|o| o.awaiting_fills())
+            .map(|o| o.created_at)
+            .min();
+        match *self.last_fill_sync.read().await {
+            Some(last) => Some(oldest.map_or(last, |oldest| oldest.min(last))),
+            None => oldest,
+        }
+    }
+
+    /// Apply the exchange's answer to a submitted order.
+    ///
+    /// Exchange rejections are recorded on the order and returned; other
+    /// errors leave it `Submitted`.
+    fn apply_ack(
+        order: &mut ManagedOrder,
+        result: Result<OrderAck, ExecutionError>,
+    ) -> Result<(), ExecutionError> {
+        let now = Utc::now();
+        match result {
+            Ok(ack) => {
+                order.exchange_order_id = ack.order_id;
+                order.transition(OrderState::Acknowledged, now)?;
+                if ack.status == OrderStatus::Filled || ack.filled_size > 0.0 {
+                    order.sync_filled_size(ack.filled_size, ack.avg_fill_price, now);
+                }
+                info!(
+                    client_order_id = %order.client_order_id,
+                    order_id = ack.order_id,
+                    symbol = %order.request.symbol,
+                    state = order.state.as_str(),
+                    "Order {} {}",
+                    order.client_order_id,
+                    order.state.as_str()
+                );
+                Ok(())
+            }
+            Err(
+                e @ (ExecutionError::Rejected(_)
+                | ExecutionError::InvalidOrder(_)
+                | ExecutionError::UnknownSymbol(_)
+                | ExecutionError::RiskRejected(_)),
+            ) => {
+                warn!(client_order_id = %order.client_order_id, error = %e, "Order {} rejected", order.client_order_id);
+                order.reject(e.to_string(), now);
+                Err(e)
+            }
+            Err(e) => {
+                // The request may or may not have reached the exchange
+                warn!(client_order_id = %order.client_order_id, error = %e, "Order {} submission outcome unknown", order.client_order_id);
+                Err(e)
+            }
+        }
+    }
+
+    fn find_mut<'a>(
+        orders: &'a mut HashMap<String, ManagedOrder>,
+        fill: &ExchangeFill,
+    ) -> Option<&'a mut ManagedOrder> {
+        let key = Self::find_key(orders, fill.client_order_id.as_deref(), fill.order_id)?;
+        orders.get_mut(&key)
+    }
+
+    /// Client order id of the tracked order with the given client or exchange id
+    fn find_key(
+        orders: &HashMap<String, ManagedOrder>,
+        client_order_id: Option<&str>,
+        order_id: u64,
+    ) -> Option<String> {
+        if let Some(cloid) = client_order_id {
+            let cloid = cloid.to_lowercase();
+            if orders.contains_key(&cloid) {
+                return Some(cloid);
+            }
+        }
+        orders
+            .values()
+            .find(|o| o.exchange_order_id == Some(order_id))
+            .map(|o| o.client_order_id.clone())
+    }
+
+    /// Move an order to the state the exchange reports for it
+    fn sync_with_report(
+        order: &mut ManagedOrder,
+        report: &ExchangeOrderReport,
+        now: DateTime<Utc>,
+    ) -> Result<(), ExecutionError> {
+        order.exchange_order_id = Some(report.order_id);
+        if order.state.is_pending() {
+            order.transition(OrderState::Acknowledged, now)?;
+        }
+        order.sync_filled_size(report.filled_size(), None, now);
+        match report.state {
+            ExchangeOrderState::Open | ExchangeOrderState::Filled => {}
+            ExchangeOrderState::Cancelled => order.cancel(now),
+            ExchangeOrderState::Rejected => order.reject("Rejected by exchange", now),
+        }
+        Ok(())
+    }
+
+    /// Apply `change` to a tracked order and persist the result if it changed
+    async fn update<F>(
+        &self,
+        client_order_id: &str,
+        change: F,
+    ) -> Result<ManagedOrder, ExecutionError>
+    where
+        F: FnOnce(&mut ManagedOrder) -> Result<(), ExecutionError>,
+    {
+        let (before, after) = {
+            let mut orders = self.orders.write().await;
+            let order = orders.get_mut(client_order_id).ok_or_else(|| {
+                ExecutionError::InvalidOrder(format!("Unknown client order id {}", client_order_id))
+            })?;
+            let before = order.clone();
+            change(order)?;
+            (before, order.clone())
+        };
+        if after != before {
+            self.store
+                .save_order(&after)
+                .await
+                .map_err(|e| ExecutionError::Storage(e.to_string()))?;
+        }
+        Ok(after)
+    }
+
+    async fn persist(&self, order: &ManagedOrder) -> Result<(), ExecutionError> {
+        self.orders
+            .write()
+            .await
+            .insert(order.client_order_id.clone(), order.clone());
+        self.store
+            .save_order(order)
+            .await
+            .map_err(|e| ExecutionError::Storage(e.to_string()))
+    }
+}
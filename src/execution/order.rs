@@ -0,0 +1,257 @@
+//! Exchange-agnostic order, order state and position types
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+pub enum OrderSide {
+    Buy,
+    Sell,
+}
+
+impl OrderSide {
+    pub fn is_buy(&self) -> bool {
+        *self == OrderSide::Buy
+    }
+
+    pub fn opposite(&self) -> Self {
+        match self {
+            OrderSide::Buy => OrderSide::Sell,
+            OrderSide::Sell => OrderSide::Buy,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimeInForce {
+    /// Good till cancelled
+    Gtc,
+    /// Immediate or cancel
+    Ioc,
+    /// Add liquidity only (post-only)
+    Alo,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum OrderType {
+    Limit(TimeInForce),
+    /// Fills immediately at `price` or better
+    Market,
+    /// Market order triggered when the mark price crosses `trigger_price`
+    StopMarket {
+        trigger_price: f64,
+    },
+    TakeProfitMarket {
+        trigger_price: f64,
+    },
+    /// Limit order at `price` triggered when the mark price crosses `trigger_price`
+    TakeProfitLimit {
+        trigger_price: f64,
+    },
+}
+
+impl OrderType {
+    /// Trigger price of stop and take-profit orders
+    pub fn trigger_price(&self) -> Option<f64> {
+        match self {
+            OrderType::StopMarket { trigger_price }
+            | OrderType::TakeProfitMarket { trigger_price }
+            | OrderType::TakeProfitLimit { trigger_price } => Some(*trigger_price),
+            OrderType::Limit(_) | OrderType::Market => None,
+        }
+    }
+}
+
+/// How orders submitted together relate to each other
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum OrderGrouping {
+    /// Independent orders
+    #[default]
+    None,
+    /// An entry followed by its stop loss and take profit, which activate
+    /// once the entry fills and are sized to it
+    NormalTpsl,
+    /// Stop loss and take profit of the whole position, resized by the
+    /// exchange as the position changes and cancelled when it closes
+    PositionTpsl,
+}
+
+/// Order to submit to an exchange.
+///
+/// For market and trigger orders `price` is the worst acceptable fill price.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderRequest {
+    pub symbol: String,
+    pub side: OrderSide,
+    /// Size in base units
+    pub size: f64,
+    pub price: f64,
+    pub order_type: OrderType,
+    pub reduce_only: bool,
+    pub client_order_id: Option<String>,
+}
+
+impl OrderRequest {
+    /// Good-till-cancelled limit order
+    pub fn limit(symbol: &str, side: OrderSide, size: f64, price: f64) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            side,
+            size,
+            price,
+            order_type: OrderType::Limit(TimeInForce::Gtc),
+            reduce_only: false,
+            client_order_id: None,
+        }
+    }
+
+    /// Market order filling no worse than `worst_price`
+    pub fn market(symbol: &str, side: OrderSide, size: f64, worst_price: f64) -> Self {
+        Self {
+            order_type: OrderType::Market,
+            ..Self::limit(symbol, side, size, worst_price)
+        }
+    }
+
+    pub fn with_order_type(mut self, order_type: OrderType) -> Self {
+        self.order_type = order_type;
+        self
+    }
+
+    pub fn with_reduce_only(mut self, reduce_only: bool) -> Self {
+        self.reduce_only = reduce_only;
+        self
+    }
+
+    pub fn with_client_order_id(mut self, client_order_id: impl Into<String>) -> Self {
+        self.client_order_id = Some(client_order_id.into());
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderStatus {
+    /// Accepted and resting on the book (or waiting for its trigger)
+    Resting,
+    Filled,
+    /// Accepted as a grouped TP/SL leg waiting for its entry to fill; the
+    /// exchange assigns no order id until the leg is placed
+    Waiting,
+}
+
+/// Exchange acknowledgement of a placed or modified order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderAck {
+    /// `None` while the order is [`OrderStatus::Waiting`]
+    pub order_id: Option<u64>,
+    pub status: OrderStatus,
+    pub filled_size: f64,
+    pub avg_fill_price: Option<f64>,
+}
+
+/// Order currently resting on the exchange
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpenOrder {
+    pub order_id: u64,
+    pub symbol: String,
+    pub side: OrderSide,
+    /// Remaining size in base units
+    pub size: f64,
+    pub price: f64,
+    pub client_order_id: Option<String>,
+    #[serde(default)]
+    pub reduce_only: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Open position as reported by the exchange
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExchangePosition {
+    pub symbol: String,
+    /// Signed size in base units (negative when short)
+    pub size: f64,
+    pub entry_price: f64,
+    pub unrealized_pnl: f64,
+    pub leverage: f64,
+    pub liquidation_price: Option<f64>,
+}
+
+/// Best bid and ask of an order book
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TopOfBook {
+    pub bid: f64,
+    pub ask: f64,
+}
+
+impl TopOfBook {
+    /// Price that joins the book on `side` without crossing
+    pub fn touch(&self, side: OrderSide) -> f64 {
+        match side {
+            OrderSide::Buy => self.bid,
+            OrderSide::Sell => self.ask,
+        }
+    }
+
+    /// Price that takes liquidity for `side`
+    pub fn far_touch(&self, side: OrderSide) -> f64 {
+        match side {
+            OrderSide::Buy => self.ask,
+            OrderSide::Sell => self.bid,
+        }
+    }
+}
+
+/// Lifecycle state of an order as reported by the exchange
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExchangeOrderState {
+    /// Resting on the book or waiting for its trigger
+    Open,
+    Filled,
+    Cancelled,
+    Rejected,
+}
+
+/// Exchange view of a single order, looked up by client order id
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExchangeOrderReport {
+    pub order_id: u64,
+    pub client_order_id: Option<String>,
+    pub symbol: String,
+    pub state: ExchangeOrderState,
+    pub original_size: f64,
+    pub remaining_size: f64,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ExchangeOrderReport {
+    pub fn filled_size(&self) -> f64 {
+        (self.original_size - self.remaining_size).max(0.0)
+    }
+}
+
+/// Execution of (part of) an order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExchangeFill {
+    /// Exchange trade id, unique per fill
+    pub trade_id: u64,
+    pub order_id: u64,
+    pub client_order_id: Option<String>,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub size: f64,
+    pub price: f64,
+    pub fee: f64,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Funding paid or received on a position
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FundingPayment {
+    pub symbol: String,
+    /// Signed position size the funding applied to
+    pub position_size: f64,
+    pub rate: f64,
+    /// Received (positive) or paid (negative), in quote currency
+    pub amount: f64,
+    pub timestamp: DateTime<Utc>,
+}
@@ -0,0 +1,264 @@
+//! Per-strategy rules for turning signals into orders
+//!
+//! An [`ExecutionPolicy`] is part of a strategy's configuration. Strategies
+//! without one are never traded. [`ExecutionPolicy::decide`] maps a signal and
+//! the current position to a [`SignalAction`]; the policy engine turns that
+//! action into orders.
+
+use crate::execution::order::OrderSide;
+use crate::models::signal::{SignalDirection, SignalOutput};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// What to do when a signal points against the open position
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "PascalCase")]
+pub enum OppositeSignalAction {
+    /// Close the position and open one in the signal's direction
+    #[default]
+    Flip,
+    /// Close the position without opening a new one
+    CloseOnly,
+    /// Keep the position
+    Ignore,
+}
+
+/// How entry orders are placed
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "PascalCase")]
+pub enum EntryOrderType {
+    /// Immediate-or-cancel at the signal price plus slippage
+    #[default]
+    Market,
+    /// Resting limit order at the current mid price
+    LimitAtMid,
+    /// Add-liquidity-only order at the mid price, cancelled if not filled in time
+    PostOnly { timeout_seconds: u64 },
+}
+
+/// How stop loss and take profit orders are attached to entries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "PascalCase")]
+pub enum BracketGrouping {
+    /// Placed as independent reduce-only orders once the entry fills
+    #[default]
+    Separate,
+    /// Placed once the entry fills as TP/SL of the whole position, which the
+    /// exchange resizes as the position changes
+    Position,
+    /// Sent together with a market entry and activated when it fills
+    Entry,
+}
+
+/// Translation of a strategy's signals into orders
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ExecutionPolicy {
+    /// Notional of each entry in quote currency
+    pub order_notional: f64,
+    /// Signals below this confidence (0-1) are ignored
+    #[serde(default = "ExecutionPolicy::default_min_confidence")]
+    pub min_confidence: f64,
+    #[serde(default)]
+    pub on_opposite_signal: OppositeSignalAction,
+    /// Close the position on a Neutral signal
+    #[serde(default = "ExecutionPolicy::default_true")]
+    pub close_on_neutral: bool,
+    /// Maximum entries in the same direction; 1 disables pyramiding
+    #[serde(default = "ExecutionPolicy::default_max_entries")]
+    pub max_entries: u32,
+    #[serde(default)]
+    pub entry_order: EntryOrderType,
+    /// Worst price offset of market and trigger orders, as a fraction of the price
+    #[serde(default = "ExecutionPolicy::default_slippage")]
+    pub slippage: f64,
+    /// Place the signal's recommended stop loss as a native stop-market order
+    #[serde(default = "ExecutionPolicy::default_true")]
+    pub place_stop_loss: bool,
+    /// Place the signal's recommended take profit as a native take-profit order
+    #[serde(default = "ExecutionPolicy::default_true")]
+    pub place_take_profit: bool,
+    /// Place the take profit as a limit order at its trigger price instead of
+    /// a market order
+    #[serde(default)]
+    pub take_profit_limit: bool,
+    #[serde(default)]
+    pub bracket_grouping: BracketGrouping,
+    /// Move the stop loss to the entry price once the price has moved this many
+    /// percent in the position's favour
+    #[serde(default)]
+    pub break_even_after_pct: Option<f64>,
+    /// Signals older than this are not acted on
+    #[serde(default = "ExecutionPolicy::default_max_signal_age")]
+    pub max_signal_age_seconds: u64,
+}
+
+impl ExecutionPolicy {
+    fn default_min_confidence() -> f64 {
+        0.5
+    }
+
+    fn default_true() -> bool {
+        true
+    }
+
+    fn default_max_entries() -> u32 {
+        1
+    }
+
+    fn default_slippage() -> f64 {
+        0.01
+    }
+
+    fn default_max_signal_age() -> u64 {
+        120
+    }
+
+    /// Policy with defaults for everything but the entry size
+    pub fn new(order_notional: f64) -> Self {
+        Self {
+            order_notional,
+            min_confidence: Self::default_min_confidence(),
+            on_opposite_signal: OppositeSignalAction::default(),
+            close_on_neutral: true,
+            max_entries: Self::default_max_entries(),
+            entry_order: EntryOrderType::default(),
+            slippage: Self::default_slippage(),
+            place_stop_loss: true,
+            place_take_profit: true,
+            take_profit_limit: false,
+            bracket_grouping: BracketGrouping::default(),
+            break_even_after_pct: None,
+            max_signal_age_seconds: Self::default_max_signal_age(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.order_notional.is_finite() || self.order_notional <= 0.0 {
+            return Err("order_notional must be positive".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.min_confidence) {
+            return Err("min_confidence must be between 0 and 1".to_string());
+        }
+        if self.max_entries == 0 {
+            return Err("max_entries must be at least 1".to_string());
+        }
+        if !(0.0..1.0).contains(&self.slippage) {
+            return Err("slippage must be between 0 and 1".to_string());
+        }
+        if self.bracket_grouping == BracketGrouping::Entry
+            && self.entry_order != EntryOrderType::Market
+        {
+            return Err("Entry bracket grouping requires Market entry orders".to_string());
+        }
+        if let Some(pct) = self.break_even_after_pct {
+            if !pct.is_finite() || pct <= 0.0 {
+                return Err("break_even_after_pct must be positive".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Decide how to act on a signal given the strategy's position.
+    ///
+    /// `position_size` is signed (negative for shorts) and `entries` counts the
+    /// entries making up the current position.
+    pub fn decide(
+        &self,
+        signal: &SignalOutput,
+        position_size: f64,
+        entries: u32,
+        now: DateTime<Utc>,
+    ) -> SignalAction {
+        let age = now.signed_duration_since(signal.timestamp).num_seconds();
+        if age > self.max_signal_age_seconds as i64 {
+            return SignalAction::Ignore(format!("signal is {}s old", age));
+        }
+
+        let side = match signal.direction {
+            SignalDirection::Long => OrderSide::Buy,
+            SignalDirection::Short => OrderSide::Sell,
+            SignalDirection::Neutral => {
+                return if position_size != 0.0 && self.close_on_neutral {
+                    SignalAction::Close
+                } else {
+                    SignalAction::Ignore("neutral signal".to_string())
+                };
+            }
+        };
+
+        if signal.confidence < self.min_confidence {
+            return SignalAction::Ignore(format!(
+                "confidence {:.2} below {:.2}",
+                signal.confidence, self.min_confidence
+            ));
+        }
+
+        if position_size == 0.0 {
+            return SignalAction::Open(side);
+        }
+
+        let position_side = if position_size > 0.0 {
+            OrderSide::Buy
+        } else {
+            OrderSide::Sell
+        };
+        if position_side == side {
+            return if entries < self.max_entries {
+                SignalAction::Add(side)
+            } else {
+                SignalAction::Ignore(format!("already {} entries", entries))
+            };
+        }
+
+        match self.on_opposite_signal {
+            OppositeSignalAction::Flip => SignalAction::Flip(side),
+            OppositeSignalAction::CloseOnly => SignalAction::Close,
+            OppositeSignalAction::Ignore => {
+                SignalAction::Ignore("opposite signal ignored".to_string())
+            }
+        }
+    }
+}
+
+/// Outcome of applying a policy to a signal
+#[derive(Debug, Clone, PartialEq)]
+pub enum SignalAction {
+    /// Open a new position
+    Open(OrderSide),
+    /// Add to the position in the same direction
+    Add(OrderSide),
+    /// Close the position
+    Close,
+    /// Close the position and open one on the given side
+    Flip(OrderSide),
+    /// Do nothing, with the reason
+    Ignore(String),
+}
+
+impl SignalAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SignalAction::Open(_) => "open",
+            SignalAction::Add(_) => "add",
+            SignalAction::Close => "close",
+            SignalAction::Flip(_) => "flip",
+            SignalAction::Ignore(_) => "ignore",
+        }
+    }
+
+    /// Side of the entry order, if the action opens or adds to a position
+    pub fn entry_side(&self) -> Option<OrderSide> {
+        match self {
+            SignalAction::Open(side) | SignalAction::Add(side) | SignalAction::Flip(side) => {
+                Some(*side)
+            }
+            SignalAction::Close | SignalAction::Ignore(_) => None,
+        }
+    }
+
+    /// Whether the current position is closed first
+    pub fn closes_position(&self) -> bool {
+        matches!(self, SignalAction::Close | SignalAction::Flip(_))
+    }
+}
@@ -0,0 +1,835 @@
+//! Places the orders a strategy's execution policy asks for
+//!
+//! [`PolicyEngine`] sits between stored signals and the order management
+//! system. For each signal it looks at the strategy's position, applies the
+//! policy and submits the close, entry and protective orders. Stop-loss and
+//! take-profit orders are placed once the entry has filled, or together with
+//! it as an exchange-side bracket, and unfilled post-only entries are
+//! cancelled when their timeout expires. Placed brackets follow the position:
+//! legs are shrunk when it is partly closed, cancelled when it is closed and
+//! the stop moves to break-even when the policy asks for it. All of this is
+//! handled by [`PolicyEngine::run`]. Each entry's [`EntryPlan`] is persisted
+//! with the order, so [`PolicyEngine::restore`] picks the work up again after
+//! a restart.
+
+use crate::execution::error::ExecutionError;
+use crate::execution::oms::OrderManager;
+use crate::execution::order::{OrderGrouping, OrderRequest, OrderSide, OrderType, TimeInForce};
+use crate::execution::policy::{BracketGrouping, EntryOrderType, ExecutionPolicy, SignalAction};
+use crate::execution::state::{EntryPlan, ManagedOrder, OrderOrigin, OrderState};
+use crate::models::signal::SignalOutput;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use sha3::{Digest, Keccak256};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::{debug, info, warn};
+
+/// Orders placed for one signal
+#[derive(Debug, Clone)]
+pub struct SignalExecution {
+    pub action: SignalAction,
+    pub orders: Vec<ManagedOrder>,
+}
+
+/// Stop loss and take profit protecting a filled entry
+#[derive(Debug, Clone)]
+struct Bracket {
+    strategy_id: i64,
+    symbol: String,
+    /// Side of the entry
+    side: OrderSide,
+    entry_price: f64,
+    legs: Vec<String>,
+    grouping: BracketGrouping,
+    break_even_after_pct: Option<f64>,
+    slippage: f64,
+    /// Brackets are only managed once their position has been seen, so a
+    /// position update that lags the entry fill does not cancel them
+    position_seen: bool,
+    at_break_even: bool,
+}
+
+/// Position size differences below this are rounding
+const SIZE_TOLERANCE: f64 = 1e-9;
+
+/// Entries making up a strategy's position in one symbol
+#[derive(Debug, Clone, Copy)]
+struct EntryCount {
+    side: OrderSide,
+    entries: u32,
+}
+
+pub struct PolicyEngine {
+    oms: Arc<OrderManager>,
+    entries: Mutex<HashMap<(i64, String), EntryCount>>,
+    /// Entry client order id → protective orders to place once it fills
+    pending_protection: Mutex<HashMap<String, EntryPlan>>,
+    /// Entry client order id → its placed stop loss and take profit
+    brackets: Mutex<HashMap<String, Bracket>>,
+    /// Post-only entry client order id → cancel deadline
+    expiries: Mutex<HashMap<String, DateTime<Utc>>>,
+    sweep_interval: Duration,
+}
+
+/// Deterministic client order id for one leg of a signal's orders, so a
+/// retried signal resolves to the orders already submitted
+pub fn signal_client_order_id(strategy_id: i64, signal_id: i64, leg: &str) -> String {
+    let mut hasher = Keccak256::new();
+    hasher.update(strategy_id.to_be_bytes());
+    hasher.update(signal_id.to_be_bytes());
+    hasher.update(leg.as_bytes());
+    let hash = hasher.finalize();
+    format!("0x{}", hex::encode(&hash[..16]))
+}
+
+fn signed(side: OrderSide, size: f64) -> f64 {
+    if side.is_buy() {
+        size
+    } else {
+        -size
+    }
+}
+
+/// Price moved by `offset` (a fraction) in the direction that is worse for `side`
+fn worst_price(side: OrderSide, price: f64, offset: f64) -> f64 {
+    if side.is_buy() {
+        price * (1.0 + offset)
+    } else {
+        price * (1.0 - offset)
+    }
+}
+
+impl PolicyEngine {
+    pub fn new(oms: Arc<OrderManager>) -> Self {
+        Self {
+            oms,
+            entries: Mutex::new(HashMap::new()),
+            pending_protection: Mutex::new(HashMap::new()),
+            brackets: Mutex::new(HashMap::new()),
+            expiries: Mutex::new(HashMap::new()),
+            sweep_interval: Duration::from_secs(5),
+        }
+    }
+
+    /// How often [`run`](Self::run) checks pending protection, brackets and
+    /// post-only timeouts
+    pub fn with_sweep_interval(mut self, interval: Duration) -> Self {
+        self.sweep_interval = interval;
+        self
+    }
+
+    pub fn oms(&self) -> &Arc<OrderManager> {
+        &self.oms
+    }
+
+    /// Pick up the entries placed before a restart from the orders the OMS
+    /// restored: protect filled entries that have no stop loss or take profit
+    /// yet, manage the brackets already placed, cancel post-only entries on
+    /// their deadline and count the entries behind open positions.
+    ///
+    /// Call after [`OrderManager::restore`].
+    pub async fn restore(&self) -> Result<(), ExecutionError> {
+        let orders = self.oms.orders().await;
+        let positions = self.oms.executor().positions().await?;
+        let position_size = |symbol: &str| {
+            positions
+                .iter()
+                .find(|p| p.symbol == symbol)
+                .map_or(0.0, |p| p.size)
+        };
+
+        // Entries since each strategy's last close, in one direction
+        let mut open_entries: HashMap<(i64, String), Vec<&ManagedOrder>> = HashMap::new();
+        for order in &orders {
+            let (Some(strategy_id), Some(signal_id)) =
+                (order.origin.strategy_id, order.origin.signal_id)
+            else {
+                continue;
+            };
+            let key = (strategy_id, order.symbol().to_string());
+            if order.client_order_id == signal_client_order_id(strategy_id, signal_id, "close") {
+                open_entries.remove(&key);
+            } else if order.entry_plan.is_some() {
+                let entries = open_entries.entry(key).or_default();
+                if entries.first().is_some_and(|e| e.side() != order.side()) {
+                    entries.clear();
+                }
+                entries.push(order);
+            }
+        }
+
+        let (mut pending, mut brackets) = (0, 0);
+        for (key, entries) in &open_entries {
+            let filled = entries.iter().filter(|e| e.filled_size > 0.0).count() as u32;
+            if filled > 0 {
+                self.entries.lock().await.insert(
+                    key.clone(),
+                    EntryCount {
+                        side: entries[0].side(),
+                        entries: filled,
+                    },
+                );
+            }
+        }
+
+        for entry in &orders {
+            let Some(plan) = entry.entry_plan.as_ref() else {
+                continue;
+            };
+            if let (Some(deadline), false) = (plan.expires_at, entry.state.is_terminal()) {
+                self.expiries
+                    .lock()
+                    .await
+                    .insert(entry.client_order_id.clone(), deadline);
+            }
+            if !plan.is_protected() {
+                continue;
+            }
+            let (strategy_id, signal_id) = (
+                entry.origin.strategy_id.unwrap_or_default(),
+                entry.origin.signal_id.unwrap_or_default(),
+            );
+            let mut legs = Vec::new();
+            for leg in ["sl", "tp"] {
+                legs.extend(
+                    self.oms
+                        .order(&signal_client_order_id(strategy_id, signal_id, leg))
+                        .await,
+                );
+            }
+
+            if legs.is_empty() {
+                // Only entries behind the open position still need protecting
+                let open = open_entries
+                    .get(&(strategy_id, entry.symbol().to_string()))
+                    .is_some_and(|entries| {
+                        entries
+                            .iter()
+                            .any(|e| e.client_order_id == entry.client_order_id)
+                    });
+                let filled_and_open = entry.filled_size > 0.0
+                    && signed(entry.side(), position_size(entry.symbol())) > SIZE_TOLERANCE;
+                if open && (!entry.state.is_terminal() || filled_and_open) {
+                    self.pending_protection
+                        .lock()
+                        .await
+                        .insert(entry.client_order_id.clone(), plan.clone());
+                    pending += 1;
+                }
+                continue;
+            }
+
+            let entry_price = entry.avg_fill_price.unwrap_or(entry.request.price);
+            self.track_bracket(entry, entry_price, plan, &legs).await;
+            if let Some(bracket) = self.brackets.lock().await.get_mut(&entry.client_order_id) {
+                // Positions come straight from the exchange after a restart, so
+                // a filled entry without one has been closed
+                bracket.position_seen = entry.filled_size > 0.0;
+                brackets += 1;
+            }
+        }
+
+        info!(
+            pending = pending,
+            brackets = brackets,
+            "Restored {} entries awaiting protection and {} brackets",
+            pending,
+            brackets
+        );
+        Ok(())
+    }
+
+    /// Apply a strategy's policy to a signal and submit the resulting orders
+    pub async fn execute(
+        &self,
+        strategy_id: i64,
+        policy: &ExecutionPolicy,
+        signal: &SignalOutput,
+    ) -> Result<SignalExecution, ExecutionError> {
+        let symbol = signal.symbol.as_str();
+        let signal_id = signal.timestamp.timestamp_millis();
+        let origin = OrderOrigin::new(strategy_id, signal_id);
+
+        // A retried job must not act on the position its first run created
+        for leg in ["close", "entry"] {
+            let cloid = signal_client_order_id(strategy_id, signal_id, leg);
+            if self.oms.order(&cloid).await.is_some() {
+                return Ok(SignalExecution {
+                    action: SignalAction::Ignore("signal already executed".to_string()),
+                    orders: Vec::new(),
+                });
+            }
+        }
+
+        let position_size = self
+            .oms
+            .executor()
+            .positions()
+            .await?
+            .into_iter()
+            .find(|p| p.symbol == symbol)
+            .map_or(0.0, |p| p.size);
+
+        // Resting entries count as exposure so a new signal does not stack on them
+        let resting: f64 = self
+            .strategy_orders(strategy_id, symbol)
+            .await
+            .iter()
+            .filter(|o| !o.request.reduce_only)
+            .map(|o| signed(o.side(), o.remaining_size()))
+            .sum();
+        let exposure = position_size + resting;
+        let entries = self.entry_count(strategy_id, symbol, exposure).await;
+
+        let action = policy.decide(signal, exposure, entries, Utc::now());
+        let mut execution = SignalExecution {
+            action: action.clone(),
+            orders: Vec::new(),
+        };
+        if let SignalAction::Ignore(reason) = &action {
+            debug!(
+                strategy_id = strategy_id,
+                symbol = symbol,
+                reason = %reason,
+                "Signal not acted on: {}",
+                reason
+            );
+            return Ok(execution);
+        }
+
+        if action.closes_position() {
+            self.cancel_strategy_orders(strategy_id, symbol).await;
+            if position_size != 0.0 {
+                let side = if position_size > 0.0 {
+                    OrderSide::Sell
+                } else {
+                    OrderSide::Buy
+                };
+                let request = OrderRequest::market(
+                    symbol,
+                    side,
+                    position_size.abs(),
+                    worst_price(side, signal.price, policy.slippage),
+                )
+                .with_reduce_only(true)
+                .with_client_order_id(signal_client_order_id(strategy_id, signal_id, "close"));
+                execution
+                    .orders
+                    .push(self.oms.submit(request, origin).await?);
+            }
+            self.entries
+                .lock()
+                .await
+                .remove(&(strategy_id, symbol.to_string()));
+        }
+
+        if let Some(side) = action.entry_side() {
+            let cloid = signal_client_order_id(strategy_id, signal_id, "entry");
+            let request = self.entry_request(policy, signal, side, cloid).await?;
+            let plan = EntryPlan {
+                stop_loss_pct: (policy.place_stop_loss && signal.recommended_sl_pct > 0.0)
+                    .then_some(signal.recommended_sl_pct),
+                take_profit_pct: (policy.place_take_profit && signal.recommended_tp_pct > 0.0)
+                    .then_some(signal.recommended_tp_pct),
+                take_profit_limit: policy.take_profit_limit,
+                grouping: policy.bracket_grouping,
+                break_even_after_pct: policy.break_even_after_pct,
+                slippage: policy.slippage,
+                expires_at: match policy.entry_order {
+                    EntryOrderType::PostOnly { timeout_seconds } => {
+                        Some(Utc::now() + ChronoDuration::seconds(timeout_seconds as i64))
+                    }
+                    _ => None,
+                },
+            };
+            let protected = plan.is_protected();
+
+            let (order, legs) = if protected && policy.bracket_grouping == BracketGrouping::Entry {
+                // Legs are priced off the signal; the exchange sizes them to the fill
+                let mut group = vec![request.clone()];
+                group.extend(Self::protective_orders(
+                    symbol,
+                    side,
+                    request.size,
+                    signal.price,
+                    origin,
+                    &plan,
+                ));
+                let mut orders = self
+                    .oms
+                    .submit_entry_group(group, OrderGrouping::NormalTpsl, origin, plan.clone())
+                    .await?
+                    .into_iter();
+                let order = orders.next().ok_or_else(|| {
+                    ExecutionError::InvalidResponse("Empty order group result".to_string())
+                })?;
+                if order.state == OrderState::Rejected {
+                    return Err(ExecutionError::Rejected(
+                        order.reject_reason.clone().unwrap_or_default(),
+                    ));
+                }
+                let legs: Vec<ManagedOrder> = orders.collect();
+                let entry_price = order.avg_fill_price.unwrap_or(signal.price);
+                self.track_bracket(&order, entry_price, &plan, &legs).await;
+                (order, legs)
+            } else {
+                (
+                    self.oms.submit_entry(request, origin, plan.clone()).await?,
+                    Vec::new(),
+                )
+            };
+            {
+                let mut counts = self.entries.lock().await;
+                let count = counts
+                    .entry((strategy_id, symbol.to_string()))
+                    .or_insert(EntryCount { side, entries: 0 });
+                if count.side != side {
+                    *count = EntryCount { side, entries: 0 };
+                }
+                count.entries += 1;
+            }
+
+            if let Some(deadline) = plan.expires_at {
+                self.expiries
+                    .lock()
+                    .await
+                    .insert(order.client_order_id.clone(), deadline);
+            }
+
+            if protected && policy.bracket_grouping != BracketGrouping::Entry {
+                self.pending_protection
+                    .lock()
+                    .await
+                    .insert(order.client_order_id.clone(), plan);
+            }
+            execution.orders.push(order);
+            execution.orders.extend(legs);
+            execution.orders.extend(self.sweep().await);
+        }
+
+        info!(
+            strategy_id = strategy_id,
+            symbol = symbol,
+            action = action.as_str(),
+            orders = execution.orders.len(),
+            "Signal for {} executed as {}",
+            symbol,
+            action.as_str()
+        );
+        Ok(execution)
+    }
+
+    /// Cancel expired post-only entries, protect filled entries and manage
+    /// placed brackets, returning the protective orders placed
+    pub async fn sweep(&self) -> Vec<ManagedOrder> {
+        let now = Utc::now();
+        let expired: Vec<String> = {
+            let mut expiries = self.expiries.lock().await;
+            let expired: Vec<String> = expiries
+                .iter()
+                .filter(|(_, deadline)| **deadline <= now)
+                .map(|(cloid, _)| cloid.clone())
+                .collect();
+            for cloid in &expired {
+                expiries.remove(cloid);
+            }
+            expired
+        };
+        for cloid in expired {
+            if let Some(order) = self.oms.order(&cloid).await {
+                if !order.state.is_terminal() {
+                    info!(client_order_id = %cloid, "Cancelling unfilled post-only entry {}", cloid);
+                    if let Err(e) = self.oms.cancel(&cloid).await {
+                        warn!(client_order_id = %cloid, error = %e, "Failed to cancel expired entry");
+                    }
+                }
+            }
+        }
+
+        let pending: Vec<(String, EntryPlan)> = self
+            .pending_protection
+            .lock()
+            .await
+            .iter()
+            .map(|(cloid, p)| (cloid.clone(), p.clone()))
+            .collect();
+        let mut placed = Vec::new();
+        for (cloid, plan) in pending {
+            let Some(entry) = self.oms.order(&cloid).await else {
+                self.pending_protection.lock().await.remove(&cloid);
+                continue;
+            };
+            if !entry.state.is_terminal() {
+                continue;
+            }
+            self.pending_protection.lock().await.remove(&cloid);
+            if entry.filled_size <= 0.0 {
+                continue;
+            }
+            placed.extend(self.protect(&entry, &plan).await);
+        }
+        self.manage_brackets().await;
+        placed
+    }
+
+    /// Sweep until the task is cancelled
+    pub async fn run(&self) {
+        let mut interval = tokio::time::interval(self.sweep_interval);
+        loop {
+            interval.tick().await;
+            self.sweep().await;
+        }
+    }
+
+    async fn entry_request(
+        &self,
+        policy: &ExecutionPolicy,
+        signal: &SignalOutput,
+        side: OrderSide,
+        client_order_id: String,
+    ) -> Result<OrderRequest, ExecutionError> {
+        let symbol = signal.symbol.as_str();
+        let (price, order_type) = match policy.entry_order {
+            EntryOrderType::Market => (
+                worst_price(side, signal.price, policy.slippage),
+                OrderType::Market,
+            ),
+            EntryOrderType::LimitAtMid => (
+                self.oms.executor().mid_price(symbol).await?,
+                OrderType::Limit(TimeInForce::Gtc),
+            ),
+            EntryOrderType::PostOnly { .. } => (
+                self.oms.executor().mid_price(symbol).await?,
+                OrderType::Limit(TimeInForce::Alo),
+            ),
+        };
+        if !price.is_finite() || price <= 0.0 {
+            return Err(ExecutionError::InvalidOrder(format!(
+                "No usable price for {}",
+                symbol
+            )));
+        }
+
+        // Size from the reference price so slippage does not shrink the order
+        let reference = match policy.entry_order {
+            EntryOrderType::Market => signal.price,
+            _ => price,
+        };
+        Ok(
+            OrderRequest::limit(symbol, side, policy.order_notional / reference, price)
+                .with_order_type(order_type)
+                .with_client_order_id(client_order_id),
+        )
+    }
+
+    /// Stop loss and take profit closing `size` of an entry at `entry_price`
+    fn protective_orders(
+        symbol: &str,
+        entry_side: OrderSide,
+        size: f64,
+        entry_price: f64,
+        origin: OrderOrigin,
+        plan: &EntryPlan,
+    ) -> Vec<OrderRequest> {
+        let close_side = entry_side.opposite();
+        let direction = if entry_side.is_buy() { 1.0 } else { -1.0 };
+        let strategy_id = origin.strategy_id.unwrap_or_default();
+        let signal_id = origin.signal_id.unwrap_or_default();
+
+        let mut legs = Vec::new();
+        if let Some(pct) = plan.stop_loss_pct {
+            let trigger_price = entry_price * (1.0 - direction * pct / 100.0);
+            legs.push((
+                "sl",
+                worst_price(close_side, trigger_price, plan.slippage),
+                OrderType::StopMarket { trigger_price },
+            ));
+        }
+        if let Some(pct) = plan.take_profit_pct {
+            let trigger_price = entry_price * (1.0 + direction * pct / 100.0);
+            legs.push(if plan.take_profit_limit {
+                (
+                    "tp",
+                    trigger_price,
+                    OrderType::TakeProfitLimit { trigger_price },
+                )
+            } else {
+                (
+                    "tp",
+                    worst_price(close_side, trigger_price, plan.slippage),
+                    OrderType::TakeProfitMarket { trigger_price },
+                )
+            });
+        }
+
+        legs.into_iter()
+            .map(|(leg, price, order_type)| {
+                OrderRequest::limit(symbol, close_side, size, price)
+                    .with_order_type(order_type)
+                    .with_reduce_only(true)
+                    .with_client_order_id(signal_client_order_id(strategy_id, signal_id, leg))
+            })
+            .collect()
+    }
+
+    /// Place the stop loss and take profit for a filled entry
+    async fn protect(&self, entry: &ManagedOrder, plan: &EntryPlan) -> Vec<ManagedOrder> {
+        let entry_price = entry.avg_fill_price.unwrap_or(entry.request.price);
+        let requests = Self::protective_orders(
+            entry.symbol(),
+            entry.side(),
+            entry.filled_size,
+            entry_price,
+            entry.origin,
+            plan,
+        );
+
+        let mut placed = Vec::new();
+        if plan.grouping == BracketGrouping::Position {
+            match self
+                .oms
+                .submit_group(requests, OrderGrouping::PositionTpsl, entry.origin)
+                .await
+            {
+                Ok(orders) => placed = orders,
+                Err(e) => warn!(
+                    client_order_id = %entry.client_order_id,
+                    error = %e,
+                    "Failed to place protective orders for {}",
+                    entry.client_order_id
+                ),
+            }
+        } else {
+            for request in requests {
+                match self.oms.submit(request, entry.origin).await {
+                    Ok(order) => placed.push(order),
+                    Err(e) => warn!(
+                        client_order_id = %entry.client_order_id,
+                        error = %e,
+                        "Failed to place protective order for {}",
+                        entry.client_order_id
+                    ),
+                }
+            }
+        }
+        self.track_bracket(entry, entry_price, plan, &placed).await;
+        placed
+    }
+
+    async fn track_bracket(
+        &self,
+        entry: &ManagedOrder,
+        entry_price: f64,
+        plan: &EntryPlan,
+        legs: &[ManagedOrder],
+    ) {
+        let legs: Vec<String> = legs
+            .iter()
+            .filter(|o| !o.state.is_terminal())
+            .map(|o| o.client_order_id.clone())
+            .collect();
+        if legs.is_empty() {
+            return;
+        }
+        self.brackets.lock().await.insert(
+            entry.client_order_id.clone(),
+            Bracket {
+                strategy_id: entry.origin.strategy_id.unwrap_or_default(),
+                symbol: entry.symbol().to_string(),
+                side: entry.side(),
+                entry_price,
+                legs,
+                grouping: plan.grouping,
+                break_even_after_pct: plan.break_even_after_pct,
+                slippage: plan.slippage,
+                position_seen: false,
+                at_break_even: false,
+            },
+        );
+    }
+
+    /// Keep brackets in line with their positions: cancel the legs once the
+    /// position is closed or flipped, shrink legs larger than the position and
+    /// move the stop to the entry price once the move in favour is large enough
+    async fn manage_brackets(&self) {
+        let brackets: Vec<(String, Bracket)> = self
+            .brackets
+            .lock()
+            .await
+            .iter()
+            .map(|(cloid, b)| (cloid.clone(), b.clone()))
+            .collect();
+        if brackets.is_empty() {
+            return;
+        }
+        let positions = match self.oms.executor().positions().await {
+            Ok(positions) => positions,
+            Err(e) => {
+                warn!(error = %e, "Failed to load positions for bracket management");
+                return;
+            }
+        };
+
+        for (entry, mut bracket) in brackets {
+            let mut legs = Vec::new();
+            for cloid in &bracket.legs {
+                if let Some(order) = self.oms.order(cloid).await {
+                    if !order.state.is_terminal() {
+                        legs.push(order);
+                    }
+                }
+            }
+            if legs.is_empty() {
+                self.brackets.lock().await.remove(&entry);
+                continue;
+            }
+
+            let size = positions
+                .iter()
+                .find(|p| p.symbol == bracket.symbol)
+                .map_or(0.0, |p| p.size);
+            let open = signed(bracket.side, size) > SIZE_TOLERANCE;
+            if !bracket.position_seen {
+                if !open {
+                    continue;
+                }
+                bracket.position_seen = true;
+            }
+
+            if !open {
+                info!(
+                    strategy_id = bracket.strategy_id,
+                    symbol = %bracket.symbol,
+                    "Position in {} closed, cancelling its bracket",
+                    bracket.symbol
+                );
+                // The exchange cancels position TP/SL itself
+                if bracket.grouping != BracketGrouping::Position {
+                    for leg in &legs {
+                        if let Err(e) = self.oms.cancel(&leg.client_order_id).await {
+                            warn!(client_order_id = %leg.client_order_id, error = %e, "Failed to cancel bracket order");
+                        }
+                    }
+                }
+                self.brackets.lock().await.remove(&entry);
+                continue;
+            }
+
+            let size = size.abs();
+            let break_even = match bracket.break_even_after_pct {
+                Some(pct) if !bracket.at_break_even => {
+                    match self.oms.executor().mid_price(&bracket.symbol).await {
+                        Ok(mid) => {
+                            let moved = signed(bracket.side, mid - bracket.entry_price)
+                                / bracket.entry_price
+                                * 100.0;
+                            moved >= pct
+                        }
+                        Err(e) => {
+                            warn!(symbol = %bracket.symbol, error = %e, "Failed to read mid price for break-even");
+                            false
+                        }
+                    }
+                }
+                _ => false,
+            };
+
+            for leg in &legs {
+                let mut request = leg.request.clone();
+                // The exchange resizes position TP/SL itself
+                if bracket.grouping != BracketGrouping::Position
+                    && leg.remaining_size() > size + SIZE_TOLERANCE
+                {
+                    request.size = leg.filled_size + size;
+                }
+                let is_stop = matches!(leg.request.order_type, OrderType::StopMarket { .. });
+                if break_even && is_stop {
+                    request.order_type = OrderType::StopMarket {
+                        trigger_price: bracket.entry_price,
+                    };
+                    request.price = worst_price(
+                        bracket.side.opposite(),
+                        bracket.entry_price,
+                        bracket.slippage,
+                    );
+                }
+                if request == leg.request {
+                    continue;
+                }
+                match self.oms.modify(&leg.client_order_id, request).await {
+                    Ok(order) => {
+                        info!(
+                            client_order_id = %order.client_order_id,
+                            size = order.request.size,
+                            trigger_price = ?order.request.order_type.trigger_price(),
+                            "Adjusted bracket order {}",
+                            order.client_order_id
+                        );
+                        if break_even && is_stop {
+                            bracket.at_break_even = true;
+                        }
+                    }
+                    Err(e) => {
+                        warn!(client_order_id = %leg.client_order_id, error = %e, "Failed to adjust bracket order")
+                    }
+                }
+            }
+
+            let mut brackets = self.brackets.lock().await;
+            if let Some(current) = brackets.get_mut(&entry) {
+                *current = bracket;
+            }
+        }
+    }
+
+    /// Open orders this strategy placed on a symbol
+    async fn strategy_orders(&self, strategy_id: i64, symbol: &str) -> Vec<ManagedOrder> {
+        self.oms
+            .open_orders()
+            .await
+            .into_iter()
+            .filter(|o| o.origin.strategy_id == Some(strategy_id) && o.symbol() == symbol)
+            .collect()
+    }
+
+    async fn cancel_strategy_orders(&self, strategy_id: i64, symbol: &str) {
+        self.brackets
+            .lock()
+            .await
+            .retain(|_, b| b.strategy_id != strategy_id || b.symbol != symbol);
+        for order in self.strategy_orders(strategy_id, symbol).await {
+            self.pending_protection
+                .lock()
+                .await
+                .remove(&order.client_order_id);
+            self.expiries.lock().await.remove(&order.client_order_id);
+            if let Err(e) = self.oms.cancel(&order.client_order_id).await {
+                warn!(client_order_id = %order.client_order_id, error = %e, "Failed to cancel strategy order");
+            }
+        }
+    }
+
+    /// Entries behind `exposure`, assuming one if the position predates this process
+    async fn entry_count(&self, strategy_id: i64, symbol: &str, exposure: f64) -> u32 {
+        let key = (strategy_id, symbol.to_string());
+        let mut counts = self.entries.lock().await;
+        if exposure == 0.0 {
+            counts.remove(&key);
+            return 0;
+        }
+        let side = if exposure > 0.0 {
+            OrderSide::Buy
+        } else {
+            OrderSide::Sell
+        };
+        match counts.get(&key) {
+            Some(count) if count.side == side && count.entries > 0 => count.entries,
+            _ => {
+                counts.insert(key, EntryCount { side, entries: 1 });
+                1
+            }
+        }
+    }
+}
@@ -0,0 +1,559 @@
+//! Position and PnL tracking
+//!
+//! Keeps the position of an account in every symbol it has traded, built from
+//! its fills: signed size, average entry price, realized PnL of closed size,
+//! fees and funding. Open positions are marked to the exchange mid price for
+//! unrealized PnL. Account snapshots from the exchange correct the size when
+//! fills were missed and supply leverage and the liquidation price; without
+//! one the liquidation price is estimated from leverage and maintenance margin.
+//! Every change is persisted as a snapshot.
+
+use crate::db::PositionStore;
+use crate::execution::error::ExecutionError;
+use crate::execution::events::UserEvent;
+use crate::execution::executor::ExchangeExecutor;
+use crate::execution::order::{ExchangeFill, ExchangePosition, FundingPayment, OrderSide};
+use crate::execution::risk::{mark_price, AccountSnapshot};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, RwLock};
+use tracing::{error, info, warn};
+
+/// Sizes below this are treated as flat
+const SIZE_EPSILON: f64 = 1e-9;
+
+/// Fills can arrive out of order by up to this much; older ones are replays
+const FILL_REORDER_WINDOW_SECS: i64 = 60 * 60;
+
+/// A fill applied to a position, remembered to ignore its replays
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct AppliedFill {
+    pub trade_id: u64,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Position of one account in one symbol
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct TrackedPosition {
+    pub account: String,
+    pub symbol: String,
+    /// Signed size in base units (negative when short, zero when flat)
+    pub size: f64,
+    /// Average entry price of the open size
+    pub entry_price: f64,
+    /// Latest price the position was marked at
+    pub mark_price: Option<f64>,
+    /// PnL of closed size, before fees and funding
+    pub realized_pnl: f64,
+    /// PnL of the open size at the mark price
+    pub unrealized_pnl: f64,
+    pub fees_paid: f64,
+    /// Funding paid (positive) or received (negative)
+    pub funding_paid: f64,
+    pub leverage: Option<f64>,
+    /// Reported by the exchange, or estimated from leverage
+    pub liquidation_price: Option<f64>,
+    /// Time of the latest applied fill
+    pub last_fill_at: Option<DateTime<Utc>>,
+    /// Fills applied within the reorder window before `last_fill_at`
+    #[serde(default)]
+    pub recent_fills: Vec<AppliedFill>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl TrackedPosition {
+    pub fn new(account: &str, symbol: &str, at: DateTime<Utc>) -> Self {
+        Self {
+            account: account.to_string(),
+            symbol: symbol.to_string(),
+            size: 0.0,
+            entry_price: 0.0,
+            mark_price: None,
+            realized_pnl: 0.0,
+            unrealized_pnl: 0.0,
+            fees_paid: 0.0,
+            funding_paid: 0.0,
+            leverage: None,
+            liquidation_price: None,
+            last_fill_at: None,
+            recent_fills: Vec::new(),
+            updated_at: at,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.size != 0.0
+    }
+
+    /// Realized and unrealized PnL after fees and funding
+    pub fn net_pnl(&self) -> f64 {
+        self.realized_pnl + self.unrealized_pnl - self.fees_paid - self.funding_paid
+    }
+
+    /// Apply a fill, returning false if it was already applied.
+    ///
+    /// Fills are recognized by trade id, so one arriving after a later fill
+    /// still counts. Fills older than the reorder window before the latest one
+    /// are taken for replays of fills whose ids were forgotten.
+    pub fn apply_fill(&mut self, fill: &ExchangeFill) -> bool {
+        if self
+            .recent_fills
+            .iter()
+            .any(|applied| applied.trade_id == fill.trade_id)
+        {
+            return false;
+        }
+        if let Some(last) = self.last_fill_at {
+            if fill.timestamp < last - chrono::Duration::seconds(FILL_REORDER_WINDOW_SECS) {
+                return false;
+            }
+        }
+
+        let signed = match fill.side {
+            OrderSide::Buy => fill.size,
+            OrderSide::Sell => -fill.size,
+        };
+        let old = self.size;
+        if old == 0.0 || old.signum() == signed.signum() {
+            self.entry_price =
+                (old.abs() * self.entry_price + fill.size * fill.price) / (old.abs() + fill.size);
+        } else {
+            let closed = fill.size.min(old.abs());
+            self.realized_pnl += closed * (fill.price - self.entry_price) * old.signum();
+            if fill.size > old.abs() {
+                // Flipped: the remainder opens at the fill price
+                self.entry_price = fill.price;
+            }
+        }
+        self.size = old + signed;
+        if self.size.abs() < SIZE_EPSILON {
+            self.size = 0.0;
+            self.entry_price = 0.0;
+        }
+        self.fees_paid += fill.fee;
+
+        let last = self.last_fill_at.max(Some(fill.timestamp));
+        self.last_fill_at = last;
+        self.recent_fills.push(AppliedFill {
+            trade_id: fill.trade_id,
+            timestamp: fill.timestamp,
+        });
+        if let Some(last) = last {
+            let window_start = last - chrono::Duration::seconds(FILL_REORDER_WINDOW_SECS);
+            self.recent_fills.retain(|applied| applied.timestamp >= window_start);
+        }
+        self.mark(self.mark_price.unwrap_or(fill.price), fill.timestamp);
+        true
+    }
+
+    pub fn apply_funding(&mut self, payment: &FundingPayment) {
+        self.funding_paid -= payment.amount;
+        self.updated_at = payment.timestamp;
+    }
+
+    /// Update unrealized PnL at `price`
+    pub fn mark(&mut self, price: f64, at: DateTime<Utc>) {
+        self.mark_price = Some(price);
+        self.unrealized_pnl = if self.is_open() {
+            (price - self.entry_price) * self.size
+        } else {
+            0.0
+        };
+        self.updated_at = at;
+    }
+}
+
+/// Isolated-margin liquidation price estimate.
+///
+/// The position is liquidated once its loss eats the initial margin down to
+/// the maintenance margin: `entry * (1 - 1/leverage) / (1 - mm)` for longs
+/// and `entry * (1 + 1/leverage) / (1 + mm)` for shorts. Returns `None` for
+/// flat positions and when the estimate is not positive (e.g. 1x longs).
+pub fn estimate_liquidation_price(
+    size: f64,
+    entry_price: f64,
+    leverage: f64,
+    maintenance_margin: f64,
+) -> Option<f64> {
+    if size == 0.0 || !leverage.is_finite() || leverage <= 0.0 {
+        return None;
+    }
+    let price = if size > 0.0 {
+        entry_price * (1.0 - 1.0 / leverage) / (1.0 - maintenance_margin)
+    } else {
+        entry_price * (1.0 + 1.0 / leverage) / (1.0 + maintenance_margin)
+    };
+    (price.is_finite() && price > 0.0).then_some(price)
+}
+
+/// PnL of an account summed over its positions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct AccountPnl {
+    pub account: String,
+    pub realized_pnl: f64,
+    pub unrealized_pnl: f64,
+    pub fees_paid: f64,
+    /// Funding paid (positive) or received (negative)
+    pub funding_paid: f64,
+    /// Realized and unrealized PnL after fees and funding
+    pub net_pnl: f64,
+    /// Positions with a non-zero size
+    pub open_positions: usize,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl AccountPnl {
+    pub fn new(account: &str, at: DateTime<Utc>) -> Self {
+        Self {
+            account: account.to_string(),
+            realized_pnl: 0.0,
+            unrealized_pnl: 0.0,
+            fees_paid: 0.0,
+            funding_paid: 0.0,
+            net_pnl: 0.0,
+            open_positions: 0,
+            updated_at: at,
+        }
+    }
+
+    /// Sum the positions of every account, one entry per account
+    pub fn from_positions(positions: &[TrackedPosition]) -> Vec<AccountPnl> {
+        let mut accounts: Vec<AccountPnl> = Vec::new();
+        for position in positions {
+            let index = match accounts.iter().position(|a| a.account == position.account) {
+                Some(index) => index,
+                None => {
+                    accounts.push(AccountPnl::new(&position.account, position.updated_at));
+                    accounts.len() - 1
+                }
+            };
+            let pnl = &mut accounts[index];
+            pnl.realized_pnl += position.realized_pnl;
+            pnl.unrealized_pnl += position.unrealized_pnl;
+            pnl.fees_paid += position.fees_paid;
+            pnl.funding_paid += position.funding_paid;
+            pnl.net_pnl += position.net_pnl();
+            if position.is_open() {
+                pnl.open_positions += 1;
+            }
+            pnl.updated_at = pnl.updated_at.max(position.updated_at);
+        }
+        accounts
+    }
+}
+
+pub struct PositionTracker {
+    account: String,
+    executor: Arc<dyn ExchangeExecutor>,
+    store: Arc<dyn PositionStore>,
+    positions: RwLock<HashMap<String, TrackedPosition>>,
+    mark_interval: Duration,
+    default_leverage: f64,
+    maintenance_margin: f64,
+}
+
+impl PositionTracker {
+    pub fn new(
+        account: &str,
+        executor: Arc<dyn ExchangeExecutor>,
+        store: Arc<dyn PositionStore>,
+    ) -> Self {
+        Self {
+            account: account.to_lowercase(),
+            executor,
+            store,
+            positions: RwLock::new(HashMap::new()),
+            mark_interval: Duration::from_secs(60),
+            default_leverage: 1.0,
+            maintenance_margin: 0.01,
+        }
+    }
+
+    /// How often open positions are marked to the mid price and persisted
+    pub fn with_mark_interval(mut self, interval: Duration) -> Self {
+        self.mark_interval = interval;
+        self
+    }
+
+    /// Leverage assumed for the liquidation estimate until the exchange reports one
+    pub fn with_default_leverage(mut self, leverage: f64) -> Self {
+        self.default_leverage = leverage;
+        self
+    }
+
+    /// Maintenance margin as a fraction of notional, for the liquidation estimate
+    pub fn with_maintenance_margin(mut self, maintenance_margin: f64) -> Self {
+        self.maintenance_margin = maintenance_margin;
+        self
+    }
+
+    pub fn account(&self) -> &str {
+        &self.account
+    }
+
+    /// Load persisted positions and correct them against the exchange
+    pub async fn restore(&self) -> Result<(), ExecutionError> {
+        let restored = self
+            .store
+            .load_positions(&self.account)
+            .await
+            .map_err(|e| ExecutionError::Storage(e.to_string()))?;
+        let count = restored.len();
+        {
+            let mut positions = self.positions.write().await;
+            for position in restored {
+                positions.insert(position.symbol.clone(), position);
+            }
+        }
+        info!(positions = count, "Restored {} positions", count);
+
+        let exchange_positions = self.executor.positions().await?;
+        self.apply_account(&AccountSnapshot {
+            positions: exchange_positions,
+            equity: None,
+            ..AccountSnapshot::default()
+        })
+        .await
+    }
+
+    pub async fn position(&self, symbol: &str) -> Option<TrackedPosition> {
+        self.positions.read().await.get(symbol).cloned()
+    }
+
+    /// Every tracked position, flat ones included, ordered by symbol
+    pub async fn positions(&self) -> Vec<TrackedPosition> {
+        let mut positions: Vec<TrackedPosition> =
+            self.positions.read().await.values().cloned().collect();
+        positions.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        positions
+    }
+
+    pub async fn pnl(&self) -> AccountPnl {
+        AccountPnl::from_positions(&self.positions().await)
+            .pop()
+            .unwrap_or_else(|| AccountPnl::new(&self.account, Utc::now()))
+    }
+
+    /// Apply a fill; `None` if it was already applied
+    pub async fn apply_fill(
+        &self,
+        fill: &ExchangeFill,
+    ) -> Result<Option<TrackedPosition>, ExecutionError> {
+        let position = {
+            let mut positions = self.positions.write().await;
+            let position = positions.entry(fill.symbol.clone()).or_insert_with(|| {
+                TrackedPosition::new(&self.account, &fill.symbol, fill.timestamp)
+            });
+            if !position.apply_fill(fill) {
+                return Ok(None);
+            }
+            self.refresh_liquidation_price(position, None);
+            position.clone()
+        };
+        self.persist(&position).await?;
+        Ok(Some(position))
+    }
+
+    pub async fn apply_funding(
+        &self,
+        payment: &FundingPayment,
+    ) -> Result<TrackedPosition, ExecutionError> {
+        let position = {
+            let mut positions = self.positions.write().await;
+            let position = positions.entry(payment.symbol.clone()).or_insert_with(|| {
+                TrackedPosition::new(&self.account, &payment.symbol, payment.timestamp)
+            });
+            position.apply_funding(payment);
+            position.clone()
+        };
+        self.persist(&position).await?;
+        Ok(position)
+    }
+
+    /// Align sizes with the exchange and take its leverage and liquidation price
+    pub async fn apply_account(&self, snapshot: &AccountSnapshot) -> Result<(), ExecutionError> {
+        let now = Utc::now();
+        let mut changed = Vec::new();
+        {
+            let mut positions = self.positions.write().await;
+            for exchange in &snapshot.positions {
+                let position = positions
+                    .entry(exchange.symbol.clone())
+                    .or_insert_with(|| TrackedPosition::new(&self.account, &exchange.symbol, now));
+                if self.sync_with_exchange(position, Some(exchange), now) {
+                    changed.push(position.clone());
+                }
+            }
+            // Open positions the exchange no longer reports were closed
+            for position in positions.values_mut() {
+                if position.is_open()
+                    && !snapshot
+                        .positions
+                        .iter()
+                        .any(|p| p.symbol == position.symbol)
+                    && self.sync_with_exchange(position, None, now)
+                {
+                    changed.push(position.clone());
+                }
+            }
+        }
+        for position in &changed {
+            self.persist(position).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn apply_user_event(&self, event: &UserEvent) -> Result<(), ExecutionError> {
+        match event {
+            UserEvent::Fill(fill) => self.apply_fill(fill).await.map(|_| ()),
+            UserEvent::Funding(payment) => self.apply_funding(payment).await.map(|_| ()),
+            UserEvent::Account(snapshot) => self.apply_account(snapshot).await,
+            UserEvent::OrderUpdate(_) => Ok(()),
+        }
+    }
+
+    /// Apply user events until the channel closes, resyncing with the
+    /// exchange if events were missed
+    pub async fn follow_user_events(&self, mut events: broadcast::Receiver<UserEvent>) {
+        loop {
+            match events.recv().await {
+                Ok(event) => {
+                    if let Err(e) = self.apply_user_event(&event).await {
+                        error!(error = %e, "Failed to apply user event to positions");
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    warn!(
+                        missed = missed,
+                        "Missed {} user events, resyncing positions", missed
+                    );
+                    if let Err(e) = self.sync().await {
+                        error!(error = %e, "Position resync failed");
+                    }
+                }
+                Err(broadcast::error::RecvError::Closed) => {
+                    info!("User event stream closed");
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Correct positions against the exchange's view
+    pub async fn sync(&self) -> Result<(), ExecutionError> {
+        let positions = self.executor.positions().await?;
+        self.apply_account(&AccountSnapshot {
+            positions,
+            equity: None,
+            ..AccountSnapshot::default()
+        })
+        .await
+    }
+
+    /// Mark open positions to the current mid price and persist them
+    pub async fn mark_to_market(&self) -> Result<Vec<TrackedPosition>, ExecutionError> {
+        let symbols: Vec<String> = self
+            .positions
+            .read()
+            .await
+            .values()
+            .filter(|p| p.is_open())
+            .map(|p| p.symbol.clone())
+            .collect();
+
+        let mut marked = Vec::new();
+        for symbol in symbols {
+            let price = match self.executor.mid_price(&symbol).await {
+                Ok(price) => price,
+                Err(e) => {
+                    warn!(error = %e, symbol = %symbol, "No mark price for {}", symbol);
+                    continue;
+                }
+            };
+            let position = {
+                let mut positions = self.positions.write().await;
+                let Some(position) = positions.get_mut(&symbol) else {
+                    continue;
+                };
+                position.mark(price, Utc::now());
+                position.clone()
+            };
+            self.persist(&position).await?;
+            marked.push(position);
+        }
+        Ok(marked)
+    }
+
+    /// Mark positions periodically until the task is cancelled
+    pub async fn run(&self) {
+        let mut interval = tokio::time::interval(self.mark_interval);
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.mark_to_market().await {
+                error!(error = %e, "Failed to mark positions");
+            }
+        }
+    }
+
+    /// Returns true if size, entry price or leverage changed. Mark price and
+    /// liquidation price move with every snapshot and are persisted by `run`.
+    fn sync_with_exchange(
+        &self,
+        position: &mut TrackedPosition,
+        exchange: Option<&ExchangePosition>,
+        now: DateTime<Utc>,
+    ) -> bool {
+        let before = (position.size, position.entry_price, position.leverage);
+        match exchange {
+            Some(exchange) => {
+                if (exchange.size - position.size).abs() > SIZE_EPSILON {
+                    warn!(
+                        symbol = %position.symbol,
+                        tracked = position.size,
+                        exchange = exchange.size,
+                        "Position size of {} differs from the exchange, adopting {}",
+                        position.symbol,
+                        exchange.size
+                    );
+                    position.size = exchange.size;
+                    position.entry_price = exchange.entry_price;
+                }
+                if exchange.leverage > 0.0 {
+                    position.leverage = Some(exchange.leverage);
+                }
+                position.mark(mark_price(exchange), now);
+                self.refresh_liquidation_price(position, exchange.liquidation_price);
+            }
+            None => {
+                warn!(symbol = %position.symbol, "Position in {} closed outside of tracked fills", position.symbol);
+                position.size = 0.0;
+                position.entry_price = 0.0;
+                position.unrealized_pnl = 0.0;
+                position.liquidation_price = None;
+                position.updated_at = now;
+            }
+        }
+        before != (position.size, position.entry_price, position.leverage)
+    }
+
+    fn refresh_liquidation_price(&self, position: &mut TrackedPosition, reported: Option<f64>) {
+        position.liquidation_price = reported.or_else(|| {
+            estimate_liquidation_price(
+                position.size,
+                position.entry_price,
+                position.leverage.unwrap_or(self.default_leverage),
+                self.maintenance_margin,
+            )
+        });
+    }
+
+    async fn persist(&self, position: &TrackedPosition) -> Result<(), ExecutionError> {
+        self.store
+            .save_position(position)
+            .await
+            .map_err(|e| ExecutionError::Storage(e.to_string()))
+    }
+}
@@ -0,0 +1,824 @@
+//! Pre-trade risk checks
+//!
+//! [`RiskManager`] evaluates orders against configured [`RiskLimits`] and the
+//! global [`KillSwitch`]. [`RiskGuardedExecutor`] wraps an exchange executor so
+//! every order placed through it is checked first. Reduce-only orders and
+//! cancels always pass, so positions can still be closed when limits are hit.
+//!
+//! The daily loss limit is measured from the first equity seen on the UTC day,
+//! sampled by [`RiskGuardedExecutor::run`] as soon as the day starts and kept
+//! in a [`DayStartEquityStore`] so restarts do not reset it. While that start
+//! is unknown, for instance because the store cannot be read, new orders are
+//! rejected.
+//!
+//! Position limits count resting orders that add to a position as if they
+//! had filled, so a burst of limit orders cannot build past a limit.
+
+use crate::execution::error::ExecutionError;
+use crate::execution::executor::ExchangeExecutor;
+use crate::execution::kill_switch::{KillSwitch, KillSwitchState};
+use crate::execution::order::{
+    ExchangeFill, ExchangeOrderReport, ExchangePosition, OpenOrder, OrderAck, OrderGrouping,
+    OrderRequest, OrderSide, TopOfBook,
+};
+use crate::metrics::Metrics;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration as ChronoDuration, NaiveDate, Utc};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// Worst price offset of the market orders sent when flattening
+const DEFAULT_FLATTEN_SLIPPAGE: f64 = 0.05;
+
+/// Limits enforced before an order is sent; `None` disables a limit
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RiskLimits {
+    /// Maximum absolute notional of the position in any one symbol
+    pub max_position_notional: Option<f64>,
+    /// Maximum gross notional of all positions divided by account equity
+    pub max_total_leverage: Option<f64>,
+    pub max_open_positions: Option<usize>,
+    /// Maximum drop in account equity since the start of the UTC day
+    pub max_daily_loss: Option<f64>,
+    pub max_orders_per_minute: Option<usize>,
+    /// Symbols that may be traded; all symbols if unset
+    pub allowed_symbols: Option<Vec<String>>,
+}
+
+impl RiskLimits {
+    /// Limits from `RISK_MAX_POSITION_NOTIONAL`, `RISK_MAX_TOTAL_LEVERAGE`,
+    /// `RISK_MAX_OPEN_POSITIONS`, `RISK_MAX_DAILY_LOSS`,
+    /// `RISK_MAX_ORDERS_PER_MINUTE` and `RISK_ALLOWED_SYMBOLS` (comma separated)
+    pub fn from_env() -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        fn parse<T: std::str::FromStr>(
+            name: &str,
+        ) -> Result<Option<T>, Box<dyn std::error::Error + Send + Sync>> {
+            match std::env::var(name) {
+                Ok(raw) if !raw.trim().is_empty() => raw.trim().parse().map(Some).map_err(|_| {
+                    Box::new(std::io::Error::other(format!("Invalid {} '{}'", name, raw)))
+                        as Box<dyn std::error::Error + Send + Sync>
+                }),
+                _ => Ok(None),
+            }
+        }
+
+        let allowed_symbols = std::env::var("RISK_ALLOWED_SYMBOLS").ok().and_then(|raw| {
+            let symbols: Vec<String> = raw
+                .split(',')
+                .map(|s| s.trim().to_uppercase())
+                .filter(|s| !s.is_empty())
+                .collect();
+            (!symbols.is_empty()).then_some(symbols)
+        });
+
+        Ok(Self {
+            max_position_notional: parse("RISK_MAX_POSITION_NOTIONAL")?,
+            max_total_leverage: parse("RISK_MAX_TOTAL_LEVERAGE")?,
+            max_open_positions: parse("RISK_MAX_OPEN_POSITIONS")?,
+            max_daily_loss: parse("RISK_MAX_DAILY_LOSS")?,
+            max_orders_per_minute: parse("RISK_MAX_ORDERS_PER_MINUTE")?,
+            allowed_symbols,
+        })
+    }
+
+    fn needs_positions(&self) -> bool {
+        self.max_position_notional.is_some()
+            || self.max_total_leverage.is_some()
+            || self.max_open_positions.is_some()
+    }
+
+    fn needs_equity(&self) -> bool {
+        self.max_total_leverage.is_some() || self.max_daily_loss.is_some()
+    }
+}
+
+/// Why an order was blocked before reaching the exchange
+#[derive(Debug, Clone, PartialEq)]
+pub enum RiskRejection {
+    KillSwitchActive {
+        reason: Option<String>,
+    },
+    SymbolNotAllowed {
+        symbol: String,
+    },
+    OrderRate {
+        orders: usize,
+        limit: usize,
+    },
+    DailyLoss {
+        loss: f64,
+        limit: f64,
+    },
+    PositionNotional {
+        symbol: String,
+        notional: f64,
+        limit: f64,
+    },
+    OpenPositions {
+        open: usize,
+        limit: usize,
+    },
+    TotalLeverage {
+        leverage: f64,
+        limit: f64,
+    },
+    /// The daily loss cannot be measured
+    DayStartEquityUnknown,
+}
+
+impl RiskRejection {
+    /// Stable identifier, used as the metric label
+    pub fn code(&self) -> &'static str {
+        match self {
+            RiskRejection::KillSwitchActive { .. } => "kill_switch",
+            RiskRejection::SymbolNotAllowed { .. } => "symbol_not_allowed",
+            RiskRejection::OrderRate { .. } => "order_rate",
+            RiskRejection::DailyLoss { .. } => "daily_loss",
+            RiskRejection::PositionNotional { .. } => "position_notional",
+            RiskRejection::OpenPositions { .. } => "open_positions",
+            RiskRejection::TotalLeverage { .. } => "total_leverage",
+            RiskRejection::DayStartEquityUnknown => "day_start_equity_unknown",
+        }
+    }
+}
+
+impl fmt::Display for RiskRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RiskRejection::KillSwitchActive { reason } => match reason {
+                Some(reason) => write!(f, "kill switch active ({})", reason),
+                None => write!(f, "kill switch active"),
+            },
+            RiskRejection::SymbolNotAllowed { symbol } => {
+                write!(f, "{} is not in the allowed symbols", symbol)
+            }
+            RiskRejection::OrderRate { orders, limit } => write!(
+                f,
+                "{} orders in the last minute, limit is {}",
+                orders, limit
+            ),
+            RiskRejection::DailyLoss { loss, limit } => {
+                write!(f, "daily loss {:.2} reached limit {:.2}", loss, limit)
+            }
+            RiskRejection::PositionNotional {
+                symbol,
+                notional,
+                limit,
+            } => write!(
+                f,
+                "{} position notional {:.2} would exceed limit {:.2}",
+                symbol, notional, limit
+            ),
+            RiskRejection::OpenPositions { open, limit } => {
+                write!(f, "{} open positions, limit is {}", open, limit)
+            }
+            RiskRejection::TotalLeverage { leverage, limit } => write!(
+                f,
+                "total leverage {:.2}x would exceed limit {:.2}x",
+                leverage, limit
+            ),
+            RiskRejection::DayStartEquityUnknown => {
+                write!(f, "starting equity of the day is unknown")
+            }
+        }
+    }
+}
+
+/// Shared storage for the equity the daily loss limit is measured from
+#[async_trait]
+pub trait DayStartEquityStore: Send + Sync {
+    /// Equity recorded for `day`, recording `equity` first if there is none yet
+    async fn day_start_equity(
+        &self,
+        day: NaiveDate,
+        equity: f64,
+    ) -> Result<f64, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// Process-local store, for tests and single-process setups
+#[derive(Default)]
+pub struct MemoryDayStartEquityStore {
+    days: Mutex<HashMap<NaiveDate, f64>>,
+}
+
+#[async_trait]
+impl DayStartEquityStore for MemoryDayStartEquityStore {
+    async fn day_start_equity(
+        &self,
+        day: NaiveDate,
+        equity: f64,
+    ) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(*self.days.lock().unwrap().entry(day).or_insert(equity))
+    }
+}
+
+/// Account state the checks run against
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AccountSnapshot {
+    pub positions: Vec<ExchangePosition>,
+    pub equity: Option<f64>,
+    /// Resting orders; those that are not reduce-only count as filled
+    pub open_orders: Vec<OpenOrder>,
+}
+
+/// Mark price implied by a position's entry price and unrealized PnL
+pub fn mark_price(position: &ExchangePosition) -> f64 {
+    if position.size == 0.0 {
+        position.entry_price
+    } else {
+        position.entry_price + position.unrealized_pnl / position.size
+    }
+}
+
+pub struct RiskManager {
+    limits: RiskLimits,
+    kill_switch: Arc<KillSwitch>,
+    metrics: Option<Arc<Metrics>>,
+    /// Times of orders that passed the checks within the last minute
+    recent_orders: Mutex<VecDeque<DateTime<Utc>>>,
+    /// Equity first seen on the current UTC day
+    day_start_equity: Mutex<Option<(NaiveDate, f64)>>,
+    equity_store: Option<Arc<dyn DayStartEquityStore>>,
+}
+
+impl RiskManager {
+    pub fn new(limits: RiskLimits, kill_switch: Arc<KillSwitch>) -> Self {
+        Self {
+            limits,
+            kill_switch,
+            metrics: None,
+            recent_orders: Mutex::new(VecDeque::new()),
+            day_start_equity: Mutex::new(None),
+            equity_store: None,
+        }
+    }
+
+    /// Share the day's starting equity through `store`, so it survives restarts
+    pub fn with_equity_store(mut self, store: Arc<dyn DayStartEquityStore>) -> Self {
+        self.equity_store = Some(store);
+        self
+    }
+
+    /// Count rejections in the `risk_rejections_total` metric
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    pub fn limits(&self) -> &RiskLimits {
+        &self.limits
+    }
+
+    pub fn kill_switch(&self) -> &Arc<KillSwitch> {
+        &self.kill_switch
+    }
+
+    /// Check an order, recording it against the order rate limit if it passes
+    pub fn check(
+        &self,
+        order: &OrderRequest,
+        kill_switch: &KillSwitchState,
+        account: &AccountSnapshot,
+        now: DateTime<Utc>,
+    ) -> Result<(), RiskRejection> {
+        self.check_group(std::slice::from_ref(order), kill_switch, account, now)
+    }
+
+    /// Check orders that are sent together.
+    ///
+    /// Each order is checked against the account as it would be once the
+    /// resting orders and the orders before it filled, so orders that pass
+    /// alone cannot add up past a limit. The orders only count against the
+    /// order rate limit if all pass.
+    pub fn check_group(
+        &self,
+        orders: &[OrderRequest],
+        kill_switch: &KillSwitchState,
+        account: &AccountSnapshot,
+        now: DateTime<Utc>,
+    ) -> Result<(), RiskRejection> {
+        let mut projected = account.clone();
+        for resting in account.open_orders.iter().filter(|o| !o.reduce_only) {
+            let size = projected
+                .positions
+                .iter()
+                .find(|p| p.symbol == resting.symbol)
+                .map_or(0.0, |p| p.size);
+            // Resting orders against the position would only reduce it
+            if size == 0.0 || (size > 0.0) == resting.side.is_buy() {
+                apply_fill(
+                    &mut projected.positions,
+                    &OrderRequest::limit(&resting.symbol, resting.side, resting.size, resting.price),
+                );
+            }
+        }
+        let mut entries = 0;
+        for order in orders {
+            if let Err(rejection) = self.evaluate(order, kill_switch, &projected, entries, now) {
+                self.record_rejection(order, &rejection);
+                return Err(rejection);
+            }
+            if !order.reduce_only {
+                entries += 1;
+                apply_fill(&mut projected.positions, order);
+            }
+        }
+        self.recent_orders
+            .lock()
+            .unwrap()
+            .extend(std::iter::repeat_n(now, entries));
+        Ok(())
+    }
+
+    /// Whether the starting equity of the UTC day of `now` is known
+    pub fn has_day_start_equity(&self, now: DateTime<Utc>) -> bool {
+        matches!(*self.day_start_equity.lock().unwrap(), Some((day, _)) if day == now.date_naive())
+    }
+
+    /// Record an equity observation, returning the starting equity of the UTC day.
+    ///
+    /// The first observation of a day becomes its start, or the one already
+    /// in the store if another process or an earlier run recorded it.
+    pub async fn observe_equity(&self, equity: f64, now: DateTime<Utc>) -> f64 {
+        let today = now.date_naive();
+        if let Some((day, start)) = *self.day_start_equity.lock().unwrap() {
+            if day == today {
+                return start;
+            }
+        }
+        let start = match &self.equity_store {
+            Some(store) => match store.day_start_equity(today, equity).await {
+                Ok(start) => start,
+                Err(e) => {
+                    // Not remembered, so the store is asked again next time
+                    warn!(error = %e, "Failed to read the day's starting equity");
+                    return equity;
+                }
+            },
+            None => equity,
+        };
+        *self.day_start_equity.lock().unwrap() = Some((today, start));
+        start
+    }
+
+    /// Count and log a rejection
+    pub fn record_rejection(&self, order: &OrderRequest, rejection: &RiskRejection) {
+        warn!(
+            symbol = %order.symbol,
+            side = ?order.side,
+            size = order.size,
+            reason = rejection.code(),
+            "Order blocked by risk check: {}",
+            rejection
+        );
+        self.count_rejection(rejection);
+    }
+
+    fn count_rejection(&self, rejection: &RiskRejection) {
+        if let Some(metrics) = &self.metrics {
+            metrics
+                .risk_rejections_total
+                .with_label_values(&[rejection.code()])
+                .inc();
+        }
+    }
+
+    /// Check one order; `pending` orders of the same group passed before it
+    fn evaluate(
+        &self,
+        order: &OrderRequest,
+        kill_switch: &KillSwitchState,
+        account: &AccountSnapshot,
+        pending: usize,
+        now: DateTime<Utc>,
+    ) -> Result<(), RiskRejection> {
+        if order.reduce_only {
+            return Ok(());
+        }
+        if kill_switch.active {
+            return Err(RiskRejection::KillSwitchActive {
+                reason: kill_switch.reason.clone(),
+            });
+        }
+
+        let limits = &self.limits;
+        if let Some(allowed) = &limits.allowed_symbols {
+            if !allowed
+                .iter()
+                .any(|s| s.eq_ignore_ascii_case(&order.symbol))
+            {
+                return Err(RiskRejection::SymbolNotAllowed {
+                    symbol: order.symbol.clone(),
+                });
+            }
+        }
+
+        if let Some(limit) = limits.max_orders_per_minute {
+            let mut recent = self.recent_orders.lock().unwrap();
+            let cutoff = now - ChronoDuration::minutes(1);
+            while recent.front().is_some_and(|t| *t <= cutoff) {
+                recent.pop_front();
+            }
+            let orders = recent.len() + pending;
+            if orders >= limit {
+                return Err(RiskRejection::OrderRate { orders, limit });
+            }
+        }
+
+        if let (Some(limit), Some(equity)) = (limits.max_daily_loss, account.equity) {
+            let start = {
+                let mut day_start = self.day_start_equity.lock().unwrap();
+                let today = now.date_naive();
+                match *day_start {
+                    Some((day, start)) if day == today => start,
+                    // With a store the start is only taken from `observe_equity`
+                    _ if self.equity_store.is_some() => {
+                        return Err(RiskRejection::DayStartEquityUnknown)
+                    }
+                    _ => {
+                        *day_start = Some((today, equity));
+                        equity
+                    }
+                }
+            };
+            let loss = start - equity;
+            if loss >= limit {
+                return Err(RiskRejection::DailyLoss { loss, limit });
+            }
+        }
+
+        let current = account.positions.iter().find(|p| p.symbol == order.symbol);
+        let current_size = current.map_or(0.0, |p| p.size);
+        let signed_size = if order.side.is_buy() {
+            order.size
+        } else {
+            -order.size
+        };
+        let projected = current_size + signed_size;
+        if projected.abs() <= current_size.abs() {
+            // Orders that only shrink or flip within the current size add no exposure
+            return Ok(());
+        }
+
+        let price = if order.price > 0.0 {
+            order.price
+        } else {
+            current.map_or(0.0, mark_price)
+        };
+        let notional = projected.abs() * price;
+        if let Some(limit) = limits.max_position_notional {
+            if notional > limit {
+                return Err(RiskRejection::PositionNotional {
+                    symbol: order.symbol.clone(),
+                    notional,
+                    limit,
+                });
+            }
+        }
+
+        if let Some(limit) = limits.max_open_positions {
+            let open = account.positions.iter().filter(|p| p.size != 0.0).count();
+            if current_size == 0.0 && open >= limit {
+                return Err(RiskRejection::OpenPositions { open, limit });
+            }
+        }
+
+        if let (Some(limit), Some(equity)) = (limits.max_total_leverage, account.equity) {
+            let others: f64 = account
+                .positions
+                .iter()
+                .filter(|p| p.symbol != order.symbol)
+                .map(|p| p.size.abs() * mark_price(p))
+                .sum();
+            let leverage = if equity > 0.0 {
+                (others + notional) / equity
+            } else {
+                f64::INFINITY
+            };
+            if leverage > limit {
+                return Err(RiskRejection::TotalLeverage { leverage, limit });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Apply an order to `positions` as if it filled at its price
+fn apply_fill(positions: &mut Vec<ExchangePosition>, order: &OrderRequest) {
+    let signed_size = if order.side.is_buy() {
+        order.size
+    } else {
+        -order.size
+    };
+    match positions.iter_mut().find(|p| p.symbol == order.symbol) {
+        Some(position) => {
+            let price = if order.price > 0.0 {
+                order.price
+            } else {
+                mark_price(position)
+            };
+            position.size += signed_size;
+            position.entry_price = price;
+            position.unrealized_pnl = 0.0;
+        }
+        None => positions.push(ExchangePosition {
+            symbol: order.symbol.clone(),
+            size: signed_size,
+            entry_price: order.price,
+            unrealized_pnl: 0.0,
+            leverage: 0.0,
+            liquidation_price: None,
+        }),
+    }
+}
+
+/// Outcome of closing everything after a flatten request
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FlattenReport {
+    pub cancelled: usize,
+    /// Symbols whose positions were closed
+    pub closed: Vec<String>,
+    /// Orders or positions that could not be closed
+    pub failed: Vec<String>,
+}
+
+/// Executor that runs every order through a [`RiskManager`] and honours the
+/// kill switch before delegating to the wrapped executor.
+///
+/// Placing, modifying and changing leverage are checked; cancels and account
+/// queries pass straight through.
+pub struct RiskGuardedExecutor {
+    inner: Arc<dyn ExchangeExecutor>,
+    risk: Arc<RiskManager>,
+    flatten_slippage: f64,
+    kill_switch_poll_interval: Duration,
+    flatten_lock: tokio::sync::Mutex<()>,
+}
+
+impl RiskGuardedExecutor {
+    pub fn new(inner: Arc<dyn ExchangeExecutor>, risk: Arc<RiskManager>) -> Self {
+        Self {
+            inner,
+            risk,
+            flatten_slippage: DEFAULT_FLATTEN_SLIPPAGE,
+            kill_switch_poll_interval: Duration::from_secs(5),
+            flatten_lock: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Worst price of flattening market orders, as a fraction of the mark price
+    pub fn with_flatten_slippage(mut self, slippage: f64) -> Self {
+        self.flatten_slippage = slippage;
+        self
+    }
+
+    /// How often [`run`](Self::run) looks for flatten requests
+    pub fn with_kill_switch_poll_interval(mut self, interval: Duration) -> Self {
+        self.kill_switch_poll_interval = interval;
+        self
+    }
+
+    pub fn risk(&self) -> &Arc<RiskManager> {
+        &self.risk
+    }
+
+    pub fn inner(&self) -> &Arc<dyn ExchangeExecutor> {
+        &self.inner
+    }
+
+    /// Read the kill switch and carry out a pending flatten request
+    pub async fn enforce_kill_switch(&self) -> KillSwitchState {
+        let kill_switch = self.risk.kill_switch();
+        let state = kill_switch.state().await;
+        if !(state.active && state.flatten_requested) {
+            return state;
+        }
+
+        let _guard = self.flatten_lock.lock().await;
+        let state = kill_switch.state().await;
+        if !(state.active && state.flatten_requested) {
+            return state;
+        }
+
+        warn!(reason = ?state.reason, "Kill switch flatten requested, closing all positions");
+        match self.flatten_all().await {
+            Ok(report) if report.failed.is_empty() => {
+                info!(
+                    cancelled = report.cancelled,
+                    closed = report.closed.len(),
+                    "Flattened all positions"
+                );
+                match kill_switch.mark_flattened().await {
+                    Ok(state) => return state,
+                    Err(e) => error!(error = %e, "Failed to record completed flatten"),
+                }
+            }
+            Ok(report) => {
+                error!(failed = ?report.failed, "Flatten incomplete, will retry");
+            }
+            Err(e) => {
+                error!(error = %e, "Flatten failed, will retry");
+            }
+        }
+        state
+    }
+
+    /// Cancel every open order and close every position with reduce-only market orders
+    pub async fn flatten_all(&self) -> Result<FlattenReport, ExecutionError> {
+        let mut report = FlattenReport::default();
+
+        for order in self.inner.open_orders().await? {
+            match self.inner.cancel_order(&order.symbol, order.order_id).await {
+                Ok(()) => report.cancelled += 1,
+                Err(e) => {
+                    warn!(order_id = order.order_id, error = %e, "Failed to cancel order while flattening");
+                    report.failed.push(format!("order {}", order.order_id));
+                }
+            }
+        }
+
+        for position in self.inner.positions().await? {
+            if position.size == 0.0 {
+                continue;
+            }
+            let mark = mark_price(&position);
+            let side = if position.size > 0.0 {
+                OrderSide::Sell
+            } else {
+                OrderSide::Buy
+            };
+            let worst_price = if side.is_buy() {
+                mark * (1.0 + self.flatten_slippage)
+            } else {
+                mark * (1.0 - self.flatten_slippage)
+            };
+            if worst_price <= 0.0 {
+                report.failed.push(position.symbol.clone());
+                continue;
+            }
+
+            let order =
+                OrderRequest::market(&position.symbol, side, position.size.abs(), worst_price)
+                    .with_reduce_only(true);
+            match self.inner.place_order(&order).await {
+                Ok(_) => report.closed.push(position.symbol.clone()),
+                Err(e) => {
+                    warn!(symbol = %position.symbol, error = %e, "Failed to close position while flattening");
+                    report.failed.push(position.symbol.clone());
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Poll the kill switch so flatten requests are handled without order
+    /// flow, and record the day's starting equity as soon as the day starts
+    pub async fn run(&self) {
+        let mut interval = tokio::time::interval(self.kill_switch_poll_interval);
+        loop {
+            interval.tick().await;
+            self.enforce_kill_switch().await;
+            self.sample_day_start_equity().await;
+        }
+    }
+
+    /// Record the equity if the daily loss limit has no start for today yet
+    pub async fn sample_day_start_equity(&self) {
+        let now = Utc::now();
+        if self.risk.limits().max_daily_loss.is_none() || self.risk.has_day_start_equity(now) {
+            return;
+        }
+        match self.inner.account_equity().await {
+            Ok(equity) => {
+                self.risk.observe_equity(equity, now).await;
+            }
+            Err(e) => warn!(error = %e, "Failed to sample equity for the daily loss limit"),
+        }
+    }
+
+    /// Check orders about to be sent; `replacing` is a resting order they
+    /// replace, which no longer counts as resting
+    async fn check(
+        &self,
+        orders: &[OrderRequest],
+        replacing: Option<u64>,
+    ) -> Result<(), ExecutionError> {
+        let state = self.enforce_kill_switch().await;
+        let limits = self.risk.limits();
+
+        let mut account = AccountSnapshot::default();
+        if orders.iter().any(|o| !o.reduce_only) && !state.active {
+            if limits.needs_positions() {
+                account.positions = self.inner.positions().await?;
+                account.open_orders = self.inner.open_orders().await?;
+                account
+                    .open_orders
+                    .retain(|o| Some(o.order_id) != replacing);
+            }
+            if limits.needs_equity() {
+                let equity = self.inner.account_equity().await?;
+                if limits.max_daily_loss.is_some() {
+                    self.risk.observe_equity(equity, Utc::now()).await;
+                }
+                account.equity = Some(equity);
+            }
+        }
+
+        self.risk
+            .check_group(orders, &state, &account, Utc::now())
+            .map_err(ExecutionError::RiskRejected)
+    }
+}
+
+#[async_trait]
+impl ExchangeExecutor for RiskGuardedExecutor {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    async fn place_order(&self, order: &OrderRequest) -> Result<OrderAck, ExecutionError> {
+        self.check(std::slice::from_ref(order), None).await?;
+        self.inner.place_order(order).await
+    }
+
+    /// A group is only sent if every order in it passes the checks, with the
+    /// exposure of the whole group counted
+    async fn place_orders(
+        &self,
+        orders: &[OrderRequest],
+        grouping: OrderGrouping,
+    ) -> Result<Vec<Result<OrderAck, ExecutionError>>, ExecutionError> {
+        self.check(orders, None).await?;
+        self.inner.place_orders(orders, grouping).await
+    }
+
+    async fn cancel_order(&self, symbol: &str, order_id: u64) -> Result<(), ExecutionError> {
+        self.inner.cancel_order(symbol, order_id).await
+    }
+
+    async fn modify_order(
+        &self,
+        order_id: u64,
+        order: &OrderRequest,
+    ) -> Result<OrderAck, ExecutionError> {
+        self.check(std::slice::from_ref(order), Some(order_id))
+            .await?;
+        self.inner.modify_order(order_id, order).await
+    }
+
+    async fn open_orders(&self) -> Result<Vec<OpenOrder>, ExecutionError> {
+        self.inner.open_orders().await
+    }
+
+    async fn order_status(
+        &self,
+        client_order_id: &str,
+    ) -> Result<Option<ExchangeOrderReport>, ExecutionError> {
+        self.inner.order_status(client_order_id).await
+    }
+
+    async fn fills_since(&self, since: DateTime<Utc>) -> Result<Vec<ExchangeFill>, ExecutionError> {
+        self.inner.fills_since(since).await
+    }
+
+    async fn positions(&self) -> Result<Vec<ExchangePosition>, ExecutionError> {
+        self.inner.positions().await
+    }
+
+    async fn account_equity(&self) -> Result<f64, ExecutionError> {
+        self.inner.account_equity().await
+    }
+
+    async fn mid_price(&self, symbol: &str) -> Result<f64, ExecutionError> {
+        self.inner.mid_price(symbol).await
+    }
+
+    async fn top_of_book(&self, symbol: &str) -> Result<TopOfBook, ExecutionError> {
+        self.inner.top_of_book(symbol).await
+    }
+
+    async fn set_leverage(
+        &self,
+        symbol: &str,
+        leverage: u32,
+        cross_margin: bool,
+    ) -> Result<(), ExecutionError> {
+        let state = self.enforce_kill_switch().await;
+        if state.active {
+            let rejection = RiskRejection::KillSwitchActive {
+                reason: state.reason.clone(),
+            };
+            warn!(symbol = symbol, "Leverage change blocked: {}", rejection);
+            self.risk.count_rejection(&rejection);
+            return Err(ExecutionError::RiskRejected(rejection));
+        }
+        self.inner
+            .set_leverage(symbol, leverage, cross_margin)
+            .await
+    }
+}
@@ -0,0 +1,285 @@
+//! Shadow execution
+//!
+//! [`ShadowExecutor`] wraps a real executor so the full signal → policy →
+//! risk → OMS path runs against the real account without sending anything.
+//! Positions, equity and prices come from the wrapped executor; orders,
+//! cancels, modifications and leverage changes are recorded instead of sent.
+//!
+//! Marketable orders are assumed to fill at the current far touch, so the
+//! orders that follow an entry (stop loss, take profit) are recorded too.
+//! Resting orders never fill. Shadow fills do not change the real positions
+//! later signals see.
+
+use crate::execution::error::ExecutionError;
+use crate::execution::executor::ExchangeExecutor;
+use crate::execution::order::{
+    ExchangeFill, ExchangeOrderReport, ExchangeOrderState, ExchangePosition, OpenOrder, OrderAck,
+    OrderRequest, OrderSide, OrderStatus, OrderType, TimeInForce, TopOfBook,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::info;
+
+/// Exchange name recorded on orders placed through a shadow executor
+pub fn shadow_exchange_name(exchange: &str) -> String {
+    format!("{}-shadow", exchange)
+}
+
+#[derive(Default)]
+struct ShadowBook {
+    next_order_id: u64,
+    next_trade_id: u64,
+    /// Every order request received, in order
+    intended: Vec<OrderRequest>,
+    orders: HashMap<u64, (OrderRequest, ExchangeOrderReport)>,
+    fills: Vec<ExchangeFill>,
+}
+
+pub struct ShadowExecutor {
+    inner: Arc<dyn ExchangeExecutor>,
+    name: String,
+    book: Mutex<ShadowBook>,
+}
+
+impl ShadowExecutor {
+    pub fn new(inner: Arc<dyn ExchangeExecutor>) -> Self {
+        Self {
+            name: shadow_exchange_name(inner.name()),
+            inner,
+            book: Mutex::new(ShadowBook::default()),
+        }
+    }
+
+    /// Orders that would have been sent, oldest first
+    pub async fn intended_orders(&self) -> Vec<OrderRequest> {
+        self.book.lock().await.intended.clone()
+    }
+
+    /// Price a marketable order would fill at, `None` if it would rest
+    fn matching_price(order: &OrderRequest, book: &TopOfBook) -> Option<f64> {
+        let far = book.far_touch(order.side);
+        let crosses = match order.side {
+            OrderSide::Buy => order.price >= far,
+            OrderSide::Sell => order.price <= far,
+        };
+        crosses.then_some(far)
+    }
+}
+
+#[async_trait]
+impl ExchangeExecutor for ShadowExecutor {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn place_order(&self, order: &OrderRequest) -> Result<OrderAck, ExecutionError> {
+        let fill_price = match order.order_type {
+            OrderType::Limit(_) | OrderType::Market => {
+                let book = self.inner.top_of_book(&order.symbol).await?;
+                Self::matching_price(order, &book)
+            }
+            OrderType::StopMarket { .. }
+            | OrderType::TakeProfitMarket { .. }
+            | OrderType::TakeProfitLimit { .. } => None,
+        };
+
+        let mut book = self.book.lock().await;
+        book.intended.push(order.clone());
+        info!(
+            symbol = %order.symbol,
+            side = ?order.side,
+            size = order.size,
+            price = order.price,
+            order_type = ?order.order_type,
+            reduce_only = order.reduce_only,
+            client_order_id = ?order.client_order_id,
+            "Shadow order for {} recorded, not sent",
+            order.symbol
+        );
+
+        let takes_only = matches!(
+            order.order_type,
+            OrderType::Market | OrderType::Limit(TimeInForce::Ioc)
+        );
+        if takes_only && fill_price.is_none() {
+            return Err(ExecutionError::Rejected(
+                "Order could not immediately match against any resting orders.".to_string(),
+            ));
+        }
+        if order.order_type == OrderType::Limit(TimeInForce::Alo) && fill_price.is_some() {
+            return Err(ExecutionError::Rejected(
+                "Post only order would have immediately matched.".to_string(),
+            ));
+        }
+
+        book.next_order_id += 1;
+        let order_id = book.next_order_id;
+        let now = Utc::now();
+        let mut report = ExchangeOrderReport {
+            order_id,
+            client_order_id: order.client_order_id.clone(),
+            symbol: order.symbol.clone(),
+            state: ExchangeOrderState::Open,
+            original_size: order.size,
+            remaining_size: order.size,
+            updated_at: now,
+        };
+        let ack = match fill_price {
+            Some(price) => {
+                report.state = ExchangeOrderState::Filled;
+                report.remaining_size = 0.0;
+                book.next_trade_id += 1;
+                let fill = ExchangeFill {
+                    trade_id: book.next_trade_id,
+                    order_id,
+                    client_order_id: order.client_order_id.clone(),
+                    symbol: order.symbol.clone(),
+                    side: order.side,
+                    size: order.size,
+                    price,
+                    fee: 0.0,
+                    timestamp: now,
+                };
+                book.fills.push(fill);
+                OrderAck {
+                    order_id: Some(order_id),
+                    status: OrderStatus::Filled,
+                    filled_size: order.size,
+                    avg_fill_price: Some(price),
+                }
+            }
+            None => OrderAck {
+                order_id: Some(order_id),
+                status: OrderStatus::Resting,
+                filled_size: 0.0,
+                avg_fill_price: None,
+            },
+        };
+        book.orders.insert(order_id, (order.clone(), report));
+        Ok(ack)
+    }
+
+    async fn cancel_order(&self, symbol: &str, order_id: u64) -> Result<(), ExecutionError> {
+        let mut book = self.book.lock().await;
+        match book.orders.get_mut(&order_id) {
+            Some((_, report)) if report.state == ExchangeOrderState::Open => {
+                report.state = ExchangeOrderState::Cancelled;
+                report.updated_at = Utc::now();
+                info!(
+                    symbol = symbol,
+                    order_id = order_id,
+                    "Shadow cancel of order {} recorded, not sent",
+                    order_id
+                );
+                Ok(())
+            }
+            _ => Err(ExecutionError::Rejected(
+                "Order was never placed, already canceled, or filled.".to_string(),
+            )),
+        }
+    }
+
+    async fn modify_order(
+        &self,
+        order_id: u64,
+        order: &OrderRequest,
+    ) -> Result<OrderAck, ExecutionError> {
+        let mut book = self.book.lock().await;
+        let (request, report) = book
+            .orders
+            .get_mut(&order_id)
+            .filter(|(_, r)| r.state == ExchangeOrderState::Open)
+            .ok_or_else(|| ExecutionError::Rejected("Cannot modify order".to_string()))?;
+        *request = order.clone();
+        report.original_size = order.size;
+        report.remaining_size = order.size;
+        report.updated_at = Utc::now();
+        info!(symbol = %order.symbol, order_id = order_id, "Shadow modification of order {} recorded, not sent", order_id);
+        Ok(OrderAck {
+            order_id: Some(order_id),
+            status: OrderStatus::Resting,
+            filled_size: 0.0,
+            avg_fill_price: None,
+        })
+    }
+
+    /// Resting shadow orders; the account's real orders are not tracked here
+    async fn open_orders(&self) -> Result<Vec<OpenOrder>, ExecutionError> {
+        let book = self.book.lock().await;
+        let mut orders: Vec<OpenOrder> = book
+            .orders
+            .values()
+            .filter(|(_, r)| r.state == ExchangeOrderState::Open)
+            .map(|(request, report)| OpenOrder {
+                order_id: report.order_id,
+                symbol: report.symbol.clone(),
+                side: request.side,
+                size: report.remaining_size,
+                price: request.price,
+                client_order_id: report.client_order_id.clone(),
+                reduce_only: request.reduce_only,
+                created_at: report.updated_at,
+            })
+            .collect();
+        orders.sort_by_key(|o| o.order_id);
+        Ok(orders)
+    }
+
+    async fn order_status(
+        &self,
+        client_order_id: &str,
+    ) -> Result<Option<ExchangeOrderReport>, ExecutionError> {
+        let book = self.book.lock().await;
+        Ok(book
+            .orders
+            .values()
+            .map(|(_, report)| report)
+            .find(|r| r.client_order_id.as_deref() == Some(client_order_id))
+            .cloned())
+    }
+
+    async fn fills_since(&self, since: DateTime<Utc>) -> Result<Vec<ExchangeFill>, ExecutionError> {
+        let book = self.book.lock().await;
+        Ok(book
+            .fills
+            .iter()
+            .filter(|f| f.timestamp >= since)
+            .cloned()
+            .collect())
+    }
+
+    async fn positions(&self) -> Result<Vec<ExchangePosition>, ExecutionError> {
+        self.inner.positions().await
+    }
+
+    async fn account_equity(&self) -> Result<f64, ExecutionError> {
+        self.inner.account_equity().await
+    }
+
+    async fn mid_price(&self, symbol: &str) -> Result<f64, ExecutionError> {
+        self.inner.mid_price(symbol).await
+    }
+
+    async fn top_of_book(&self, symbol: &str) -> Result<TopOfBook, ExecutionError> {
+        self.inner.top_of_book(symbol).await
+    }
+
+    async fn set_leverage(
+        &self,
+        symbol: &str,
+        leverage: u32,
+        cross_margin: bool,
+    ) -> Result<(), ExecutionError> {
+        info!(
+            symbol = symbol,
+            leverage = leverage,
+            cross_margin = cross_margin,
+            "Shadow leverage change for {} recorded, not sent",
+            symbol
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,330 @@
+//! Order lifecycle tracked by the order management system
+
+use crate::execution::error::ExecutionError;
+use crate::execution::order::{ExchangeFill, OrderRequest, OrderSide};
+use crate::execution::policy::BracketGrouping;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Tolerance when comparing filled and requested sizes
+const SIZE_EPSILON: f64 = 1e-9;
+
+static CLIENT_ORDER_SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// Generate a unique client order id (`0x` + 32 hex characters)
+pub fn new_client_order_id() -> String {
+    let mut hasher = Keccak256::new();
+    hasher.update(
+        Utc::now()
+            .timestamp_nanos_opt()
+            .unwrap_or_default()
+            .to_be_bytes(),
+    );
+    hasher.update(std::process::id().to_be_bytes());
+    hasher.update(
+        CLIENT_ORDER_SEQ
+            .fetch_add(1, Ordering::Relaxed)
+            .to_be_bytes(),
+    );
+    let hash = hasher.finalize();
+    format!("0x{}", hex::encode(&hash[..16]))
+}
+
+/// created → submitted → acknowledged → partially filled → filled, with
+/// cancelled and rejected reachable from any non-terminal state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderState {
+    /// Recorded locally, not yet sent
+    Created,
+    /// Sent to the exchange, no acknowledgement yet
+    Submitted,
+    /// Accepted and resting on the exchange
+    Acknowledged,
+    PartiallyFilled,
+    Filled,
+    Cancelled,
+    Rejected,
+}
+
+impl OrderState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrderState::Created => "created",
+            OrderState::Submitted => "submitted",
+            OrderState::Acknowledged => "acknowledged",
+            OrderState::PartiallyFilled => "partially_filled",
+            OrderState::Filled => "filled",
+            OrderState::Cancelled => "cancelled",
+            OrderState::Rejected => "rejected",
+        }
+    }
+
+    /// Not yet acknowledged by the exchange
+    pub fn is_pending(&self) -> bool {
+        matches!(self, OrderState::Created | OrderState::Submitted)
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            OrderState::Filled | OrderState::Cancelled | OrderState::Rejected
+        )
+    }
+
+    /// Position along the happy path, used to forbid moving backwards
+    fn progress(&self) -> u8 {
+        match self {
+            OrderState::Created => 0,
+            OrderState::Submitted => 1,
+            OrderState::Acknowledged => 2,
+            OrderState::PartiallyFilled => 3,
+            OrderState::Filled | OrderState::Cancelled | OrderState::Rejected => 4,
+        }
+    }
+
+    pub fn can_transition_to(&self, next: OrderState) -> bool {
+        if self.is_terminal() {
+            return false;
+        }
+        match next {
+            OrderState::Cancelled | OrderState::Rejected => true,
+            OrderState::PartiallyFilled if *self == OrderState::PartiallyFilled => true,
+            _ => next.progress() > self.progress(),
+        }
+    }
+}
+
+/// Strategy and signal that caused an order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct OrderOrigin {
+    pub strategy_id: Option<i64>,
+    /// Id of the stored signal (its timestamp in milliseconds)
+    pub signal_id: Option<i64>,
+}
+
+impl OrderOrigin {
+    pub fn new(strategy_id: i64, signal_id: i64) -> Self {
+        Self {
+            strategy_id: Some(strategy_id),
+            signal_id: Some(signal_id),
+        }
+    }
+
+    /// Order placed by hand or by a component without a signal
+    pub fn manual() -> Self {
+        Self::default()
+    }
+}
+
+/// What the policy engine does with an entry once it is placed: the stop
+/// loss and take profit to protect it with and, for post-only entries, when
+/// to cancel it. Kept with the entry so it survives a restart.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntryPlan {
+    pub stop_loss_pct: Option<f64>,
+    pub take_profit_pct: Option<f64>,
+    pub take_profit_limit: bool,
+    pub grouping: BracketGrouping,
+    pub break_even_after_pct: Option<f64>,
+    pub slippage: f64,
+    /// Cancel deadline of an unfilled post-only entry
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl EntryPlan {
+    pub fn is_protected(&self) -> bool {
+        self.stop_loss_pct.is_some() || self.take_profit_pct.is_some()
+    }
+}
+
+/// An order tracked through its lifecycle
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManagedOrder {
+    pub client_order_id: String,
+    pub exchange: String,
+    pub origin: OrderOrigin,
+    pub request: OrderRequest,
+    pub state: OrderState,
+    pub exchange_order_id: Option<u64>,
+    pub filled_size: f64,
+    pub avg_fill_price: Option<f64>,
+    pub fees_paid: f64,
+    /// Trade ids of applied fills, so replayed fills are ignored
+    #[serde(default)]
+    pub fill_ids: Vec<u64>,
+    /// Size and notional covered by applied fills. `filled_size` can run ahead
+    /// of these when the exchange reports a fill before its trade records.
+    #[serde(default)]
+    pub fills_size: f64,
+    #[serde(default)]
+    pub fills_notional: f64,
+    pub reject_reason: Option<String>,
+    /// Set on entries placed by the policy engine
+    #[serde(default)]
+    pub entry_plan: Option<EntryPlan>,
+    /// Entry a grouped stop loss or take profit waits for
+    #[serde(default)]
+    pub parent_order_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ManagedOrder {
+    pub fn new(
+        exchange: &str,
+        request: OrderRequest,
+        origin: OrderOrigin,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let client_order_id = request
+            .client_order_id
+            .clone()
+            .unwrap_or_else(new_client_order_id);
+        Self {
+            request: OrderRequest {
+                client_order_id: Some(client_order_id.clone()),
+                ..request
+            },
+            client_order_id,
+            exchange: exchange.to_string(),
+            origin,
+            state: OrderState::Created,
+            exchange_order_id: None,
+            filled_size: 0.0,
+            avg_fill_price: None,
+            fees_paid: 0.0,
+            fill_ids: Vec::new(),
+            fills_size: 0.0,
+            fills_notional: 0.0,
+            reject_reason: None,
+            entry_plan: None,
+            parent_order_id: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn symbol(&self) -> &str {
+        &self.request.symbol
+    }
+
+    pub fn side(&self) -> OrderSide {
+        self.request.side
+    }
+
+    pub fn remaining_size(&self) -> f64 {
+        (self.request.size - self.filled_size).max(0.0)
+    }
+
+    /// Still open, or filled further than the fill records seen so far
+    pub fn awaiting_fills(&self) -> bool {
+        !self.state.is_terminal() || self.filled_size > self.fills_size + SIZE_EPSILON
+    }
+
+    pub fn is_fully_filled(&self) -> bool {
+        self.filled_size + SIZE_EPSILON >= self.request.size
+    }
+
+    /// Move to `next`; moving to the current state is a no-op
+    pub fn transition(
+        &mut self,
+        next: OrderState,
+        now: DateTime<Utc>,
+    ) -> Result<(), ExecutionError> {
+        if next == self.state && next != OrderState::PartiallyFilled {
+            return Ok(());
+        }
+        if !self.state.can_transition_to(next) {
+            return Err(ExecutionError::InvalidTransition(format!(
+                "order {} cannot move from {} to {}",
+                self.client_order_id,
+                self.state.as_str(),
+                next.as_str()
+            )));
+        }
+        self.state = next;
+        self.updated_at = now;
+        Ok(())
+    }
+
+    /// Mark the order cancelled unless it already reached a terminal state
+    pub fn cancel(&mut self, now: DateTime<Utc>) {
+        if !self.state.is_terminal() {
+            self.state = OrderState::Cancelled;
+            self.updated_at = now;
+        }
+    }
+
+    /// Mark the order rejected unless it already reached a terminal state
+    pub fn reject(&mut self, reason: impl Into<String>, now: DateTime<Utc>) {
+        if !self.state.is_terminal() {
+            self.state = OrderState::Rejected;
+            self.reject_reason = Some(reason.into());
+            self.updated_at = now;
+        }
+    }
+
+    /// Raise the filled size to what the exchange reports, without a fill record
+    pub fn sync_filled_size(
+        &mut self,
+        filled_size: f64,
+        avg_price: Option<f64>,
+        now: DateTime<Utc>,
+    ) {
+        if filled_size > self.filled_size + SIZE_EPSILON {
+            self.filled_size = filled_size;
+            if self.fill_ids.is_empty() && avg_price.is_some() {
+                self.avg_fill_price = avg_price;
+            }
+            self.updated_at = now;
+        }
+        self.advance_fill_state(now);
+    }
+
+    /// Apply an exchange fill, returning false if it was already applied.
+    ///
+    /// Fills arriving after cancellation still count towards the filled size.
+    pub fn apply_fill(&mut self, fill: &ExchangeFill) -> bool {
+        if self.fill_ids.contains(&fill.trade_id) {
+            return false;
+        }
+        self.fill_ids.push(fill.trade_id);
+        if self.exchange_order_id.is_none() {
+            self.exchange_order_id = Some(fill.order_id);
+        }
+
+        self.fills_size += fill.size;
+        self.fills_notional += fill.price * fill.size;
+        self.filled_size = self.filled_size.max(self.fills_size);
+        self.avg_fill_price = Some(self.fills_notional / self.fills_size);
+        self.fees_paid += fill.fee;
+        self.updated_at = fill.timestamp.max(self.updated_at);
+        self.advance_fill_state(self.updated_at);
+        true
+    }
+
+    fn advance_fill_state(&mut self, now: DateTime<Utc>) {
+        if self.state.is_terminal() || self.filled_size <= SIZE_EPSILON {
+            return;
+        }
+        let next = if self.is_fully_filled() {
+            OrderState::Filled
+        } else {
+            OrderState::PartiallyFilled
+        };
+        if self.state.can_transition_to(next) {
+            self.state = next;
+            self.updated_at = now;
+        }
+    }
+}
+
+/// A fill persisted together with the order it belongs to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderFill {
+    pub client_order_id: String,
+    pub origin: OrderOrigin,
+    pub fill: ExchangeFill,
+}
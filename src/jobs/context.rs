@@ -1,7 +1,11 @@
 //! Job context for dependency injection
 
 use crate::db::QuestDatabase;
+use crate::execution::PolicyEngine;
 use crate::metrics::Metrics;
+use crate::services::candle_gaps::EmptyRangeStore;
+use crate::services::hyperliquid::backfill::CandleBackfiller;
+use crate::services::market_bus::MarketBus;
 use crate::services::market_data::MarketDataProvider;
 use std::sync::Arc;
 
@@ -11,6 +15,11 @@ use std::sync::Arc;
 /// - Market data provider (reads from Redis/QuestDB cache)
 /// - Database (for storing signals)
 /// - Metrics (for tracking evaluation statistics)
+/// - Candle backfiller (optional, for historical backfill jobs)
+/// - Policy engine (optional, for placing orders from signals)
+/// - Market data bus (optional, for publishing stored signals)
+/// - Candle interval (optional, for refusing evaluation across gaps)
+/// - Empty range store (optional, for the bars confirmed not to exist)
 /// 
 /// Note: WebSocket service is NOT included - jobs never create connections,
 /// they only read from stored data.
@@ -18,6 +27,11 @@ pub struct JobContext {
     pub data_provider: Arc<dyn MarketDataProvider + Send + Sync>,
     pub database: Option<Arc<QuestDatabase>>,
     pub metrics: Option<Arc<Metrics>>,
+    pub backfiller: Option<Arc<CandleBackfiller>>,
+    pub policy_engine: Option<Arc<PolicyEngine>>,
+    pub bus: Option<Arc<dyn MarketBus>>,
+    pub candle_interval: Option<String>,
+    pub empty_ranges: Option<Arc<dyn EmptyRangeStore>>,
 }
 
 impl JobContext {
@@ -30,8 +44,43 @@ impl JobContext {
             data_provider,
             database,
             metrics,
+            backfiller: None,
+            policy_engine: None,
+            bus: None,
+            candle_interval: None,
+            empty_ranges: None,
         }
     }
+
+    /// Enable BackfillCandlesJob handling
+    pub fn with_backfiller(mut self, backfiller: Arc<CandleBackfiller>) -> Self {
+        self.backfiller = Some(backfiller);
+        self
+    }
+
+    /// Enable ExecuteSignalJob handling
+    pub fn with_policy_engine(mut self, policy_engine: Arc<PolicyEngine>) -> Self {
+        self.policy_engine = Some(policy_engine);
+        self
+    }
+
+    /// Refuse to evaluate candles of this interval that have gaps
+    pub fn with_candle_interval(mut self, interval: &str) -> Self {
+        self.candle_interval = Some(interval.to_string());
+        self
+    }
+
+    /// Do not count the bars confirmed empty by the gap audit as gaps
+    pub fn with_empty_ranges(mut self, store: Arc<dyn EmptyRangeStore>) -> Self {
+        self.empty_ranges = Some(store);
+        self
+    }
+
+    /// Publish stored signals to the market data bus
+    pub fn with_bus(mut self, bus: Arc<dyn MarketBus>) -> Self {
+        self.bus = Some(bus);
+        self
+    }
 }
 
 
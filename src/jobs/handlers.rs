@@ -1,12 +1,20 @@
 //! Job handlers for signal evaluation workflow
 
+use crate::execution::ExecutionError;
 use crate::jobs::context::JobContext;
-use crate::jobs::types::{EvaluateSignalJob, FetchCandlesJob, StoreSignalJob};
+use crate::jobs::types::{
+    BackfillCandlesJob, EvaluateSignalJob, ExecuteSignalJob, FetchCandlesJob, StoreSignalJob,
+};
+use crate::models::signal::StoredSignal;
+use crate::services::candle_gaps::recent_gaps;
+use crate::services::hyperliquid::rest::interval_duration_ms;
+use crate::services::market_bus::MarketEvent;
 use crate::signals::engine::MIN_CANDLES;
 use apalis::prelude::*;
+use chrono::Utc;
 use std::sync::Arc;
 use std::time::Instant;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 /// Handler for fetching candles job
 /// 
@@ -66,6 +74,51 @@ pub async fn handle_fetch_candles(
         )) as Box<dyn std::error::Error + Send + Sync>);
     }
 
+    // Indicators would silently compute across missing bars; wait for the gap repair
+    let interval = ctx
+        .candle_interval
+        .as_deref()
+        .and_then(|i| Some((i, interval_duration_ms(i)?)));
+    if let Some((interval, interval_ms)) = interval {
+        let confirmed_empty = match ctx.empty_ranges {
+            Some(ref store) => store
+                .load_empty_ranges(&job.symbol, interval)
+                .await
+                .unwrap_or_else(|e| {
+                    warn!(symbol = %job.symbol, error = %e, "FetchCandlesJob: failed to load confirmed empty ranges");
+                    Vec::new()
+                }),
+            None => Vec::new(),
+        };
+        let gaps = recent_gaps(&candles, interval_ms, Utc::now(), &confirmed_empty);
+        if !gaps.is_empty() {
+            let missing: usize = gaps.iter().map(|g| g.missing_bars).sum();
+            warn!(
+                symbol = %job.symbol,
+                gaps = gaps.len(),
+                missing = missing,
+                "FetchCandlesJob: refusing to evaluate {}, {} bars missing from recent candles",
+                job.symbol,
+                missing
+            );
+            if let Some(ref metrics) = ctx.metrics {
+                metrics
+                    .signal_evaluations_refused_total
+                    .with_label_values(&["candle_gap"])
+                    .inc();
+            }
+            return Err(Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "Candle history has {} gaps ({} missing bars), first from {}",
+                    gaps.len(),
+                    missing,
+                    gaps[0].from
+                ),
+            )) as Box<dyn std::error::Error + Send + Sync>);
+        }
+    }
+
     // Enqueue next job: EvaluateSignalJob
     let next_job = EvaluateSignalJob {
         symbol: job.symbol.clone(),
@@ -187,10 +240,11 @@ pub async fn handle_evaluate_signal(
 /// Handler for storing signal job
 /// 
 /// Stores the signal in the database and updates metrics.
-/// This is the final step in the workflow.
+/// When signal execution is enabled, enqueues ExecuteSignalJob.
 pub async fn handle_store_signal(
     job: StoreSignalJob,
     ctx: Data<Arc<JobContext>>,
+    execute_storage: Data<Option<apalis_redis::RedisStorage<ExecuteSignalJob>>>,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let start = Instant::now();
     let symbol = &job.symbol;
@@ -251,6 +305,27 @@ pub async fn handle_store_signal(
         }
     }
 
+    // Publish to the bus; subscribers that miss it can still read the database
+    if let Some(ref bus) = ctx.bus {
+        let event = MarketEvent::Signal(StoredSignal {
+            strategy_id: job.strategy_id,
+            signal: job.signal.clone(),
+        });
+        match bus.publish(&event).await {
+            Ok(_) => {
+                if let Some(ref metrics) = ctx.metrics {
+                    metrics
+                        .bus_events_published_total
+                        .with_label_values(&[event.topic().as_str()])
+                        .inc();
+                }
+            }
+            Err(e) => {
+                warn!(symbol = %symbol, strategy_id = job.strategy_id, error = %e, "StoreSignalJob: failed to publish signal for {}", symbol)
+            }
+        }
+    }
+
     // Record duration and decrement active
     if let Some(ref metrics) = ctx.metrics {
         let duration = start.elapsed();
@@ -260,6 +335,119 @@ pub async fn handle_store_signal(
         metrics.signal_evaluations_active.dec();
     }
 
+    // Enqueue next job: ExecuteSignalJob
+    if let Some(storage) = execute_storage.as_ref() {
+        let next_job = ExecuteSignalJob {
+            symbol: job.symbol.clone(),
+            signal: job.signal.clone(),
+            strategy_id: job.strategy_id,
+        };
+        let mut storage = storage.clone();
+        storage.push(next_job).await.map_err(|e| {
+            Box::new(std::io::Error::other(format!(
+                "Failed to enqueue ExecuteSignalJob: {}",
+                e
+            ))) as Box<dyn std::error::Error + Send + Sync>
+        })?;
+        debug!(symbol = %symbol, "StoreSignalJob: enqueued ExecuteSignalJob for {}", symbol);
+    }
+
     Ok(())
 }
 
+/// Handler for executing signal job
+///
+/// Loads the strategy and hands the signal to the policy engine, which places
+/// orders according to the strategy's execution policy. Strategies without a
+/// policy are skipped. Requires a policy engine in the job context.
+pub async fn handle_execute_signal(
+    job: ExecuteSignalJob,
+    ctx: Data<Arc<JobContext>>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let Some(ref engine) = ctx.policy_engine else {
+        return Err(Box::new(std::io::Error::other(
+            "ExecuteSignalJob: no policy engine configured",
+        )) as Box<dyn std::error::Error + Send + Sync>);
+    };
+    let Some(ref db) = ctx.database else {
+        return Err(Box::new(std::io::Error::other(
+            "ExecuteSignalJob: no database available to load the strategy",
+        )) as Box<dyn std::error::Error + Send + Sync>);
+    };
+
+    let strategy = db.get_strategy(job.strategy_id).await.map_err(|e| {
+        Box::new(std::io::Error::other(format!(
+            "Failed to load strategy {}: {}",
+            job.strategy_id, e
+        ))) as Box<dyn std::error::Error + Send + Sync>
+    })?;
+    let Some(ref policy) = strategy.config.execution else {
+        debug!(
+            symbol = %job.symbol,
+            strategy_id = job.strategy_id,
+            "ExecuteSignalJob: strategy {} has no execution policy, skipping",
+            job.strategy_id
+        );
+        return Ok(());
+    };
+
+    match engine.execute(job.strategy_id, policy, &job.signal).await {
+        Ok(execution) => {
+            debug!(
+                symbol = %job.symbol,
+                strategy_id = job.strategy_id,
+                action = execution.action.as_str(),
+                orders = execution.orders.len(),
+                "ExecuteSignalJob: {} for {} (strategy_id: {})",
+                execution.action.as_str(),
+                job.symbol,
+                job.strategy_id
+            );
+            Ok(())
+        }
+        Err(ExecutionError::RiskRejected(rejection)) => {
+            // Retrying will not change the outcome
+            warn!(
+                symbol = %job.symbol,
+                strategy_id = job.strategy_id,
+                reason = rejection.code(),
+                "ExecuteSignalJob: order for {} blocked by risk check: {}",
+                job.symbol,
+                rejection
+            );
+            Ok(())
+        }
+        Err(e) => Err(Box::new(e) as Box<dyn std::error::Error + Send + Sync>),
+    }
+}
+
+/// Handler for backfilling historical candles job
+///
+/// Pages candles for the requested range into QuestDB, resuming after the
+/// candles already stored from the start of the range. Requires a backfiller in the job context.
+pub async fn handle_backfill_candles(
+    job: BackfillCandlesJob,
+    ctx: Data<Arc<JobContext>>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let Some(ref backfiller) = ctx.backfiller else {
+        return Err(Box::new(std::io::Error::other(
+            "BackfillCandlesJob: no backfiller configured",
+        )) as Box<dyn std::error::Error + Send + Sync>);
+    };
+
+    let report = backfiller
+        .backfill(&job.symbol, &job.interval, job.start, job.end)
+        .await?;
+
+    info!(
+        symbol = %job.symbol,
+        interval = %job.interval,
+        pages = report.pages,
+        stored = report.candles_stored,
+        "BackfillCandlesJob: stored {} candles for {}/{}",
+        report.candles_stored,
+        job.symbol,
+        job.interval
+    );
+    Ok(())
+}
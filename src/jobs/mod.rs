@@ -6,7 +6,9 @@ pub mod types;
 pub mod workflow;
 
 pub use context::JobContext;
-pub use types::{EvaluateSignalJob, FetchCandlesJob, StoreSignalJob};
+pub use types::{
+    BackfillCandlesJob, EvaluateSignalJob, ExecuteSignalJob, FetchCandlesJob, StoreSignalJob,
+};
 
 
 
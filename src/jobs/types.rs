@@ -2,6 +2,7 @@
 
 use crate::models::indicators::Candle;
 use crate::models::signal::SignalOutput;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 /// Job to fetch candles for a symbol
@@ -25,6 +26,19 @@ pub struct StoreSignalJob {
     pub strategy_id: i64,
 }
 
+/// Job to turn a stored signal into orders using the strategy's execution policy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecuteSignalJob {
+    pub symbol: String,
+    pub signal: SignalOutput,
+    pub strategy_id: i64,
+}
 
-
-
+/// Job to backfill historical candles for a symbol and interval
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackfillCandlesJob {
+    pub symbol: String,
+    pub interval: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
@@ -1,7 +1,8 @@
 //! Workflow builder for signal evaluation job chain
 //!
 //! This module provides utilities for building and managing the signal evaluation workflow.
-//! The workflow is: FetchCandlesJob → EvaluateSignalJob → StoreSignalJob
+//! The workflow is: FetchCandlesJob → EvaluateSignalJob → StoreSignalJob → ExecuteSignalJob
+//! (the last step only when signal execution is enabled)
 
 // For now, we use manual job chaining in handlers.
 // This module can be extended to use apalis-workflow in the future if needed.
@@ -1,3 +1,4 @@
+pub mod backtest;
 pub mod cache;
 pub mod common;
 pub mod config;
@@ -5,11 +6,13 @@ pub mod core;
 pub mod db;
 pub mod engine;
 pub mod evaluation;
+pub mod execution;
 pub mod indicators;
 pub mod jobs;
 pub mod logging;
 pub mod metrics;
 pub mod models;
+pub mod paper;
 pub mod services;
 pub mod signals;
 pub mod strategies;
@@ -1,10 +1,14 @@
 //! Prometheus metrics for Perptrix signal engine
 //!
-//! Provides metrics for HTTP requests, signal evaluations, and system health.
+//! Provides metrics for HTTP requests, signal evaluations, system health,
+//! pre-trade risk checks, the market data bus, candle history gaps and market
+//! data subscriptions.
 
 use prometheus::{
-    register_counter_with_registry, register_gauge_with_registry, register_histogram_with_registry,
-    Counter, Gauge, Histogram, Registry, TextEncoder,
+    register_counter_vec_with_registry, register_counter_with_registry,
+    register_gauge_vec_with_registry, register_gauge_with_registry,
+    register_histogram_with_registry, Counter, CounterVec, Gauge, GaugeVec, Histogram, Registry,
+    TextEncoder,
 };
 use std::sync::Arc;
 
@@ -23,11 +27,27 @@ pub struct Metrics {
     pub signal_evaluation_duration_seconds: Histogram,
     pub signal_evaluations_active: Gauge,
     pub signal_evaluation_errors_total: Counter,
+    pub signal_evaluations_refused_total: CounterVec,
 
     // System health metrics
     pub database_connected: Gauge,
     pub cache_connected: Gauge,
     pub websocket_connected: Gauge,
+
+    // Risk metrics
+    pub risk_rejections_total: CounterVec,
+    pub kill_switch_active: Gauge,
+
+    // Market data bus metrics
+    pub bus_events_published_total: CounterVec,
+    pub bus_consumer_lag: GaugeVec,
+
+    // Candle history metrics
+    pub candle_gaps_detected_total: CounterVec,
+    pub candle_missing_bars: GaugeVec,
+
+    // Market data subscription metrics
+    pub market_data_subscriptions: Gauge,
 }
 
 impl Metrics {
@@ -79,6 +99,13 @@ impl Metrics {
             &registry
         )?;
 
+        let signal_evaluations_refused_total = register_counter_vec_with_registry!(
+            "signal_evaluations_refused_total",
+            "Total number of evaluations refused because of unusable candle data",
+            &["reason"],
+            &registry
+        )?;
+
         // System health metrics
         let database_connected = register_gauge_with_registry!(
             "database_connected",
@@ -98,6 +125,57 @@ impl Metrics {
             &registry
         )?;
 
+        // Risk metrics
+        let risk_rejections_total = register_counter_vec_with_registry!(
+            "risk_rejections_total",
+            "Total number of orders blocked by pre-trade risk checks",
+            &["reason"],
+            &registry
+        )?;
+
+        let kill_switch_active = register_gauge_with_registry!(
+            "kill_switch_active",
+            "Kill switch status (1 = active, 0 = inactive)",
+            &registry
+        )?;
+
+        // Market data bus metrics
+        let bus_events_published_total = register_counter_vec_with_registry!(
+            "bus_events_published_total",
+            "Total number of events published to the market data bus",
+            &["topic"],
+            &registry
+        )?;
+
+        let bus_consumer_lag = register_gauge_vec_with_registry!(
+            "bus_consumer_lag",
+            "Events of a bus topic not yet acknowledged by a consumer group",
+            &["topic", "group"],
+            &registry
+        )?;
+
+        // Candle history metrics
+        let candle_gaps_detected_total = register_counter_vec_with_registry!(
+            "candle_gaps_detected_total",
+            "Total number of gaps found in stored candle history",
+            &["symbol", "interval"],
+            &registry
+        )?;
+
+        let candle_missing_bars = register_gauge_vec_with_registry!(
+            "candle_missing_bars",
+            "Bars still missing from recent candle history after the last audit",
+            &["symbol", "interval"],
+            &registry
+        )?;
+
+        // Market data subscription metrics
+        let market_data_subscriptions = register_gauge_with_registry!(
+            "market_data_subscriptions",
+            "Number of symbols the websocket-service streams",
+            &registry
+        )?;
+
         Ok(Self {
             registry: Arc::new(registry),
             http_requests_total,
@@ -107,9 +185,17 @@ impl Metrics {
             signal_evaluation_duration_seconds,
             signal_evaluations_active,
             signal_evaluation_errors_total,
+            signal_evaluations_refused_total,
             database_connected,
             cache_connected,
             websocket_connected,
+            risk_rejections_total,
+            kill_switch_active,
+            bus_events_published_total,
+            bus_consumer_lag,
+            candle_gaps_detected_total,
+            candle_missing_bars,
+            market_data_subscriptions,
         })
     }
 
@@ -13,6 +13,34 @@ pub struct Candle {
     pub open_interest: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub funding_rate: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mark_price: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oracle_price: Option<f64>,
+    /// Premium of the mark over the oracle price, as a fraction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub premium: Option<f64>,
+    /// Notional volume over the trailing 24 hours
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub day_notional_volume: Option<f64>,
+    /// Whether the bar has ended. Live updates of the still-forming bar are
+    /// not closed; candles stored before the flag existed are.
+    #[serde(default = "closed_by_default")]
+    pub is_closed: bool,
+}
+
+fn closed_by_default() -> bool {
+    true
+}
+
+/// Market state of a perp at a point in time, attached to candles as they close
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct AssetContext {
+    pub open_interest: Option<f64>,
+    pub mark_price: Option<f64>,
+    pub oracle_price: Option<f64>,
+    pub premium: Option<f64>,
+    pub day_notional_volume: Option<f64>,
 }
 
 impl Candle {
@@ -33,9 +61,30 @@ impl Candle {
             timestamp,
             open_interest: None,
             funding_rate: None,
+            mark_price: None,
+            oracle_price: None,
+            premium: None,
+            day_notional_volume: None,
+            is_closed: true,
         }
     }
 
+    /// Mark the bar closed once `now` is past its end time (Unix millis, inclusive)
+    pub fn with_end_time(mut self, end_time_ms: i64, now: DateTime<Utc>) -> Self {
+        self.is_closed = now.timestamp_millis() > end_time_ms;
+        self
+    }
+
+    /// Fill the asset context fields that are known
+    pub fn with_asset_context(mut self, context: &AssetContext) -> Self {
+        self.open_interest = context.open_interest.or(self.open_interest);
+        self.mark_price = context.mark_price.or(self.mark_price);
+        self.oracle_price = context.oracle_price.or(self.oracle_price);
+        self.premium = context.premium.or(self.premium);
+        self.day_notional_volume = context.day_notional_volume.or(self.day_notional_volume);
+        self
+    }
+
     pub fn with_open_interest(mut self, open_interest: f64) -> Self {
         self.open_interest = Some(open_interest);
         self
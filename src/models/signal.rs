@@ -2,7 +2,7 @@ use crate::models::indicators::IndicatorSet;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
 pub enum SignalDirection {
     Long,
     Short,
@@ -50,6 +50,13 @@ impl SignalOutput {
     }
 }
 
+/// A persisted signal together with the strategy that produced it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredSignal {
+    pub strategy_id: i64,
+    pub signal: SignalOutput,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SignalEvaluation {
     pub signal: SignalOutput,
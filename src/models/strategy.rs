@@ -1,5 +1,6 @@
 //! Strategy builder system data models
 
+use crate::execution::policy::ExecutionPolicy;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -24,6 +25,13 @@ pub struct StrategyConfig {
     pub rules: Vec<Rule>,
     /// Aggregation configuration
     pub aggregation: AggregationConfig,
+    /// How signals are turned into orders; the strategy is not traded if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub execution: Option<ExecutionPolicy>,
+    /// Evaluate on every update of the forming bar; by default only closed
+    /// bars are evaluated so signals do not repaint
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub intrabar: bool,
 }
 
 /// Individual condition or group
@@ -90,6 +98,8 @@ pub enum IndicatorType {
     VolumeProfile,
     FundingRate,
     OpenInterest,
+    /// Premium of the mark price over the oracle price
+    Premium,
 }
 
 /// Comparison operations
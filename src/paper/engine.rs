@@ -0,0 +1,396 @@
+//! Virtual account bookkeeping for paper trading.
+//!
+//! Each strategy trades its own account. Signals open, close (Neutral) or flip
+//! the account's position at the provider's latest price. Fills pay taker fees
+//! and adverse slippage the same way the backtester models them, and open
+//! positions accrue hourly funding from the latest funding rate.
+
+use crate::models::signal::SignalDirection;
+use crate::paper::error::PaperError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Simulation parameters shared by all paper accounts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaperConfig {
+    /// Starting equity of a new account (quote currency)
+    pub initial_capital: f64,
+    /// Fraction of equity committed as margin per position (0-1]
+    pub position_fraction: f64,
+    /// Leverage applied to the committed margin
+    pub leverage: f64,
+    /// Taker fee per fill in basis points
+    pub fee_bps: f64,
+    /// Adverse price slippage per fill in basis points
+    pub slippage_bps: f64,
+    /// Accrue hourly funding on open positions
+    pub apply_funding: bool,
+}
+
+impl Default for PaperConfig {
+    fn default() -> Self {
+        Self {
+            initial_capital: 10_000.0,
+            position_fraction: 1.0,
+            leverage: 1.0,
+            fee_bps: 4.5,
+            slippage_bps: 1.0,
+            apply_funding: true,
+        }
+    }
+}
+
+impl PaperConfig {
+    pub fn validate(&self) -> Result<(), PaperError> {
+        if !self.initial_capital.is_finite() || self.initial_capital <= 0.0 {
+            return Err(PaperError::InvalidConfig(
+                "initial_capital must be positive".to_string(),
+            ));
+        }
+        if !(self.position_fraction > 0.0 && self.position_fraction <= 1.0) {
+            return Err(PaperError::InvalidConfig(
+                "position_fraction must be in (0, 1]".to_string(),
+            ));
+        }
+        if !self.leverage.is_finite() || self.leverage <= 0.0 {
+            return Err(PaperError::InvalidConfig(
+                "leverage must be positive".to_string(),
+            ));
+        }
+        if self.fee_bps < 0.0 || self.slippage_bps < 0.0 {
+            return Err(PaperError::InvalidConfig(
+                "fee_bps and slippage_bps must not be negative".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Open virtual position
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct PaperPosition {
+    pub direction: SignalDirection,
+    /// Position size in base units
+    pub size: f64,
+    pub entry_price: f64,
+    pub entry_time: DateTime<Utc>,
+    /// Entry fee paid
+    pub fees: f64,
+    /// Funding paid (positive) or received (negative) since entry
+    pub funding: f64,
+}
+
+impl PaperPosition {
+    pub fn side(&self) -> f64 {
+        if self.direction == SignalDirection::Short {
+            -1.0
+        } else {
+            1.0
+        }
+    }
+
+    pub fn unrealized_pnl(&self, price: f64) -> f64 {
+        (price - self.entry_price) * self.size * self.side()
+    }
+}
+
+/// Paper account of a single strategy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaperAccount {
+    pub strategy_id: i64,
+    pub symbol: String,
+    pub initial_capital: f64,
+    /// Equity excluding the open position's unrealized PnL
+    pub cash: f64,
+    /// Net PnL of closed trades after fees and funding
+    pub realized_pnl: f64,
+    pub fees_paid: f64,
+    pub funding_paid: f64,
+    /// Closed round trips
+    pub trades: usize,
+    pub position: Option<PaperPosition>,
+    /// Latest price the account was marked at
+    pub mark_price: Option<f64>,
+    /// Timestamp of the last signal applied to the account
+    pub last_signal_at: Option<DateTime<Utc>>,
+    /// Funding has been accrued up to this time
+    pub last_funding_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl PaperAccount {
+    pub fn new(strategy_id: i64, symbol: String, initial_capital: f64, at: DateTime<Utc>) -> Self {
+        Self {
+            strategy_id,
+            symbol,
+            initial_capital,
+            cash: initial_capital,
+            realized_pnl: 0.0,
+            fees_paid: 0.0,
+            funding_paid: 0.0,
+            trades: 0,
+            position: None,
+            mark_price: None,
+            last_signal_at: None,
+            last_funding_at: None,
+            updated_at: at,
+        }
+    }
+
+    pub fn unrealized_pnl(&self) -> f64 {
+        match (&self.position, self.mark_price) {
+            (Some(pos), Some(price)) => pos.unrealized_pnl(price),
+            _ => 0.0,
+        }
+    }
+
+    /// Marked-to-market equity
+    pub fn equity(&self) -> f64 {
+        self.cash + self.unrealized_pnl()
+    }
+
+    /// Equity change since the account was opened
+    pub fn total_pnl(&self) -> f64 {
+        self.equity() - self.initial_capital
+    }
+
+    pub fn return_pct(&self) -> f64 {
+        if self.initial_capital > 0.0 {
+            self.total_pnl() / self.initial_capital * 100.0
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Whether a fill opened or closed a position
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+pub enum FillAction {
+    Open,
+    Close,
+}
+
+impl FillAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FillAction::Open => "Open",
+            FillAction::Close => "Close",
+        }
+    }
+}
+
+/// A simulated execution
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct PaperFill {
+    pub strategy_id: i64,
+    pub symbol: String,
+    pub timestamp: DateTime<Utc>,
+    /// Direction of the position being opened or closed
+    pub direction: SignalDirection,
+    pub action: FillAction,
+    /// Fill price after slippage
+    pub price: f64,
+    pub size: f64,
+    pub fee: f64,
+    /// Net PnL of the round trip (closing fills only)
+    pub realized_pnl: f64,
+}
+
+/// Point-in-time account valuation
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct EquitySnapshot {
+    pub strategy_id: i64,
+    pub symbol: String,
+    pub timestamp: DateTime<Utc>,
+    pub equity: f64,
+    pub realized_pnl: f64,
+    pub unrealized_pnl: f64,
+    pub fees_paid: f64,
+    pub funding_paid: f64,
+    /// Signed position size (negative when short)
+    pub position_size: f64,
+}
+
+/// Applies signals, funding and marks to paper accounts
+pub struct PaperEngine {
+    config: PaperConfig,
+}
+
+impl PaperEngine {
+    pub fn new(config: PaperConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn config(&self) -> &PaperConfig {
+        &self.config
+    }
+
+    /// Create an empty account funded with the configured initial capital
+    pub fn open_account(&self, strategy_id: i64, symbol: &str, at: DateTime<Utc>) -> PaperAccount {
+        PaperAccount::new(strategy_id, symbol.to_string(), self.config.initial_capital, at)
+    }
+
+    /// Apply a signal at `price`: Neutral closes, the opposite direction flips and
+    /// the same direction keeps the open position.
+    pub fn apply_signal(
+        &self,
+        account: &mut PaperAccount,
+        direction: SignalDirection,
+        price: f64,
+        at: DateTime<Utc>,
+    ) -> Result<Vec<PaperFill>, PaperError> {
+        if !price.is_finite() || price <= 0.0 {
+            return Err(PaperError::InvalidPrice {
+                symbol: account.symbol.clone(),
+                price,
+            });
+        }
+
+        account.mark_price = Some(price);
+        account.updated_at = at;
+
+        let mut fills = Vec::new();
+        let keep = account
+            .position
+            .as_ref()
+            .is_some_and(|pos| pos.direction == direction);
+        if !keep {
+            if let Some(fill) = self.close(account, price, at) {
+                fills.push(fill);
+            }
+            if direction != SignalDirection::Neutral {
+                if let Some(fill) = self.open(account, direction, price, at) {
+                    fills.push(fill);
+                }
+            }
+        }
+
+        Ok(fills)
+    }
+
+    /// Accrue funding on the open position up to `at` using an hourly `rate`
+    pub fn accrue_funding(
+        &self,
+        account: &mut PaperAccount,
+        rate: Option<f64>,
+        price: f64,
+        at: DateTime<Utc>,
+    ) -> f64 {
+        let since = account.last_funding_at.replace(at);
+        let (Some(pos), Some(since), Some(rate)) = (account.position.as_mut(), since, rate) else {
+            return 0.0;
+        };
+        if !self.config.apply_funding || at <= since {
+            return 0.0;
+        }
+
+        let hours = (at - since).num_milliseconds() as f64 / 3_600_000.0;
+        let payment = pos.size * price * rate * hours * pos.side();
+        pos.funding += payment;
+        account.cash -= payment;
+        account.funding_paid += payment;
+        account.updated_at = at;
+        payment
+    }
+
+    /// Record the latest price without trading
+    pub fn mark(&self, account: &mut PaperAccount, price: f64, at: DateTime<Utc>) {
+        if price.is_finite() && price > 0.0 {
+            account.mark_price = Some(price);
+            account.updated_at = at;
+        }
+    }
+
+    pub fn snapshot(&self, account: &PaperAccount, at: DateTime<Utc>) -> EquitySnapshot {
+        EquitySnapshot {
+            strategy_id: account.strategy_id,
+            symbol: account.symbol.clone(),
+            timestamp: at,
+            equity: account.equity(),
+            realized_pnl: account.realized_pnl,
+            unrealized_pnl: account.unrealized_pnl(),
+            fees_paid: account.fees_paid,
+            funding_paid: account.funding_paid,
+            position_size: account
+                .position
+                .as_ref()
+                .map(|pos| pos.size * pos.side())
+                .unwrap_or(0.0),
+        }
+    }
+
+    /// Apply slippage against a fill in direction `side` (+1 buy, -1 sell)
+    fn slipped(&self, price: f64, side: f64) -> f64 {
+        price * (1.0 + side * self.config.slippage_bps / 10_000.0)
+    }
+
+    fn open(
+        &self,
+        account: &mut PaperAccount,
+        direction: SignalDirection,
+        price: f64,
+        at: DateTime<Utc>,
+    ) -> Option<PaperFill> {
+        if account.cash <= 0.0 {
+            return None;
+        }
+        let side = if direction == SignalDirection::Short {
+            -1.0
+        } else {
+            1.0
+        };
+        let entry_price = self.slipped(price, side);
+        let notional = account.cash * self.config.position_fraction * self.config.leverage;
+        let size = notional / entry_price;
+        let fee = notional * self.config.fee_bps / 10_000.0;
+
+        account.cash -= fee;
+        account.fees_paid += fee;
+        account.last_funding_at = Some(at);
+        account.position = Some(PaperPosition {
+            direction,
+            size,
+            entry_price,
+            entry_time: at,
+            fees: fee,
+            funding: 0.0,
+        });
+
+        Some(PaperFill {
+            strategy_id: account.strategy_id,
+            symbol: account.symbol.clone(),
+            timestamp: at,
+            direction,
+            action: FillAction::Open,
+            price: entry_price,
+            size,
+            fee,
+            realized_pnl: 0.0,
+        })
+    }
+
+    fn close(&self, account: &mut PaperAccount, price: f64, at: DateTime<Utc>) -> Option<PaperFill> {
+        let pos = account.position.take()?;
+        let exit_price = self.slipped(price, -pos.side());
+        let gross = pos.unrealized_pnl(exit_price);
+        let fee = exit_price * pos.size * self.config.fee_bps / 10_000.0;
+        let pnl = gross - pos.fees - fee - pos.funding;
+
+        account.cash += gross - fee;
+        account.fees_paid += fee;
+        account.realized_pnl += pnl;
+        account.trades += 1;
+
+        Some(PaperFill {
+            strategy_id: account.strategy_id,
+            symbol: account.symbol.clone(),
+            timestamp: at,
+            direction: pos.direction,
+            action: FillAction::Close,
+            price: exit_price,
+            size: pos.size,
+            fee,
+            realized_pnl: pnl,
+        })
+    }
+}
@@ -0,0 +1,20 @@
+use std::fmt;
+
+#[derive(Debug, Clone)]
+pub enum PaperError {
+    InvalidConfig(String),
+    InvalidPrice { symbol: String, price: f64 },
+}
+
+impl fmt::Display for PaperError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PaperError::InvalidConfig(msg) => write!(f, "Invalid paper trading config: {}", msg),
+            PaperError::InvalidPrice { symbol, price } => {
+                write!(f, "Invalid price {} for {}", price, symbol)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PaperError {}
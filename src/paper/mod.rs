@@ -0,0 +1,9 @@
+//! Paper trading: live signals against simulated accounts.
+
+pub mod engine;
+pub mod error;
+pub mod service;
+
+pub use engine::*;
+pub use error::PaperError;
+pub use service::*;
@@ -0,0 +1,433 @@
+//! Paper trading service
+//!
+//! Consumes signals from the `bus:signals` stream of the market data bus, or
+//! polls the stored signals without a bus, and applies them to per-strategy
+//! paper accounts at the market data provider's latest price. Every tick also accrues funding,
+//! marks open positions and periodically persists equity snapshots.
+//!
+//! Bus events are acknowledged once applied and redelivered otherwise. Stored
+//! signals can share a timestamp and become visible out of order while QuestDB
+//! applies its write-ahead log, so each poll re-reads a short window behind the
+//! newest applied signal. Either way, signals already applied are skipped.
+
+use crate::db::PaperStore;
+use crate::paper::engine::{PaperAccount, PaperConfig, PaperEngine};
+use crate::paper::error::PaperError;
+use crate::models::signal::StoredSignal;
+use crate::services::market_bus::{BusConsumer, BusMessage, MarketEvent, StreamOffset};
+use crate::services::market_data::MarketDataProvider;
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Mutex, RwLock};
+use tracing::{debug, error, info, warn};
+
+/// Maximum signals fetched per query
+const SIGNAL_BATCH_SIZE: usize = 500;
+
+/// How far behind the newest applied signal each poll re-reads
+const SIGNAL_LOOKBACK_SECS: i64 = 60;
+
+/// A strategy stores at most one signal per symbol and timestamp
+type SignalKey = (i64, String, DateTime<Utc>);
+
+fn signal_key(stored: &StoredSignal) -> SignalKey {
+    (
+        stored.strategy_id,
+        stored.signal.symbol.clone(),
+        stored.signal.timestamp,
+    )
+}
+
+/// Which stored signals have been applied
+#[derive(Debug, Clone)]
+struct SignalCursor {
+    /// Timestamp of the newest applied signal
+    latest: DateTime<Utc>,
+    /// Signals up to here were handled before the service started
+    floor: DateTime<Utc>,
+    /// Signals applied within the lookback window
+    applied: HashSet<SignalKey>,
+}
+
+impl SignalCursor {
+    fn new(floor: DateTime<Utc>) -> Self {
+        Self {
+            latest: floor,
+            floor,
+            applied: HashSet::new(),
+        }
+    }
+
+    /// Where the next poll starts reading
+    fn read_from(&self) -> DateTime<Utc> {
+        (self.latest - chrono::Duration::seconds(SIGNAL_LOOKBACK_SECS)).max(self.floor)
+    }
+
+    fn mark_applied(&mut self, stored: &StoredSignal) {
+        self.latest = self.latest.max(stored.signal.timestamp);
+        self.applied.insert(signal_key(stored));
+    }
+
+    /// Forget signals that fell out of the lookback window
+    fn prune(&mut self) {
+        let from = self.read_from();
+        self.applied.retain(|(_, _, timestamp)| *timestamp >= from);
+    }
+}
+
+/// Outcome of one polling cycle
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PaperTickReport {
+    pub signals: usize,
+    pub fills: usize,
+    pub snapshots: usize,
+}
+
+/// Latest price and hourly funding rate for a symbol
+#[derive(Debug, Clone, Copy)]
+struct MarketQuote {
+    price: f64,
+    funding_rate: Option<f64>,
+}
+
+/// What the signals applied in one tick touched
+#[derive(Default)]
+struct SignalBatch {
+    report: PaperTickReport,
+    quotes: HashMap<String, Option<MarketQuote>>,
+    /// Accounts with fills, snapshotted at the end of the tick
+    changed: Vec<i64>,
+}
+
+pub struct PaperTradingService {
+    engine: PaperEngine,
+    store: Arc<dyn PaperStore>,
+    provider: Arc<dyn MarketDataProvider>,
+    /// Signal source instead of the store
+    signal_bus: Option<BusConsumer>,
+    accounts: RwLock<HashMap<i64, PaperAccount>>,
+    cursor: RwLock<Option<SignalCursor>>,
+    last_snapshot: RwLock<HashMap<i64, DateTime<Utc>>>,
+    poll_interval: Duration,
+    snapshot_interval: Duration,
+}
+
+impl PaperTradingService {
+    pub fn new(
+        config: PaperConfig,
+        store: Arc<dyn PaperStore>,
+        provider: Arc<dyn MarketDataProvider>,
+    ) -> Result<Self, PaperError> {
+        config.validate()?;
+        Ok(Self {
+            engine: PaperEngine::new(config),
+            store,
+            provider,
+            signal_bus: None,
+            accounts: RwLock::new(HashMap::new()),
+            cursor: RwLock::new(None),
+            last_snapshot: RwLock::new(HashMap::new()),
+            poll_interval: Duration::from_secs(10),
+            snapshot_interval: Duration::from_secs(60),
+        })
+    }
+
+    /// Consume signals from the bus instead of polling the store
+    pub fn with_signal_bus(mut self, consumer: BusConsumer) -> Self {
+        self.signal_bus = Some(consumer);
+        self
+    }
+
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Minimum time between equity snapshots of an account without fills
+    pub fn with_snapshot_interval(mut self, interval: Duration) -> Self {
+        self.snapshot_interval = interval;
+        self
+    }
+
+    pub fn engine(&self) -> &PaperEngine {
+        &self.engine
+    }
+
+    /// Load persisted accounts and resume from the newest applied signal.
+    ///
+    /// Without any applied signal, only signals stored from now on are traded.
+    /// Signals at the resume point are skipped for the accounts that applied them.
+    /// A new bus consumer group likewise starts at the end of the stream.
+    pub async fn restore(&self) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        if let Some(ref consumer) = self.signal_bus {
+            consumer.join(&StreamOffset::Latest).await?;
+        }
+
+        let restored = self.store.load_accounts().await?;
+        let resume_from = restored
+            .iter()
+            .filter_map(|account| account.last_signal_at)
+            .max()
+            .unwrap_or_else(Utc::now);
+
+        let count = restored.len();
+        let mut accounts = self.accounts.write().await;
+        for account in restored {
+            accounts.insert(account.strategy_id, account);
+        }
+        *self.cursor.write().await = Some(SignalCursor::new(resume_from));
+
+        info!(accounts = count, resume_from = %resume_from, "Restored {} paper accounts, trading signals after {}", count, resume_from);
+        Ok(count)
+    }
+
+    /// Current state of all paper accounts, ordered by strategy
+    pub async fn accounts(&self) -> Vec<PaperAccount> {
+        let mut accounts: Vec<PaperAccount> = self.accounts.read().await.values().cloned().collect();
+        accounts.sort_by_key(|a| a.strategy_id);
+        accounts
+    }
+
+    pub async fn account(&self, strategy_id: i64) -> Option<PaperAccount> {
+        self.accounts.read().await.get(&strategy_id).cloned()
+    }
+
+    /// Apply new signals, then accrue funding and mark every account
+    pub async fn tick(&self) -> Result<PaperTickReport, Box<dyn std::error::Error + Send + Sync>> {
+        if self.cursor.read().await.is_none() {
+            self.restore().await?;
+        }
+
+        let batch = match self.signal_bus {
+            Some(ref consumer) => self.consume_signals(consumer).await?,
+            None => self.poll_signals().await?,
+        };
+        if let Some(cursor) = self.cursor.write().await.as_mut() {
+            cursor.prune();
+        }
+
+        let SignalBatch {
+            mut report,
+            mut quotes,
+            changed,
+        } = batch;
+
+        // Mark every account and snapshot those that traded or are due
+        let now = Utc::now();
+        let mut accounts = self.accounts.write().await;
+        let mut last_snapshot = self.last_snapshot.write().await;
+        for account in accounts.values_mut() {
+            let quote = match quotes.get(&account.symbol) {
+                Some(quote) => *quote,
+                None => {
+                    let quote = self.quote(&account.symbol).await;
+                    quotes.insert(account.symbol.clone(), quote);
+                    quote
+                }
+            };
+            if let Some(quote) = quote {
+                self.engine
+                    .accrue_funding(account, quote.funding_rate, quote.price, now);
+                self.engine.mark(account, quote.price, now);
+            }
+
+            let due = last_snapshot.get(&account.strategy_id).is_none_or(|at| {
+                (now - *at).to_std().unwrap_or_default() >= self.snapshot_interval
+            });
+            if due || changed.contains(&account.strategy_id) {
+                let snapshot = self.engine.snapshot(account, now);
+                self.store.record_snapshot(&snapshot).await?;
+                self.store.save_account(account).await?;
+                last_snapshot.insert(account.strategy_id, now);
+                report.snapshots += 1;
+            }
+        }
+
+        if report.signals > 0 {
+            debug!(signals = report.signals, fills = report.fills, "Paper tick applied {} signals ({} fills)", report.signals, report.fills);
+        }
+        Ok(report)
+    }
+
+    /// Apply the signals stored since the lookback window
+    async fn poll_signals(&self) -> Result<SignalBatch, Box<dyn std::error::Error + Send + Sync>> {
+        let mut batch = SignalBatch::default();
+        let mut from = match self.cursor.read().await.as_ref() {
+            Some(cursor) => cursor.read_from(),
+            None => Utc::now(),
+        };
+        loop {
+            let signals = self.store.signals_since(from, SIGNAL_BATCH_SIZE).await?;
+            let next = signals
+                .last()
+                .map(|stored| stored.signal.timestamp)
+                .filter(|last| signals.len() >= SIGNAL_BATCH_SIZE && *last > from);
+            for stored in &signals {
+                if !self.is_applied(stored).await {
+                    self.apply_signal(stored, &mut batch).await?;
+                }
+            }
+            // A full batch may be followed by more signals
+            match next {
+                Some(last) => from = last,
+                None => break,
+            }
+        }
+        Ok(batch)
+    }
+
+    /// Apply one batch of bus signals, acknowledging those handled
+    async fn consume_signals(
+        &self,
+        consumer: &BusConsumer,
+    ) -> Result<SignalBatch, Box<dyn std::error::Error + Send + Sync>> {
+        let batch = Mutex::new(SignalBatch::default());
+        let handler = |message: BusMessage| {
+            let batch = &batch;
+            async move {
+                let MarketEvent::Signal(stored) = message.event else {
+                    return Ok(());
+                };
+                if !self.is_applied(&stored).await {
+                    self.apply_signal(&stored, &mut *batch.lock().await).await?;
+                }
+                Ok(())
+            }
+        };
+        consumer.poll(&handler).await?;
+
+        match consumer.lag().await {
+            Ok(lag) => debug!(lag = lag, "Paper signal consumer lags {} events", lag),
+            Err(e) => warn!(error = %e, "Failed to read the paper signal consumer lag"),
+        }
+        Ok(batch.into_inner())
+    }
+
+    /// Applied in this run, or before the restart according to its account
+    async fn is_applied(&self, stored: &StoredSignal) -> bool {
+        let timestamp = stored.signal.timestamp;
+        let cursor = self.cursor.read().await;
+        let Some(cursor) = cursor.as_ref() else {
+            return false;
+        };
+        if cursor.applied.contains(&signal_key(stored)) {
+            return true;
+        }
+        timestamp <= cursor.floor
+            && self
+                .accounts
+                .read()
+                .await
+                .get(&stored.strategy_id)
+                .and_then(|account| account.last_signal_at)
+                .is_some_and(|last| last >= timestamp)
+    }
+
+    /// Trade one signal on its strategy's account
+    async fn apply_signal(
+        &self,
+        stored: &StoredSignal,
+        batch: &mut SignalBatch,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let signal = &stored.signal;
+        if let Some(cursor) = self.cursor.write().await.as_mut() {
+            cursor.mark_applied(stored);
+        }
+        batch.report.signals += 1;
+
+        let quote = match batch.quotes.get(&signal.symbol) {
+            Some(quote) => *quote,
+            None => {
+                let quote = self.quote(&signal.symbol).await;
+                batch.quotes.insert(signal.symbol.clone(), quote);
+                quote
+            }
+        };
+        let Some(quote) = quote else {
+            warn!(symbol = %signal.symbol, strategy_id = stored.strategy_id, "No price for {}, skipping paper signal", signal.symbol);
+            return Ok(());
+        };
+
+        let now = Utc::now();
+        let mut accounts = self.accounts.write().await;
+        let account = accounts
+            .entry(stored.strategy_id)
+            .or_insert_with(|| self.engine.open_account(stored.strategy_id, &signal.symbol, now));
+
+        self.engine
+            .accrue_funding(account, quote.funding_rate, quote.price, now);
+        let fills = match self
+            .engine
+            .apply_signal(account, signal.direction, quote.price, now)
+        {
+            Ok(fills) => fills,
+            Err(e) => {
+                warn!(error = %e, strategy_id = stored.strategy_id, "Rejected paper signal");
+                return Ok(());
+            }
+        };
+        account.last_signal_at = account.last_signal_at.max(Some(signal.timestamp));
+
+        for fill in &fills {
+            info!(
+                strategy_id = fill.strategy_id,
+                symbol = %fill.symbol,
+                action = ?fill.action,
+                direction = ?fill.direction,
+                price = fill.price,
+                size = fill.size,
+                "Paper {:?} {:?} {} {:.6} @ {:.4}",
+                fill.action,
+                fill.direction,
+                fill.symbol,
+                fill.size,
+                fill.price
+            );
+            self.store.record_fill(fill).await?;
+        }
+        batch.report.fills += fills.len();
+        self.store.save_account(account).await?;
+        if !fills.is_empty() {
+            batch.changed.push(stored.strategy_id);
+        }
+        Ok(())
+    }
+
+    /// Poll until the task is cancelled
+    pub async fn run(&self) {
+        loop {
+            if let Err(e) = self.tick().await {
+                error!(error = %e, "Paper trading tick failed");
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+
+    /// Latest close and funding rate from the provider's newest candle, falling
+    /// back to its latest price
+    async fn quote(&self, symbol: &str) -> Option<MarketQuote> {
+        match self.provider.get_candles(symbol, 1).await {
+            Ok(candles) => {
+                if let Some(candle) = candles.last() {
+                    return Some(MarketQuote {
+                        price: candle.close,
+                        funding_rate: candle.funding_rate,
+                    });
+                }
+            }
+            Err(e) => {
+                warn!(symbol = %symbol, error = %e, "Failed to load latest candle for {}", symbol);
+            }
+        }
+
+        match self.provider.get_latest_price(symbol).await {
+            Ok(price) if price > 0.0 => Some(MarketQuote {
+                price,
+                funding_rate: None,
+            }),
+            _ => None,
+        }
+    }
+}
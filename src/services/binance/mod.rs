@@ -0,0 +1,10 @@
+//! Binance USDⓈ-M futures integration: market data over WebSocket and REST
+
+pub mod provider;
+pub mod rest;
+
+pub use provider::BinanceMarketDataProvider;
+pub use rest::BinanceRestClient;
+
+/// Venue prefix of Binance USDⓈ-M futures symbols (`binance:BTCUSDT`)
+pub const BINANCE_VENUE: &str = "binance";
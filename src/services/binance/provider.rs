@@ -0,0 +1,665 @@
+//! Binance USDⓈ-M futures market data provider
+//!
+//! Historical klines, funding and open interest come from REST; live candles
+//! from the `<symbol>@kline_<interval>` WebSocket streams. Candles are stored
+//! in QuestDB and Redis under the venue-namespaced symbol (`binance:BTCUSDT`)
+//! so they never mix with another venue's data for the same name.
+
+use crate::cache::RedisCache;
+use crate::config;
+use crate::db::{CandleStore, QuestDatabase};
+use crate::models::indicators::Candle;
+use crate::services::candle_events::CandleClosed;
+use crate::services::candle_gaps::GapFiller;
+use crate::services::hyperliquid::client::{ClientEvent, HyperliquidClient, WebSocketClient};
+use crate::services::hyperliquid::rest::{
+    align_funding_rates, interval_duration_ms, FundingRatePoint,
+};
+use crate::services::market_bus::{market_event_channel, MarketEvent};
+use crate::services::market_data::{split_symbol, venue_symbol, MarketDataProvider};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
+use tokio::time::{sleep, Duration};
+use tracing::{debug, error, info, warn};
+
+use super::rest::BinanceRestClient;
+use super::BINANCE_VENUE;
+
+/// Candles kept in memory per symbol and interval
+const MEMORY_CANDLES: usize = 1000;
+
+/// Candles kept in the Redis cache per symbol and interval
+const CACHED_CANDLES: usize = 200;
+
+/// Kline event from a `<symbol>@kline_<interval>` stream
+#[derive(Debug, Clone, Deserialize)]
+pub struct KlineEvent {
+    #[serde(rename = "s")]
+    pub symbol: String,
+    #[serde(rename = "k")]
+    pub kline: Kline,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Kline {
+    #[serde(rename = "T")]
+    pub close_time: i64,
+    #[serde(rename = "i")]
+    pub interval: String,
+    #[serde(rename = "o")]
+    pub open: String,
+    #[serde(rename = "h")]
+    pub high: String,
+    #[serde(rename = "l")]
+    pub low: String,
+    #[serde(rename = "c")]
+    pub close: String,
+    #[serde(rename = "v")]
+    pub volume: String,
+    /// Whether the kline is closed
+    #[serde(rename = "x")]
+    pub closed: bool,
+}
+
+impl Kline {
+    fn to_candle(&self) -> Result<Candle, Box<dyn std::error::Error + Send + Sync>> {
+        let parse =
+            |value: &str, name: &str| -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
+                value
+                    .parse()
+                    .map_err(|e| format!("Invalid {}: {} ({})", name, value, e).into())
+            };
+        let timestamp =
+            DateTime::from_timestamp(self.close_time / 1000, 0).unwrap_or_else(Utc::now);
+        let mut candle = Candle::new(
+            parse(&self.open, "open")?,
+            parse(&self.high, "high")?,
+            parse(&self.low, "low")?,
+            parse(&self.close, "close")?,
+            parse(&self.volume, "volume")?,
+            timestamp,
+        );
+        candle.is_closed = self.closed;
+        Ok(candle)
+    }
+}
+
+/// `SUBSCRIBE` request for a set of streams
+#[derive(Debug, Serialize)]
+struct StreamRequest<'a> {
+    method: &'a str,
+    params: &'a [String],
+    id: u64,
+}
+
+/// Stream name of a symbol's klines
+pub fn kline_stream(symbol: &str, interval: &str) -> String {
+    format!("{}@kline_{}", symbol.to_lowercase(), interval)
+}
+
+/// State shared with the message handling task
+#[derive(Clone)]
+struct Feed {
+    client: Arc<dyn WebSocketClient>,
+    rest: Arc<BinanceRestClient>,
+    candles: Arc<RwLock<HashMap<String, VecDeque<Candle>>>>,
+    latest_prices: Arc<RwLock<HashMap<String, f64>>>,
+    /// Streams to (re)subscribe on every connection
+    streams: Arc<RwLock<Vec<String>>>,
+    next_request_id: Arc<AtomicU64>,
+    funding_cache: Arc<RwLock<HashMap<String, FundingRatePoint>>>,
+    database: Option<Arc<QuestDatabase>>,
+    cache: Option<Arc<RedisCache>>,
+    market_events: broadcast::Sender<MarketEvent>,
+}
+
+pub struct BinanceMarketDataProvider {
+    feed: Feed,
+    candle_intervals: Vec<String>,
+    started: AtomicBool,
+}
+
+impl BinanceMarketDataProvider {
+    pub fn new() -> Self {
+        Self::with_clients(
+            Arc::new(HyperliquidClient::with_url(
+                config::get_binance_futures_ws_url(),
+            )),
+            Arc::new(BinanceRestClient::new()),
+            vec![
+                "1m".to_string(),
+                "5m".to_string(),
+                "15m".to_string(),
+                "1h".to_string(),
+            ],
+        )
+    }
+
+    /// Provider on the given clients. The WebSocket connection is opened on
+    /// the first subscription, after the storage backends are configured.
+    pub fn with_clients(
+        websocket_client: Arc<dyn WebSocketClient>,
+        rest_client: Arc<BinanceRestClient>,
+        candle_intervals: Vec<String>,
+    ) -> Self {
+        Self {
+            feed: Feed {
+                client: websocket_client,
+                rest: rest_client,
+                candles: Arc::new(RwLock::new(HashMap::new())),
+                latest_prices: Arc::new(RwLock::new(HashMap::new())),
+                streams: Arc::new(RwLock::new(Vec::new())),
+                next_request_id: Arc::new(AtomicU64::new(1)),
+                funding_cache: Arc::new(RwLock::new(HashMap::new())),
+                database: None,
+                cache: None,
+                market_events: market_event_channel().0,
+            },
+            candle_intervals,
+            started: AtomicBool::new(false),
+        }
+    }
+
+    pub fn with_database(mut self, database: Arc<QuestDatabase>) -> Self {
+        self.feed.database = Some(database);
+        self
+    }
+
+    pub fn with_cache(mut self, cache: Arc<RedisCache>) -> Self {
+        self.feed.cache = Some(cache);
+        self
+    }
+
+    pub fn client(&self) -> Arc<dyn WebSocketClient> {
+        self.feed.client.clone()
+    }
+
+    /// Receive candle updates and closes and funding updates from now on,
+    /// under the namespaced symbol
+    pub fn subscribe_market_events(&self) -> broadcast::Receiver<MarketEvent> {
+        self.feed.market_events.subscribe()
+    }
+
+    fn start(&self) {
+        if self.started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let client = self.feed.client.clone();
+        tokio::spawn(async move {
+            let _ = client.connect().await;
+        });
+        let feed = self.feed.clone();
+        tokio::spawn(async move {
+            feed.handle_messages().await;
+        });
+    }
+
+    /// Kline intervals subscribed for every symbol
+    pub fn intervals(&self) -> &[String] {
+        &self.candle_intervals
+    }
+
+    fn primary_interval(&self) -> &str {
+        self.candle_intervals
+            .first()
+            .map(|s| s.as_str())
+            .unwrap_or("1m")
+    }
+
+    async fn subscribe_klines(
+        &self,
+        symbol: &str,
+        interval: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.start();
+        let stored = venue_symbol(BINANCE_VENUE, symbol);
+        let count = config::get_historical_candle_count();
+        match self
+            .feed
+            .rest
+            .fetch_historical_candles(symbol, interval, count)
+            .await
+        {
+            Ok(mut candles) => {
+                debug!(symbol = %stored, interval = %interval, count = candles.len(), "Fetched {} historical candles for {}/{}", candles.len(), stored, interval);
+                self.attach_funding_rates(symbol, &mut candles).await;
+                if let Some(ref db) = self.feed.database {
+                    let closed: Vec<Candle> =
+                        candles.iter().filter(|c| c.is_closed).cloned().collect();
+                    if let Err(e) = db.store_candles_batch(&stored, interval, &closed).await {
+                        warn!(symbol = %stored, interval = %interval, error = %e, "Failed to store historical candles in QuestDB");
+                    }
+                }
+                if let Some(ref cache) = self.feed.cache {
+                    if let Err(e) = cache.cache_candles(&stored, interval, &candles).await {
+                        warn!(symbol = %stored, interval = %interval, error = %e, "Failed to cache historical candles in Redis");
+                    }
+                }
+                let mut candles_map = self.feed.candles.write().await;
+                let buffer = candles_map
+                    .entry(format!("{}_{}", symbol, interval))
+                    .or_default();
+                buffer.extend(candles);
+                while buffer.len() > MEMORY_CANDLES {
+                    buffer.pop_front();
+                }
+            }
+            Err(e) => {
+                warn!(symbol = %stored, interval = %interval, error = %e, "Failed to fetch historical candles for {}/{}", stored, interval);
+            }
+        }
+
+        let stream = kline_stream(symbol, interval);
+        {
+            let mut streams = self.feed.streams.write().await;
+            if streams.contains(&stream) {
+                return Ok(());
+            }
+            streams.push(stream.clone());
+        }
+        if self.feed.client.is_connected().await {
+            self.feed.send_subscribe(&[stream]).await
+        } else {
+            debug!(stream = %stream, "Not connected yet, subscription to {} queued", stream);
+            Ok(())
+        }
+    }
+
+    async fn attach_funding_rates(&self, symbol: &str, candles: &mut [Candle]) {
+        let (Some(first), Some(last)) = (candles.first(), candles.last()) else {
+            return;
+        };
+        // Funding settles every 8 hours; reach back far enough to cover the first candle
+        let start = (first.timestamp - ChronoDuration::hours(8))
+            .timestamp_millis()
+            .max(0) as u64;
+        let end = last.timestamp.timestamp_millis().max(0) as u64;
+        match self
+            .feed
+            .rest
+            .fetch_funding_history(symbol, start, end)
+            .await
+        {
+            Ok(points) => align_funding_rates(candles, &points),
+            Err(e) => {
+                warn!(symbol = %symbol, error = %e, "Failed to fetch funding history for {}", symbol)
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl GapFiller for BinanceMarketDataProvider {
+    async fn fill(
+        &self,
+        symbol: &str,
+        interval: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        let database = self
+            .feed
+            .database
+            .as_ref()
+            .ok_or("No database to store repaired candles")?;
+        let interval_ms = interval_duration_ms(interval)
+            .ok_or_else(|| format!("Unsupported candle interval: {}", interval))?;
+        let (_, native) = split_symbol(symbol);
+        let stored = venue_symbol(BINANCE_VENUE, native);
+
+        // Candles are stamped with their close time; page by open time
+        let end = to.timestamp_millis().max(0) as u64;
+        let mut cursor = (from.timestamp_millis().max(0) as u64).saturating_sub(interval_ms - 1);
+        let mut count = 0;
+        while cursor <= end {
+            let mut candles = self
+                .feed
+                .rest
+                .fetch_candles_range(native, interval, cursor, end)
+                .await?;
+            let Some(last) = candles.last() else {
+                break;
+            };
+            let next = last.timestamp.timestamp_millis().max(0) as u64 + 1;
+            candles.retain(|c| c.is_closed && c.timestamp >= from && c.timestamp <= to);
+            if !candles.is_empty() {
+                self.attach_funding_rates(native, &mut candles).await;
+                count += database
+                    .store_missing_candles(&stored, interval, &candles)
+                    .await?;
+            }
+            if next <= cursor {
+                break;
+            }
+            cursor = next;
+        }
+        Ok(count)
+    }
+}
+
+impl Default for BinanceMarketDataProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Feed {
+    async fn handle_messages(&self) {
+        loop {
+            while let Some(event) = self.client.receive().await {
+                match event {
+                    ClientEvent::Message(text) => {
+                        if let Err(e) = self.process_message(&text).await {
+                            error!(error = %e, "Error processing Binance message");
+                        }
+                    }
+                    ClientEvent::Connected => {
+                        let streams = self.streams.read().await.clone();
+                        if streams.is_empty() {
+                            continue;
+                        }
+                        info!(
+                            streams = streams.len(),
+                            "Binance WebSocket connected, subscribing to {} streams",
+                            streams.len()
+                        );
+                        if let Err(e) = self.send_subscribe(&streams).await {
+                            warn!(error = %e, "Failed to resubscribe to Binance streams");
+                        }
+                    }
+                    ClientEvent::Disconnected => debug!("Binance WebSocket disconnected"),
+                    ClientEvent::Error(e) => error!(error = %e, "Binance WebSocket error"),
+                }
+            }
+            sleep(Duration::from_millis(100)).await;
+        }
+    }
+
+    async fn send_subscribe(
+        &self,
+        streams: &[String],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.send_request("SUBSCRIBE", streams).await
+    }
+
+    async fn send_unsubscribe(
+        &self,
+        streams: &[String],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.send_request("UNSUBSCRIBE", streams).await
+    }
+
+    async fn send_request(
+        &self,
+        method: &str,
+        streams: &[String],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let request = StreamRequest {
+            method,
+            params: streams,
+            id: self.next_request_id.fetch_add(1, Ordering::SeqCst),
+        };
+        let json = serde_json::to_string(&request)?;
+        debug!(request = %json, "Sending Binance {} request", method);
+        self.client
+            .send_text(json)
+            .await
+            .map_err(|e| format!("WebSocket send error: {}", e).into())
+    }
+
+    async fn process_message(
+        &self,
+        text: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let value: serde_json::Value = serde_json::from_str(text)?;
+        match value.get("e").and_then(|e| e.as_str()) {
+            Some("kline") => {
+                let event: KlineEvent = serde_json::from_value(value)?;
+                self.apply_kline(event).await
+            }
+            Some(other) => {
+                debug!(event = %other, "Ignoring Binance {} event", other);
+                Ok(())
+            }
+            None => {
+                if let Some(error) = value.get("error") {
+                    error!(error = %error, "Binance subscription error");
+                } else {
+                    debug!(response = %text, "Binance subscription response");
+                }
+                Ok(())
+            }
+        }
+    }
+
+    async fn apply_kline(
+        &self,
+        event: KlineEvent,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let symbol = event.symbol;
+        let interval = event.kline.interval.clone();
+        let stored = venue_symbol(BINANCE_VENUE, &symbol);
+        let mut candle = event.kline.to_candle()?;
+        self.attach_live_funding_rate(&symbol, &mut candle).await;
+        if event.kline.closed {
+            match self.rest.fetch_open_interest(&symbol).await {
+                Ok(open_interest) => candle.open_interest = Some(open_interest),
+                Err(e) => {
+                    warn!(symbol = %stored, error = %e, "Failed to fetch open interest for {}", stored)
+                }
+            }
+        }
+
+        // Only closed bars are persisted; the forming bar lives in memory and Redis
+        if let Some(db) = self.database.as_ref().filter(|_| candle.is_closed) {
+            if let Err(e) = db.store_candle(&stored, &interval, &candle).await {
+                warn!(symbol = %stored, interval = %interval, error = %e, "Failed to store candle in QuestDB");
+            }
+        }
+
+        {
+            let mut candles_map = self.candles.write().await;
+            let buffer = candles_map
+                .entry(format!("{}_{}", symbol, interval))
+                .or_default();
+            buffer.retain(|c| c.timestamp != candle.timestamp);
+            buffer.push_back(candle.clone());
+            while buffer.len() > MEMORY_CANDLES {
+                buffer.pop_front();
+            }
+        }
+
+        if let Some(ref cache) = self.cache {
+            let mut cached = cache
+                .get_cached_candles(&stored, &interval)
+                .await
+                .ok()
+                .flatten()
+                .unwrap_or_default();
+            cached.retain(|c| c.timestamp != candle.timestamp);
+            cached.push(candle.clone());
+            if cached.len() > CACHED_CANDLES {
+                cached.drain(..cached.len() - CACHED_CANDLES);
+            }
+            if let Err(e) = cache.cache_candles(&stored, &interval, &cached).await {
+                warn!(symbol = %stored, interval = %interval, error = %e, "Failed to update Redis cache");
+            }
+        }
+
+        self.latest_prices
+            .write()
+            .await
+            .insert(symbol, candle.close);
+
+        let _ = self.market_events.send(MarketEvent::CandleUpdate {
+            symbol: stored.clone(),
+            interval: interval.clone(),
+            candle: candle.clone(),
+        });
+        if candle.is_closed {
+            let _ = self
+                .market_events
+                .send(MarketEvent::CandleClosed(CandleClosed {
+                    symbol: stored,
+                    interval,
+                    timestamp: candle.timestamp,
+                }));
+        }
+        Ok(())
+    }
+
+    /// Attach the last settled funding rate, refreshed at most hourly
+    async fn attach_live_funding_rate(&self, symbol: &str, candle: &mut Candle) {
+        if let Some(point) = self.funding_cache.read().await.get(symbol) {
+            if candle.timestamp <= point.timestamp + ChronoDuration::hours(1) {
+                candle.funding_rate = Some(point.funding_rate);
+                return;
+            }
+        }
+        match self.rest.fetch_latest_funding_rate(symbol).await {
+            Ok(point) => {
+                candle.funding_rate = Some(point.funding_rate);
+                let _ = self.market_events.send(MarketEvent::FundingUpdate {
+                    symbol: venue_symbol(BINANCE_VENUE, symbol),
+                    funding_rate: point.funding_rate,
+                    timestamp: point.timestamp,
+                });
+                self.funding_cache
+                    .write()
+                    .await
+                    .insert(symbol.to_string(), point);
+            }
+            Err(e) => {
+                warn!(symbol = %symbol, error = %e, "Failed to fetch latest funding rate for {}", symbol)
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl MarketDataProvider for BinanceMarketDataProvider {
+    async fn get_candles(
+        &self,
+        symbol: &str,
+        limit: usize,
+    ) -> Result<Vec<Candle>, Box<dyn std::error::Error + Send + Sync>> {
+        let interval = self.primary_interval();
+        let stored = venue_symbol(BINANCE_VENUE, symbol);
+
+        if let Some(ref cache) = self.feed.cache {
+            if let Ok(Some(mut candles)) = cache.get_cached_candles(&stored, interval).await {
+                candles.sort_by_key(|c| c.timestamp);
+                if candles.len() > limit {
+                    candles.drain(..candles.len() - limit);
+                }
+                return Ok(candles);
+            }
+        }
+
+        if let Some(ref db) = self.feed.database {
+            match db.get_candles(&stored, interval, Some(limit)).await {
+                Ok(mut candles) => {
+                    candles.sort_by_key(|c| c.timestamp);
+                    if let Some(ref cache) = self.feed.cache {
+                        let _ = cache.cache_candles(&stored, interval, &candles).await;
+                    }
+                    return Ok(candles);
+                }
+                Err(e) => {
+                    warn!(symbol = %stored, interval = %interval, error = %e, "Failed to get candles from QuestDB")
+                }
+            }
+        }
+
+        let candles_map = self.feed.candles.read().await;
+        if let Some(buffer) = candles_map.get(&format!("{}_{}", symbol, interval)) {
+            let mut candles: Vec<Candle> = buffer.iter().cloned().collect();
+            candles.sort_by_key(|c| c.timestamp);
+            if candles.len() > limit {
+                candles.drain(..candles.len() - limit);
+            }
+            return Ok(candles);
+        }
+        drop(candles_map);
+
+        debug!(symbol = %stored, "No candles for {}, subscribing", stored);
+        if let Err(e) = self.subscribe_klines(symbol, interval).await {
+            error!(symbol = %stored, error = %e, "Failed to subscribe to {}", stored);
+        }
+        Ok(Vec::new())
+    }
+
+    async fn get_latest_price(
+        &self,
+        symbol: &str,
+    ) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
+        if let Some(&price) = self.feed.latest_prices.read().await.get(symbol) {
+            return Ok(price);
+        }
+        if let Err(e) = self.subscribe_klines(symbol, self.primary_interval()).await {
+            error!(symbol = %symbol, error = %e, "Failed to subscribe to {}", symbol);
+        }
+        // Wait a bit for a price to arrive
+        sleep(Duration::from_millis(500)).await;
+        Ok(self
+            .feed
+            .latest_prices
+            .read()
+            .await
+            .get(symbol)
+            .copied()
+            .unwrap_or(0.0))
+    }
+
+    async fn subscribe(
+        &self,
+        symbol: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        for interval in &self.candle_intervals {
+            if let Err(e) = self.subscribe_klines(symbol, interval).await {
+                error!(symbol = %symbol, interval = %interval, error = %e, "Failed to subscribe to {} {}", symbol, interval);
+            }
+        }
+        Ok(())
+    }
+
+    async fn unsubscribe(
+        &self,
+        symbol: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        // Streams of every interval, including those only `get_candles` subscribed
+        let prefix = format!("{}@kline_", symbol.to_lowercase());
+        let removed: Vec<String> = {
+            let mut streams = self.feed.streams.write().await;
+            let removed = streams
+                .iter()
+                .filter(|s| s.starts_with(&prefix))
+                .cloned()
+                .collect();
+            streams.retain(|s| !s.starts_with(&prefix));
+            removed
+        };
+
+        let buffer_prefix = format!("{}_", symbol);
+        self.feed
+            .candles
+            .write()
+            .await
+            .retain(|key, _| !key.starts_with(&buffer_prefix));
+        self.feed.latest_prices.write().await.remove(symbol);
+
+        if removed.is_empty() || !self.feed.client.is_connected().await {
+            return Ok(());
+        }
+        self.feed.send_unsubscribe(&removed).await
+    }
+
+    async fn is_connected(&self) -> bool {
+        // Nothing to be connected to before the first subscription
+        !self.started.load(Ordering::SeqCst) || self.feed.client.is_connected().await
+    }
+}
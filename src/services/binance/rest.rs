@@ -0,0 +1,245 @@
+//! Binance USDⓈ-M futures REST client for klines, funding and open interest
+
+use crate::config;
+use crate::models::indicators::Candle;
+use crate::services::hyperliquid::rest::FundingRatePoint;
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+use tracing::debug;
+
+/// Maximum number of klines returned by one `/fapi/v1/klines` request
+pub const KLINES_LIMIT: usize = 1500;
+
+/// Maximum number of entries returned by one `/fapi/v1/fundingRate` request
+pub const FUNDING_RATE_LIMIT: usize = 1000;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FundingRateEntry {
+    symbol: String,
+    funding_rate: String,
+    funding_time: i64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PremiumIndex {
+    symbol: String,
+    last_funding_rate: String,
+    time: i64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OpenInterest {
+    open_interest: String,
+}
+
+fn parse_f64(value: &str, field: &str) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
+    value
+        .parse()
+        .map_err(|e| format!("Invalid {}: {} ({})", field, value, e).into())
+}
+
+fn millis(ms: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp_millis(ms).unwrap_or_else(Utc::now)
+}
+
+/// Parse one kline array: `[openTime, open, high, low, close, volume, closeTime, ...]`.
+///
+/// Like Hyperliquid candles, the candle is stamped with its close time.
+pub fn parse_kline(kline: &Value) -> Result<Candle, Box<dyn std::error::Error + Send + Sync>> {
+    let fields = kline.as_array().ok_or("Kline is not an array")?;
+    let text = |i: usize, name: &str| -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
+        let value = fields
+            .get(i)
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| format!("Kline is missing {}", name))?;
+        parse_f64(value, name)
+    };
+    let close_time = fields
+        .get(6)
+        .and_then(|v| v.as_i64())
+        .ok_or("Kline is missing close time")?;
+    let timestamp = DateTime::from_timestamp(close_time / 1000, 0).unwrap_or_else(Utc::now);
+    Ok(Candle::new(
+        text(1, "open")?,
+        text(2, "high")?,
+        text(3, "low")?,
+        text(4, "close")?,
+        text(5, "volume")?,
+        timestamp,
+    )
+    .with_end_time(close_time, Utc::now()))
+}
+
+pub struct BinanceRestClient {
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl BinanceRestClient {
+    pub fn new() -> Self {
+        Self::with_client(
+            config::get_binance_futures_rest_url(),
+            reqwest::Client::new(),
+        )
+    }
+
+    pub fn with_client(base_url: impl Into<String>, client: reqwest::Client) -> Self {
+        Self {
+            base_url: base_url.into(),
+            client,
+        }
+    }
+
+    async fn get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<T, Box<dyn std::error::Error + Send + Sync>> {
+        let url = format!("{}{}", self.base_url, path);
+        let response = self
+            .client
+            .get(&url)
+            .query(query)
+            .send()
+            .await
+            .map_err(|e| format!("HTTP request failed: {}", e))?;
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .map_err(|e| format!("Failed to read response: {}", e))?;
+        if !status.is_success() {
+            debug!(status = %status, response = %text, "Binance REST API error response");
+            return Err(format!("HTTP error: {} - Response: {}", status, text).into());
+        }
+        serde_json::from_str(&text).map_err(|e| {
+            format!(
+                "Failed to parse {} response: {} - Response: {}",
+                path, e, text
+            )
+            .into()
+        })
+    }
+
+    /// Fetch the latest `count` klines, oldest first
+    pub async fn fetch_historical_candles(
+        &self,
+        symbol: &str,
+        interval: &str,
+        count: usize,
+    ) -> Result<Vec<Candle>, Box<dyn std::error::Error + Send + Sync>> {
+        let klines: Vec<Value> = self
+            .get(
+                "/fapi/v1/klines",
+                &[
+                    ("symbol", symbol.to_string()),
+                    ("interval", interval.to_string()),
+                    ("limit", count.min(KLINES_LIMIT).to_string()),
+                ],
+            )
+            .await?;
+        let mut candles = klines
+            .iter()
+            .map(parse_kline)
+            .collect::<Result<Vec<_>, _>>()?;
+        candles.sort_by_key(|c| c.timestamp);
+        Ok(candles)
+    }
+
+    /// Fetch up to `KLINES_LIMIT` klines opening within `[start_time, end_time]` (Unix millis), oldest first
+    pub async fn fetch_candles_range(
+        &self,
+        symbol: &str,
+        interval: &str,
+        start_time: u64,
+        end_time: u64,
+    ) -> Result<Vec<Candle>, Box<dyn std::error::Error + Send + Sync>> {
+        let klines: Vec<Value> = self
+            .get(
+                "/fapi/v1/klines",
+                &[
+                    ("symbol", symbol.to_string()),
+                    ("interval", interval.to_string()),
+                    ("startTime", start_time.to_string()),
+                    ("endTime", end_time.to_string()),
+                    ("limit", KLINES_LIMIT.to_string()),
+                ],
+            )
+            .await?;
+        let mut candles = klines
+            .iter()
+            .map(parse_kline)
+            .collect::<Result<Vec<_>, _>>()?;
+        candles.sort_by_key(|c| c.timestamp);
+        Ok(candles)
+    }
+
+    /// Fetch funding rate history within `[start_time, end_time]` (Unix millis), oldest first
+    pub async fn fetch_funding_history(
+        &self,
+        symbol: &str,
+        start_time: u64,
+        end_time: u64,
+    ) -> Result<Vec<FundingRatePoint>, Box<dyn std::error::Error + Send + Sync>> {
+        let entries: Vec<FundingRateEntry> = self
+            .get(
+                "/fapi/v1/fundingRate",
+                &[
+                    ("symbol", symbol.to_string()),
+                    ("startTime", start_time.to_string()),
+                    ("endTime", end_time.to_string()),
+                    ("limit", FUNDING_RATE_LIMIT.to_string()),
+                ],
+            )
+            .await?;
+        let mut points = entries
+            .into_iter()
+            .map(|e| {
+                Ok(FundingRatePoint {
+                    funding_rate: parse_f64(&e.funding_rate, "funding rate")?,
+                    coin: e.symbol,
+                    timestamp: millis(e.funding_time),
+                })
+            })
+            .collect::<Result<Vec<_>, Box<dyn std::error::Error + Send + Sync>>>()?;
+        points.sort_by_key(|p| p.timestamp);
+        Ok(points)
+    }
+
+    /// Funding rate of the last settled funding interval
+    pub async fn fetch_latest_funding_rate(
+        &self,
+        symbol: &str,
+    ) -> Result<FundingRatePoint, Box<dyn std::error::Error + Send + Sync>> {
+        let index: PremiumIndex = self
+            .get("/fapi/v1/premiumIndex", &[("symbol", symbol.to_string())])
+            .await?;
+        Ok(FundingRatePoint {
+            funding_rate: parse_f64(&index.last_funding_rate, "funding rate")?,
+            coin: index.symbol,
+            timestamp: millis(index.time),
+        })
+    }
+
+    /// Current open interest in base units
+    pub async fn fetch_open_interest(
+        &self,
+        symbol: &str,
+    ) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
+        let open_interest: OpenInterest = self
+            .get("/fapi/v1/openInterest", &[("symbol", symbol.to_string())])
+            .await?;
+        parse_f64(&open_interest.open_interest, "open interest")
+    }
+}
+
+impl Default for BinanceRestClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
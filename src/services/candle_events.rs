@@ -0,0 +1,52 @@
+//! Candle close events
+//!
+//! Market data providers broadcast a [`MarketEvent::CandleClosed`] when a bar
+//! closes. The websocket-service forwards them to Redis pub/sub so
+//! the worker can evaluate the strategies of a symbol as soon as its bar
+//! closes, instead of waiting for the next cron tick.
+
+use crate::cache::RedisCache;
+use crate::services::market_bus::MarketEvent;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+
+/// Redis pub/sub channel carrying JSON-encoded [`CandleClosed`] events
+pub const CANDLE_CLOSED_CHANNEL: &str = "events:candle_closed";
+
+/// A bar of `symbol` and `interval` closed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CandleClosed {
+    /// Symbol as stored (e.g., "BTC", "binance:BTCUSDT")
+    pub symbol: String,
+    pub interval: String,
+    /// Close time of the bar, which is also the candle timestamp
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Publish candle closes to Redis until the channel closes
+pub async fn forward_to_redis(
+    mut events: broadcast::Receiver<MarketEvent>,
+    cache: Arc<RedisCache>,
+) {
+    loop {
+        match events.recv().await {
+            Ok(MarketEvent::CandleClosed(event)) => {
+                if let Err(e) = cache.publish_candle_closed(&event).await {
+                    warn!(symbol = %event.symbol, interval = %event.interval, error = %e, "Failed to publish candle close for {}", event.symbol);
+                }
+            }
+            Ok(_) => {}
+            Err(broadcast::error::RecvError::Lagged(missed)) => {
+                // The cron fallback picks up whatever was missed
+                warn!(missed = missed, "Missed {} market events", missed);
+            }
+            Err(broadcast::error::RecvError::Closed) => {
+                info!("Candle close stream closed");
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,560 @@
+//! Candle history gap detection and repair
+//!
+//! Bars go missing while the WebSocket is disconnected or the
+//! websocket-service is down, and indicators would otherwise compute across
+//! the hole. The [`CandleGapAuditor`] checks the recent stored history of
+//! every watched symbol and interval, on reconnect and periodically, fetches
+//! the missing bars through the venue's [`GapFiller`], and merges them into
+//! the Redis cache. The worker refuses to evaluate candles with gaps (see
+//! [`find_gaps`]).
+//!
+//! Some bars never exist: the venue had an outage, or an illiquid coin did not
+//! trade during an interval. Bars a successful fill could not find are recorded
+//! in an [`EmptyRangeStore`] as confirmed empty; they are not fetched again and
+//! do not count as gaps.
+
+use crate::cache::RedisCache;
+use crate::db::CandleStore;
+use crate::metrics::Metrics;
+use crate::models::indicators::Candle;
+use crate::services::hyperliquid::backfill::CandleBackfiller;
+use crate::services::hyperliquid::rest::interval_duration_ms;
+use crate::services::market_data::split_symbol;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, Duration};
+use tracing::{debug, info, warn};
+
+/// Stored bars checked per audit
+pub const DEFAULT_LOOKBACK_BARS: usize = 500;
+
+/// Time given to a reconnected feed to deliver its first bars before auditing
+const RECONNECT_AUDIT_DELAY: Duration = Duration::from_secs(10);
+
+/// Consecutive bars missing from a candle history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CandleGap {
+    /// Timestamp of the first missing bar
+    pub from: DateTime<Utc>,
+    /// Timestamp of the last missing bar
+    pub to: DateTime<Utc>,
+    pub missing_bars: usize,
+}
+
+impl CandleGap {
+    fn after(last: DateTime<Utc>, interval_ms: i64, missing_bars: usize) -> Self {
+        Self {
+            from: last + ChronoDuration::milliseconds(interval_ms),
+            to: last + ChronoDuration::milliseconds(interval_ms * missing_bars as i64),
+            missing_bars,
+        }
+    }
+
+    /// The bars from `from` to `to` (inclusive), `None` if that is empty
+    fn between(from: DateTime<Utc>, to: DateTime<Utc>, interval_ms: i64) -> Option<Self> {
+        (from <= to).then(|| Self {
+            from,
+            to,
+            missing_bars: ((to - from).num_milliseconds() / interval_ms) as usize + 1,
+        })
+    }
+
+    /// The bars of this gap that also lie in `other`
+    fn overlap(&self, other: &CandleGap, interval_ms: i64) -> Option<Self> {
+        Self::between(
+            self.from.max(other.from),
+            self.to.min(other.to),
+            interval_ms,
+        )
+    }
+}
+
+/// `gaps` without the bars in `confirmed_empty`, splitting gaps around them
+fn exclude_ranges(
+    gaps: Vec<CandleGap>,
+    confirmed_empty: &[CandleGap],
+    interval_ms: i64,
+) -> Vec<CandleGap> {
+    let interval = ChronoDuration::milliseconds(interval_ms);
+    confirmed_empty.iter().fold(gaps, |gaps, empty| {
+        gaps.into_iter()
+            .flat_map(|gap| {
+                if gap.to < empty.from || gap.from > empty.to {
+                    return vec![gap];
+                }
+                [
+                    CandleGap::between(gap.from, empty.from - interval, interval_ms),
+                    CandleGap::between(empty.to + interval, gap.to, interval_ms),
+                ]
+                .into_iter()
+                .flatten()
+                .collect()
+            })
+            .collect()
+    })
+}
+
+/// Sorted ranges with overlapping and adjacent ones joined
+fn merge_ranges(mut ranges: Vec<CandleGap>, interval_ms: i64) -> Vec<CandleGap> {
+    ranges.sort_by_key(|r| r.from);
+    let interval = ChronoDuration::milliseconds(interval_ms);
+    let mut merged: Vec<CandleGap> = Vec::new();
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.from <= last.to + interval => {
+                if range.to > last.to {
+                    *last = CandleGap::between(last.from, range.to, interval_ms)
+                        .expect("range ends after it starts");
+                }
+            }
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// Gaps between consecutive candles, sorted by timestamp, leaving out the
+/// bars in `confirmed_empty`.
+///
+/// Duplicate timestamps are ignored; spacing is rounded to whole intervals.
+pub fn find_gaps(
+    candles: &[Candle],
+    interval_ms: u64,
+    confirmed_empty: &[CandleGap],
+) -> Vec<CandleGap> {
+    let interval_ms = interval_ms.max(1) as i64;
+    let mut timestamps: Vec<DateTime<Utc>> = candles.iter().map(|c| c.timestamp).collect();
+    timestamps.sort();
+    timestamps.dedup();
+    let gaps = timestamps
+        .windows(2)
+        .filter_map(|pair| {
+            let spacing = (pair[1] - pair[0]).num_milliseconds();
+            let bars = (spacing + interval_ms / 2) / interval_ms;
+            (bars > 1).then(|| CandleGap::after(pair[0], interval_ms, bars as usize - 1))
+        })
+        .collect();
+    exclude_ranges(gaps, confirmed_empty, interval_ms)
+}
+
+/// Closed bars missing after the last candle as of `now`.
+///
+/// The most recently closed bar is left to the live feed, which stores it
+/// once the next bar starts.
+pub fn trailing_gap(candles: &[Candle], interval_ms: u64, now: DateTime<Utc>) -> Option<CandleGap> {
+    let interval_ms = interval_ms.max(1) as i64;
+    let last = candles.iter().map(|c| c.timestamp).max()?;
+    let closed_since = (now - last).num_milliseconds() / interval_ms;
+    (closed_since > 1).then(|| CandleGap::after(last, interval_ms, closed_since as usize - 1))
+}
+
+/// Gaps in `candles` and after them, leaving out the bars in
+/// `confirmed_empty`; what the worker refuses to evaluate
+pub fn recent_gaps(
+    candles: &[Candle],
+    interval_ms: u64,
+    now: DateTime<Utc>,
+    confirmed_empty: &[CandleGap],
+) -> Vec<CandleGap> {
+    let mut gaps = find_gaps(candles, interval_ms, confirmed_empty);
+    gaps.extend(exclude_ranges(
+        trailing_gap(candles, interval_ms, now)
+            .into_iter()
+            .collect(),
+        confirmed_empty,
+        interval_ms.max(1) as i64,
+    ));
+    gaps
+}
+
+/// Shared record of the bars a venue confirmed it has no data for
+#[async_trait]
+pub trait EmptyRangeStore: Send + Sync {
+    /// Confirmed empty ranges of `symbol` and `interval`, sorted
+    async fn load_empty_ranges(
+        &self,
+        symbol: &str,
+        interval: &str,
+    ) -> Result<Vec<CandleGap>, Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn save_empty_ranges(
+        &self,
+        symbol: &str,
+        interval: &str,
+        ranges: &[CandleGap],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// Process-local store, for tests and single-process setups
+#[derive(Default)]
+pub struct MemoryEmptyRangeStore {
+    ranges: Mutex<HashMap<(String, String), Vec<CandleGap>>>,
+}
+
+#[async_trait]
+impl EmptyRangeStore for MemoryEmptyRangeStore {
+    async fn load_empty_ranges(
+        &self,
+        symbol: &str,
+        interval: &str,
+    ) -> Result<Vec<CandleGap>, Box<dyn std::error::Error + Send + Sync>> {
+        let ranges = self.ranges.lock().unwrap();
+        Ok(ranges
+            .get(&(symbol.to_string(), interval.to_string()))
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    async fn save_empty_ranges(
+        &self,
+        symbol: &str,
+        interval: &str,
+        ranges: &[CandleGap],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.ranges
+            .lock()
+            .unwrap()
+            .insert((symbol.to_string(), interval.to_string()), ranges.to_vec());
+        Ok(())
+    }
+}
+
+/// Source of missing bars for one venue
+#[async_trait]
+pub trait GapFiller: Send + Sync {
+    /// Fetch the closed bars stamped between `from` and `to` (inclusive) and
+    /// store those not stored yet, returning the number stored
+    async fn fill(
+        &self,
+        symbol: &str,
+        interval: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+#[async_trait]
+impl GapFiller for CandleBackfiller {
+    async fn fill(
+        &self,
+        symbol: &str,
+        interval: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self
+            .backfill_gap(symbol, interval, from, to)
+            .await?
+            .candles_stored)
+    }
+}
+
+/// Outcome of auditing one symbol and interval
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GapReport {
+    pub symbol: String,
+    pub interval: String,
+    /// Gaps found before repairing
+    pub gaps: Vec<CandleGap>,
+    pub repaired_bars: usize,
+    /// Missing bars the venue confirmed it has no data for
+    pub confirmed_empty_bars: usize,
+    /// Bars still missing after repairing
+    pub missing_bars: usize,
+}
+
+pub struct CandleGapAuditor {
+    store: Arc<dyn CandleStore>,
+    /// Gap fillers by venue; gaps of other venues are only reported
+    fillers: HashMap<String, Arc<dyn GapFiller>>,
+    cache: Option<Arc<RedisCache>>,
+    empty_ranges: Arc<dyn EmptyRangeStore>,
+    metrics: Option<Arc<Metrics>>,
+    lookback_bars: usize,
+    watched: Mutex<BTreeSet<(String, String)>>,
+    handles: Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl CandleGapAuditor {
+    pub fn new(store: Arc<dyn CandleStore>) -> Self {
+        Self {
+            store,
+            fillers: HashMap::new(),
+            cache: None,
+            empty_ranges: Arc::new(MemoryEmptyRangeStore::default()),
+            metrics: None,
+            lookback_bars: DEFAULT_LOOKBACK_BARS,
+            watched: Mutex::new(BTreeSet::new()),
+            handles: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Repair gaps of `venue` symbols through `filler`
+    pub fn with_filler(mut self, venue: &str, filler: Arc<dyn GapFiller>) -> Self {
+        self.fillers.insert(venue.to_lowercase(), filler);
+        self
+    }
+
+    /// Merge repaired bars into the cached candles
+    pub fn with_cache(mut self, cache: Arc<RedisCache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Share confirmed empty ranges through `store`, so the worker skips them
+    pub fn with_empty_ranges(mut self, store: Arc<dyn EmptyRangeStore>) -> Self {
+        self.empty_ranges = store;
+        self
+    }
+
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Number of most recent bars checked per audit
+    pub fn with_lookback(mut self, bars: usize) -> Self {
+        self.lookback_bars = bars.max(2);
+        self
+    }
+
+    /// Audit `symbol` in each of `intervals` from now on
+    pub fn watch(&self, symbol: &str, intervals: &[String]) {
+        let mut watched = self.watched.lock().unwrap();
+        for interval in intervals {
+            watched.insert((symbol.to_string(), interval.clone()));
+        }
+    }
+
+    /// Stop auditing `symbol`
+    pub fn unwatch(&self, symbol: &str) {
+        self.watched.lock().unwrap().retain(|(s, _)| s != symbol);
+    }
+
+    /// Watched (symbol, interval) pairs, sorted
+    pub fn watched(&self) -> Vec<(String, String)> {
+        self.watched.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Find and repair the gaps in the recent history of `symbol` and `interval`
+    pub async fn audit(
+        &self,
+        symbol: &str,
+        interval: &str,
+        now: DateTime<Utc>,
+    ) -> Result<GapReport, Box<dyn std::error::Error + Send + Sync>> {
+        let interval_ms = interval_duration_ms(interval)
+            .ok_or_else(|| format!("Unsupported candle interval: {}", interval))?;
+        let confirmed_empty = match self.empty_ranges.load_empty_ranges(symbol, interval).await {
+            Ok(ranges) => ranges,
+            Err(e) => {
+                warn!(symbol = %symbol, interval = %interval, error = %e, "Failed to load confirmed empty ranges of {}/{}", symbol, interval);
+                Vec::new()
+            }
+        };
+        let mut report = GapReport {
+            symbol: symbol.to_string(),
+            interval: interval.to_string(),
+            gaps: self
+                .stored_gaps(symbol, interval, interval_ms, now, &confirmed_empty)
+                .await?,
+            repaired_bars: 0,
+            confirmed_empty_bars: 0,
+            missing_bars: 0,
+        };
+        if let Some(ref metrics) = self.metrics {
+            metrics
+                .candle_gaps_detected_total
+                .with_label_values(&[symbol, interval])
+                .inc_by(report.gaps.len() as f64);
+        }
+
+        if !report.gaps.is_empty() {
+            let missing: usize = report.gaps.iter().map(|g| g.missing_bars).sum();
+            warn!(symbol = %symbol, interval = %interval, gaps = report.gaps.len(), missing = missing, "Found {} gaps ({} bars) in {}/{} candles", report.gaps.len(), missing, symbol, interval);
+
+            let (venue, _) = split_symbol(symbol);
+            // Gaps the venue was asked for successfully, whatever it returned
+            let mut filled = Vec::new();
+            match self.fillers.get(&venue) {
+                Some(filler) => {
+                    for gap in &report.gaps {
+                        match filler.fill(symbol, interval, gap.from, gap.to).await {
+                            Ok(stored) => {
+                                report.repaired_bars += stored;
+                                filled.push(gap.clone());
+                            }
+                            Err(e) => {
+                                warn!(symbol = %symbol, interval = %interval, from = %gap.from, to = %gap.to, error = %e, "Failed to repair {}/{} gap from {} to {}", symbol, interval, gap.from, gap.to)
+                            }
+                        }
+                    }
+                }
+                None => {
+                    debug!(symbol = %symbol, venue = %venue, "No gap filler for venue {}", venue)
+                }
+            }
+
+            if report.repaired_bars > 0 {
+                self.refresh_cache(&report).await;
+            }
+            let mut remaining = self
+                .stored_gaps(symbol, interval, interval_ms, now, &confirmed_empty)
+                .await?;
+
+            // Whatever a successful fill did not find does not exist
+            let newly_empty: Vec<CandleGap> = remaining
+                .iter()
+                .flat_map(|gap| {
+                    filled
+                        .iter()
+                        .filter_map(|f| gap.overlap(f, interval_ms as i64))
+                })
+                .collect();
+            if !newly_empty.is_empty() {
+                report.confirmed_empty_bars = newly_empty.iter().map(|g| g.missing_bars).sum();
+                remaining = exclude_ranges(remaining, &newly_empty, interval_ms as i64);
+                let mut ranges = confirmed_empty;
+                ranges.extend(newly_empty);
+                // Ranges past the lookback are never audited or evaluated again
+                let since = self.lookback_start(interval_ms, now);
+                let ranges: Vec<CandleGap> = merge_ranges(ranges, interval_ms as i64)
+                    .into_iter()
+                    .filter(|r| r.to >= since)
+                    .collect();
+                if let Err(e) = self
+                    .empty_ranges
+                    .save_empty_ranges(symbol, interval, &ranges)
+                    .await
+                {
+                    warn!(symbol = %symbol, interval = %interval, error = %e, "Failed to save confirmed empty ranges of {}/{}", symbol, interval);
+                }
+            }
+            report.missing_bars = remaining.iter().map(|g| g.missing_bars).sum();
+            info!(symbol = %symbol, interval = %interval, repaired = report.repaired_bars, empty = report.confirmed_empty_bars, missing = report.missing_bars, "Repaired {} bars of {}/{}, {} confirmed empty, {} still missing", report.repaired_bars, symbol, interval, report.confirmed_empty_bars, report.missing_bars);
+        }
+
+        if let Some(ref metrics) = self.metrics {
+            metrics
+                .candle_missing_bars
+                .with_label_values(&[symbol, interval])
+                .set(report.missing_bars as f64);
+        }
+        Ok(report)
+    }
+
+    /// Audit every watched symbol and interval; failures are logged and skipped
+    pub async fn audit_all(&self, now: DateTime<Utc>) -> Vec<GapReport> {
+        let mut reports = Vec::new();
+        for (symbol, interval) in self.watched() {
+            match self.audit(&symbol, &interval, now).await {
+                Ok(report) => reports.push(report),
+                Err(e) => {
+                    warn!(symbol = %symbol, interval = %interval, error = %e, "Failed to audit {}/{} candles", symbol, interval)
+                }
+            }
+        }
+        reports
+    }
+
+    /// Audit every `period`, starting after the first period
+    pub fn start(self: &Arc<Self>, period: Duration) {
+        let auditor = self.clone();
+        let handle = tokio::spawn(async move {
+            loop {
+                sleep(period).await;
+                auditor.audit_all(Utc::now()).await;
+            }
+        });
+        self.handles.lock().unwrap().push(handle);
+    }
+
+    /// Audit shortly after each notification that a feed (re)connected
+    pub fn audit_on_connect(self: &Arc<Self>, mut connections: broadcast::Receiver<()>) {
+        let auditor = self.clone();
+        let handle = tokio::spawn(async move {
+            loop {
+                match connections.recv().await {
+                    Ok(()) | Err(broadcast::error::RecvError::Lagged(_)) => {
+                        sleep(RECONNECT_AUDIT_DELAY).await;
+                        // Drain reconnects that happened meanwhile; one audit covers them
+                        while connections.try_recv().is_ok() {}
+                        info!("Feed connected, auditing candle history");
+                        auditor.audit_all(Utc::now()).await;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return,
+                }
+            }
+        });
+        self.handles.lock().unwrap().push(handle);
+    }
+
+    pub fn stop(&self) {
+        for handle in self.handles.lock().unwrap().drain(..) {
+            handle.abort();
+        }
+    }
+
+    /// Start of the audited history as of `now`
+    fn lookback_start(&self, interval_ms: u64, now: DateTime<Utc>) -> DateTime<Utc> {
+        now - ChronoDuration::milliseconds((interval_ms * self.lookback_bars as u64) as i64)
+    }
+
+    async fn stored_gaps(
+        &self,
+        symbol: &str,
+        interval: &str,
+        interval_ms: u64,
+        now: DateTime<Utc>,
+        confirmed_empty: &[CandleGap],
+    ) -> Result<Vec<CandleGap>, Box<dyn std::error::Error + Send + Sync>> {
+        let since = self.lookback_start(interval_ms, now);
+        let candles = self
+            .store
+            .candles_in_range(symbol, interval, Some(since), None)
+            .await?;
+        Ok(recent_gaps(&candles, interval_ms, now, confirmed_empty))
+    }
+
+    /// Merge the repaired bars into the cached candles, keeping the cache's
+    /// depth; the cache is dropped if that fails so readers fall back to storage
+    async fn refresh_cache(&self, report: &GapReport) {
+        let Some(ref cache) = self.cache else {
+            return;
+        };
+        let (symbol, interval) = (report.symbol.as_str(), report.interval.as_str());
+        let (Some(first), Some(last)) = (report.gaps.first(), report.gaps.last()) else {
+            return;
+        };
+        let refreshed = async {
+            let Some(mut cached) = cache.get_cached_candles(symbol, interval).await? else {
+                return Ok(());
+            };
+            let repaired = self
+                .store
+                .candles_in_range(symbol, interval, Some(first.from), Some(last.to))
+                .await?;
+            let depth = cached.len();
+            cached.retain(|c| !repaired.iter().any(|r| r.timestamp == c.timestamp));
+            cached.extend(repaired);
+            cached.sort_by_key(|c| c.timestamp);
+            cached.dedup_by_key(|c| c.timestamp);
+            if cached.len() > depth {
+                cached.drain(..cached.len() - depth);
+            }
+            cache.cache_candles(symbol, interval, &cached).await
+        };
+        let result: Result<(), Box<dyn std::error::Error + Send + Sync>> = refreshed.await;
+        if let Err(e) = result {
+            warn!(symbol = %symbol, interval = %interval, error = %e, "Failed to refresh cached {}/{} candles, invalidating", symbol, interval);
+            if let Err(e) = cache.invalidate_candles(symbol, interval).await {
+                warn!(symbol = %symbol, interval = %interval, error = %e, "Failed to invalidate cached {}/{} candles", symbol, interval);
+            }
+        }
+    }
+}
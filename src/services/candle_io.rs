@@ -0,0 +1,492 @@
+//! Candle import/export in CSV and Parquet
+//!
+//! Both formats use the same columns: `timestamp`, `open`, `high`, `low`, `close`,
+//! `volume`, `open_interest` and `funding_rate` (the last two optional). CSV
+//! timestamps are written as RFC 3339 and read as RFC 3339, `YYYY-MM-DD` or epoch
+//! milliseconds; Parquet stores them as UTC millisecond timestamps.
+
+use crate::db::CandleStore;
+use crate::models::indicators::Candle;
+use arrow_array::{Array, ArrayRef, Float64Array, Int64Array, RecordBatch, TimestampMillisecondArray};
+use arrow_schema::{DataType, Field, Schema, TimeUnit};
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ArrowWriter;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CandleFormat {
+    Csv,
+    Parquet,
+}
+
+impl CandleFormat {
+    /// Infer the format from a file extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| ext.parse().ok())
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            CandleFormat::Csv => "csv",
+            CandleFormat::Parquet => "parquet",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            CandleFormat::Csv => "text/csv",
+            CandleFormat::Parquet => "application/vnd.apache.parquet",
+        }
+    }
+}
+
+impl FromStr for CandleFormat {
+    type Err = CandleIoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "csv" => Ok(CandleFormat::Csv),
+            "parquet" | "pq" => Ok(CandleFormat::Parquet),
+            other => Err(CandleIoError::Format(format!(
+                "unsupported candle format '{}'",
+                other
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum CandleIoError {
+    /// File could not be encoded or decoded
+    Format(String),
+    /// A candle failed validation; `row` is 1-based in file order
+    InvalidRow { row: usize, reason: String },
+    /// Reading from or writing to the candle store failed
+    Storage(String),
+}
+
+impl fmt::Display for CandleIoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CandleIoError::Format(msg) => write!(f, "Invalid candle file: {}", msg),
+            CandleIoError::InvalidRow { row, reason } => {
+                write!(f, "Invalid candle at row {}: {}", row, reason)
+            }
+            CandleIoError::Storage(msg) => write!(f, "Candle storage error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for CandleIoError {}
+
+/// Outcome of importing a candle file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CandleImportReport {
+    pub symbol: String,
+    pub interval: String,
+    /// Valid candles read from the file
+    pub rows: usize,
+    /// Candles written to the store
+    pub imported: usize,
+    /// Candles skipped because their timestamp was already stored
+    pub skipped_existing: usize,
+    pub first: Option<DateTime<Utc>>,
+    pub last: Option<DateTime<Utc>>,
+}
+
+/// Parse an RFC 3339 timestamp, a `YYYY-MM-DD` date (midnight UTC) or epoch milliseconds
+pub fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(ts) = DateTime::parse_from_rfc3339(value) {
+        return Some(ts.with_timezone(&Utc));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return date.and_hms_opt(0, 0, 0).map(|dt| dt.and_utc());
+    }
+    value
+        .parse::<i64>()
+        .ok()
+        .and_then(DateTime::from_timestamp_millis)
+}
+
+#[derive(Serialize, Deserialize)]
+struct CsvCandleRecord {
+    timestamp: String,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    volume: f64,
+    open_interest: Option<f64>,
+    funding_rate: Option<f64>,
+}
+
+/// Encode candles in the given format
+pub fn encode_candles(candles: &[Candle], format: CandleFormat) -> Result<Vec<u8>, CandleIoError> {
+    match format {
+        CandleFormat::Csv => encode_csv(candles),
+        CandleFormat::Parquet => encode_parquet(candles),
+    }
+}
+
+/// Decode candles without validating them
+pub fn decode_candles(data: &[u8], format: CandleFormat) -> Result<Vec<Candle>, CandleIoError> {
+    match format {
+        CandleFormat::Csv => decode_csv(data),
+        CandleFormat::Parquet => decode_parquet(data),
+    }
+}
+
+/// Check every candle for consistent OHLCV values, then sort oldest first and
+/// reject duplicate timestamps
+pub fn validate_candles(candles: &mut [Candle]) -> Result<(), CandleIoError> {
+    for (i, candle) in candles.iter().enumerate() {
+        validate_candle(candle).map_err(|reason| CandleIoError::InvalidRow { row: i + 1, reason })?;
+    }
+
+    let mut seen = HashSet::with_capacity(candles.len());
+    for (i, candle) in candles.iter().enumerate() {
+        if !seen.insert(candle.timestamp) {
+            return Err(CandleIoError::InvalidRow {
+                row: i + 1,
+                reason: format!("duplicate timestamp {}", candle.timestamp),
+            });
+        }
+    }
+
+    candles.sort_by_key(|c| c.timestamp);
+    Ok(())
+}
+
+fn validate_candle(candle: &Candle) -> Result<(), String> {
+    let prices = [
+        ("open", candle.open),
+        ("high", candle.high),
+        ("low", candle.low),
+        ("close", candle.close),
+    ];
+    for (name, value) in prices {
+        if !value.is_finite() || value <= 0.0 {
+            return Err(format!("{} must be a positive number, got {}", name, value));
+        }
+    }
+    if candle.high < candle.open.max(candle.close) || candle.low > candle.open.min(candle.close) {
+        return Err(format!(
+            "high/low ({}/{}) do not contain open/close ({}/{})",
+            candle.high, candle.low, candle.open, candle.close
+        ));
+    }
+    if !candle.volume.is_finite() || candle.volume < 0.0 {
+        return Err(format!("volume must be non-negative, got {}", candle.volume));
+    }
+    if let Some(oi) = candle.open_interest {
+        if !oi.is_finite() || oi < 0.0 {
+            return Err(format!("open_interest must be non-negative, got {}", oi));
+        }
+    }
+    if let Some(rate) = candle.funding_rate {
+        if !rate.is_finite() {
+            return Err(format!("funding_rate must be finite, got {}", rate));
+        }
+    }
+    Ok(())
+}
+
+/// Export stored candles for a symbol and interval over an optional inclusive range
+pub async fn export_candles(
+    store: &dyn CandleStore,
+    symbol: &str,
+    interval: &str,
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+    format: CandleFormat,
+) -> Result<Vec<u8>, CandleIoError> {
+    let candles = store
+        .candles_in_range(symbol, interval, start, end)
+        .await
+        .map_err(|e| CandleIoError::Storage(e.to_string()))?;
+    encode_candles(&candles, format)
+}
+
+/// Decode, validate and store a candle file.
+///
+/// Candles whose timestamp is already stored are skipped, so importing the same
+/// file twice does not duplicate rows.
+pub async fn import_candles(
+    store: &dyn CandleStore,
+    symbol: &str,
+    interval: &str,
+    data: &[u8],
+    format: CandleFormat,
+) -> Result<CandleImportReport, CandleIoError> {
+    let mut candles = decode_candles(data, format)?;
+    validate_candles(&mut candles)?;
+
+    let first = candles.first().map(|c| c.timestamp);
+    let last = candles.last().map(|c| c.timestamp);
+    let rows = candles.len();
+
+    if rows > 0 {
+        let existing: HashSet<DateTime<Utc>> = store
+            .candles_in_range(symbol, interval, first, last)
+            .await
+            .map_err(|e| CandleIoError::Storage(e.to_string()))?
+            .into_iter()
+            .map(|c| c.timestamp)
+            .collect();
+        candles.retain(|c| !existing.contains(&c.timestamp));
+
+        if !candles.is_empty() {
+            store
+                .store_candles_batch(symbol, interval, &candles)
+                .await
+                .map_err(|e| CandleIoError::Storage(e.to_string()))?;
+        }
+    }
+
+    Ok(CandleImportReport {
+        symbol: symbol.to_string(),
+        interval: interval.to_string(),
+        rows,
+        imported: candles.len(),
+        skipped_existing: rows - candles.len(),
+        first,
+        last,
+    })
+}
+
+fn encode_csv(candles: &[Candle]) -> Result<Vec<u8>, CandleIoError> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for candle in candles {
+        writer
+            .serialize(CsvCandleRecord {
+                timestamp: candle.timestamp.to_rfc3339_opts(SecondsFormat::Millis, true),
+                open: candle.open,
+                high: candle.high,
+                low: candle.low,
+                close: candle.close,
+                volume: candle.volume,
+                open_interest: candle.open_interest,
+                funding_rate: candle.funding_rate,
+            })
+            .map_err(|e| CandleIoError::Format(e.to_string()))?;
+    }
+    if candles.is_empty() {
+        writer
+            .write_record(CSV_HEADER)
+            .map_err(|e| CandleIoError::Format(e.to_string()))?;
+    }
+    writer
+        .into_inner()
+        .map_err(|e| CandleIoError::Format(e.to_string()))
+}
+
+const CSV_HEADER: [&str; 8] = [
+    "timestamp",
+    "open",
+    "high",
+    "low",
+    "close",
+    "volume",
+    "open_interest",
+    "funding_rate",
+];
+
+fn decode_csv(data: &[u8]) -> Result<Vec<Candle>, CandleIoError> {
+    let mut reader = csv::ReaderBuilder::new().trim(csv::Trim::All).from_reader(data);
+    let mut candles = Vec::new();
+    for (i, record) in reader.deserialize::<CsvCandleRecord>().enumerate() {
+        let record = record.map_err(|e| CandleIoError::InvalidRow {
+            row: i + 1,
+            reason: e.to_string(),
+        })?;
+        let timestamp = parse_timestamp(&record.timestamp).ok_or_else(|| {
+            CandleIoError::InvalidRow {
+                row: i + 1,
+                reason: format!("invalid timestamp '{}'", record.timestamp),
+            }
+        })?;
+        candles.push(Candle {
+            open: record.open,
+            high: record.high,
+            low: record.low,
+            close: record.close,
+            volume: record.volume,
+            timestamp,
+            open_interest: record.open_interest,
+            funding_rate: record.funding_rate,
+            mark_price: None,
+            oracle_price: None,
+            premium: None,
+            day_notional_volume: None,
+            is_closed: true,
+        });
+    }
+    Ok(candles)
+}
+
+fn parquet_schema() -> Arc<Schema> {
+    Arc::new(Schema::new(vec![
+        Field::new(
+            "timestamp",
+            DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into())),
+            false,
+        ),
+        Field::new("open", DataType::Float64, false),
+        Field::new("high", DataType::Float64, false),
+        Field::new("low", DataType::Float64, false),
+        Field::new("close", DataType::Float64, false),
+        Field::new("volume", DataType::Float64, false),
+        Field::new("open_interest", DataType::Float64, true),
+        Field::new("funding_rate", DataType::Float64, true),
+    ]))
+}
+
+fn encode_parquet(candles: &[Candle]) -> Result<Vec<u8>, CandleIoError> {
+    let schema = parquet_schema();
+    let values = |f: fn(&Candle) -> f64| -> ArrayRef {
+        Arc::new(Float64Array::from_iter_values(candles.iter().map(f)))
+    };
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(
+            TimestampMillisecondArray::from_iter_values(
+                candles.iter().map(|c| c.timestamp.timestamp_millis()),
+            )
+            .with_timezone("UTC"),
+        ),
+        values(|c| c.open),
+        values(|c| c.high),
+        values(|c| c.low),
+        values(|c| c.close),
+        values(|c| c.volume),
+        Arc::new(Float64Array::from_iter(candles.iter().map(|c| c.open_interest))),
+        Arc::new(Float64Array::from_iter(candles.iter().map(|c| c.funding_rate))),
+    ];
+    let batch = RecordBatch::try_new(schema.clone(), columns)
+        .map_err(|e| CandleIoError::Format(e.to_string()))?;
+
+    let mut buffer = Vec::new();
+    let mut writer = ArrowWriter::try_new(&mut buffer, schema, None)
+        .map_err(|e| CandleIoError::Format(e.to_string()))?;
+    writer
+        .write(&batch)
+        .map_err(|e| CandleIoError::Format(e.to_string()))?;
+    writer
+        .close()
+        .map_err(|e| CandleIoError::Format(e.to_string()))?;
+    Ok(buffer)
+}
+
+fn decode_parquet(data: &[u8]) -> Result<Vec<Candle>, CandleIoError> {
+    let reader = ParquetRecordBatchReaderBuilder::try_new(bytes::Bytes::copy_from_slice(data))
+        .and_then(|builder| builder.build())
+        .map_err(|e| CandleIoError::Format(e.to_string()))?;
+
+    let mut candles = Vec::new();
+    for batch in reader {
+        let batch = batch.map_err(|e| CandleIoError::Format(e.to_string()))?;
+        let timestamps = timestamp_column(&batch)?;
+        let open = float_column(&batch, "open")?;
+        let high = float_column(&batch, "high")?;
+        let low = float_column(&batch, "low")?;
+        let close = float_column(&batch, "close")?;
+        let volume = float_column(&batch, "volume")?;
+        let open_interest = optional_float_column(&batch, "open_interest")?;
+        let funding_rate = optional_float_column(&batch, "funding_rate")?;
+
+        for (i, timestamp) in timestamps.into_iter().enumerate() {
+            let row = candles.len() + 1;
+            let required = |column: &Float64Array, name: &str| {
+                if column.is_null(i) {
+                    Err(CandleIoError::InvalidRow {
+                        row,
+                        reason: format!("missing {}", name),
+                    })
+                } else {
+                    Ok(column.value(i))
+                }
+            };
+            let timestamp = timestamp.ok_or_else(|| CandleIoError::InvalidRow {
+                row,
+                reason: "missing or out-of-range timestamp".to_string(),
+            })?;
+            let nullable = |column: Option<&Float64Array>| {
+                column.filter(|c| !c.is_null(i)).map(|c| c.value(i))
+            };
+
+            candles.push(Candle {
+                open: required(open, "open")?,
+                high: required(high, "high")?,
+                low: required(low, "low")?,
+                close: required(close, "close")?,
+                volume: required(volume, "volume")?,
+                timestamp,
+                open_interest: nullable(open_interest),
+                funding_rate: nullable(funding_rate),
+                mark_price: None,
+                oracle_price: None,
+                premium: None,
+                day_notional_volume: None,
+                is_closed: true,
+            });
+        }
+    }
+    Ok(candles)
+}
+
+/// Timestamps as UTC, accepting millisecond timestamps or epoch-millisecond integers
+fn timestamp_column(batch: &RecordBatch) -> Result<Vec<Option<DateTime<Utc>>>, CandleIoError> {
+    let column = batch
+        .column_by_name("timestamp")
+        .ok_or_else(|| CandleIoError::Format("missing column 'timestamp'".to_string()))?;
+    let millis: Vec<Option<i64>> =
+        if let Some(ts) = column.as_any().downcast_ref::<TimestampMillisecondArray>() {
+            ts.iter().collect()
+        } else if let Some(ints) = column.as_any().downcast_ref::<Int64Array>() {
+            ints.iter().collect()
+        } else {
+            return Err(CandleIoError::Format(format!(
+                "column 'timestamp' must be a millisecond timestamp or Int64, got {}",
+                column.data_type()
+            )));
+        };
+    Ok(millis
+        .into_iter()
+        .map(|ms| ms.and_then(DateTime::from_timestamp_millis))
+        .collect())
+}
+
+fn float_column<'a>(batch: &'a RecordBatch, name: &str) -> Result<&'a Float64Array, CandleIoError> {
+    optional_float_column(batch, name)?
+        .ok_or_else(|| CandleIoError::Format(format!("missing column '{}'", name)))
+}
+
+fn optional_float_column<'a>(
+    batch: &'a RecordBatch,
+    name: &str,
+) -> Result<Option<&'a Float64Array>, CandleIoError> {
+    batch
+        .column_by_name(name)
+        .map(|column| {
+            column
+                .as_any()
+                .downcast_ref::<Float64Array>()
+                .ok_or_else(|| {
+                    CandleIoError::Format(format!(
+                        "column '{}' must be Float64, got {}",
+                        name,
+                        column.data_type()
+                    ))
+                })
+        })
+        .transpose()
+}
@@ -0,0 +1,356 @@
+//! Historical candle backfill from the Hyperliquid REST API
+//!
+//! Pages through `candleSnapshot` over an arbitrary date range, aligns funding
+//! history onto each page and writes it to a `CandleStore` in bulk. Runs resume
+//! after the candles already stored without a hole from the start of the range,
+//! so an interrupted backfill can simply be started again with the same range;
+//! candles stored later in the range (such as live bars) are skipped when
+//! writing, not used to resume.
+
+use super::rest::{
+    align_funding_rates, interval_duration_ms, FundingRatePoint, HyperliquidRestClient,
+    CANDLE_SNAPSHOT_LIMIT, FUNDING_HISTORY_LIMIT,
+};
+use crate::db::CandleStore;
+use crate::models::indicators::Candle;
+use backon::{ExponentialBuilder, Retryable};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+use tracing::{debug, info, warn};
+
+/// Funding is settled hourly; look back one period so the first candles of a page get a rate
+const FUNDING_LOOKBACK_MS: u64 = 60 * 60 * 1000;
+
+#[derive(Debug, Clone)]
+pub struct BackfillConfig {
+    /// Candles requested per `candleSnapshot` call (capped at `CANDLE_SNAPSHOT_LIMIT`)
+    pub page_size: usize,
+    /// Minimum delay between consecutive REST requests
+    pub request_interval: Duration,
+    /// Retries per request before the backfill fails
+    pub max_retries: usize,
+    /// Fetch funding history and align it onto the candles
+    pub include_funding: bool,
+}
+
+impl Default for BackfillConfig {
+    fn default() -> Self {
+        Self {
+            page_size: CANDLE_SNAPSHOT_LIMIT,
+            request_interval: Duration::from_millis(500),
+            max_retries: 5,
+            include_funding: true,
+        }
+    }
+}
+
+/// Outcome of backfilling one symbol and interval
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackfillReport {
+    pub symbol: String,
+    pub interval: String,
+    pub pages: usize,
+    pub candles_fetched: usize,
+    pub candles_stored: usize,
+    pub funding_points: usize,
+    /// Start of the fetched range when the beginning of the range was
+    /// already stored without gaps
+    pub resumed_from: Option<DateTime<Utc>>,
+}
+
+/// Last candle of the gapless run of `stored` that begins with the first bar
+/// at or after `start_ms`, if that bar is stored
+fn stored_prefix_end(stored: &[Candle], start_ms: u64, interval_ms: u64) -> Option<DateTime<Utc>> {
+    let mut timestamps: Vec<u64> = stored
+        .iter()
+        .map(|c| c.timestamp.timestamp_millis().max(0) as u64)
+        .collect();
+    timestamps.sort_unstable();
+    timestamps.dedup();
+    let first = *timestamps.first()?;
+    if first > start_ms + interval_ms {
+        return None;
+    }
+    // Spacing is rounded to whole intervals, as in the gap audit
+    let last = timestamps
+        .windows(2)
+        .take_while(|pair| pair[1] - pair[0] < interval_ms + interval_ms / 2)
+        .last()
+        .map_or(first, |pair| pair[1]);
+    DateTime::from_timestamp_millis(last as i64)
+}
+
+pub struct CandleBackfiller {
+    rest: Arc<HyperliquidRestClient>,
+    store: Arc<dyn CandleStore>,
+    config: BackfillConfig,
+    last_request: Mutex<Option<Instant>>,
+}
+
+impl CandleBackfiller {
+    pub fn new(rest: Arc<HyperliquidRestClient>, store: Arc<dyn CandleStore>) -> Self {
+        Self {
+            rest,
+            store,
+            config: BackfillConfig::default(),
+            last_request: Mutex::new(None),
+        }
+    }
+
+    pub fn with_config(mut self, config: BackfillConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn config(&self) -> &BackfillConfig {
+        &self.config
+    }
+
+    /// Backfill every symbol and interval combination sequentially
+    pub async fn backfill_all(
+        &self,
+        symbols: &[String],
+        intervals: &[String],
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<BackfillReport>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut reports = Vec::with_capacity(symbols.len() * intervals.len());
+        for symbol in symbols {
+            for interval in intervals {
+                reports.push(self.backfill(symbol, interval, start, end).await?);
+            }
+        }
+        Ok(reports)
+    }
+
+    /// Backfill closed candles for `symbol`/`interval` between `start` and `end`.
+    ///
+    /// Resumes after the stored candles that cover the range from `start`
+    /// without a gap; other candles already stored are skipped when writing.
+    pub async fn backfill(
+        &self,
+        symbol: &str,
+        interval: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<BackfillReport, Box<dyn std::error::Error + Send + Sync>> {
+        let interval_ms = interval_duration_ms(interval).ok_or_else(|| {
+            Box::new(std::io::Error::other(format!(
+                "Unsupported candle interval: {}",
+                interval
+            ))) as Box<dyn std::error::Error + Send + Sync>
+        })?;
+        if end <= start {
+            return Err(Box::new(std::io::Error::other(format!(
+                "Backfill range is empty: {} >= {}",
+                start, end
+            ))) as Box<dyn std::error::Error + Send + Sync>);
+        }
+
+        let mut report = BackfillReport {
+            symbol: symbol.to_string(),
+            interval: interval.to_string(),
+            pages: 0,
+            candles_fetched: 0,
+            candles_stored: 0,
+            funding_points: 0,
+            resumed_from: None,
+        };
+
+        let start_ms = start.timestamp_millis().max(0) as u64;
+        let end_ms = end.timestamp_millis().max(0) as u64;
+        let mut cursor = start_ms;
+        let stored = self
+            .store
+            .candles_in_range(symbol, interval, Some(start), Some(end))
+            .await?;
+        if let Some(latest) = stored_prefix_end(&stored, start_ms, interval_ms) {
+            let resume_ms = latest.timestamp_millis().max(0) as u64 + 1;
+            if resume_ms > cursor {
+                cursor = resume_ms;
+                report.resumed_from = DateTime::from_timestamp_millis(resume_ms as i64);
+                info!(symbol = %symbol, interval = %interval, resume_from = %latest, "Resuming backfill for {}/{} after {}", symbol, interval, latest);
+            }
+        }
+
+        self.backfill_pages(&mut report, interval_ms, cursor, end_ms)
+            .await?;
+
+        info!(
+            symbol = %symbol,
+            interval = %interval,
+            pages = report.pages,
+            stored = report.candles_stored,
+            "Backfill complete for {}/{}: stored {} candles over {} pages",
+            symbol,
+            interval,
+            report.candles_stored,
+            report.pages
+        );
+        Ok(report)
+    }
+
+    /// Backfill the closed candles between `from` and `to` (inclusive), even
+    /// if later candles are already stored
+    pub async fn backfill_gap(
+        &self,
+        symbol: &str,
+        interval: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<BackfillReport, Box<dyn std::error::Error + Send + Sync>> {
+        let interval_ms = interval_duration_ms(interval).ok_or_else(|| {
+            Box::new(std::io::Error::other(format!(
+                "Unsupported candle interval: {}",
+                interval
+            ))) as Box<dyn std::error::Error + Send + Sync>
+        })?;
+        let mut report = BackfillReport {
+            symbol: symbol.to_string(),
+            interval: interval.to_string(),
+            pages: 0,
+            candles_fetched: 0,
+            candles_stored: 0,
+            funding_points: 0,
+            resumed_from: None,
+        };
+        // Candles are stamped with their close time; fetch from the open of the first bar
+        let start_ms = (from.timestamp_millis().max(0) as u64).saturating_sub(interval_ms - 1);
+        let end_ms = to.timestamp_millis().max(0) as u64;
+        self.backfill_pages(&mut report, interval_ms, start_ms, end_ms)
+            .await?;
+        Ok(report)
+    }
+
+    /// Fetch and store pages of closed candles stamped within `[cursor, end_ms]`
+    async fn backfill_pages(
+        &self,
+        report: &mut BackfillReport,
+        interval_ms: u64,
+        mut cursor: u64,
+        end_ms: u64,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let symbol = report.symbol.clone();
+        let interval = report.interval.clone();
+        let (symbol, interval) = (symbol.as_str(), interval.as_str());
+        let page_span = interval_ms * self.config.page_size.clamp(1, CANDLE_SNAPSHOT_LIMIT) as u64;
+
+        while cursor < end_ms {
+            let page_end = (cursor + page_span - 1).min(end_ms);
+            let mut candles = self.fetch_candle_page(symbol, interval, cursor, page_end).await?;
+            report.pages += 1;
+            report.candles_fetched += candles.len();
+
+            // Skip the still-forming bar and anything the API returns outside the page
+            candles.retain(|c| {
+                let ts = c.timestamp.timestamp_millis().max(0) as u64;
+                ts >= cursor && ts <= end_ms && c.is_closed
+            });
+
+            if !candles.is_empty() {
+                if self.config.include_funding {
+                    let funding = self.fetch_funding_page(symbol, cursor, page_end).await?;
+                    report.funding_points += funding.len();
+                    align_funding_rates(&mut candles, &funding);
+                }
+
+                report.candles_stored += self
+                    .store
+                    .store_missing_candles(symbol, interval, &candles)
+                    .await?;
+            }
+
+            debug!(
+                symbol = %symbol,
+                interval = %interval,
+                page = report.pages,
+                count = candles.len(),
+                "Backfilled page {} for {}/{} ({} candles)",
+                report.pages,
+                symbol,
+                interval,
+                candles.len()
+            );
+            cursor = page_end + 1;
+        }
+        Ok(())
+    }
+
+    async fn fetch_candle_page(
+        &self,
+        symbol: &str,
+        interval: &str,
+        start_ms: u64,
+        end_ms: u64,
+    ) -> Result<Vec<Candle>, Box<dyn std::error::Error + Send + Sync>> {
+        self.with_retry(|| self.rest.fetch_candles_range(symbol, interval, start_ms, end_ms))
+            .await
+    }
+
+    /// Fetch all funding entries covering a candle page, paging past `FUNDING_HISTORY_LIMIT`
+    async fn fetch_funding_page(
+        &self,
+        symbol: &str,
+        start_ms: u64,
+        end_ms: u64,
+    ) -> Result<Vec<FundingRatePoint>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut points: Vec<FundingRatePoint> = Vec::new();
+        let mut cursor = start_ms.saturating_sub(FUNDING_LOOKBACK_MS);
+
+        while cursor <= end_ms {
+            let mut page = self
+                .with_retry(|| self.rest.fetch_funding_history(symbol, Some(cursor), Some(end_ms)))
+                .await?;
+            page.sort_by_key(|p| p.timestamp);
+            let full_page = page.len() >= FUNDING_HISTORY_LIMIT;
+            let Some(last) = page.last() else {
+                break;
+            };
+            let next = last.timestamp.timestamp_millis().max(0) as u64 + 1;
+            points.extend(page);
+            if !full_page || next <= cursor {
+                break;
+            }
+            cursor = next;
+        }
+
+        Ok(points)
+    }
+
+    /// Run a REST request with pacing and exponential backoff
+    async fn with_retry<T, F, Fut>(
+        &self,
+        request: F,
+    ) -> Result<T, Box<dyn std::error::Error + Send + Sync>>
+    where
+        F: Fn() -> Fut,
+        Fut: std::future::Future<Output = Result<T, Box<dyn std::error::Error + Send + Sync>>>,
+    {
+        let backoff = ExponentialBuilder::default()
+            .with_min_delay(self.config.request_interval.max(Duration::from_millis(1)))
+            .with_max_times(self.config.max_retries);
+
+        (|| async {
+            self.throttle().await;
+            request().await
+        })
+        .retry(backoff)
+        .notify(|e, delay| {
+            warn!(error = %e, delay_ms = delay.as_millis() as u64, "Backfill request failed, retrying in {:?}", delay);
+        })
+        .await
+    }
+
+    /// Wait until `request_interval` has elapsed since the previous request
+    async fn throttle(&self) {
+        let mut last_request = self.last_request.lock().await;
+        if let Some(last) = *last_request {
+            tokio::time::sleep_until(last + self.config.request_interval).await;
+        }
+        *last_request = Some(Instant::now());
+    }
+}
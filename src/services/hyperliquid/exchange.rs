@@ -0,0 +1,914 @@
+//! Hyperliquid order execution
+//!
+//! Signs order, cancel, modify and updateLeverage actions with an agent wallet
+//! and submits them to the `/exchange` endpoint. Asset ids and size precision
+//! come from the `meta` info request; open orders and positions are read from
+//! the info endpoint for the trading account.
+
+use crate::config;
+use crate::execution::{
+    ExchangeExecutor, ExchangeFill, ExchangeOrderReport, ExchangeOrderState, ExchangePosition,
+    ExecutionError, OpenOrder, OrderAck, OrderGrouping, OrderRequest, OrderSide, OrderStatus,
+    OrderType, TimeInForce, TopOfBook,
+};
+use crate::services::hyperliquid::messages::WsClearinghouseState;
+use crate::services::hyperliquid::signing::{ActionSignature, HyperliquidSigner};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::RwLock;
+use tracing::{debug, info};
+
+/// Significant figures allowed in a Hyperliquid price
+const PRICE_SIG_FIGS: i32 = 5;
+
+/// Maximum decimals of a perp price before subtracting the asset's size decimals
+const MAX_PRICE_DECIMALS: u32 = 6;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LimitWire {
+    pub tif: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TriggerWire {
+    pub is_market: bool,
+    pub trigger_px: String,
+    pub tpsl: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OrderTypeWire {
+    Limit(LimitWire),
+    Trigger(TriggerWire),
+}
+
+/// Order in the exchange's compact wire format
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderWire {
+    /// Asset index
+    pub a: u32,
+    /// Is buy
+    pub b: bool,
+    /// Limit price
+    pub p: String,
+    /// Size
+    pub s: String,
+    /// Reduce only
+    pub r: bool,
+    pub t: OrderTypeWire,
+    /// Client order id
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub c: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CancelWire {
+    pub a: u32,
+    pub o: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModifyWire {
+    pub oid: u64,
+    pub order: OrderWire,
+}
+
+/// Signed exchange action.
+///
+/// Field order matters: the msgpack encoding of this value is what gets signed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum HyperliquidAction {
+    Order {
+        orders: Vec<OrderWire>,
+        grouping: String,
+    },
+    Cancel {
+        cancels: Vec<CancelWire>,
+    },
+    BatchModify {
+        modifies: Vec<ModifyWire>,
+    },
+    #[serde(rename_all = "camelCase")]
+    UpdateLeverage {
+        asset: u32,
+        is_cross: bool,
+        leverage: u32,
+    },
+}
+
+/// Body POSTed to `/exchange`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExchangeRequest {
+    pub action: HyperliquidAction,
+    pub nonce: u64,
+    pub signature: ActionSignature,
+    pub vault_address: Option<String>,
+}
+
+/// Format a number the way the exchange hashes it: at most 8 decimals, no
+/// trailing zeros
+pub fn float_to_wire(value: f64) -> Result<String, ExecutionError> {
+    let rounded = format!("{:.8}", value);
+    let parsed: f64 = rounded
+        .parse()
+        .map_err(|_| ExecutionError::InvalidOrder(format!("Invalid number {}", value)))?;
+    if (parsed - value).abs() >= 1e-12 {
+        return Err(ExecutionError::InvalidOrder(format!(
+            "{} has more than 8 decimals",
+            value
+        )));
+    }
+    let trimmed = rounded.trim_end_matches('0').trim_end_matches('.');
+    Ok(match trimmed {
+        "-0" | "" => "0".to_string(),
+        other => other.to_string(),
+    })
+}
+
+/// Round a perp price to 5 significant figures and at most `6 - sz_decimals` decimals
+pub fn round_price(price: f64, sz_decimals: u32) -> f64 {
+    if price == 0.0 || !price.is_finite() {
+        return price;
+    }
+    let magnitude = price.abs().log10().floor() as i32;
+    let sig_factor = 10f64.powi(PRICE_SIG_FIGS - 1 - magnitude);
+    let significant = (price * sig_factor).round() / sig_factor;
+    let decimals = MAX_PRICE_DECIMALS.saturating_sub(sz_decimals) as i32;
+    let factor = 10f64.powi(decimals);
+    (significant * factor).round() / factor
+}
+
+/// Smallest price increment at `price` under the rules applied by [`round_price`]
+pub fn tick_size(price: f64, sz_decimals: u32) -> f64 {
+    let decimal_tick = 10f64.powi(-(MAX_PRICE_DECIMALS.saturating_sub(sz_decimals) as i32));
+    if price <= 0.0 || !price.is_finite() {
+        return decimal_tick;
+    }
+    let magnitude = price.log10().floor() as i32;
+    decimal_tick.max(10f64.powi(magnitude + 1 - PRICE_SIG_FIGS))
+}
+
+/// Round a size to the asset's size decimals
+pub fn round_size(size: f64, sz_decimals: u32) -> f64 {
+    let factor = 10f64.powi(sz_decimals as i32);
+    (size * factor).round() / factor
+}
+
+#[derive(Debug, Clone, Copy)]
+struct AssetInfo {
+    index: u32,
+    sz_decimals: u32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MetaAsset {
+    name: String,
+    sz_decimals: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct MetaResponse {
+    universe: Vec<MetaAsset>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OpenOrderResponse {
+    coin: String,
+    side: String,
+    limit_px: String,
+    sz: String,
+    oid: u64,
+    timestamp: i64,
+    #[serde(default)]
+    cloid: Option<String>,
+    #[serde(default)]
+    reduce_only: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StatusOrderResponse {
+    coin: String,
+    sz: String,
+    oid: u64,
+    orig_sz: String,
+    #[serde(default)]
+    cloid: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OrderStatusEntry {
+    order: StatusOrderResponse,
+    status: String,
+    status_timestamp: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct OrderStatusResponse {
+    status: String,
+    #[serde(default)]
+    order: Option<OrderStatusEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BookLevelResponse {
+    px: String,
+}
+
+/// `l2Book` snapshot: bid levels then ask levels, best first
+#[derive(Debug, Deserialize)]
+struct L2BookResponse {
+    levels: Vec<Vec<BookLevelResponse>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FillResponse {
+    coin: String,
+    px: String,
+    sz: String,
+    side: String,
+    time: i64,
+    oid: u64,
+    tid: u64,
+    #[serde(default)]
+    fee: Option<String>,
+    #[serde(default)]
+    cloid: Option<String>,
+}
+
+/// Map an order status name from the info endpoint to its lifecycle state.
+///
+/// Besides "canceled", the exchange reports reasons such as "marginCanceled" or
+/// "reduceOnlyCanceled", and rejections such as "perpMarginRejected".
+pub(crate) fn exchange_order_state(status: &str) -> Option<ExchangeOrderState> {
+    let lower = status.to_ascii_lowercase();
+    match lower.as_str() {
+        "open" | "triggered" => Some(ExchangeOrderState::Open),
+        "filled" => Some(ExchangeOrderState::Filled),
+        _ if lower.ends_with("rejected") => Some(ExchangeOrderState::Rejected),
+        _ if lower.ends_with("canceled") || lower.ends_with("cancelled") => {
+            Some(ExchangeOrderState::Cancelled)
+        }
+        _ => None,
+    }
+}
+
+pub(crate) fn parse_side(side: &str) -> OrderSide {
+    if side == "B" {
+        OrderSide::Buy
+    } else {
+        OrderSide::Sell
+    }
+}
+
+pub(crate) fn parse_number(field: &str, raw: &str) -> Result<f64, ExecutionError> {
+    raw.parse()
+        .map_err(|_| ExecutionError::InvalidResponse(format!("Invalid {} '{}'", field, raw)))
+}
+
+/// Open positions in a clearinghouse state, from the info endpoint or `webData2`
+pub(crate) fn account_positions(
+    state: &WsClearinghouseState,
+) -> Result<Vec<ExchangePosition>, ExecutionError> {
+    state
+        .asset_positions
+        .iter()
+        .map(|entry| {
+            let position = &entry.position;
+            Ok(ExchangePosition {
+                symbol: position.coin.clone(),
+                size: parse_number("szi", &position.szi)?,
+                entry_price: position
+                    .entry_px
+                    .as_deref()
+                    .map(|px| parse_number("entryPx", px))
+                    .transpose()?
+                    .unwrap_or(0.0),
+                unrealized_pnl: parse_number("unrealizedPnl", &position.unrealized_pnl)?,
+                leverage: position.leverage.value,
+                liquidation_price: position
+                    .liquidation_px
+                    .as_deref()
+                    .map(|px| parse_number("liquidationPx", px))
+                    .transpose()?,
+            })
+        })
+        .filter(|position: &Result<ExchangePosition, ExecutionError>| {
+            position.as_ref().map_or(true, |p| p.size != 0.0)
+        })
+        .collect()
+}
+
+/// Account value in a clearinghouse state
+pub(crate) fn account_equity(state: &WsClearinghouseState) -> Result<f64, ExecutionError> {
+    let summary = state.margin_summary.as_ref().ok_or_else(|| {
+        ExecutionError::InvalidResponse("clearinghouseState has no marginSummary".to_string())
+    })?;
+    parse_number("accountValue", &summary.account_value)
+}
+
+pub struct HyperliquidExecutor {
+    base_url: String,
+    client: reqwest::Client,
+    signer: HyperliquidSigner,
+    /// Master account (or vault) whose orders and positions are queried
+    account_address: String,
+    vault_address: Option<String>,
+    is_mainnet: bool,
+    last_nonce: AtomicU64,
+    assets: RwLock<Option<HashMap<String, AssetInfo>>>,
+}
+
+impl HyperliquidExecutor {
+    /// Executor for the configured environment using `HYPERLIQUID_AGENT_KEY`,
+    /// `HYPERLIQUID_ACCOUNT_ADDRESS` and optional `HYPERLIQUID_VAULT_ADDRESS`
+    pub fn from_env() -> Result<Self, ExecutionError> {
+        let key = std::env::var("HYPERLIQUID_AGENT_KEY")
+            .map_err(|_| ExecutionError::Signing("HYPERLIQUID_AGENT_KEY is not set".to_string()))?;
+        let account = std::env::var("HYPERLIQUID_ACCOUNT_ADDRESS").map_err(|_| {
+            ExecutionError::Signing("HYPERLIQUID_ACCOUNT_ADDRESS is not set".to_string())
+        })?;
+        let signer = HyperliquidSigner::from_hex(&key)?;
+
+        let mut executor = Self::with_client(
+            config::get_hyperliquid_rest_url(),
+            reqwest::Client::new(),
+            signer,
+            account,
+        );
+        if let Ok(vault) = std::env::var("HYPERLIQUID_VAULT_ADDRESS") {
+            if !vault.trim().is_empty() {
+                executor = executor.with_vault_address(vault.trim());
+            }
+        }
+        Ok(executor)
+    }
+
+    pub fn with_client(
+        base_url: impl Into<String>,
+        client: reqwest::Client,
+        signer: HyperliquidSigner,
+        account_address: impl Into<String>,
+    ) -> Self {
+        let is_mainnet = !matches!(config::get_environment().as_str(), "sandbox" | "testnet");
+        Self {
+            base_url: base_url.into(),
+            client,
+            signer,
+            account_address: account_address.into().to_lowercase(),
+            vault_address: None,
+            is_mainnet,
+            last_nonce: AtomicU64::new(0),
+            assets: RwLock::new(None),
+        }
+    }
+
+    /// Trade on behalf of a vault or subaccount; its orders and positions are queried instead
+    pub fn with_vault_address(mut self, vault_address: impl Into<String>) -> Self {
+        let vault = vault_address.into().to_lowercase();
+        self.account_address = vault.clone();
+        self.vault_address = Some(vault);
+        self
+    }
+
+    /// Sign for mainnet (`true`) or testnet; defaults to the configured environment
+    pub fn with_mainnet(mut self, is_mainnet: bool) -> Self {
+        self.is_mainnet = is_mainnet;
+        self
+    }
+
+    pub fn signer(&self) -> &HyperliquidSigner {
+        &self.signer
+    }
+
+    pub fn is_mainnet(&self) -> bool {
+        self.is_mainnet
+    }
+
+    /// Millisecond timestamp, strictly increasing across calls
+    fn next_nonce(&self) -> u64 {
+        let now = Utc::now().timestamp_millis() as u64;
+        let mut last = self.last_nonce.load(Ordering::SeqCst);
+        loop {
+            let next = now.max(last + 1);
+            match self
+                .last_nonce
+                .compare_exchange(last, next, Ordering::SeqCst, Ordering::SeqCst)
+            {
+                Ok(_) => return next,
+                Err(current) => last = current,
+            }
+        }
+    }
+
+    async fn post<T: Serialize>(&self, path: &str, body: &T) -> Result<String, ExecutionError> {
+        let url = format!("{}/{}", self.base_url, path);
+        let response = self
+            .client
+            .post(&url)
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| ExecutionError::Transport(format!("HTTP request failed: {}", e)))?;
+
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .map_err(|e| ExecutionError::Transport(format!("Failed to read response: {}", e)))?;
+
+        if !status.is_success() {
+            debug!(status = %status, response = %text, "Hyperliquid {} error response", path);
+            return Err(ExecutionError::Transport(format!(
+                "HTTP error: {} - Response: {}",
+                status, text
+            )));
+        }
+        Ok(text)
+    }
+
+    async fn info<R: for<'de> Deserialize<'de>>(
+        &self,
+        request: serde_json::Value,
+    ) -> Result<R, ExecutionError> {
+        let text = self.post("info", &request).await?;
+        serde_json::from_str(&text).map_err(|e| {
+            ExecutionError::InvalidResponse(format!(
+                "Failed to parse info response: {} - Response: {}",
+                e, text
+            ))
+        })
+    }
+
+    async fn clearinghouse_state(&self) -> Result<WsClearinghouseState, ExecutionError> {
+        self.info(serde_json::json!({ "type": "clearinghouseState", "user": self.account_address }))
+            .await
+    }
+
+    /// Sign and submit an action, returning the `response` payload of an "ok" reply
+    async fn submit(&self, action: HyperliquidAction) -> Result<serde_json::Value, ExecutionError> {
+        let nonce = self.next_nonce();
+        let signature = self.signer.sign_l1_action(
+            &action,
+            nonce,
+            self.vault_address.as_deref(),
+            self.is_mainnet,
+        )?;
+        let request = ExchangeRequest {
+            action,
+            nonce,
+            signature,
+            vault_address: self.vault_address.clone(),
+        };
+
+        let text = self.post("exchange", &request).await?;
+        let body: serde_json::Value = serde_json::from_str(&text).map_err(|e| {
+            ExecutionError::InvalidResponse(format!(
+                "Failed to parse exchange response: {} - Response: {}",
+                e, text
+            ))
+        })?;
+
+        match body.get("status").and_then(|s| s.as_str()) {
+            Some("ok") => Ok(body.get("response").cloned().unwrap_or_default()),
+            Some("err") => Err(ExecutionError::Rejected(
+                body.get("response")
+                    .and_then(|r| r.as_str())
+                    .map(str::to_string)
+                    .unwrap_or_else(|| text.clone()),
+            )),
+            _ => Err(ExecutionError::InvalidResponse(text)),
+        }
+    }
+
+    /// Per-order statuses of an order, cancel or modify response
+    fn statuses(response: &serde_json::Value) -> Result<Vec<serde_json::Value>, ExecutionError> {
+        response
+            .pointer("/data/statuses")
+            .and_then(|s| s.as_array())
+            .cloned()
+            .ok_or_else(|| {
+                ExecutionError::InvalidResponse(format!("Missing statuses: {}", response))
+            })
+    }
+
+    fn order_ack(status: &serde_json::Value) -> Result<OrderAck, ExecutionError> {
+        if let Some(error) = status.get("error").and_then(|e| e.as_str()) {
+            return Err(ExecutionError::Rejected(error.to_string()));
+        }
+        // Grouped TP/SL legs waiting for their entry are accepted without an
+        // order id; reconciliation picks them up once they are placed
+        if status.as_str().is_some() {
+            return Ok(OrderAck {
+                order_id: None,
+                status: OrderStatus::Waiting,
+                filled_size: 0.0,
+                avg_fill_price: None,
+            });
+        }
+        if let Some(resting) = status.get("resting") {
+            let order_id = resting.get("oid").and_then(|o| o.as_u64()).ok_or_else(|| {
+                ExecutionError::InvalidResponse(format!("Missing oid: {}", status))
+            })?;
+            return Ok(OrderAck {
+                order_id: Some(order_id),
+                status: OrderStatus::Resting,
+                filled_size: 0.0,
+                avg_fill_price: None,
+            });
+        }
+        if let Some(filled) = status.get("filled") {
+            let order_id = filled.get("oid").and_then(|o| o.as_u64()).ok_or_else(|| {
+                ExecutionError::InvalidResponse(format!("Missing oid: {}", status))
+            })?;
+            let total = filled
+                .get("totalSz")
+                .and_then(|v| v.as_str())
+                .unwrap_or("0");
+            let avg_px = filled.get("avgPx").and_then(|v| v.as_str());
+            return Ok(OrderAck {
+                order_id: Some(order_id),
+                status: OrderStatus::Filled,
+                filled_size: parse_number("totalSz", total)?,
+                avg_fill_price: avg_px.map(|px| parse_number("avgPx", px)).transpose()?,
+            });
+        }
+        Err(ExecutionError::InvalidResponse(format!(
+            "Unknown order status: {}",
+            status
+        )))
+    }
+
+    async fn asset(&self, symbol: &str) -> Result<AssetInfo, ExecutionError> {
+        if let Some(assets) = self.assets.read().await.as_ref() {
+            if let Some(info) = assets.get(symbol) {
+                return Ok(*info);
+            }
+        }
+
+        // Refresh once in case the symbol was listed since the last fetch
+        let meta: MetaResponse = self.info(serde_json::json!({ "type": "meta" })).await?;
+        let assets: HashMap<String, AssetInfo> = meta
+            .universe
+            .into_iter()
+            .enumerate()
+            .map(|(index, asset)| {
+                (
+                    asset.name,
+                    AssetInfo {
+                        index: index as u32,
+                        sz_decimals: asset.sz_decimals,
+                    },
+                )
+            })
+            .collect();
+        let info = assets.get(symbol).copied();
+        *self.assets.write().await = Some(assets);
+        info.ok_or_else(|| ExecutionError::UnknownSymbol(symbol.to_string()))
+    }
+
+    async fn order_wire(&self, order: &OrderRequest) -> Result<OrderWire, ExecutionError> {
+        if !order.size.is_finite() || order.size <= 0.0 {
+            return Err(ExecutionError::InvalidOrder(format!(
+                "Size must be positive, got {}",
+                order.size
+            )));
+        }
+        if !order.price.is_finite() || order.price <= 0.0 {
+            return Err(ExecutionError::InvalidOrder(format!(
+                "Price must be positive, got {}",
+                order.price
+            )));
+        }
+        if let Some(cloid) = &order.client_order_id {
+            let hex = cloid.strip_prefix("0x").unwrap_or("");
+            if hex.len() != 32 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(ExecutionError::InvalidOrder(format!(
+                    "Client order id must be 0x followed by 32 hex characters, got '{}'",
+                    cloid
+                )));
+            }
+        }
+
+        let asset = self.asset(&order.symbol).await?;
+        let size = round_size(order.size, asset.sz_decimals);
+        if size <= 0.0 {
+            return Err(ExecutionError::InvalidOrder(format!(
+                "Size {} rounds to zero at {} decimals",
+                order.size, asset.sz_decimals
+            )));
+        }
+
+        let trigger = |trigger_price: f64,
+                       tpsl: &str,
+                       is_market: bool|
+         -> Result<OrderTypeWire, ExecutionError> {
+            Ok(OrderTypeWire::Trigger(TriggerWire {
+                is_market,
+                trigger_px: float_to_wire(round_price(trigger_price, asset.sz_decimals))?,
+                tpsl: tpsl.to_string(),
+            }))
+        };
+        let t = match order.order_type {
+            OrderType::Limit(tif) => OrderTypeWire::Limit(LimitWire {
+                tif: match tif {
+                    TimeInForce::Gtc => "Gtc",
+                    TimeInForce::Ioc => "Ioc",
+                    TimeInForce::Alo => "Alo",
+                }
+                .to_string(),
+            }),
+            // Hyperliquid has no native market order: an aggressive IOC limit
+            OrderType::Market => OrderTypeWire::Limit(LimitWire {
+                tif: "Ioc".to_string(),
+            }),
+            OrderType::StopMarket { trigger_price } => trigger(trigger_price, "sl", true)?,
+            OrderType::TakeProfitMarket { trigger_price } => trigger(trigger_price, "tp", true)?,
+            OrderType::TakeProfitLimit { trigger_price } => trigger(trigger_price, "tp", false)?,
+        };
+
+        Ok(OrderWire {
+            a: asset.index,
+            b: order.side.is_buy(),
+            p: float_to_wire(round_price(order.price, asset.sz_decimals))?,
+            s: float_to_wire(size)?,
+            r: order.reduce_only,
+            t,
+            c: order.client_order_id.as_ref().map(|c| c.to_lowercase()),
+        })
+    }
+}
+
+#[async_trait]
+impl ExchangeExecutor for HyperliquidExecutor {
+    fn name(&self) -> &str {
+        "hyperliquid"
+    }
+
+    async fn place_order(&self, order: &OrderRequest) -> Result<OrderAck, ExecutionError> {
+        let wire = self.order_wire(order).await?;
+        let response = self
+            .submit(HyperliquidAction::Order {
+                orders: vec![wire],
+                grouping: "na".to_string(),
+            })
+            .await?;
+        let statuses = Self::statuses(&response)?;
+        let status = statuses
+            .first()
+            .ok_or_else(|| ExecutionError::InvalidResponse("Empty order statuses".to_string()))?;
+        let ack = Self::order_ack(status)?;
+        info!(
+            symbol = %order.symbol,
+            side = ?order.side,
+            size = order.size,
+            price = order.price,
+            order_id = ack.order_id,
+            status = ?ack.status,
+            "Placed {:?} order on {}",
+            order.side,
+            order.symbol
+        );
+        Ok(ack)
+    }
+
+    async fn place_orders(
+        &self,
+        orders: &[OrderRequest],
+        grouping: OrderGrouping,
+    ) -> Result<Vec<Result<OrderAck, ExecutionError>>, ExecutionError> {
+        let mut wires = Vec::with_capacity(orders.len());
+        for order in orders {
+            wires.push(self.order_wire(order).await?);
+        }
+        let response = self
+            .submit(HyperliquidAction::Order {
+                orders: wires,
+                grouping: match grouping {
+                    OrderGrouping::None => "na",
+                    OrderGrouping::NormalTpsl => "normalTpsl",
+                    OrderGrouping::PositionTpsl => "positionTpsl",
+                }
+                .to_string(),
+            })
+            .await?;
+        let statuses = Self::statuses(&response)?;
+        if statuses.len() != orders.len() {
+            return Err(ExecutionError::InvalidResponse(format!(
+                "Expected {} order statuses, got {}",
+                orders.len(),
+                statuses.len()
+            )));
+        }
+        info!(
+            orders = orders.len(),
+            grouping = ?grouping,
+            "Placed {} grouped orders on {}",
+            orders.len(),
+            orders.first().map_or("", |o| o.symbol.as_str())
+        );
+        Ok(statuses.iter().map(Self::order_ack).collect())
+    }
+
+    async fn cancel_order(&self, symbol: &str, order_id: u64) -> Result<(), ExecutionError> {
+        let asset = self.asset(symbol).await?;
+        let response = self
+            .submit(HyperliquidAction::Cancel {
+                cancels: vec![CancelWire {
+                    a: asset.index,
+                    o: order_id,
+                }],
+            })
+            .await?;
+        match Self::statuses(&response)?.first() {
+            Some(serde_json::Value::String(s)) if s == "success" => {
+                info!(symbol = %symbol, order_id, "Cancelled order {}", order_id);
+                Ok(())
+            }
+            Some(status) => match status.get("error").and_then(|e| e.as_str()) {
+                Some(error) => Err(ExecutionError::Rejected(error.to_string())),
+                None => Err(ExecutionError::InvalidResponse(format!(
+                    "Unknown cancel status: {}",
+                    status
+                ))),
+            },
+            None => Err(ExecutionError::InvalidResponse(
+                "Empty cancel statuses".to_string(),
+            )),
+        }
+    }
+
+    async fn modify_order(
+        &self,
+        order_id: u64,
+        order: &OrderRequest,
+    ) -> Result<OrderAck, ExecutionError> {
+        let wire = self.order_wire(order).await?;
+        let response = self
+            .submit(HyperliquidAction::BatchModify {
+                modifies: vec![ModifyWire {
+                    oid: order_id,
+                    order: wire,
+                }],
+            })
+            .await?;
+        let statuses = Self::statuses(&response)?;
+        let status = statuses
+            .first()
+            .ok_or_else(|| ExecutionError::InvalidResponse("Empty modify statuses".to_string()))?;
+        Self::order_ack(status)
+    }
+
+    async fn open_orders(&self) -> Result<Vec<OpenOrder>, ExecutionError> {
+        let orders: Vec<OpenOrderResponse> = self
+            // Unlike `openOrders`, this reports which orders are reduce-only
+            .info(serde_json::json!({ "type": "frontendOpenOrders", "user": self.account_address }))
+            .await?;
+        orders
+            .into_iter()
+            .map(|order| {
+                Ok(OpenOrder {
+                    order_id: order.oid,
+                    side: parse_side(&order.side),
+                    size: parse_number("sz", &order.sz)?,
+                    price: parse_number("limitPx", &order.limit_px)?,
+                    client_order_id: order.cloid,
+                    reduce_only: order.reduce_only,
+                    created_at: DateTime::from_timestamp_millis(order.timestamp)
+                        .unwrap_or_else(Utc::now),
+                    symbol: order.coin,
+                })
+            })
+            .collect()
+    }
+
+    async fn order_status(
+        &self,
+        client_order_id: &str,
+    ) -> Result<Option<ExchangeOrderReport>, ExecutionError> {
+        let response: OrderStatusResponse = self
+            .info(serde_json::json!({
+                "type": "orderStatus",
+                "user": self.account_address,
+                "oid": client_order_id.to_lowercase(),
+            }))
+            .await?;
+        let Some(entry) = response.order.filter(|_| response.status == "order") else {
+            return Ok(None);
+        };
+        let state = exchange_order_state(&entry.status).ok_or_else(|| {
+            ExecutionError::InvalidResponse(format!("Unknown order status '{}'", entry.status))
+        })?;
+        Ok(Some(ExchangeOrderReport {
+            order_id: entry.order.oid,
+            client_order_id: entry.order.cloid,
+            symbol: entry.order.coin,
+            state,
+            original_size: parse_number("origSz", &entry.order.orig_sz)?,
+            remaining_size: parse_number("sz", &entry.order.sz)?,
+            updated_at: DateTime::from_timestamp_millis(entry.status_timestamp)
+                .unwrap_or_else(Utc::now),
+        }))
+    }
+
+    async fn fills_since(&self, since: DateTime<Utc>) -> Result<Vec<ExchangeFill>, ExecutionError> {
+        let fills: Vec<FillResponse> = self
+            .info(serde_json::json!({
+                "type": "userFillsByTime",
+                "user": self.account_address,
+                "startTime": since.timestamp_millis(),
+            }))
+            .await?;
+        let mut fills = fills
+            .into_iter()
+            .map(|fill| {
+                Ok(ExchangeFill {
+                    trade_id: fill.tid,
+                    order_id: fill.oid,
+                    client_order_id: fill.cloid,
+                    side: parse_side(&fill.side),
+                    size: parse_number("sz", &fill.sz)?,
+                    price: parse_number("px", &fill.px)?,
+                    fee: fill
+                        .fee
+                        .as_deref()
+                        .map(|fee| parse_number("fee", fee))
+                        .transpose()?
+                        .unwrap_or(0.0),
+                    timestamp: DateTime::from_timestamp_millis(fill.time).unwrap_or_else(Utc::now),
+                    symbol: fill.coin,
+                })
+            })
+            .collect::<Result<Vec<_>, ExecutionError>>()?;
+        fills.sort_by_key(|fill| (fill.timestamp, fill.trade_id));
+        Ok(fills)
+    }
+
+    async fn positions(&self) -> Result<Vec<ExchangePosition>, ExecutionError> {
+        account_positions(&self.clearinghouse_state().await?)
+    }
+
+    async fn account_equity(&self) -> Result<f64, ExecutionError> {
+        account_equity(&self.clearinghouse_state().await?)
+    }
+
+    async fn mid_price(&self, symbol: &str) -> Result<f64, ExecutionError> {
+        let mids: HashMap<String, String> =
+            self.info(serde_json::json!({ "type": "allMids" })).await?;
+        let mid = mids
+            .get(symbol)
+            .ok_or_else(|| ExecutionError::UnknownSymbol(symbol.to_string()))?;
+        parse_number("mid", mid)
+    }
+
+    async fn top_of_book(&self, symbol: &str) -> Result<TopOfBook, ExecutionError> {
+        let book: L2BookResponse = self
+            .info(serde_json::json!({ "type": "l2Book", "coin": symbol }))
+            .await?;
+        let best = |side: usize| {
+            book.levels
+                .get(side)
+                .and_then(|levels| levels.first())
+                .ok_or_else(|| {
+                    ExecutionError::InvalidResponse(format!("Empty order book for {}", symbol))
+                })
+                .and_then(|level| parse_number("px", &level.px))
+        };
+        Ok(TopOfBook {
+            bid: best(0)?,
+            ask: best(1)?,
+        })
+    }
+
+    async fn set_leverage(
+        &self,
+        symbol: &str,
+        leverage: u32,
+        cross_margin: bool,
+    ) -> Result<(), ExecutionError> {
+        if leverage == 0 {
+            return Err(ExecutionError::InvalidOrder(
+                "Leverage must be at least 1".to_string(),
+            ));
+        }
+        let asset = self.asset(symbol).await?;
+        self.submit(HyperliquidAction::UpdateLeverage {
+            asset: asset.index,
+            is_cross: cross_margin,
+            leverage,
+        })
+        .await?;
+        info!(symbol = %symbol, leverage, cross_margin, "Set {} leverage to {}x", symbol, leverage);
+        Ok(())
+    }
+}
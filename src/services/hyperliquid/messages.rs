@@ -1,5 +1,6 @@
 //! Hyperliquid WebSocket message types
 
+use crate::models::indicators::AssetContext;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,13 +21,21 @@ pub enum Subscription {
         coin: String,
         interval: String,
     },
+    /// Per-coin channels: activeAssetCtx. Listed before `AllMids`, which would
+    /// otherwise match any subscription with only a type.
+    Coin {
+        #[serde(rename = "type")]
+        sub_type: String,
+        coin: String,
+    },
     AllMids {
         #[serde(rename = "type")]
         sub_type: String,
         #[serde(skip_serializing_if = "Option::is_none")]
         dex: Option<String>,
     },
-    Notification {
+    /// Per-user channels: notification, orderUpdates, userFills, userFundings, webData2
+    User {
         #[serde(rename = "type")]
         sub_type: String,
         user: String,
@@ -42,12 +51,46 @@ impl Subscription {
         }
     }
 
+    pub fn active_asset_ctx(coin: &str) -> Self {
+        Subscription::Coin {
+            sub_type: "activeAssetCtx".to_string(),
+            coin: coin.to_string(),
+        }
+    }
+
     pub fn all_mids(dex: Option<String>) -> Self {
         Subscription::AllMids {
             sub_type: "allMids".to_string(),
             dex,
         }
     }
+
+    fn user(sub_type: &str, user: &str) -> Self {
+        Subscription::User {
+            sub_type: sub_type.to_string(),
+            user: user.to_string(),
+        }
+    }
+
+    pub fn notification(user: &str) -> Self {
+        Self::user("notification", user)
+    }
+
+    pub fn order_updates(user: &str) -> Self {
+        Self::user("orderUpdates", user)
+    }
+
+    pub fn user_fills(user: &str) -> Self {
+        Self::user("userFills", user)
+    }
+
+    pub fn user_fundings(user: &str) -> Self {
+        Self::user("userFundings", user)
+    }
+
+    pub fn web_data2(user: &str) -> Self {
+        Self::user("webData2", user)
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -67,8 +110,10 @@ pub struct SubscriptionResponseData {
 #[derive(Debug, Clone, Deserialize)]
 #[serde(untagged)]
 pub enum WebSocketMessage {
+    User(UserMessage),
     SubscriptionResponse(SubscriptionResponse),
     CandleData(CandleData),
+    ActiveAssetCtxData(ActiveAssetCtxData),
     AllMidsData(AllMidsData),
     Error(ErrorMessage),
 }
@@ -105,6 +150,51 @@ pub struct CandleUpdate {
     pub trades: Option<u64>,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub struct ActiveAssetCtxData {
+    pub channel: String,
+    pub data: ActiveAssetCtx,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ActiveAssetCtx {
+    pub coin: String,
+    pub ctx: PerpAssetCtx,
+}
+
+/// Perp asset context; numbers are decimal strings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PerpAssetCtx {
+    /// Notional volume over the trailing 24 hours
+    pub day_ntl_vlm: String,
+    #[serde(default)]
+    pub prev_day_px: Option<String>,
+    #[serde(default)]
+    pub mark_px: Option<String>,
+    #[serde(default)]
+    pub mid_px: Option<String>,
+    pub funding: String,
+    pub open_interest: String,
+    pub oracle_px: String,
+    #[serde(default)]
+    pub premium: Option<String>,
+}
+
+impl PerpAssetCtx {
+    /// Parsed context; values that are missing or not numbers are left unset
+    pub fn to_asset_context(&self) -> AssetContext {
+        let parse = |value: Option<&str>| value.and_then(|v| v.parse::<f64>().ok());
+        AssetContext {
+            open_interest: parse(Some(&self.open_interest)),
+            mark_price: parse(self.mark_px.as_deref()),
+            oracle_price: parse(Some(&self.oracle_px)),
+            premium: parse(self.premium.as_deref()),
+            day_notional_volume: parse(Some(&self.day_ntl_vlm)),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct AllMidsData {
     pub channel: String,
@@ -127,3 +217,151 @@ pub struct ErrorMessage {
 pub struct ErrorData {
     pub error: String,
 }
+
+/// Messages on the per-user channels, keyed by channel name
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "channel", content = "data", rename_all = "camelCase")]
+pub enum UserMessage {
+    Notification(WsNotification),
+    OrderUpdates(Vec<WsOrderUpdate>),
+    UserFills(WsUserFills),
+    UserFundings(WsUserFundings),
+    WebData2(WsWebData2),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WsNotification {
+    pub notification: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WsOrderUpdate {
+    pub order: WsBasicOrder,
+    /// open, filled, canceled, triggered, rejected, marginCanceled, ...
+    pub status: String,
+    pub status_timestamp: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WsBasicOrder {
+    pub coin: String,
+    /// "B" for bids (buys), "A" for asks (sells)
+    pub side: String,
+    pub limit_px: String,
+    /// Remaining size
+    pub sz: String,
+    pub oid: u64,
+    pub timestamp: i64,
+    pub orig_sz: String,
+    #[serde(default)]
+    pub cloid: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WsUserFills {
+    /// Set on the first message after subscribing, which carries recent history
+    #[serde(default)]
+    pub is_snapshot: Option<bool>,
+    pub user: String,
+    pub fills: Vec<WsFill>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WsFill {
+    pub coin: String,
+    pub px: String,
+    pub sz: String,
+    pub side: String,
+    pub time: i64,
+    #[serde(default)]
+    pub start_position: Option<String>,
+    #[serde(default)]
+    pub dir: Option<String>,
+    #[serde(default)]
+    pub closed_pnl: Option<String>,
+    #[serde(default)]
+    pub hash: Option<String>,
+    pub oid: u64,
+    #[serde(default)]
+    pub crossed: bool,
+    #[serde(default)]
+    pub fee: Option<String>,
+    pub tid: u64,
+    #[serde(default)]
+    pub fee_token: Option<String>,
+    #[serde(default)]
+    pub cloid: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WsUserFundings {
+    #[serde(default)]
+    pub is_snapshot: Option<bool>,
+    pub user: String,
+    pub fundings: Vec<WsUserFunding>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WsUserFunding {
+    pub time: i64,
+    pub coin: String,
+    /// Amount received (positive) or paid (negative)
+    pub usdc: String,
+    /// Signed position size the funding applied to
+    pub szi: String,
+    pub funding_rate: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WsWebData2 {
+    pub clearinghouse_state: WsClearinghouseState,
+    #[serde(default)]
+    pub user: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WsClearinghouseState {
+    pub asset_positions: Vec<WsAssetPosition>,
+    #[serde(default)]
+    pub margin_summary: Option<WsMarginSummary>,
+    #[serde(default)]
+    pub time: Option<i64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WsAssetPosition {
+    pub position: WsPosition,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WsPosition {
+    pub coin: String,
+    /// Signed size, negative for shorts
+    pub szi: String,
+    #[serde(default)]
+    pub entry_px: Option<String>,
+    pub unrealized_pnl: String,
+    pub leverage: WsLeverage,
+    #[serde(default)]
+    pub liquidation_px: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WsLeverage {
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WsMarginSummary {
+    pub account_value: String,
+}
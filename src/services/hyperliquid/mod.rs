@@ -1,11 +1,22 @@
-//! Hyperliquid WebSocket integration for market data
+//! Hyperliquid integration: market data over WebSocket and REST, order execution
+//! and the account's user event stream
 
+pub mod backfill;
 pub mod client;
+pub mod exchange;
 pub mod messages;
 pub mod provider;
+pub mod recording;
 pub mod rest;
+pub mod signing;
 pub mod subscriptions;
+pub mod user_events;
 
+pub use backfill::{BackfillConfig, BackfillReport, CandleBackfiller};
 pub use client::{HyperliquidClient, MockWebSocketClient, WebSocketClient};
+pub use exchange::HyperliquidExecutor;
 pub use provider::HyperliquidMarketDataProvider;
+pub use recording::{RecordedEvent, RecordingWebSocketClient, ReplayWebSocketClient};
 pub use rest::HyperliquidRestClient;
+pub use signing::HyperliquidSigner;
+pub use user_events::{parse_user_events, HyperliquidUserEvents};
@@ -3,19 +3,21 @@
 use crate::cache::RedisCache;
 use crate::config;
 use crate::db::QuestDatabase;
-use crate::models::indicators::Candle;
+use crate::models::indicators::{AssetContext, Candle};
+use crate::services::candle_events::CandleClosed;
+use crate::services::market_bus::{market_event_channel, MarketEvent};
 use crate::services::market_data::MarketDataProvider;
 use chrono::{DateTime, Duration as ChronoDuration, Utc};
 use serde_json;
 use std::collections::{HashMap, VecDeque};
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{broadcast, RwLock};
 use tokio::time::{sleep, Duration};
 use tracing::{debug, error, warn};
 
 use super::client::{ClientEvent, HyperliquidClient, WebSocketClient};
 use super::messages::{CandleData, CandleUpdate, RequestMessage, Subscription, WebSocketMessage};
-use super::rest::HyperliquidRestClient;
+use super::rest::{align_funding_rates, HyperliquidRestClient};
 use super::subscriptions::{SubscriptionKey, SubscriptionManager};
 
 pub struct HyperliquidMarketDataProvider {
@@ -28,17 +30,42 @@ pub struct HyperliquidMarketDataProvider {
     rest_client: Arc<HyperliquidRestClient>,
     database: Option<Arc<QuestDatabase>>,
     cache: Option<Arc<RedisCache>>,
+    backends: Arc<Mutex<Backends>>,
     funding_cache: Arc<RwLock<HashMap<String, FundingCacheEntry>>>,
+    asset_contexts: Arc<RwLock<HashMap<String, AssetContext>>>,
+    market_events: broadcast::Sender<MarketEvent>,
+    connections: broadcast::Sender<()>,
+}
+
+/// Storage backends of live candles, shared with the message task because it
+/// starts before the builders configure them
+#[derive(Default)]
+struct Backends {
+    database: Option<Arc<QuestDatabase>>,
+    cache: Option<Arc<RedisCache>>,
 }
 
 impl HyperliquidMarketDataProvider {
     pub fn new() -> Self {
-        Self::with_intervals(vec![
+        Self::with_intervals(Self::default_intervals())
+    }
+
+    fn default_intervals() -> Vec<String> {
+        vec![
             "1m".to_string(),
             "5m".to_string(),
             "15m".to_string(),
             "1h".to_string(),
-        ])
+        ]
+    }
+
+    /// Use a custom WebSocket client (e.g. recording or replay) with the default intervals
+    pub fn with_websocket_client(websocket_client: Arc<dyn WebSocketClient>) -> Self {
+        Self::with_clients(
+            websocket_client,
+            Arc::new(HyperliquidRestClient::new()),
+            Self::default_intervals(),
+        )
     }
 
     pub fn with_intervals(candle_intervals: Vec<String>) -> Self {
@@ -62,7 +89,11 @@ impl HyperliquidMarketDataProvider {
             rest_client,
             database: None,
             cache: None,
+            backends: Arc::new(Mutex::new(Backends::default())),
             funding_cache: Arc::new(RwLock::new(HashMap::new())),
+            asset_contexts: Arc::new(RwLock::new(HashMap::new())),
+            market_events: market_event_channel().0,
+            connections: broadcast::channel(16).0,
         };
 
         provider.spawn_background_tasks();
@@ -77,10 +108,12 @@ impl HyperliquidMarketDataProvider {
             latest_prices: self.latest_prices.clone(),
             pending_subscriptions: self.pending_subscriptions.clone(),
             candle_intervals: self.candle_intervals.clone(),
-            database: self.database.clone(),
-            cache: self.cache.clone(),
+            backends: self.backends.clone(),
             rest_client: self.rest_client.clone(),
             funding_cache: self.funding_cache.clone(),
+            asset_contexts: self.asset_contexts.clone(),
+            market_events: self.market_events.clone(),
+            connections: self.connections.clone(),
         }
     }
 
@@ -118,10 +151,12 @@ impl HyperliquidMarketDataProvider {
 
                 // Store in QuestDB if available
                 if let Some(ref db) = self.database {
-                    if let Err(e) = db
-                        .store_candles_batch(coin, interval, &historical_candles)
-                        .await
-                    {
+                    let closed: Vec<Candle> = historical_candles
+                        .iter()
+                        .filter(|c| c.is_closed)
+                        .cloned()
+                        .collect();
+                    if let Err(e) = db.store_candles_batch(coin, interval, &closed).await {
                         warn!(coin = %coin, interval = %interval, error = %e, "Failed to store historical candles in QuestDB");
                     } else {
                         debug!(coin = %coin, interval = %interval, count = historical_candles.len(), "Stored {} historical candles in QuestDB", historical_candles.len());
@@ -208,6 +243,10 @@ impl HyperliquidMarketDataProvider {
         })?;
 
         self.subscriptions.add(key).await;
+
+        if let Err(e) = subscribe_asset_ctx(&self.client, &self.subscriptions, coin).await {
+            warn!(coin = %coin, error = %e, "Failed to subscribe to asset context for {}", coin);
+        }
         Ok(())
     }
 
@@ -239,21 +278,7 @@ impl HyperliquidMarketDataProvider {
                     return;
                 }
                 funding_points.sort_by_key(|p| p.timestamp);
-
-                let mut idx = 0usize;
-                let mut current_rate: Option<f64> = None;
-                for candle in candles.iter_mut() {
-                    while idx < funding_points.len()
-                        && funding_points[idx].timestamp <= candle.timestamp
-                    {
-                        current_rate = Some(funding_points[idx].funding_rate);
-                        idx += 1;
-                    }
-
-                    if let Some(rate) = current_rate {
-                        candle.funding_rate = Some(rate);
-                    }
-                }
+                align_funding_rates(candles, &funding_points);
             }
             Err(e) => {
                 warn!(coin = %coin, error = %e, "Failed to fetch funding history for {}", coin);
@@ -261,7 +286,8 @@ impl HyperliquidMarketDataProvider {
         }
     }
 
-    fn get_primary_interval(&self) -> &str {
+    /// Interval `get_candles` reads, which strategies are evaluated on
+    pub fn primary_interval(&self) -> &str {
         self.candle_intervals
             .first()
             .map(|s| s.as_str())
@@ -272,12 +298,34 @@ impl HyperliquidMarketDataProvider {
         self.client.clone()
     }
 
+    /// Receive candle updates and closes, mid prices and funding updates from now on
+    pub fn subscribe_market_events(&self) -> broadcast::Receiver<MarketEvent> {
+        self.market_events.subscribe()
+    }
+
+    /// Notified whenever the WebSocket (re)connects and subscriptions were sent again
+    pub fn subscribe_connections(&self) -> broadcast::Receiver<()> {
+        self.connections.subscribe()
+    }
+
+    /// Candle intervals subscribed for every coin
+    pub fn intervals(&self) -> &[String] {
+        &self.candle_intervals
+    }
+
+    /// Latest asset context received for a coin
+    pub async fn asset_context(&self, coin: &str) -> Option<AssetContext> {
+        self.asset_contexts.read().await.get(coin).copied()
+    }
+
     pub fn with_database(mut self, database: Arc<QuestDatabase>) -> Self {
+        self.backends.lock().unwrap().database = Some(database.clone());
         self.database = Some(database);
         self
     }
 
     pub fn with_cache(mut self, cache: Arc<RedisCache>) -> Self {
+        self.backends.lock().unwrap().cache = Some(cache.clone());
         self.cache = Some(cache);
         self
     }
@@ -286,17 +334,18 @@ impl HyperliquidMarketDataProvider {
 #[derive(Clone)]
 struct TaskProvider {
     client: Arc<dyn WebSocketClient>,
-    #[allow(dead_code)] // Kept for future resubscription functionality
     subscriptions: Arc<SubscriptionManager>,
     candles: Arc<RwLock<HashMap<String, VecDeque<Candle>>>>,
     latest_prices: Arc<RwLock<HashMap<String, f64>>>,
     pending_subscriptions: Arc<RwLock<Vec<(String, String)>>>,
     #[allow(dead_code)] // Used for resubscription
     candle_intervals: Vec<String>,
-    database: Option<Arc<QuestDatabase>>,
-    cache: Option<Arc<RedisCache>>,
+    backends: Arc<Mutex<Backends>>,
     rest_client: Arc<HyperliquidRestClient>,
     funding_cache: Arc<RwLock<HashMap<String, FundingCacheEntry>>>,
+    asset_contexts: Arc<RwLock<HashMap<String, AssetContext>>>,
+    market_events: broadcast::Sender<MarketEvent>,
+    connections: broadcast::Sender<()>,
 }
 
 impl TaskProvider {
@@ -327,9 +376,18 @@ impl TaskProvider {
                                 debug!(coin = %coin, interval = %interval, "Resubscribed to {} {}", coin, interval);
                             }
                         }
+                        // No receivers is fine: gap audits are optional
+                        let _ = self.connections.send(());
                     }
                     ClientEvent::Disconnected => {
                         debug!("WebSocket disconnected");
+                        // Subscriptions died with the connection and are sent again on reconnect
+                        self.subscriptions.clear().await;
+                        // Forming bars missed their last updates; leave them to the gap repair
+                        // instead of storing them as closed when the next bar arrives
+                        for candles in self.candles.write().await.values_mut() {
+                            candles.retain(|c| c.is_closed);
+                        }
                     }
                     ClientEvent::Error(e) => {
                         error!(error = %e, "WebSocket error");
@@ -374,6 +432,10 @@ impl TaskProvider {
         })?;
 
         self.subscriptions.add(key).await;
+
+        if let Err(e) = subscribe_asset_ctx(&self.client, &self.subscriptions, coin).await {
+            warn!(coin = %coin, error = %e, "Failed to subscribe to asset context for {}", coin);
+        }
         Ok(())
     }
 
@@ -450,14 +512,51 @@ impl TaskProvider {
                     debug!("Successfully processed candle update");
                 }
             }
+            WebSocketMessage::ActiveAssetCtxData(ctx_data) => {
+                let context = ctx_data.data.ctx.to_asset_context();
+                debug!(coin = %ctx_data.data.coin, context = ?context, "Received asset context for {}", ctx_data.data.coin);
+                let now = Utc::now();
+                if let Ok(funding_rate) = ctx_data.data.ctx.funding.parse::<f64>() {
+                    let _ = self.market_events.send(MarketEvent::FundingUpdate {
+                        symbol: ctx_data.data.coin.clone(),
+                        funding_rate,
+                        timestamp: now,
+                    });
+                }
+                if let Some(price) = ctx_data
+                    .data
+                    .ctx
+                    .mid_px
+                    .as_deref()
+                    .and_then(|px| px.parse::<f64>().ok())
+                {
+                    let _ = self.market_events.send(MarketEvent::MidPrice {
+                        symbol: ctx_data.data.coin.clone(),
+                        price,
+                        timestamp: now,
+                    });
+                }
+                self.asset_contexts
+                    .write()
+                    .await
+                    .insert(ctx_data.data.coin, context);
+            }
             WebSocketMessage::AllMidsData(mids_data) => {
                 debug!(
                     count = mids_data.data.len(),
                     "Received allMids data: {} prices",
                     mids_data.data.len()
                 );
+                let now = Utc::now();
                 for mid in mids_data.data {
                     let price: f64 = mid.px.parse().unwrap_or(0.0);
+                    if price > 0.0 {
+                        let _ = self.market_events.send(MarketEvent::MidPrice {
+                            symbol: mid.coin.clone(),
+                            price,
+                            timestamp: now,
+                        });
+                    }
                     let mut prices = self.latest_prices.write().await;
                     prices.insert(mid.coin, price);
                 }
@@ -465,8 +564,9 @@ impl TaskProvider {
             WebSocketMessage::SubscriptionResponse(resp) => {
                 let sub_info = match &resp.data.subscription {
                     Subscription::Candle { coin, interval, .. } => format!("{}/{}", coin, interval),
+                    Subscription::Coin { sub_type, coin } => format!("{}/{}", sub_type, coin),
                     Subscription::AllMids { .. } => "allMids".to_string(),
-                    Subscription::Notification { user, .. } => format!("notification/{}", user),
+                    Subscription::User { sub_type, user } => format!("{}/{}", sub_type, user),
                 };
                 let snapshot_info = resp
                     .is_snapshot
@@ -477,6 +577,9 @@ impl TaskProvider {
             WebSocketMessage::Error(err) => {
                 error!(error = %err.data.error, "WebSocket error");
             }
+            WebSocketMessage::User(_) => {
+                debug!("Ignoring user channel message");
+            }
         }
 
         Ok(())
@@ -536,41 +639,82 @@ impl TaskProvider {
         let timestamp =
             DateTime::from_timestamp(update.end_time as i64 / 1000, 0).unwrap_or_else(Utc::now);
 
-        let mut candle = Candle::new(open, high, low, close, volume, timestamp);
+        // Updates of the forming bar keep arriving until it ends; it is closed
+        // once its end time has passed or an update for a later bar arrives
+        let mut candle = Candle::new(open, high, low, close, volume, timestamp)
+            .with_end_time(update.end_time as i64, Utc::now());
+
+        // The last update of a bar carries the asset context as of its close
+        if let Some(context) = self.asset_contexts.read().await.get(coin) {
+            candle = candle.with_asset_context(context);
+        }
 
         self.attach_live_funding_rate(coin, &mut candle).await;
 
-        // Store in QuestDB
-        if let Some(ref db) = self.database {
-            if let Err(e) = db.store_candle(coin, interval, &candle).await {
-                warn!(coin = %coin, interval = %interval, error = %e, "Failed to store candle in QuestDB");
+        // Update in-memory buffer, closing the bars this update supersedes
+        let symbol_key = format!("{}_{}", coin, interval);
+        let mut closed = Vec::new();
+        let newly_closed;
+        {
+            let mut candles_map = self.candles.write().await;
+            let candles = candles_map
+                .entry(symbol_key.clone())
+                .or_insert_with(VecDeque::new);
+
+            for previous in candles
+                .iter_mut()
+                .filter(|c| !c.is_closed && c.timestamp < timestamp)
+            {
+                previous.is_closed = true;
+                closed.push(previous.clone());
             }
-        }
 
-        // Update in-memory buffer
-        let symbol_key = format!("{}_{}", coin, interval);
-        let mut candles_map = self.candles.write().await;
-        let candles = candles_map
-            .entry(symbol_key.clone())
-            .or_insert_with(VecDeque::new);
-
-        // Remove any existing candle with the same timestamp (update existing candle)
-        candles.retain(|c| c.timestamp != timestamp);
-        candles.push_back(candle.clone());
-
-        // Keep only last 1000 candles per symbol
-        while candles.len() > 1000 {
-            candles.pop_front();
+            // Late updates of a bar that already closed are kept in memory but
+            // neither persisted nor announced again
+            newly_closed = candle.is_closed
+                && !candles
+                    .iter()
+                    .any(|c| c.timestamp == timestamp && c.is_closed);
+
+            // Remove any existing candle with the same timestamp (update existing candle)
+            candles.retain(|c| c.timestamp != timestamp);
+            candles.push_back(candle.clone());
+
+            // Keep only last 1000 candles per symbol
+            while candles.len() > 1000 {
+                candles.pop_front();
+            }
+
+            debug!(symbol = %symbol_key, count = candles.len(), closed = candle.is_closed, "Stored candle for {}: total candles = {}", symbol_key, candles.len());
+        }
+        if newly_closed {
+            closed.push(candle.clone());
         }
 
-        debug!(symbol = %symbol_key, count = candles.len(), "Stored candle for {}: total candles = {}", symbol_key, candles.len());
+        let (database, cache) = {
+            let backends = self.backends.lock().unwrap();
+            (backends.database.clone(), backends.cache.clone())
+        };
+
+        // Only closed bars are persisted; the forming bar lives in memory and Redis
+        if let Some(ref db) = database {
+            for bar in &closed {
+                if let Err(e) = db.store_candle(coin, interval, bar).await {
+                    warn!(coin = %coin, interval = %interval, error = %e, "Failed to store candle in QuestDB");
+                }
+            }
+        }
 
         // Update Redis cache - get current cached candles, add new one, and update cache
-        if let Some(ref cache) = self.cache {
+        if let Some(ref cache) = cache {
             if let Ok(Some(mut cached_candles)) = cache.get_cached_candles(coin, interval).await {
-                // Remove duplicate timestamp
-                cached_candles.retain(|c| c.timestamp != timestamp);
+                // Replace this bar and the bars it closed
+                cached_candles.retain(|c| {
+                    c.timestamp != timestamp && !closed.iter().any(|b| b.timestamp == c.timestamp)
+                });
+                cached_candles.extend(closed.iter().filter(|b| b.timestamp != timestamp).cloned());
                 cached_candles.push(candle.clone());
+                cached_candles.sort_by_key(|c| c.timestamp);
                 // Keep only last 200 in cache
                 if cached_candles.len() > 200 {
                     cached_candles.remove(0);
@@ -592,6 +736,24 @@ impl TaskProvider {
 
         let mut prices = self.latest_prices.write().await;
         prices.insert(coin.clone(), close);
+        drop(prices);
+
+        // Announce the update and closes once the bars are readable from memory
+        // and Redis. No receivers is fine: nobody follows events in this process
+        let _ = self.market_events.send(MarketEvent::CandleUpdate {
+            symbol: coin.clone(),
+            interval: interval.clone(),
+            candle: candle.clone(),
+        });
+        for bar in &closed {
+            let _ = self
+                .market_events
+                .send(MarketEvent::CandleClosed(CandleClosed {
+                    symbol: coin.clone(),
+                    interval: interval.clone(),
+                    timestamp: bar.timestamp,
+                }));
+        }
 
         Ok(())
     }
@@ -638,6 +800,52 @@ impl TaskProvider {
     }
 }
 
+/// Subscribe to the `activeAssetCtx` channel of a coin, once per connection
+async fn subscribe_asset_ctx(
+    client: &Arc<dyn WebSocketClient>,
+    subscriptions: &SubscriptionManager,
+    coin: &str,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let key = SubscriptionKey::active_asset_ctx(coin);
+    if subscriptions.contains(&key).await {
+        return Ok(());
+    }
+
+    let request = RequestMessage::Subscribe {
+        subscription: Subscription::active_asset_ctx(coin),
+    };
+    let json = serde_json::to_string(&request)?;
+    debug!(subscription = %json, "Sending subscription");
+    client
+        .send_text(json)
+        .await
+        .map_err(|e| format!("WebSocket send error: {}", e))?;
+
+    subscriptions.add(key).await;
+    Ok(())
+}
+
+/// Unsubscribe from a channel if it is subscribed on the current connection
+async fn unsubscribe_channel(
+    client: &Arc<dyn WebSocketClient>,
+    subscriptions: &SubscriptionManager,
+    key: SubscriptionKey,
+    subscription: Subscription,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    if !subscriptions.contains(&key).await {
+        return Ok(());
+    }
+    subscriptions.remove(&key).await;
+
+    let request = RequestMessage::Unsubscribe { subscription };
+    let json = serde_json::to_string(&request)?;
+    debug!(subscription = %json, "Sending unsubscription");
+    client
+        .send_text(json)
+        .await
+        .map_err(|e| format!("WebSocket send error: {}", e).into())
+}
+
 #[derive(Debug, Clone)]
 struct FundingCacheEntry {
     timestamp: DateTime<Utc>,
@@ -651,7 +859,7 @@ impl MarketDataProvider for HyperliquidMarketDataProvider {
         symbol: &str,
         limit: usize,
     ) -> Result<Vec<Candle>, Box<dyn std::error::Error + Send + Sync>> {
-        let interval = self.get_primary_interval();
+        let interval = self.primary_interval();
         let symbol_key = format!("{}_{}", symbol, interval);
 
         // Try Redis cache first
@@ -722,10 +930,7 @@ impl MarketDataProvider for HyperliquidMarketDataProvider {
             Ok(price)
         } else {
             // Subscribe to get price updates
-            if let Err(e) = self
-                .subscribe_candle(symbol, self.get_primary_interval())
-                .await
-            {
+            if let Err(e) = self.subscribe_candle(symbol, self.primary_interval()).await {
                 error!(symbol = %symbol, error = %e, "Failed to subscribe to {}", symbol);
             }
             // Wait a bit for price to arrive
@@ -748,6 +953,59 @@ impl MarketDataProvider for HyperliquidMarketDataProvider {
         }
         Ok(())
     }
+
+    async fn unsubscribe(
+        &self,
+        symbol: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        // Forget the coin first so a reconnect does not subscribe it again
+        let intervals: Vec<String> = {
+            let mut pending = self.pending_subscriptions.write().await;
+            let intervals = pending
+                .iter()
+                .filter(|(coin, _)| coin == symbol)
+                .map(|(_, interval)| interval.clone())
+                .collect();
+            pending.retain(|(coin, _)| coin != symbol);
+            intervals
+        };
+
+        let mut channels: Vec<(SubscriptionKey, Subscription)> = intervals
+            .iter()
+            .map(|interval| {
+                (
+                    SubscriptionKey::candle(symbol, interval),
+                    Subscription::candle(symbol, interval),
+                )
+            })
+            .collect();
+        channels.push((
+            SubscriptionKey::active_asset_ctx(symbol),
+            Subscription::active_asset_ctx(symbol),
+        ));
+        for (key, subscription) in channels {
+            // A dead connection took the subscription with it
+            if let Err(e) =
+                unsubscribe_channel(&self.client, &self.subscriptions, key, subscription).await
+            {
+                warn!(coin = %symbol, error = %e, "Failed to unsubscribe from {}", symbol);
+            }
+        }
+
+        // Buffers would serve stale bars if the coin is subscribed again later
+        let prefix = format!("{}_", symbol);
+        self.candles
+            .write()
+            .await
+            .retain(|key, _| !key.starts_with(&prefix));
+        self.asset_contexts.write().await.remove(symbol);
+        debug!(coin = %symbol, "Unsubscribed from {}", symbol);
+        Ok(())
+    }
+
+    async fn is_connected(&self) -> bool {
+        self.client.is_connected().await
+    }
 }
 
 impl HyperliquidMarketDataProvider {
@@ -759,6 +1017,14 @@ impl HyperliquidMarketDataProvider {
         // Delegate to the trait method
         <Self as MarketDataProvider>::subscribe(self, symbol).await
     }
+
+    /// Unsubscribe from a symbol in every interval (public wrapper)
+    pub async fn unsubscribe(
+        &self,
+        symbol: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        <Self as MarketDataProvider>::unsubscribe(self, symbol).await
+    }
 }
 
 impl Default for HyperliquidMarketDataProvider {
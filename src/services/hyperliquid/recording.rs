@@ -0,0 +1,308 @@
+//! Record and replay of raw WebSocket client events
+//!
+//! `RecordingWebSocketClient` wraps any `WebSocketClient` and appends every
+//! `ClientEvent` it hands out to a JSON Lines file together with the time it was
+//! received. `ReplayWebSocketClient` feeds such a recording back through
+//! `HyperliquidMarketDataProvider` at real or accelerated speed, so incidents can
+//! be reproduced end-to-end without network access.
+
+use super::client::{ClientEvent, WebSocketClient};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, BufWriter};
+use tokio::sync::{mpsc, Mutex, RwLock};
+use tokio::time::{sleep, Duration};
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, info, warn};
+
+/// One line of a recording file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedEvent {
+    pub received_at: DateTime<Utc>,
+    pub event: ClientEvent,
+}
+
+/// Read a JSON Lines recording, skipping blank lines
+pub async fn read_recording(
+    path: impl AsRef<Path>,
+) -> Result<Vec<RecordedEvent>, Box<dyn std::error::Error + Send + Sync>> {
+    let path = path.as_ref();
+    let file = tokio::fs::File::open(path).await.map_err(|e| {
+        Box::new(std::io::Error::other(format!(
+            "Failed to open recording {}: {}",
+            path.display(),
+            e
+        ))) as Box<dyn std::error::Error + Send + Sync>
+    })?;
+
+    let mut lines = BufReader::new(file).lines();
+    let mut events = Vec::new();
+    let mut line_number = 0usize;
+    while let Some(line) = lines.next_line().await? {
+        line_number += 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let event: RecordedEvent = serde_json::from_str(&line).map_err(|e| {
+            Box::new(std::io::Error::other(format!(
+                "Invalid recording entry at {}:{}: {}",
+                path.display(),
+                line_number,
+                e
+            ))) as Box<dyn std::error::Error + Send + Sync>
+        })?;
+        events.push(event);
+    }
+    Ok(events)
+}
+
+/// Appends received events to a recording file
+pub struct EventRecorder {
+    path: PathBuf,
+    writer: Mutex<BufWriter<tokio::fs::File>>,
+}
+
+impl EventRecorder {
+    /// Open `path` for appending, creating it if needed
+    pub async fn create(
+        path: impl Into<PathBuf>,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let path = path.into();
+        let file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .await
+            .map_err(|e| {
+                Box::new(std::io::Error::other(format!(
+                    "Failed to open recording {}: {}",
+                    path.display(),
+                    e
+                ))) as Box<dyn std::error::Error + Send + Sync>
+            })?;
+        Ok(Self {
+            path,
+            writer: Mutex::new(BufWriter::new(file)),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Write one event stamped with the current time
+    pub async fn record(
+        &self,
+        event: &ClientEvent,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut line = serde_json::to_string(&RecordedEvent {
+            received_at: Utc::now(),
+            event: event.clone(),
+        })?;
+        line.push('\n');
+
+        // Flush every line so a crash loses at most the event being written
+        let mut writer = self.writer.lock().await;
+        writer.write_all(line.as_bytes()).await?;
+        writer.flush().await?;
+        Ok(())
+    }
+}
+
+/// `WebSocketClient` decorator that records every received event
+pub struct RecordingWebSocketClient {
+    inner: Arc<dyn WebSocketClient>,
+    recorder: EventRecorder,
+}
+
+impl RecordingWebSocketClient {
+    pub fn new(inner: Arc<dyn WebSocketClient>, recorder: EventRecorder) -> Self {
+        Self { inner, recorder }
+    }
+
+    /// Record events from `inner` to the file at `path`
+    pub async fn to_file(
+        inner: Arc<dyn WebSocketClient>,
+        path: impl Into<PathBuf>,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let recorder = EventRecorder::create(path).await?;
+        info!(path = %recorder.path().display(), "Recording WebSocket events to {}", recorder.path().display());
+        Ok(Self::new(inner, recorder))
+    }
+}
+
+#[async_trait]
+impl WebSocketClient for RecordingWebSocketClient {
+    async fn connect(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.connect().await
+    }
+
+    async fn send(&self, message: Message) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.send(message).await
+    }
+
+    async fn receive(&self) -> Option<ClientEvent> {
+        let event = self.inner.receive().await?;
+        if let Err(e) = self.recorder.record(&event).await {
+            warn!(error = %e, "Failed to record WebSocket event");
+        }
+        Some(event)
+    }
+
+    async fn is_connected(&self) -> bool {
+        self.inner.is_connected().await
+    }
+
+    async fn wait_for_connection(&self, timeout: Duration) -> bool {
+        self.inner.wait_for_connection(timeout).await
+    }
+}
+
+/// `WebSocketClient` that plays back a recording instead of connecting.
+///
+/// Playback starts on `connect()`. Gaps between events are divided by the speed
+/// factor; a speed of zero replays without any delay. Sent messages are kept for
+/// inspection and never leave the process.
+pub struct ReplayWebSocketClient {
+    events: Arc<Vec<RecordedEvent>>,
+    speed: f64,
+    started: AtomicBool,
+    finished: Arc<AtomicBool>,
+    connected: Arc<RwLock<bool>>,
+    sent_messages: Arc<RwLock<Vec<Message>>>,
+    sender: Mutex<Option<mpsc::UnboundedSender<ClientEvent>>>,
+    receiver: RwLock<Option<mpsc::UnboundedReceiver<ClientEvent>>>,
+}
+
+impl ReplayWebSocketClient {
+    pub fn new(events: Vec<RecordedEvent>) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self {
+            events: Arc::new(events),
+            speed: 1.0,
+            started: AtomicBool::new(false),
+            finished: Arc::new(AtomicBool::new(false)),
+            connected: Arc::new(RwLock::new(false)),
+            sent_messages: Arc::new(RwLock::new(Vec::new())),
+            sender: Mutex::new(Some(tx)),
+            receiver: RwLock::new(Some(rx)),
+        }
+    }
+
+    /// Load a recording written by `RecordingWebSocketClient`
+    pub async fn from_file(
+        path: impl AsRef<Path>,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self::new(read_recording(path).await?))
+    }
+
+    /// Playback speed relative to the recording (1.0 = real time, 0 = no delay)
+    pub fn with_speed(mut self, speed: f64) -> Self {
+        self.speed = if speed.is_finite() { speed.max(0.0) } else { 1.0 };
+        self
+    }
+
+    pub fn event_count(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Whether every recorded event has been delivered
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::SeqCst)
+    }
+
+    pub async fn wait_until_finished(&self, timeout: Duration) -> bool {
+        let start = std::time::Instant::now();
+        while start.elapsed() < timeout {
+            if self.is_finished() {
+                return true;
+            }
+            sleep(Duration::from_millis(10)).await;
+        }
+        self.is_finished()
+    }
+
+    /// Messages the provider attempted to send during replay
+    pub async fn sent_messages(&self) -> Vec<Message> {
+        self.sent_messages.read().await.clone()
+    }
+}
+
+#[async_trait]
+impl WebSocketClient for ReplayWebSocketClient {
+    async fn connect(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if self.started.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+        let Some(sender) = self.sender.lock().await.take() else {
+            return Ok(());
+        };
+
+        let events = self.events.clone();
+        let speed = self.speed;
+        let connected = self.connected.clone();
+        let finished = self.finished.clone();
+        info!(events = events.len(), speed = speed, "Replaying {} recorded WebSocket events", events.len());
+
+        tokio::spawn(async move {
+            let mut previous: Option<DateTime<Utc>> = None;
+            for recorded in events.iter() {
+                if let Some(previous) = previous {
+                    let gap = (recorded.received_at - previous).to_std().unwrap_or_default();
+                    if speed > 0.0 && !gap.is_zero() {
+                        sleep(gap.div_f64(speed)).await;
+                    }
+                }
+                previous = Some(recorded.received_at);
+
+                match recorded.event {
+                    ClientEvent::Connected => *connected.write().await = true,
+                    ClientEvent::Disconnected => *connected.write().await = false,
+                    _ => {}
+                }
+                if sender.send(recorded.event.clone()).is_err() {
+                    break;
+                }
+            }
+            finished.store(true, Ordering::SeqCst);
+            debug!("WebSocket replay finished");
+        });
+
+        Ok(())
+    }
+
+    async fn send(&self, message: Message) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.sent_messages.write().await.push(message);
+        Ok(())
+    }
+
+    async fn receive(&self) -> Option<ClientEvent> {
+        let mut receiver_guard = self.receiver.write().await;
+        if let Some(mut receiver) = receiver_guard.take() {
+            let result = receiver.recv().await;
+            *receiver_guard = Some(receiver);
+            result
+        } else {
+            None
+        }
+    }
+
+    async fn is_connected(&self) -> bool {
+        *self.connected.read().await
+    }
+
+    async fn wait_for_connection(&self, timeout: Duration) -> bool {
+        let start = std::time::Instant::now();
+        while start.elapsed() < timeout {
+            if self.is_connected().await {
+                return true;
+            }
+            sleep(Duration::from_millis(50)).await;
+        }
+        false
+    }
+}
@@ -1,7 +1,10 @@
-//! Hyperliquid REST API client for fetching historical candles
+//! Hyperliquid REST API client for historical candles, funding and instrument metadata
 
 use crate::config;
 use crate::models::indicators::Candle;
+use crate::services::hyperliquid::exchange::tick_size;
+use crate::services::instruments::{normalize_symbol, Instrument};
+use crate::services::market_data::DEFAULT_VENUE;
 use chrono::{DateTime, Utc};
 use serde::Deserialize;
 use tracing::debug;
@@ -47,6 +50,28 @@ struct HyperliquidFundingHistoryEntry {
     timestamp: u64,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UniverseEntry {
+    name: String,
+    sz_decimals: u32,
+    max_leverage: u32,
+    #[serde(default)]
+    is_delisted: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct MetaUniverse {
+    universe: Vec<UniverseEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AssetCtxEntry {
+    #[serde(default)]
+    mark_px: Option<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct FundingRatePoint {
     pub coin: String,
@@ -54,6 +79,52 @@ pub struct FundingRatePoint {
     pub timestamp: DateTime<Utc>,
 }
 
+/// Maximum number of candles returned by one `candleSnapshot` request
+pub const CANDLE_SNAPSHOT_LIMIT: usize = 5000;
+
+/// Maximum number of entries returned by one `fundingHistory` request
+pub const FUNDING_HISTORY_LIMIT: usize = 500;
+
+/// Duration of a Hyperliquid candle interval in milliseconds
+pub fn interval_duration_ms(interval: &str) -> Option<u64> {
+    let minute = 60_000;
+    let ms = match interval {
+        "1m" => minute,
+        "3m" => 3 * minute,
+        "5m" => 5 * minute,
+        "15m" => 15 * minute,
+        "30m" => 30 * minute,
+        "1h" => 60 * minute,
+        "2h" => 120 * minute,
+        "4h" => 240 * minute,
+        "8h" => 480 * minute,
+        "12h" => 720 * minute,
+        "1d" => 1440 * minute,
+        "3d" => 3 * 1440 * minute,
+        "1w" => 7 * 1440 * minute,
+        _ => return None,
+    };
+    Some(ms)
+}
+
+/// Set each candle's funding rate to the latest funding entry at or before its timestamp.
+///
+/// Both slices must be sorted oldest first. Candles before the first entry are left untouched.
+pub fn align_funding_rates(candles: &mut [Candle], funding_points: &[FundingRatePoint]) {
+    let mut idx = 0usize;
+    let mut current_rate: Option<f64> = None;
+    for candle in candles.iter_mut() {
+        while idx < funding_points.len() && funding_points[idx].timestamp <= candle.timestamp {
+            current_rate = Some(funding_points[idx].funding_rate);
+            idx += 1;
+        }
+
+        if let Some(rate) = current_rate {
+            candle.funding_rate = Some(rate);
+        }
+    }
+}
+
 pub struct HyperliquidRestClient {
     base_url: String,
     client: reqwest::Client,
@@ -86,29 +157,37 @@ impl HyperliquidRestClient {
         interval: &str,
         count: usize,
     ) -> Result<Vec<Candle>, Box<dyn std::error::Error + Send + Sync>> {
-        // Hyperliquid REST API uses POST with JSON body
-        // Format: {"type":"candleSnapshot","req":{"coin":"BTC","interval":"1m","startTime":...,"endTime":...}}
-        let url = format!("{}/info", self.base_url);
-
         // Calculate timestamps based on interval and count
         let now = Utc::now();
         let end_time = now.timestamp_millis() as u64;
 
-        // Calculate start time based on interval duration
-        let interval_seconds = match interval {
-            "1m" => 60,
-            "5m" => 300,
-            "15m" => 900,
-            "1h" => 3600,
-            "4h" => 14400,
-            "1d" => 86400,
-            _ => 60, // default to 1 minute
-        };
+        // Calculate start time based on interval duration (default to 1 minute)
+        let interval_ms = interval_duration_ms(interval).unwrap_or(60_000);
 
         // Add some buffer (extra 10% to ensure we get enough candles)
-        let duration_ms = (interval_seconds * count as u64 * 110 / 100) * 1000;
+        let duration_ms = interval_ms * count as u64 * 110 / 100;
         let start_time = end_time.saturating_sub(duration_ms);
 
+        self.fetch_candles_range(coin, interval, start_time, end_time)
+            .await
+    }
+
+    /// Fetch candles whose open time falls within `[start_time, end_time]` (Unix millis)
+    ///
+    /// A single `candleSnapshot` request; Hyperliquid returns at most
+    /// `CANDLE_SNAPSHOT_LIMIT` candles per call, so callers covering long ranges
+    /// must page through them.
+    pub async fn fetch_candles_range(
+        &self,
+        coin: &str,
+        interval: &str,
+        start_time: u64,
+        end_time: u64,
+    ) -> Result<Vec<Candle>, Box<dyn std::error::Error + Send + Sync>> {
+        // Hyperliquid REST API uses POST with JSON body
+        // Format: {"type":"candleSnapshot","req":{"coin":"BTC","interval":"1m","startTime":...,"endTime":...}}
+        let url = format!("{}/info", self.base_url);
+
         let request_body = serde_json::json!({
             "type": "candleSnapshot",
             "req": {
@@ -158,6 +237,7 @@ impl HyperliquidRestClient {
             )) as Box<dyn std::error::Error + Send + Sync>
         })?;
 
+        let now = Utc::now();
         let mut result = Vec::new();
         for candle in candles {
             let open: f64 = candle.open.parse().map_err(|e| {
@@ -195,7 +275,10 @@ impl HyperliquidRestClient {
             let timestamp =
                 DateTime::from_timestamp(candle.end_time as i64 / 1000, 0).unwrap_or_else(Utc::now);
 
-            result.push(Candle::new(open, high, low, close, volume, timestamp));
+            result.push(
+                Candle::new(open, high, low, close, volume, timestamp)
+                    .with_end_time(candle.end_time as i64, now),
+            );
         }
 
         // Sort by timestamp (oldest first)
@@ -255,6 +338,75 @@ impl HyperliquidRestClient {
         parse_funding_history_response(&text)
     }
 
+    /// Fetch every perp listed on Hyperliquid, delisted ones included.
+    ///
+    /// Tick sizes are derived from the current mark price, as prices are
+    /// limited to significant figures rather than a fixed increment.
+    pub async fn fetch_instruments(
+        &self,
+    ) -> Result<Vec<Instrument>, Box<dyn std::error::Error + Send + Sync>> {
+        let url = format!("{}/info", self.base_url);
+        let response = self
+            .client
+            .post(&url)
+            .json(&serde_json::json!({ "type": "metaAndAssetCtxs" }))
+            .send()
+            .await
+            .map_err(|e| {
+                Box::new(std::io::Error::other(format!("HTTP request failed: {}", e)))
+                    as Box<dyn std::error::Error + Send + Sync>
+            })?;
+
+        let status = response.status();
+        let text = response.text().await.map_err(|e| {
+            Box::new(std::io::Error::other(format!(
+                "Failed to read response: {}",
+                e
+            ))) as Box<dyn std::error::Error + Send + Sync>
+        })?;
+
+        if !status.is_success() {
+            debug!(status = %status, response = %text, "Hyperliquid REST API error response");
+            return Err(Box::new(std::io::Error::other(format!(
+                "HTTP error: {} - Response: {}",
+                status, text
+            ))) as Box<dyn std::error::Error + Send + Sync>);
+        }
+
+        let (meta, ctxs): (MetaUniverse, Vec<AssetCtxEntry>) = serde_json::from_str(&text)
+            .map_err(|e| {
+                Box::new(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!(
+                        "Failed to parse metaAndAssetCtxs response: {} - Response: {}",
+                        e, text
+                    ),
+                )) as Box<dyn std::error::Error + Send + Sync>
+            })?;
+
+        Ok(meta
+            .universe
+            .into_iter()
+            .enumerate()
+            .map(|(index, asset)| {
+                let mark_price = ctxs
+                    .get(index)
+                    .and_then(|ctx| ctx.mark_px.as_deref())
+                    .and_then(|px| px.parse::<f64>().ok())
+                    .unwrap_or(0.0);
+                Instrument {
+                    symbol: normalize_symbol(&asset.name),
+                    venue: DEFAULT_VENUE.to_string(),
+                    tick_size: tick_size(mark_price, asset.sz_decimals),
+                    venue_symbol: asset.name,
+                    size_decimals: asset.sz_decimals,
+                    max_leverage: asset.max_leverage,
+                    listed: !asset.is_delisted,
+                }
+            })
+            .collect())
+    }
+
     /// Fetch the most recent funding rate entry for a coin.
     pub async fn fetch_latest_funding_rate(
         &self,
@@ -306,6 +458,12 @@ fn parse_funding_history_response(
     Ok(points)
 }
 
+impl Default for HyperliquidRestClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -331,10 +489,38 @@ mod tests {
         let result = parse_funding_history_response(json);
         assert!(result.is_err());
     }
-}
 
-impl Default for HyperliquidRestClient {
-    fn default() -> Self {
-        Self::new()
+    #[test]
+    fn aligns_latest_funding_rate_onto_candles() {
+        let candle = |secs: i64| Candle {
+            open: 1.0,
+            high: 1.0,
+            low: 1.0,
+            close: 1.0,
+            volume: 0.0,
+            timestamp: DateTime::from_timestamp(secs, 0).unwrap(),
+            open_interest: None,
+            funding_rate: None,
+            mark_price: None,
+            oracle_price: None,
+            premium: None,
+            day_notional_volume: None,
+            is_closed: true,
+        };
+        let point = |secs: i64, rate: f64| FundingRatePoint {
+            coin: "BTC".to_string(),
+            funding_rate: rate,
+            timestamp: DateTime::from_timestamp(secs, 0).unwrap(),
+        };
+
+        let mut candles = vec![candle(0), candle(3600), candle(5400), candle(7200)];
+        align_funding_rates(&mut candles, &[point(3600, 0.0001), point(7200, -0.0002)]);
+
+        assert_eq!(candles[0].funding_rate, None);
+        assert_eq!(candles[1].funding_rate, Some(0.0001));
+        assert_eq!(candles[2].funding_rate, Some(0.0001));
+        assert_eq!(candles[3].funding_rate, Some(-0.0002));
+        assert_eq!(interval_duration_ms("1h"), Some(3_600_000));
+        assert_eq!(interval_duration_ms("7m"), None);
     }
 }
@@ -0,0 +1,176 @@
+//! Hyperliquid L1 action signing
+//!
+//! Exchange actions are msgpack-encoded, hashed together with the nonce and
+//! optional vault address, and signed as an EIP-712 "phantom agent" message
+//! whose connection id is that hash.
+
+use crate::execution::ExecutionError;
+use k256::ecdsa::{RecoveryId, Signature, SigningKey, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+/// Chain id of the EIP-712 domain used for L1 actions (mainnet and testnet)
+const L1_CHAIN_ID: u64 = 1337;
+
+/// ECDSA signature in the `{r, s, v}` form expected by the `/exchange` endpoint
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActionSignature {
+    pub r: String,
+    pub s: String,
+    pub v: u8,
+}
+
+fn keccak(data: &[u8]) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
+
+fn parse_address(address: &str) -> Result<[u8; 20], ExecutionError> {
+    let bytes = hex::decode(address.trim_start_matches("0x"))
+        .map_err(|e| ExecutionError::Signing(format!("Invalid address '{}': {}", address, e)))?;
+    bytes
+        .try_into()
+        .map_err(|_| ExecutionError::Signing(format!("Invalid address length '{}'", address)))
+}
+
+fn address_of(key: &VerifyingKey) -> String {
+    let point = key.to_encoded_point(false);
+    let hash = keccak(&point.as_bytes()[1..]);
+    format!("0x{}", hex::encode(&hash[12..]))
+}
+
+/// Hash of a msgpack-encoded action, its nonce and the optional vault address
+pub fn action_hash<T: Serialize>(
+    action: &T,
+    nonce: u64,
+    vault_address: Option<&str>,
+) -> Result<[u8; 32], ExecutionError> {
+    let mut data = rmp_serde::to_vec_named(action)
+        .map_err(|e| ExecutionError::Signing(format!("Failed to encode action: {}", e)))?;
+    data.extend_from_slice(&nonce.to_be_bytes());
+    match vault_address {
+        Some(vault) => {
+            data.push(1);
+            data.extend_from_slice(&parse_address(vault)?);
+        }
+        None => data.push(0),
+    }
+    Ok(keccak(&data))
+}
+
+/// EIP-712 digest of the phantom agent message for an action hash
+pub fn l1_action_digest(connection_id: &[u8; 32], is_mainnet: bool) -> [u8; 32] {
+    let domain_type = keccak(
+        b"EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)",
+    );
+    let mut chain_id = [0u8; 32];
+    chain_id[24..].copy_from_slice(&L1_CHAIN_ID.to_be_bytes());
+    let mut domain = Vec::with_capacity(5 * 32);
+    domain.extend_from_slice(&domain_type);
+    domain.extend_from_slice(&keccak(b"Exchange"));
+    domain.extend_from_slice(&keccak(b"1"));
+    domain.extend_from_slice(&chain_id);
+    domain.extend_from_slice(&[0u8; 32]);
+    let domain_separator = keccak(&domain);
+
+    let agent_type = keccak(b"Agent(string source,bytes32 connectionId)");
+    let source: &[u8] = if is_mainnet { b"a" } else { b"b" };
+    let mut agent = Vec::with_capacity(3 * 32);
+    agent.extend_from_slice(&agent_type);
+    agent.extend_from_slice(&keccak(source));
+    agent.extend_from_slice(connection_id);
+    let struct_hash = keccak(&agent);
+
+    let mut message = Vec::with_capacity(2 + 2 * 32);
+    message.extend_from_slice(&[0x19, 0x01]);
+    message.extend_from_slice(&domain_separator);
+    message.extend_from_slice(&struct_hash);
+    keccak(&message)
+}
+
+/// Address that produced `signature` over `digest`
+pub fn recover_address(
+    digest: &[u8; 32],
+    signature: &ActionSignature,
+) -> Result<String, ExecutionError> {
+    let r = hex::decode(signature.r.trim_start_matches("0x"))
+        .map_err(|e| ExecutionError::Signing(format!("Invalid r: {}", e)))?;
+    let s = hex::decode(signature.s.trim_start_matches("0x"))
+        .map_err(|e| ExecutionError::Signing(format!("Invalid s: {}", e)))?;
+    if r.len() > 32 || s.len() > 32 {
+        return Err(ExecutionError::Signing(
+            "Signature component too long".to_string(),
+        ));
+    }
+    let mut bytes = [0u8; 64];
+    bytes[32 - r.len()..32].copy_from_slice(&r);
+    bytes[64 - s.len()..].copy_from_slice(&s);
+
+    let sig = Signature::from_slice(&bytes)
+        .map_err(|e| ExecutionError::Signing(format!("Invalid signature: {}", e)))?;
+    let recovery_id = signature
+        .v
+        .checked_sub(27)
+        .and_then(RecoveryId::from_byte)
+        .ok_or_else(|| ExecutionError::Signing(format!("Invalid v: {}", signature.v)))?;
+    let key = VerifyingKey::recover_from_prehash(digest, &sig, recovery_id)
+        .map_err(|e| ExecutionError::Signing(format!("Failed to recover signer: {}", e)))?;
+    Ok(address_of(&key))
+}
+
+/// Agent wallet key used to sign exchange actions
+#[derive(Clone)]
+pub struct HyperliquidSigner {
+    key: SigningKey,
+    address: String,
+}
+
+impl HyperliquidSigner {
+    /// Load a signer from a hex-encoded secp256k1 private key
+    pub fn from_hex(private_key: &str) -> Result<Self, ExecutionError> {
+        let bytes = hex::decode(private_key.trim().trim_start_matches("0x"))
+            .map_err(|e| ExecutionError::Signing(format!("Invalid private key hex: {}", e)))?;
+        let key = SigningKey::from_slice(&bytes)
+            .map_err(|e| ExecutionError::Signing(format!("Invalid private key: {}", e)))?;
+        let address = address_of(key.verifying_key());
+        Ok(Self { key, address })
+    }
+
+    /// Lowercase hex address of the agent wallet
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// Sign a prehashed 32-byte digest
+    pub fn sign_digest(&self, digest: &[u8; 32]) -> Result<ActionSignature, ExecutionError> {
+        let (signature, recovery_id) = self
+            .key
+            .sign_prehash_recoverable(digest)
+            .map_err(|e| ExecutionError::Signing(format!("Signing failed: {}", e)))?;
+        let bytes = signature.to_bytes();
+        Ok(ActionSignature {
+            r: format!("0x{}", hex::encode(&bytes[..32])),
+            s: format!("0x{}", hex::encode(&bytes[32..])),
+            v: 27 + recovery_id.to_byte(),
+        })
+    }
+
+    /// Sign an L1 exchange action (order, cancel, updateLeverage, ...)
+    pub fn sign_l1_action<T: Serialize>(
+        &self,
+        action: &T,
+        nonce: u64,
+        vault_address: Option<&str>,
+        is_mainnet: bool,
+    ) -> Result<ActionSignature, ExecutionError> {
+        let hash = action_hash(action, nonce, vault_address)?;
+        self.sign_digest(&l1_action_digest(&hash, is_mainnet))
+    }
+}
+
+impl std::fmt::Debug for HyperliquidSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HyperliquidSigner")
+            .field("address", &self.address)
+            .finish_non_exhaustive()
+    }
+}
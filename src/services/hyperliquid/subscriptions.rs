@@ -19,6 +19,14 @@ impl SubscriptionKey {
         }
     }
 
+    pub fn active_asset_ctx(coin: &str) -> Self {
+        Self {
+            sub_type: "activeAssetCtx".to_string(),
+            coin: Some(coin.to_string()),
+            interval: None,
+        }
+    }
+
     pub fn all_mids() -> Self {
         Self {
             sub_type: "allMids".to_string(),
@@ -54,6 +62,12 @@ impl SubscriptionManager {
         active.contains(key)
     }
 
+    /// Forget every subscription, e.g. when the connection carrying them dropped
+    pub async fn clear(&self) {
+        let mut active = self.active.write().await;
+        active.clear();
+    }
+
     pub async fn is_empty(&self) -> bool {
         let active = self.active.read().await;
         active.is_empty()
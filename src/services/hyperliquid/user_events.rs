@@ -0,0 +1,226 @@
+//! Hyperliquid user event stream
+//!
+//! Subscribes to the account's `orderUpdates`, `userFills`, `userFundings` and
+//! `webData2` channels and publishes them as [`UserEvent`]s. Subscriptions are
+//! sent again after every reconnect; the fill snapshot that follows a
+//! subscription covers fills made while disconnected.
+
+use crate::execution::risk::AccountSnapshot;
+use crate::execution::{
+    user_event_channel, ExchangeFill, ExchangeOrderReport, ExecutionError, FundingPayment,
+    UserEvent,
+};
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tokio::time::{sleep, Duration};
+use tracing::{debug, error, info, warn};
+
+use super::client::{ClientEvent, HyperliquidClient, WebSocketClient};
+use super::exchange::{
+    account_equity, account_positions, exchange_order_state, parse_number, parse_side,
+};
+use super::messages::{RequestMessage, Subscription, UserMessage};
+
+/// Channels carrying user data
+const USER_CHANNELS: [&str; 5] = [
+    "notification",
+    "orderUpdates",
+    "userFills",
+    "userFundings",
+    "webData2",
+];
+
+pub struct HyperliquidUserEvents {
+    client: Arc<dyn WebSocketClient>,
+    user: String,
+    sender: broadcast::Sender<UserEvent>,
+}
+
+impl HyperliquidUserEvents {
+    /// Stream for `user` over its own connection to the configured endpoint
+    pub fn new(user: &str) -> Self {
+        Self::with_websocket_client(Arc::new(HyperliquidClient::new()), user)
+    }
+
+    /// Stream for `HYPERLIQUID_ACCOUNT_ADDRESS`
+    pub fn from_env() -> Result<Self, ExecutionError> {
+        let user = std::env::var("HYPERLIQUID_ACCOUNT_ADDRESS").map_err(|_| {
+            ExecutionError::Signing("HYPERLIQUID_ACCOUNT_ADDRESS is not set".to_string())
+        })?;
+        Ok(Self::new(&user))
+    }
+
+    pub fn with_websocket_client(client: Arc<dyn WebSocketClient>, user: &str) -> Self {
+        let (sender, _) = user_event_channel();
+        Self {
+            client,
+            user: user.to_lowercase(),
+            sender,
+        }
+    }
+
+    /// Lowercase account address the stream follows
+    pub fn user(&self) -> &str {
+        &self.user
+    }
+
+    /// Receive every event published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<UserEvent> {
+        self.sender.subscribe()
+    }
+
+    pub fn subscriptions(&self) -> Vec<Subscription> {
+        vec![
+            Subscription::notification(&self.user),
+            Subscription::order_updates(&self.user),
+            Subscription::user_fills(&self.user),
+            Subscription::user_fundings(&self.user),
+            Subscription::web_data2(&self.user),
+        ]
+    }
+
+    /// Connect and publish events until the task is cancelled
+    pub async fn run(&self) {
+        let client = self.client.clone();
+        tokio::spawn(async move {
+            let _ = client.connect().await;
+        });
+
+        loop {
+            while let Some(event) = self.client.receive().await {
+                match event {
+                    ClientEvent::Message(text) => self.publish(&text),
+                    ClientEvent::Connected => {
+                        if let Err(e) = self.send_subscriptions().await {
+                            error!(error = %e, "Failed to subscribe to user events");
+                        }
+                    }
+                    ClientEvent::Disconnected => debug!("User event stream disconnected"),
+                    ClientEvent::Error(e) => error!(error = %e, "User event stream error"),
+                }
+            }
+            sleep(Duration::from_millis(100)).await;
+        }
+    }
+
+    async fn send_subscriptions(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        for subscription in self.subscriptions() {
+            let request = RequestMessage::Subscribe { subscription };
+            self.client
+                .send_text(serde_json::to_string(&request)?)
+                .await?;
+        }
+        info!(user = %self.user, "Subscribed to user events for {}", self.user);
+        Ok(())
+    }
+
+    fn publish(&self, text: &str) {
+        match parse_user_events(text) {
+            Ok(events) => {
+                for event in events {
+                    // No receivers is not an error: nobody is interested yet
+                    let _ = self.sender.send(event);
+                }
+            }
+            Err(e) => warn!(error = %e, "Failed to parse user event"),
+        }
+    }
+}
+
+/// Parse a WebSocket message into user events.
+///
+/// Messages on other channels yield no events. Funding snapshots are dropped
+/// because they repeat past payments; fill snapshots are kept since fills
+/// carry a trade id and can be deduplicated.
+pub fn parse_user_events(text: &str) -> Result<Vec<UserEvent>, ExecutionError> {
+    let value: serde_json::Value = serde_json::from_str(text)
+        .map_err(|e| ExecutionError::InvalidResponse(format!("Invalid JSON: {}", e)))?;
+    let is_user_channel = value
+        .get("channel")
+        .and_then(|c| c.as_str())
+        .is_some_and(|channel| USER_CHANNELS.contains(&channel));
+    if !is_user_channel {
+        return Ok(Vec::new());
+    }
+
+    let message: UserMessage = serde_json::from_value(value)
+        .map_err(|e| ExecutionError::InvalidResponse(format!("Invalid user event: {}", e)))?;
+    match message {
+        UserMessage::Notification(notification) => {
+            info!(notification = %notification.notification, "Exchange notification: {}", notification.notification);
+            Ok(Vec::new())
+        }
+        UserMessage::OrderUpdates(updates) => {
+            let mut events = Vec::new();
+            for update in updates {
+                let Some(state) = exchange_order_state(&update.status) else {
+                    debug!(status = %update.status, oid = update.order.oid, "Ignoring order status {}", update.status);
+                    continue;
+                };
+                events.push(UserEvent::OrderUpdate(ExchangeOrderReport {
+                    order_id: update.order.oid,
+                    client_order_id: update.order.cloid,
+                    symbol: update.order.coin,
+                    state,
+                    original_size: parse_number("origSz", &update.order.orig_sz)?,
+                    remaining_size: parse_number("sz", &update.order.sz)?,
+                    updated_at: timestamp(update.status_timestamp),
+                }));
+            }
+            Ok(events)
+        }
+        UserMessage::UserFills(fills) => fills
+            .fills
+            .into_iter()
+            .map(|fill| {
+                Ok(UserEvent::Fill(ExchangeFill {
+                    trade_id: fill.tid,
+                    order_id: fill.oid,
+                    client_order_id: fill.cloid,
+                    side: parse_side(&fill.side),
+                    size: parse_number("sz", &fill.sz)?,
+                    price: parse_number("px", &fill.px)?,
+                    fee: fill
+                        .fee
+                        .as_deref()
+                        .map(|fee| parse_number("fee", fee))
+                        .transpose()?
+                        .unwrap_or(0.0),
+                    timestamp: timestamp(fill.time),
+                    symbol: fill.coin,
+                }))
+            })
+            .collect(),
+        UserMessage::UserFundings(fundings) => {
+            if fundings.is_snapshot == Some(true) {
+                return Ok(Vec::new());
+            }
+            fundings
+                .fundings
+                .into_iter()
+                .map(|funding| {
+                    Ok(UserEvent::Funding(FundingPayment {
+                        position_size: parse_number("szi", &funding.szi)?,
+                        rate: parse_number("fundingRate", &funding.funding_rate)?,
+                        amount: parse_number("usdc", &funding.usdc)?,
+                        timestamp: timestamp(funding.time),
+                        symbol: funding.coin,
+                    }))
+                })
+                .collect()
+        }
+        UserMessage::WebData2(data) => {
+            let state = data.clearinghouse_state;
+            Ok(vec![UserEvent::Account(AccountSnapshot {
+                positions: account_positions(&state)?,
+                equity: account_equity(&state).ok(),
+                ..AccountSnapshot::default()
+            })])
+        }
+    }
+}
+
+fn timestamp(millis: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp_millis(millis).unwrap_or_else(Utc::now)
+}
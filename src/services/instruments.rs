@@ -0,0 +1,136 @@
+//! Instrument metadata and symbol normalization.
+//!
+//! Symbols arrive in several spellings (`BTC`, `btc`, `BTC-PERP`, `BTC-USD`,
+//! `BTC/USDC`). [`normalize_symbol`] reduces them to the canonical form used
+//! for storage, cache keys and subscriptions: the venue's coin name,
+//! namespaced by venue unless it is a Hyperliquid coin. The
+//! [`InstrumentRegistry`] adds per-instrument metadata and rejects symbols a
+//! venue does not list.
+
+use crate::services::market_data::{split_symbol, venue_symbol, DEFAULT_VENUE};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Quote and contract suffixes stripped from default venue symbols
+const SYMBOL_SUFFIXES: [&str; 5] = ["-PERP", "-USDC", "-USD", "/USDC", "/USD"];
+
+/// Canonical spelling of a base asset. Hyperliquid lists some assets in
+/// thousands with a lowercase `k` prefix (`kPEPE`), which is kept.
+fn canonical_base(base: &str) -> String {
+    let mut chars = base.chars();
+    match (chars.next(), chars.clone().next()) {
+        (Some('k'), Some(next)) if next.is_ascii_uppercase() => {
+            format!("k{}", chars.as_str().to_uppercase())
+        }
+        _ => base.to_uppercase(),
+    }
+}
+
+/// Canonical form of a symbol without consulting instrument metadata.
+///
+/// Bare symbols lose quote and contract suffixes (`BTC-PERP`, `BTC-USD` and
+/// `btc` all become `BTC`); symbols of other venues keep their native name,
+/// upper-cased, behind the lower-cased venue prefix (`binance:BTCUSDT`).
+pub fn normalize_symbol(symbol: &str) -> String {
+    let (venue, native) = split_symbol(symbol.trim());
+    let native = native.trim();
+    if venue != DEFAULT_VENUE {
+        return venue_symbol(&venue, &native.to_uppercase());
+    }
+
+    let mut base = native;
+    while let Some(stripped) = SYMBOL_SUFFIXES.iter().find_map(|suffix| {
+        let split = base.len().checked_sub(suffix.len()).filter(|&i| i > 0)?;
+        base.get(split..)
+            .filter(|tail| tail.eq_ignore_ascii_case(suffix))
+            .map(|_| &base[..split])
+    }) {
+        base = stripped;
+    }
+    canonical_base(base)
+}
+
+/// Tradable instrument of a venue
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct Instrument {
+    /// Canonical symbol (e.g., "BTC", "binance:BTCUSDT")
+    pub symbol: String,
+    /// Venue listing the instrument
+    pub venue: String,
+    /// Symbol as named by the venue
+    pub venue_symbol: String,
+    /// Smallest price increment at the price the metadata was loaded at
+    pub tick_size: f64,
+    /// Decimals allowed in an order size
+    pub size_decimals: u32,
+    /// Maximum leverage allowed by the venue
+    pub max_leverage: u32,
+    /// Whether the instrument is still listed for trading
+    pub listed: bool,
+}
+
+/// Instruments by canonical symbol.
+///
+/// Lookups are case-insensitive and accept any spelling understood by
+/// [`normalize_symbol`]. Only venues whose instruments were loaded are
+/// validated; symbols of other venues are normalized and passed through.
+#[derive(Debug, Clone, Default)]
+pub struct InstrumentRegistry {
+    instruments: HashMap<String, Instrument>,
+    venues: HashSet<String>,
+}
+
+impl InstrumentRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the full instrument list of a venue
+    pub fn with_instruments(mut self, venue: &str, instruments: Vec<Instrument>) -> Self {
+        self.venues.insert(venue.to_lowercase());
+        for instrument in instruments {
+            self.instruments
+                .insert(instrument.symbol.to_lowercase(), instrument);
+        }
+        self
+    }
+
+    /// Registry of every Hyperliquid perp, loaded from `metaAndAssetCtxs`
+    pub async fn load() -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let instruments = crate::services::hyperliquid::HyperliquidRestClient::new()
+            .fetch_instruments()
+            .await?;
+        Ok(Self::new().with_instruments(DEFAULT_VENUE, instruments))
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<&Instrument> {
+        self.instruments
+            .get(&normalize_symbol(symbol).to_lowercase())
+    }
+
+    /// Registered instruments, sorted by symbol
+    pub fn instruments(&self) -> Vec<Instrument> {
+        let mut instruments: Vec<Instrument> = self.instruments.values().cloned().collect();
+        instruments.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        instruments
+    }
+
+    /// Canonical symbol of a listed instrument.
+    ///
+    /// Fails for symbols a loaded venue does not list or has delisted.
+    pub fn normalize(
+        &self,
+        symbol: &str,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let normalized = normalize_symbol(symbol);
+        let (venue, _) = split_symbol(&normalized);
+        if !self.venues.contains(&venue) {
+            return Ok(normalized);
+        }
+        match self.instruments.get(&normalized.to_lowercase()) {
+            Some(instrument) if instrument.listed => Ok(instrument.symbol.clone()),
+            Some(instrument) => Err(format!("Symbol {} is delisted", instrument.symbol).into()),
+            None => Err(format!("Unknown symbol {}", symbol.trim()).into()),
+        }
+    }
+}
@@ -0,0 +1,813 @@
+//! Market data bus over Redis Streams
+//!
+//! Market data providers and the worker publish typed [`MarketEvent`]s to one
+//! stream per [`BusTopic`]. Consumers read a topic through a consumer group:
+//! every group sees every event, and the consumers of a group share them.
+//! Delivery is at-least-once: an event stays pending until its consumer
+//! acknowledges it, and events left pending by a consumer that died are
+//! claimed by another one. Consumers can also replay a topic from an offset,
+//! and report how far their group lags behind the stream.
+
+use crate::config;
+use crate::metrics::Metrics;
+use crate::models::indicators::Candle;
+use crate::models::signal::StoredSignal;
+use crate::services::candle_events::CandleClosed;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use redis::streams::{
+    StreamAutoClaimOptions, StreamAutoClaimReply, StreamId, StreamInfoGroupsReply, StreamMaxlen,
+    StreamRangeReply, StreamReadOptions, StreamReadReply,
+};
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::sync::broadcast;
+use tokio::time::{sleep, Duration};
+use tracing::{debug, info, warn};
+
+/// Events buffered per subscriber before it starts lagging
+pub const MARKET_EVENT_CAPACITY: usize = 4096;
+
+/// Stream entry field holding the JSON-encoded event
+const EVENT_FIELD: &str = "event";
+
+/// Topics of the bus, one Redis stream each
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BusTopic {
+    CandleUpdates,
+    CandleCloses,
+    MidPrices,
+    FundingUpdates,
+    Signals,
+}
+
+impl BusTopic {
+    pub const ALL: [BusTopic; 5] = [
+        BusTopic::CandleUpdates,
+        BusTopic::CandleCloses,
+        BusTopic::MidPrices,
+        BusTopic::FundingUpdates,
+        BusTopic::Signals,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BusTopic::CandleUpdates => "candle_updates",
+            BusTopic::CandleCloses => "candle_closes",
+            BusTopic::MidPrices => "mid_prices",
+            BusTopic::FundingUpdates => "funding_updates",
+            BusTopic::Signals => "signals",
+        }
+    }
+
+    /// Redis stream key of the topic (e.g., "bus:candle_closes")
+    pub fn stream_key(&self) -> String {
+        format!("bus:{}", self.as_str())
+    }
+}
+
+/// Event carried by the bus
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MarketEvent {
+    /// The forming or a closed bar of `symbol` changed
+    CandleUpdate {
+        symbol: String,
+        interval: String,
+        candle: Candle,
+    },
+    CandleClosed(CandleClosed),
+    MidPrice {
+        symbol: String,
+        price: f64,
+        timestamp: DateTime<Utc>,
+    },
+    /// Funding rate currently applying to `symbol`
+    FundingUpdate {
+        symbol: String,
+        funding_rate: f64,
+        timestamp: DateTime<Utc>,
+    },
+    Signal(StoredSignal),
+}
+
+impl MarketEvent {
+    pub fn topic(&self) -> BusTopic {
+        match self {
+            MarketEvent::CandleUpdate { .. } => BusTopic::CandleUpdates,
+            MarketEvent::CandleClosed(_) => BusTopic::CandleCloses,
+            MarketEvent::MidPrice { .. } => BusTopic::MidPrices,
+            MarketEvent::FundingUpdate { .. } => BusTopic::FundingUpdates,
+            MarketEvent::Signal(_) => BusTopic::Signals,
+        }
+    }
+
+    pub fn symbol(&self) -> &str {
+        match self {
+            MarketEvent::CandleUpdate { symbol, .. }
+            | MarketEvent::MidPrice { symbol, .. }
+            | MarketEvent::FundingUpdate { symbol, .. } => symbol,
+            MarketEvent::CandleClosed(closed) => &closed.symbol,
+            MarketEvent::Signal(stored) => &stored.signal.symbol,
+        }
+    }
+}
+
+pub fn market_event_channel() -> (
+    broadcast::Sender<MarketEvent>,
+    broadcast::Receiver<MarketEvent>,
+) {
+    broadcast::channel(MARKET_EVENT_CAPACITY)
+}
+
+/// An event read from the bus, with the stream entry ID to acknowledge
+#[derive(Debug, Clone)]
+pub struct BusMessage {
+    pub id: String,
+    pub event: MarketEvent,
+}
+
+/// Position in a topic
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamOffset {
+    /// The first retained event
+    Beginning,
+    /// Only events published from now on
+    Latest,
+    /// Events published after the given entry ID
+    After(String),
+}
+
+#[async_trait]
+pub trait MarketBus: Send + Sync {
+    /// Append an event to its topic, returning the entry ID
+    async fn publish(
+        &self,
+        event: &MarketEvent,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Create a consumer group reading from `offset`; existing groups keep their position
+    async fn create_group(
+        &self,
+        topic: BusTopic,
+        group: &str,
+        offset: &StreamOffset,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Deliver up to `count` events not yet delivered to the group.
+    ///
+    /// Delivered events stay pending for `consumer` until acknowledged.
+    async fn read_group(
+        &self,
+        topic: BusTopic,
+        group: &str,
+        consumer: &str,
+        count: usize,
+    ) -> Result<Vec<BusMessage>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Take over up to `count` events pending for at least `min_idle`, e.g.
+    /// because the consumer they were delivered to stopped
+    async fn claim_stale(
+        &self,
+        topic: BusTopic,
+        group: &str,
+        consumer: &str,
+        min_idle: Duration,
+        count: usize,
+    ) -> Result<Vec<BusMessage>, Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn ack(
+        &self,
+        topic: BusTopic,
+        group: &str,
+        ids: &[String],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Read up to `count` retained events from `offset`, outside of any group
+    async fn replay(
+        &self,
+        topic: BusTopic,
+        offset: &StreamOffset,
+        count: usize,
+    ) -> Result<Vec<BusMessage>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Events the group has not acknowledged yet: undelivered plus pending
+    async fn lag(
+        &self,
+        topic: BusTopic,
+        group: &str,
+    ) -> Result<u64, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// Publish market events to the bus until the channel closes
+pub async fn forward_to_bus(
+    mut events: broadcast::Receiver<MarketEvent>,
+    bus: Arc<dyn MarketBus>,
+    metrics: Option<Arc<Metrics>>,
+) {
+    loop {
+        match events.recv().await {
+            Ok(event) => match bus.publish(&event).await {
+                Ok(_) => {
+                    if let Some(ref metrics) = metrics {
+                        metrics
+                            .bus_events_published_total
+                            .with_label_values(&[event.topic().as_str()])
+                            .inc();
+                    }
+                }
+                Err(e) => {
+                    warn!(topic = event.topic().as_str(), symbol = %event.symbol(), error = %e, "Failed to publish {} event for {}", event.topic().as_str(), event.symbol())
+                }
+            },
+            Err(broadcast::error::RecvError::Lagged(missed)) => {
+                warn!(
+                    missed = missed,
+                    "Dropped {} market events before the bus", missed
+                );
+            }
+            Err(broadcast::error::RecvError::Closed) => {
+                info!("Market event stream closed");
+                return;
+            }
+        }
+    }
+}
+
+/// Sequence number of an in-memory entry ID ("<seq>-0")
+fn memory_seq(id: &str) -> Option<u64> {
+    id.split('-').next()?.parse().ok()
+}
+
+#[derive(Default)]
+struct MemoryGroup {
+    /// Entries up to this sequence number were delivered
+    delivered: u64,
+    /// Unacknowledged entries by sequence number: (consumer, delivered at)
+    pending: BTreeMap<u64, (String, Instant)>,
+}
+
+#[derive(Default)]
+struct MemoryStream {
+    /// Entries by sequence number, starting at 1
+    entries: BTreeMap<u64, MarketEvent>,
+    last_seq: u64,
+    groups: HashMap<String, MemoryGroup>,
+}
+
+impl MemoryStream {
+    fn message(&self, seq: u64) -> Option<BusMessage> {
+        self.entries.get(&seq).map(|event| BusMessage {
+            id: format!("{}-0", seq),
+            event: event.clone(),
+        })
+    }
+
+    fn start_seq(&self, offset: &StreamOffset) -> u64 {
+        match offset {
+            StreamOffset::Beginning => 0,
+            StreamOffset::Latest => self.last_seq,
+            StreamOffset::After(id) => memory_seq(id).unwrap_or(0),
+        }
+    }
+}
+
+/// In-process bus with the delivery semantics of the Redis one, for tests
+#[derive(Default)]
+pub struct MemoryMarketBus {
+    streams: Mutex<HashMap<BusTopic, MemoryStream>>,
+}
+
+impl MemoryMarketBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+fn unknown_group(topic: BusTopic, group: &str) -> Box<dyn std::error::Error + Send + Sync> {
+    format!("No consumer group {} on {}", group, topic.stream_key()).into()
+}
+
+#[async_trait]
+impl MarketBus for MemoryMarketBus {
+    async fn publish(
+        &self,
+        event: &MarketEvent,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let mut streams = self.streams.lock().unwrap();
+        let stream = streams.entry(event.topic()).or_default();
+        stream.last_seq += 1;
+        stream.entries.insert(stream.last_seq, event.clone());
+        Ok(format!("{}-0", stream.last_seq))
+    }
+
+    async fn create_group(
+        &self,
+        topic: BusTopic,
+        group: &str,
+        offset: &StreamOffset,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut streams = self.streams.lock().unwrap();
+        let stream = streams.entry(topic).or_default();
+        if !stream.groups.contains_key(group) {
+            let delivered = stream.start_seq(offset);
+            stream.groups.insert(
+                group.to_string(),
+                MemoryGroup {
+                    delivered,
+                    pending: BTreeMap::new(),
+                },
+            );
+        }
+        Ok(())
+    }
+
+    async fn read_group(
+        &self,
+        topic: BusTopic,
+        group: &str,
+        consumer: &str,
+        count: usize,
+    ) -> Result<Vec<BusMessage>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut streams = self.streams.lock().unwrap();
+        let stream = streams
+            .get_mut(&topic)
+            .ok_or_else(|| unknown_group(topic, group))?;
+        let delivered = stream
+            .groups
+            .get(group)
+            .ok_or_else(|| unknown_group(topic, group))?
+            .delivered;
+        let seqs: Vec<u64> = stream
+            .entries
+            .range(delivered + 1..)
+            .take(count)
+            .map(|(seq, _)| *seq)
+            .collect();
+        let messages: Vec<BusMessage> =
+            seqs.iter().filter_map(|&seq| stream.message(seq)).collect();
+
+        let state = stream.groups.get_mut(group).unwrap();
+        for seq in seqs {
+            state.delivered = seq;
+            state
+                .pending
+                .insert(seq, (consumer.to_string(), Instant::now()));
+        }
+        Ok(messages)
+    }
+
+    async fn claim_stale(
+        &self,
+        topic: BusTopic,
+        group: &str,
+        consumer: &str,
+        min_idle: Duration,
+        count: usize,
+    ) -> Result<Vec<BusMessage>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut streams = self.streams.lock().unwrap();
+        let stream = streams
+            .get_mut(&topic)
+            .ok_or_else(|| unknown_group(topic, group))?;
+        let state = stream
+            .groups
+            .get_mut(group)
+            .ok_or_else(|| unknown_group(topic, group))?;
+        let stale: Vec<u64> = state
+            .pending
+            .iter()
+            .filter(|(_, (_, delivered_at))| delivered_at.elapsed() >= min_idle)
+            .take(count)
+            .map(|(seq, _)| *seq)
+            .collect();
+        for seq in &stale {
+            state
+                .pending
+                .insert(*seq, (consumer.to_string(), Instant::now()));
+        }
+        Ok(stale
+            .into_iter()
+            .filter_map(|seq| stream.message(seq))
+            .collect())
+    }
+
+    async fn ack(
+        &self,
+        topic: BusTopic,
+        group: &str,
+        ids: &[String],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut streams = self.streams.lock().unwrap();
+        let state = streams
+            .get_mut(&topic)
+            .and_then(|stream| stream.groups.get_mut(group))
+            .ok_or_else(|| unknown_group(topic, group))?;
+        for seq in ids.iter().filter_map(|id| memory_seq(id)) {
+            state.pending.remove(&seq);
+        }
+        Ok(())
+    }
+
+    async fn replay(
+        &self,
+        topic: BusTopic,
+        offset: &StreamOffset,
+        count: usize,
+    ) -> Result<Vec<BusMessage>, Box<dyn std::error::Error + Send + Sync>> {
+        let streams = self.streams.lock().unwrap();
+        let Some(stream) = streams.get(&topic) else {
+            return Ok(Vec::new());
+        };
+        Ok(stream
+            .entries
+            .range(stream.start_seq(offset) + 1..)
+            .take(count)
+            .filter_map(|(seq, _)| stream.message(*seq))
+            .collect())
+    }
+
+    async fn lag(
+        &self,
+        topic: BusTopic,
+        group: &str,
+    ) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+        let streams = self.streams.lock().unwrap();
+        let stream = streams
+            .get(&topic)
+            .ok_or_else(|| unknown_group(topic, group))?;
+        let state = stream
+            .groups
+            .get(group)
+            .ok_or_else(|| unknown_group(topic, group))?;
+        let undelivered = stream.entries.range(state.delivered + 1..).count();
+        Ok((undelivered + state.pending.len()) as u64)
+    }
+}
+
+/// Bus on Redis Streams.
+///
+/// Group reads block the connection for up to the block time while waiting
+/// for events, so each consumer should read through its own bus instance.
+pub struct RedisMarketBus {
+    connection: redis::aio::ConnectionManager,
+    max_len: usize,
+    block: Duration,
+}
+
+impl RedisMarketBus {
+    pub async fn new() -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let redis_url = config::get_redis_url();
+        let client = redis::Client::open(redis_url.as_str())
+            .map_err(|e| format!("Failed to create Redis client: {}", e))?;
+        let connection = client
+            .get_connection_manager()
+            .await
+            .map_err(|e| format!("Failed to connect to Redis: {}", e))?;
+        Ok(Self {
+            connection,
+            max_len: config::get_bus_max_len(),
+            block: Duration::from_secs(1),
+        })
+    }
+
+    /// Approximate number of events kept per topic
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    /// How long a group read waits for new events
+    pub fn with_block(mut self, block: Duration) -> Self {
+        self.block = block;
+        self
+    }
+
+    /// Decode stream entries; malformed ones are acknowledged and skipped so
+    /// they are not redelivered forever
+    async fn decode(
+        &self,
+        topic: BusTopic,
+        group: Option<&str>,
+        ids: Vec<StreamId>,
+    ) -> Result<Vec<BusMessage>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut messages = Vec::with_capacity(ids.len());
+        let mut malformed = Vec::new();
+        for entry in ids {
+            let event = entry
+                .get::<String>(EVENT_FIELD)
+                .and_then(|json| serde_json::from_str::<MarketEvent>(&json).ok());
+            match event {
+                Some(event) => messages.push(BusMessage {
+                    id: entry.id,
+                    event,
+                }),
+                None => {
+                    warn!(topic = topic.as_str(), id = %entry.id, "Skipping malformed bus entry {}", entry.id);
+                    malformed.push(entry.id);
+                }
+            }
+        }
+        if let (Some(group), false) = (group, malformed.is_empty()) {
+            self.ack(topic, group, &malformed).await?;
+        }
+        Ok(messages)
+    }
+}
+
+#[async_trait]
+impl MarketBus for RedisMarketBus {
+    async fn publish(
+        &self,
+        event: &MarketEvent,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let json = serde_json::to_string(event)
+            .map_err(|e| format!("Failed to serialize market event: {}", e))?;
+        let mut conn = self.connection.clone();
+        let id: String = conn
+            .xadd_maxlen(
+                event.topic().stream_key(),
+                StreamMaxlen::Approx(self.max_len),
+                "*",
+                &[(EVENT_FIELD, json)],
+            )
+            .await
+            .map_err(|e| format!("Failed to publish market event: {}", e))?;
+        Ok(id)
+    }
+
+    async fn create_group(
+        &self,
+        topic: BusTopic,
+        group: &str,
+        offset: &StreamOffset,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let start = match offset {
+            StreamOffset::Beginning => "0",
+            StreamOffset::Latest => "$",
+            StreamOffset::After(id) => id.as_str(),
+        };
+        let mut conn = self.connection.clone();
+        match conn
+            .xgroup_create_mkstream::<_, _, _, ()>(topic.stream_key(), group, start)
+            .await
+        {
+            Ok(()) => Ok(()),
+            Err(e) if e.code() == Some("BUSYGROUP") => Ok(()),
+            Err(e) => Err(format!("Failed to create consumer group {}: {}", group, e).into()),
+        }
+    }
+
+    async fn read_group(
+        &self,
+        topic: BusTopic,
+        group: &str,
+        consumer: &str,
+        count: usize,
+    ) -> Result<Vec<BusMessage>, Box<dyn std::error::Error + Send + Sync>> {
+        let options = StreamReadOptions::default()
+            .group(group, consumer)
+            .count(count)
+            .block(self.block.as_millis() as usize);
+        let mut conn = self.connection.clone();
+        let reply: Option<StreamReadReply> = conn
+            .xread_options(&[topic.stream_key()], &[">"], &options)
+            .await
+            .map_err(|e| format!("Failed to read {}: {}", topic.stream_key(), e))?;
+        let ids = reply
+            .into_iter()
+            .flat_map(|reply| reply.keys)
+            .flat_map(|key| key.ids)
+            .collect();
+        self.decode(topic, Some(group), ids).await
+    }
+
+    async fn claim_stale(
+        &self,
+        topic: BusTopic,
+        group: &str,
+        consumer: &str,
+        min_idle: Duration,
+        count: usize,
+    ) -> Result<Vec<BusMessage>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut conn = self.connection.clone();
+        let reply: StreamAutoClaimReply = conn
+            .xautoclaim_options(
+                topic.stream_key(),
+                group,
+                consumer,
+                min_idle.as_millis() as u64,
+                "0-0",
+                StreamAutoClaimOptions::default().count(count),
+            )
+            .await
+            .map_err(|e| format!("Failed to claim stale {} events: {}", topic.stream_key(), e))?;
+        if !reply.deleted_ids.is_empty() {
+            debug!(
+                topic = topic.as_str(),
+                count = reply.deleted_ids.len(),
+                "Dropped {} pending events trimmed from {}",
+                reply.deleted_ids.len(),
+                topic.stream_key()
+            );
+        }
+        self.decode(topic, Some(group), reply.claimed).await
+    }
+
+    async fn ack(
+        &self,
+        topic: BusTopic,
+        group: &str,
+        ids: &[String],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+        let mut conn = self.connection.clone();
+        conn.xack::<_, _, _, ()>(topic.stream_key(), group, ids)
+            .await
+            .map_err(|e| format!("Failed to acknowledge {} events: {}", topic.stream_key(), e))?;
+        Ok(())
+    }
+
+    async fn replay(
+        &self,
+        topic: BusTopic,
+        offset: &StreamOffset,
+        count: usize,
+    ) -> Result<Vec<BusMessage>, Box<dyn std::error::Error + Send + Sync>> {
+        let start = match offset {
+            StreamOffset::Beginning => "-".to_string(),
+            StreamOffset::Latest => return Ok(Vec::new()),
+            // Exclusive range start
+            StreamOffset::After(id) => format!("({}", id),
+        };
+        let mut conn = self.connection.clone();
+        let reply: StreamRangeReply = conn
+            .xrange_count(topic.stream_key(), start, "+", count)
+            .await
+            .map_err(|e| format!("Failed to replay {}: {}", topic.stream_key(), e))?;
+        self.decode(topic, None, reply.ids).await
+    }
+
+    /// Redis cannot count undelivered events once the stream was trimmed past
+    /// the group's position; the lag then covers pending events only.
+    async fn lag(
+        &self,
+        topic: BusTopic,
+        group: &str,
+    ) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+        let mut conn = self.connection.clone();
+        let reply: StreamInfoGroupsReply = conn
+            .xinfo_groups(topic.stream_key())
+            .await
+            .map_err(|e| format!("Failed to read groups of {}: {}", topic.stream_key(), e))?;
+        let info = reply
+            .groups
+            .into_iter()
+            .find(|g| g.name == group)
+            .ok_or_else(|| unknown_group(topic, group))?;
+        Ok((info.lag.unwrap_or(0) + info.pending) as u64)
+    }
+}
+
+/// A consumer of one topic within a consumer group
+pub struct BusConsumer {
+    bus: Arc<dyn MarketBus>,
+    topic: BusTopic,
+    group: String,
+    consumer: String,
+    batch_size: usize,
+    claim_idle: Duration,
+    metrics: Option<Arc<Metrics>>,
+}
+
+impl BusConsumer {
+    pub fn new(bus: Arc<dyn MarketBus>, topic: BusTopic, group: &str, consumer: &str) -> Self {
+        Self {
+            bus,
+            topic,
+            group: group.to_string(),
+            consumer: consumer.to_string(),
+            batch_size: 100,
+            claim_idle: Duration::from_secs(60),
+            metrics: None,
+        }
+    }
+
+    /// Events handled per poll
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// How long an event stays pending before another consumer retries it
+    pub fn with_claim_idle(mut self, claim_idle: Duration) -> Self {
+        self.claim_idle = claim_idle;
+        self
+    }
+
+    /// Report the group's lag on `bus_consumer_lag`
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Join the group, creating it at `offset` if it does not exist yet
+    pub async fn join(
+        &self,
+        offset: &StreamOffset,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.bus.create_group(self.topic, &self.group, offset).await
+    }
+
+    /// Events the group has not acknowledged yet
+    pub async fn lag(&self) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+        self.bus.lag(self.topic, &self.group).await
+    }
+
+    /// Handle one batch: stale events first, then new ones.
+    ///
+    /// Events are acknowledged once handled; an event whose handler fails
+    /// stays pending and is retried after the claim idle time. Returns the
+    /// number of events handled.
+    pub async fn poll<F, Fut>(
+        &self,
+        handler: &F,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>>
+    where
+        F: Fn(BusMessage) -> Fut,
+        Fut: Future<Output = Result<(), Box<dyn std::error::Error + Send + Sync>>>,
+    {
+        let mut messages = self
+            .bus
+            .claim_stale(
+                self.topic,
+                &self.group,
+                &self.consumer,
+                self.claim_idle,
+                self.batch_size,
+            )
+            .await?;
+        if messages.len() < self.batch_size {
+            messages.extend(
+                self.bus
+                    .read_group(
+                        self.topic,
+                        &self.group,
+                        &self.consumer,
+                        self.batch_size - messages.len(),
+                    )
+                    .await?,
+            );
+        }
+
+        let mut handled = Vec::with_capacity(messages.len());
+        for message in messages {
+            let id = message.id.clone();
+            match handler(message).await {
+                Ok(()) => handled.push(id),
+                Err(e) => {
+                    warn!(topic = self.topic.as_str(), group = %self.group, id = %id, error = %e, "Failed to handle bus event {}, leaving it pending", id)
+                }
+            }
+        }
+        self.bus.ack(self.topic, &self.group, &handled).await?;
+
+        if let Some(ref metrics) = self.metrics {
+            let lag = self.lag().await?;
+            metrics
+                .bus_consumer_lag
+                .with_label_values(&[self.topic.as_str(), &self.group])
+                .set(lag as f64);
+        }
+        Ok(handled.len())
+    }
+
+    /// Join the group and handle events until the task is dropped
+    pub async fn run<F, Fut>(&self, offset: StreamOffset, handler: F)
+    where
+        F: Fn(BusMessage) -> Fut,
+        Fut: Future<Output = Result<(), Box<dyn std::error::Error + Send + Sync>>>,
+    {
+        while let Err(e) = self.join(&offset).await {
+            warn!(topic = self.topic.as_str(), group = %self.group, error = %e, "Failed to join consumer group, retrying in 5s");
+            sleep(Duration::from_secs(5)).await;
+        }
+        info!(topic = self.topic.as_str(), group = %self.group, consumer = %self.consumer, "Consuming {} as {}", self.topic.stream_key(), self.consumer);
+        loop {
+            match self.poll(&handler).await {
+                // Redis reads block while idle; the in-memory bus returns at once
+                Ok(0) => sleep(Duration::from_millis(100)).await,
+                Ok(_) => {}
+                Err(e) => {
+                    warn!(topic = self.topic.as_str(), group = %self.group, error = %e, "Failed to poll bus, retrying in 5s");
+                    sleep(Duration::from_secs(5)).await;
+                }
+            }
+        }
+    }
+}
@@ -1,6 +1,33 @@
-//! Market data provider interface for future data source integration.
+//! Market data provider interface and the registry routing symbols to venues.
+//!
+//! Symbols may be namespaced by venue as `venue:SYMBOL` (for example
+//! `binance:BTCUSDT`); bare symbols belong to [`DEFAULT_VENUE`]. Each venue's
+//! provider only sees its native symbol.
 
 use crate::models::indicators::Candle;
+use crate::services::instruments::normalize_symbol;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Venue of symbols without a venue prefix
+pub const DEFAULT_VENUE: &str = "hyperliquid";
+
+/// Split a possibly namespaced symbol into its venue and venue-native symbol
+pub fn split_symbol(symbol: &str) -> (String, &str) {
+    match symbol.split_once(':') {
+        Some((venue, native)) => (venue.to_lowercase(), native),
+        None => (DEFAULT_VENUE.to_string(), symbol),
+    }
+}
+
+/// Namespaced form of a venue-native symbol; default venue symbols stay bare
+pub fn venue_symbol(venue: &str, symbol: &str) -> String {
+    if venue.eq_ignore_ascii_case(DEFAULT_VENUE) {
+        symbol.to_string()
+    } else {
+        format!("{}:{}", venue.to_lowercase(), symbol)
+    }
+}
 
 #[async_trait::async_trait]
 pub trait MarketDataProvider: Send + Sync {
@@ -19,6 +46,120 @@ pub trait MarketDataProvider: Send + Sync {
 
     async fn subscribe(&self, symbol: &str)
         -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Stop streaming a symbol; providers without live subscriptions have
+    /// nothing to stop
+    async fn unsubscribe(
+        &self,
+        _symbol: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        Ok(())
+    }
+
+    /// Whether the provider's live connection is up; providers without one
+    /// are always connected
+    async fn is_connected(&self) -> bool {
+        true
+    }
+}
+
+/// Market data providers by venue.
+///
+/// The registry is itself a [`MarketDataProvider`]: each call goes to the
+/// provider of the symbol's venue with the venue prefix removed. Symbols are
+/// normalized first, so `BTC-PERP` and `BTC` reach the same subscription.
+#[derive(Clone)]
+pub struct MarketDataRegistry {
+    providers: HashMap<String, Arc<dyn MarketDataProvider>>,
+}
+
+impl MarketDataRegistry {
+    /// Registry serving the default venue from `provider`
+    pub fn new(provider: Arc<dyn MarketDataProvider>) -> Self {
+        Self {
+            providers: HashMap::from([(DEFAULT_VENUE.to_string(), provider)]),
+        }
+    }
+
+    pub fn with_provider(mut self, venue: &str, provider: Arc<dyn MarketDataProvider>) -> Self {
+        self.providers.insert(venue.to_lowercase(), provider);
+        self
+    }
+
+    pub fn provider(&self, venue: &str) -> Option<Arc<dyn MarketDataProvider>> {
+        self.providers.get(&venue.to_lowercase()).cloned()
+    }
+
+    /// Registered venues, sorted
+    pub fn venues(&self) -> Vec<String> {
+        let mut venues: Vec<String> = self.providers.keys().cloned().collect();
+        venues.sort();
+        venues
+    }
+
+    /// Venues whose live connection is down
+    pub async fn disconnected_venues(&self) -> Vec<String> {
+        let mut disconnected = Vec::new();
+        for venue in self.venues() {
+            if !self.providers[&venue].is_connected().await {
+                disconnected.push(venue);
+            }
+        }
+        disconnected
+    }
+
+    /// Provider registered for `venue`, or an error naming it
+    fn resolve(
+        &self,
+        venue: &str,
+    ) -> Result<Arc<dyn MarketDataProvider>, Box<dyn std::error::Error + Send + Sync>> {
+        self.provider(venue)
+            .ok_or_else(|| format!("No market data provider registered for venue {}", venue).into())
+    }
+}
+
+#[async_trait::async_trait]
+impl MarketDataProvider for MarketDataRegistry {
+    async fn get_candles(
+        &self,
+        symbol: &str,
+        limit: usize,
+    ) -> Result<Vec<Candle>, Box<dyn std::error::Error + Send + Sync>> {
+        let symbol = normalize_symbol(symbol);
+        let (venue, native) = split_symbol(&symbol);
+        self.resolve(&venue)?.get_candles(native, limit).await
+    }
+
+    async fn get_latest_price(
+        &self,
+        symbol: &str,
+    ) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
+        let symbol = normalize_symbol(symbol);
+        let (venue, native) = split_symbol(&symbol);
+        self.resolve(&venue)?.get_latest_price(native).await
+    }
+
+    async fn subscribe(
+        &self,
+        symbol: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let symbol = normalize_symbol(symbol);
+        let (venue, native) = split_symbol(&symbol);
+        self.resolve(&venue)?.subscribe(native).await
+    }
+
+    async fn unsubscribe(
+        &self,
+        symbol: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let symbol = normalize_symbol(symbol);
+        let (venue, native) = split_symbol(&symbol);
+        self.resolve(&venue)?.unsubscribe(native).await
+    }
+
+    async fn is_connected(&self) -> bool {
+        self.disconnected_venues().await.is_empty()
+    }
 }
 
 pub struct PlaceholderMarketDataProvider;
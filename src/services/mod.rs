@@ -1,7 +1,14 @@
 //! Long-running services (data feeds, persistence facades).
 
+pub mod binance;
+pub mod candle_events;
+pub mod candle_gaps;
+pub mod candle_io;
 pub mod hyperliquid;
+pub mod instruments;
+pub mod market_bus;
 pub mod market_data;
+pub mod subscription_sync;
 pub mod websocket;
 pub mod persistence {
     //! Database service placeholder.
@@ -0,0 +1,440 @@
+//! Runtime market data subscriptions
+//!
+//! The websocket-service streams the symbols of the strategy table, plus the
+//! `SYMBOLS` it was started with, and follows changes without a restart:
+//! [`SubscriptionSync`] re-reads the strategies periodically and as soon as a
+//! [`SubscriptionCommand`] arrives on the Redis control channel, subscribing
+//! new symbols and unsubscribing symbols no strategy uses anymore. The API
+//! publishes `reload` after strategy changes and can subscribe or unsubscribe
+//! symbols by hand. The active set is written to a [`SubscriptionStore`] for
+//! the admin API.
+
+use crate::cache::RedisCache;
+use crate::db::QuestDatabase;
+use crate::metrics::Metrics;
+use crate::services::candle_gaps::CandleGapAuditor;
+use crate::services::instruments::normalize_symbol;
+use crate::services::market_data::{split_symbol, MarketDataProvider};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::{Mutex, RwLock};
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, Duration};
+use tracing::{debug, error, info, warn};
+
+/// Redis pub/sub channel carrying JSON-encoded [`SubscriptionCommand`]s
+pub const SUBSCRIPTION_CONTROL_CHANNEL: &str = "events:subscriptions";
+
+/// Instruction to the services following the strategy table
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum SubscriptionCommand {
+    /// Strategies changed; re-read the strategy table
+    Reload,
+    /// Stream a symbol even if no strategy uses it
+    Subscribe { symbol: String },
+    /// Stop streaming a symbol subscribed with `subscribe`
+    Unsubscribe { symbol: String },
+}
+
+/// Why a symbol is streamed
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, utoipa::ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum SubscriptionSource {
+    /// Listed in `SYMBOLS`; kept for the lifetime of the service
+    Static,
+    /// Used by a strategy
+    Strategy,
+    /// Subscribed through the control channel
+    Manual,
+}
+
+/// A symbol the websocket-service streams
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ActiveSubscription {
+    /// Symbol as stored (e.g., "BTC", "binance:BTCUSDT")
+    pub symbol: String,
+    pub sources: Vec<SubscriptionSource>,
+    pub since: DateTime<Utc>,
+}
+
+/// Shares the active subscriptions and the control commands between services
+#[async_trait]
+pub trait SubscriptionStore: Send + Sync {
+    async fn save_subscriptions(
+        &self,
+        active: &[ActiveSubscription],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Subscriptions last saved by the websocket-service
+    async fn load_subscriptions(
+        &self,
+    ) -> Result<Vec<ActiveSubscription>, Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn send_subscription_command(
+        &self,
+        command: &SubscriptionCommand,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// Process-local store, for tests and single-process setups
+#[derive(Default)]
+pub struct MemorySubscriptionStore {
+    active: RwLock<Vec<ActiveSubscription>>,
+    commands: RwLock<Vec<SubscriptionCommand>>,
+}
+
+impl MemorySubscriptionStore {
+    /// Commands sent so far, oldest first
+    pub async fn commands(&self) -> Vec<SubscriptionCommand> {
+        self.commands.read().await.clone()
+    }
+}
+
+#[async_trait]
+impl SubscriptionStore for MemorySubscriptionStore {
+    async fn save_subscriptions(
+        &self,
+        active: &[ActiveSubscription],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        *self.active.write().await = active.to_vec();
+        Ok(())
+    }
+
+    async fn load_subscriptions(
+        &self,
+    ) -> Result<Vec<ActiveSubscription>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.active.read().await.clone())
+    }
+
+    async fn send_subscription_command(
+        &self,
+        command: &SubscriptionCommand,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.commands.write().await.push(command.clone());
+        Ok(())
+    }
+}
+
+/// Symbols the strategies need market data for
+#[async_trait]
+pub trait SymbolSource: Send + Sync {
+    /// Canonical symbols, sorted and without duplicates
+    async fn symbols(&self) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+#[async_trait]
+impl SymbolSource for QuestDatabase {
+    async fn symbols(&self) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+        let strategies = self.get_strategies(None).await?;
+        let symbols: BTreeSet<String> = strategies
+            .iter()
+            .map(|s| normalize_symbol(&s.symbol))
+            .collect();
+        Ok(symbols.into_iter().collect())
+    }
+}
+
+/// Spawn a task running `on_command` for every command published on the
+/// control channel, resubscribing if the subscription drops
+pub fn follow_commands<F, Fut>(cache: Arc<RedisCache>, on_command: F) -> JoinHandle<()>
+where
+    F: Fn(SubscriptionCommand) -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send,
+{
+    tokio::spawn(async move {
+        loop {
+            match cache.subscribe_subscription_commands().await {
+                Ok(commands) => {
+                    let mut commands = Box::pin(commands);
+                    while let Some(command) = commands.next().await {
+                        on_command(command).await;
+                    }
+                    warn!("Subscription command stream ended, resubscribing");
+                }
+                Err(e) => {
+                    error!(error = %e, "Failed to subscribe to subscription commands");
+                }
+            }
+            sleep(Duration::from_secs(5)).await;
+        }
+    })
+}
+
+/// Symbols subscribed and unsubscribed by one sync
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncReport {
+    pub subscribed: Vec<String>,
+    pub unsubscribed: Vec<String>,
+    /// Symbols whose (un)subscription failed; retried on the next sync
+    pub failed: Vec<String>,
+}
+
+/// Gap auditor and the intervals it audits per venue
+type AuditedIntervals = (Arc<CandleGapAuditor>, HashMap<String, Vec<String>>);
+
+#[derive(Default)]
+struct SyncState {
+    strategy: BTreeSet<String>,
+    manual: BTreeSet<String>,
+    /// Subscribed symbols and when they were subscribed
+    active: BTreeMap<String, DateTime<Utc>>,
+}
+
+/// Keeps the provider's subscriptions in line with the static symbols, the
+/// strategy table and the manual subscriptions
+pub struct SubscriptionSync {
+    provider: Arc<dyn MarketDataProvider>,
+    static_symbols: BTreeSet<String>,
+    source: Option<Arc<dyn SymbolSource>>,
+    store: Option<Arc<dyn SubscriptionStore>>,
+    auditor: Option<AuditedIntervals>,
+    metrics: Option<Arc<Metrics>>,
+    state: Mutex<SyncState>,
+    handles: std::sync::Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl SubscriptionSync {
+    pub fn new(provider: Arc<dyn MarketDataProvider>) -> Self {
+        Self {
+            provider,
+            static_symbols: BTreeSet::new(),
+            source: None,
+            store: None,
+            auditor: None,
+            metrics: None,
+            state: Mutex::new(SyncState::default()),
+            handles: std::sync::Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Symbols streamed whatever the strategies use
+    pub fn with_static_symbols(mut self, symbols: &[String]) -> Self {
+        self.static_symbols = symbols.iter().map(|s| normalize_symbol(s)).collect();
+        self
+    }
+
+    /// Follow the symbols of `source`, usually the strategy table
+    pub fn with_source(mut self, source: Arc<dyn SymbolSource>) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    /// Save the active subscriptions after every sync
+    pub fn with_store(mut self, store: Arc<dyn SubscriptionStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Watch subscribed symbols in the `intervals` of their venue
+    pub fn with_auditor(
+        mut self,
+        auditor: Arc<CandleGapAuditor>,
+        intervals: HashMap<String, Vec<String>>,
+    ) -> Self {
+        self.auditor = Some((auditor, intervals));
+        self
+    }
+
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Subscribed symbols, sorted
+    pub async fn active(&self) -> Vec<ActiveSubscription> {
+        let state = self.state.lock().await;
+        self.active_subscriptions(&state)
+    }
+
+    /// Re-read the symbol source, then subscribe and unsubscribe accordingly.
+    /// Without a source only the static and manual symbols are streamed; if
+    /// the source fails, its previous symbols are kept and the error returned.
+    pub async fn reload(&self) -> Result<SyncReport, Box<dyn std::error::Error + Send + Sync>> {
+        let symbols = match self.source {
+            Some(ref source) => Some(source.symbols().await),
+            None => None,
+        };
+        let mut state = self.state.lock().await;
+        match symbols {
+            Some(Ok(symbols)) => {
+                state.strategy = symbols.iter().map(|s| normalize_symbol(s)).collect();
+            }
+            Some(Err(e)) => {
+                self.sync(&mut state).await;
+                return Err(e);
+            }
+            None => {}
+        }
+        Ok(self.sync(&mut state).await)
+    }
+
+    /// Apply a control command
+    pub async fn handle(
+        &self,
+        command: &SubscriptionCommand,
+    ) -> Result<SyncReport, Box<dyn std::error::Error + Send + Sync>> {
+        match command {
+            SubscriptionCommand::Reload => self.reload().await,
+            SubscriptionCommand::Subscribe { symbol } => {
+                let mut state = self.state.lock().await;
+                state.manual.insert(normalize_symbol(symbol));
+                Ok(self.sync(&mut state).await)
+            }
+            SubscriptionCommand::Unsubscribe { symbol } => {
+                let mut state = self.state.lock().await;
+                state.manual.remove(&normalize_symbol(symbol));
+                Ok(self.sync(&mut state).await)
+            }
+        }
+    }
+
+    /// Reload every `period`
+    pub fn start(self: &Arc<Self>, period: Duration) {
+        let sync = self.clone();
+        let handle = tokio::spawn(async move {
+            loop {
+                sleep(period).await;
+                if let Err(e) = sync.reload().await {
+                    warn!(error = %e, "Failed to reload subscribed symbols");
+                }
+            }
+        });
+        self.handles.lock().unwrap().push(handle);
+    }
+
+    /// Apply the commands published on the control channel
+    pub fn follow(self: &Arc<Self>, cache: Arc<RedisCache>) {
+        let sync = self.clone();
+        let handle = follow_commands(cache, move |command| {
+            let sync = sync.clone();
+            async move {
+                info!(command = ?command, "Received subscription command");
+                if let Err(e) = sync.handle(&command).await {
+                    warn!(command = ?command, error = %e, "Failed to apply subscription command");
+                }
+            }
+        });
+        self.handles.lock().unwrap().push(handle);
+    }
+
+    pub fn stop(&self) {
+        for handle in self.handles.lock().unwrap().drain(..) {
+            handle.abort();
+        }
+    }
+
+    async fn sync(&self, state: &mut SyncState) -> SyncReport {
+        let wanted: BTreeSet<String> = self
+            .static_symbols
+            .iter()
+            .chain(&state.strategy)
+            .chain(&state.manual)
+            .cloned()
+            .collect();
+        let mut report = SyncReport::default();
+
+        let added: Vec<String> = wanted
+            .iter()
+            .filter(|s| !state.active.contains_key(*s))
+            .cloned()
+            .collect();
+        for symbol in added {
+            match self.provider.subscribe(&symbol).await {
+                Ok(()) => {
+                    info!(symbol = %symbol, "Subscribed to {}", symbol);
+                    if let Some((ref auditor, ref intervals)) = self.auditor {
+                        if let Some(intervals) = intervals.get(&split_symbol(&symbol).0) {
+                            auditor.watch(&symbol, intervals);
+                        }
+                    }
+                    state.active.insert(symbol.clone(), Utc::now());
+                    report.subscribed.push(symbol);
+                }
+                Err(e) => {
+                    error!(symbol = %symbol, error = %e, "Failed to subscribe to {}", symbol);
+                    report.failed.push(symbol);
+                }
+            }
+        }
+
+        let removed: Vec<String> = state
+            .active
+            .keys()
+            .filter(|s| !wanted.contains(*s))
+            .cloned()
+            .collect();
+        for symbol in removed {
+            match self.provider.unsubscribe(&symbol).await {
+                Ok(()) => {
+                    info!(symbol = %symbol, "Unsubscribed from {}", symbol);
+                    if let Some((ref auditor, _)) = self.auditor {
+                        auditor.unwatch(&symbol);
+                    }
+                    state.active.remove(&symbol);
+                    report.unsubscribed.push(symbol);
+                }
+                Err(e) => {
+                    error!(symbol = %symbol, error = %e, "Failed to unsubscribe from {}", symbol);
+                    report.failed.push(symbol);
+                }
+            }
+        }
+
+        if let Some(ref metrics) = self.metrics {
+            metrics
+                .market_data_subscriptions
+                .set(state.active.len() as f64);
+        }
+        if let Some(ref store) = self.store {
+            if let Err(e) = store
+                .save_subscriptions(&self.active_subscriptions(state))
+                .await
+            {
+                warn!(error = %e, "Failed to save active subscriptions");
+            }
+        }
+        debug!(
+            active = state.active.len(),
+            subscribed = report.subscribed.len(),
+            unsubscribed = report.unsubscribed.len(),
+            "Subscriptions synced"
+        );
+        report
+    }
+
+    fn active_subscriptions(&self, state: &SyncState) -> Vec<ActiveSubscription> {
+        state
+            .active
+            .iter()
+            .map(|(symbol, since)| {
+                let sources = [
+                    (
+                        SubscriptionSource::Static,
+                        self.static_symbols.contains(symbol),
+                    ),
+                    (
+                        SubscriptionSource::Strategy,
+                        state.strategy.contains(symbol),
+                    ),
+                    (SubscriptionSource::Manual, state.manual.contains(symbol)),
+                ]
+                .into_iter()
+                .filter_map(|(source, applies)| applies.then_some(source))
+                .collect();
+                ActiveSubscription {
+                    symbol: symbol.clone(),
+                    sources,
+                    since: *since,
+                }
+            })
+            .collect()
+    }
+}
@@ -1,6 +1,7 @@
 //! WebSocket service for maintaining long-lived connection to market data provider
 
 use crate::services::hyperliquid::HyperliquidMarketDataProvider;
+use crate::services::market_data::{MarketDataProvider, MarketDataRegistry};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tokio::time::Duration;
@@ -13,6 +14,8 @@ use tracing::{info, warn};
 /// Jobs read from the stored data and never create new connections.
 pub struct WebSocketService {
     provider: Arc<HyperliquidMarketDataProvider>,
+    /// Hyperliquid plus any other venues; subscriptions are routed through it
+    registry: MarketDataRegistry,
     handle: Arc<RwLock<Option<tokio::task::JoinHandle<()>>>>,
 }
 
@@ -24,18 +27,27 @@ impl WebSocketService {
     pub fn new(provider: HyperliquidMarketDataProvider) -> Self {
         // The provider's spawn_background_tasks() is called in with_clients(),
         // so the connection is already being maintained
+        let provider = Arc::new(provider);
         Self {
-            provider: Arc::new(provider),
+            registry: MarketDataRegistry::new(provider.clone()),
+            provider,
             handle: Arc::new(RwLock::new(None)),
         }
     }
 
+    /// Serve symbols namespaced with `venue` from another provider
+    pub fn with_venue(mut self, venue: &str, provider: Arc<dyn MarketDataProvider>) -> Self {
+        self.registry = self.registry.with_provider(venue, provider);
+        self
+    }
+
     /// Start the WebSocket service monitoring
     /// 
     /// This monitors the connection health. The actual connection
     /// is maintained by the provider's background tasks.
     pub async fn start(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let provider = self.provider.clone();
+        let registry = self.registry.clone();
         let handle_arc = self.handle.clone();
 
         let handle = tokio::spawn(async move {
@@ -50,9 +62,8 @@ impl WebSocketService {
             // Monitor connection health periodically
             loop {
                 tokio::time::sleep(Duration::from_secs(60)).await;
-                let is_connected = client.is_connected().await;
-                if !is_connected {
-                    warn!("WebSocket service: connection lost, background tasks will reconnect");
+                for venue in registry.disconnected_venues().await {
+                    warn!(venue = %venue, "WebSocket service: {} connection lost, background tasks will reconnect", venue);
                 }
             }
         });
@@ -79,10 +90,19 @@ impl WebSocketService {
             self.provider.clone()
         }
 
-        /// Subscribe to a symbol
+        /// Providers of all venues served by this service
+        pub fn registry(&self) -> &MarketDataRegistry {
+            &self.registry
+        }
+
+        /// Subscribe to a symbol, namespaced by venue unless it is a Hyperliquid coin
         pub async fn subscribe(&self, symbol: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-            // Arc<T> implements Deref<Target = T>, so we can call methods directly
-            self.provider.subscribe(symbol).await
+            self.registry.subscribe(symbol).await
+        }
+
+        /// Stop streaming a symbol subscribed with [`Self::subscribe`]
+        pub async fn unsubscribe(&self, symbol: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+            self.registry.unsubscribe(symbol).await
         }
 
     /// Check if the service is running
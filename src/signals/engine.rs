@@ -3,7 +3,7 @@
 use crate::models::indicators::{Candle, IndicatorSet};
 use crate::models::signal::SignalOutput;
 use crate::models::strategy::Strategy;
-use crate::strategies::evaluator::StrategyEvaluator;
+use crate::strategies::evaluator::{IndicatorSettings, StrategyEvaluator};
 
 pub const MIN_CANDLES: usize = 50;
 
@@ -13,7 +13,24 @@ impl SignalEngine {
     /// Evaluate signal from candles using a strategy.
     /// This replaces the hardcoded evaluation logic.
     pub fn evaluate(candles: &[Candle], strategy: &Strategy) -> Option<SignalOutput> {
-        StrategyEvaluator::evaluate_strategy(strategy, candles)
+        StrategyEvaluator::evaluate_strategy(strategy, Self::bars(candles, strategy))
+    }
+
+    /// Candles a strategy needs before its first evaluation
+    pub fn warmup(strategy: &Strategy) -> usize {
+        IndicatorSettings::from_rules(&strategy.config.rules)
+            .history()
+            .max(MIN_CANDLES)
+    }
+
+    /// Candles a strategy is evaluated on: the still-forming bar is dropped
+    /// unless the strategy opts into intrabar evaluation
+    pub fn bars<'a>(candles: &'a [Candle], strategy: &Strategy) -> &'a [Candle] {
+        if strategy.config.intrabar {
+            return candles;
+        }
+        let forming = candles.iter().rev().take_while(|c| !c.is_closed).count();
+        &candles[..candles.len() - forming]
     }
 
     /// Evaluate signal and return full indicator set (for API responses/debugging)
@@ -22,6 +39,7 @@ impl SignalEngine {
         strategy: &Strategy,
     ) -> Option<(SignalOutput, IndicatorSet)> {
         let signal = Self::evaluate(candles, strategy)?;
+        let candles = Self::bars(candles, strategy);
         let mut indicator_set = IndicatorSet::new(strategy.symbol.clone(), signal.price);
 
         if let Some(funding_rate) = candles.last().and_then(|c| c.funding_rate) {
@@ -13,6 +13,7 @@ use crate::models::strategy::{
 };
 use crate::signals::decision::StopLossTakeProfit;
 use chrono::Utc;
+use serde_json::Value;
 use std::collections::VecDeque;
 
 const MIN_CANDLES: usize = 50;
@@ -59,6 +60,13 @@ pub struct IndicatorValues {
     
     // Open Interest
     pub oi_signal: Option<open_interest::OpenInterestSignal>,
+    pub open_interest_value: Option<f64>,
+
+    // Asset context at the close of the latest candle
+    pub mark_price: Option<f64>,
+    pub oracle_price: Option<f64>,
+    pub premium_value: Option<f64>,
+    pub day_notional_volume: Option<f64>,
     
     // Funding Rate
     pub funding_signal: Option<funding_rate::FundingSignal>,
@@ -91,6 +99,11 @@ impl IndicatorValues {
             obv_signal: None,
             volume_profile_signal: None,
             oi_signal: None,
+            open_interest_value: None,
+            mark_price: None,
+            oracle_price: None,
+            premium_value: None,
+            day_notional_volume: None,
             funding_signal: None,
             funding_rate_value: None,
             current_price,
@@ -98,6 +111,132 @@ impl IndicatorValues {
     }
 }
 
+/// Indicator periods and multipliers used when computing indicator values.
+///
+/// Defaults match the documented indicator parameters. Overrides are read from
+/// `Condition::indicator_params`; the first condition that sets a parameter wins.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndicatorSettings {
+    pub rsi_period: usize,
+    pub macd_fast_period: usize,
+    pub macd_slow_period: usize,
+    pub macd_signal_period: usize,
+    pub ema_fast_period: usize,
+    pub ema_slow_period: usize,
+    pub supertrend_period: usize,
+    pub supertrend_multiplier: f64,
+    pub bollinger_period: usize,
+    pub bollinger_std_dev: f64,
+    pub atr_period: usize,
+    pub funding_lookback: usize,
+}
+
+impl Default for IndicatorSettings {
+    fn default() -> Self {
+        Self {
+            rsi_period: 14,
+            macd_fast_period: 12,
+            macd_slow_period: 26,
+            macd_signal_period: 9,
+            ema_fast_period: 20,
+            ema_slow_period: 50,
+            supertrend_period: 10,
+            supertrend_multiplier: 3.0,
+            bollinger_period: 20,
+            bollinger_std_dev: 2.0,
+            atr_period: 14,
+            funding_lookback: 24,
+        }
+    }
+}
+
+impl IndicatorSettings {
+    /// Resolve settings from the indicator parameters of every condition in `rules`
+    pub fn from_rules(rules: &[Rule]) -> Self {
+        let mut settings = Self::default();
+        let mut seen: Vec<(IndicatorType, &str)> = Vec::new();
+        Self::collect(rules, &mut settings, &mut seen);
+        settings
+    }
+
+    fn collect<'a>(
+        rules: &'a [Rule],
+        settings: &mut Self,
+        seen: &mut Vec<(IndicatorType, &'a str)>,
+    ) {
+        for rule in rules {
+            if let Some(ref condition) = rule.condition {
+                for (name, value) in &condition.indicator_params {
+                    let key = (condition.indicator, name.as_str());
+                    if seen.contains(&key) {
+                        continue;
+                    }
+                    if settings.apply(condition.indicator, name, value) {
+                        seen.push(key);
+                    }
+                }
+            }
+            if let Some(ref children) = rule.children {
+                Self::collect(children, settings, seen);
+            }
+        }
+    }
+
+    /// Apply a single parameter, returning false if it is unknown or invalid
+    fn apply(&mut self, indicator: IndicatorType, name: &str, value: &Value) -> bool {
+        let Some(number) = value.as_f64().filter(|v| v.is_finite() && *v > 0.0) else {
+            return false;
+        };
+        let period = number.round().max(1.0) as usize;
+
+        match (indicator, name) {
+            (IndicatorType::RSI, "period") => self.rsi_period = period,
+            (IndicatorType::MACD, "fast_period") => self.macd_fast_period = period,
+            (IndicatorType::MACD, "slow_period") => self.macd_slow_period = period,
+            (IndicatorType::MACD, "signal_period") => self.macd_signal_period = period,
+            (IndicatorType::EMA, "fast_period") => self.ema_fast_period = period,
+            (IndicatorType::EMA, "slow_period") => self.ema_slow_period = period,
+            (IndicatorType::SuperTrend, "period") => self.supertrend_period = period,
+            (IndicatorType::SuperTrend, "multiplier") => self.supertrend_multiplier = number,
+            (IndicatorType::Bollinger, "period") => self.bollinger_period = period,
+            (IndicatorType::Bollinger, "std_dev") => self.bollinger_std_dev = number,
+            (IndicatorType::ATR, "period") => self.atr_period = period,
+            (IndicatorType::FundingRate, "lookback") => self.funding_lookback = period,
+            _ => return false,
+        }
+        true
+    }
+
+    /// Candles needed before every indicator, including the ATR average used
+    /// for stops, has a value
+    pub fn history(&self) -> usize {
+        [
+            self.rsi_period + 1,
+            self.macd_slow_period + self.macd_signal_period,
+            self.ema_slow_period,
+            self.supertrend_period,
+            self.bollinger_period,
+            self.atr_period * 2,
+        ]
+        .into_iter()
+        .max()
+        .unwrap_or(0)
+    }
+
+    /// Names of the tunable parameters supported for an indicator
+    pub fn parameter_names(indicator: IndicatorType) -> &'static [&'static str] {
+        match indicator {
+            IndicatorType::RSI | IndicatorType::ATR => &["period"],
+            IndicatorType::MACD => &["fast_period", "slow_period", "signal_period"],
+            IndicatorType::EMA => &["fast_period", "slow_period"],
+            IndicatorType::SuperTrend => &["period", "multiplier"],
+            IndicatorType::Bollinger => &["period", "std_dev"],
+            IndicatorType::FundingRate => &["lookback"],
+            _ => &[],
+        }
+    }
+}
+
 pub struct StrategyEvaluator;
 
 impl StrategyEvaluator {
@@ -111,7 +250,8 @@ impl StrategyEvaluator {
         }
 
         let current_price = candles.last()?.close;
-        let indicator_values = Self::compute_indicators(candles, current_price);
+        let settings = IndicatorSettings::from_rules(&strategy.config.rules);
+        let indicator_values = Self::compute_indicators(candles, current_price, &settings);
 
         // Evaluate all rules
         let mut rule_results = Vec::new();
@@ -175,21 +315,34 @@ impl StrategyEvaluator {
     }
 
     /// Compute all indicator values from candles
-    fn compute_indicators(candles: &[Candle], current_price: f64) -> IndicatorValues {
+    fn compute_indicators(
+        candles: &[Candle],
+        current_price: f64,
+        settings: &IndicatorSettings,
+    ) -> IndicatorValues {
         let mut values = IndicatorValues::new(current_price);
 
-        // Initialize indicators with default parameters
-        let mut ema_cross = ema::EMACrossover::new(20, 50);
-        let mut supertrend = supertrend::SuperTrend::new(10, 3.0);
-        let mut rsi = rsi::RSI::new(14);
-        let mut macd = macd::MACD::new(12, 26, 9);
-        let mut atr = atr::ATR::new(14);
-        let mut bollinger = bollinger::BollingerBands::new(20, 2.0);
+        // Initialize indicators with the resolved parameters
+        let mut ema_cross =
+            ema::EMACrossover::new(settings.ema_fast_period, settings.ema_slow_period);
+        let mut supertrend = supertrend::SuperTrend::new(
+            settings.supertrend_period,
+            settings.supertrend_multiplier,
+        );
+        let mut rsi = rsi::RSI::new(settings.rsi_period);
+        let mut macd = macd::MACD::new(
+            settings.macd_fast_period,
+            settings.macd_slow_period,
+            settings.macd_signal_period,
+        );
+        let mut atr = atr::ATR::new(settings.atr_period);
+        let mut bollinger =
+            bollinger::BollingerBands::new(settings.bollinger_period, settings.bollinger_std_dev);
         let mut obv = obv::OBV::new();
         let mut volume_profile =
             volume_profile::VolumeProfile::new(VOLUME_PROFILE_TICK, VOLUME_PROFILE_LOOKBACK);
         let mut open_interest = open_interest::OpenInterest::new();
-        let mut funding_rate = funding_rate::FundingRate::new(24);
+        let mut funding_rate = funding_rate::FundingRate::new(settings.funding_lookback);
         let mut atr_history: VecDeque<f64> = VecDeque::new();
         let mut prev_close: Option<f64> = None;
 
@@ -220,7 +373,7 @@ impl StrategyEvaluator {
 
             let atr_value = atr.update(candle.high, candle.low, candle.close);
             atr_history.push_back(atr_value);
-            if atr_history.len() > 14 {
+            if atr_history.len() > settings.atr_period {
                 atr_history.pop_front();
             }
             values.atr_value = Some(atr_value);
@@ -240,7 +393,12 @@ impl StrategyEvaluator {
 
             if let Some(oi) = candle.open_interest {
                 values.oi_signal = Some(open_interest.update(oi, candle.close));
+                values.open_interest_value = Some(oi);
             }
+            values.mark_price = candle.mark_price.or(values.mark_price);
+            values.oracle_price = candle.oracle_price.or(values.oracle_price);
+            values.premium_value = candle.premium.or(values.premium_value);
+            values.day_notional_volume = candle.day_notional_volume.or(values.day_notional_volume);
 
             if let Some(funding) = candle.funding_rate {
                 let (funding_sig, _) = funding_rate.update(funding);
@@ -350,7 +508,9 @@ impl StrategyEvaluator {
             IndicatorType::Bollinger => values.bollinger_middle,
             IndicatorType::SuperTrend => values.supertrend_value,
             IndicatorType::FundingRate => values.funding_rate_value,
-            _ => None, // OBV, VolumeProfile, OpenInterest don't have simple numeric values
+            IndicatorType::OpenInterest => values.open_interest_value,
+            IndicatorType::Premium => values.premium_value,
+            _ => None, // OBV and VolumeProfile don't have simple numeric values
         }
     }
 
@@ -400,6 +560,19 @@ impl StrategyEvaluator {
                     false
                 }
             }
+            IndicatorType::OpenInterest => {
+                if let Some(signal) = values.oi_signal {
+                    match signal_state {
+                        "BullishExpansion" => matches!(signal, open_interest::OpenInterestSignal::BullishExpansion),
+                        "BearishExpansion" => matches!(signal, open_interest::OpenInterestSignal::BearishExpansion),
+                        "LongSqueeze" => matches!(signal, open_interest::OpenInterestSignal::LongSqueeze),
+                        "ShortSqueeze" => matches!(signal, open_interest::OpenInterestSignal::ShortSqueeze),
+                        _ => false,
+                    }
+                } else {
+                    false
+                }
+            }
             _ => false, // Other indicators not yet implemented
         }
     }
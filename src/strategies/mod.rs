@@ -2,4 +2,4 @@
 
 pub mod evaluator;
 
-pub use evaluator::{IndicatorValues, StrategyEvaluator};
+pub use evaluator::{IndicatorSettings, IndicatorValues, StrategyEvaluator};
@@ -4,6 +4,22 @@
 //! - api_server: HTTP API endpoints and business logic
 //! - websocket_service: WebSocket connection and data ingestion
 //! - worker: Job processing and workflow execution
+//! - backfill: Paginated historical candle backfill and gap repair
+//! - paper_trading: Paper trading on live signals
+//! - execution: Signed Hyperliquid order placement
+//! - order_management: Order lifecycle tracking and reconciliation
+//! - risk: Pre-trade risk checks and the kill switch
+//! - policy_engine: Signal-driven order placement
+//! - user_events: Hyperliquid user event stream into the OMS
+//! - positions: Position and PnL tracking
+//! - algos: TWAP, iceberg and chase-limit execution algorithms
+//! - shadow: Shadow execution that records orders instead of sending them
+//! - binance: Binance USDⓈ-M market data behind the venue registry
+//! - subscription_sync: Runtime market data subscriptions following the strategies
+
+// In-memory exchange and order store shared by the execution test modules
+#[path = "integration/execution/test_utils.rs"]
+mod mock_exchange;
 
 #[path = "integration/api_server.rs"]
 mod api_server;
@@ -17,3 +33,39 @@ mod worker;
 // Legacy integration tests (can be migrated to api_server)
 #[path = "integration/hyperliquid.rs"]
 mod hyperliquid;
+
+#[path = "integration/backfill.rs"]
+mod backfill;
+
+#[path = "integration/paper_trading.rs"]
+mod paper_trading;
+
+#[path = "integration/execution.rs"]
+mod execution;
+
+#[path = "integration/order_management.rs"]
+mod order_management;
+
+#[path = "integration/risk.rs"]
+mod risk;
+
+#[path = "integration/policy_engine.rs"]
+mod policy_engine;
+
+#[path = "integration/user_events.rs"]
+mod user_events;
+
+#[path = "integration/positions.rs"]
+mod positions;
+
+#[path = "integration/algos.rs"]
+mod algos;
+
+#[path = "integration/shadow.rs"]
+mod shadow;
+
+#[path = "integration/binance.rs"]
+mod binance;
+
+#[path = "integration/subscription_sync.rs"]
+mod subscription_sync;
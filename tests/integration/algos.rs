@@ -0,0 +1,256 @@
+//! Integration tests for the execution algorithms
+//!
+//! Runs algos through the OMS against the mock exchange's simulated book.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::mock_exchange::{MemoryOrderStore, MockExecutor, PlaceMode};
+use chrono::Utc;
+use perptrix::execution::{
+    AlgoEngine, AlgoOrder, AlgoParams, AlgoProgress, AlgoState, AlgoStore, MemoryAlgoStore,
+    OrderManager, OrderOrigin, OrderRequest, OrderSide, OrderState, OrderType, TimeInForce,
+};
+
+struct Harness {
+    executor: Arc<MockExecutor>,
+    oms: Arc<OrderManager>,
+    store: Arc<MemoryAlgoStore>,
+    engine: AlgoEngine,
+}
+
+fn harness() -> Harness {
+    let executor = Arc::new(MockExecutor::new());
+    executor.set_mode(PlaceMode::Book);
+    executor.set_book("BTC", 100.0, 101.0);
+    let oms = Arc::new(OrderManager::new(
+        executor.clone(),
+        Arc::new(MemoryOrderStore::default()),
+    ));
+    let store = Arc::new(MemoryAlgoStore::default());
+    let engine =
+        AlgoEngine::new(oms.clone(), store.clone()).with_tick_interval(Duration::from_millis(10));
+    Harness {
+        executor,
+        oms,
+        store,
+        engine,
+    }
+}
+
+fn buy(size: f64, params: AlgoParams) -> AlgoOrder {
+    AlgoOrder {
+        symbol: "BTC".to_string(),
+        side: OrderSide::Buy,
+        size,
+        reduce_only: false,
+        params,
+    }
+}
+
+/// Start `order` and return its id
+async fn start(h: &Harness, order: AlgoOrder) -> String {
+    let progress = h.engine.submit(order).await.unwrap();
+    assert_eq!(
+        h.engine.start_pending().await.unwrap(),
+        vec![progress.id.clone()]
+    );
+    progress.id
+}
+
+/// Poll the store until `done` holds for the algo's progress
+async fn wait_for(
+    store: &MemoryAlgoStore,
+    id: &str,
+    done: impl Fn(&AlgoProgress) -> bool,
+) -> AlgoProgress {
+    for _ in 0..500 {
+        if let Some(progress) = store.load_algo(id).await.unwrap() {
+            if done(&progress) {
+                return progress;
+            }
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("algo {} did not reach the expected state", id);
+}
+
+#[tokio::test]
+async fn twap_takes_liquidity_in_even_slices() {
+    let h = harness();
+    let id = start(
+        &h,
+        buy(
+            1.0,
+            AlgoParams::Twap {
+                duration_seconds: 1,
+                slices: 2,
+                max_slippage: 0.01,
+            },
+        ),
+    )
+    .await;
+
+    let progress = wait_for(&h.store, &id, |p| p.state.is_terminal()).await;
+    assert_eq!(progress.state, AlgoState::Completed);
+    assert_eq!(progress.filled_size, 1.0);
+    assert_eq!(progress.avg_fill_price, Some(101.0));
+    assert_eq!(progress.child_orders.len(), 2);
+
+    let placed = h.executor.placed();
+    assert_eq!(placed.len(), 2);
+    for slice in &placed {
+        assert_eq!(slice.size, 0.5);
+        assert_eq!(slice.price, 101.0 * 1.01);
+        assert_eq!(slice.order_type, OrderType::Limit(TimeInForce::Ioc));
+    }
+}
+
+#[tokio::test]
+async fn iceberg_shows_one_clip_at_a_time() {
+    let h = harness();
+    let id = start(
+        &h,
+        buy(
+            0.3,
+            AlgoParams::Iceberg {
+                clip_size: 0.1,
+                limit_price: 99.0,
+            },
+        ),
+    )
+    .await;
+
+    let progress = wait_for(&h.store, &id, |p| !p.child_orders.is_empty()).await;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(h.executor.placed().len(), 1);
+    assert_eq!(progress.filled_size, 0.0);
+
+    // The offer comes down to the limit: each clip fills and the next replaces it
+    h.executor.set_book("BTC", 98.0, 99.0);
+    let progress = wait_for(&h.store, &id, |p| p.state.is_terminal()).await;
+    assert_eq!(progress.state, AlgoState::Completed);
+    assert!((progress.filled_size - 0.3).abs() < 1e-9);
+    let placed = h.executor.placed();
+    assert_eq!(placed.len(), 3);
+    assert!(placed
+        .iter()
+        .all(|o| (o.size - 0.1).abs() < 1e-9 && o.price == 99.0));
+}
+
+#[tokio::test]
+async fn chase_limit_follows_the_touch_until_filled() {
+    let h = harness();
+    let id = start(
+        &h,
+        buy(
+            1.0,
+            AlgoParams::ChaseLimit {
+                timeout_seconds: 5,
+                limit_price: None,
+            },
+        ),
+    )
+    .await;
+
+    let first = wait_for(&h.store, &id, |p| p.child_orders.len() == 1).await;
+    let placed = h.executor.placed();
+    assert_eq!(placed[0].price, 100.0);
+    assert_eq!(placed[0].order_type, OrderType::Limit(TimeInForce::Alo));
+
+    // The bid moves up: the resting order is cancelled and re-placed at the new touch
+    h.executor.set_book("BTC", 100.5, 101.5);
+    wait_for(&h.store, &id, |p| p.child_orders.len() == 2).await;
+    let replaced = h.oms.order(&first.child_orders[0]).await.unwrap();
+    assert_eq!(replaced.state, OrderState::Cancelled);
+    assert_eq!(h.executor.placed()[1].price, 100.5);
+
+    h.executor.set_book("BTC", 99.5, 100.5);
+    let progress = wait_for(&h.store, &id, |p| p.state.is_terminal()).await;
+    assert_eq!(progress.state, AlgoState::Completed);
+    assert_eq!(progress.filled_size, 1.0);
+    assert_eq!(progress.avg_fill_price, Some(100.5));
+}
+
+#[tokio::test]
+async fn chase_limit_expires_and_cancels_its_order() {
+    let h = harness();
+    let id = start(
+        &h,
+        buy(
+            1.0,
+            AlgoParams::ChaseLimit {
+                timeout_seconds: 1,
+                limit_price: Some(99.0),
+            },
+        ),
+    )
+    .await;
+
+    let progress = wait_for(&h.store, &id, |p| p.state.is_terminal()).await;
+    assert_eq!(progress.state, AlgoState::Expired);
+    assert_eq!(progress.filled_size, 0.0);
+    // Capped at the limit rather than joining the bid
+    assert_eq!(h.executor.placed()[0].price, 99.0);
+    assert_eq!(h.executor.cancelled().len(), 1);
+}
+
+#[tokio::test]
+async fn cancellation_through_the_store_stops_the_algo() {
+    let h = harness();
+    let id = start(
+        &h,
+        buy(
+            1.0,
+            AlgoParams::Iceberg {
+                clip_size: 0.25,
+                limit_price: 99.0,
+            },
+        ),
+    )
+    .await;
+    wait_for(&h.store, &id, |p| !p.child_orders.is_empty()).await;
+
+    h.store.request_algo_cancel(&id).await.unwrap();
+    let progress = wait_for(&h.store, &id, |p| p.state.is_terminal()).await;
+    assert_eq!(progress.state, AlgoState::Cancelled);
+    assert_eq!(h.executor.cancelled().len(), 1);
+    assert!(h.oms.open_orders().await.is_empty());
+}
+
+#[tokio::test]
+async fn restore_fails_algos_interrupted_by_a_restart() {
+    let h = harness();
+    let child = h
+        .oms
+        .submit(
+            OrderRequest::limit("BTC", OrderSide::Buy, 0.1, 99.0),
+            OrderOrigin::manual(),
+        )
+        .await
+        .unwrap();
+    let mut progress = AlgoProgress::new(
+        buy(
+            1.0,
+            AlgoParams::Iceberg {
+                clip_size: 0.1,
+                limit_price: 99.0,
+            },
+        ),
+        Utc::now(),
+    );
+    progress.state = AlgoState::Running;
+    progress.child_orders.push(child.client_order_id.clone());
+    h.store.save_algo(&progress).await.unwrap();
+
+    assert_eq!(h.engine.restore().await.unwrap(), 1);
+    let restored = h.store.load_algo(&progress.id).await.unwrap().unwrap();
+    assert_eq!(restored.state, AlgoState::Failed);
+    assert!(restored.error.is_some());
+    assert_eq!(
+        h.oms.order(&child.client_order_id).await.unwrap().state,
+        OrderState::Cancelled
+    );
+    // Failed algos are not picked up again
+    assert!(h.engine.start_pending().await.unwrap().is_empty());
+}
@@ -5,9 +5,12 @@
 #[path = "api_server/test_utils.rs"]
 mod test_utils;
 
-use serde_json::Value;
+use perptrix::services::subscription_sync::{
+    ActiveSubscription, SubscriptionCommand, SubscriptionSource, SubscriptionStore,
+};
+use serde_json::{json, Value};
 
-use test_utils::TestApiServer;
+use test_utils::{TestApiServer, ALLOWED_ORIGIN, EXECUTION_TOKEN};
 
 #[tokio::test]
 async fn health_endpoint_reports_healthy_status() {
@@ -122,6 +125,506 @@ async fn api_server_handles_concurrent_requests() {
     }
 }
 
+#[tokio::test]
+async fn candle_endpoints_require_database() {
+    let app = TestApiServer::new().await;
+
+    let export = app
+        .server
+        .get("/api/candles/export?symbol=BTC&interval=1h&format=parquet")
+        .await;
+    assert_eq!(export.status_code(), 503);
+
+    let import = app
+        .server
+        .post("/api/candles/import?symbol=BTC&interval=1h&format=csv")
+        .authorization_bearer(EXECUTION_TOKEN)
+        .bytes("timestamp,open,high,low,close,volume,open_interest,funding_rate\n".into())
+        .await;
+    assert_eq!(import.status_code(), 503);
+
+    let bad_format = app
+        .server
+        .get("/api/candles/export?symbol=BTC&interval=1h&format=xlsx")
+        .await;
+    assert_eq!(bad_format.status_code(), 400);
+}
+
+#[tokio::test]
+async fn candle_imports_require_the_execution_token_and_a_small_file() {
+    let app = TestApiServer::new().await;
+    let url = "/api/candles/import?symbol=BTC&interval=1h&format=csv";
+
+    let anonymous = app
+        .server
+        .post(url)
+        .bytes("timestamp,open,high,low,close,volume,open_interest,funding_rate\n".into())
+        .await;
+    assert_eq!(anonymous.status_code(), 401);
+
+    let oversized = app
+        .server
+        .post(url)
+        .authorization_bearer(EXECUTION_TOKEN)
+        .bytes(vec![b'0'; 9 * 1024 * 1024].into())
+        .await;
+    assert_eq!(oversized.status_code(), 413);
+}
+
+#[tokio::test]
+async fn paper_pnl_endpoints_require_database() {
+    let app = TestApiServer::new().await;
+
+    let all = app.server.get("/api/paper/pnl").await;
+    assert_eq!(all.status_code(), 503);
+
+    let single = app.server.get("/api/paper/pnl/1").await;
+    assert_eq!(single.status_code(), 503);
+}
+
+#[tokio::test]
+async fn position_endpoints_require_database() {
+    let app = TestApiServer::new().await;
+
+    let positions = app.server.get("/api/positions").await;
+    assert_eq!(positions.status_code(), 503);
+
+    let pnl = app.server.get("/api/pnl?account=0xabc").await;
+    assert_eq!(pnl.status_code(), 503);
+
+    let shadow = app.server.get("/api/shadow/orders?strategy_id=1").await;
+    assert_eq!(shadow.status_code(), 503);
+}
+
+#[tokio::test]
+async fn strategy_with_invalid_execution_policy_is_rejected() {
+    let app = TestApiServer::new().await;
+
+    let response = app
+        .server
+        .post("/api/strategies")
+        .authorization_bearer(EXECUTION_TOKEN)
+        .json(&json!({
+            "name": "bad policy",
+            "symbol": "BTC",
+            "config": {
+                "rules": [],
+                "aggregation": {
+                    "method": "Sum",
+                    "thresholds": { "long_min": 1, "short_max": -1 }
+                },
+                "execution": { "order_notional": 500, "min_confidence": 1.5 }
+            }
+        }))
+        .await;
+    assert_eq!(response.status_code(), 400);
+}
+
+#[tokio::test]
+async fn strategy_changes_require_the_execution_token() {
+    let app = TestApiServer::new().await;
+    let live = json!({
+        "name": "live",
+        "symbol": "BTC",
+        "config": {
+            "rules": [],
+            "aggregation": {
+                "method": "Sum",
+                "thresholds": { "long_min": 1, "short_max": -1 }
+            },
+            "execution": { "order_notional": 1000000 }
+        }
+    });
+
+    let created = app.server.post("/api/strategies").json(&live).await;
+    assert_eq!(created.status_code(), 401);
+    let updated = app.server.put("/api/strategies/1").json(&live).await;
+    assert_eq!(updated.status_code(), 401);
+    assert_eq!(app.server.delete("/api/strategies/1").await.status_code(), 401);
+
+    // With the token the request gets through, and only fails for lack of a database
+    let authorized = app
+        .server
+        .post("/api/strategies")
+        .authorization_bearer(EXECUTION_TOKEN)
+        .json(&live)
+        .await;
+    assert_eq!(authorized.status_code(), 503);
+    // Reads stay open
+    assert_eq!(app.server.get("/api/strategies").await.status_code(), 503);
+}
+
+#[tokio::test]
+async fn strategy_symbols_are_checked_against_instruments() {
+    let app = TestApiServer::new().await;
+
+    let instruments: Value = app.server.get("/api/instruments").await.json();
+    let symbols: Vec<&str> = instruments
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|i| i["symbol"].as_str().unwrap())
+        .collect();
+    assert_eq!(symbols, vec!["BTC", "ETH", "LUNA", "kPEPE"]);
+
+    let create = |symbol: &str| {
+        json!({
+            "name": "trend",
+            "symbol": symbol,
+            "config": {
+                "rules": [],
+                "aggregation": {
+                    "method": "Sum",
+                    "thresholds": { "long_min": 1, "short_max": -1 }
+                }
+            }
+        })
+    };
+    for rejected in ["DOGE", "LUNA-PERP"] {
+        let response = app
+            .server
+            .post("/api/strategies")
+            .authorization_bearer(EXECUTION_TOKEN)
+            .json(&create(rejected))
+            .await;
+        assert_eq!(response.status_code(), 400, "{} should be rejected", rejected);
+    }
+    // Known symbols pass validation and only fail for lack of a database
+    let response = app
+        .server
+        .post("/api/strategies")
+        .authorization_bearer(EXECUTION_TOKEN)
+        .json(&create("btc-perp"))
+        .await;
+    assert_eq!(response.status_code(), 503);
+
+    let algo: Value = app
+        .server
+        .post("/api/algos")
+        .authorization_bearer(EXECUTION_TOKEN)
+        .json(&json!({
+            "symbol": "eth-usd",
+            "side": "Sell",
+            "size": 1.0,
+            "params": { "type": "Iceberg", "clip_size": 0.1, "limit_price": 3000.0 }
+        }))
+        .await
+        .json();
+    assert_eq!(algo["order"]["symbol"], "ETH");
+}
+
+#[tokio::test]
+async fn symbol_requests_are_refused_until_instruments_load() {
+    let app = TestApiServer::new().await;
+    let registry = app.instruments.write().await.take();
+
+    let strategy = json!({
+        "name": "unvalidated",
+        "symbol": "DOGE",
+        "config": {
+            "rules": [],
+            "aggregation": {
+                "method": "Sum",
+                "thresholds": { "long_min": 1, "short_max": -1 }
+            }
+        }
+    });
+    let created = app
+        .server
+        .post("/api/strategies")
+        .authorization_bearer(EXECUTION_TOKEN)
+        .json(&strategy)
+        .await;
+    assert_eq!(created.status_code(), 503);
+    let updated = app
+        .server
+        .put("/api/strategies/1")
+        .authorization_bearer(EXECUTION_TOKEN)
+        .json(&json!({ "symbol": "DOGE" }))
+        .await;
+    assert_eq!(updated.status_code(), 503);
+    let subscribed = app
+        .server
+        .post("/api/admin/subscriptions")
+        .authorization_bearer(EXECUTION_TOKEN)
+        .json(&json!({ "symbol": "DOGE" }))
+        .await;
+    assert_eq!(subscribed.status_code(), 503);
+    assert_eq!(app.server.get("/api/instruments").await.status_code(), 503);
+    assert!(app.subscriptions.commands().await.is_empty());
+
+    // Once loaded, unknown symbols are rejected
+    *app.instruments.write().await = registry;
+    let created = app
+        .server
+        .post("/api/strategies")
+        .authorization_bearer(EXECUTION_TOKEN)
+        .json(&strategy)
+        .await;
+    assert_eq!(created.status_code(), 400);
+}
+
+#[tokio::test]
+async fn kill_switch_can_be_activated_and_cleared() {
+    let app = TestApiServer::new().await;
+
+    let initial = app.server.get("/api/risk/kill-switch").await;
+    assert_eq!(initial.status_code(), 200);
+    assert_eq!(initial.json::<Value>()["active"], false);
+
+    let activated = app
+        .server
+        .post("/api/risk/kill-switch")
+        .authorization_bearer(EXECUTION_TOKEN)
+        .json(&json!({ "active": true, "reason": "exchange outage", "flatten": true }))
+        .await;
+    assert_eq!(activated.status_code(), 200);
+    let body: Value = activated.json();
+    assert_eq!(body["active"], true);
+    assert_eq!(body["reason"], "exchange outage");
+    assert_eq!(body["flatten_requested"], true);
+    assert_eq!(app.metrics.kill_switch_active.get(), 1.0);
+
+    let current: Value = app.server.get("/api/risk/kill-switch").await.json();
+    assert_eq!(current["active"], true);
+
+    let cleared: Value = app
+        .server
+        .post("/api/risk/kill-switch")
+        .authorization_bearer(EXECUTION_TOKEN)
+        .json(&json!({ "active": false }))
+        .await
+        .json();
+    assert_eq!(cleared["active"], false);
+    assert_eq!(cleared["flatten_requested"], false);
+    assert_eq!(app.metrics.kill_switch_active.get(), 0.0);
+}
+
+#[tokio::test]
+async fn kill_switch_changes_require_the_execution_token() {
+    let app = TestApiServer::new().await;
+    let activate = json!({ "active": true, "reason": "test" });
+
+    let anonymous = app.server.post("/api/risk/kill-switch").json(&activate).await;
+    assert_eq!(anonymous.status_code(), 401);
+    let wrong_token = app
+        .server
+        .post("/api/risk/kill-switch")
+        .authorization_bearer("not-the-token")
+        .json(&activate)
+        .await;
+    assert_eq!(wrong_token.status_code(), 401);
+    let foreign_origin = app
+        .server
+        .post("/api/risk/kill-switch")
+        .authorization_bearer(EXECUTION_TOKEN)
+        .add_header("Origin", "https://evil.example")
+        .json(&activate)
+        .await;
+    assert_eq!(foreign_origin.status_code(), 403);
+
+    // Reading the kill switch stays open
+    let current: Value = app.server.get("/api/risk/kill-switch").await.json();
+    assert_eq!(current["active"], false);
+
+    let allowed_origin = app
+        .server
+        .post("/api/risk/kill-switch")
+        .authorization_bearer(EXECUTION_TOKEN)
+        .add_header("Origin", ALLOWED_ORIGIN)
+        .json(&activate)
+        .await;
+    assert_eq!(allowed_origin.status_code(), 200);
+    assert_eq!(allowed_origin.json::<Value>()["active"], true);
+}
+
+#[tokio::test]
+async fn kill_switch_changes_are_disabled_without_a_token() {
+    let app = TestApiServer::with_execution_auth(None).await;
+
+    let response = app
+        .server
+        .post("/api/risk/kill-switch")
+        .authorization_bearer(EXECUTION_TOKEN)
+        .json(&json!({ "active": true }))
+        .await;
+    assert_eq!(response.status_code(), 403);
+    assert_eq!(app.server.get("/api/risk/kill-switch").await.status_code(), 200);
+}
+
+#[tokio::test]
+async fn algos_can_be_requested_and_cancelled() {
+    let app = TestApiServer::new().await;
+
+    let invalid = app
+        .server
+        .post("/api/algos")
+        .authorization_bearer(EXECUTION_TOKEN)
+        .json(&json!({
+            "symbol": "BTC",
+            "side": "Buy",
+            "size": 1.0,
+            "params": { "type": "Twap", "duration_seconds": 60, "slices": 0 }
+        }))
+        .await;
+    assert_eq!(invalid.status_code(), 400);
+
+    let submitted = app
+        .server
+        .post("/api/algos")
+        .authorization_bearer(EXECUTION_TOKEN)
+        .json(&json!({
+            "symbol": "BTC",
+            "side": "Buy",
+            "size": 1.0,
+            "params": { "type": "Iceberg", "clip_size": 0.1, "limit_price": 50000.0 }
+        }))
+        .await;
+    assert_eq!(submitted.status_code(), 202);
+    let body: Value = submitted.json();
+    assert_eq!(body["state"], "Pending");
+    let id = body["id"].as_str().unwrap().to_string();
+
+    let listed: Value = app.server.get("/api/algos").await.json();
+    assert_eq!(listed.as_array().unwrap().len(), 1);
+    let single = app.server.get(&format!("/api/algos/{}", id)).await;
+    assert_eq!(single.status_code(), 200);
+    assert_eq!(single.json::<Value>()["order"]["params"]["clip_size"], 0.1);
+    assert_eq!(app.server.get("/api/algos/unknown").await.status_code(), 404);
+
+    let cancelled = app
+        .server
+        .post(&format!("/api/algos/{}/cancel", id))
+        .authorization_bearer(EXECUTION_TOKEN)
+        .await;
+    assert_eq!(cancelled.status_code(), 202);
+    assert_eq!(
+        app.server
+            .post("/api/algos/unknown/cancel")
+            .authorization_bearer(EXECUTION_TOKEN)
+            .await
+            .status_code(),
+        404
+    );
+}
+
+#[tokio::test]
+async fn algo_requests_require_the_execution_token() {
+    let app = TestApiServer::new().await;
+    let order = json!({
+        "symbol": "BTC",
+        "side": "Buy",
+        "size": 1.0,
+        "params": { "type": "Iceberg", "clip_size": 0.1, "limit_price": 50000.0 }
+    });
+
+    assert_eq!(app.server.post("/api/algos").json(&order).await.status_code(), 401);
+    let foreign_origin = app
+        .server
+        .post("/api/algos")
+        .authorization_bearer(EXECUTION_TOKEN)
+        .add_header("Origin", "https://evil.example")
+        .json(&order)
+        .await;
+    assert_eq!(foreign_origin.status_code(), 403);
+    let listed: Value = app.server.get("/api/algos").await.json();
+    assert!(listed.as_array().unwrap().is_empty());
+
+    let submitted: Value = app
+        .server
+        .post("/api/algos")
+        .authorization_bearer(EXECUTION_TOKEN)
+        .json(&order)
+        .await
+        .json();
+    let cancel = format!("/api/algos/{}/cancel", submitted["id"].as_str().unwrap());
+    assert_eq!(app.server.post(&cancel).await.status_code(), 401);
+    let algo: Value = app
+        .server
+        .get(&format!("/api/algos/{}", submitted["id"].as_str().unwrap()))
+        .await
+        .json();
+    assert_eq!(algo["state"], "Pending");
+
+    let disabled = TestApiServer::with_execution_auth(None).await;
+    let response = disabled
+        .server
+        .post("/api/algos")
+        .authorization_bearer(EXECUTION_TOKEN)
+        .json(&order)
+        .await;
+    assert_eq!(response.status_code(), 403);
+}
+
+#[tokio::test]
+async fn subscriptions_are_listed_and_requested() {
+    let app = TestApiServer::new().await;
+
+    let listed: Value = app.server.get("/api/admin/subscriptions").await.json();
+    assert_eq!(listed, json!([]));
+    app.subscriptions
+        .save_subscriptions(&[ActiveSubscription {
+            symbol: "BTC".to_string(),
+            sources: vec![SubscriptionSource::Static, SubscriptionSource::Strategy],
+            since: chrono::Utc::now(),
+        }])
+        .await
+        .unwrap();
+    let listed: Value = app.server.get("/api/admin/subscriptions").await.json();
+    assert_eq!(listed[0]["symbol"], "BTC");
+    assert_eq!(listed[0]["sources"], json!(["static", "strategy"]));
+
+    let subscribed = app
+        .server
+        .post("/api/admin/subscriptions")
+        .authorization_bearer(EXECUTION_TOKEN)
+        .json(&json!({ "symbol": "eth-perp" }))
+        .await;
+    assert_eq!(subscribed.status_code(), 202);
+    assert_eq!(
+        subscribed.json::<Value>(),
+        json!({ "action": "subscribe", "symbol": "ETH" })
+    );
+    let unknown = app
+        .server
+        .post("/api/admin/subscriptions")
+        .authorization_bearer(EXECUTION_TOKEN)
+        .json(&json!({ "symbol": "LUNA" }))
+        .await;
+    assert_eq!(unknown.status_code(), 400);
+
+    // Changes need the token; the list does not
+    let anonymous = app
+        .server
+        .post("/api/admin/subscriptions")
+        .json(&json!({ "symbol": "SOL" }))
+        .await;
+    assert_eq!(anonymous.status_code(), 401);
+    assert_eq!(
+        app.server.delete("/api/admin/subscriptions/eth").await.status_code(),
+        401
+    );
+
+    let unsubscribed = app
+        .server
+        .delete("/api/admin/subscriptions/eth")
+        .authorization_bearer(EXECUTION_TOKEN)
+        .await;
+    assert_eq!(unsubscribed.status_code(), 202);
+
+    assert_eq!(
+        app.subscriptions.commands().await,
+        vec![
+            SubscriptionCommand::Subscribe {
+                symbol: "ETH".to_string()
+            },
+            SubscriptionCommand::Unsubscribe {
+                symbol: "ETH".to_string()
+            },
+        ]
+    );
+}
+
 // Future tests for business logic endpoints will go here:
 // - GET /signals - List signals
 // - GET /signals/{symbol} - Get signals for a symbol
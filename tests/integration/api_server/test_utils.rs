@@ -1,33 +1,92 @@
 //! Test utilities for API server integration tests
 
 use axum_test::TestServer;
-use perptrix::core::http::{create_router, AppState, HealthStatus};
+use perptrix::core::http::{create_router, AppState, ExecutionAuth, HealthStatus};
+use perptrix::execution::{KillSwitch, MemoryAlgoStore, MemoryKillSwitchStore};
 use perptrix::metrics::Metrics;
+use perptrix::services::instruments::{Instrument, InstrumentRegistry};
+use perptrix::services::subscription_sync::MemorySubscriptionStore;
 use std::sync::Arc;
 use std::time::Instant;
 use tokio::sync::RwLock;
 
+/// Bearer token accepted by the execution endpoints of `TestApiServer::new`
+pub const EXECUTION_TOKEN: &str = "test-execution-token";
+
+/// Browser origin allowed to call the execution endpoints of `TestApiServer::new`
+pub const ALLOWED_ORIGIN: &str = "https://dashboard.example";
+
 /// Test helper for API server integration tests
 #[allow(dead_code)]
 pub struct TestApiServer {
     pub server: TestServer,
     pub metrics: Arc<Metrics>,
+    pub subscriptions: Arc<MemorySubscriptionStore>,
+    /// Instrument registry slot, cleared to simulate a failed load
+    pub instruments: Arc<RwLock<Option<Arc<InstrumentRegistry>>>>,
 }
 
 impl TestApiServer {
     pub async fn new() -> Self {
+        Self::with_execution_auth(Some(
+            ExecutionAuth::new(EXECUTION_TOKEN)
+                .with_allowed_origins(vec![ALLOWED_ORIGIN.to_string()]),
+        ))
+        .await
+    }
+
+    pub async fn with_execution_auth(execution_auth: Option<ExecutionAuth>) -> Self {
         let metrics = Arc::new(Metrics::new().expect("metrics initialization"));
+        let subscriptions = Arc::new(MemorySubscriptionStore::default());
+        let instruments = Arc::new(RwLock::new(Some(Arc::new(test_instruments()))));
         let state = AppState {
             health: Arc::new(RwLock::new(HealthStatus::default())),
             metrics: metrics.clone(),
             start_time: Arc::new(Instant::now()),
             database: None,
+            kill_switch: Some(Arc::new(
+                KillSwitch::new(Arc::new(MemoryKillSwitchStore::default()))
+                    .with_metrics(metrics.clone()),
+            )),
+            algos: Some(Arc::new(MemoryAlgoStore::default())),
+            instruments: instruments.clone(),
+            subscriptions: Some(subscriptions.clone()),
+            execution_auth: execution_auth.map(Arc::new),
         };
 
         let app = create_router(state);
         let server = TestServer::new(app).expect("start test server");
 
-        Self { server, metrics }
+        Self {
+            server,
+            metrics,
+            subscriptions,
+            instruments,
+        }
     }
 }
 
+fn instrument(name: &str, listed: bool) -> Instrument {
+    Instrument {
+        symbol: name.to_string(),
+        venue: "hyperliquid".to_string(),
+        venue_symbol: name.to_string(),
+        tick_size: 1.0,
+        size_decimals: 3,
+        max_leverage: 20,
+        listed,
+    }
+}
+
+/// Listed BTC, ETH and kPEPE, and a delisted LUNA
+fn test_instruments() -> InstrumentRegistry {
+    InstrumentRegistry::new().with_instruments(
+        "hyperliquid",
+        vec![
+            instrument("BTC", true),
+            instrument("ETH", true),
+            instrument("kPEPE", true),
+            instrument("LUNA", false),
+        ],
+    )
+}
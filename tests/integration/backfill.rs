@@ -0,0 +1,505 @@
+//! Integration tests for historical candle backfill
+//!
+//! Serves paged candleSnapshot/fundingHistory responses from wiremock and
+//! backfills into an in-memory candle store, including the repair of gaps
+//! found by the candle gap auditor.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use perptrix::db::CandleStore;
+use perptrix::metrics::Metrics;
+use perptrix::models::indicators::Candle;
+use perptrix::services::candle_gaps::{
+    find_gaps, recent_gaps, CandleGapAuditor, EmptyRangeStore, GapFiller, MemoryEmptyRangeStore,
+};
+use perptrix::services::hyperliquid::{BackfillConfig, CandleBackfiller, HyperliquidRestClient};
+use perptrix::services::market_data::DEFAULT_VENUE;
+use serde_json::Value;
+use tokio::sync::Mutex;
+use wiremock::matchers::{body_string_contains, method, path};
+use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+
+const HOUR_MS: u64 = 60 * 60 * 1000;
+
+#[derive(Default)]
+struct MemoryCandleStore {
+    candles: Mutex<Vec<Candle>>,
+}
+
+#[async_trait]
+impl CandleStore for MemoryCandleStore {
+    async fn latest_candle_timestamp(
+        &self,
+        _symbol: &str,
+        _interval: &str,
+    ) -> Result<Option<DateTime<Utc>>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.candles.lock().await.iter().map(|c| c.timestamp).max())
+    }
+
+    async fn store_candles_batch(
+        &self,
+        _symbol: &str,
+        _interval: &str,
+        candles: &[Candle],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.candles.lock().await.extend_from_slice(candles);
+        Ok(())
+    }
+
+    async fn candles_in_range(
+        &self,
+        _symbol: &str,
+        _interval: &str,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Candle>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self
+            .candles
+            .lock()
+            .await
+            .iter()
+            .filter(|c| start.is_none_or(|s| c.timestamp >= s) && end.is_none_or(|e| c.timestamp <= e))
+            .cloned()
+            .collect())
+    }
+}
+
+/// Venue that has no bars for any range, or fails every request
+#[derive(Default)]
+struct EmptyFiller {
+    calls: std::sync::atomic::AtomicUsize,
+    failing: bool,
+}
+
+#[async_trait]
+impl GapFiller for EmptyFiller {
+    async fn fill(
+        &self,
+        _symbol: &str,
+        _interval: &str,
+        _from: DateTime<Utc>,
+        _to: DateTime<Utc>,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        self.calls
+            .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        if self.failing {
+            return Err("venue unavailable".into());
+        }
+        Ok(0)
+    }
+}
+
+fn request_range(request: &Request) -> (u64, u64) {
+    let body: Value = serde_json::from_slice(&request.body).expect("json body");
+    let req = if body.get("req").is_some() {
+        &body["req"]
+    } else {
+        &body
+    };
+    (
+        req["startTime"].as_u64().expect("startTime"),
+        req["endTime"].as_u64().expect("endTime"),
+    )
+}
+
+/// Hourly candles whose open time falls inside the requested range
+struct HourlyCandles;
+
+impl Respond for HourlyCandles {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let (start, end) = request_range(request);
+        let first = start.div_ceil(HOUR_MS) * HOUR_MS;
+        let candles: Vec<Value> = (first..=end)
+            .step_by(HOUR_MS as usize)
+            .map(|t| {
+                serde_json::json!({
+                    "t": t, "T": t + HOUR_MS - 1, "s": "BTC", "i": "1h",
+                    "o": "100", "h": "101", "l": "99", "c": "100.5", "v": "10", "n": 5
+                })
+            })
+            .collect();
+        ResponseTemplate::new(200).set_body_json(candles)
+    }
+}
+
+/// Hourly funding settlements inside the requested range
+struct HourlyFunding;
+
+impl Respond for HourlyFunding {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let (start, end) = request_range(request);
+        let first = start.div_ceil(HOUR_MS) * HOUR_MS;
+        let points: Vec<Value> = (first..=end)
+            .step_by(HOUR_MS as usize)
+            .map(|t| {
+                serde_json::json!({
+                    "coin": "BTC",
+                    "fundingRate": format!("{}", (t / HOUR_MS) as f64 * 1e-6),
+                    "premium": "0",
+                    "time": t
+                })
+            })
+            .collect();
+        ResponseTemplate::new(200).set_body_json(points)
+    }
+}
+
+async fn mock_history(server: &MockServer) {
+    Mock::given(method("POST"))
+        .and(path("/info"))
+        .and(body_string_contains("candleSnapshot"))
+        .respond_with(HourlyCandles)
+        .mount(server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/info"))
+        .and(body_string_contains("fundingHistory"))
+        .respond_with(HourlyFunding)
+        .mount(server)
+        .await;
+}
+
+fn backfiller(server: &MockServer, store: Arc<MemoryCandleStore>) -> CandleBackfiller {
+    let rest = Arc::new(HyperliquidRestClient::with_client(
+        server.uri(),
+        reqwest::Client::new(),
+    ));
+    CandleBackfiller::new(rest, store).with_config(BackfillConfig {
+        page_size: 24,
+        request_interval: Duration::from_millis(1),
+        max_retries: 3,
+        include_funding: true,
+    })
+}
+
+fn range() -> (DateTime<Utc>, DateTime<Utc>) {
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    (start, start + chrono::Duration::days(3))
+}
+
+async fn candle_requests(server: &MockServer) -> Vec<(u64, u64)> {
+    server
+        .received_requests()
+        .await
+        .expect("wiremock requests")
+        .iter()
+        .filter(|r| String::from_utf8_lossy(&r.body).contains("candleSnapshot"))
+        .map(request_range)
+        .collect()
+}
+
+#[tokio::test]
+async fn backfill_pages_range_and_aligns_funding() {
+    let server = MockServer::start().await;
+    mock_history(&server).await;
+    let store = Arc::new(MemoryCandleStore::default());
+    let (start, end) = range();
+
+    let report = backfiller(&server, store.clone())
+        .backfill("BTC", "1h", start, end)
+        .await
+        .expect("backfill succeeds");
+
+    assert_eq!(report.pages, 3);
+    assert_eq!(report.candles_stored, 72);
+    assert!(report.resumed_from.is_none());
+    assert_eq!(candle_requests(&server).await.len(), 3);
+
+    let candles = store.candles.lock().await;
+    assert_eq!(candles.len(), 72);
+    assert!(candles.windows(2).all(|w| w[0].timestamp < w[1].timestamp));
+    // Each bar closes just before the next settlement, so it carries its own open-hour rate
+    for candle in candles.iter() {
+        let open_hour = (candle.timestamp.timestamp_millis() as u64) / HOUR_MS;
+        let expected = open_hour as f64 * 1e-6;
+        let rate = candle.funding_rate.expect("funding aligned");
+        assert!((rate - expected).abs() < 1e-12);
+    }
+}
+
+#[tokio::test]
+async fn backfill_resumes_after_latest_stored_candle() {
+    let server = MockServer::start().await;
+    mock_history(&server).await;
+    let store = Arc::new(MemoryCandleStore::default());
+    let (start, end) = range();
+
+    // First run covers only the first day
+    backfiller(&server, store.clone())
+        .backfill("BTC", "1h", start, start + chrono::Duration::days(1))
+        .await
+        .expect("first backfill");
+    let first_requests = candle_requests(&server).await.len();
+
+    let report = backfiller(&server, store.clone())
+        .backfill("BTC", "1h", start, end)
+        .await
+        .expect("resumed backfill");
+
+    assert!(report.resumed_from.expect("resumed") > start);
+    assert_eq!(report.candles_stored, 48);
+    assert_eq!(store.candles.lock().await.len(), 72);
+
+    let requests = candle_requests(&server).await;
+    let resumed_start = requests[first_requests].0;
+    assert!(resumed_start >= (start + chrono::Duration::days(1)).timestamp_millis() as u64 - HOUR_MS);
+}
+
+#[tokio::test]
+async fn backfill_ignores_candles_stored_after_the_range() {
+    let server = MockServer::start().await;
+    mock_history(&server).await;
+    let store = Arc::new(MemoryCandleStore::default());
+    let (start, end) = range();
+
+    // Live bars stored long after the requested range
+    backfiller(&server, store.clone())
+        .backfill("BTC", "1h", end + chrono::Duration::days(30), end + chrono::Duration::days(31))
+        .await
+        .expect("recent backfill");
+    assert_eq!(store.candles.lock().await.len(), 24);
+
+    let report = backfiller(&server, store.clone())
+        .backfill("BTC", "1h", start, start + chrono::Duration::days(1))
+        .await
+        .expect("older backfill");
+
+    assert!(report.resumed_from.is_none());
+    assert_eq!(report.candles_stored, 24);
+    assert_eq!(store.candles.lock().await.len(), 48);
+}
+
+#[tokio::test]
+async fn backfill_retries_failed_requests() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/info"))
+        .respond_with(ResponseTemplate::new(429))
+        .up_to_n_times(2)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    mock_history(&server).await;
+    let store = Arc::new(MemoryCandleStore::default());
+    let start = range().0;
+
+    let report = backfiller(&server, store.clone())
+        .backfill("BTC", "1h", start, start + chrono::Duration::days(1))
+        .await
+        .expect("backfill recovers from rate limiting");
+
+    assert_eq!(report.candles_stored, 24);
+}
+
+#[tokio::test]
+async fn backfill_rejects_unknown_interval() {
+    let server = MockServer::start().await;
+    let store = Arc::new(MemoryCandleStore::default());
+    let (start, end) = range();
+
+    let result = backfiller(&server, store)
+        .backfill("BTC", "7m", start, end)
+        .await;
+
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn gap_audit_backfills_missing_bars() {
+    let server = MockServer::start().await;
+    mock_history(&server).await;
+    let store = Arc::new(MemoryCandleStore::default());
+    let start = range().0;
+    backfiller(&server, store.clone())
+        .backfill("BTC", "1h", start, start + chrono::Duration::days(1))
+        .await
+        .expect("backfill");
+
+    // Drop five bars mid-history; the audit runs four hours after the last bar
+    let (removed, last) = {
+        let mut candles = store.candles.lock().await;
+        let removed: Vec<DateTime<Utc>> = candles.drain(5..10).map(|c| c.timestamp).collect();
+        (removed, candles.last().unwrap().timestamp)
+    };
+    let now = last + chrono::Duration::hours(4) + chrono::Duration::minutes(1);
+
+    let metrics = Arc::new(Metrics::new().unwrap());
+    let auditor = CandleGapAuditor::new(store.clone())
+        .with_filler(DEFAULT_VENUE, Arc::new(backfiller(&server, store.clone())))
+        .with_metrics(metrics.clone());
+    let report = auditor.audit("BTC", "1h", now).await.expect("audit");
+
+    assert_eq!(report.gaps.len(), 2);
+    assert_eq!(report.gaps[0].from, removed[0]);
+    assert_eq!(report.gaps[0].to, removed[4]);
+    assert_eq!(report.gaps[0].missing_bars, 5);
+    // The bar that closed last is left to the live feed
+    assert_eq!(report.gaps[1].missing_bars, 3);
+    assert_eq!(report.repaired_bars, 8);
+    assert_eq!(report.missing_bars, 0);
+
+    let candles = store.candles.lock().await.clone();
+    assert_eq!(candles.len(), 24 - 5 + 8);
+    assert!(find_gaps(&candles, HOUR_MS, &[]).is_empty());
+    assert!(candles
+        .iter()
+        .filter(|c| removed.contains(&c.timestamp))
+        .all(|c| c.funding_rate.is_some()));
+
+    assert_eq!(
+        metrics
+            .candle_gaps_detected_total
+            .with_label_values(&["BTC", "1h"])
+            .get(),
+        2.0
+    );
+    assert_eq!(
+        metrics
+            .candle_missing_bars
+            .with_label_values(&["BTC", "1h"])
+            .get(),
+        0.0
+    );
+
+    // Nothing left to repair
+    let report = auditor.audit("BTC", "1h", now).await.expect("audit");
+    assert!(report.gaps.is_empty());
+}
+
+#[tokio::test]
+async fn gap_backfill_only_stores_missing_bars() {
+    let server = MockServer::start().await;
+    mock_history(&server).await;
+    let store = Arc::new(MemoryCandleStore::default());
+    let start = range().0;
+    let backfiller = backfiller(&server, store.clone());
+    backfiller
+        .backfill("BTC", "1h", start, start + chrono::Duration::days(1))
+        .await
+        .expect("backfill");
+    let (first, last) = {
+        let mut candles = store.candles.lock().await;
+        candles.drain(5..10);
+        (candles[0].timestamp, candles.last().unwrap().timestamp)
+    };
+
+    // Repairing the whole, partly stored range writes each bar once
+    let report = backfiller
+        .backfill_gap("BTC", "1h", first, last)
+        .await
+        .expect("gap backfill");
+    assert_eq!(report.candles_stored, 5);
+    let report = backfiller
+        .backfill_gap("BTC", "1h", first, last)
+        .await
+        .expect("gap backfill");
+    assert_eq!(report.candles_stored, 0);
+
+    let candles = store.candles.lock().await.clone();
+    assert_eq!(candles.len(), 24);
+    assert!(find_gaps(&candles, HOUR_MS, &[]).is_empty());
+}
+
+#[tokio::test]
+async fn gap_audit_reports_gaps_it_cannot_repair() {
+    let server = MockServer::start().await;
+    mock_history(&server).await;
+    let store = Arc::new(MemoryCandleStore::default());
+    let start = range().0;
+    backfiller(&server, store.clone())
+        .backfill("BTC", "1h", start, start + chrono::Duration::days(1))
+        .await
+        .expect("backfill");
+    let last = {
+        let mut candles = store.candles.lock().await;
+        candles.remove(3);
+        candles.last().unwrap().timestamp
+    };
+
+    // No filler is registered for Binance
+    let metrics = Arc::new(Metrics::new().unwrap());
+    let auditor = CandleGapAuditor::new(store.clone())
+        .with_filler(DEFAULT_VENUE, Arc::new(backfiller(&server, store.clone())))
+        .with_metrics(metrics.clone());
+    auditor.watch("binance:BTCUSDT", &["1h".to_string()]);
+    let reports = auditor.audit_all(last + chrono::Duration::minutes(30)).await;
+
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].repaired_bars, 0);
+    assert_eq!(reports[0].missing_bars, 1);
+    assert_eq!(
+        metrics
+            .candle_missing_bars
+            .with_label_values(&["binance:BTCUSDT", "1h"])
+            .get(),
+        1.0
+    );
+
+    auditor.unwatch("binance:BTCUSDT");
+    assert!(auditor.watched().is_empty());
+}
+
+#[tokio::test]
+async fn gap_audit_confirms_ranges_the_venue_has_no_bars_for() {
+    let server = MockServer::start().await;
+    mock_history(&server).await;
+    let store = Arc::new(MemoryCandleStore::default());
+    let start = range().0;
+    backfiller(&server, store.clone())
+        .backfill("BTC", "1h", start, start + chrono::Duration::days(1))
+        .await
+        .expect("backfill");
+    let last = {
+        let mut candles = store.candles.lock().await;
+        candles.remove(3);
+        candles.last().unwrap().timestamp
+    };
+    let now = last + chrono::Duration::minutes(30);
+
+    // A failing venue confirms nothing; the gap is retried on the next audit
+    let failing = Arc::new(EmptyFiller {
+        failing: true,
+        ..EmptyFiller::default()
+    });
+    let auditor =
+        CandleGapAuditor::new(store.clone()).with_filler(DEFAULT_VENUE, failing.clone());
+    let report = auditor.audit("BTC", "1h", now).await.expect("audit");
+    assert_eq!(report.confirmed_empty_bars, 0);
+    assert_eq!(report.missing_bars, 1);
+    assert_eq!(auditor.audit("BTC", "1h", now).await.unwrap().gaps.len(), 1);
+    assert_eq!(failing.calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+
+    // A venue that answers with no bars confirms the range empty
+    let filler = Arc::new(EmptyFiller::default());
+    let empty_ranges = Arc::new(MemoryEmptyRangeStore::default());
+    let metrics = Arc::new(Metrics::new().unwrap());
+    let auditor = CandleGapAuditor::new(store.clone())
+        .with_filler(DEFAULT_VENUE, filler.clone())
+        .with_empty_ranges(empty_ranges.clone())
+        .with_metrics(metrics.clone());
+    let report = auditor.audit("BTC", "1h", now).await.expect("audit");
+    assert_eq!(report.gaps.len(), 1);
+    assert_eq!(report.repaired_bars, 0);
+    assert_eq!(report.confirmed_empty_bars, 1);
+    assert_eq!(report.missing_bars, 0);
+    assert_eq!(
+        metrics
+            .candle_missing_bars
+            .with_label_values(&["BTC", "1h"])
+            .get(),
+        0.0
+    );
+
+    // The range is neither fetched again nor a gap for the worker
+    let report = auditor.audit("BTC", "1h", now).await.expect("audit");
+    assert!(report.gaps.is_empty());
+    assert_eq!(filler.calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+    let confirmed = empty_ranges.load_empty_ranges("BTC", "1h").await.unwrap();
+    assert_eq!(confirmed.len(), 1);
+    let candles = store.candles.lock().await.clone();
+    assert_eq!(find_gaps(&candles, HOUR_MS, &[]).len(), 1);
+    assert!(recent_gaps(&candles, HOUR_MS, now, &confirmed).is_empty());
+}
@@ -0,0 +1,235 @@
+//! Integration tests for Binance USDⓈ-M market data
+//!
+//! Serves the futures REST endpoints from wiremock and feeds kline events
+//! through the mock WebSocket client, reading through the venue registry.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use perptrix::services::binance::{BinanceMarketDataProvider, BinanceRestClient, BINANCE_VENUE};
+use perptrix::services::hyperliquid::client::ClientEvent;
+use perptrix::services::hyperliquid::MockWebSocketClient;
+use perptrix::services::market_data::{
+    MarketDataProvider, MarketDataRegistry, PlaceholderMarketDataProvider,
+};
+use serde_json::json;
+use tokio_tungstenite::tungstenite::Message;
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const OPEN_TIME: i64 = 1_700_000_000_000;
+
+fn kline(open_time: i64, close: &str) -> serde_json::Value {
+    json!([
+        open_time,
+        "100.0",
+        "110.0",
+        "90.0",
+        close,
+        "12.5",
+        open_time + 59_999,
+        "1250.0",
+        42,
+        "6.0",
+        "600.0",
+        "0"
+    ])
+}
+
+async fn mock_rest() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/fapi/v1/klines"))
+        .and(query_param("symbol", "BTCUSDT"))
+        .and(query_param("interval", "1m"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            kline(OPEN_TIME, "105.0"),
+            kline(OPEN_TIME + 60_000, "106.0")
+        ])))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/fapi/v1/fundingRate"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            { "symbol": "BTCUSDT", "fundingRate": "0.00010000", "fundingTime": OPEN_TIME - 3_600_000, "markPrice": "100.0" }
+        ])))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/fapi/v1/premiumIndex"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "symbol": "BTCUSDT",
+            "markPrice": "107.0",
+            "lastFundingRate": "0.00020000",
+            "nextFundingTime": OPEN_TIME + 28_800_000,
+            "time": OPEN_TIME + 120_000
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/fapi/v1/openInterest"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "openInterest": "10659.509",
+            "symbol": "BTCUSDT",
+            "time": OPEN_TIME
+        })))
+        .mount(&server)
+        .await;
+    server
+}
+
+fn rest_client(server: &MockServer) -> Arc<BinanceRestClient> {
+    Arc::new(BinanceRestClient::with_client(
+        server.uri(),
+        reqwest::Client::new(),
+    ))
+}
+
+#[tokio::test]
+async fn rest_klines_funding_and_open_interest_are_parsed() {
+    let server = mock_rest().await;
+    let rest = rest_client(&server);
+
+    let candles = rest
+        .fetch_historical_candles("BTCUSDT", "1m", 2)
+        .await
+        .unwrap();
+    assert_eq!(candles.len(), 2);
+    assert_eq!(candles[0].close, 105.0);
+    assert_eq!(candles[0].volume, 12.5);
+    // Stamped with the close time, like Hyperliquid candles
+    assert_eq!(
+        candles[0].timestamp.timestamp(),
+        (OPEN_TIME + 59_999) / 1000
+    );
+
+    let funding = rest
+        .fetch_funding_history("BTCUSDT", 0, OPEN_TIME as u64)
+        .await
+        .unwrap();
+    assert_eq!(funding.len(), 1);
+    assert_eq!(funding[0].funding_rate, 0.0001);
+
+    let latest = rest.fetch_latest_funding_rate("BTCUSDT").await.unwrap();
+    assert_eq!(latest.funding_rate, 0.0002);
+    assert_eq!(
+        rest.fetch_open_interest("BTCUSDT").await.unwrap(),
+        10659.509
+    );
+}
+
+#[tokio::test]
+async fn namespaced_symbols_stream_klines_from_binance() {
+    let server = mock_rest().await;
+    let websocket = Arc::new(MockWebSocketClient::new());
+    let binance = BinanceMarketDataProvider::with_clients(
+        websocket.clone(),
+        rest_client(&server),
+        vec!["1m".to_string()],
+    );
+    let registry = MarketDataRegistry::new(Arc::new(PlaceholderMarketDataProvider))
+        .with_provider(BINANCE_VENUE, Arc::new(binance));
+
+    registry.subscribe("binance:BTCUSDT").await.unwrap();
+
+    // History is loaded with funding attached
+    let candles = registry.get_candles("binance:BTCUSDT", 10).await.unwrap();
+    assert_eq!(candles.len(), 2);
+    assert!(candles.iter().all(|c| c.funding_rate == Some(0.0001)));
+
+    // The kline stream is subscribed once connected
+    let mut subscribed = false;
+    for _ in 0..100 {
+        subscribed = websocket.sent_messages().await.iter().any(|m| {
+            matches!(m, Message::Text(text)
+                if text.contains("\"SUBSCRIBE\"") && text.contains("btcusdt@kline_1m"))
+        });
+        if subscribed {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert!(subscribed, "kline stream subscription was not sent");
+
+    let event = json!({
+        "e": "kline",
+        "E": OPEN_TIME + 120_500,
+        "s": "BTCUSDT",
+        "k": {
+            "t": OPEN_TIME + 120_000,
+            "T": OPEN_TIME + 179_999,
+            "s": "BTCUSDT",
+            "i": "1m",
+            "o": "106.0",
+            "c": "107.5",
+            "h": "108.0",
+            "l": "105.5",
+            "v": "3.0",
+            "x": true
+        }
+    });
+    websocket
+        .push_event(ClientEvent::Message(event.to_string()))
+        .await;
+
+    let mut candles = Vec::new();
+    for _ in 0..100 {
+        candles = registry.get_candles("binance:BTCUSDT", 10).await.unwrap();
+        if candles.len() == 3 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(candles.len(), 3);
+    let live = candles.last().unwrap();
+    assert_eq!(live.close, 107.5);
+    assert_eq!(live.funding_rate, Some(0.0002));
+    assert_eq!(live.open_interest, Some(10659.509));
+    assert_eq!(
+        registry.get_latest_price("binance:BTCUSDT").await.unwrap(),
+        107.5
+    );
+}
+
+#[tokio::test]
+async fn unsubscribed_streams_are_not_resubscribed_on_reconnect() {
+    let server = mock_rest().await;
+    let websocket = Arc::new(MockWebSocketClient::new());
+    let binance = BinanceMarketDataProvider::with_clients(
+        websocket.clone(),
+        rest_client(&server),
+        vec!["1m".to_string()],
+    );
+    let registry = MarketDataRegistry::new(Arc::new(PlaceholderMarketDataProvider))
+        .with_provider(BINANCE_VENUE, Arc::new(binance));
+    let requests = |method: &'static str| {
+        let websocket = websocket.clone();
+        async move {
+            websocket
+                .sent_messages()
+                .await
+                .iter()
+                .filter(|m| {
+                    matches!(m, Message::Text(text)
+                        if text.contains(&format!("\"{}\"", method)) && text.contains("btcusdt@kline_1m"))
+                })
+                .count()
+        }
+    };
+
+    registry.subscribe("binance:BTCUSDT").await.unwrap();
+    for _ in 0..100 {
+        if requests("SUBSCRIBE").await == 1 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(requests("SUBSCRIBE").await, 1);
+
+    registry.unsubscribe("binance:btcusdt").await.unwrap();
+    assert_eq!(requests("UNSUBSCRIBE").await, 1);
+
+    websocket.push_event(ClientEvent::Connected).await;
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert_eq!(requests("SUBSCRIBE").await, 1);
+}
@@ -0,0 +1,586 @@
+//! Integration tests for Hyperliquid order execution
+//!
+//! Serves `meta`/`frontendOpenOrders`/`clearinghouseState` info responses and a stand-in
+//! `/exchange` endpoint from wiremock, and checks the signed action bodies.
+
+use perptrix::execution::{
+    ExchangeExecutor, ExchangeOrderState, ExecutionError, OrderGrouping, OrderRequest, OrderSide,
+    OrderStatus, OrderType, TimeInForce,
+};
+use perptrix::services::hyperliquid::exchange::{
+    ExchangeRequest, HyperliquidAction, OrderTypeWire,
+};
+use perptrix::services::hyperliquid::signing::{action_hash, l1_action_digest, recover_address};
+use perptrix::services::hyperliquid::{HyperliquidExecutor, HyperliquidSigner};
+use serde_json::{json, Value};
+use wiremock::matchers::{body_partial_json, method, path};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+const AGENT_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+const AGENT_ADDRESS: &str = "0x2c7536e3605d9c16a7a3d7b1898e529396a65c23";
+const ACCOUNT: &str = "0x00000000000000000000000000000000000000aa";
+
+async fn mock_meta(server: &MockServer) {
+    Mock::given(method("POST"))
+        .and(path("/info"))
+        .and(body_partial_json(json!({ "type": "meta" })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "universe": [
+                { "name": "BTC", "szDecimals": 5, "maxLeverage": 50 },
+                { "name": "ETH", "szDecimals": 4, "maxLeverage": 50 }
+            ]
+        })))
+        .mount(server)
+        .await;
+}
+
+async fn mock_exchange(server: &MockServer, response: Value) {
+    Mock::given(method("POST"))
+        .and(path("/exchange"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(response))
+        .mount(server)
+        .await;
+}
+
+fn executor(server: &MockServer) -> HyperliquidExecutor {
+    HyperliquidExecutor::with_client(
+        server.uri(),
+        reqwest::Client::new(),
+        HyperliquidSigner::from_hex(AGENT_KEY).unwrap(),
+        ACCOUNT,
+    )
+    .with_mainnet(false)
+}
+
+/// Signed requests received by the `/exchange` stand-in
+async fn exchange_requests(server: &MockServer) -> Vec<ExchangeRequest> {
+    server
+        .received_requests()
+        .await
+        .unwrap_or_default()
+        .iter()
+        .filter(|r: &&Request| r.url.path() == "/exchange")
+        .map(|r| serde_json::from_slice(&r.body).expect("exchange request body"))
+        .collect()
+}
+
+fn assert_signed_by_agent(request: &ExchangeRequest) {
+    let hash = action_hash(
+        &request.action,
+        request.nonce,
+        request.vault_address.as_deref(),
+    )
+    .unwrap();
+    let digest = l1_action_digest(&hash, false);
+    assert_eq!(
+        recover_address(&digest, &request.signature).unwrap(),
+        AGENT_ADDRESS
+    );
+}
+
+#[tokio::test]
+async fn place_limit_order_signs_and_returns_resting_ack() {
+    let server = MockServer::start().await;
+    mock_meta(&server).await;
+    mock_exchange(
+        &server,
+        json!({
+            "status": "ok",
+            "response": { "type": "order", "data": { "statuses": [ { "resting": { "oid": 77738308 } } ] } }
+        }),
+    )
+    .await;
+
+    let executor = executor(&server);
+    let order = OrderRequest::limit("ETH", OrderSide::Buy, 0.123456, 1891.456)
+        .with_order_type(OrderType::Limit(TimeInForce::Alo))
+        .with_client_order_id("0x1234567890abcdef1234567890ABCDEF");
+    let ack = executor.place_order(&order).await.unwrap();
+    assert_eq!(ack.order_id, Some(77738308));
+    assert_eq!(ack.status, OrderStatus::Resting);
+    assert_eq!(ack.filled_size, 0.0);
+
+    let requests = exchange_requests(&server).await;
+    assert_eq!(requests.len(), 1);
+    let request = &requests[0];
+    assert!(request.vault_address.is_none());
+    assert_signed_by_agent(request);
+
+    let HyperliquidAction::Order { orders, grouping } = &request.action else {
+        panic!("expected order action, got {:?}", request.action);
+    };
+    assert_eq!(grouping, "na");
+    let wire = &orders[0];
+    assert_eq!(wire.a, 1);
+    assert!(wire.b);
+    // 5 significant figures, sizes at the asset's 4 decimals
+    assert_eq!(wire.p, "1891.5");
+    assert_eq!(wire.s, "0.1235");
+    assert!(!wire.r);
+    assert!(matches!(&wire.t, OrderTypeWire::Limit(limit) if limit.tif == "Alo"));
+    assert_eq!(
+        wire.c.as_deref(),
+        Some("0x1234567890abcdef1234567890abcdef")
+    );
+
+    // Raw JSON uses the exchange's field names
+    let raw: Value = serde_json::from_slice(
+        &server
+            .received_requests()
+            .await
+            .unwrap()
+            .last()
+            .unwrap()
+            .body,
+    )
+    .unwrap();
+    assert_eq!(raw["action"]["type"], "order");
+    assert_eq!(raw["action"]["orders"][0]["t"]["limit"]["tif"], "Alo");
+    assert!(raw["signature"]["r"].as_str().unwrap().starts_with("0x"));
+    assert!(raw.get("vaultAddress").is_some());
+}
+
+#[tokio::test]
+async fn market_and_stop_orders_use_ioc_and_triggers() {
+    let server = MockServer::start().await;
+    mock_meta(&server).await;
+    mock_exchange(
+        &server,
+        json!({
+            "status": "ok",
+            "response": { "type": "order", "data": { "statuses": [
+                { "filled": { "totalSz": "0.02", "avgPx": "43250.5", "oid": 77747314 } }
+            ] } }
+        }),
+    )
+    .await;
+
+    let executor = executor(&server);
+    let ack = executor
+        .place_order(&OrderRequest::market("BTC", OrderSide::Sell, 0.02, 43000.0))
+        .await
+        .unwrap();
+    assert_eq!(ack.status, OrderStatus::Filled);
+    assert_eq!(ack.filled_size, 0.02);
+    assert_eq!(ack.avg_fill_price, Some(43250.5));
+
+    let stop = OrderRequest::market("BTC", OrderSide::Sell, 0.02, 40000.0)
+        .with_order_type(OrderType::StopMarket {
+            trigger_price: 41000.0,
+        })
+        .with_reduce_only(true);
+    executor.place_order(&stop).await.unwrap();
+
+    let requests = exchange_requests(&server).await;
+    assert_eq!(requests.len(), 2);
+    assert!(requests[1].nonce > requests[0].nonce);
+
+    let HyperliquidAction::Order { orders, .. } = &requests[0].action else {
+        panic!("expected order action");
+    };
+    assert!(!orders[0].b);
+    assert!(matches!(&orders[0].t, OrderTypeWire::Limit(limit) if limit.tif == "Ioc"));
+
+    let HyperliquidAction::Order { orders, .. } = &requests[1].action else {
+        panic!("expected order action");
+    };
+    assert!(orders[0].r);
+    match &orders[0].t {
+        OrderTypeWire::Trigger(trigger) => {
+            assert!(trigger.is_market);
+            assert_eq!(trigger.trigger_px, "41000");
+            assert_eq!(trigger.tpsl, "sl");
+        }
+        other => panic!("expected trigger, got {:?}", other),
+    }
+    for request in &requests {
+        assert_signed_by_agent(request);
+    }
+}
+
+#[tokio::test]
+async fn grouped_orders_are_sent_in_one_action() {
+    let server = MockServer::start().await;
+    mock_meta(&server).await;
+    mock_exchange(
+        &server,
+        json!({
+            "status": "ok",
+            "response": { "type": "order", "data": { "statuses": [
+                { "filled": { "totalSz": "0.02", "avgPx": "43000", "oid": 100 } },
+                "waitingForFill",
+                { "error": "Invalid TP/SL price." }
+            ] } }
+        }),
+    )
+    .await;
+
+    let executor = executor(&server);
+    let orders = vec![
+        OrderRequest::market("BTC", OrderSide::Buy, 0.02, 43500.0),
+        OrderRequest::market("BTC", OrderSide::Sell, 0.02, 41000.0)
+            .with_order_type(OrderType::StopMarket {
+                trigger_price: 42000.0,
+            })
+            .with_reduce_only(true),
+        OrderRequest::limit("BTC", OrderSide::Sell, 0.02, 45000.0)
+            .with_order_type(OrderType::TakeProfitLimit {
+                trigger_price: 45000.0,
+            })
+            .with_reduce_only(true),
+    ];
+    let results = executor
+        .place_orders(&orders, OrderGrouping::NormalTpsl)
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].as_ref().unwrap().status, OrderStatus::Filled);
+    // Legs waiting for the entry are accepted without an order id
+    let waiting = results[1].as_ref().unwrap();
+    assert_eq!(waiting.status, OrderStatus::Waiting);
+    assert_eq!(waiting.order_id, None);
+    assert!(matches!(results[2], Err(ExecutionError::Rejected(_))));
+
+    let requests = exchange_requests(&server).await;
+    assert_eq!(requests.len(), 1);
+    assert_signed_by_agent(&requests[0]);
+    let HyperliquidAction::Order { orders, grouping } = &requests[0].action else {
+        panic!("expected order action");
+    };
+    assert_eq!(grouping, "normalTpsl");
+    assert_eq!(orders.len(), 3);
+    match &orders[2].t {
+        OrderTypeWire::Trigger(trigger) => {
+            assert!(!trigger.is_market);
+            assert_eq!(trigger.tpsl, "tp");
+            assert_eq!(trigger.trigger_px, "45000");
+        }
+        other => panic!("expected trigger, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn order_errors_are_rejections() {
+    let server = MockServer::start().await;
+    mock_meta(&server).await;
+    mock_exchange(
+        &server,
+        json!({
+            "status": "ok",
+            "response": { "type": "order", "data": { "statuses": [
+                { "error": "Order must have minimum value of $10." }
+            ] } }
+        }),
+    )
+    .await;
+
+    let executor = executor(&server);
+    let result = executor
+        .place_order(&OrderRequest::limit("BTC", OrderSide::Buy, 0.0001, 40000.0))
+        .await;
+    assert!(
+        matches!(&result, Err(ExecutionError::Rejected(msg)) if msg.contains("minimum value")),
+        "{:?}",
+        result
+    );
+
+    // Validation happens before anything is signed or sent
+    let result = executor
+        .place_order(&OrderRequest::limit("DOGE", OrderSide::Buy, 1.0, 0.1))
+        .await;
+    assert!(matches!(result, Err(ExecutionError::UnknownSymbol(_))));
+    let result = executor
+        .place_order(&OrderRequest::limit(
+            "BTC",
+            OrderSide::Buy,
+            0.000001,
+            40000.0,
+        ))
+        .await;
+    assert!(matches!(result, Err(ExecutionError::InvalidOrder(_))));
+    let result = executor
+        .place_order(
+            &OrderRequest::limit("BTC", OrderSide::Buy, 0.1, 40000.0).with_client_order_id("abc"),
+        )
+        .await;
+    assert!(matches!(result, Err(ExecutionError::InvalidOrder(_))));
+    assert_eq!(exchange_requests(&server).await.len(), 1);
+}
+
+#[tokio::test]
+async fn exchange_err_status_is_rejection() {
+    let server = MockServer::start().await;
+    mock_meta(&server).await;
+    mock_exchange(
+        &server,
+        json!({ "status": "err", "response": "User or API Wallet does not exist." }),
+    )
+    .await;
+
+    let result = executor(&server).set_leverage("BTC", 10, true).await;
+    assert!(
+        matches!(&result, Err(ExecutionError::Rejected(msg)) if msg.contains("does not exist")),
+        "{:?}",
+        result
+    );
+}
+
+#[tokio::test]
+async fn cancel_modify_and_leverage_actions() {
+    let server = MockServer::start().await;
+    mock_meta(&server).await;
+    Mock::given(method("POST"))
+        .and(path("/exchange"))
+        .and(body_partial_json(json!({ "action": { "type": "cancel" } })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "status": "ok",
+            "response": { "type": "cancel", "data": { "statuses": ["success"] } }
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/exchange"))
+        .and(body_partial_json(json!({ "action": { "type": "batchModify" } })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "status": "ok",
+            "response": { "type": "order", "data": { "statuses": [ { "resting": { "oid": 42 } } ] } }
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/exchange"))
+        .and(body_partial_json(
+            json!({ "action": { "type": "updateLeverage" } }),
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "status": "ok",
+            "response": { "type": "default" }
+        })))
+        .mount(&server)
+        .await;
+
+    let vault = "0x1719884eb866cb12b2287399b15f7db5e7d775ea";
+    let executor = executor(&server).with_vault_address(vault);
+    executor.cancel_order("ETH", 77738308).await.unwrap();
+    let ack = executor
+        .modify_order(
+            41,
+            &OrderRequest::limit("BTC", OrderSide::Buy, 0.5, 42000.0),
+        )
+        .await
+        .unwrap();
+    assert_eq!(ack.order_id, Some(42));
+    executor.set_leverage("ETH", 7, false).await.unwrap();
+
+    let requests = exchange_requests(&server).await;
+    assert_eq!(requests.len(), 3);
+    for request in &requests {
+        assert_eq!(request.vault_address.as_deref(), Some(vault));
+        assert_signed_by_agent(request);
+    }
+
+    match &requests[0].action {
+        HyperliquidAction::Cancel { cancels } => {
+            assert_eq!(cancels.len(), 1);
+            assert_eq!((cancels[0].a, cancels[0].o), (1, 77738308));
+        }
+        other => panic!("expected cancel, got {:?}", other),
+    }
+    match &requests[1].action {
+        HyperliquidAction::BatchModify { modifies } => {
+            assert_eq!(modifies[0].oid, 41);
+            assert_eq!(modifies[0].order.a, 0);
+            assert_eq!(modifies[0].order.p, "42000");
+        }
+        other => panic!("expected batchModify, got {:?}", other),
+    }
+    assert_eq!(
+        requests[2].action,
+        HyperliquidAction::UpdateLeverage {
+            asset: 1,
+            is_cross: false,
+            leverage: 7,
+        }
+    );
+
+    let raw: Value = serde_json::from_slice(
+        &server
+            .received_requests()
+            .await
+            .unwrap()
+            .last()
+            .unwrap()
+            .body,
+    )
+    .unwrap();
+    assert_eq!(
+        raw["action"],
+        json!({ "type": "updateLeverage", "asset": 1, "isCross": false, "leverage": 7 })
+    );
+}
+
+#[tokio::test]
+async fn open_orders_and_positions_are_parsed() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/info"))
+        .and(body_partial_json(json!({ "type": "frontendOpenOrders", "user": ACCOUNT })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            { "coin": "BTC", "limitPx": "29792.0", "oid": 91490942, "side": "A", "sz": "0.01", "timestamp": 1681247412573u64,
+              "reduceOnly": true, "isTrigger": false, "orderType": "Limit" },
+            { "coin": "ETH", "limitPx": "1800.5", "oid": 91490943, "side": "B", "sz": "1.5", "timestamp": 1681247412574u64,
+              "cloid": "0x1234567890abcdef1234567890abcdef" }
+        ])))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/info"))
+        .and(body_partial_json(json!({ "type": "clearinghouseState", "user": ACCOUNT })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "assetPositions": [
+                { "type": "oneWay", "position": {
+                    "coin": "BTC", "szi": "-0.5", "entryPx": "43000.0", "unrealizedPnl": "125.5",
+                    "leverage": { "type": "cross", "value": 10 }, "liquidationPx": "51000.0",
+                    "positionValue": "21437.25", "marginUsed": "2143.7", "returnOnEquity": "0.05"
+                } },
+                { "type": "oneWay", "position": {
+                    "coin": "ETH", "szi": "2.0", "entryPx": "1800.0", "unrealizedPnl": "-10.0",
+                    "leverage": { "type": "isolated", "value": 5, "rawUsd": "-3000" }, "liquidationPx": null
+                } }
+            ],
+            "marginSummary": { "accountValue": "10000.0" },
+            "withdrawable": "8000.0"
+        })))
+        .mount(&server)
+        .await;
+
+    let executor = executor(&server);
+    let orders = executor.open_orders().await.unwrap();
+    assert_eq!(orders.len(), 2);
+    assert_eq!(orders[0].symbol, "BTC");
+    assert_eq!(orders[0].side, OrderSide::Sell);
+    assert_eq!(orders[0].price, 29792.0);
+    assert_eq!(orders[0].size, 0.01);
+    assert_eq!(orders[0].created_at.timestamp_millis(), 1681247412573);
+    assert!(orders[0].reduce_only);
+    assert_eq!(orders[1].side, OrderSide::Buy);
+    assert!(!orders[1].reduce_only);
+    assert_eq!(
+        orders[1].client_order_id.as_deref(),
+        Some("0x1234567890abcdef1234567890abcdef")
+    );
+
+    let positions = executor.positions().await.unwrap();
+    assert_eq!(positions.len(), 2);
+    assert_eq!(positions[0].symbol, "BTC");
+    assert_eq!(positions[0].size, -0.5);
+    assert_eq!(positions[0].entry_price, 43000.0);
+    assert_eq!(positions[0].unrealized_pnl, 125.5);
+    assert_eq!(positions[0].leverage, 10.0);
+    assert_eq!(positions[0].liquidation_price, Some(51000.0));
+    assert_eq!(positions[1].liquidation_price, None);
+    assert_eq!(executor.account_equity().await.unwrap(), 10000.0);
+}
+
+#[tokio::test]
+async fn order_status_and_fills_are_parsed() {
+    let server = MockServer::start().await;
+    let cloid = "0x1234567890abcdef1234567890abcdef";
+    Mock::given(method("POST"))
+        .and(path("/info"))
+        .and(body_partial_json(
+            json!({ "type": "orderStatus", "user": ACCOUNT, "oid": cloid }),
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "status": "order",
+            "order": {
+                "order": {
+                    "coin": "ETH", "side": "B", "limitPx": "1800.0", "sz": "0.5", "oid": 555,
+                    "timestamp": 1700000000000u64, "origSz": "2.0", "cloid": cloid
+                },
+                "status": "marginCanceled",
+                "statusTimestamp": 1700000005000u64
+            }
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/info"))
+        .and(body_partial_json(
+            json!({ "type": "orderStatus", "oid": "0xmissing" }),
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "status": "unknownOid" })))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/info"))
+        .and(body_partial_json(
+            json!({ "type": "userFillsByTime", "user": ACCOUNT, "startTime": 1700000000000u64 }),
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            { "coin": "ETH", "px": "1801.0", "sz": "1.0", "side": "B", "time": 1700000003000u64,
+              "startPosition": "0.5", "dir": "Open Long", "closedPnl": "0.0", "hash": "0x00",
+              "oid": 555, "crossed": true, "fee": "0.81", "tid": 9002, "feeToken": "USDC" },
+            { "coin": "ETH", "px": "1800.0", "sz": "0.5", "side": "B", "time": 1700000001000u64,
+              "startPosition": "0.0", "dir": "Open Long", "closedPnl": "0.0", "hash": "0x00",
+              "oid": 555, "crossed": false, "fee": "-0.05", "tid": 9001, "cloid": cloid }
+        ])))
+        .mount(&server)
+        .await;
+
+    let executor = executor(&server);
+    let report = executor.order_status(cloid).await.unwrap().unwrap();
+    assert_eq!(report.order_id, 555);
+    assert_eq!(report.state, ExchangeOrderState::Cancelled);
+    assert_eq!(report.filled_size(), 1.5);
+    assert_eq!(report.updated_at.timestamp_millis(), 1700000005000);
+    assert!(executor.order_status("0xmissing").await.unwrap().is_none());
+
+    let since = chrono::DateTime::from_timestamp_millis(1700000000000).unwrap();
+    let fills = executor.fills_since(since).await.unwrap();
+    assert_eq!(fills.len(), 2);
+    // Oldest first
+    assert_eq!(fills[0].trade_id, 9001);
+    assert_eq!(fills[0].client_order_id.as_deref(), Some(cloid));
+    assert_eq!(fills[0].fee, -0.05);
+    assert_eq!(fills[1].size, 1.0);
+    assert_eq!(fills[1].price, 1801.0);
+    assert_eq!(fills[1].side, OrderSide::Buy);
+}
+
+#[tokio::test]
+async fn top_of_book_reads_best_levels() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/info"))
+        .and(body_partial_json(json!({ "type": "l2Book", "coin": "BTC" })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "coin": "BTC",
+            "time": 1700000000000u64,
+            "levels": [
+                [ { "px": "43000.0", "sz": "1.2", "n": 3 }, { "px": "42999.0", "sz": "0.5", "n": 1 } ],
+                [ { "px": "43001.0", "sz": "0.8", "n": 2 } ]
+            ]
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/info"))
+        .and(body_partial_json(json!({ "type": "l2Book", "coin": "ETH" })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "coin": "ETH", "time": 1700000000000u64, "levels": [[], []]
+        })))
+        .mount(&server)
+        .await;
+
+    let executor = executor(&server);
+    let book = executor.top_of_book("BTC").await.unwrap();
+    assert_eq!(book.bid, 43000.0);
+    assert_eq!(book.ask, 43001.0);
+    assert_eq!(book.touch(OrderSide::Buy), 43000.0);
+    assert_eq!(book.far_touch(OrderSide::Buy), 43001.0);
+    assert!(matches!(
+        executor.top_of_book("ETH").await,
+        Err(ExecutionError::InvalidResponse(_))
+    ));
+}
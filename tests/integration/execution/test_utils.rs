@@ -0,0 +1,575 @@
+//! In-memory exchange, order store and position store shared by execution tests
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use perptrix::db::{OrderStore, PositionStore};
+use perptrix::execution::{
+    ExchangeExecutor, ExchangeFill, ExchangeOrderReport, ExchangeOrderState, ExchangePosition,
+    ExecutionError, ManagedOrder, OpenOrder, OrderAck, OrderFill, OrderGrouping, OrderRequest, OrderSide,
+    OrderStatus, OrderType, TimeInForce, TopOfBook, TrackedPosition,
+};
+
+/// How the mock exchange answers `place_order`
+#[allow(dead_code)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlaceMode {
+    /// Accept and rest on the book
+    Rest,
+    /// Fill completely at the order price; trigger orders rest
+    Fill,
+    Reject(String),
+    /// Accept the order but lose the response
+    LoseResponse,
+    /// Fail before the order reaches the exchange
+    Drop,
+    /// Match against the book set with `set_book`: crossing orders fill at the
+    /// far touch, IOC orders that do not cross and crossing post-only orders
+    /// are rejected, the rest rests
+    Book,
+}
+
+struct MockState {
+    mode: PlaceMode,
+    next_order_id: u64,
+    next_trade_id: u64,
+    placed: Vec<OrderRequest>,
+    groupings: Vec<OrderGrouping>,
+    /// Grouped TP/SL legs held until their resting entry fills, by entry id
+    waiting: Vec<(u64, OrderRequest)>,
+    cancelled: Vec<u64>,
+    reports: HashMap<u64, ExchangeOrderReport>,
+    requests: HashMap<u64, OrderRequest>,
+    fills: Vec<ExchangeFill>,
+    positions: Vec<ExchangePosition>,
+    equity: f64,
+    mids: HashMap<String, f64>,
+    books: HashMap<String, TopOfBook>,
+    leverage: Vec<(String, u32, bool)>,
+}
+
+/// Exchange stand-in that keeps orders, fills and positions in memory
+pub struct MockExecutor {
+    state: Mutex<MockState>,
+}
+
+#[allow(dead_code)]
+impl MockExecutor {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(MockState {
+                mode: PlaceMode::Rest,
+                next_order_id: 1000,
+                next_trade_id: 1,
+                placed: Vec::new(),
+                groupings: Vec::new(),
+                waiting: Vec::new(),
+                cancelled: Vec::new(),
+                reports: HashMap::new(),
+                requests: HashMap::new(),
+                fills: Vec::new(),
+                positions: Vec::new(),
+                equity: 100_000.0,
+                mids: HashMap::new(),
+                books: HashMap::new(),
+                leverage: Vec::new(),
+            }),
+        }
+    }
+
+    pub fn set_mode(&self, mode: PlaceMode) {
+        self.state.lock().unwrap().mode = mode;
+    }
+
+    /// Every order request received, including rejected ones
+    pub fn placed(&self) -> Vec<OrderRequest> {
+        self.state.lock().unwrap().placed.clone()
+    }
+
+    /// Grouping of each `place_orders` call
+    pub fn groupings(&self) -> Vec<OrderGrouping> {
+        self.state.lock().unwrap().groupings.clone()
+    }
+
+    pub fn cancelled(&self) -> Vec<u64> {
+        self.state.lock().unwrap().cancelled.clone()
+    }
+
+    pub fn leverage_calls(&self) -> Vec<(String, u32, bool)> {
+        self.state.lock().unwrap().leverage.clone()
+    }
+
+    pub fn set_positions(&self, positions: Vec<ExchangePosition>) {
+        self.state.lock().unwrap().positions = positions;
+    }
+
+    pub fn set_equity(&self, equity: f64) {
+        self.state.lock().unwrap().equity = equity;
+    }
+
+    pub fn set_mid(&self, symbol: &str, mid: f64) {
+        self.state
+            .lock()
+            .unwrap()
+            .mids
+            .insert(symbol.to_string(), mid);
+    }
+
+    /// Move the book; resting orders the new touch crosses fill at their price
+    pub fn set_book(&self, symbol: &str, bid: f64, ask: f64) {
+        let mut state = self.state.lock().unwrap();
+        let book = TopOfBook { bid, ask };
+        state.books.insert(symbol.to_string(), book);
+        let mut crossed: Vec<(u64, f64, f64)> = state
+            .reports
+            .values()
+            .filter(|r| r.state == ExchangeOrderState::Open && r.symbol == symbol)
+            .filter_map(|r| {
+                let request = &state.requests[&r.order_id];
+                Self::crosses(request, &book).then_some((r.order_id, r.remaining_size, request.price))
+            })
+            .collect();
+        crossed.sort_by_key(|(order_id, _, _)| *order_id);
+        for (order_id, size, price) in crossed {
+            Self::execute(&mut state, order_id, size, price);
+        }
+    }
+
+    /// Exchange order id of a placed order by client order id
+    pub fn order_id(&self, client_order_id: &str) -> Option<u64> {
+        self.state
+            .lock()
+            .unwrap()
+            .reports
+            .values()
+            .find(|r| r.client_order_id.as_deref() == Some(client_order_id))
+            .map(|r| r.order_id)
+    }
+
+    /// Rest an order on the exchange that did not come through the code under test
+    pub fn add_external_order(&self, request: OrderRequest) -> u64 {
+        let mut state = self.state.lock().unwrap();
+        Self::rest(&mut state, request)
+    }
+
+    /// Execute `size` of a resting order at `price`
+    pub fn fill(&self, order_id: u64, size: f64, price: f64) -> ExchangeFill {
+        let mut state = self.state.lock().unwrap();
+        Self::execute(&mut state, order_id, size, price)
+    }
+
+    /// Cancel a resting order on the exchange side
+    pub fn cancel_on_exchange(&self, order_id: u64) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(report) = state.reports.get_mut(&order_id) {
+            report.state = ExchangeOrderState::Cancelled;
+            report.updated_at = Utc::now();
+        }
+        state.waiting.retain(|(entry, _)| *entry != order_id);
+    }
+
+    fn rest(state: &mut MockState, request: OrderRequest) -> u64 {
+        state.next_order_id += 1;
+        let order_id = state.next_order_id;
+        state.reports.insert(
+            order_id,
+            ExchangeOrderReport {
+                order_id,
+                client_order_id: request.client_order_id.clone(),
+                symbol: request.symbol.clone(),
+                state: ExchangeOrderState::Open,
+                original_size: request.size,
+                remaining_size: request.size,
+                updated_at: Utc::now(),
+            },
+        );
+        state.requests.insert(order_id, request);
+        order_id
+    }
+
+    /// Place the legs that waited for an entry once it has filled
+    fn place_waiting(state: &mut MockState, entry_id: u64) {
+        let (legs, waiting): (Vec<_>, Vec<_>) = std::mem::take(&mut state.waiting)
+            .into_iter()
+            .partition(|(entry, _)| *entry == entry_id);
+        state.waiting = waiting;
+        for (_, leg) in legs {
+            Self::rest(state, leg);
+        }
+    }
+
+    fn crosses(request: &OrderRequest, book: &TopOfBook) -> bool {
+        match request.side {
+            OrderSide::Buy => request.price >= book.ask,
+            OrderSide::Sell => request.price <= book.bid,
+        }
+    }
+
+    fn match_book(state: &mut MockState, order: &OrderRequest) -> Result<OrderAck, ExecutionError> {
+        let book = *state
+            .books
+            .get(&order.symbol)
+            .ok_or_else(|| ExecutionError::UnknownSymbol(order.symbol.clone()))?;
+        let crosses = Self::crosses(order, &book);
+        let takes_only = matches!(
+            order.order_type,
+            OrderType::Market | OrderType::Limit(TimeInForce::Ioc)
+        );
+        if takes_only && !crosses {
+            return Err(ExecutionError::Rejected(
+                "Order could not immediately match against any resting orders.".to_string(),
+            ));
+        }
+        if order.order_type == OrderType::Limit(TimeInForce::Alo) && crosses {
+            return Err(ExecutionError::Rejected(
+                "Post only order would have immediately matched.".to_string(),
+            ));
+        }
+
+        let order_id = Self::rest(state, order.clone());
+        if !crosses {
+            return Ok(OrderAck {
+                order_id: Some(order_id),
+                status: OrderStatus::Resting,
+                filled_size: 0.0,
+                avg_fill_price: None,
+            });
+        }
+        let price = book.far_touch(order.side);
+        Self::execute(state, order_id, order.size, price);
+        Ok(OrderAck {
+            order_id: Some(order_id),
+            status: OrderStatus::Filled,
+            filled_size: order.size,
+            avg_fill_price: Some(price),
+        })
+    }
+
+    fn execute(state: &mut MockState, order_id: u64, size: f64, price: f64) -> ExchangeFill {
+        let request = state.requests.get(&order_id).cloned().expect("known order");
+        let report = state.reports.get_mut(&order_id).expect("known order");
+        report.remaining_size = (report.remaining_size - size).max(0.0);
+        if report.remaining_size <= 1e-12 {
+            report.state = ExchangeOrderState::Filled;
+        }
+        report.updated_at = Utc::now();
+        if report.state == ExchangeOrderState::Filled {
+            Self::place_waiting(state, order_id);
+        }
+
+        state.next_trade_id += 1;
+        let fill = ExchangeFill {
+            trade_id: state.next_trade_id,
+            order_id,
+            client_order_id: request.client_order_id.clone(),
+            symbol: request.symbol.clone(),
+            side: request.side,
+            size,
+            price,
+            fee: size * price * 0.00045,
+            timestamp: Utc::now(),
+        };
+        state.fills.push(fill.clone());
+        fill
+    }
+}
+
+#[async_trait]
+impl ExchangeExecutor for MockExecutor {
+    fn name(&self) -> &str {
+        "mock"
+    }
+
+    async fn place_order(&self, order: &OrderRequest) -> Result<OrderAck, ExecutionError> {
+        let mut state = self.state.lock().unwrap();
+        state.placed.push(order.clone());
+        match state.mode.clone() {
+            PlaceMode::Rest => {
+                let order_id = Self::rest(&mut state, order.clone());
+                Ok(OrderAck {
+                    order_id: Some(order_id),
+                    status: OrderStatus::Resting,
+                    filled_size: 0.0,
+                    avg_fill_price: None,
+                })
+            }
+            PlaceMode::Fill if order.order_type.trigger_price().is_some() => {
+                let order_id = Self::rest(&mut state, order.clone());
+                Ok(OrderAck {
+                    order_id: Some(order_id),
+                    status: OrderStatus::Resting,
+                    filled_size: 0.0,
+                    avg_fill_price: None,
+                })
+            }
+            PlaceMode::Fill => {
+                let order_id = Self::rest(&mut state, order.clone());
+                Self::execute(&mut state, order_id, order.size, order.price);
+                Ok(OrderAck {
+                    order_id: Some(order_id),
+                    status: OrderStatus::Filled,
+                    filled_size: order.size,
+                    avg_fill_price: Some(order.price),
+                })
+            }
+            PlaceMode::Reject(reason) => Err(ExecutionError::Rejected(reason)),
+            PlaceMode::LoseResponse => {
+                Self::rest(&mut state, order.clone());
+                Err(ExecutionError::Transport("connection reset".to_string()))
+            }
+            PlaceMode::Drop => Err(ExecutionError::Transport("connection refused".to_string())),
+            PlaceMode::Book => Self::match_book(&mut state, order),
+        }
+    }
+
+    async fn place_orders(
+        &self,
+        orders: &[OrderRequest],
+        grouping: OrderGrouping,
+    ) -> Result<Vec<Result<OrderAck, ExecutionError>>, ExecutionError> {
+        self.state.lock().unwrap().groupings.push(grouping);
+        let mut results = Vec::with_capacity(orders.len());
+        let mut entry = None;
+        for order in orders {
+            // Legs of a resting grouped entry wait for it to fill
+            if let Some(entry_id) = entry {
+                let mut state = self.state.lock().unwrap();
+                state.placed.push(order.clone());
+                state.waiting.push((entry_id, order.clone()));
+                results.push(Ok(OrderAck {
+                    order_id: None,
+                    status: OrderStatus::Waiting,
+                    filled_size: 0.0,
+                    avg_fill_price: None,
+                }));
+                continue;
+            }
+            let result = self.place_order(order).await;
+            if grouping == OrderGrouping::NormalTpsl && results.is_empty() {
+                entry = result
+                    .as_ref()
+                    .ok()
+                    .filter(|ack| ack.status == OrderStatus::Resting)
+                    .and_then(|ack| ack.order_id);
+            }
+            results.push(result);
+        }
+        Ok(results)
+    }
+
+    async fn cancel_order(&self, _symbol: &str, order_id: u64) -> Result<(), ExecutionError> {
+        let mut state = self.state.lock().unwrap();
+        match state.reports.get_mut(&order_id) {
+            Some(report) if report.state == ExchangeOrderState::Open => {
+                report.state = ExchangeOrderState::Cancelled;
+                state.cancelled.push(order_id);
+                state.waiting.retain(|(entry, _)| *entry != order_id);
+                Ok(())
+            }
+            _ => Err(ExecutionError::Rejected(
+                "Order was never placed, already canceled, or filled.".to_string(),
+            )),
+        }
+    }
+
+    async fn modify_order(
+        &self,
+        order_id: u64,
+        order: &OrderRequest,
+    ) -> Result<OrderAck, ExecutionError> {
+        let mut state = self.state.lock().unwrap();
+        let report = state
+            .reports
+            .get_mut(&order_id)
+            .filter(|r| r.state == ExchangeOrderState::Open)
+            .ok_or_else(|| ExecutionError::Rejected("Cannot modify order".to_string()))?;
+        report.original_size = order.size;
+        report.remaining_size = order.size;
+        state.requests.insert(order_id, order.clone());
+        Ok(OrderAck {
+            order_id: Some(order_id),
+            status: OrderStatus::Resting,
+            filled_size: 0.0,
+            avg_fill_price: None,
+        })
+    }
+
+    async fn open_orders(&self) -> Result<Vec<OpenOrder>, ExecutionError> {
+        let state = self.state.lock().unwrap();
+        let mut orders: Vec<OpenOrder> = state
+            .reports
+            .values()
+            .filter(|r| r.state == ExchangeOrderState::Open)
+            .map(|r| {
+                let request = &state.requests[&r.order_id];
+                OpenOrder {
+                    order_id: r.order_id,
+                    symbol: r.symbol.clone(),
+                    side: request.side,
+                    size: r.remaining_size,
+                    price: request.price,
+                    client_order_id: r.client_order_id.clone(),
+                    reduce_only: request.reduce_only,
+                    created_at: r.updated_at,
+                }
+            })
+            .collect();
+        orders.sort_by_key(|o| o.order_id);
+        Ok(orders)
+    }
+
+    async fn order_status(
+        &self,
+        client_order_id: &str,
+    ) -> Result<Option<ExchangeOrderReport>, ExecutionError> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .reports
+            .values()
+            .find(|r| r.client_order_id.as_deref() == Some(client_order_id))
+            .cloned())
+    }
+
+    async fn fills_since(&self, since: DateTime<Utc>) -> Result<Vec<ExchangeFill>, ExecutionError> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .fills
+            .iter()
+            .filter(|f| f.timestamp >= since)
+            .cloned()
+            .collect())
+    }
+
+    async fn positions(&self) -> Result<Vec<ExchangePosition>, ExecutionError> {
+        Ok(self.state.lock().unwrap().positions.clone())
+    }
+
+    async fn account_equity(&self) -> Result<f64, ExecutionError> {
+        Ok(self.state.lock().unwrap().equity)
+    }
+
+    async fn mid_price(&self, symbol: &str) -> Result<f64, ExecutionError> {
+        self.state
+            .lock()
+            .unwrap()
+            .mids
+            .get(symbol)
+            .copied()
+            .ok_or_else(|| ExecutionError::UnknownSymbol(symbol.to_string()))
+    }
+
+    async fn top_of_book(&self, symbol: &str) -> Result<TopOfBook, ExecutionError> {
+        self.state
+            .lock()
+            .unwrap()
+            .books
+            .get(symbol)
+            .copied()
+            .ok_or_else(|| ExecutionError::UnknownSymbol(symbol.to_string()))
+    }
+
+    async fn set_leverage(
+        &self,
+        symbol: &str,
+        leverage: u32,
+        cross_margin: bool,
+    ) -> Result<(), ExecutionError> {
+        self.state
+            .lock()
+            .unwrap()
+            .leverage
+            .push((symbol.to_string(), leverage, cross_margin));
+        Ok(())
+    }
+}
+
+/// Order store that keeps every appended order row and fill in memory
+#[derive(Default)]
+pub struct MemoryOrderStore {
+    pub rows: tokio::sync::Mutex<Vec<ManagedOrder>>,
+    pub fills: tokio::sync::Mutex<Vec<OrderFill>>,
+}
+
+#[allow(dead_code)]
+impl MemoryOrderStore {
+    /// Persisted states of one order, oldest first
+    pub async fn history(&self, client_order_id: &str) -> Vec<ManagedOrder> {
+        self.rows
+            .lock()
+            .await
+            .iter()
+            .filter(|o| o.client_order_id == client_order_id)
+            .cloned()
+            .collect()
+    }
+}
+
+#[async_trait]
+impl OrderStore for MemoryOrderStore {
+    async fn load_orders(
+        &self,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<ManagedOrder>, Box<dyn std::error::Error + Send + Sync>> {
+        let rows = self.rows.lock().await;
+        let mut latest: Vec<ManagedOrder> = Vec::new();
+        for order in rows.iter().rev().filter(|o| o.created_at >= since) {
+            if !latest
+                .iter()
+                .any(|o| o.client_order_id == order.client_order_id)
+            {
+                latest.push(order.clone());
+            }
+        }
+        latest.sort_by_key(|o| o.created_at);
+        Ok(latest)
+    }
+
+    async fn save_order(
+        &self,
+        order: &ManagedOrder,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.rows.lock().await.push(order.clone());
+        Ok(())
+    }
+
+    async fn record_fill(
+        &self,
+        fill: &OrderFill,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.fills.lock().await.push(fill.clone());
+        Ok(())
+    }
+}
+
+/// Position store that keeps every appended snapshot in memory
+#[derive(Default)]
+pub struct MemoryPositionStore {
+    pub rows: tokio::sync::Mutex<Vec<TrackedPosition>>,
+}
+
+#[async_trait]
+impl PositionStore for MemoryPositionStore {
+    async fn load_positions(
+        &self,
+        account: &str,
+    ) -> Result<Vec<TrackedPosition>, Box<dyn std::error::Error + Send + Sync>> {
+        let rows = self.rows.lock().await;
+        let mut latest: Vec<TrackedPosition> = Vec::new();
+        for position in rows.iter().rev().filter(|p| p.account == account) {
+            if !latest.iter().any(|p| p.symbol == position.symbol) {
+                latest.push(position.clone());
+            }
+        }
+        Ok(latest)
+    }
+
+    async fn save_position(
+        &self,
+        position: &TrackedPosition,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.rows.lock().await.push(position.clone());
+        Ok(())
+    }
+}
@@ -1,8 +1,12 @@
 //! Integration tests for the Hyperliquid-powered HTTP stack.
 mod test_utils;
 
-use serde_json::Value;
+use perptrix::services::hyperliquid::HyperliquidRestClient;
+use perptrix::services::instruments::InstrumentRegistry;
+use serde_json::{json, Value};
 use tokio_tungstenite::tungstenite::Message;
+use wiremock::matchers::{body_string_contains, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
 
 use test_utils::TestApp;
 
@@ -60,3 +64,46 @@ async fn subscriptions_use_mocked_dependencies() {
     );
 }
 
+#[tokio::test]
+async fn instruments_are_loaded_from_meta_and_asset_ctxs() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/info"))
+        .and(body_string_contains("metaAndAssetCtxs"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            {
+                "universe": [
+                    { "name": "BTC", "szDecimals": 5, "maxLeverage": 40 },
+                    { "name": "kPEPE", "szDecimals": 0, "maxLeverage": 10 },
+                    { "name": "LUNA", "szDecimals": 1, "maxLeverage": 3, "isDelisted": true }
+                ]
+            },
+            [
+                { "markPx": "64321.5", "funding": "0.0000125", "openInterest": "1000.0" },
+                { "markPx": "0.012345", "funding": "0.0", "openInterest": "5.0" },
+                { "markPx": null, "funding": "0.0", "openInterest": "0.0" }
+            ]
+        ])))
+        .mount(&server)
+        .await;
+
+    let client = HyperliquidRestClient::with_client(server.uri(), reqwest::Client::new());
+    let instruments = client.fetch_instruments().await.expect("instruments");
+    assert_eq!(instruments.len(), 3);
+
+    let btc = &instruments[0];
+    assert_eq!(btc.symbol, "BTC");
+    assert_eq!(btc.size_decimals, 5);
+    assert_eq!(btc.max_leverage, 40);
+    assert_eq!(btc.tick_size, 1.0);
+    assert!(btc.listed);
+    assert_eq!(instruments[1].symbol, "kPEPE");
+    assert!((instruments[1].tick_size - 1e-6).abs() < 1e-12);
+    assert!(!instruments[2].listed);
+
+    let registry = InstrumentRegistry::new().with_instruments("hyperliquid", instruments);
+    assert_eq!(registry.normalize("btc-perp").unwrap(), "BTC");
+    assert_eq!(registry.normalize("KPEPE").unwrap(), "kPEPE");
+    assert!(registry.normalize("LUNA").is_err());
+    assert!(registry.normalize("DOGE").is_err());
+}
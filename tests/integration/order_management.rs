@@ -0,0 +1,252 @@
+//! Integration tests for the order management system
+//!
+//! Drives the OMS against an in-memory exchange and order store.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::mock_exchange::{MemoryOrderStore, MockExecutor, PlaceMode};
+use perptrix::execution::{
+    ExecutionError, OrderManager, OrderOrigin, OrderRequest, OrderSide, OrderState,
+};
+
+fn manager(executor: &Arc<MockExecutor>, store: &Arc<MemoryOrderStore>) -> OrderManager {
+    OrderManager::new(executor.clone(), store.clone())
+}
+
+fn buy(size: f64) -> OrderRequest {
+    OrderRequest::limit("BTC", OrderSide::Buy, size, 40000.0)
+}
+
+#[tokio::test]
+async fn submit_persists_each_transition_with_origin() {
+    let executor = Arc::new(MockExecutor::new());
+    let store = Arc::new(MemoryOrderStore::default());
+    let oms = manager(&executor, &store);
+
+    let order = oms
+        .submit(buy(1.0), OrderOrigin::new(3, 1_700_000_000_000))
+        .await
+        .unwrap();
+    assert_eq!(order.state, OrderState::Acknowledged);
+    assert_eq!(order.exchange, "mock");
+    assert_eq!(order.origin.strategy_id, Some(3));
+    assert_eq!(order.origin.signal_id, Some(1_700_000_000_000));
+    assert_eq!(
+        order.exchange_order_id,
+        executor.order_id(&order.client_order_id)
+    );
+
+    // The client order id generated by the OMS is what the exchange saw
+    let placed = executor.placed();
+    assert_eq!(placed.len(), 1);
+    assert_eq!(
+        placed[0].client_order_id.as_deref(),
+        Some(order.client_order_id.as_str())
+    );
+
+    let states: Vec<OrderState> = store
+        .history(&order.client_order_id)
+        .await
+        .iter()
+        .map(|o| o.state)
+        .collect();
+    assert_eq!(
+        states,
+        vec![
+            OrderState::Created,
+            OrderState::Submitted,
+            OrderState::Acknowledged
+        ]
+    );
+}
+
+#[tokio::test]
+async fn submit_is_idempotent_by_client_order_id() {
+    let executor = Arc::new(MockExecutor::new());
+    let store = Arc::new(MemoryOrderStore::default());
+    let oms = manager(&executor, &store);
+
+    let cloid = "0x0000000000000000000000000000abcd";
+    let first = oms
+        .submit(buy(1.0).with_client_order_id(cloid), OrderOrigin::manual())
+        .await
+        .unwrap();
+    let second = oms
+        .submit(
+            buy(1.0).with_client_order_id(cloid.to_uppercase().replace("0X", "0x")),
+            OrderOrigin::manual(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(first.client_order_id, cloid);
+    assert_eq!(second.client_order_id, cloid);
+    assert_eq!(executor.placed().len(), 1);
+    assert_eq!(oms.orders().await.len(), 1);
+}
+
+#[tokio::test]
+async fn immediate_fills_and_rejections_are_recorded() {
+    let executor = Arc::new(MockExecutor::new());
+    let store = Arc::new(MemoryOrderStore::default());
+    let oms = manager(&executor, &store);
+
+    executor.set_mode(PlaceMode::Fill);
+    let filled = oms.submit(buy(0.5), OrderOrigin::new(1, 1)).await.unwrap();
+    assert_eq!(filled.state, OrderState::Filled);
+    assert_eq!(filled.filled_size, 0.5);
+    assert_eq!(filled.avg_fill_price, Some(40000.0));
+
+    executor.set_mode(PlaceMode::Reject("Insufficient margin".to_string()));
+    let result = oms.submit(buy(100.0), OrderOrigin::new(1, 2)).await;
+    assert!(matches!(result, Err(ExecutionError::Rejected(_))));
+
+    let orders = oms.orders().await;
+    let rejected = orders
+        .iter()
+        .find(|o| o.state == OrderState::Rejected)
+        .expect("rejected order is tracked");
+    assert!(rejected
+        .reject_reason
+        .as_deref()
+        .unwrap()
+        .contains("Insufficient margin"));
+    assert_eq!(
+        store
+            .history(&rejected.client_order_id)
+            .await
+            .last()
+            .unwrap()
+            .state,
+        OrderState::Rejected
+    );
+
+    // Reconciling picks up the trade record without double counting the fill
+    let report = oms.reconcile().await.unwrap();
+    assert_eq!(report.fills, 1);
+    let filled = oms.order(&filled.client_order_id).await.unwrap();
+    assert_eq!(filled.filled_size, 0.5);
+    assert_eq!(store.fills.lock().await.len(), 1);
+    assert_eq!(store.fills.lock().await[0].origin, OrderOrigin::new(1, 1));
+}
+
+#[tokio::test]
+async fn reconcile_applies_partial_fills_and_exchange_cancels() {
+    let executor = Arc::new(MockExecutor::new());
+    let store = Arc::new(MemoryOrderStore::default());
+    let oms = manager(&executor, &store);
+
+    let first = oms.submit(buy(1.0), OrderOrigin::new(1, 10)).await.unwrap();
+    let second = oms.submit(buy(2.0), OrderOrigin::new(2, 20)).await.unwrap();
+
+    executor.fill(first.exchange_order_id.unwrap(), 0.4, 39990.0);
+    executor.cancel_on_exchange(second.exchange_order_id.unwrap());
+
+    let report = oms.reconcile().await.unwrap();
+    assert_eq!(report.checked, 2);
+    assert_eq!(report.fills, 1);
+    assert_eq!(report.updated, 2);
+    assert!(report.untracked.is_empty());
+
+    let first_state = oms.order(&first.client_order_id).await.unwrap();
+    assert_eq!(first_state.state, OrderState::PartiallyFilled);
+    assert!((first_state.filled_size - 0.4).abs() < 1e-12);
+    assert_eq!(
+        oms.order(&second.client_order_id).await.unwrap().state,
+        OrderState::Cancelled
+    );
+
+    executor.fill(first.exchange_order_id.unwrap(), 0.6, 40010.0);
+    let report = oms.reconcile().await.unwrap();
+    assert_eq!(report.fills, 1);
+    let first_state = oms.order(&first.client_order_id).await.unwrap();
+    assert_eq!(first_state.state, OrderState::Filled);
+    assert!((first_state.avg_fill_price.unwrap() - 40002.0).abs() < 1e-9);
+    assert!(oms.open_orders().await.is_empty());
+
+    // Nothing new on a quiet pass
+    let report = oms.reconcile().await.unwrap();
+    assert_eq!((report.checked, report.updated, report.fills), (0, 0, 0));
+    assert_eq!(store.fills.lock().await.len(), 2);
+}
+
+#[tokio::test]
+async fn lost_submissions_are_resolved_by_reconciliation() {
+    let executor = Arc::new(MockExecutor::new());
+    let store = Arc::new(MemoryOrderStore::default());
+    let oms = manager(&executor, &store).with_unknown_order_grace(Duration::ZERO);
+
+    executor.set_mode(PlaceMode::LoseResponse);
+    let result = oms.submit(buy(1.0), OrderOrigin::manual()).await;
+    assert!(matches!(result, Err(ExecutionError::Transport(_))));
+    executor.set_mode(PlaceMode::Drop);
+    let result = oms.submit(buy(2.0), OrderOrigin::manual()).await;
+    assert!(matches!(result, Err(ExecutionError::Transport(_))));
+
+    let pending = oms.open_orders().await;
+    assert_eq!(pending.len(), 2);
+    assert!(pending.iter().all(|o| o.state == OrderState::Submitted));
+
+    oms.reconcile().await.unwrap();
+    let reached = oms.order(&pending[0].client_order_id).await.unwrap();
+    assert_eq!(reached.state, OrderState::Acknowledged);
+    assert_eq!(
+        reached.exchange_order_id,
+        executor.order_id(&reached.client_order_id)
+    );
+    let dropped = oms.order(&pending[1].client_order_id).await.unwrap();
+    assert_eq!(dropped.state, OrderState::Rejected);
+    assert_eq!(
+        dropped.reject_reason.as_deref(),
+        Some("Not found on exchange")
+    );
+}
+
+#[tokio::test]
+async fn cancel_goes_through_the_exchange() {
+    let executor = Arc::new(MockExecutor::new());
+    let store = Arc::new(MemoryOrderStore::default());
+    let oms = manager(&executor, &store);
+
+    let order = oms.submit(buy(1.0), OrderOrigin::manual()).await.unwrap();
+    let cancelled = oms.cancel(&order.client_order_id).await.unwrap();
+    assert_eq!(cancelled.state, OrderState::Cancelled);
+    assert_eq!(executor.cancelled(), vec![order.exchange_order_id.unwrap()]);
+
+    // Cancelling again is a no-op
+    oms.cancel(&order.client_order_id).await.unwrap();
+    assert_eq!(executor.cancelled().len(), 1);
+    assert!(oms.cancel("0xunknown").await.is_err());
+}
+
+#[tokio::test]
+async fn restore_reloads_orders_and_reconciles() {
+    let executor = Arc::new(MockExecutor::new());
+    let store = Arc::new(MemoryOrderStore::default());
+    let order = {
+        let oms = manager(&executor, &store);
+        oms.submit(buy(1.0), OrderOrigin::new(5, 50)).await.unwrap()
+    };
+
+    // While the process is down the order fills and someone places an order by hand
+    executor.fill(order.exchange_order_id.unwrap(), 1.0, 40000.0);
+    let external = executor.add_external_order(buy(0.1));
+
+    let oms = manager(&executor, &store);
+    let report = oms.restore().await.unwrap();
+    assert_eq!(report.fills, 1);
+    assert_eq!(report.untracked, vec![external]);
+
+    let restored = oms.order(&order.client_order_id).await.unwrap();
+    assert_eq!(restored.state, OrderState::Filled);
+    assert_eq!(restored.origin, OrderOrigin::new(5, 50));
+
+    // Known ids are still not resubmitted after a restart
+    oms.submit(
+        buy(1.0).with_client_order_id(order.client_order_id.clone()),
+        OrderOrigin::new(5, 50),
+    )
+    .await
+    .unwrap();
+    assert_eq!(executor.placed().len(), 1);
+}
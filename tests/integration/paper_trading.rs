@@ -0,0 +1,320 @@
+//! Integration tests for the paper trading service
+//!
+//! Signals are fed through an in-memory store and priced by a provider whose
+//! latest candle can be moved between ticks.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use perptrix::db::PaperStore;
+use perptrix::models::indicators::Candle;
+use perptrix::models::signal::{SignalDirection, SignalOutput, StoredSignal};
+use perptrix::paper::{
+    EquitySnapshot, FillAction, PaperAccount, PaperConfig, PaperFill, PaperTradingService,
+};
+use perptrix::services::market_bus::{
+    BusConsumer, BusTopic, MarketBus, MarketEvent, MemoryMarketBus,
+};
+use perptrix::services::market_data::MarketDataProvider;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+#[derive(Default)]
+struct MemoryPaperStore {
+    signals: Mutex<Vec<StoredSignal>>,
+    accounts: Mutex<Vec<PaperAccount>>,
+    fills: Mutex<Vec<PaperFill>>,
+    snapshots: Mutex<Vec<EquitySnapshot>>,
+}
+
+impl MemoryPaperStore {
+    async fn push_signal(&self, strategy_id: i64, direction: SignalDirection, at: DateTime<Utc>) {
+        let mut signal = SignalOutput::new(direction, 0.8, 2.0, 4.0, Vec::new(), "BTC".to_string(), 0.0);
+        signal.timestamp = at;
+        self.signals.lock().await.push(StoredSignal {
+            strategy_id,
+            signal,
+        });
+    }
+}
+
+#[async_trait]
+impl PaperStore for MemoryPaperStore {
+    async fn signals_since(
+        &self,
+        since: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<StoredSignal>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut signals: Vec<StoredSignal> = self
+            .signals
+            .lock()
+            .await
+            .iter()
+            .filter(|s| s.signal.timestamp >= since)
+            .cloned()
+            .collect();
+        signals.sort_by_key(|s| s.signal.timestamp);
+        signals.truncate(limit);
+        Ok(signals)
+    }
+
+    async fn load_accounts(
+        &self,
+    ) -> Result<Vec<PaperAccount>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.accounts.lock().await.clone())
+    }
+
+    async fn save_account(
+        &self,
+        account: &PaperAccount,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut accounts = self.accounts.lock().await;
+        accounts.retain(|a| a.strategy_id != account.strategy_id);
+        accounts.push(account.clone());
+        Ok(())
+    }
+
+    async fn record_fill(
+        &self,
+        fill: &PaperFill,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.fills.lock().await.push(fill.clone());
+        Ok(())
+    }
+
+    async fn record_snapshot(
+        &self,
+        snapshot: &EquitySnapshot,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.snapshots.lock().await.push(snapshot.clone());
+        Ok(())
+    }
+}
+
+/// Provider whose latest candle close is set by the test
+struct QuoteProvider {
+    price: Mutex<f64>,
+}
+
+impl QuoteProvider {
+    fn new(price: f64) -> Self {
+        Self {
+            price: Mutex::new(price),
+        }
+    }
+
+    async fn set(&self, price: f64) {
+        *self.price.lock().await = price;
+    }
+}
+
+#[async_trait]
+impl MarketDataProvider for QuoteProvider {
+    async fn get_candles(
+        &self,
+        _symbol: &str,
+        _limit: usize,
+    ) -> Result<Vec<Candle>, Box<dyn std::error::Error + Send + Sync>> {
+        let price = *self.price.lock().await;
+        Ok(vec![Candle::new(price, price, price, price, 1.0, Utc::now())])
+    }
+
+    async fn get_latest_price(
+        &self,
+        _symbol: &str,
+    ) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(*self.price.lock().await)
+    }
+
+    async fn subscribe(
+        &self,
+        _symbol: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        Ok(())
+    }
+}
+
+fn config() -> PaperConfig {
+    PaperConfig {
+        initial_capital: 1_000.0,
+        fee_bps: 0.0,
+        slippage_bps: 0.0,
+        ..PaperConfig::default()
+    }
+}
+
+#[tokio::test]
+async fn paper_trader_follows_new_signals_only() {
+    let store = Arc::new(MemoryPaperStore::default());
+    let provider = Arc::new(QuoteProvider::new(100.0));
+
+    // Signals stored before the service started are not traded
+    store
+        .push_signal(1, SignalDirection::Short, Utc::now() - Duration::minutes(5))
+        .await;
+
+    let service = PaperTradingService::new(config(), store.clone(), provider.clone()).unwrap();
+    assert_eq!(service.restore().await.unwrap(), 0);
+
+    store
+        .push_signal(1, SignalDirection::Long, Utc::now() + Duration::milliseconds(10))
+        .await;
+    let report = service.tick().await.unwrap();
+    assert_eq!(report.signals, 1);
+    assert_eq!(report.fills, 1);
+
+    provider.set(120.0).await;
+    store
+        .push_signal(1, SignalDirection::Short, Utc::now() + Duration::milliseconds(20))
+        .await;
+    let report = service.tick().await.unwrap();
+    assert_eq!(report.fills, 2);
+
+    let account = service.account(1).await.unwrap();
+    assert!((account.realized_pnl - 200.0).abs() < 1e-6);
+    assert_eq!(
+        account.position.as_ref().map(|p| p.direction),
+        Some(SignalDirection::Short)
+    );
+
+    let fills = store.fills.lock().await.clone();
+    let actions: Vec<FillAction> = fills.iter().map(|f| f.action).collect();
+    assert_eq!(
+        actions,
+        vec![FillAction::Open, FillAction::Close, FillAction::Open]
+    );
+
+    // Nothing new: no fills, and no further snapshot within the interval
+    let report = service.tick().await.unwrap();
+    assert_eq!(report.signals, 0);
+    assert_eq!(report.fills, 0);
+    assert_eq!(report.snapshots, 0);
+
+    // Marks follow the provider
+    provider.set(110.0).await;
+    service.tick().await.unwrap();
+    let account = service.account(1).await.unwrap();
+    assert!((account.unrealized_pnl() - 1_200.0 / 120.0 * 10.0).abs() < 1e-6);
+
+    let snapshots = store.snapshots.lock().await;
+    assert_eq!(snapshots.len(), 2);
+    assert!((snapshots[0].equity - 1_000.0).abs() < 1e-6);
+    assert!((snapshots[1].equity - 1_200.0).abs() < 1e-6);
+}
+
+#[tokio::test]
+async fn paper_trader_resumes_from_persisted_accounts() {
+    let store = Arc::new(MemoryPaperStore::default());
+    let provider = Arc::new(QuoteProvider::new(50.0));
+
+    let first = PaperTradingService::new(config(), store.clone(), provider.clone()).unwrap();
+    first.restore().await.unwrap();
+    store
+        .push_signal(3, SignalDirection::Long, Utc::now() + Duration::milliseconds(10))
+        .await;
+    first.tick().await.unwrap();
+    assert_eq!(store.fills.lock().await.len(), 1);
+
+    // A restarted service picks up the account and does not re-apply the signal
+    let second = PaperTradingService::new(config(), store.clone(), provider.clone()).unwrap();
+    assert_eq!(second.restore().await.unwrap(), 1);
+    let report = second.tick().await.unwrap();
+    assert_eq!(report.signals, 0);
+    assert_eq!(store.fills.lock().await.len(), 1);
+
+    store
+        .push_signal(3, SignalDirection::Neutral, Utc::now() + Duration::milliseconds(20))
+        .await;
+    second.tick().await.unwrap();
+    let account = second.account(3).await.unwrap();
+    assert!(account.position.is_none());
+    assert_eq!(account.trades, 1);
+}
+
+#[tokio::test]
+async fn paper_trader_applies_signals_that_become_visible_late() {
+    let store = Arc::new(MemoryPaperStore::default());
+    let provider = Arc::new(QuoteProvider::new(100.0));
+    let service = PaperTradingService::new(config(), store.clone(), provider.clone()).unwrap();
+    service.restore().await.unwrap();
+
+    let at = Utc::now() + Duration::milliseconds(50);
+    store.push_signal(1, SignalDirection::Long, at).await;
+    assert_eq!(service.tick().await.unwrap().signals, 1);
+
+    // Another strategy's signal with the same timestamp, and an older one,
+    // only show up after the first was applied
+    store.push_signal(2, SignalDirection::Long, at).await;
+    store
+        .push_signal(3, SignalDirection::Short, at - Duration::milliseconds(20))
+        .await;
+    let report = service.tick().await.unwrap();
+    assert_eq!(report.signals, 2);
+    assert_eq!(report.fills, 2);
+    assert_eq!(service.tick().await.unwrap().signals, 0);
+
+    let accounts = service.accounts().await;
+    assert_eq!(accounts.len(), 3);
+    assert!(accounts.iter().all(|account| account.position.is_some()));
+    assert_eq!(store.fills.lock().await.len(), 3);
+
+    // After a restart the signals at the resume point are not applied again
+    let restarted = PaperTradingService::new(config(), store.clone(), provider).unwrap();
+    assert_eq!(restarted.restore().await.unwrap(), 3);
+    assert_eq!(restarted.tick().await.unwrap().signals, 0);
+    assert_eq!(store.fills.lock().await.len(), 3);
+}
+
+fn bus_signal(strategy_id: i64, direction: SignalDirection, at: DateTime<Utc>) -> MarketEvent {
+    let mut signal = SignalOutput::new(direction, 0.8, 2.0, 4.0, Vec::new(), "BTC".to_string(), 0.0);
+    signal.timestamp = at;
+    MarketEvent::Signal(StoredSignal {
+        strategy_id,
+        signal,
+    })
+}
+
+#[tokio::test]
+async fn paper_trader_consumes_signals_from_the_bus() {
+    let store = Arc::new(MemoryPaperStore::default());
+    let provider = Arc::new(QuoteProvider::new(100.0));
+    let bus = Arc::new(MemoryMarketBus::new());
+    let consumer = || BusConsumer::new(bus.clone(), BusTopic::Signals, "paper-trader", "a");
+
+    // Published before the group existed
+    bus.publish(&bus_signal(1, SignalDirection::Short, Utc::now()))
+        .await
+        .unwrap();
+    let service = PaperTradingService::new(config(), store.clone(), provider.clone())
+        .unwrap()
+        .with_signal_bus(consumer());
+    service.restore().await.unwrap();
+
+    // The store is not read while a bus is configured
+    let at = Utc::now() + Duration::milliseconds(10);
+    store.push_signal(2, SignalDirection::Long, at).await;
+    bus.publish(&bus_signal(1, SignalDirection::Long, at)).await.unwrap();
+    bus.publish(&bus_signal(3, SignalDirection::Long, at)).await.unwrap();
+    let report = service.tick().await.unwrap();
+    assert_eq!(report.signals, 2);
+    assert_eq!(report.fills, 2);
+    assert_eq!(bus.lag(BusTopic::Signals, "paper-trader").await.unwrap(), 0);
+    assert!(service.account(2).await.is_none());
+
+    // Redelivered signals are applied once
+    bus.publish(&bus_signal(1, SignalDirection::Long, at)).await.unwrap();
+    assert_eq!(service.tick().await.unwrap().signals, 0);
+
+    // A restarted trader keeps the group position and skips what it applied
+    let restarted = PaperTradingService::new(config(), store.clone(), provider)
+        .unwrap()
+        .with_signal_bus(consumer());
+    restarted.restore().await.unwrap();
+    bus.publish(&bus_signal(3, SignalDirection::Long, at)).await.unwrap();
+    bus.publish(&bus_signal(1, SignalDirection::Neutral, at + Duration::milliseconds(10)))
+        .await
+        .unwrap();
+    let report = restarted.tick().await.unwrap();
+    assert_eq!(report.signals, 1);
+    assert!(restarted.account(1).await.unwrap().position.is_none());
+    assert_eq!(store.fills.lock().await.len(), 3);
+}
@@ -0,0 +1,502 @@
+//! Integration tests for signal-driven order placement
+//!
+//! Runs the policy engine on top of the OMS and the in-memory exchange.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::mock_exchange::{MemoryOrderStore, MockExecutor, PlaceMode};
+use chrono::{DateTime, Utc};
+use perptrix::execution::{
+    BracketGrouping, EntryOrderType, ExchangePosition, ExecutionPolicy, ManagedOrder,
+    OppositeSignalAction, OrderGrouping, OrderManager, OrderSide, OrderState, OrderType,
+    PolicyEngine, SignalAction, TimeInForce,
+};
+use perptrix::models::signal::{SignalDirection, SignalOutput};
+
+const STRATEGY_ID: i64 = 7;
+
+fn engine(exchange: &Arc<MockExecutor>) -> PolicyEngine {
+    let oms = OrderManager::new(exchange.clone(), Arc::new(MemoryOrderStore::default()));
+    PolicyEngine::new(Arc::new(oms))
+}
+
+fn signal(direction: SignalDirection, timestamp: DateTime<Utc>) -> SignalOutput {
+    let mut signal =
+        SignalOutput::new(direction, 0.8, 2.0, 4.0, vec![], "BTC".to_string(), 40000.0);
+    signal.timestamp = timestamp;
+    signal
+}
+
+fn position(size: f64) -> ExchangePosition {
+    ExchangePosition {
+        symbol: "BTC".to_string(),
+        size,
+        entry_price: 40000.0,
+        unrealized_pnl: 0.0,
+        leverage: 5.0,
+        liquidation_price: None,
+    }
+}
+
+#[tokio::test]
+async fn market_entry_is_protected_by_native_triggers() {
+    let exchange = Arc::new(MockExecutor::new());
+    exchange.set_mode(PlaceMode::Fill);
+    let engine = engine(&exchange);
+    let policy = ExecutionPolicy::new(1000.0);
+    let long = signal(SignalDirection::Long, Utc::now());
+
+    let execution = engine.execute(STRATEGY_ID, &policy, &long).await.unwrap();
+    assert_eq!(execution.action, SignalAction::Open(OrderSide::Buy));
+    assert_eq!(execution.orders.len(), 3);
+
+    let placed = exchange.placed();
+    let entry = &placed[0];
+    assert_eq!(entry.order_type, OrderType::Market);
+    assert_eq!(entry.side, OrderSide::Buy);
+    assert!((entry.size - 0.025).abs() < 1e-12);
+    assert!((entry.price - 40400.0).abs() < 1e-6);
+
+    // Entry filled at 40400: stop 2% below, take profit 4% above
+    let stop = &placed[1];
+    assert!(stop.reduce_only);
+    assert_eq!(stop.side, OrderSide::Sell);
+    match stop.order_type {
+        OrderType::StopMarket { trigger_price } => {
+            assert!((trigger_price - 40400.0 * 0.98).abs() < 1e-6)
+        }
+        other => panic!("expected a stop market order, got {:?}", other),
+    }
+    let take_profit = &placed[2];
+    assert!(take_profit.reduce_only);
+    match take_profit.order_type {
+        OrderType::TakeProfitMarket { trigger_price } => {
+            assert!((trigger_price - 40400.0 * 1.04).abs() < 1e-6)
+        }
+        other => panic!("expected a take profit order, got {:?}", other),
+    }
+    assert!(execution
+        .orders
+        .iter()
+        .all(|o| o.origin.strategy_id == Some(STRATEGY_ID)));
+
+    // A retried job does not trade again
+    let retry = engine.execute(STRATEGY_ID, &policy, &long).await.unwrap();
+    assert!(matches!(retry.action, SignalAction::Ignore(_)));
+    assert_eq!(exchange.placed().len(), 3);
+}
+
+#[tokio::test]
+async fn resting_entry_is_protected_once_filled() {
+    let exchange = Arc::new(MockExecutor::new());
+    exchange.set_mid("BTC", 39990.0);
+    let engine = engine(&exchange);
+    let policy = ExecutionPolicy {
+        entry_order: EntryOrderType::LimitAtMid,
+        place_take_profit: false,
+        ..ExecutionPolicy::new(1000.0)
+    };
+
+    let execution = engine
+        .execute(
+            STRATEGY_ID,
+            &policy,
+            &signal(SignalDirection::Short, Utc::now()),
+        )
+        .await
+        .unwrap();
+    let entry = &execution.orders[0];
+    assert_eq!(entry.request.order_type, OrderType::Limit(TimeInForce::Gtc));
+    assert_eq!(entry.request.price, 39990.0);
+    assert!(engine.sweep().await.is_empty());
+
+    let order_id = exchange.order_id(&entry.client_order_id).unwrap();
+    exchange.fill(order_id, entry.request.size, 39990.0);
+    engine.oms().reconcile().await.unwrap();
+
+    let protection = engine.sweep().await;
+    assert_eq!(protection.len(), 1);
+    assert_eq!(protection[0].side(), OrderSide::Buy);
+    assert_eq!(
+        protection[0].request.order_type,
+        OrderType::StopMarket {
+            trigger_price: 39990.0 * 1.02
+        }
+    );
+    assert!(engine.sweep().await.is_empty());
+}
+
+#[tokio::test]
+async fn post_only_entry_is_cancelled_after_timeout() {
+    let exchange = Arc::new(MockExecutor::new());
+    exchange.set_mid("BTC", 40010.0);
+    let engine = engine(&exchange);
+    let policy = ExecutionPolicy {
+        entry_order: EntryOrderType::PostOnly { timeout_seconds: 1 },
+        ..ExecutionPolicy::new(1000.0)
+    };
+
+    let execution = engine
+        .execute(
+            STRATEGY_ID,
+            &policy,
+            &signal(SignalDirection::Long, Utc::now()),
+        )
+        .await
+        .unwrap();
+    let entry = &execution.orders[0];
+    assert_eq!(entry.request.order_type, OrderType::Limit(TimeInForce::Alo));
+
+    engine.sweep().await;
+    assert!(exchange.cancelled().is_empty());
+
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    assert!(engine.sweep().await.is_empty());
+    let order = engine.oms().order(&entry.client_order_id).await.unwrap();
+    assert_eq!(order.state, OrderState::Cancelled);
+    assert_eq!(exchange.cancelled().len(), 1);
+}
+
+#[tokio::test]
+async fn opposite_signal_closes_and_flips() {
+    let exchange = Arc::new(MockExecutor::new());
+    exchange.set_mid("BTC", 40000.0);
+    let engine = engine(&exchange);
+    let policy = ExecutionPolicy {
+        entry_order: EntryOrderType::LimitAtMid,
+        ..ExecutionPolicy::new(1000.0)
+    };
+    let start = Utc::now();
+
+    // A resting long entry counts as exposure, so a short signal flips it
+    let first = engine
+        .execute(STRATEGY_ID, &policy, &signal(SignalDirection::Long, start))
+        .await
+        .unwrap();
+    let resting = exchange.order_id(&first.orders[0].client_order_id).unwrap();
+    let flip = engine
+        .execute(
+            STRATEGY_ID,
+            &policy,
+            &signal(SignalDirection::Short, start + chrono::Duration::seconds(1)),
+        )
+        .await
+        .unwrap();
+    assert_eq!(flip.action, SignalAction::Flip(OrderSide::Sell));
+    assert_eq!(exchange.cancelled(), vec![resting]);
+    assert_eq!(flip.orders.len(), 1);
+    assert_eq!(flip.orders[0].side(), OrderSide::Sell);
+
+    // With a filled position the close goes out as a reduce-only market order
+    let close_only = ExecutionPolicy {
+        on_opposite_signal: OppositeSignalAction::CloseOnly,
+        ..policy
+    };
+    exchange.set_positions(vec![position(-0.025)]);
+    let close = engine
+        .execute(
+            STRATEGY_ID,
+            &close_only,
+            &signal(SignalDirection::Long, start + chrono::Duration::seconds(2)),
+        )
+        .await
+        .unwrap();
+    assert_eq!(close.action, SignalAction::Close);
+    assert_eq!(close.orders.len(), 1);
+    let request = &close.orders[0].request;
+    assert!(request.reduce_only);
+    assert_eq!(request.order_type, OrderType::Market);
+    assert_eq!(request.side, OrderSide::Buy);
+    assert_eq!(request.size, 0.025);
+    assert!((request.price - 40400.0).abs() < 1e-6);
+}
+
+#[tokio::test]
+async fn pyramiding_stops_at_max_entries() {
+    let exchange = Arc::new(MockExecutor::new());
+    exchange.set_mode(PlaceMode::Fill);
+    let engine = engine(&exchange);
+    let policy = ExecutionPolicy {
+        max_entries: 2,
+        place_stop_loss: false,
+        place_take_profit: false,
+        ..ExecutionPolicy::new(1000.0)
+    };
+    let start = Utc::now();
+
+    let mut actions = Vec::new();
+    for i in 0..3 {
+        let long = signal(SignalDirection::Long, start + chrono::Duration::seconds(i));
+        let execution = engine.execute(STRATEGY_ID, &policy, &long).await.unwrap();
+        actions.push(execution.action);
+        exchange.set_positions(vec![position(0.025 * (i + 1) as f64)]);
+    }
+
+    assert_eq!(actions[0], SignalAction::Open(OrderSide::Buy));
+    assert_eq!(actions[1], SignalAction::Add(OrderSide::Buy));
+    assert!(matches!(actions[2], SignalAction::Ignore(_)));
+    assert_eq!(exchange.placed().len(), 2);
+}
+
+/// Current state of the stop loss and take profit placed for an execution
+async fn legs(engine: &PolicyEngine, orders: &[ManagedOrder]) -> Vec<ManagedOrder> {
+    let mut legs = Vec::new();
+    for order in orders.iter().filter(|o| o.request.reduce_only) {
+        legs.push(engine.oms().order(&order.client_order_id).await.unwrap());
+    }
+    legs
+}
+
+#[tokio::test]
+async fn entry_bracket_is_sent_as_one_group() {
+    let exchange = Arc::new(MockExecutor::new());
+    exchange.set_mode(PlaceMode::Fill);
+    let engine = engine(&exchange);
+    let policy = ExecutionPolicy {
+        bracket_grouping: BracketGrouping::Entry,
+        take_profit_limit: true,
+        ..ExecutionPolicy::new(1000.0)
+    };
+
+    let long = signal(SignalDirection::Long, Utc::now());
+    let execution = engine.execute(STRATEGY_ID, &policy, &long).await.unwrap();
+    assert_eq!(execution.orders.len(), 3);
+    assert_eq!(exchange.groupings(), vec![OrderGrouping::NormalTpsl]);
+    assert_eq!(execution.orders[0].state, OrderState::Filled);
+    assert!(execution.orders[1..]
+        .iter()
+        .all(|o| o.state == OrderState::Acknowledged));
+
+    // Legs are priced off the signal since the fill is not known when they are sent
+    let placed = exchange.placed();
+    assert_eq!(
+        placed[1].order_type,
+        OrderType::StopMarket {
+            trigger_price: 40000.0 * 0.98
+        }
+    );
+    let take_profit = &placed[2];
+    assert_eq!(
+        take_profit.order_type,
+        OrderType::TakeProfitLimit {
+            trigger_price: 40000.0 * 1.04
+        }
+    );
+    assert_eq!(take_profit.price, 40000.0 * 1.04);
+    assert!(take_profit.reduce_only);
+}
+
+#[tokio::test]
+async fn resting_entry_bracket_waits_for_the_fill() {
+    let exchange = Arc::new(MockExecutor::new());
+    exchange.set_mid("BTC", 40000.0);
+    let oms = OrderManager::new(exchange.clone(), Arc::new(MemoryOrderStore::default()))
+        .with_unknown_order_grace(Duration::ZERO);
+    let engine = PolicyEngine::new(Arc::new(oms));
+    let policy = ExecutionPolicy {
+        entry_order: EntryOrderType::LimitAtMid,
+        bracket_grouping: BracketGrouping::Entry,
+        ..ExecutionPolicy::new(1000.0)
+    };
+
+    let execution = engine
+        .execute(STRATEGY_ID, &policy, &signal(SignalDirection::Long, Utc::now()))
+        .await
+        .unwrap();
+    assert_eq!(execution.orders.len(), 3);
+    assert_eq!(execution.orders[0].state, OrderState::Acknowledged);
+    for leg in &execution.orders[1..] {
+        assert_eq!(leg.state, OrderState::Acknowledged);
+        assert_eq!(leg.exchange_order_id, None);
+    }
+
+    // The exchange does not know the legs yet, but they are not rejected
+    // while the entry rests
+    engine.oms().reconcile().await.unwrap();
+    engine.sweep().await;
+    let waiting = legs(&engine, &execution.orders).await;
+    assert_eq!(waiting.len(), 2);
+    assert!(waiting.iter().all(|o| o.state == OrderState::Acknowledged));
+
+    // Once the entry fills the legs are placed and picked up
+    let entry = &execution.orders[0];
+    let order_id = exchange.order_id(&entry.client_order_id).unwrap();
+    exchange.fill(order_id, entry.request.size, 40000.0);
+    exchange.set_positions(vec![position(entry.request.size)]);
+    engine.oms().reconcile().await.unwrap();
+    engine.sweep().await;
+    let placed = legs(&engine, &execution.orders).await;
+    assert!(placed
+        .iter()
+        .all(|o| o.state == OrderState::Acknowledged && o.exchange_order_id.is_some()));
+
+    // The bracket is still managed: closing the position cancels it
+    exchange.set_positions(vec![]);
+    engine.sweep().await;
+    assert_eq!(exchange.cancelled().len(), 2);
+}
+
+#[tokio::test]
+async fn bracket_is_resized_and_cancelled_with_the_position() {
+    let exchange = Arc::new(MockExecutor::new());
+    exchange.set_mode(PlaceMode::Fill);
+    let engine = engine(&exchange);
+    let policy = ExecutionPolicy::new(1000.0);
+
+    let long = signal(SignalDirection::Long, Utc::now());
+    let execution = engine.execute(STRATEGY_ID, &policy, &long).await.unwrap();
+    assert_eq!(exchange.groupings(), Vec::<OrderGrouping>::new());
+
+    // The position has not shown up yet: the bracket is left alone
+    engine.sweep().await;
+    exchange.set_positions(vec![position(0.025)]);
+    engine.sweep().await;
+    let legs_before = legs(&engine, &execution.orders).await;
+    assert_eq!(legs_before.len(), 2);
+    assert!(legs_before
+        .iter()
+        .all(|o| o.state == OrderState::Acknowledged && (o.request.size - 0.025).abs() < 1e-12));
+
+    // Partly closed: both legs shrink to the position
+    exchange.set_positions(vec![position(0.01)]);
+    engine.sweep().await;
+    for leg in legs(&engine, &execution.orders).await {
+        assert!((leg.request.size - 0.01).abs() < 1e-12);
+        assert_eq!(leg.state, OrderState::Acknowledged);
+    }
+
+    // Closed: the remaining legs are cancelled
+    exchange.set_positions(vec![]);
+    engine.sweep().await;
+    assert_eq!(exchange.cancelled().len(), 2);
+    assert!(legs(&engine, &execution.orders)
+        .await
+        .iter()
+        .all(|o| o.state == OrderState::Cancelled));
+}
+
+#[tokio::test]
+async fn stop_moves_to_break_even_once() {
+    let exchange = Arc::new(MockExecutor::new());
+    exchange.set_mode(PlaceMode::Fill);
+    let engine = engine(&exchange);
+    let policy = ExecutionPolicy {
+        place_take_profit: false,
+        break_even_after_pct: Some(1.0),
+        ..ExecutionPolicy::new(1000.0)
+    };
+
+    let long = signal(SignalDirection::Long, Utc::now());
+    let execution = engine.execute(STRATEGY_ID, &policy, &long).await.unwrap();
+    exchange.set_positions(vec![position(0.025)]);
+
+    exchange.set_mid("BTC", 40400.0 * 1.005);
+    engine.sweep().await;
+    let stop = &legs(&engine, &execution.orders).await[0];
+    assert_eq!(
+        stop.request.order_type,
+        OrderType::StopMarket {
+            trigger_price: 40400.0 * 0.98
+        }
+    );
+
+    // Filled at 40400: 1% in favour moves the stop to the entry
+    exchange.set_mid("BTC", 40400.0 * 1.011);
+    engine.sweep().await;
+    let stop = &legs(&engine, &execution.orders).await[0];
+    assert_eq!(
+        stop.request.order_type,
+        OrderType::StopMarket {
+            trigger_price: 40400.0
+        }
+    );
+    assert!(stop.request.price < 40400.0);
+    assert!(stop.request.reduce_only);
+
+    // The stop is only moved once
+    exchange.set_mid("BTC", 40400.0 * 1.02);
+    engine.sweep().await;
+    let stop = &legs(&engine, &execution.orders).await[0];
+    assert_eq!(stop.request.order_type.trigger_price(), Some(40400.0));
+}
+
+#[tokio::test]
+async fn position_bracket_is_left_to_the_exchange() {
+    let exchange = Arc::new(MockExecutor::new());
+    exchange.set_mode(PlaceMode::Fill);
+    let engine = engine(&exchange);
+    let policy = ExecutionPolicy {
+        bracket_grouping: BracketGrouping::Position,
+        ..ExecutionPolicy::new(1000.0)
+    };
+
+    let long = signal(SignalDirection::Long, Utc::now());
+    let execution = engine.execute(STRATEGY_ID, &policy, &long).await.unwrap();
+    assert_eq!(execution.orders.len(), 3);
+    assert_eq!(exchange.groupings(), vec![OrderGrouping::PositionTpsl]);
+
+    exchange.set_positions(vec![position(0.025)]);
+    engine.sweep().await;
+    exchange.set_positions(vec![position(0.01)]);
+    engine.sweep().await;
+    assert!(legs(&engine, &execution.orders)
+        .await
+        .iter()
+        .all(|o| (o.request.size - 0.025).abs() < 1e-12));
+}
+
+#[tokio::test]
+async fn entry_filled_across_a_restart_is_protected() {
+    let exchange = Arc::new(MockExecutor::new());
+    exchange.set_mid("BTC", 40000.0);
+    let store = Arc::new(MemoryOrderStore::default());
+    let engine = PolicyEngine::new(Arc::new(OrderManager::new(
+        exchange.clone(),
+        store.clone(),
+    )));
+    let policy = ExecutionPolicy {
+        entry_order: EntryOrderType::LimitAtMid,
+        ..ExecutionPolicy::new(1000.0)
+    };
+    let execution = engine
+        .execute(STRATEGY_ID, &policy, &signal(SignalDirection::Long, Utc::now()))
+        .await
+        .unwrap();
+    let entry = &execution.orders[0];
+    drop(engine);
+
+    // The entry fills while the worker is down
+    let order_id = exchange.order_id(&entry.client_order_id).unwrap();
+    exchange.fill(order_id, entry.request.size, 40000.0);
+    exchange.set_positions(vec![position(entry.request.size)]);
+
+    let oms = Arc::new(OrderManager::new(exchange.clone(), store.clone()));
+    oms.restore().await.unwrap();
+    let engine = PolicyEngine::new(oms);
+    engine.restore().await.unwrap();
+
+    let protection = engine.sweep().await;
+    assert_eq!(protection.len(), 2);
+    assert!(protection
+        .iter()
+        .all(|o| o.request.reduce_only && o.side() == OrderSide::Sell));
+    assert_eq!(
+        protection[0].request.order_type,
+        OrderType::StopMarket {
+            trigger_price: 40000.0 * 0.98
+        }
+    );
+
+    // Another restart manages the placed bracket instead of placing a second one
+    let oms = Arc::new(OrderManager::new(exchange.clone(), store.clone()));
+    oms.restore().await.unwrap();
+    let engine = PolicyEngine::new(oms);
+    engine.restore().await.unwrap();
+    assert!(engine.sweep().await.is_empty());
+    assert_eq!(exchange.placed().len(), 3);
+
+    exchange.set_positions(vec![]);
+    engine.sweep().await;
+    assert_eq!(exchange.cancelled().len(), 2);
+}
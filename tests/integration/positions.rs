@@ -0,0 +1,145 @@
+//! Integration tests for the position and PnL tracker
+//!
+//! Runs the tracker against the in-memory exchange and position store.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::mock_exchange::{MemoryPositionStore, MockExecutor};
+use chrono::Utc;
+use perptrix::execution::{
+    user_event_channel, AccountSnapshot, ExchangeFill, ExchangePosition, FundingPayment, OrderSide,
+    PositionTracker, TrackedPosition, UserEvent,
+};
+
+const ACCOUNT: &str = "0xabc";
+
+fn exchange_position(symbol: &str, size: f64, entry_price: f64) -> ExchangePosition {
+    ExchangePosition {
+        symbol: symbol.to_string(),
+        size,
+        entry_price,
+        unrealized_pnl: 0.0,
+        leverage: 4.0,
+        liquidation_price: None,
+    }
+}
+
+fn fill(trade_id: u64, side: OrderSide, size: f64, price: f64) -> ExchangeFill {
+    ExchangeFill {
+        trade_id,
+        order_id: 7,
+        client_order_id: None,
+        symbol: "BTC".to_string(),
+        side,
+        size,
+        price,
+        fee: 2.0,
+        timestamp: Utc::now(),
+    }
+}
+
+#[tokio::test]
+async fn restore_adopts_exchange_positions_and_marks_them() {
+    let exchange = Arc::new(MockExecutor::new());
+    exchange.set_positions(vec![ExchangePosition {
+        unrealized_pnl: 50.0,
+        liquidation_price: Some(33000.0),
+        leverage: 5.0,
+        ..exchange_position("BTC", 0.5, 40000.0)
+    }]);
+    let store = Arc::new(MemoryPositionStore::default());
+    let mut previous = TrackedPosition::new(ACCOUNT, "BTC", Utc::now());
+    previous.realized_pnl = 100.0;
+    store.rows.lock().await.push(previous);
+
+    let tracker = PositionTracker::new(ACCOUNT, exchange.clone(), store.clone());
+    tracker.restore().await.unwrap();
+
+    let position = tracker.position("BTC").await.unwrap();
+    assert_eq!(position.size, 0.5);
+    assert_eq!(position.entry_price, 40000.0);
+    assert_eq!(position.realized_pnl, 100.0);
+    assert_eq!(position.leverage, Some(5.0));
+    assert_eq!(position.liquidation_price, Some(33000.0));
+    assert_eq!(position.mark_price, Some(40100.0));
+    assert!((position.unrealized_pnl - 50.0).abs() < 1e-9);
+
+    exchange.set_mid("BTC", 40200.0);
+    let marked = tracker.mark_to_market().await.unwrap();
+    assert_eq!(marked.len(), 1);
+    assert!((marked[0].unrealized_pnl - 100.0).abs() < 1e-9);
+    assert_eq!(store.rows.lock().await.last(), Some(&marked[0]));
+
+    let pnl = tracker.pnl().await;
+    assert_eq!(pnl.open_positions, 1);
+    assert!((pnl.net_pnl - 200.0).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn user_events_update_positions_and_pnl() {
+    let exchange = Arc::new(MockExecutor::new());
+    let store = Arc::new(MemoryPositionStore::default());
+    let tracker = Arc::new(PositionTracker::new(ACCOUNT, exchange, store.clone()));
+    let (sender, events) = user_event_channel();
+    let follower = tracker.clone();
+    let follow = tokio::spawn(async move { follower.follow_user_events(events).await });
+
+    let opening = fill(1, OrderSide::Buy, 1.0, 40000.0);
+    sender.send(UserEvent::Fill(opening.clone())).unwrap();
+    sender.send(UserEvent::Fill(opening)).unwrap();
+    sender
+        .send(UserEvent::Funding(FundingPayment {
+            symbol: "BTC".to_string(),
+            position_size: 1.0,
+            rate: 0.0000375,
+            amount: -1.5,
+            timestamp: Utc::now(),
+        }))
+        .unwrap();
+    sender
+        .send(UserEvent::Fill(fill(2, OrderSide::Sell, 1.0, 40500.0)))
+        .unwrap();
+    // A position opened elsewhere shows up in the account snapshot
+    sender
+        .send(UserEvent::Account(AccountSnapshot {
+            positions: vec![exchange_position("ETH", -2.0, 2000.0)],
+            equity: Some(10_000.0),
+            ..AccountSnapshot::default()
+        }))
+        .unwrap();
+
+    let mut eth = None;
+    for _ in 0..50 {
+        eth = tracker.position("ETH").await;
+        if eth.is_some() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    let btc = tracker.position("BTC").await.unwrap();
+    assert!(!btc.is_open());
+    assert!((btc.realized_pnl - 500.0).abs() < 1e-9);
+    assert!((btc.fees_paid - 4.0).abs() < 1e-9);
+    assert!((btc.funding_paid - 1.5).abs() < 1e-9);
+    assert!((btc.net_pnl() - 494.5).abs() < 1e-9);
+
+    let eth = eth.expect("ETH position tracked");
+    assert_eq!(eth.size, -2.0);
+    assert_eq!(eth.leverage, Some(4.0));
+    let estimate = eth.liquidation_price.unwrap();
+    assert!((estimate - 2000.0 * 1.25 / 1.01).abs() < 1e-6);
+
+    // Every change was persisted; the duplicate fill was not
+    let rows = store.rows.lock().await;
+    assert_eq!(rows.iter().filter(|p| p.symbol == "BTC").count(), 3);
+    drop(rows);
+
+    let pnl = tracker.pnl().await;
+    assert_eq!(pnl.account, ACCOUNT);
+    assert_eq!(pnl.open_positions, 1);
+    assert!((pnl.realized_pnl - 500.0).abs() < 1e-9);
+
+    follow.abort();
+}
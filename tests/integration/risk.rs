@@ -0,0 +1,311 @@
+//! Integration tests for pre-trade risk checks and the kill switch
+//!
+//! Wraps the in-memory exchange in a risk-guarded executor and checks that
+//! blocked orders never reach the exchange.
+
+use std::sync::Arc;
+
+use crate::mock_exchange::{MemoryOrderStore, MockExecutor};
+use perptrix::execution::{
+    DayStartEquityStore, ExchangeExecutor, ExchangePosition, ExecutionError, KillSwitch,
+    MemoryDayStartEquityStore, MemoryKillSwitchStore, OrderManager, OrderOrigin, OrderRequest,
+    OrderGrouping, OrderSide, OrderState, OrderType, RiskGuardedExecutor, RiskLimits, RiskManager,
+    RiskRejection,
+};
+use perptrix::metrics::Metrics;
+
+struct Harness {
+    exchange: Arc<MockExecutor>,
+    kill_switch: Arc<KillSwitch>,
+    metrics: Arc<Metrics>,
+    guarded: Arc<RiskGuardedExecutor>,
+}
+
+fn harness(limits: RiskLimits) -> Harness {
+    let exchange = Arc::new(MockExecutor::new());
+    let metrics = Arc::new(Metrics::new().unwrap());
+    let kill_switch = Arc::new(
+        KillSwitch::new(Arc::new(MemoryKillSwitchStore::default())).with_metrics(metrics.clone()),
+    );
+    let risk =
+        Arc::new(RiskManager::new(limits, kill_switch.clone()).with_metrics(metrics.clone()));
+    let guarded = Arc::new(RiskGuardedExecutor::new(exchange.clone(), risk));
+    Harness {
+        exchange,
+        kill_switch,
+        metrics,
+        guarded,
+    }
+}
+
+fn position(symbol: &str, size: f64, entry_price: f64, unrealized_pnl: f64) -> ExchangePosition {
+    ExchangePosition {
+        symbol: symbol.to_string(),
+        size,
+        entry_price,
+        unrealized_pnl,
+        leverage: 5.0,
+        liquidation_price: None,
+    }
+}
+
+#[tokio::test]
+async fn blocked_orders_never_reach_the_exchange() {
+    let h = harness(RiskLimits {
+        max_position_notional: Some(50_000.0),
+        ..RiskLimits::default()
+    });
+    h.exchange
+        .set_positions(vec![position("BTC", 1.0, 40000.0, 0.0)]);
+
+    let result = h
+        .guarded
+        .place_order(&OrderRequest::limit("BTC", OrderSide::Buy, 0.5, 40000.0))
+        .await;
+    match result {
+        Err(ExecutionError::RiskRejected(RiskRejection::PositionNotional { notional, .. })) => {
+            assert_eq!(notional, 60_000.0)
+        }
+        other => panic!("expected a position notional rejection, got {:?}", other),
+    }
+    assert!(h.exchange.placed().is_empty());
+
+    h.guarded
+        .place_order(&OrderRequest::limit("BTC", OrderSide::Buy, 0.1, 40000.0))
+        .await
+        .unwrap();
+    assert_eq!(h.exchange.placed().len(), 1);
+    assert_eq!(
+        h.metrics
+            .risk_rejections_total
+            .with_label_values(&["position_notional"])
+            .get(),
+        1.0
+    );
+}
+
+#[tokio::test]
+async fn resting_orders_count_as_exposure() {
+    let h = harness(RiskLimits {
+        max_position_notional: Some(50_000.0),
+        ..RiskLimits::default()
+    });
+    let order = OrderRequest::limit("BTC", OrderSide::Buy, 0.5, 40000.0);
+    let first = h.guarded.place_order(&order).await.unwrap();
+    h.guarded.place_order(&order).await.unwrap();
+
+    // Two 20k orders rest unfilled; a third would take the position to 60k
+    let result = h.guarded.place_order(&order).await;
+    assert!(matches!(
+        result,
+        Err(ExecutionError::RiskRejected(
+            RiskRejection::PositionNotional { .. }
+        ))
+    ));
+    assert_eq!(h.exchange.placed().len(), 2);
+
+    // A modified order replaces its own exposure
+    let moved = OrderRequest::limit("BTC", OrderSide::Buy, 0.5, 39900.0);
+    h.guarded
+        .modify_order(first.order_id.unwrap(), &moved)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn grouped_orders_are_checked_together() {
+    let h = harness(RiskLimits {
+        max_position_notional: Some(50_000.0),
+        max_orders_per_minute: Some(2),
+        ..RiskLimits::default()
+    });
+
+    // Each leg is 30k on its own, 60k together
+    let legs = vec![
+        OrderRequest::limit("BTC", OrderSide::Buy, 0.75, 40000.0),
+        OrderRequest::limit("BTC", OrderSide::Buy, 0.75, 40000.0),
+    ];
+    let result = h.guarded.place_orders(&legs, OrderGrouping::None).await;
+    match result {
+        Err(ExecutionError::RiskRejected(RiskRejection::PositionNotional { notional, .. })) => {
+            assert_eq!(notional, 60_000.0)
+        }
+        other => panic!("expected a position notional rejection, got {:?}", other),
+    }
+    assert!(h.exchange.placed().is_empty());
+
+    // The rejected group did not use up the order rate limit
+    let legs = vec![
+        OrderRequest::limit("BTC", OrderSide::Buy, 0.5, 40000.0),
+        OrderRequest::limit("BTC", OrderSide::Sell, 0.5, 41000.0).with_reduce_only(true),
+        OrderRequest::limit("ETH", OrderSide::Buy, 1.0, 2000.0),
+    ];
+    h.guarded
+        .place_orders(&legs, OrderGrouping::None)
+        .await
+        .unwrap();
+    assert_eq!(h.exchange.placed().len(), 3);
+
+    let result = h
+        .guarded
+        .place_order(&OrderRequest::limit("SOL", OrderSide::Buy, 1.0, 100.0))
+        .await;
+    assert!(matches!(
+        result,
+        Err(ExecutionError::RiskRejected(RiskRejection::OrderRate {
+            orders: 2,
+            limit: 2
+        }))
+    ));
+}
+
+#[tokio::test]
+async fn daily_loss_counts_losses_before_the_first_order_across_restarts() {
+    let exchange = Arc::new(MockExecutor::new());
+    exchange.set_equity(10_000.0);
+    let store = Arc::new(MemoryDayStartEquityStore::default());
+    let guarded = |exchange: Arc<MockExecutor>| {
+        let kill_switch = Arc::new(KillSwitch::new(Arc::new(MemoryKillSwitchStore::default())));
+        let limits = RiskLimits {
+            max_daily_loss: Some(500.0),
+            ..RiskLimits::default()
+        };
+        let risk = RiskManager::new(limits, kill_switch).with_equity_store(store.clone());
+        RiskGuardedExecutor::new(exchange, Arc::new(risk))
+    };
+
+    // The poll loop records the day's start before any order is placed
+    guarded(exchange.clone()).sample_day_start_equity().await;
+
+    // The account loses 600 with no order flow, then the worker restarts
+    exchange.set_equity(9_400.0);
+    let restarted = guarded(exchange.clone());
+    let result = restarted
+        .place_order(&OrderRequest::limit("BTC", OrderSide::Buy, 0.01, 40000.0))
+        .await;
+    match result {
+        Err(ExecutionError::RiskRejected(RiskRejection::DailyLoss { loss, .. })) => {
+            assert_eq!(loss, 600.0)
+        }
+        other => panic!("expected a daily loss rejection, got {:?}", other),
+    }
+    assert!(exchange.placed().is_empty());
+
+    let today = chrono::Utc::now().date_naive();
+    assert_eq!(store.day_start_equity(today, 1.0).await.unwrap(), 10_000.0);
+}
+
+#[tokio::test]
+async fn oms_records_risk_rejections() {
+    let h = harness(RiskLimits {
+        allowed_symbols: Some(vec!["BTC".to_string()]),
+        ..RiskLimits::default()
+    });
+    let store = Arc::new(MemoryOrderStore::default());
+    let oms = OrderManager::new(h.guarded.clone(), store.clone());
+
+    let result = oms
+        .submit(
+            OrderRequest::limit("ETH", OrderSide::Buy, 1.0, 2000.0),
+            OrderOrigin::new(1, 1),
+        )
+        .await;
+    assert!(matches!(
+        result,
+        Err(ExecutionError::RiskRejected(
+            RiskRejection::SymbolNotAllowed { .. }
+        ))
+    ));
+
+    let orders = oms.orders().await;
+    assert_eq!(orders.len(), 1);
+    assert_eq!(orders[0].state, OrderState::Rejected);
+    assert!(orders[0]
+        .reject_reason
+        .as_deref()
+        .unwrap()
+        .contains("not in the allowed symbols"));
+    assert!(h.exchange.placed().is_empty());
+}
+
+#[tokio::test]
+async fn kill_switch_blocks_orders_and_leverage_changes() {
+    let h = harness(RiskLimits::default());
+    h.kill_switch.activate("maintenance", false).await.unwrap();
+
+    let result = h
+        .guarded
+        .place_order(&OrderRequest::limit("BTC", OrderSide::Buy, 0.1, 40000.0))
+        .await;
+    assert!(matches!(
+        result,
+        Err(ExecutionError::RiskRejected(
+            RiskRejection::KillSwitchActive { .. }
+        ))
+    ));
+    assert!(h.guarded.set_leverage("BTC", 10, true).await.is_err());
+    assert!(h.exchange.leverage_calls().is_empty());
+    assert_eq!(h.metrics.kill_switch_active.get(), 1.0);
+    assert_eq!(
+        h.metrics
+            .risk_rejections_total
+            .with_label_values(&["kill_switch"])
+            .get(),
+        2.0
+    );
+
+    // Closing orders still go out
+    h.guarded
+        .place_order(
+            &OrderRequest::market("BTC", OrderSide::Sell, 0.1, 38000.0).with_reduce_only(true),
+        )
+        .await
+        .unwrap();
+
+    h.kill_switch.deactivate().await.unwrap();
+    h.guarded
+        .place_order(&OrderRequest::limit("BTC", OrderSide::Buy, 0.1, 40000.0))
+        .await
+        .unwrap();
+    assert_eq!(h.exchange.placed().len(), 2);
+}
+
+#[tokio::test]
+async fn flatten_request_cancels_orders_and_closes_positions_once() {
+    let h = harness(RiskLimits::default());
+    let resting =
+        h.exchange
+            .add_external_order(OrderRequest::limit("ETH", OrderSide::Buy, 1.0, 1900.0));
+    h.exchange.set_positions(vec![
+        position("BTC", 0.5, 40000.0, 500.0),
+        position("ETH", -2.0, 2000.0, 100.0),
+    ]);
+
+    h.kill_switch.activate("drawdown", true).await.unwrap();
+    let state = h.guarded.enforce_kill_switch().await;
+    assert!(state.active);
+    assert!(!state.flatten_requested);
+    assert!(state.flattened_at.is_some());
+
+    assert_eq!(h.exchange.cancelled(), vec![resting]);
+    let placed = h.exchange.placed();
+    assert_eq!(placed.len(), 2);
+    assert!(placed
+        .iter()
+        .all(|o| o.reduce_only && o.order_type == OrderType::Market));
+
+    // Long BTC marked at 41000 is sold no lower than 5% below the mark
+    let btc = placed.iter().find(|o| o.symbol == "BTC").unwrap();
+    assert_eq!(btc.side, OrderSide::Sell);
+    assert_eq!(btc.size, 0.5);
+    assert!((btc.price - 41000.0 * 0.95).abs() < 1e-6);
+    // Short ETH marked at 1950 is bought back no higher than 5% above the mark
+    let eth = placed.iter().find(|o| o.symbol == "ETH").unwrap();
+    assert_eq!(eth.side, OrderSide::Buy);
+    assert_eq!(eth.size, 2.0);
+    assert!((eth.price - 1950.0 * 1.05).abs() < 1e-6);
+
+    // The request is carried out only once
+    h.guarded.enforce_kill_switch().await;
+    assert_eq!(h.exchange.placed().len(), 2);
+}
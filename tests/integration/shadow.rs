@@ -0,0 +1,154 @@
+//! Integration tests for shadow execution
+//!
+//! Runs the policy engine and OMS on a shadow executor wrapping the in-memory
+//! exchange, which must never receive an order.
+
+use std::sync::Arc;
+
+use crate::mock_exchange::{MemoryOrderStore, MockExecutor};
+use chrono::Utc;
+use perptrix::db::OrderStore;
+use perptrix::execution::{
+    ExchangeExecutor, ExchangePosition, ExecutionError, ExecutionPolicy, ManagedOrder,
+    OrderManager, OrderOrigin, OrderRequest, OrderSide, OrderState, OrderType, PolicyEngine,
+    ShadowExecutor, SignalAction, TimeInForce,
+};
+use perptrix::models::signal::{SignalDirection, SignalOutput};
+
+struct Harness {
+    exchange: Arc<MockExecutor>,
+    shadow: Arc<ShadowExecutor>,
+    store: Arc<MemoryOrderStore>,
+    oms: Arc<OrderManager>,
+}
+
+fn harness() -> Harness {
+    let exchange = Arc::new(MockExecutor::new());
+    exchange.set_book("BTC", 39990.0, 40010.0);
+    let shadow = Arc::new(ShadowExecutor::new(exchange.clone()));
+    let store = Arc::new(MemoryOrderStore::default());
+    let oms = Arc::new(OrderManager::new(shadow.clone(), store.clone()));
+    Harness {
+        exchange,
+        shadow,
+        store,
+        oms,
+    }
+}
+
+#[tokio::test]
+async fn signal_path_records_orders_without_sending_them() {
+    let h = harness();
+    h.exchange.set_positions(vec![ExchangePosition {
+        symbol: "ETH".to_string(),
+        size: 2.0,
+        entry_price: 1800.0,
+        unrealized_pnl: 0.0,
+        leverage: 5.0,
+        liquidation_price: None,
+    }]);
+    let engine = PolicyEngine::new(h.oms.clone());
+    let signal = SignalOutput::new(
+        SignalDirection::Long,
+        0.8,
+        2.0,
+        4.0,
+        vec![],
+        "BTC".to_string(),
+        40000.0,
+    );
+
+    let execution = engine
+        .execute(7, &ExecutionPolicy::new(1000.0), &signal)
+        .await
+        .unwrap();
+    assert_eq!(execution.action, SignalAction::Open(OrderSide::Buy));
+
+    // Entry assumed filled at the ask, so the stop loss and take profit follow
+    assert_eq!(execution.orders.len(), 3);
+    let entry = &execution.orders[0];
+    assert_eq!(entry.exchange, "mock-shadow");
+    assert_eq!(entry.state, OrderState::Filled);
+    assert_eq!(entry.avg_fill_price, Some(40010.0));
+    assert_eq!(h.shadow.intended_orders().await.len(), 3);
+    assert!(h.exchange.placed().is_empty());
+
+    // Real account state still comes from the exchange
+    assert_eq!(h.shadow.positions().await.unwrap()[0].symbol, "ETH");
+    assert!(h
+        .store
+        .rows
+        .lock()
+        .await
+        .iter()
+        .all(|o| o.origin.strategy_id == Some(7)));
+}
+
+#[tokio::test]
+async fn resting_orders_can_be_cancelled_and_survive_reconciliation() {
+    let h = harness();
+    // A real order on the account is not the shadow OMS's business
+    h.exchange
+        .add_external_order(OrderRequest::limit("BTC", OrderSide::Sell, 1.0, 45000.0));
+
+    let resting = h
+        .oms
+        .submit(
+            OrderRequest::limit("BTC", OrderSide::Buy, 0.1, 39000.0),
+            OrderOrigin::manual(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resting.state, OrderState::Acknowledged);
+
+    let report = h.oms.reconcile().await.unwrap();
+    assert!(report.untracked.is_empty());
+    assert_eq!(
+        h.oms.order(&resting.client_order_id).await.unwrap().state,
+        OrderState::Acknowledged
+    );
+
+    let cancelled = h.oms.cancel(&resting.client_order_id).await.unwrap();
+    assert_eq!(cancelled.state, OrderState::Cancelled);
+    assert!(h.exchange.cancelled().is_empty());
+
+    h.shadow.set_leverage("BTC", 10, true).await.unwrap();
+    assert!(h.exchange.leverage_calls().is_empty());
+}
+
+#[tokio::test]
+async fn orders_that_would_not_match_are_rejected_like_the_exchange() {
+    let h = harness();
+
+    let ioc = OrderRequest::limit("BTC", OrderSide::Buy, 0.1, 40000.0)
+        .with_order_type(OrderType::Limit(TimeInForce::Ioc));
+    let err = h.oms.submit(ioc, OrderOrigin::manual()).await.unwrap_err();
+    assert!(matches!(err, ExecutionError::Rejected(_)));
+
+    let post_only = OrderRequest::limit("BTC", OrderSide::Sell, 0.1, 39990.0)
+        .with_order_type(OrderType::Limit(TimeInForce::Alo));
+    let err = h
+        .oms
+        .submit(post_only, OrderOrigin::manual())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ExecutionError::Rejected(_)));
+
+    assert_eq!(h.shadow.intended_orders().await.len(), 2);
+    assert!(h.exchange.placed().is_empty());
+}
+
+#[tokio::test]
+async fn restore_only_picks_up_orders_of_its_own_exchange() {
+    let h = harness();
+    let request = OrderRequest::limit("BTC", OrderSide::Buy, 0.1, 39000.0);
+    let mut live = ManagedOrder::new("mock", request.clone(), OrderOrigin::manual(), Utc::now());
+    live.state = OrderState::Acknowledged;
+    h.store.save_order(&live).await.unwrap();
+    let shadow = ManagedOrder::new("mock-shadow", request, OrderOrigin::manual(), Utc::now());
+    h.store.save_order(&shadow).await.unwrap();
+
+    h.oms.restore().await.unwrap();
+    assert!(h.oms.order(&live.client_order_id).await.is_none());
+    assert!(h.oms.order(&shadow.client_order_id).await.is_some());
+}
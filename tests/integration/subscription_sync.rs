@@ -0,0 +1,264 @@
+//! Integration tests for runtime subscription management
+//!
+//! Drives a [`SubscriptionSync`] over a recording provider, a mutable symbol
+//! source standing in for the strategy table and an in-memory subscription
+//! store.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use perptrix::db::CandleStore;
+use perptrix::metrics::Metrics;
+use perptrix::models::indicators::Candle;
+use perptrix::services::candle_gaps::CandleGapAuditor;
+use perptrix::services::market_data::{MarketDataProvider, DEFAULT_VENUE};
+use perptrix::services::subscription_sync::{
+    MemorySubscriptionStore, SubscriptionCommand, SubscriptionSource, SubscriptionStore,
+    SubscriptionSync, SymbolSource,
+};
+use tokio::sync::Mutex;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Records (un)subscriptions; symbols in `failing` fail once
+#[derive(Default)]
+struct RecordingProvider {
+    calls: Mutex<Vec<String>>,
+    failing: Mutex<HashSet<String>>,
+}
+
+impl RecordingProvider {
+    async fn calls(&self) -> Vec<String> {
+        self.calls.lock().await.clone()
+    }
+}
+
+#[async_trait]
+impl MarketDataProvider for RecordingProvider {
+    async fn get_candles(&self, _symbol: &str, _limit: usize) -> Result<Vec<Candle>, BoxError> {
+        Ok(Vec::new())
+    }
+
+    async fn get_latest_price(&self, _symbol: &str) -> Result<f64, BoxError> {
+        Ok(0.0)
+    }
+
+    async fn subscribe(&self, symbol: &str) -> Result<(), BoxError> {
+        if self.failing.lock().await.remove(symbol) {
+            return Err(format!("Subscription to {} rejected", symbol).into());
+        }
+        self.calls.lock().await.push(format!("+{}", symbol));
+        Ok(())
+    }
+
+    async fn unsubscribe(&self, symbol: &str) -> Result<(), BoxError> {
+        self.calls.lock().await.push(format!("-{}", symbol));
+        Ok(())
+    }
+}
+
+/// Strategy symbols that tests change between reloads
+#[derive(Default)]
+struct Strategies {
+    symbols: Mutex<Vec<String>>,
+    unavailable: Mutex<bool>,
+}
+
+impl Strategies {
+    async fn set(&self, symbols: &[&str]) {
+        *self.symbols.lock().await = symbols.iter().map(|s| s.to_string()).collect();
+    }
+}
+
+#[async_trait]
+impl SymbolSource for Strategies {
+    async fn symbols(&self) -> Result<Vec<String>, BoxError> {
+        if *self.unavailable.lock().await {
+            return Err("Strategy table unavailable".into());
+        }
+        Ok(self.symbols.lock().await.clone())
+    }
+}
+
+struct EmptyCandleStore;
+
+#[async_trait]
+impl CandleStore for EmptyCandleStore {
+    async fn latest_candle_timestamp(
+        &self,
+        _symbol: &str,
+        _interval: &str,
+    ) -> Result<Option<DateTime<Utc>>, BoxError> {
+        Ok(None)
+    }
+
+    async fn store_candles_batch(
+        &self,
+        _symbol: &str,
+        _interval: &str,
+        _candles: &[Candle],
+    ) -> Result<(), BoxError> {
+        Ok(())
+    }
+
+    async fn candles_in_range(
+        &self,
+        _symbol: &str,
+        _interval: &str,
+        _start: Option<DateTime<Utc>>,
+        _end: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Candle>, BoxError> {
+        Ok(Vec::new())
+    }
+}
+
+struct Fixture {
+    provider: Arc<RecordingProvider>,
+    strategies: Arc<Strategies>,
+    store: Arc<MemorySubscriptionStore>,
+    sync: SubscriptionSync,
+}
+
+fn fixture(static_symbols: &[&str]) -> Fixture {
+    let provider = Arc::new(RecordingProvider::default());
+    let strategies = Arc::new(Strategies::default());
+    let store = Arc::new(MemorySubscriptionStore::default());
+    let static_symbols: Vec<String> = static_symbols.iter().map(|s| s.to_string()).collect();
+    let sync = SubscriptionSync::new(provider.clone())
+        .with_static_symbols(&static_symbols)
+        .with_source(strategies.clone())
+        .with_store(store.clone());
+    Fixture {
+        provider,
+        strategies,
+        store,
+        sync,
+    }
+}
+
+#[tokio::test]
+async fn strategy_symbols_are_subscribed_and_unsubscribed_on_reload() {
+    let f = fixture(&["BTC"]);
+    let metrics = Arc::new(Metrics::new().unwrap());
+    let sync = f.sync.with_metrics(metrics.clone());
+
+    f.strategies.set(&["ETH", "BTC"]).await;
+    let report = sync.reload().await.unwrap();
+    assert_eq!(report.subscribed, vec!["BTC", "ETH"]);
+    assert!(report.unsubscribed.is_empty());
+
+    // Symbols are normalized; BTC stays subscribed through SYMBOLS
+    f.strategies.set(&["binance:btcusdt", "SOL-PERP"]).await;
+    let report = sync.reload().await.unwrap();
+    assert_eq!(report.subscribed, vec!["SOL", "binance:BTCUSDT"]);
+    assert_eq!(report.unsubscribed, vec!["ETH"]);
+    assert_eq!(
+        f.provider.calls().await,
+        vec!["+BTC", "+ETH", "+SOL", "+binance:BTCUSDT", "-ETH"]
+    );
+
+    // Nothing changed, nothing to do
+    assert_eq!(sync.reload().await.unwrap(), Default::default());
+
+    let saved = f.store.load_subscriptions().await.unwrap();
+    let symbols: Vec<&str> = saved.iter().map(|s| s.symbol.as_str()).collect();
+    assert_eq!(symbols, vec!["BTC", "SOL", "binance:BTCUSDT"]);
+    assert_eq!(saved[0].sources, vec![SubscriptionSource::Static]);
+    assert_eq!(saved[1].sources, vec![SubscriptionSource::Strategy]);
+    assert_eq!(saved, sync.active().await);
+    assert_eq!(metrics.market_data_subscriptions.get(), 3.0);
+}
+
+#[tokio::test]
+async fn manual_subscriptions_follow_commands() {
+    let f = fixture(&["BTC"]);
+    f.strategies.set(&["ETH"]).await;
+    f.sync.reload().await.unwrap();
+
+    let report = f
+        .sync
+        .handle(&SubscriptionCommand::Subscribe {
+            symbol: "sol-perp".to_string(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(report.subscribed, vec!["SOL"]);
+
+    // A manual subscription of a strategy symbol adds a source, not a subscription
+    f.sync
+        .handle(&SubscriptionCommand::Subscribe {
+            symbol: "ETH".to_string(),
+        })
+        .await
+        .unwrap();
+    let eth = f
+        .sync
+        .active()
+        .await
+        .into_iter()
+        .find(|s| s.symbol == "ETH")
+        .unwrap();
+    assert_eq!(
+        eth.sources,
+        vec![SubscriptionSource::Strategy, SubscriptionSource::Manual]
+    );
+
+    for symbol in ["SOL", "ETH", "BTC"] {
+        f.sync
+            .handle(&SubscriptionCommand::Unsubscribe {
+                symbol: symbol.to_string(),
+            })
+            .await
+            .unwrap();
+    }
+    // Only SOL was kept by nothing but the manual subscription
+    assert_eq!(
+        f.provider.calls().await,
+        vec!["+BTC", "+ETH", "+SOL", "-SOL"]
+    );
+
+    f.strategies.set(&[]).await;
+    let report = f.sync.handle(&SubscriptionCommand::Reload).await.unwrap();
+    assert_eq!(report.unsubscribed, vec!["ETH"]);
+}
+
+#[tokio::test]
+async fn failed_subscriptions_are_retried_and_source_errors_keep_symbols() {
+    let f = fixture(&[]);
+    f.provider.failing.lock().await.insert("ETH".to_string());
+    f.strategies.set(&["BTC", "ETH"]).await;
+
+    let report = f.sync.reload().await.unwrap();
+    assert_eq!(report.subscribed, vec!["BTC"]);
+    assert_eq!(report.failed, vec!["ETH"]);
+
+    let report = f.sync.reload().await.unwrap();
+    assert_eq!(report.subscribed, vec!["ETH"]);
+
+    // An unreadable strategy table does not unsubscribe anything
+    *f.strategies.unavailable.lock().await = true;
+    assert!(f.sync.reload().await.is_err());
+    assert_eq!(f.sync.active().await.len(), 2);
+}
+
+#[tokio::test]
+async fn subscribed_symbols_are_audited_for_gaps() {
+    let f = fixture(&[]);
+    let auditor = Arc::new(CandleGapAuditor::new(Arc::new(EmptyCandleStore)));
+    let intervals = HashMap::from([(DEFAULT_VENUE.to_string(), vec!["1m".to_string()])]);
+    let sync = f.sync.with_auditor(auditor.clone(), intervals);
+
+    f.strategies.set(&["BTC", "binance:BTCUSDT"]).await;
+    sync.reload().await.unwrap();
+    // Venues without audited intervals are not watched
+    assert_eq!(
+        auditor.watched(),
+        vec![("BTC".to_string(), "1m".to_string())]
+    );
+
+    f.strategies.set(&["binance:BTCUSDT"]).await;
+    sync.reload().await.unwrap();
+    assert!(auditor.watched().is_empty());
+}
@@ -45,6 +45,11 @@ impl TestApp {
             metrics: metrics.clone(),
             start_time: Arc::new(Instant::now()),
             database: None,
+            kill_switch: None,
+            algos: None,
+            instruments: Arc::new(RwLock::new(None)),
+            subscriptions: None,
+            execution_auth: None,
         };
 
         let router = create_router(state);
@@ -0,0 +1,140 @@
+//! Integration tests for the Hyperliquid user event stream
+//!
+//! Feeds user channel messages through a mock WebSocket into the OMS.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::mock_exchange::{MemoryOrderStore, MockExecutor};
+use perptrix::execution::{OrderManager, OrderOrigin, OrderRequest, OrderSide, OrderState};
+use perptrix::services::hyperliquid::client::ClientEvent;
+use perptrix::services::hyperliquid::{HyperliquidUserEvents, MockWebSocketClient};
+use tokio_tungstenite::tungstenite::Message;
+
+const USER: &str = "0x2C7536E3605D9C16A7A3D7B1898E529396A65C23";
+
+async fn wait_for_state(oms: &OrderManager, client_order_id: &str, state: OrderState) -> bool {
+    for _ in 0..50 {
+        if oms.order(client_order_id).await.map(|o| o.state) == Some(state) {
+            return true;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    false
+}
+
+#[tokio::test]
+async fn stream_subscribes_after_connecting() {
+    let client = Arc::new(MockWebSocketClient::new());
+    let stream = Arc::new(HyperliquidUserEvents::with_websocket_client(
+        client.clone(),
+        USER,
+    ));
+    let runner = stream.clone();
+    let handle = tokio::spawn(async move { runner.run().await });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let sent: Vec<serde_json::Value> = client
+        .sent_messages()
+        .await
+        .into_iter()
+        .filter_map(|m| match m {
+            Message::Text(text) => serde_json::from_str(text.as_str()).ok(),
+            _ => None,
+        })
+        .collect();
+    let types: Vec<&str> = sent
+        .iter()
+        .filter_map(|m| m["subscription"]["type"].as_str())
+        .collect();
+    assert_eq!(
+        types,
+        vec![
+            "notification",
+            "orderUpdates",
+            "userFills",
+            "userFundings",
+            "webData2"
+        ]
+    );
+    assert!(sent
+        .iter()
+        .all(|m| m["subscription"]["user"] == USER.to_lowercase()));
+
+    handle.abort();
+}
+
+#[tokio::test]
+async fn fills_and_order_updates_reach_the_oms_without_polling() {
+    let exchange = Arc::new(MockExecutor::new());
+    let oms = Arc::new(OrderManager::new(
+        exchange.clone(),
+        Arc::new(MemoryOrderStore::default()),
+    ));
+    let order = oms
+        .submit(
+            OrderRequest::limit("BTC", OrderSide::Buy, 1.0, 40000.0),
+            OrderOrigin::manual(),
+        )
+        .await
+        .unwrap();
+    let oid = exchange.order_id(&order.client_order_id).unwrap();
+
+    let client = Arc::new(MockWebSocketClient::new());
+    let stream = Arc::new(HyperliquidUserEvents::with_websocket_client(
+        client.clone(),
+        USER,
+    ));
+    let follower = oms.clone();
+    let events = stream.subscribe();
+    let follow = tokio::spawn(async move { follower.follow_user_events(events).await });
+    let runner = stream.clone();
+    let run = tokio::spawn(async move { runner.run().await });
+
+    // Partial fill first, then the rest with the closing order update
+    client
+        .push_event(ClientEvent::Message(format!(
+            r#"{{"channel":"userFills","data":{{"user":"{user}","fills":[
+                {{"coin":"BTC","px":"39990.0","sz":"0.4","side":"B","time":1700000000000,
+                  "startPosition":"0.0","dir":"Open Long","closedPnl":"0.0","hash":"0x01",
+                  "oid":{oid},"crossed":false,"fee":"0.5","tid":1,"feeToken":"USDC"}}
+            ]}}}}"#,
+            user = USER.to_lowercase(),
+            oid = oid
+        )))
+        .await;
+    assert!(wait_for_state(&oms, &order.client_order_id, OrderState::PartiallyFilled).await);
+
+    client
+        .push_event(ClientEvent::Message(format!(
+            r#"{{"channel":"userFills","data":{{"user":"{user}","fills":[
+                {{"coin":"BTC","px":"40000.0","sz":"0.6","side":"B","time":1700000001000,
+                  "startPosition":"0.4","dir":"Open Long","closedPnl":"0.0","hash":"0x02",
+                  "oid":{oid},"crossed":false,"fee":"0.7","tid":2,"feeToken":"USDC"}}
+            ]}}}}"#,
+            user = USER.to_lowercase(),
+            oid = oid
+        )))
+        .await;
+    client
+        .push_event(ClientEvent::Message(format!(
+            r#"{{"channel":"orderUpdates","data":[
+                {{"order":{{"coin":"BTC","side":"B","limitPx":"40000.0","sz":"0.0","oid":{oid},
+                  "timestamp":1700000000000,"origSz":"1.0","cloid":"{cloid}"}},
+                  "status":"filled","statusTimestamp":1700000001000}}
+            ]}}"#,
+            oid = oid,
+            cloid = order.client_order_id
+        )))
+        .await;
+    assert!(wait_for_state(&oms, &order.client_order_id, OrderState::Filled).await);
+
+    let filled = oms.order(&order.client_order_id).await.unwrap();
+    assert_eq!(filled.filled_size, 1.0);
+    assert_eq!(filled.fill_ids, vec![1, 2]);
+    assert!((filled.fees_paid - 1.2).abs() < 1e-9);
+    assert!((filled.avg_fill_price.unwrap() - 39996.0).abs() < 1e-6);
+
+    run.abort();
+    follow.abort();
+}
@@ -1,10 +1,22 @@
 //! Integration tests for the WebSocket Service
 //!
-//! Tests WebSocket connection, subscription management, and data ingestion.
+//! Tests WebSocket connection, subscription management, data ingestion and
+//! record/replay of raw client events.
 
 #[path = "websocket_service/test_utils.rs"]
 mod test_utils;
 
+use perptrix::services::hyperliquid::client::ClientEvent;
+use perptrix::services::hyperliquid::recording::read_recording;
+use perptrix::services::hyperliquid::{
+    HyperliquidMarketDataProvider, HyperliquidRestClient, MockWebSocketClient,
+    RecordingWebSocketClient, ReplayWebSocketClient, WebSocketClient,
+};
+use perptrix::services::candle_events::CandleClosed;
+use perptrix::services::market_bus::MarketEvent;
+use perptrix::services::market_data::MarketDataProvider;
+use std::sync::Arc;
+use tokio::sync::broadcast;
 use tokio::time::{sleep, Duration};
 
 use test_utils::TestWebSocketService;
@@ -126,7 +138,7 @@ async fn websocket_service_handles_reconnection() {
     sleep(Duration::from_millis(100)).await;
     
     // Service should still be running
-    assert!(true, "Service should handle disconnection gracefully");
+    // Service should handle disconnection gracefully
 }
 
 #[tokio::test]
@@ -138,7 +150,7 @@ async fn websocket_service_is_singleton_aware() {
     
     // Both should initialize independently
     // In production, only one should run
-    assert!(true, "Multiple services can be created, but only one should run");
+    // Multiple services can be created, but only one should run
 }
 
 #[tokio::test]
@@ -149,6 +161,341 @@ async fn websocket_service_stores_data_to_cache() {
     
     // Service should be able to write to storage when configured
     // This is a placeholder for future test with actual storage
-    assert!(true, "Service should store data when storage is configured");
+    // Service should store data when storage is configured
 }
 
+
+fn candle_message(minute: u64, close: f64) -> String {
+    serde_json::json!({
+        "channel": "candle",
+        "data": {
+            "t": minute * 60_000,
+            "T": (minute + 1) * 60_000 - 1,
+            "s": "BTC",
+            "i": "1m",
+            "o": "100",
+            "h": "110",
+            "l": "90",
+            "c": close.to_string(),
+            "v": "10",
+            "n": 1
+        }
+    })
+    .to_string()
+}
+
+fn replay_provider(
+    websocket: Arc<dyn WebSocketClient>,
+    rest_uri: String,
+) -> HyperliquidMarketDataProvider {
+    let rest_client = Arc::new(HyperliquidRestClient::with_client(
+        rest_uri,
+        reqwest::Client::new(),
+    ));
+    HyperliquidMarketDataProvider::with_clients(websocket, rest_client, vec!["1m".to_string()])
+}
+
+/// Poll the in-memory buffer until `count` candles arrived (the provider pauses
+/// after `Connected` before draining further events)
+async fn wait_for_candles(
+    provider: &HyperliquidMarketDataProvider,
+    count: usize,
+) -> Vec<perptrix::models::indicators::Candle> {
+    let start = std::time::Instant::now();
+    loop {
+        let candles = provider.get_candles("BTC", 10).await.expect("Candles");
+        if candles.len() >= count || start.elapsed() > Duration::from_secs(3) {
+            return candles;
+        }
+        sleep(Duration::from_millis(50)).await;
+    }
+}
+
+#[tokio::test]
+async fn websocket_recording_replays_to_identical_candles() {
+    let rest = wiremock::MockServer::start().await;
+    test_utils::mock_hyperliquid_funding_history(&rest).await;
+
+    let path = std::env::temp_dir().join(format!(
+        "perptrix-ws-recording-{}-{}.jsonl",
+        std::process::id(),
+        chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+    ));
+
+    // Record a live session driven by the mock client
+    let mock = Arc::new(MockWebSocketClient::new());
+    let recorder = Arc::new(
+        RecordingWebSocketClient::to_file(mock.clone(), &path)
+            .await
+            .expect("Recording file should open"),
+    );
+    let live = replay_provider(recorder, rest.uri());
+    sleep(Duration::from_millis(50)).await;
+    for (minute, close) in [(0, 101.0), (1, 102.5), (2, 99.0)] {
+        mock.push_event(ClientEvent::Message(candle_message(minute, close)))
+            .await;
+    }
+    let recorded = wait_for_candles(&live, 3).await;
+    assert_eq!(recorded.len(), 3);
+
+    // Replay the file without network access to the exchange
+    let events = read_recording(&path).await.expect("Recording should parse");
+    assert!(matches!(events.first().map(|e| &e.event), Some(ClientEvent::Connected)));
+    assert_eq!(events.len(), 4);
+
+    let replay = Arc::new(
+        ReplayWebSocketClient::from_file(&path)
+            .await
+            .expect("Recording should load")
+            .with_speed(0.0),
+    );
+    let replayed_provider = replay_provider(replay.clone(), rest.uri());
+    assert!(replay.wait_until_finished(Duration::from_secs(2)).await);
+    assert!(replay.wait_for_connection(Duration::from_secs(1)).await);
+
+    let replayed = wait_for_candles(&replayed_provider, 3).await;
+    assert_eq!(replayed.len(), recorded.len());
+    for (a, b) in recorded.iter().zip(&replayed) {
+        assert_eq!(a.timestamp, b.timestamp);
+        assert_eq!((a.open, a.high, a.low, a.close), (b.open, b.high, b.low, b.close));
+        assert_eq!(a.funding_rate, b.funding_rate);
+    }
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn asset_contexts_are_attached_to_live_candles() {
+    let rest = wiremock::MockServer::start().await;
+    test_utils::mock_hyperliquid_funding_history(&rest).await;
+
+    let mock = Arc::new(MockWebSocketClient::new());
+    let provider = replay_provider(mock.clone(), rest.uri());
+    assert!(mock.wait_for_connection(Duration::from_secs(1)).await);
+    provider.subscribe("BTC").await.expect("Subscribe");
+
+    let sent = mock.sent_messages().await;
+    assert!(sent.iter().any(|m| matches!(m,
+        tokio_tungstenite::tungstenite::Message::Text(text)
+            if text.contains("\"activeAssetCtx\"") && text.contains("\"BTC\""))));
+
+    let context = serde_json::json!({
+        "channel": "activeAssetCtx",
+        "data": {
+            "coin": "BTC",
+            "ctx": {
+                "dayNtlVlm": "1250000.5",
+                "prevDayPx": "98.0",
+                "markPx": "101.5",
+                "midPx": "101.4",
+                "funding": "0.0000125",
+                "openInterest": "4321.0",
+                "oraclePx": "101.0",
+                "premium": "0.0005"
+            }
+        }
+    });
+    let mut events = provider.subscribe_market_events();
+    mock.push_event(ClientEvent::Message(context.to_string()))
+        .await;
+    mock.push_event(ClientEvent::Message(candle_message(0, 101.0)))
+        .await;
+
+    let candles = wait_for_candles(&provider, 1).await;
+    let candle = candles.last().expect("Live candle");
+    assert_eq!(candle.close, 101.0);
+    assert_eq!(candle.open_interest, Some(4321.0));
+    assert_eq!(candle.mark_price, Some(101.5));
+    assert_eq!(candle.oracle_price, Some(101.0));
+    assert_eq!(candle.premium, Some(0.0005));
+    assert_eq!(candle.day_notional_volume, Some(1_250_000.5));
+
+    let latest = provider.asset_context("BTC").await.expect("Asset context");
+    assert_eq!(latest.open_interest, Some(4321.0));
+
+    // The context's funding and mid price are published as market events
+    let mut funding = None;
+    let mut mid = None;
+    while funding.is_none() || mid.is_none() {
+        match tokio::time::timeout(Duration::from_secs(1), events.recv())
+            .await
+            .expect("Market event")
+            .unwrap()
+        {
+            MarketEvent::FundingUpdate { symbol, funding_rate, .. } if symbol == "BTC" => {
+                funding = Some(funding_rate)
+            }
+            MarketEvent::MidPrice { symbol, price, .. } if symbol == "BTC" => mid = Some(price),
+            _ => {}
+        }
+    }
+    assert_eq!(funding, Some(0.0000125));
+    assert_eq!(mid, Some(101.4));
+}
+
+/// Next candle close among the market events, skipping other events
+async fn next_close(events: &mut broadcast::Receiver<MarketEvent>) -> Option<CandleClosed> {
+    loop {
+        match tokio::time::timeout(Duration::from_secs(1), events.recv()).await {
+            Ok(Ok(MarketEvent::CandleClosed(closed))) => return Some(closed),
+            Ok(Ok(_)) => continue,
+            _ => return None,
+        }
+    }
+}
+
+#[tokio::test]
+async fn forming_candles_close_when_a_later_bar_arrives() {
+    let rest = wiremock::MockServer::start().await;
+    test_utils::mock_hyperliquid_funding_history(&rest).await;
+
+    let mock = Arc::new(MockWebSocketClient::new());
+    let provider = replay_provider(mock.clone(), rest.uri());
+    let mut events = provider.subscribe_market_events();
+    sleep(Duration::from_millis(50)).await;
+
+    // Bars that end in the future are still forming
+    let minute = chrono::Utc::now().timestamp_millis() as u64 / 60_000 + 5;
+    mock.push_event(ClientEvent::Message(candle_message(minute, 101.0)))
+        .await;
+    mock.push_event(ClientEvent::Message(candle_message(minute, 101.5)))
+        .await;
+    let mut candles = Vec::new();
+    for _ in 0..60 {
+        candles = provider.get_candles("BTC", 10).await.expect("Candles");
+        if candles.first().map(|c| c.close) == Some(101.5) {
+            break;
+        }
+        sleep(Duration::from_millis(50)).await;
+    }
+    assert_eq!(candles.len(), 1);
+    assert_eq!(candles[0].close, 101.5);
+    assert!(!candles[0].is_closed);
+
+    mock.push_event(ClientEvent::Message(candle_message(minute + 1, 102.0)))
+        .await;
+    let candles = wait_for_candles(&provider, 2).await;
+    assert_eq!(candles.len(), 2);
+    assert!(candles[0].is_closed);
+    assert_eq!(candles[0].close, 101.5);
+    assert!(!candles[1].is_closed);
+
+    // Every update is published, and the close is announced once, for the superseded bar
+    let mut updates = Vec::new();
+    let mut closes = Vec::new();
+    while updates.len() < 3 || closes.is_empty() {
+        let event = tokio::time::timeout(Duration::from_secs(1), events.recv())
+            .await
+            .expect("Market event")
+            .unwrap();
+        match event {
+            MarketEvent::CandleUpdate { candle, .. } => updates.push(candle.close),
+            MarketEvent::CandleClosed(closed) => closes.push(closed),
+            _ => {}
+        }
+    }
+    assert!(events.try_recv().is_err());
+    assert_eq!(updates, vec![101.0, 101.5, 102.0]);
+    assert_eq!(closes.len(), 1);
+    assert_eq!(
+        (closes[0].symbol.as_str(), closes[0].interval.as_str()),
+        ("BTC", "1m")
+    );
+    assert_eq!(closes[0].timestamp, candles[0].timestamp);
+
+    // Bars whose end time has passed are closed on arrival
+    mock.push_event(ClientEvent::Message(candle_message(0, 99.0)))
+        .await;
+    let candles = wait_for_candles(&provider, 3).await;
+    assert!(candles[0].is_closed);
+    let event = next_close(&mut events).await.expect("Candle close event");
+    assert_eq!(event.timestamp, candles[0].timestamp);
+
+    // Late updates of a closed bar are neither persisted nor announced again
+    mock.push_event(ClientEvent::Message(candle_message(0, 99.5)))
+        .await;
+    assert!(next_close(&mut events).await.is_none());
+    let candles = provider.get_candles("BTC", 10).await.expect("Candles");
+    assert_eq!(candles[0].close, 99.5);
+}
+
+fn candle_subscriptions(sent: &[tokio_tungstenite::tungstenite::Message]) -> usize {
+    sent.iter()
+        .filter(|m| matches!(m,
+            tokio_tungstenite::tungstenite::Message::Text(text)
+                if text.contains("\"subscribe\"") && text.contains("\"candle\"")))
+        .count()
+}
+
+#[tokio::test]
+async fn reconnect_resubscribes_and_drops_forming_bars() {
+    let rest = wiremock::MockServer::start().await;
+    test_utils::mock_hyperliquid_funding_history(&rest).await;
+
+    let mock = Arc::new(MockWebSocketClient::new());
+    let provider = replay_provider(mock.clone(), rest.uri());
+    let mut connections = provider.subscribe_connections();
+    assert!(mock.wait_for_connection(Duration::from_secs(1)).await);
+    // The initial connection is announced too
+    tokio::time::timeout(Duration::from_secs(3), connections.recv())
+        .await
+        .expect("Connection notification")
+        .unwrap();
+    provider.subscribe("BTC").await.expect("Subscribe");
+    assert_eq!(candle_subscriptions(&mock.sent_messages().await), 1);
+
+    let minute = chrono::Utc::now().timestamp_millis() as u64 / 60_000 + 5;
+    mock.push_event(ClientEvent::Message(candle_message(0, 99.0)))
+        .await;
+    mock.push_event(ClientEvent::Message(candle_message(minute, 101.0)))
+        .await;
+    assert_eq!(wait_for_candles(&provider, 2).await.len(), 2);
+
+    mock.push_event(ClientEvent::Disconnected).await;
+    mock.push_event(ClientEvent::Connected).await;
+    tokio::time::timeout(Duration::from_secs(3), connections.recv())
+        .await
+        .expect("Connection notification")
+        .unwrap();
+
+    // The candle subscription is sent again on the new connection
+    assert_eq!(candle_subscriptions(&mock.sent_messages().await), 2);
+    // Only the closed bar survives; the forming one is left to the gap repair
+    let candles = provider.get_candles("BTC", 10).await.expect("Candles");
+    assert_eq!(candles.len(), 1);
+    assert!(candles[0].is_closed);
+}
+
+#[tokio::test]
+async fn unsubscribed_coins_are_not_resubscribed_on_reconnect() {
+    let rest = wiremock::MockServer::start().await;
+    test_utils::mock_hyperliquid_funding_history(&rest).await;
+
+    let mock = Arc::new(MockWebSocketClient::new());
+    let provider = replay_provider(mock.clone(), rest.uri());
+    let mut connections = provider.subscribe_connections();
+    assert!(mock.wait_for_connection(Duration::from_secs(1)).await);
+    tokio::time::timeout(Duration::from_secs(3), connections.recv())
+        .await
+        .expect("Connection notification")
+        .unwrap();
+    provider.subscribe("BTC").await.expect("Subscribe");
+
+    provider.unsubscribe("BTC").await.expect("Unsubscribe");
+    let sent = mock.sent_messages().await;
+    let unsubscribed = |channel: &str| {
+        sent.iter().any(|m| matches!(m,
+            tokio_tungstenite::tungstenite::Message::Text(text)
+                if text.contains("\"unsubscribe\"") && text.contains(channel) && text.contains("\"BTC\"")))
+    };
+    assert!(unsubscribed("\"candle\""));
+    assert!(unsubscribed("\"activeAssetCtx\""));
+
+    mock.push_event(ClientEvent::Disconnected).await;
+    mock.push_event(ClientEvent::Connected).await;
+    tokio::time::timeout(Duration::from_secs(3), connections.recv())
+        .await
+        .expect("Connection notification")
+        .unwrap();
+    assert_eq!(candle_subscriptions(&mock.sent_messages().await), 1);
+}
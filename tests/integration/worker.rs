@@ -203,9 +203,6 @@ async fn worker_reads_from_cache_not_websocket() {
     // Worker's data provider should be read-only
     // It should not have WebSocket client
     // This is verified by the test setup - workers use read-only provider
-    assert!(
-        true,
-        "Worker should use read-only data provider (no WebSocket connections)"
-    );
+    // Worker should use read-only data provider (no WebSocket connections)
 }
 
@@ -57,8 +57,65 @@ mod engine_aggregator;
 #[path = "unit/services/market_data.rs"]
 mod services_market_data;
 
+#[path = "unit/services/instruments.rs"]
+mod services_instruments;
+
+#[path = "unit/services/candle_gaps.rs"]
+mod services_candle_gaps;
+
+#[path = "unit/services/subscription_sync.rs"]
+mod services_subscription_sync;
+
+#[path = "unit/services/market_bus.rs"]
+mod services_market_bus;
+
+#[path = "unit/services/candle_io.rs"]
+mod services_candle_io;
+
+#[path = "unit/services/hyperliquid_signing.rs"]
+mod services_hyperliquid_signing;
+
+#[path = "unit/services/hyperliquid_user_events.rs"]
+mod services_hyperliquid_user_events;
+
 #[path = "unit/core/http.rs"]
 mod core_http;
 
 #[path = "unit/core/runtime.rs"]
 mod core_runtime;
+
+#[path = "unit/core/scheduler.rs"]
+mod core_scheduler;
+
+#[path = "unit/backtest/engine.rs"]
+mod backtest_engine;
+
+#[path = "unit/backtest/optimizer.rs"]
+mod backtest_optimizer;
+
+#[path = "unit/backtest/walk_forward.rs"]
+mod backtest_walk_forward;
+
+#[path = "unit/backtest/monte_carlo.rs"]
+mod backtest_monte_carlo;
+
+#[path = "unit/backtest/portfolio.rs"]
+mod backtest_portfolio;
+
+#[path = "unit/paper/engine.rs"]
+mod paper_engine;
+
+#[path = "unit/execution/state.rs"]
+mod execution_state;
+
+#[path = "unit/execution/policy.rs"]
+mod execution_policy;
+
+#[path = "unit/execution/risk.rs"]
+mod execution_risk;
+
+#[path = "unit/execution/positions.rs"]
+mod execution_positions;
+
+#[path = "unit/execution/algo.rs"]
+mod execution_algo;
@@ -0,0 +1,239 @@
+//! Unit tests for the backtest engine and performance metrics
+
+use chrono::{Duration, TimeZone, Utc};
+use perptrix::backtest::{
+    max_drawdown_pct, profit_factor, BacktestConfig, BacktestError, Backtester, BarSignal,
+    ExitReason, SignalSeries, PROFIT_FACTOR_CAP,
+};
+use perptrix::models::indicators::Candle;
+use perptrix::models::signal::SignalDirection;
+use perptrix::models::strategy::{
+    AggregationConfig, AggregationMethod, Comparison, Condition, IndicatorType, Rule, RuleType,
+    SignalThresholds, Strategy, StrategyConfig,
+};
+
+fn create_always_long_strategy() -> Strategy {
+    Strategy {
+        id: None,
+        name: "Always Long".to_string(),
+        symbol: "BTC".to_string(),
+        config: StrategyConfig {
+            rules: vec![Rule {
+                id: "rsi_rule".to_string(),
+                rule_type: RuleType::Condition,
+                weight: Some(1.0),
+                operator: None,
+                condition: Some(Condition {
+                    indicator: IndicatorType::RSI,
+                    indicator_params: std::collections::HashMap::new(),
+                    comparison: Comparison::GreaterThan,
+                    threshold: Some(-100.0), // Always true (RSI is 0-100)
+                    signal_state: None,
+                }),
+                children: None,
+            }],
+            aggregation: AggregationConfig {
+                method: AggregationMethod::Sum,
+                thresholds: SignalThresholds {
+                    long_min: 1,
+                    short_max: -1,
+                },
+            },
+            execution: None,
+            intrabar: false,
+        },
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+fn create_candles(closes: &[f64]) -> Vec<Candle> {
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    closes
+        .iter()
+        .enumerate()
+        .map(|(i, &close)| {
+            Candle::new(
+                close,
+                close * 1.001,
+                close * 0.999,
+                close,
+                1000.0,
+                start + Duration::hours(i as i64),
+            )
+        })
+        .collect()
+}
+
+fn frictionless_config() -> BacktestConfig {
+    BacktestConfig {
+        fee_bps: 0.0,
+        slippage_bps: 0.0,
+        apply_funding: false,
+        ..BacktestConfig::default()
+    }
+}
+
+/// Signals for the default 50-candle warm-up
+fn series(signals: Vec<Option<BarSignal>>) -> SignalSeries {
+    SignalSeries { start: 49, signals }
+}
+
+fn signal(direction: SignalDirection, sl_pct: f64, tp_pct: f64) -> Option<BarSignal> {
+    Some(BarSignal {
+        direction,
+        sl_pct,
+        tp_pct,
+    })
+}
+
+#[test]
+fn test_run_insufficient_data() {
+    let candles = create_candles(&[100.0; 20]);
+    let backtester = Backtester::new(BacktestConfig::default());
+    let result = backtester.run(&create_always_long_strategy(), &candles);
+    assert!(matches!(
+        result,
+        Err(BacktestError::InsufficientData {
+            required: 50,
+            available: 20
+        })
+    ));
+}
+
+#[test]
+fn test_config_rejects_short_lookback() {
+    let config = BacktestConfig {
+        lookback: 10,
+        ..BacktestConfig::default()
+    };
+    assert!(matches!(
+        config.validate(),
+        Err(BacktestError::InvalidConfig(_))
+    ));
+}
+
+#[test]
+fn test_run_long_strategy_on_uptrend_is_profitable() {
+    let closes: Vec<f64> = (0..100).map(|i| 100.0 + i as f64 * 0.5).collect();
+    let candles = create_candles(&closes);
+    let backtester = Backtester::new(BacktestConfig::default());
+
+    let result = backtester
+        .run(&create_always_long_strategy(), &candles)
+        .unwrap();
+
+    assert!(!result.trades.is_empty());
+    assert!(result
+        .trades
+        .iter()
+        .all(|t| t.direction == SignalDirection::Long));
+    assert!(result.metrics.total_return_pct > 0.0);
+    assert_eq!(result.equity_curve.len(), 51);
+    assert_eq!(result.metrics.total_trades, result.trades.len());
+}
+
+#[test]
+fn test_run_starts_after_indicator_warmup() {
+    let mut strategy = create_always_long_strategy();
+    let condition = strategy.config.rules[0].condition.as_mut().unwrap();
+    condition
+        .indicator_params
+        .insert("period".to_string(), serde_json::json!(79));
+    let candles = create_candles(&[100.0; 120]);
+
+    let series = Backtester::generate_signals(&strategy, &candles, 200).unwrap();
+    assert_eq!(series.start, 79);
+    assert!(series.signals[..79].iter().all(|s| s.is_none()));
+
+    // Bars without a signal after the warm-up still count
+    let quiet = SignalSeries {
+        start: series.start,
+        signals: vec![None; candles.len()],
+    };
+    let result = Backtester::new(frictionless_config()).simulate(&candles, &quiet);
+    assert!(result.trades.is_empty());
+    assert_eq!(result.equity_curve.len(), 41);
+    assert_eq!(result.equity_curve[0].timestamp, candles[79].timestamp);
+}
+
+#[test]
+fn test_reversal_closes_and_flips_position() {
+    let closes: Vec<f64> = (0..60).map(|i| 100.0 + i as f64).collect();
+    let candles = create_candles(&closes);
+    let mut signals = vec![None; candles.len()];
+    signals[49] = signal(SignalDirection::Long, 0.0, 0.0);
+    signals[55] = signal(SignalDirection::Short, 0.0, 0.0);
+
+    let result = Backtester::new(frictionless_config()).simulate(&candles, &series(signals));
+
+    assert_eq!(result.trades.len(), 2);
+    let long = &result.trades[0];
+    assert_eq!(long.exit_reason, ExitReason::Reversal);
+    assert!((long.entry_price - 149.0).abs() < 1e-9);
+    assert!((long.exit_price - 155.0).abs() < 1e-9);
+    let expected_pnl = 10_000.0 / 149.0 * 6.0;
+    assert!((long.pnl - expected_pnl).abs() < 1e-6);
+
+    let short = &result.trades[1];
+    assert_eq!(short.direction, SignalDirection::Short);
+    assert_eq!(short.exit_reason, ExitReason::EndOfData);
+    assert!(short.pnl < 0.0);
+}
+
+#[test]
+fn test_stop_loss_exits_at_stop_price() {
+    let mut candles = create_candles(&[100.0; 55]);
+    // Intrabar wick through the stop without a gap at the open
+    candles[52].low = 97.0;
+    let mut signals = vec![None; candles.len()];
+    signals[49] = signal(SignalDirection::Long, 1.0, 5.0);
+
+    let result = Backtester::new(frictionless_config()).simulate(&candles, &series(signals));
+
+    assert_eq!(result.trades.len(), 1);
+    let trade = &result.trades[0];
+    assert_eq!(trade.exit_reason, ExitReason::StopLoss);
+    assert!((trade.exit_price - 99.0).abs() < 1e-9);
+    assert!((trade.return_pct + 1.0).abs() < 1e-6);
+}
+
+#[test]
+fn test_fees_and_funding_reduce_pnl() {
+    let closes = vec![100.0; 60];
+    let candles: Vec<Candle> = create_candles(&closes)
+        .into_iter()
+        .map(|c| c.with_funding_rate(0.0001))
+        .collect();
+    let mut signals = vec![None; candles.len()];
+    signals[49] = signal(SignalDirection::Long, 0.0, 0.0);
+
+    let config = BacktestConfig {
+        fee_bps: 5.0,
+        slippage_bps: 0.0,
+        apply_funding: true,
+        ..BacktestConfig::default()
+    };
+    let result = Backtester::new(config).simulate(&candles, &series(signals));
+
+    let trade = &result.trades[0];
+    // Ten hourly funding payments of 1bp on a 10k notional
+    assert!((trade.funding - 10.0).abs() < 1e-6);
+    assert!((trade.fees - 10.0).abs() < 1e-6);
+    assert!((trade.pnl + 20.0).abs() < 1e-6);
+    assert!((result.metrics.final_equity - 9_980.0).abs() < 1e-6);
+}
+
+#[test]
+fn test_max_drawdown_pct() {
+    let drawdown = max_drawdown_pct([100.0, 120.0, 90.0, 130.0, 117.0]);
+    assert!((drawdown - 25.0).abs() < 1e-9);
+    assert_eq!(max_drawdown_pct([100.0, 110.0, 120.0]), 0.0);
+}
+
+#[test]
+fn test_profit_factor() {
+    assert!((profit_factor([30.0, -10.0, 20.0, -15.0]) - 2.0).abs() < 1e-9);
+    assert_eq!(profit_factor([10.0, 5.0]), PROFIT_FACTOR_CAP);
+    assert_eq!(profit_factor([-10.0]), 0.0);
+}
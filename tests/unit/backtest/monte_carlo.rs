@@ -0,0 +1,116 @@
+//! Unit tests for Monte Carlo trade analysis
+
+use chrono::{TimeZone, Utc};
+use perptrix::backtest::{
+    percentile, BacktestError, BacktestTrade, ExitReason, MonteCarlo, MonteCarloConfig,
+    ResampleMethod,
+};
+use perptrix::models::signal::SignalDirection;
+
+fn create_trade(return_pct: f64) -> BacktestTrade {
+    let time = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    BacktestTrade {
+        direction: SignalDirection::Long,
+        entry_time: time,
+        entry_price: 100.0,
+        exit_time: time,
+        exit_price: 100.0 * (1.0 + return_pct / 100.0),
+        size: 1.0,
+        pnl: return_pct,
+        fees: 0.0,
+        funding: 0.0,
+        return_pct,
+        exit_reason: ExitReason::TakeProfit,
+    }
+}
+
+fn create_trades() -> Vec<BacktestTrade> {
+    [5.0, -3.0, 4.0, -2.0, 6.0, -4.0, 3.0, -1.0, 2.0, -5.0]
+        .iter()
+        .map(|r| create_trade(*r))
+        .collect()
+}
+
+fn config(iterations: usize) -> MonteCarloConfig {
+    MonteCarloConfig {
+        iterations,
+        ..MonteCarloConfig::default()
+    }
+}
+
+#[test]
+fn test_percentile_interpolates() {
+    let sorted = [1.0, 2.0, 3.0, 4.0, 5.0];
+    assert_eq!(percentile(&sorted, 0.0), 1.0);
+    assert_eq!(percentile(&sorted, 50.0), 3.0);
+    assert_eq!(percentile(&sorted, 100.0), 5.0);
+    assert!((percentile(&sorted, 25.0) - 2.0).abs() < 1e-9);
+    assert!((percentile(&[1.0, 2.0], 50.0) - 1.5).abs() < 1e-9);
+}
+
+#[test]
+fn test_shuffle_preserves_final_equity() {
+    let trades = create_trades();
+    let result = MonteCarlo::new(config(200))
+        .run(&trades, 10_000.0, ResampleMethod::Shuffle)
+        .unwrap();
+
+    // Compounding is order independent, only the path changes
+    assert!((result.final_equity.min - result.original_final_equity).abs() < 1e-6);
+    assert!((result.final_equity.max - result.original_final_equity).abs() < 1e-6);
+    assert!(result.max_drawdown_pct.max >= result.max_drawdown_pct.min);
+    assert_eq!(result.iterations, 200);
+}
+
+#[test]
+fn test_bootstrap_produces_spread_and_ordered_percentiles() {
+    let trades = create_trades();
+    let result = MonteCarlo::new(config(500))
+        .run(&trades, 10_000.0, ResampleMethod::Bootstrap)
+        .unwrap();
+
+    assert!(result.final_equity.max > result.final_equity.min);
+    let values: Vec<f64> = result
+        .final_equity
+        .percentiles
+        .iter()
+        .map(|p| p.value)
+        .collect();
+    assert!(values.windows(2).all(|w| w[0] <= w[1]));
+    assert!(result.final_equity.get(50.0).is_some());
+    assert!((0.0..=1.0).contains(&result.risk_of_ruin));
+}
+
+#[test]
+fn test_risk_of_ruin_for_losing_trades() {
+    let trades: Vec<BacktestTrade> = (0..10).map(|_| create_trade(-10.0)).collect();
+    let report = MonteCarlo::new(config(50))
+        .analyze(&trades, 10_000.0)
+        .unwrap();
+
+    assert_eq!(report.shuffle.risk_of_ruin, 1.0);
+    assert_eq!(report.bootstrap.risk_of_ruin, 1.0);
+    assert!(report.random_skip.risk_of_ruin > 0.0);
+    assert!(report.shuffle.original_max_drawdown_pct > 60.0);
+}
+
+#[test]
+fn test_runs_are_reproducible_with_seed() {
+    let trades = create_trades();
+    let first = MonteCarlo::new(config(100))
+        .run(&trades, 10_000.0, ResampleMethod::RandomSkip)
+        .unwrap();
+    let second = MonteCarlo::new(config(100))
+        .run(&trades, 10_000.0, ResampleMethod::RandomSkip)
+        .unwrap();
+    assert_eq!(first.final_equity.mean, second.final_equity.mean);
+}
+
+#[test]
+fn test_empty_trades_rejected() {
+    let result = MonteCarlo::new(config(10)).run(&[], 10_000.0, ResampleMethod::Shuffle);
+    assert!(matches!(
+        result,
+        Err(BacktestError::InsufficientData { .. })
+    ));
+}
@@ -0,0 +1,224 @@
+//! Unit tests for the strategy parameter optimizer
+
+use chrono::{Duration, TimeZone, Utc};
+use perptrix::backtest::{
+    BacktestError, Objective, ObjectiveMetric, OptimizerConfig, Optimizer, ParameterRange,
+    ParameterTarget, SearchMethod,
+};
+use perptrix::models::indicators::Candle;
+use perptrix::models::strategy::{
+    AggregationConfig, AggregationMethod, Comparison, Condition, IndicatorType, Rule, RuleType,
+    SignalThresholds, Strategy, StrategyConfig,
+};
+
+fn create_rsi_strategy() -> Strategy {
+    Strategy {
+        id: None,
+        name: "RSI Threshold".to_string(),
+        symbol: "BTC".to_string(),
+        config: StrategyConfig {
+            rules: vec![Rule {
+                id: "rsi_rule".to_string(),
+                rule_type: RuleType::Condition,
+                weight: Some(1.0),
+                operator: None,
+                condition: Some(Condition {
+                    indicator: IndicatorType::RSI,
+                    indicator_params: std::collections::HashMap::new(),
+                    comparison: Comparison::GreaterThan,
+                    threshold: Some(50.0),
+                    signal_state: None,
+                }),
+                children: None,
+            }],
+            aggregation: AggregationConfig {
+                method: AggregationMethod::Sum,
+                thresholds: SignalThresholds {
+                    long_min: 1,
+                    short_max: -1,
+                },
+            },
+            execution: None,
+            intrabar: false,
+        },
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+fn create_wave_candles(count: usize) -> Vec<Candle> {
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    (0..count)
+        .map(|i| {
+            let close = 100.0 + i as f64 * 0.2 + (i as f64 / 6.0).sin() * 3.0;
+            Candle::new(
+                close,
+                close * 1.002,
+                close * 0.998,
+                close,
+                1000.0,
+                start + Duration::hours(i as i64),
+            )
+        })
+        .collect()
+}
+
+fn threshold_range() -> ParameterRange {
+    ParameterRange::new(
+        ParameterTarget::RuleThreshold {
+            rule_id: "rsi_rule".to_string(),
+        },
+        -100.0,
+        200.0,
+        50.0,
+    )
+}
+
+fn optimizer(search: SearchMethod, objective: Objective) -> Optimizer {
+    Optimizer::new(OptimizerConfig {
+        search,
+        objective,
+        top_n: 3,
+        threads: Some(2),
+        ..OptimizerConfig::default()
+    })
+}
+
+#[test]
+fn test_parameter_range_values() {
+    let values = threshold_range().values().unwrap();
+    assert_eq!(values, vec![-100.0, -50.0, 0.0, 50.0, 100.0, 150.0, 200.0]);
+
+    let invalid = ParameterRange::new(ParameterTarget::LongMin, 1.0, 3.0, 0.0);
+    assert!(matches!(
+        invalid.values(),
+        Err(BacktestError::InvalidParameter(_))
+    ));
+
+    // Too many values, including a subnormal step, are rejected before allocating
+    for step in [1e-6, f64::MIN_POSITIVE / 4.0] {
+        let huge = ParameterRange::new(ParameterTarget::LongMin, 0.0, 1.0, step);
+        assert!(matches!(
+            huge.values(),
+            Err(BacktestError::InvalidParameter(_))
+        ));
+    }
+}
+
+#[test]
+fn test_indicator_param_is_stored_as_integer() {
+    let mut config = create_rsi_strategy().config;
+    let target = ParameterTarget::IndicatorParam {
+        rule_id: "rsi_rule".to_string(),
+        param: "period".to_string(),
+    };
+    target.apply(&mut config, 21.0).unwrap();
+
+    let params = &config.rules[0].condition.as_ref().unwrap().indicator_params;
+    assert_eq!(params.get("period"), Some(&serde_json::json!(21)));
+}
+
+#[test]
+fn test_optimize_unknown_rule_is_rejected() {
+    let range = ParameterRange::new(
+        ParameterTarget::RuleWeight {
+            rule_id: "missing".to_string(),
+        },
+        1.0,
+        2.0,
+        1.0,
+    );
+    let result = optimizer(SearchMethod::Grid, Objective::new(ObjectiveMetric::Sharpe)).optimize(
+        &create_rsi_strategy(),
+        &[range],
+        &create_wave_candles(80),
+    );
+    assert!(matches!(result, Err(BacktestError::InvalidParameter(_))));
+}
+
+#[test]
+fn test_grid_search_returns_sorted_top_candidates() {
+    let candles = create_wave_candles(100);
+    let candidates = optimizer(
+        SearchMethod::Grid,
+        Objective::new(ObjectiveMetric::TotalReturn),
+    )
+    .optimize(&create_rsi_strategy(), &[threshold_range()], &candles)
+    .unwrap();
+
+    assert!(!candidates.is_empty());
+    assert!(candidates.len() <= 3);
+    assert!(candidates.windows(2).all(|w| w[0].score >= w[1].score));
+
+    let best = &candidates[0];
+    let threshold = best.parameters["rsi_rule.threshold"];
+    assert_eq!(
+        best.config.rules[0].condition.as_ref().unwrap().threshold,
+        Some(threshold)
+    );
+    assert!((best.score - best.metrics.total_return_pct).abs() < 1e-9);
+}
+
+#[test]
+fn test_drawdown_cap_excludes_candidates() {
+    let candles = create_wave_candles(100);
+    let candidates = optimizer(
+        SearchMethod::Grid,
+        Objective::new(ObjectiveMetric::Sharpe).with_max_drawdown(0.0),
+    )
+    .optimize(&create_rsi_strategy(), &[threshold_range()], &candles)
+    .unwrap();
+
+    assert!(candidates.is_empty());
+}
+
+#[test]
+fn test_genetic_search_is_deterministic() {
+    let candles = create_wave_candles(90);
+    let space = [
+        threshold_range(),
+        ParameterRange::new(
+            ParameterTarget::IndicatorParam {
+                rule_id: "rsi_rule".to_string(),
+                param: "period".to_string(),
+            },
+            7.0,
+            21.0,
+            7.0,
+        ),
+    ];
+    let search = SearchMethod::Genetic {
+        population: 6,
+        generations: 2,
+        mutation_rate: 0.2,
+    };
+
+    let first = optimizer(search.clone(), Objective::new(ObjectiveMetric::ProfitFactor))
+        .optimize(&create_rsi_strategy(), &space, &candles)
+        .unwrap();
+    let second = optimizer(search, Objective::new(ObjectiveMetric::ProfitFactor))
+        .optimize(&create_rsi_strategy(), &space, &candles)
+        .unwrap();
+
+    assert!(!first.is_empty());
+    let params = |c: &[perptrix::backtest::OptimizationCandidate]| {
+        c.iter().map(|x| x.parameters.clone()).collect::<Vec<_>>()
+    };
+    assert_eq!(params(&first), params(&second));
+}
+
+#[test]
+fn test_random_search_samples_within_ranges() {
+    let candles = create_wave_candles(90);
+    let candidates = optimizer(
+        SearchMethod::Random { samples: 4 },
+        Objective::new(ObjectiveMetric::Sharpe),
+    )
+    .optimize(&create_rsi_strategy(), &[threshold_range()], &candles)
+    .unwrap();
+
+    for candidate in &candidates {
+        let threshold = candidate.parameters["rsi_rule.threshold"];
+        assert!((-100.0..=200.0).contains(&threshold));
+    }
+}
@@ -0,0 +1,227 @@
+//! Unit tests for portfolio backtesting
+
+use chrono::{Duration, TimeZone, Utc};
+use perptrix::backtest::{
+    correlation, AllocationMethod, BacktestConfig, BacktestError, PortfolioBacktester,
+    PortfolioConfig, PortfolioLeg,
+};
+use perptrix::models::indicators::Candle;
+use perptrix::models::strategy::{
+    AggregationConfig, AggregationMethod, Comparison, Condition, IndicatorType, Rule, RuleType,
+    SignalThresholds, Strategy, StrategyConfig,
+};
+
+fn create_always_long_strategy(name: &str, symbol: &str) -> Strategy {
+    Strategy {
+        id: None,
+        name: name.to_string(),
+        symbol: symbol.to_string(),
+        config: StrategyConfig {
+            rules: vec![Rule {
+                id: "rsi_rule".to_string(),
+                rule_type: RuleType::Condition,
+                weight: Some(1.0),
+                operator: None,
+                condition: Some(Condition {
+                    indicator: IndicatorType::RSI,
+                    indicator_params: std::collections::HashMap::new(),
+                    comparison: Comparison::GreaterThan,
+                    threshold: Some(-100.0), // Always true (RSI is 0-100)
+                    signal_state: None,
+                }),
+                children: None,
+            }],
+            aggregation: AggregationConfig {
+                method: AggregationMethod::Sum,
+                thresholds: SignalThresholds {
+                    long_min: 1,
+                    short_max: -1,
+                },
+            },
+            execution: None,
+            intrabar: false,
+        },
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+/// Uptrending candles with an oscillation of `swing` percent
+fn create_candles(count: usize, base: f64, swing: f64) -> Vec<Candle> {
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    (0..count)
+        .map(|i| {
+            let wave = if i % 2 == 0 { swing } else { -swing } / 100.0;
+            let close = base * (1.0 + i as f64 * 0.002) * (1.0 + wave);
+            Candle::new(
+                close,
+                close * 1.0005,
+                close * 0.9995,
+                close,
+                1000.0,
+                start + Duration::hours(i as i64),
+            )
+        })
+        .collect()
+}
+
+fn config() -> PortfolioConfig {
+    PortfolioConfig {
+        backtest: BacktestConfig {
+            lookback: 60,
+            fee_bps: 0.0,
+            slippage_bps: 0.0,
+            ..BacktestConfig::default()
+        },
+        ..PortfolioConfig::default()
+    }
+}
+
+#[test]
+fn test_empty_portfolio_rejected() {
+    let result = PortfolioBacktester::new(config()).run(&[]);
+    assert!(matches!(result, Err(BacktestError::InvalidConfig(_))));
+}
+
+#[test]
+fn test_equal_weight_portfolio_combines_legs() {
+    let btc = create_always_long_strategy("Trend BTC", "BTC");
+    let eth = create_always_long_strategy("Trend ETH", "ETH");
+    let btc_candles = create_candles(80, 100.0, 0.1);
+    let eth_candles = create_candles(80, 50.0, 0.1);
+    let legs = [
+        PortfolioLeg {
+            strategy: &btc,
+            candles: &btc_candles,
+        },
+        PortfolioLeg {
+            strategy: &eth,
+            candles: &eth_candles,
+        },
+    ];
+
+    let result = PortfolioBacktester::new(config()).run(&legs).unwrap();
+
+    assert_eq!(result.contributions.len(), 2);
+    assert_eq!(result.contributions[0].symbol, "BTC");
+    assert!(result.contributions.iter().all(|c| !c.trades.is_empty()));
+    assert!(result.metrics.total_return_pct > 0.0);
+    assert_eq!(result.equity_curve.len(), 31);
+
+    let leg_pnl: f64 = result.contributions.iter().map(|c| c.total_pnl).sum();
+    let final_equity = result.equity_curve.last().unwrap().equity;
+    assert!((leg_pnl - (final_equity - 10_000.0)).abs() < 1e-6);
+    let shares: f64 = result.contributions.iter().map(|c| c.share_of_pnl).sum();
+    assert!((shares - 1.0).abs() < 1e-9);
+
+    // Both legs start at half the pool
+    let btc_notional = result.contributions[0].trades[0].size
+        * result.contributions[0].trades[0].entry_price;
+    assert!((btc_notional - 5_000.0).abs() < 1e-6);
+
+    assert_eq!(result.correlation.len(), 2);
+    assert!((result.correlation[0][0] - 1.0).abs() < 1e-9);
+    assert!(result.correlation[0][1] > 0.5);
+}
+
+#[test]
+fn test_max_concurrent_positions_limits_entries() {
+    let btc = create_always_long_strategy("Trend BTC", "BTC");
+    let eth = create_always_long_strategy("Trend ETH", "ETH");
+    let btc_candles = create_candles(70, 100.0, 0.1);
+    let eth_candles = create_candles(70, 50.0, 0.1);
+    let legs = [
+        PortfolioLeg {
+            strategy: &btc,
+            candles: &btc_candles,
+        },
+        PortfolioLeg {
+            strategy: &eth,
+            candles: &eth_candles,
+        },
+    ];
+
+    let result = PortfolioBacktester::new(PortfolioConfig {
+        max_concurrent_positions: Some(1),
+        ..config()
+    })
+    .run(&legs)
+    .unwrap();
+
+    assert!(!result.contributions[0].trades.is_empty());
+    assert!(result.contributions[1].trades.is_empty());
+    assert!(result.skipped_entries > 0);
+}
+
+#[test]
+fn test_leverage_cap_limits_gross_exposure() {
+    let btc = create_always_long_strategy("Trend BTC", "BTC");
+    let eth = create_always_long_strategy("Trend ETH", "ETH");
+    let btc_candles = create_candles(70, 100.0, 0.1);
+    let eth_candles = create_candles(70, 50.0, 0.1);
+    let legs = [
+        PortfolioLeg {
+            strategy: &btc,
+            candles: &btc_candles,
+        },
+        PortfolioLeg {
+            strategy: &eth,
+            candles: &eth_candles,
+        },
+    ];
+
+    let mut portfolio = config();
+    portfolio.max_leverage = 1.5;
+    portfolio.backtest.leverage = 3.0;
+    let result = PortfolioBacktester::new(portfolio).run(&legs).unwrap();
+
+    // The first leg is capped at 1.5x equity, leaving no room for the second
+    let first = &result.contributions[0].trades[0];
+    assert!((first.size * first.entry_price - 15_000.0).abs() < 1e-6);
+    assert!(result.contributions[1]
+        .trades
+        .first()
+        .is_none_or(|t| t.entry_time > first.entry_time));
+    assert!(result.skipped_entries > 0);
+}
+
+#[test]
+fn test_risk_parity_underweights_volatile_leg() {
+    let calm = create_always_long_strategy("Calm", "BTC");
+    let wild = create_always_long_strategy("Wild", "DOGE");
+    let calm_candles = create_candles(80, 100.0, 0.1);
+    let wild_candles = create_candles(80, 100.0, 0.4);
+    let legs = [
+        PortfolioLeg {
+            strategy: &calm,
+            candles: &calm_candles,
+        },
+        PortfolioLeg {
+            strategy: &wild,
+            candles: &wild_candles,
+        },
+    ];
+
+    let result = PortfolioBacktester::new(PortfolioConfig {
+        allocation: AllocationMethod::RiskParity {
+            volatility_lookback: 20,
+        },
+        ..config()
+    })
+    .run(&legs)
+    .unwrap();
+
+    let notional = |i: usize| {
+        let trade = &result.contributions[i].trades[0];
+        trade.size * trade.entry_price
+    };
+    assert!(notional(0) > notional(1) * 2.0);
+}
+
+#[test]
+fn test_correlation() {
+    let a = [1.0, 2.0, 3.0, 4.0];
+    assert!((correlation(&a, &[2.0, 4.0, 6.0, 8.0]) - 1.0).abs() < 1e-9);
+    assert!((correlation(&a, &[4.0, 3.0, 2.0, 1.0]) + 1.0).abs() < 1e-9);
+    assert_eq!(correlation(&a, &[1.0, 1.0, 1.0, 1.0]), 0.0);
+}
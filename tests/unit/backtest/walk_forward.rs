@@ -0,0 +1,173 @@
+//! Unit tests for walk-forward validation
+
+use chrono::{Duration, TimeZone, Utc};
+use perptrix::backtest::{
+    BacktestConfig, BacktestError, Objective, ObjectiveMetric, OptimizerConfig, ParameterRange,
+    ParameterStability, ParameterTarget, SearchMethod, WalkForward, WalkForwardConfig,
+};
+use perptrix::models::indicators::Candle;
+use perptrix::models::strategy::{
+    AggregationConfig, AggregationMethod, Comparison, Condition, IndicatorType, Rule, RuleType,
+    SignalThresholds, Strategy, StrategyConfig,
+};
+
+fn create_rsi_strategy() -> Strategy {
+    Strategy {
+        id: None,
+        name: "RSI Threshold".to_string(),
+        symbol: "BTC".to_string(),
+        config: StrategyConfig {
+            rules: vec![Rule {
+                id: "rsi_rule".to_string(),
+                rule_type: RuleType::Condition,
+                weight: Some(1.0),
+                operator: None,
+                condition: Some(Condition {
+                    indicator: IndicatorType::RSI,
+                    indicator_params: std::collections::HashMap::new(),
+                    comparison: Comparison::GreaterThan,
+                    threshold: Some(50.0),
+                    signal_state: None,
+                }),
+                children: None,
+            }],
+            aggregation: AggregationConfig {
+                method: AggregationMethod::Sum,
+                thresholds: SignalThresholds {
+                    long_min: 1,
+                    short_max: -1,
+                },
+            },
+            execution: None,
+            intrabar: false,
+        },
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+fn create_wave_candles(count: usize) -> Vec<Candle> {
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    (0..count)
+        .map(|i| {
+            let close = 100.0 + i as f64 * 0.1 + (i as f64 / 8.0).sin() * 4.0;
+            Candle::new(
+                close,
+                close * 1.002,
+                close * 0.998,
+                close,
+                1000.0,
+                start + Duration::hours(i as i64),
+            )
+        })
+        .collect()
+}
+
+fn walk_forward_config(anchored: bool) -> WalkForwardConfig {
+    WalkForwardConfig {
+        in_sample_bars: 80,
+        out_of_sample_bars: 20,
+        anchored,
+        stability_threshold: 0.25,
+        optimizer: OptimizerConfig {
+            search: SearchMethod::Grid,
+            objective: Objective::new(ObjectiveMetric::TotalReturn),
+            top_n: 1,
+            threads: Some(2),
+            backtest: BacktestConfig {
+                lookback: 60,
+                ..BacktestConfig::default()
+            },
+            ..OptimizerConfig::default()
+        },
+    }
+}
+
+fn threshold_space() -> Vec<ParameterRange> {
+    vec![ParameterRange::new(
+        ParameterTarget::RuleThreshold {
+            rule_id: "rsi_rule".to_string(),
+        },
+        30.0,
+        70.0,
+        20.0,
+    )]
+}
+
+#[test]
+fn test_fold_bounds_rolling_and_anchored() {
+    let rolling = WalkForward::new(walk_forward_config(false)).fold_bounds(145);
+    assert_eq!(rolling, vec![(0, 80, 100), (20, 100, 120), (40, 120, 140)]);
+
+    let anchored = WalkForward::new(walk_forward_config(true)).fold_bounds(145);
+    assert_eq!(anchored, vec![(0, 80, 100), (0, 100, 120), (0, 120, 140)]);
+}
+
+#[test]
+fn test_run_rejects_insufficient_history() {
+    let result = WalkForward::new(walk_forward_config(false)).run(
+        &create_rsi_strategy(),
+        &threshold_space(),
+        &create_wave_candles(90),
+    );
+    assert!(matches!(
+        result,
+        Err(BacktestError::InsufficientData {
+            required: 100,
+            available: 90
+        })
+    ));
+}
+
+#[test]
+fn test_run_stitches_out_of_sample_equity() {
+    let candles = create_wave_candles(140);
+    let report = WalkForward::new(walk_forward_config(false))
+        .run(&create_rsi_strategy(), &threshold_space(), &candles)
+        .unwrap();
+
+    assert_eq!(report.folds.len(), 3);
+    assert_eq!(report.equity_curve.len(), 60);
+    assert_eq!(report.equity_curve[0].timestamp, candles[80].timestamp);
+    assert_eq!(
+        report.equity_curve.last().unwrap().timestamp,
+        candles[139].timestamp
+    );
+    assert!(report
+        .equity_curve
+        .windows(2)
+        .all(|w| w[0].timestamp < w[1].timestamp));
+
+    for fold in &report.folds {
+        assert!(fold.out_of_sample_start > fold.in_sample_end);
+        if let Some(ref parameters) = fold.parameters {
+            let threshold = parameters["rsi_rule.threshold"];
+            assert!((30.0..=70.0).contains(&threshold));
+        }
+    }
+
+    let fold_trades: usize = report.folds.iter().map(|f| f.trades.len()).sum();
+    assert_eq!(report.out_of_sample_metrics.total_trades, fold_trades);
+    assert_eq!(report.parameter_stability.len(), 1);
+}
+
+#[test]
+fn test_parameter_stability_flags_dispersed_values() {
+    let stable = ParameterStability::from_values(
+        "rsi_rule.threshold".to_string(),
+        vec![50.0, 50.0, 55.0],
+        40.0,
+        0.25,
+    );
+    assert!(!stable.unstable);
+
+    let unstable = ParameterStability::from_values(
+        "rsi_rule.threshold".to_string(),
+        vec![30.0, 70.0, 30.0, 70.0],
+        40.0,
+        0.25,
+    );
+    assert!(unstable.unstable);
+    assert!((unstable.mean - 50.0).abs() < 1e-9);
+    assert!((unstable.normalized_dispersion - 0.5).abs() < 1e-9);
+}
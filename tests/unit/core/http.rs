@@ -14,6 +14,11 @@ async fn test_health_check() {
         metrics: Arc::new(Metrics::default()),
         start_time: Arc::new(Instant::now()),
         database: None,
+        kill_switch: None,
+        algos: None,
+        instruments: Arc::new(RwLock::new(None)),
+        subscriptions: None,
+        execution_auth: None,
     };
     let result = health_check(State(state)).await;
     assert!(result.is_ok());
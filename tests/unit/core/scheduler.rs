@@ -0,0 +1,46 @@
+//! Unit tests for the candle close trigger
+
+use chrono::Utc;
+use perptrix::core::scheduler::CandleCloseTrigger;
+use perptrix::services::candle_events::CandleClosed;
+
+fn closed(symbol: &str, interval: &str) -> CandleClosed {
+    CandleClosed {
+        symbol: symbol.to_string(),
+        interval: interval.to_string(),
+        timestamp: Utc::now(),
+    }
+}
+
+#[test]
+fn test_closes_of_strategy_symbols_on_the_evaluation_interval_enqueue_jobs() {
+    let trigger =
+        CandleCloseTrigger::new(&["BTC".to_string(), "binance:BTCUSDT".to_string()], "1m");
+
+    let job = trigger.job_for(&closed("BTC", "1m")).unwrap();
+    assert_eq!(job.symbol, "BTC");
+    // Spellings are normalized on both sides
+    assert_eq!(
+        trigger.job_for(&closed("btc-perp", "1m")).unwrap().symbol,
+        "BTC"
+    );
+    assert_eq!(
+        trigger
+            .job_for(&closed("binance:btcusdt", "1m"))
+            .unwrap()
+            .symbol,
+        "binance:BTCUSDT"
+    );
+
+    assert!(trigger.job_for(&closed("BTC", "5m")).is_none());
+    assert!(trigger.job_for(&closed("ETH", "1m")).is_none());
+}
+
+#[test]
+fn test_reloaded_symbols_replace_the_evaluated_set() {
+    let trigger = CandleCloseTrigger::new(&["BTC".to_string()], "1m");
+    trigger.set_symbols(&["eth-perp".to_string()]);
+
+    assert!(trigger.job_for(&closed("BTC", "1m")).is_none());
+    assert_eq!(trigger.job_for(&closed("ETH", "1m")).unwrap().symbol, "ETH");
+}
@@ -0,0 +1,111 @@
+//! Unit tests for execution algo orders, progress and the in-memory store
+
+use chrono::{Duration, Utc};
+use perptrix::execution::{
+    AlgoOrder, AlgoParams, AlgoProgress, AlgoState, AlgoStore, MemoryAlgoStore, OrderSide,
+};
+use serde_json::json;
+
+fn order(size: f64, params: AlgoParams) -> AlgoOrder {
+    AlgoOrder {
+        symbol: "BTC".to_string(),
+        side: OrderSide::Sell,
+        size,
+        reduce_only: false,
+        params,
+    }
+}
+
+fn twap(duration_seconds: u64, slices: u32, max_slippage: f64) -> AlgoParams {
+    AlgoParams::Twap {
+        duration_seconds,
+        slices,
+        max_slippage,
+    }
+}
+
+#[test]
+fn validates_algo_parameters() {
+    assert!(order(1.0, twap(60, 6, 0.005)).validate().is_ok());
+    assert!(order(0.0, twap(60, 6, 0.005)).validate().is_err());
+    assert!(order(1.0, twap(0, 6, 0.005)).validate().is_err());
+    assert!(order(1.0, twap(60, 0, 0.005)).validate().is_err());
+    assert!(order(1.0, twap(60, 6, 1.0)).validate().is_err());
+
+    let iceberg = |clip_size, limit_price| AlgoParams::Iceberg {
+        clip_size,
+        limit_price,
+    };
+    assert!(order(1.0, iceberg(0.1, 100.0)).validate().is_ok());
+    assert!(order(1.0, iceberg(2.0, 100.0)).validate().is_err());
+    assert!(order(1.0, iceberg(0.1, f64::NAN)).validate().is_err());
+
+    let chase = |timeout_seconds, limit_price| AlgoParams::ChaseLimit {
+        timeout_seconds,
+        limit_price,
+    };
+    assert!(order(1.0, chase(30, None)).validate().is_ok());
+    assert!(order(1.0, chase(0, None)).validate().is_err());
+    assert!(order(1.0, chase(30, Some(-1.0))).validate().is_err());
+}
+
+#[test]
+fn deserializes_tagged_params_with_defaults() {
+    let order: AlgoOrder = serde_json::from_value(json!({
+        "symbol": "ETH",
+        "side": "Buy",
+        "size": 2.0,
+        "params": { "type": "Twap", "duration_seconds": 600, "slices": 10 }
+    }))
+    .unwrap();
+    assert!(!order.reduce_only);
+    assert_eq!(order.params, twap(600, 10, 0.005));
+
+    let chase: AlgoParams =
+        serde_json::from_value(json!({ "type": "ChaseLimit", "timeout_seconds": 30 })).unwrap();
+    assert_eq!(
+        chase,
+        AlgoParams::ChaseLimit {
+            timeout_seconds: 30,
+            limit_price: None
+        }
+    );
+}
+
+#[test]
+fn progress_tracks_remaining_size() {
+    let mut progress = AlgoProgress::new(order(1.0, twap(60, 6, 0.005)), Utc::now());
+    assert_eq!(progress.state, AlgoState::Pending);
+    assert!(!progress.state.is_terminal());
+    assert_eq!(progress.remaining_size(), 1.0);
+
+    progress.filled_size = 1.0 - 1e-12;
+    assert!(progress.is_filled());
+    progress.filled_size = 1.5;
+    assert_eq!(progress.remaining_size(), 0.0);
+    assert!(AlgoState::Expired.is_terminal());
+}
+
+#[tokio::test]
+async fn memory_store_lists_newest_first_and_records_cancellation() {
+    let store = MemoryAlgoStore::default();
+    let now = Utc::now();
+    let older = AlgoProgress::new(order(1.0, twap(60, 6, 0.005)), now - Duration::minutes(1));
+    let newer = AlgoProgress::new(order(2.0, twap(60, 6, 0.005)), now);
+    store.save_algo(&older).await.unwrap();
+    store.save_algo(&newer).await.unwrap();
+
+    let ids: Vec<String> = store
+        .load_algos()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|a| a.id)
+        .collect();
+    assert_eq!(ids, vec![newer.id.clone(), older.id.clone()]);
+
+    assert!(!store.algo_cancel_requested(&older.id).await.unwrap());
+    store.request_algo_cancel(&older.id).await.unwrap();
+    assert!(store.algo_cancel_requested(&older.id).await.unwrap());
+    assert!(!store.algo_cancel_requested(&newer.id).await.unwrap());
+}
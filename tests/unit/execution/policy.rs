@@ -0,0 +1,177 @@
+//! Unit tests for execution policy decisions
+
+use chrono::{Duration, Utc};
+use perptrix::execution::{
+    BracketGrouping, EntryOrderType, ExecutionPolicy, OppositeSignalAction, OrderSide,
+    SignalAction,
+};
+use perptrix::models::signal::{SignalDirection, SignalOutput};
+
+fn signal(direction: SignalDirection, confidence: f64) -> SignalOutput {
+    SignalOutput::new(
+        direction,
+        confidence,
+        2.0,
+        4.0,
+        vec![],
+        "BTC".to_string(),
+        40000.0,
+    )
+}
+
+#[test]
+fn opens_in_the_signal_direction_when_flat() {
+    let policy = ExecutionPolicy::new(1000.0);
+    let now = Utc::now();
+
+    assert_eq!(
+        policy.decide(&signal(SignalDirection::Long, 0.8), 0.0, 0, now),
+        SignalAction::Open(OrderSide::Buy)
+    );
+    assert_eq!(
+        policy.decide(&signal(SignalDirection::Short, 0.8), 0.0, 0, now),
+        SignalAction::Open(OrderSide::Sell)
+    );
+    assert!(matches!(
+        policy.decide(&signal(SignalDirection::Neutral, 0.8), 0.0, 0, now),
+        SignalAction::Ignore(_)
+    ));
+}
+
+#[test]
+fn low_confidence_and_stale_signals_are_ignored() {
+    let policy = ExecutionPolicy {
+        min_confidence: 0.6,
+        ..ExecutionPolicy::new(1000.0)
+    };
+    let now = Utc::now();
+
+    assert!(matches!(
+        policy.decide(&signal(SignalDirection::Long, 0.55), 0.0, 0, now),
+        SignalAction::Ignore(_)
+    ));
+
+    let mut stale = signal(SignalDirection::Long, 0.9);
+    stale.timestamp = now - Duration::seconds(600);
+    assert!(matches!(
+        policy.decide(&stale, 0.0, 0, now),
+        SignalAction::Ignore(_)
+    ));
+}
+
+#[test]
+fn opposite_signal_follows_the_policy() {
+    let short = signal(SignalDirection::Short, 0.9);
+    let now = Utc::now();
+
+    let flip = ExecutionPolicy::new(1000.0);
+    assert_eq!(
+        flip.decide(&short, 0.5, 1, now),
+        SignalAction::Flip(OrderSide::Sell)
+    );
+
+    let close_only = ExecutionPolicy {
+        on_opposite_signal: OppositeSignalAction::CloseOnly,
+        ..ExecutionPolicy::new(1000.0)
+    };
+    assert_eq!(close_only.decide(&short, 0.5, 1, now), SignalAction::Close);
+
+    let ignore = ExecutionPolicy {
+        on_opposite_signal: OppositeSignalAction::Ignore,
+        ..ExecutionPolicy::new(1000.0)
+    };
+    assert!(matches!(
+        ignore.decide(&short, 0.5, 1, now),
+        SignalAction::Ignore(_)
+    ));
+}
+
+#[test]
+fn pyramiding_is_limited_by_max_entries() {
+    let policy = ExecutionPolicy {
+        max_entries: 2,
+        ..ExecutionPolicy::new(1000.0)
+    };
+    let long = signal(SignalDirection::Long, 0.9);
+    let now = Utc::now();
+
+    assert_eq!(
+        policy.decide(&long, 0.025, 1, now),
+        SignalAction::Add(OrderSide::Buy)
+    );
+    assert!(matches!(
+        policy.decide(&long, 0.05, 2, now),
+        SignalAction::Ignore(_)
+    ));
+}
+
+#[test]
+fn neutral_signal_closes_only_when_configured() {
+    let neutral = signal(SignalDirection::Neutral, 0.1);
+    let now = Utc::now();
+
+    assert_eq!(
+        ExecutionPolicy::new(1000.0).decide(&neutral, -0.5, 1, now),
+        SignalAction::Close
+    );
+    let keep = ExecutionPolicy {
+        close_on_neutral: false,
+        ..ExecutionPolicy::new(1000.0)
+    };
+    assert!(matches!(
+        keep.decide(&neutral, -0.5, 1, now),
+        SignalAction::Ignore(_)
+    ));
+}
+
+#[test]
+fn policy_json_uses_defaults() {
+    let policy: ExecutionPolicy = serde_json::from_str(
+        r#"{"order_notional": 500, "entry_order": {"type": "PostOnly", "timeout_seconds": 30}}"#,
+    )
+    .unwrap();
+    assert_eq!(policy.min_confidence, 0.5);
+    assert_eq!(policy.on_opposite_signal, OppositeSignalAction::Flip);
+    assert_eq!(
+        policy.entry_order,
+        EntryOrderType::PostOnly {
+            timeout_seconds: 30
+        }
+    );
+    assert!(policy.validate().is_ok());
+
+    let invalid = ExecutionPolicy {
+        max_entries: 0,
+        ..ExecutionPolicy::new(500.0)
+    };
+    assert!(invalid.validate().is_err());
+}
+
+#[test]
+fn bracket_settings_are_validated() {
+    let policy: ExecutionPolicy = serde_json::from_value(serde_json::json!({
+        "order_notional": 500.0,
+        "take_profit_limit": true,
+        "bracket_grouping": "Entry",
+        "break_even_after_pct": 1.5
+    }))
+    .unwrap();
+    assert_eq!(policy.bracket_grouping, BracketGrouping::Entry);
+    assert!(policy.validate().is_ok());
+    assert_eq!(
+        ExecutionPolicy::new(500.0).bracket_grouping,
+        BracketGrouping::Separate
+    );
+
+    let resting_entry = ExecutionPolicy {
+        entry_order: EntryOrderType::LimitAtMid,
+        ..policy.clone()
+    };
+    assert!(resting_entry.validate().is_err());
+
+    let zero_break_even = ExecutionPolicy {
+        break_even_after_pct: Some(0.0),
+        ..policy
+    };
+    assert!(zero_break_even.validate().is_err());
+}
@@ -0,0 +1,153 @@
+//! Unit tests for position and PnL bookkeeping
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use perptrix::execution::{
+    estimate_liquidation_price, AccountPnl, ExchangeFill, FundingPayment, OrderSide,
+    TrackedPosition,
+};
+
+fn at(seconds: i64) -> DateTime<Utc> {
+    Utc.timestamp_opt(1_700_000_000, 0).unwrap() + Duration::seconds(seconds)
+}
+
+fn fill(trade_id: u64, side: OrderSide, size: f64, price: f64, seconds: i64) -> ExchangeFill {
+    ExchangeFill {
+        trade_id,
+        order_id: 1,
+        client_order_id: None,
+        symbol: "BTC".to_string(),
+        side,
+        size,
+        price,
+        fee: 1.0,
+        timestamp: at(seconds),
+    }
+}
+
+#[test]
+fn adding_to_a_position_averages_the_entry() {
+    let mut position = TrackedPosition::new("0xabc", "BTC", at(0));
+    assert!(position.apply_fill(&fill(1, OrderSide::Buy, 1.0, 40000.0, 0)));
+    assert!(position.apply_fill(&fill(2, OrderSide::Buy, 3.0, 40400.0, 1)));
+
+    assert_eq!(position.size, 4.0);
+    assert!((position.entry_price - 40300.0).abs() < 1e-9);
+    assert_eq!(position.realized_pnl, 0.0);
+    assert_eq!(position.fees_paid, 2.0);
+}
+
+#[test]
+fn reducing_and_flipping_realize_pnl() {
+    let mut position = TrackedPosition::new("0xabc", "BTC", at(0));
+    position.apply_fill(&fill(1, OrderSide::Sell, 2.0, 40000.0, 0));
+
+    // Cover half of the short 500 lower
+    position.apply_fill(&fill(2, OrderSide::Buy, 1.0, 39500.0, 1));
+    assert_eq!(position.size, -1.0);
+    assert_eq!(position.entry_price, 40000.0);
+    assert!((position.realized_pnl - 500.0).abs() < 1e-9);
+
+    // Buy through zero: close the rest and open a long at the fill price
+    position.apply_fill(&fill(3, OrderSide::Buy, 3.0, 39000.0, 2));
+    assert_eq!(position.size, 2.0);
+    assert_eq!(position.entry_price, 39000.0);
+    assert!((position.realized_pnl - 1500.0).abs() < 1e-9);
+
+    position.mark(39250.0, at(3));
+    assert!((position.unrealized_pnl - 500.0).abs() < 1e-9);
+
+    position.apply_fill(&fill(4, OrderSide::Sell, 2.0, 39100.0, 4));
+    assert!(!position.is_open());
+    assert_eq!(position.unrealized_pnl, 0.0);
+    assert!((position.realized_pnl - 1700.0).abs() < 1e-9);
+    assert!((position.net_pnl() - (1700.0 - 4.0)).abs() < 1e-9);
+}
+
+#[test]
+fn replayed_fills_are_ignored() {
+    let mut position = TrackedPosition::new("0xabc", "BTC", at(0));
+    let first = fill(1, OrderSide::Buy, 1.0, 40000.0, 5);
+    let same_time = fill(2, OrderSide::Buy, 1.0, 40000.0, 5);
+
+    assert!(position.apply_fill(&first));
+    assert!(position.apply_fill(&same_time));
+    assert!(!position.apply_fill(&first));
+    assert_eq!(position.size, 2.0);
+
+    // Far older than the latest fill: a replay whose id was already forgotten
+    assert!(!position.apply_fill(&fill(0, OrderSide::Buy, 1.0, 40000.0, 5 - 2 * 3600)));
+    assert_eq!(position.size, 2.0);
+
+    // Dedup state survives persistence
+    let mut restored: TrackedPosition =
+        serde_json::from_str(&serde_json::to_string(&position).unwrap()).unwrap();
+    assert!(!restored.apply_fill(&same_time));
+}
+
+#[test]
+fn fills_arriving_out_of_order_are_applied_once() {
+    let mut position = TrackedPosition::new("0xabc", "BTC", at(0));
+    let earlier = fill(1, OrderSide::Buy, 1.0, 40000.0, 10);
+    let later = fill(2, OrderSide::Buy, 1.0, 40200.0, 20);
+
+    assert!(position.apply_fill(&later));
+    assert!(position.apply_fill(&earlier));
+    assert!(!position.apply_fill(&earlier));
+    assert!(!position.apply_fill(&later));
+
+    assert_eq!(position.size, 2.0);
+    assert!((position.entry_price - 40100.0).abs() < 1e-9);
+    assert_eq!(position.fees_paid, 2.0);
+    assert_eq!(position.last_fill_at, Some(at(20)));
+}
+
+#[test]
+fn funding_payments_accumulate() {
+    let mut position = TrackedPosition::new("0xabc", "ETH", at(0));
+    let payment = |amount: f64| FundingPayment {
+        symbol: "ETH".to_string(),
+        position_size: 2.0,
+        rate: 0.0001,
+        amount,
+        timestamp: at(3600),
+    };
+    position.apply_funding(&payment(-0.8));
+    position.apply_funding(&payment(0.3));
+
+    assert!((position.funding_paid - 0.5).abs() < 1e-9);
+    assert!((position.net_pnl() + 0.5).abs() < 1e-9);
+}
+
+#[test]
+fn liquidation_price_estimate() {
+    let long = estimate_liquidation_price(1.0, 40000.0, 10.0, 0.01).unwrap();
+    assert!((long - 40000.0 * 0.9 / 0.99).abs() < 1e-6);
+
+    let short = estimate_liquidation_price(-1.0, 40000.0, 10.0, 0.01).unwrap();
+    assert!((short - 40000.0 * 1.1 / 1.01).abs() < 1e-6);
+
+    assert_eq!(estimate_liquidation_price(1.0, 40000.0, 1.0, 0.01), None);
+    assert_eq!(estimate_liquidation_price(0.0, 40000.0, 10.0, 0.01), None);
+}
+
+#[test]
+fn account_pnl_sums_positions_per_account() {
+    let mut btc = TrackedPosition::new("0xabc", "BTC", at(0));
+    btc.apply_fill(&fill(1, OrderSide::Buy, 1.0, 40000.0, 0));
+    btc.mark(40100.0, at(10));
+    let mut eth = TrackedPosition::new("0xabc", "ETH", at(0));
+    eth.realized_pnl = 50.0;
+    eth.funding_paid = 2.0;
+    let other = TrackedPosition::new("0xdef", "BTC", at(0));
+
+    let pnl = AccountPnl::from_positions(&[btc, eth, other]);
+    assert_eq!(pnl.len(), 2);
+    assert_eq!(pnl[0].account, "0xabc");
+    assert_eq!(pnl[0].open_positions, 1);
+    assert!((pnl[0].realized_pnl - 50.0).abs() < 1e-9);
+    assert!((pnl[0].unrealized_pnl - 100.0).abs() < 1e-9);
+    assert!((pnl[0].net_pnl - (150.0 - 1.0 - 2.0)).abs() < 1e-9);
+    assert_eq!(pnl[0].updated_at, at(10));
+    assert_eq!(pnl[1].account, "0xdef");
+    assert_eq!(pnl[1].net_pnl, 0.0);
+}
@@ -0,0 +1,459 @@
+//! Unit tests for pre-trade risk checks
+
+use chrono::{Duration, TimeZone, Utc};
+use perptrix::execution::{
+    AccountSnapshot, DayStartEquityStore, ExchangePosition, KillSwitch, KillSwitchState,
+    KillSwitchStore, MemoryDayStartEquityStore, MemoryKillSwitchStore, OpenOrder, OrderRequest,
+    OrderSide, RiskLimits, RiskManager, RiskRejection,
+};
+use perptrix::metrics::Metrics;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+fn manager(limits: RiskLimits) -> RiskManager {
+    let kill_switch = Arc::new(KillSwitch::new(Arc::new(MemoryKillSwitchStore::default())));
+    RiskManager::new(limits, kill_switch)
+}
+
+fn position(symbol: &str, size: f64, entry_price: f64) -> ExchangePosition {
+    ExchangePosition {
+        symbol: symbol.to_string(),
+        size,
+        entry_price,
+        unrealized_pnl: 0.0,
+        leverage: 5.0,
+        liquidation_price: None,
+    }
+}
+
+fn account(positions: Vec<ExchangePosition>, equity: f64) -> AccountSnapshot {
+    AccountSnapshot {
+        positions,
+        equity: Some(equity),
+        ..AccountSnapshot::default()
+    }
+}
+
+fn buy(symbol: &str, size: f64, price: f64) -> OrderRequest {
+    OrderRequest::limit(symbol, OrderSide::Buy, size, price)
+}
+
+#[test]
+fn kill_switch_blocks_new_orders_but_not_reduce_only() {
+    let risk = manager(RiskLimits::default());
+    let now = Utc::now();
+    let state = KillSwitchState {
+        active: true,
+        reason: Some("manual halt".to_string()),
+        ..KillSwitchState::default()
+    };
+
+    let result = risk.check(
+        &buy("BTC", 1.0, 40000.0),
+        &state,
+        &AccountSnapshot::default(),
+        now,
+    );
+    assert_eq!(
+        result,
+        Err(RiskRejection::KillSwitchActive {
+            reason: Some("manual halt".to_string())
+        })
+    );
+
+    let close = OrderRequest::market("BTC", OrderSide::Sell, 1.0, 39000.0).with_reduce_only(true);
+    assert!(risk
+        .check(&close, &state, &AccountSnapshot::default(), now)
+        .is_ok());
+}
+
+#[test]
+fn symbol_allow_list_is_case_insensitive() {
+    let risk = manager(RiskLimits {
+        allowed_symbols: Some(vec!["BTC".to_string(), "ETH".to_string()]),
+        ..RiskLimits::default()
+    });
+    let state = KillSwitchState::default();
+    let now = Utc::now();
+
+    assert!(risk
+        .check(
+            &buy("eth", 1.0, 2000.0),
+            &state,
+            &AccountSnapshot::default(),
+            now
+        )
+        .is_ok());
+    let rejection = risk
+        .check(
+            &buy("DOGE", 1.0, 0.1),
+            &state,
+            &AccountSnapshot::default(),
+            now,
+        )
+        .unwrap_err();
+    assert_eq!(rejection.code(), "symbol_not_allowed");
+}
+
+#[test]
+fn position_notional_counts_the_existing_position() {
+    let risk = manager(RiskLimits {
+        max_position_notional: Some(50_000.0),
+        ..RiskLimits::default()
+    });
+    let state = KillSwitchState::default();
+    let now = Utc::now();
+    let snapshot = account(vec![position("BTC", 1.0, 40000.0)], 100_000.0);
+
+    let rejection = risk
+        .check(&buy("BTC", 0.5, 40000.0), &state, &snapshot, now)
+        .unwrap_err();
+    assert_eq!(
+        rejection,
+        RiskRejection::PositionNotional {
+            symbol: "BTC".to_string(),
+            notional: 60_000.0,
+            limit: 50_000.0
+        }
+    );
+    assert!(risk
+        .check(&buy("BTC", 0.2, 40000.0), &state, &snapshot, now)
+        .is_ok());
+
+    // Reducing an oversized position is always allowed
+    let reduce = OrderRequest::limit("BTC", OrderSide::Sell, 0.5, 40000.0);
+    let oversized = account(vec![position("BTC", 2.0, 40000.0)], 100_000.0);
+    assert!(risk.check(&reduce, &state, &oversized, now).is_ok());
+}
+
+#[test]
+fn open_positions_and_total_leverage_limits() {
+    let risk = manager(RiskLimits {
+        max_open_positions: Some(2),
+        max_total_leverage: Some(3.0),
+        ..RiskLimits::default()
+    });
+    let state = KillSwitchState::default();
+    let now = Utc::now();
+    let snapshot = account(
+        vec![position("BTC", 0.5, 40000.0), position("ETH", -5.0, 2000.0)],
+        20_000.0,
+    );
+
+    let rejection = risk
+        .check(&buy("SOL", 10.0, 100.0), &state, &snapshot, now)
+        .unwrap_err();
+    assert_eq!(
+        rejection,
+        RiskRejection::OpenPositions { open: 2, limit: 2 }
+    );
+
+    // Adding to BTC: (0.75 * 40000 + 10000) / 20000 = 2x
+    assert!(risk
+        .check(&buy("BTC", 0.25, 40000.0), &state, &snapshot, now)
+        .is_ok());
+    // (1.5 * 40000 + 10000) / 20000 = 3.5x
+    let rejection = risk
+        .check(&buy("BTC", 1.0, 40000.0), &state, &snapshot, now)
+        .unwrap_err();
+    assert_eq!(rejection.code(), "total_leverage");
+}
+
+#[test]
+fn daily_loss_is_measured_from_the_first_equity_of_the_day() {
+    let risk = manager(RiskLimits {
+        max_daily_loss: Some(1_000.0),
+        ..RiskLimits::default()
+    });
+    let state = KillSwitchState::default();
+    let morning = Utc.with_ymd_and_hms(2024, 3, 1, 8, 0, 0).unwrap();
+    let order = buy("BTC", 0.01, 40000.0);
+
+    assert!(risk
+        .check(&order, &state, &account(vec![], 10_000.0), morning)
+        .is_ok());
+    assert!(risk
+        .check(
+            &order,
+            &state,
+            &account(vec![], 9_200.0),
+            morning + Duration::hours(2)
+        )
+        .is_ok());
+    let rejection = risk
+        .check(
+            &order,
+            &state,
+            &account(vec![], 8_900.0),
+            morning + Duration::hours(4),
+        )
+        .unwrap_err();
+    assert_eq!(rejection.code(), "daily_loss");
+
+    // A new day starts from the current equity
+    let next_day = morning + Duration::days(1);
+    assert!(risk
+        .check(&order, &state, &account(vec![], 8_900.0), next_day)
+        .is_ok());
+}
+
+#[tokio::test]
+async fn stored_day_start_equity_survives_a_new_manager() {
+    let store = Arc::new(MemoryDayStartEquityStore::default());
+    let limits = RiskLimits {
+        max_daily_loss: Some(1_000.0),
+        ..RiskLimits::default()
+    };
+    let new_manager = || manager(limits.clone()).with_equity_store(store.clone());
+    let state = KillSwitchState::default();
+    let morning = Utc.with_ymd_and_hms(2024, 3, 1, 8, 0, 0).unwrap();
+    let order = buy("BTC", 0.01, 40000.0);
+
+    let risk = new_manager();
+    assert!(!risk.has_day_start_equity(morning));
+    assert_eq!(risk.observe_equity(10_000.0, morning).await, 10_000.0);
+    assert!(risk.has_day_start_equity(morning));
+
+    // A restarted manager reads the start instead of taking the current equity
+    let later = morning + Duration::hours(6);
+    let risk = new_manager();
+    assert_eq!(risk.observe_equity(8_900.0, later).await, 10_000.0);
+    let rejection = risk
+        .check(&order, &state, &account(vec![], 8_900.0), later)
+        .unwrap_err();
+    assert_eq!(
+        rejection,
+        RiskRejection::DailyLoss {
+            loss: 1_100.0,
+            limit: 1_000.0
+        }
+    );
+
+    // Without an observation, checks do not fix the day's start and fail closed
+    let next_day = morning + Duration::days(1);
+    assert_eq!(
+        risk.check(&order, &state, &account(vec![], 8_000.0), next_day),
+        Err(RiskRejection::DayStartEquityUnknown)
+    );
+    assert!(!risk.has_day_start_equity(next_day));
+}
+
+/// Day start equity store that can be made unreadable
+#[derive(Default)]
+struct FlakyEquityStore {
+    inner: MemoryDayStartEquityStore,
+    down: AtomicBool,
+}
+
+#[async_trait::async_trait]
+impl DayStartEquityStore for FlakyEquityStore {
+    async fn day_start_equity(
+        &self,
+        day: chrono::NaiveDate,
+        equity: f64,
+    ) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
+        if self.down.load(Ordering::SeqCst) {
+            return Err("connection refused".into());
+        }
+        self.inner.day_start_equity(day, equity).await
+    }
+}
+
+#[tokio::test]
+async fn daily_loss_fails_closed_while_the_equity_store_is_down() {
+    let store = Arc::new(FlakyEquityStore::default());
+    store.down.store(true, Ordering::SeqCst);
+    let risk = manager(RiskLimits {
+        max_daily_loss: Some(1_000.0),
+        ..RiskLimits::default()
+    })
+    .with_equity_store(store.clone());
+    let state = KillSwitchState::default();
+    let now = Utc::now();
+    let order = buy("BTC", 0.01, 40000.0);
+
+    risk.observe_equity(10_000.0, now).await;
+    assert!(!risk.has_day_start_equity(now));
+    assert_eq!(
+        risk.check(&order, &state, &account(vec![], 10_000.0), now),
+        Err(RiskRejection::DayStartEquityUnknown)
+    );
+    let close = buy("BTC", 0.01, 40000.0).with_reduce_only(true);
+    assert!(risk
+        .check(&close, &state, &account(vec![], 10_000.0), now)
+        .is_ok());
+
+    store.down.store(false, Ordering::SeqCst);
+    assert_eq!(risk.observe_equity(10_000.0, now).await, 10_000.0);
+    assert!(risk
+        .check(&order, &state, &account(vec![], 10_000.0), now)
+        .is_ok());
+}
+
+#[test]
+fn resting_orders_count_towards_position_limits() {
+    let risk = manager(RiskLimits {
+        max_position_notional: Some(50_000.0),
+        ..RiskLimits::default()
+    });
+    let state = KillSwitchState::default();
+    let now = Utc::now();
+    let resting = |side: OrderSide, size: f64, reduce_only: bool| OpenOrder {
+        order_id: 1,
+        symbol: "BTC".to_string(),
+        side,
+        size,
+        price: 40000.0,
+        client_order_id: None,
+        reduce_only,
+        created_at: now,
+    };
+    let mut snapshot = account(vec![position("BTC", 0.5, 40000.0)], 100_000.0);
+
+    // 20k held and 20k resting: another 20k would make 60k
+    snapshot.open_orders = vec![resting(OrderSide::Buy, 0.5, false)];
+    match risk.check(&buy("BTC", 0.5, 40000.0), &state, &snapshot, now) {
+        Err(RiskRejection::PositionNotional { notional, .. }) => assert_eq!(notional, 60_000.0),
+        other => panic!("expected a position notional rejection, got {:?}", other),
+    }
+
+    // Reduce-only and opposite resting orders add no exposure
+    snapshot.open_orders = vec![
+        resting(OrderSide::Buy, 0.5, true),
+        resting(OrderSide::Sell, 0.5, false),
+    ];
+    assert!(risk
+        .check(&buy("BTC", 0.5, 40000.0), &state, &snapshot, now)
+        .is_ok());
+}
+
+#[test]
+fn order_rate_uses_a_sliding_minute() {
+    let risk = manager(RiskLimits {
+        max_orders_per_minute: Some(2),
+        ..RiskLimits::default()
+    });
+    let state = KillSwitchState::default();
+    let start = Utc::now();
+    let snapshot = AccountSnapshot::default();
+    let order = buy("BTC", 0.01, 40000.0);
+
+    assert!(risk.check(&order, &state, &snapshot, start).is_ok());
+    assert!(risk
+        .check(&order, &state, &snapshot, start + Duration::seconds(10))
+        .is_ok());
+    assert_eq!(
+        risk.check(&order, &state, &snapshot, start + Duration::seconds(30)),
+        Err(RiskRejection::OrderRate {
+            orders: 2,
+            limit: 2
+        })
+    );
+    assert!(risk
+        .check(&order, &state, &snapshot, start + Duration::seconds(61))
+        .is_ok());
+}
+
+#[test]
+fn rejections_are_counted_by_reason() {
+    let metrics = Arc::new(Metrics::new().unwrap());
+    let risk = manager(RiskLimits {
+        allowed_symbols: Some(vec!["BTC".to_string()]),
+        ..RiskLimits::default()
+    })
+    .with_metrics(metrics.clone());
+
+    let state = KillSwitchState::default();
+    for _ in 0..2 {
+        let _ = risk.check(
+            &buy("ETH", 1.0, 2000.0),
+            &state,
+            &AccountSnapshot::default(),
+            Utc::now(),
+        );
+    }
+    assert_eq!(
+        metrics
+            .risk_rejections_total
+            .with_label_values(&["symbol_not_allowed"])
+            .get(),
+        2.0
+    );
+}
+
+#[tokio::test]
+async fn kill_switch_keeps_flatten_request_until_marked() {
+    let metrics = Arc::new(Metrics::new().unwrap());
+    let kill_switch =
+        KillSwitch::new(Arc::new(MemoryKillSwitchStore::default())).with_metrics(metrics.clone());
+    assert!(!kill_switch.is_active().await);
+
+    let state = kill_switch.activate("drawdown", true).await.unwrap();
+    assert!(state.active && state.flatten_requested);
+    assert_eq!(metrics.kill_switch_active.get(), 1.0);
+
+    // Re-activating without flatten keeps the pending request
+    let state = kill_switch.activate("still halted", false).await.unwrap();
+    assert!(state.flatten_requested);
+
+    let state = kill_switch.mark_flattened().await.unwrap();
+    assert!(state.active && !state.flatten_requested);
+    assert!(state.flattened_at.is_some());
+
+    let state = kill_switch.deactivate().await.unwrap();
+    assert!(!state.active);
+    assert_eq!(metrics.kill_switch_active.get(), 0.0);
+}
+
+/// Memory store that can be made unreadable
+#[derive(Default)]
+struct FlakyKillSwitchStore {
+    inner: MemoryKillSwitchStore,
+    down: AtomicBool,
+}
+
+#[async_trait::async_trait]
+impl KillSwitchStore for FlakyKillSwitchStore {
+    async fn load_kill_switch(
+        &self,
+    ) -> Result<Option<KillSwitchState>, Box<dyn std::error::Error + Send + Sync>> {
+        if self.down.load(Ordering::SeqCst) {
+            return Err("connection refused".into());
+        }
+        self.inner.load_kill_switch().await
+    }
+
+    async fn save_kill_switch(
+        &self,
+        state: &KillSwitchState,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.save_kill_switch(state).await
+    }
+}
+
+#[tokio::test]
+async fn kill_switch_fails_closed_until_the_store_is_read() {
+    let store = Arc::new(FlakyKillSwitchStore::default());
+    store.down.store(true, Ordering::SeqCst);
+    let kill_switch = KillSwitch::new(store.clone());
+
+    // Never read: new orders are refused, reduce-only orders still pass
+    let state = kill_switch.state().await;
+    assert!(state.active);
+    assert!(!state.flatten_requested);
+    let risk = RiskManager::new(RiskLimits::default(), Arc::new(kill_switch));
+    let now = Utc::now();
+    let account = AccountSnapshot::default();
+    assert!(matches!(
+        risk.check(&buy("BTC", 1.0, 100.0), &state, &account, now),
+        Err(RiskRejection::KillSwitchActive { .. })
+    ));
+    let close = buy("BTC", 1.0, 100.0).with_reduce_only(true);
+    assert!(risk.check(&close, &state, &account, now).is_ok());
+
+    // Once read, an outage falls back to the last known state
+    store.down.store(false, Ordering::SeqCst);
+    assert!(!risk.kill_switch().is_active().await);
+    store.down.store(true, Ordering::SeqCst);
+    assert!(!risk.kill_switch().is_active().await);
+}
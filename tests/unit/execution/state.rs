@@ -0,0 +1,130 @@
+use chrono::{Duration, TimeZone, Utc};
+use perptrix::execution::{
+    new_client_order_id, ExchangeFill, ExecutionError, ManagedOrder, OrderOrigin, OrderRequest,
+    OrderSide, OrderState,
+};
+
+fn order() -> ManagedOrder {
+    let now = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    ManagedOrder::new(
+        "test",
+        OrderRequest::limit("BTC", OrderSide::Buy, 1.0, 40000.0),
+        OrderOrigin::new(7, 1_704_067_200_000),
+        now,
+    )
+}
+
+fn fill(trade_id: u64, size: f64, price: f64) -> ExchangeFill {
+    ExchangeFill {
+        trade_id,
+        order_id: 99,
+        client_order_id: None,
+        symbol: "BTC".to_string(),
+        side: OrderSide::Buy,
+        size,
+        price,
+        fee: 0.5,
+        timestamp: Utc.with_ymd_and_hms(2024, 1, 1, 0, 1, 0).unwrap(),
+    }
+}
+
+#[test]
+fn test_client_order_ids_are_unique_and_well_formed() {
+    let a = new_client_order_id();
+    let b = new_client_order_id();
+    assert_ne!(a, b);
+    for id in [a, b] {
+        assert_eq!(id.len(), 34);
+        assert!(id.starts_with("0x"));
+        assert!(id[2..].chars().all(|c| c.is_ascii_hexdigit()));
+    }
+}
+
+#[test]
+fn test_new_order_carries_client_order_id() {
+    let order = order();
+    assert_eq!(order.state, OrderState::Created);
+    assert_eq!(
+        order.request.client_order_id.as_deref(),
+        Some(order.client_order_id.as_str())
+    );
+    assert_eq!(order.origin.strategy_id, Some(7));
+
+    let explicit = ManagedOrder::new(
+        "test",
+        OrderRequest::limit("BTC", OrderSide::Buy, 1.0, 40000.0)
+            .with_client_order_id("0x000000000000000000000000000000aa"),
+        OrderOrigin::manual(),
+        Utc::now(),
+    );
+    assert_eq!(
+        explicit.client_order_id,
+        "0x000000000000000000000000000000aa"
+    );
+    assert_eq!(explicit.origin.signal_id, None);
+}
+
+#[test]
+fn test_transitions_follow_lifecycle() {
+    assert!(OrderState::Created.can_transition_to(OrderState::Submitted));
+    assert!(OrderState::Submitted.can_transition_to(OrderState::Filled));
+    assert!(OrderState::PartiallyFilled.can_transition_to(OrderState::PartiallyFilled));
+    assert!(OrderState::Acknowledged.can_transition_to(OrderState::Cancelled));
+    assert!(OrderState::Created.can_transition_to(OrderState::Rejected));
+    assert!(!OrderState::Acknowledged.can_transition_to(OrderState::Submitted));
+    assert!(!OrderState::Filled.can_transition_to(OrderState::Cancelled));
+    assert!(!OrderState::Cancelled.can_transition_to(OrderState::Acknowledged));
+
+    let mut order = order();
+    let now = order.created_at;
+    order.transition(OrderState::Submitted, now).unwrap();
+    order.transition(OrderState::Submitted, now).unwrap();
+    order.transition(OrderState::Acknowledged, now).unwrap();
+    assert!(matches!(
+        order.transition(OrderState::Created, now),
+        Err(ExecutionError::InvalidTransition(_))
+    ));
+
+    order.cancel(now);
+    assert_eq!(order.state, OrderState::Cancelled);
+    order.reject("late", now);
+    assert_eq!(order.state, OrderState::Cancelled);
+    assert!(order.reject_reason.is_none());
+}
+
+#[test]
+fn test_fills_advance_state_and_are_deduplicated() {
+    let mut order = order();
+    let now = order.created_at;
+    order.transition(OrderState::Submitted, now).unwrap();
+    order.transition(OrderState::Acknowledged, now).unwrap();
+
+    assert!(order.apply_fill(&fill(1, 0.4, 40000.0)));
+    assert_eq!(order.state, OrderState::PartiallyFilled);
+    assert_eq!(order.exchange_order_id, Some(99));
+    assert!(!order.apply_fill(&fill(1, 0.4, 40000.0)));
+    assert!((order.filled_size - 0.4).abs() < 1e-12);
+
+    assert!(order.apply_fill(&fill(2, 0.6, 40100.0)));
+    assert_eq!(order.state, OrderState::Filled);
+    assert!((order.remaining_size()).abs() < 1e-12);
+    assert!((order.avg_fill_price.unwrap() - 40060.0).abs() < 1e-9);
+    assert!((order.fees_paid - 1.0).abs() < 1e-12);
+    assert!(order.updated_at > now);
+}
+
+#[test]
+fn test_reported_fill_size_is_not_double_counted_by_trades() {
+    let mut order = order();
+    let now = order.created_at;
+    order.transition(OrderState::Submitted, now).unwrap();
+    order.sync_filled_size(1.0, Some(40050.0), now + Duration::seconds(1));
+    assert_eq!(order.state, OrderState::Filled);
+    assert_eq!(order.avg_fill_price, Some(40050.0));
+
+    // The trade records for the same execution arrive later
+    order.apply_fill(&fill(1, 0.5, 40000.0));
+    order.apply_fill(&fill(2, 0.5, 40100.0));
+    assert!((order.filled_size - 1.0).abs() < 1e-12);
+    assert!((order.avg_fill_price.unwrap() - 40050.0).abs() < 1e-9);
+}
@@ -0,0 +1,159 @@
+//! Unit tests for paper trading account bookkeeping
+
+use chrono::{Duration, TimeZone, Utc};
+use perptrix::models::signal::SignalDirection;
+use perptrix::paper::{FillAction, PaperConfig, PaperEngine, PaperError};
+
+fn frictionless() -> PaperConfig {
+    PaperConfig {
+        fee_bps: 0.0,
+        slippage_bps: 0.0,
+        ..PaperConfig::default()
+    }
+}
+
+#[test]
+fn opens_with_fees_and_slippage() {
+    let engine = PaperEngine::new(PaperConfig {
+        initial_capital: 10_000.0,
+        fee_bps: 10.0,
+        slippage_bps: 5.0,
+        ..PaperConfig::default()
+    });
+    let at = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let mut account = engine.open_account(1, "BTC", at);
+
+    let fills = engine
+        .apply_signal(&mut account, SignalDirection::Long, 100.0, at)
+        .unwrap();
+    assert_eq!(fills.len(), 1);
+    let fill = &fills[0];
+    assert_eq!(fill.action, FillAction::Open);
+    assert!((fill.price - 100.05).abs() < 1e-9);
+    assert!((fill.fee - 10.0).abs() < 1e-9);
+    assert!((account.cash - 9_990.0).abs() < 1e-9);
+
+    // Marked at the raw price the position is down by slippage only
+    let expected = (100.0 - 100.05) * fill.size;
+    assert!((account.unrealized_pnl() - expected).abs() < 1e-9);
+}
+
+#[test]
+fn same_direction_keeps_position_and_opposite_flips() {
+    let engine = PaperEngine::new(frictionless());
+    let at = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let mut account = engine.open_account(1, "BTC", at);
+
+    engine
+        .apply_signal(&mut account, SignalDirection::Long, 100.0, at)
+        .unwrap();
+    let again = engine
+        .apply_signal(&mut account, SignalDirection::Long, 105.0, at)
+        .unwrap();
+    assert!(again.is_empty());
+
+    let flip = engine
+        .apply_signal(&mut account, SignalDirection::Short, 110.0, at)
+        .unwrap();
+    assert_eq!(flip.len(), 2);
+    assert_eq!(flip[0].action, FillAction::Close);
+    assert_eq!(flip[0].direction, SignalDirection::Long);
+    assert!((flip[0].realized_pnl - 1_000.0).abs() < 1e-6);
+    assert_eq!(flip[1].action, FillAction::Open);
+    assert_eq!(flip[1].direction, SignalDirection::Short);
+
+    // The new short is sized from the grown equity
+    let pos = account.position.as_ref().unwrap();
+    assert!((pos.size * 110.0 - 11_000.0).abs() < 1e-6);
+    assert_eq!(account.trades, 1);
+}
+
+#[test]
+fn neutral_closes_position() {
+    let engine = PaperEngine::new(frictionless());
+    let at = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let mut account = engine.open_account(1, "BTC", at);
+
+    engine
+        .apply_signal(&mut account, SignalDirection::Short, 100.0, at)
+        .unwrap();
+    let fills = engine
+        .apply_signal(&mut account, SignalDirection::Neutral, 90.0, at)
+        .unwrap();
+    assert_eq!(fills.len(), 1);
+    assert!(account.position.is_none());
+    assert!((account.realized_pnl - 1_000.0).abs() < 1e-6);
+    assert!((account.equity() - 11_000.0).abs() < 1e-6);
+    assert!((account.return_pct() - 10.0).abs() < 1e-9);
+
+    // Neutral without a position does nothing
+    assert!(engine
+        .apply_signal(&mut account, SignalDirection::Neutral, 90.0, at)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn funding_accrues_hourly_against_position_side() {
+    let engine = PaperEngine::new(frictionless());
+    let at = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let mut long = engine.open_account(1, "BTC", at);
+    let mut short = engine.open_account(2, "BTC", at);
+    engine
+        .apply_signal(&mut long, SignalDirection::Long, 100.0, at)
+        .unwrap();
+    engine
+        .apply_signal(&mut short, SignalDirection::Short, 100.0, at)
+        .unwrap();
+
+    let later = at + Duration::hours(2);
+    let paid = engine.accrue_funding(&mut long, Some(0.0001), 100.0, later);
+    let received = engine.accrue_funding(&mut short, Some(0.0001), 100.0, later);
+    assert!((paid - 2.0).abs() < 1e-9);
+    assert!((received + 2.0).abs() < 1e-9);
+    assert!((long.funding_paid - 2.0).abs() < 1e-9);
+    assert!((long.cash - 9_998.0).abs() < 1e-9);
+
+    // Already accrued up to `later`
+    assert_eq!(engine.accrue_funding(&mut long, Some(0.0001), 100.0, later), 0.0);
+
+    let fills = engine
+        .apply_signal(&mut long, SignalDirection::Neutral, 100.0, later)
+        .unwrap();
+    assert!((fills[0].realized_pnl + 2.0).abs() < 1e-9);
+}
+
+#[test]
+fn rejects_invalid_prices_and_config() {
+    let engine = PaperEngine::new(PaperConfig::default());
+    let at = Utc::now();
+    let mut account = engine.open_account(1, "BTC", at);
+    assert!(matches!(
+        engine.apply_signal(&mut account, SignalDirection::Long, 0.0, at),
+        Err(PaperError::InvalidPrice { .. })
+    ));
+    assert!(account.position.is_none());
+
+    let config = PaperConfig {
+        leverage: 0.0,
+        ..PaperConfig::default()
+    };
+    assert!(matches!(config.validate(), Err(PaperError::InvalidConfig(_))));
+}
+
+#[test]
+fn snapshot_reports_signed_position() {
+    let engine = PaperEngine::new(frictionless());
+    let at = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let mut account = engine.open_account(7, "ETH", at);
+    engine
+        .apply_signal(&mut account, SignalDirection::Short, 50.0, at)
+        .unwrap();
+    engine.mark(&mut account, 45.0, at);
+
+    let snapshot = engine.snapshot(&account, at);
+    assert_eq!(snapshot.strategy_id, 7);
+    assert!((snapshot.position_size + 200.0).abs() < 1e-9);
+    assert!((snapshot.unrealized_pnl - 1_000.0).abs() < 1e-9);
+    assert!((snapshot.equity - 11_000.0).abs() < 1e-9);
+}
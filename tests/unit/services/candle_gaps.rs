@@ -0,0 +1,111 @@
+//! Unit tests for candle gap detection
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use perptrix::models::indicators::Candle;
+use perptrix::services::candle_gaps::{find_gaps, recent_gaps, trailing_gap, CandleGap};
+
+const MINUTE_MS: u64 = 60_000;
+
+fn start() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 59).unwrap()
+}
+
+/// One-minute candles at the given bar offsets from `start`
+fn candles(bars: &[i64]) -> Vec<Candle> {
+    bars.iter()
+        .map(|&i| {
+            Candle::new(
+                100.0,
+                101.0,
+                99.0,
+                100.5,
+                1.0,
+                start() + Duration::minutes(i),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn contiguous_candles_have_no_gaps() {
+    assert!(find_gaps(&candles(&[0, 1, 2, 3]), MINUTE_MS, &[]).is_empty());
+    assert!(find_gaps(&candles(&[]), MINUTE_MS, &[]).is_empty());
+}
+
+#[test]
+fn missing_bars_are_grouped_into_gaps() {
+    let gaps = find_gaps(&candles(&[0, 1, 4, 5, 7]), MINUTE_MS, &[]);
+    assert_eq!(gaps.len(), 2);
+    assert_eq!(gaps[0].from, start() + Duration::minutes(2));
+    assert_eq!(gaps[0].to, start() + Duration::minutes(3));
+    assert_eq!(gaps[0].missing_bars, 2);
+    assert_eq!(gaps[1].from, start() + Duration::minutes(6));
+    assert_eq!(gaps[1].missing_bars, 1);
+}
+
+#[test]
+fn duplicate_and_unsorted_candles_are_tolerated() {
+    assert!(find_gaps(&candles(&[2, 0, 1, 1, 3]), MINUTE_MS, &[]).is_empty());
+}
+
+#[test]
+fn trailing_gap_leaves_the_latest_closed_bar_to_the_feed() {
+    let history = candles(&[0, 1, 2]);
+    let last = start() + Duration::minutes(2);
+
+    assert!(trailing_gap(&history, MINUTE_MS, last + Duration::seconds(119)).is_none());
+
+    let gap = trailing_gap(
+        &history,
+        MINUTE_MS,
+        last + Duration::minutes(5) + Duration::seconds(1),
+    )
+    .expect("trailing gap");
+    assert_eq!(gap.from, last + Duration::minutes(1));
+    assert_eq!(gap.to, last + Duration::minutes(4));
+    assert_eq!(gap.missing_bars, 4);
+}
+
+#[test]
+fn recent_gaps_cover_holes_and_a_stale_tail() {
+    let history = candles(&[0, 2, 3]);
+    let now = start() + Duration::minutes(3) + Duration::seconds(30);
+    assert_eq!(recent_gaps(&history, MINUTE_MS, now, &[]).len(), 1);
+
+    let gaps = recent_gaps(&history, MINUTE_MS, now + Duration::minutes(10), &[]);
+    assert_eq!(gaps.len(), 2);
+    assert_eq!(gaps[1].missing_bars, 9);
+}
+
+#[test]
+fn confirmed_empty_bars_are_not_gaps() {
+    let history = candles(&[0, 1, 6, 7]);
+    // Bars 3 and 4 are confirmed empty; 2 and 5 are still missing
+    let empty = [CandleGap {
+        from: start() + Duration::minutes(3),
+        to: start() + Duration::minutes(4),
+        missing_bars: 2,
+    }];
+    let gaps = find_gaps(&history, MINUTE_MS, &empty);
+    assert_eq!(gaps.len(), 2);
+    assert_eq!(
+        (gaps[0].from, gaps[0].missing_bars),
+        (start() + Duration::minutes(2), 1)
+    );
+    assert_eq!(
+        (gaps[1].from, gaps[1].missing_bars),
+        (start() + Duration::minutes(5), 1)
+    );
+
+    // A stale tail is only a gap past the confirmed empty bars
+    let now = start() + Duration::minutes(12) + Duration::seconds(30);
+    let empty = [CandleGap {
+        from: start() + Duration::minutes(2),
+        to: start() + Duration::minutes(10),
+        missing_bars: 9,
+    }];
+    let gaps = recent_gaps(&history, MINUTE_MS, now, &empty);
+    assert_eq!(gaps.len(), 1);
+    assert_eq!(gaps[0].from, start() + Duration::minutes(11));
+    assert_eq!(gaps[0].missing_bars, 1);
+}
@@ -0,0 +1,215 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use perptrix::db::CandleStore;
+use perptrix::models::indicators::Candle;
+use perptrix::services::candle_io::{
+    decode_candles, encode_candles, import_candles, parse_timestamp, validate_candles,
+    CandleFormat, CandleIoError,
+};
+use std::path::Path;
+use tokio::sync::Mutex;
+
+const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/candles/btc_1h.csv");
+
+fn candles(count: usize) -> Vec<Candle> {
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    (0..count)
+        .map(|i| {
+            let open = 100.0 + i as f64;
+            Candle {
+                open,
+                high: open + 2.0,
+                low: open - 1.5,
+                close: open + 1.0,
+                volume: 10.0 + i as f64,
+                timestamp: start + Duration::hours(i as i64),
+                // Leave gaps so missing values must survive the round trip
+                open_interest: (i % 2 == 0).then_some(5000.0 + i as f64),
+                funding_rate: (i % 3 != 0).then_some(0.0001 * i as f64),
+                mark_price: None,
+                oracle_price: None,
+                premium: None,
+                day_notional_volume: None,
+                is_closed: true,
+            }
+        })
+        .collect()
+}
+
+fn assert_same(a: &[Candle], b: &[Candle]) {
+    assert_eq!(a.len(), b.len());
+    for (x, y) in a.iter().zip(b) {
+        assert_eq!(x.timestamp, y.timestamp);
+        assert_eq!(
+            (x.open, x.high, x.low, x.close, x.volume),
+            (y.open, y.high, y.low, y.close, y.volume)
+        );
+        assert_eq!(x.open_interest, y.open_interest);
+        assert_eq!(x.funding_rate, y.funding_rate);
+    }
+}
+
+#[derive(Default)]
+struct MemoryCandleStore {
+    candles: Mutex<Vec<Candle>>,
+}
+
+#[async_trait]
+impl CandleStore for MemoryCandleStore {
+    async fn latest_candle_timestamp(
+        &self,
+        _symbol: &str,
+        _interval: &str,
+    ) -> Result<Option<DateTime<Utc>>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.candles.lock().await.iter().map(|c| c.timestamp).max())
+    }
+
+    async fn store_candles_batch(
+        &self,
+        _symbol: &str,
+        _interval: &str,
+        candles: &[Candle],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.candles.lock().await.extend_from_slice(candles);
+        Ok(())
+    }
+
+    async fn candles_in_range(
+        &self,
+        _symbol: &str,
+        _interval: &str,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Candle>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self
+            .candles
+            .lock()
+            .await
+            .iter()
+            .filter(|c| start.is_none_or(|s| c.timestamp >= s) && end.is_none_or(|e| c.timestamp <= e))
+            .cloned()
+            .collect())
+    }
+}
+
+#[test]
+fn csv_round_trip_preserves_optional_fields() {
+    let original = candles(10);
+    let data = encode_candles(&original, CandleFormat::Csv).unwrap();
+    let text = String::from_utf8(data.clone()).unwrap();
+    assert!(text.starts_with("timestamp,open,high,low,close,volume,open_interest,funding_rate"));
+
+    let decoded = decode_candles(&data, CandleFormat::Csv).unwrap();
+    assert_same(&original, &decoded);
+}
+
+#[test]
+fn parquet_round_trip_preserves_optional_fields() {
+    let original = candles(10);
+    let data = encode_candles(&original, CandleFormat::Parquet).unwrap();
+    assert_eq!(&data[..4], b"PAR1");
+
+    let decoded = decode_candles(&data, CandleFormat::Parquet).unwrap();
+    assert_same(&original, &decoded);
+}
+
+#[test]
+fn empty_export_still_has_csv_header() {
+    let data = encode_candles(&[], CandleFormat::Csv).unwrap();
+    assert!(String::from_utf8(data.clone()).unwrap().starts_with("timestamp,"));
+    assert!(decode_candles(&data, CandleFormat::Csv).unwrap().is_empty());
+}
+
+#[test]
+fn fixture_file_is_valid() {
+    let data = std::fs::read(FIXTURE).unwrap();
+    let mut candles = decode_candles(&data, CandleFormat::Csv).unwrap();
+    validate_candles(&mut candles).unwrap();
+    assert_eq!(candles.len(), 48);
+    assert!(candles.iter().all(|c| c.open_interest.is_some() && c.funding_rate.is_some()));
+}
+
+#[test]
+fn validation_reports_offending_row() {
+    let mut data = candles(5);
+    data[3].high = data[3].low - 1.0;
+    match validate_candles(&mut data) {
+        Err(CandleIoError::InvalidRow { row, .. }) => assert_eq!(row, 4),
+        other => panic!("expected invalid row, got {:?}", other),
+    }
+
+    let mut data = candles(5);
+    data[2].volume = -1.0;
+    assert!(matches!(
+        validate_candles(&mut data),
+        Err(CandleIoError::InvalidRow { row: 3, .. })
+    ));
+}
+
+#[test]
+fn validation_rejects_duplicates_and_sorts() {
+    let mut data = candles(4);
+    data[3].timestamp = data[1].timestamp;
+    assert!(matches!(
+        validate_candles(&mut data),
+        Err(CandleIoError::InvalidRow { row: 4, .. })
+    ));
+
+    let mut data = candles(4);
+    data.reverse();
+    validate_candles(&mut data).unwrap();
+    assert!(data.windows(2).all(|w| w[0].timestamp < w[1].timestamp));
+}
+
+#[test]
+fn csv_reports_unparseable_rows() {
+    let csv = "timestamp,open,high,low,close,volume,open_interest,funding_rate\n\
+               2024-01-01T00:00:00Z,1,2,0.5,1.5,10,,\n\
+               not-a-date,1,2,0.5,1.5,10,,\n";
+    assert!(matches!(
+        decode_candles(csv.as_bytes(), CandleFormat::Csv),
+        Err(CandleIoError::InvalidRow { row: 2, .. })
+    ));
+    assert!(matches!(
+        decode_candles(b"not parquet", CandleFormat::Parquet),
+        Err(CandleIoError::Format(_))
+    ));
+}
+
+#[test]
+fn parses_supported_timestamp_formats() {
+    let expected = Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap();
+    assert_eq!(parse_timestamp("2024-01-02T00:00:00Z"), Some(expected));
+    assert_eq!(parse_timestamp("2024-01-02"), Some(expected));
+    assert_eq!(parse_timestamp("1704153600000"), Some(expected));
+    assert_eq!(parse_timestamp("yesterday"), None);
+}
+
+#[test]
+fn infers_format_from_extension() {
+    assert_eq!(CandleFormat::from_path(Path::new("a/b.csv")), Some(CandleFormat::Csv));
+    assert_eq!(
+        CandleFormat::from_path(Path::new("b.PARQUET")),
+        Some(CandleFormat::Parquet)
+    );
+    assert_eq!(CandleFormat::from_path(Path::new("b.json")), None);
+}
+
+#[tokio::test]
+async fn import_skips_candles_already_stored() {
+    let store = MemoryCandleStore::default();
+    let data = encode_candles(&candles(6), CandleFormat::Parquet).unwrap();
+
+    let first = import_candles(&store, "BTC", "1h", &data, CandleFormat::Parquet)
+        .await
+        .unwrap();
+    assert_eq!(first.imported, 6);
+
+    let again = import_candles(&store, "BTC", "1h", &data, CandleFormat::Parquet)
+        .await
+        .unwrap();
+    assert_eq!(again.rows, 6);
+    assert_eq!(again.imported, 0);
+    assert_eq!(again.skipped_existing, 6);
+    assert_eq!(store.candles.lock().await.len(), 6);
+}
@@ -0,0 +1,149 @@
+use perptrix::execution::ExecutionError;
+use perptrix::services::hyperliquid::exchange::{
+    float_to_wire, round_price, round_size, tick_size, HyperliquidAction,
+};
+use perptrix::services::hyperliquid::signing::{
+    action_hash, l1_action_digest, recover_address, HyperliquidSigner,
+};
+use serde::Serialize;
+
+const TEST_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+const TEST_ADDRESS: &str = "0x2c7536e3605d9c16a7a3d7b1898e529396a65c23";
+
+#[derive(Serialize)]
+struct DummyAction {
+    #[serde(rename = "type")]
+    kind: String,
+    num: u64,
+}
+
+#[test]
+fn test_signer_derives_address() {
+    let signer = HyperliquidSigner::from_hex(TEST_KEY).unwrap();
+    assert_eq!(signer.address(), TEST_ADDRESS);
+
+    // Prefix is optional
+    let signer = HyperliquidSigner::from_hex(TEST_KEY.trim_start_matches("0x")).unwrap();
+    assert_eq!(signer.address(), TEST_ADDRESS);
+}
+
+#[test]
+fn test_signer_rejects_invalid_key() {
+    assert!(matches!(
+        HyperliquidSigner::from_hex("0xnothex"),
+        Err(ExecutionError::Signing(_))
+    ));
+    assert!(matches!(
+        HyperliquidSigner::from_hex("0x00"),
+        Err(ExecutionError::Signing(_))
+    ));
+}
+
+#[test]
+fn test_l1_signature_recovers_to_signer() {
+    let signer = HyperliquidSigner::from_hex(TEST_KEY).unwrap();
+    let action = HyperliquidAction::UpdateLeverage {
+        asset: 0,
+        is_cross: true,
+        leverage: 5,
+    };
+
+    for is_mainnet in [true, false] {
+        let signature = signer
+            .sign_l1_action(&action, 1_700_000_000_000, None, is_mainnet)
+            .unwrap();
+        assert!(signature.v == 27 || signature.v == 28);
+        assert_eq!(signature.r.len(), 66);
+        assert_eq!(signature.s.len(), 66);
+
+        let hash = action_hash(&action, 1_700_000_000_000, None).unwrap();
+        let digest = l1_action_digest(&hash, is_mainnet);
+        assert_eq!(recover_address(&digest, &signature).unwrap(), TEST_ADDRESS);
+
+        // The other network's digest recovers to a different address
+        let other = l1_action_digest(&hash, !is_mainnet);
+        assert_ne!(recover_address(&other, &signature).unwrap(), TEST_ADDRESS);
+    }
+}
+
+#[test]
+fn test_action_hash_covers_nonce_and_vault() {
+    let action = HyperliquidAction::Cancel { cancels: vec![] };
+    let base = action_hash(&action, 1, None).unwrap();
+    assert_eq!(base, action_hash(&action, 1, None).unwrap());
+    assert_ne!(base, action_hash(&action, 2, None).unwrap());
+    let vault = action_hash(
+        &action,
+        1,
+        Some("0x1719884eb866cb12b2287399b15f7db5e7d775ea"),
+    )
+    .unwrap();
+    assert_ne!(base, vault);
+
+    assert!(action_hash(&action, 1, Some("0x1234")).is_err());
+}
+
+#[test]
+fn test_matches_reference_signature() {
+    // Reference vector from the official Python SDK's signing tests
+    let signer = HyperliquidSigner::from_hex(
+        "0x0123456789012345678901234567890123456789012345678901234567890123",
+    )
+    .unwrap();
+    let action = DummyAction {
+        kind: "dummy".to_string(),
+        num: 100_000_000_000,
+    };
+
+    let mainnet = signer.sign_l1_action(&action, 0, None, true).unwrap();
+    // The SDK drops leading zeros; ours are padded to 32 bytes
+    assert_eq!(
+        mainnet.r,
+        "0x053749d5b30552aeb2fca34b530185976545bb22d0b3ce6f62e31be961a59298"
+    );
+    assert_eq!(
+        mainnet.s,
+        "0x755c40ba9bf05223521753995abb2f73ab3229be8ec921f350cb447e384d8ed8"
+    );
+    assert_eq!(mainnet.v, 27);
+}
+
+#[test]
+fn test_float_to_wire() {
+    assert_eq!(float_to_wire(100.0).unwrap(), "100");
+    assert_eq!(float_to_wire(0.1).unwrap(), "0.1");
+    assert_eq!(float_to_wire(1234.5).unwrap(), "1234.5");
+    assert_eq!(float_to_wire(0.00012345).unwrap(), "0.00012345");
+    assert_eq!(float_to_wire(-0.0).unwrap(), "0");
+    assert!(matches!(
+        float_to_wire(0.123456789),
+        Err(ExecutionError::InvalidOrder(_))
+    ));
+}
+
+#[test]
+fn test_price_and_size_rounding() {
+    // 5 significant figures
+    assert_eq!(round_price(43251.7, 5), 43252.0);
+    assert_eq!(round_price(1.234567, 0), 1.2346);
+    // At most 6 - szDecimals decimals
+    assert_eq!(round_price(0.0123456, 2), 0.0123);
+    assert_eq!(round_size(0.123456, 3), 0.123);
+    assert_eq!(round_size(12.6, 0), 13.0);
+}
+
+#[test]
+fn test_tick_size_follows_price_rounding() {
+    // Significant figures bind for large prices
+    assert_eq!(tick_size(64321.5, 5), 1.0);
+    assert_eq!(tick_size(123456.0, 5), 10.0);
+    // Size decimals bind for small prices
+    assert!((tick_size(0.0123456, 2) - 1e-4).abs() < 1e-12);
+    // Without a price only the decimal limit is known
+    assert!((tick_size(0.0, 3) - 1e-3).abs() < 1e-12);
+    for price in [43251.7, 1.234567, 0.0123456] {
+        let tick = tick_size(price, 2);
+        let rounded = round_price(price, 2);
+        assert!(((rounded / tick).round() * tick - rounded).abs() < tick * 1e-6);
+    }
+}
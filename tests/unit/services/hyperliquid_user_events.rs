@@ -0,0 +1,149 @@
+//! Unit tests for parsing Hyperliquid user event messages
+
+use perptrix::execution::{ExchangeOrderState, OrderSide, UserEvent};
+use perptrix::services::hyperliquid::messages::{RequestMessage, Subscription};
+use perptrix::services::hyperliquid::parse_user_events;
+
+#[test]
+fn test_order_updates_become_order_reports() {
+    let text = r#"{"channel":"orderUpdates","data":[
+        {"order":{"coin":"BTC","side":"B","limitPx":"40000.0","sz":"0.4","oid":42,
+                  "timestamp":1700000000000,"origSz":"1.0","cloid":"0x0000000000000000000000000000abcd"},
+         "status":"open","statusTimestamp":1700000001000},
+        {"order":{"coin":"BTC","side":"A","limitPx":"41000.0","sz":"0.0","oid":43,
+                  "timestamp":1700000000000,"origSz":"1.0"},
+         "status":"marginCanceled","statusTimestamp":1700000002000},
+        {"order":{"coin":"ETH","side":"A","limitPx":"2000.0","sz":"1.0","oid":44,
+                  "timestamp":1700000000000,"origSz":"1.0"},
+         "status":"someNewStatus","statusTimestamp":1700000003000}
+    ]}"#;
+
+    let events = parse_user_events(text).unwrap();
+    assert_eq!(events.len(), 2);
+    match &events[0] {
+        UserEvent::OrderUpdate(report) => {
+            assert_eq!(report.order_id, 42);
+            assert_eq!(
+                report.client_order_id.as_deref(),
+                Some("0x0000000000000000000000000000abcd")
+            );
+            assert_eq!(report.state, ExchangeOrderState::Open);
+            assert_eq!(report.original_size, 1.0);
+            assert_eq!(report.remaining_size, 0.4);
+            assert_eq!(report.updated_at.timestamp_millis(), 1700000001000);
+        }
+        other => panic!("expected an order update, got {:?}", other),
+    }
+    match &events[1] {
+        UserEvent::OrderUpdate(report) => {
+            assert_eq!(report.order_id, 43);
+            assert_eq!(report.state, ExchangeOrderState::Cancelled);
+        }
+        other => panic!("expected an order update, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_user_fills_become_fills() {
+    let text = r#"{"channel":"userFills","data":{"isSnapshot":true,"user":"0xabc","fills":[
+        {"coin":"BTC","px":"40010.5","sz":"0.25","side":"A","time":1700000000000,
+         "startPosition":"0.25","dir":"Close Long","closedPnl":"2.6","hash":"0x00",
+         "oid":42,"crossed":true,"fee":"1.2","tid":900,"feeToken":"USDC"}
+    ]}}"#;
+
+    let events = parse_user_events(text).unwrap();
+    assert_eq!(events.len(), 1);
+    match &events[0] {
+        UserEvent::Fill(fill) => {
+            assert_eq!(fill.trade_id, 900);
+            assert_eq!(fill.order_id, 42);
+            assert_eq!(fill.client_order_id, None);
+            assert_eq!(fill.symbol, "BTC");
+            assert_eq!(fill.side, OrderSide::Sell);
+            assert_eq!(fill.size, 0.25);
+            assert_eq!(fill.price, 40010.5);
+            assert_eq!(fill.fee, 1.2);
+        }
+        other => panic!("expected a fill, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_funding_snapshots_are_skipped() {
+    let update = r#"{"channel":"userFundings","data":{"user":"0xabc","fundings":[
+        {"time":1700000000000,"coin":"ETH","usdc":"-0.42","szi":"2.0","fundingRate":"0.0001"}
+    ]}}"#;
+    let events = parse_user_events(update).unwrap();
+    assert_eq!(events.len(), 1);
+    match &events[0] {
+        UserEvent::Funding(payment) => {
+            assert_eq!(payment.symbol, "ETH");
+            assert_eq!(payment.position_size, 2.0);
+            assert_eq!(payment.rate, 0.0001);
+            assert_eq!(payment.amount, -0.42);
+        }
+        other => panic!("expected a funding payment, got {:?}", other),
+    }
+
+    let snapshot = update.replace(r#"{"user""#, r#"{"isSnapshot":true,"user""#);
+    assert!(parse_user_events(&snapshot).unwrap().is_empty());
+}
+
+#[test]
+fn test_web_data_becomes_account_snapshot() {
+    let text = r#"{"channel":"webData2","data":{"user":"0xabc","clearinghouseState":{
+        "assetPositions":[
+            {"type":"oneWay","position":{"coin":"BTC","szi":"-0.5","entryPx":"40000.0",
+             "unrealizedPnl":"12.5","leverage":{"type":"cross","value":5},"liquidationPx":"48000.0"}},
+            {"type":"oneWay","position":{"coin":"ETH","szi":"0.0","entryPx":null,
+             "unrealizedPnl":"0.0","leverage":{"type":"cross","value":3},"liquidationPx":null}}
+        ],
+        "marginSummary":{"accountValue":"10250.75","totalNtlPos":"20000.0"},
+        "time":1700000000000
+    }}}"#;
+
+    let events = parse_user_events(text).unwrap();
+    assert_eq!(events.len(), 1);
+    match &events[0] {
+        UserEvent::Account(snapshot) => {
+            assert_eq!(snapshot.equity, Some(10250.75));
+            assert_eq!(snapshot.positions.len(), 1);
+            let position = &snapshot.positions[0];
+            assert_eq!(position.symbol, "BTC");
+            assert_eq!(position.size, -0.5);
+            assert_eq!(position.leverage, 5.0);
+            assert_eq!(position.liquidation_price, Some(48000.0));
+        }
+        other => panic!("expected an account snapshot, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_market_data_channels_yield_no_events() {
+    let text = r#"{"channel":"trades","data":[{"coin":"BTC","side":"B","px":"1","sz":"1","time":1,"hash":"0x","tid":1}]}"#;
+    assert!(parse_user_events(text).unwrap().is_empty());
+    assert!(parse_user_events(r#"{"channel":"pong"}"#)
+        .unwrap()
+        .is_empty());
+    assert!(parse_user_events("not json").is_err());
+}
+
+#[test]
+fn test_user_subscription_serialization() {
+    let request = RequestMessage::Subscribe {
+        subscription: Subscription::order_updates("0xabc"),
+    };
+    let json: serde_json::Value = serde_json::to_value(&request).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "method": "subscribe",
+            "subscription": {"type": "orderUpdates", "user": "0xabc"}
+        })
+    );
+
+    let fills = serde_json::to_value(Subscription::user_fills("0xabc")).unwrap();
+    assert_eq!(fills["type"], "userFills");
+    let web_data = serde_json::to_value(Subscription::web_data2("0xabc")).unwrap();
+    assert_eq!(web_data["type"], "webData2");
+}
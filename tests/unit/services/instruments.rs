@@ -0,0 +1,83 @@
+//! Unit tests for symbol normalization and the instrument registry
+
+use perptrix::services::instruments::{normalize_symbol, Instrument, InstrumentRegistry};
+
+fn instrument(symbol: &str, venue: &str, listed: bool) -> Instrument {
+    Instrument {
+        symbol: symbol.to_string(),
+        venue: venue.to_string(),
+        venue_symbol: symbol.rsplit(':').next().unwrap().to_string(),
+        tick_size: 0.1,
+        size_decimals: 2,
+        max_leverage: 10,
+        listed,
+    }
+}
+
+#[test]
+fn symbols_are_normalized_to_canonical_form() {
+    for spelling in [
+        "BTC",
+        "btc",
+        " BTC ",
+        "BTC-PERP",
+        "BTC-USD",
+        "BTC-USDC",
+        "btc/usd",
+        "BTC-USD-PERP",
+    ] {
+        assert_eq!(normalize_symbol(spelling), "BTC", "{}", spelling);
+    }
+    assert_eq!(normalize_symbol("kPEPE-PERP"), "kPEPE");
+    assert_eq!(normalize_symbol("kpepe"), "KPEPE");
+    // A suffix alone is a symbol, not something to strip
+    assert_eq!(normalize_symbol("-USD"), "-USD");
+    // Other venues keep their native name
+    assert_eq!(normalize_symbol("Binance:btcusdt"), "binance:BTCUSDT");
+    assert_eq!(normalize_symbol("hyperliquid:eth-perp"), "ETH");
+}
+
+#[test]
+fn registry_rejects_unknown_and_delisted_symbols() {
+    let registry = InstrumentRegistry::new().with_instruments(
+        "hyperliquid",
+        vec![
+            instrument("ETH", "hyperliquid", true),
+            instrument("kPEPE", "hyperliquid", true),
+            instrument("LUNA", "hyperliquid", false),
+        ],
+    );
+
+    assert_eq!(registry.normalize("eth-perp").unwrap(), "ETH");
+    assert_eq!(registry.normalize("kpepe").unwrap(), "kPEPE");
+    assert_eq!(registry.get("ETH-USD").unwrap().max_leverage, 10);
+    assert!(registry
+        .normalize("LUNA")
+        .unwrap_err()
+        .to_string()
+        .contains("delisted"));
+    assert!(registry
+        .normalize("DOGE")
+        .unwrap_err()
+        .to_string()
+        .contains("Unknown symbol DOGE"));
+
+    // Venues without loaded metadata are normalized but not validated
+    assert_eq!(
+        registry.normalize("binance:solusdt").unwrap(),
+        "binance:SOLUSDT"
+    );
+    let registry = registry.with_instruments(
+        "binance",
+        vec![instrument("binance:BTCUSDT", "binance", true)],
+    );
+    assert!(registry.normalize("binance:solusdt").is_err());
+    assert_eq!(
+        registry
+            .instruments()
+            .iter()
+            .map(|i| i.symbol.as_str())
+            .collect::<Vec<_>>(),
+        vec!["ETH", "LUNA", "binance:BTCUSDT", "kPEPE"]
+    );
+}
@@ -0,0 +1,225 @@
+//! Unit tests for the market data bus, on the in-memory implementation
+
+use chrono::Utc;
+use perptrix::metrics::Metrics;
+use perptrix::services::candle_events::CandleClosed;
+use perptrix::services::market_bus::{
+    BusConsumer, BusMessage, BusTopic, MarketBus, MarketEvent, MemoryMarketBus, StreamOffset,
+};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+fn mid(symbol: &str, price: f64) -> MarketEvent {
+    MarketEvent::MidPrice {
+        symbol: symbol.to_string(),
+        price,
+        timestamp: Utc::now(),
+    }
+}
+
+fn price(message: &BusMessage) -> f64 {
+    match message.event {
+        MarketEvent::MidPrice { price, .. } => price,
+        _ => panic!("unexpected event {:?}", message.event),
+    }
+}
+
+#[test]
+fn events_are_routed_to_their_topic_and_round_trip_as_json() {
+    let close = MarketEvent::CandleClosed(CandleClosed {
+        symbol: "binance:BTCUSDT".to_string(),
+        interval: "1m".to_string(),
+        timestamp: Utc::now(),
+    });
+    assert_eq!(close.topic(), BusTopic::CandleCloses);
+    assert_eq!(close.symbol(), "binance:BTCUSDT");
+    assert_eq!(BusTopic::CandleCloses.stream_key(), "bus:candle_closes");
+
+    let json = serde_json::to_value(&close).unwrap();
+    assert_eq!(json["type"], "candle_closed");
+    let decoded: MarketEvent = serde_json::from_value(json).unwrap();
+    assert!(matches!(decoded, MarketEvent::CandleClosed(c) if c.symbol == "binance:BTCUSDT"));
+}
+
+#[tokio::test]
+async fn groups_see_every_event_and_consumers_share_them() {
+    let bus = MemoryMarketBus::new();
+    bus.create_group(BusTopic::MidPrices, "api", &StreamOffset::Beginning)
+        .await
+        .unwrap();
+    bus.create_group(BusTopic::MidPrices, "notifier", &StreamOffset::Beginning)
+        .await
+        .unwrap();
+    for i in 0..4 {
+        bus.publish(&mid("BTC", 100.0 + i as f64)).await.unwrap();
+    }
+    // Signals live on another stream
+    bus.create_group(BusTopic::Signals, "api", &StreamOffset::Beginning)
+        .await
+        .unwrap();
+
+    let first = bus
+        .read_group(BusTopic::MidPrices, "api", "a", 2)
+        .await
+        .unwrap();
+    let second = bus
+        .read_group(BusTopic::MidPrices, "api", "b", 10)
+        .await
+        .unwrap();
+    assert_eq!(
+        first.iter().map(price).collect::<Vec<_>>(),
+        vec![100.0, 101.0]
+    );
+    assert_eq!(
+        second.iter().map(price).collect::<Vec<_>>(),
+        vec![102.0, 103.0]
+    );
+
+    let other = bus
+        .read_group(BusTopic::MidPrices, "notifier", "a", 10)
+        .await
+        .unwrap();
+    assert_eq!(other.len(), 4);
+    assert!(bus
+        .read_group(BusTopic::Signals, "api", "a", 10)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn groups_created_at_latest_skip_history_and_keep_their_position() {
+    let bus = MemoryMarketBus::new();
+    bus.publish(&mid("BTC", 100.0)).await.unwrap();
+    bus.create_group(BusTopic::MidPrices, "api", &StreamOffset::Latest)
+        .await
+        .unwrap();
+    bus.publish(&mid("BTC", 101.0)).await.unwrap();
+
+    // Creating an existing group again does not move it
+    bus.create_group(BusTopic::MidPrices, "api", &StreamOffset::Beginning)
+        .await
+        .unwrap();
+    let messages = bus
+        .read_group(BusTopic::MidPrices, "api", "a", 10)
+        .await
+        .unwrap();
+    assert_eq!(messages.iter().map(price).collect::<Vec<_>>(), vec![101.0]);
+}
+
+#[tokio::test]
+async fn unacknowledged_events_are_redelivered_to_another_consumer() {
+    let bus = Arc::new(MemoryMarketBus::new());
+    bus.publish(&mid("BTC", 100.0)).await.unwrap();
+    bus.publish(&mid("ETH", 10.0)).await.unwrap();
+
+    let consumer = BusConsumer::new(bus.clone(), BusTopic::MidPrices, "paper", "a")
+        .with_claim_idle(Duration::ZERO);
+    consumer.join(&StreamOffset::Beginning).await.unwrap();
+
+    // The ETH event fails and stays pending
+    let handled = consumer
+        .poll(&|message: BusMessage| async move {
+            match message.event.symbol() {
+                "ETH" => Err("notifier down".into()),
+                _ => Ok(()),
+            }
+        })
+        .await
+        .unwrap();
+    assert_eq!(handled, 1);
+    assert_eq!(bus.lag(BusTopic::MidPrices, "paper").await.unwrap(), 1);
+
+    // A consumer that takes over retries it
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let takeover = BusConsumer::new(bus.clone(), BusTopic::MidPrices, "paper", "b")
+        .with_claim_idle(Duration::ZERO);
+    let handled = takeover
+        .poll(&|message: BusMessage| {
+            let seen = seen.clone();
+            async move {
+                seen.lock()
+                    .unwrap()
+                    .push(message.event.symbol().to_string());
+                Ok(())
+            }
+        })
+        .await
+        .unwrap();
+    assert_eq!(handled, 1);
+    assert_eq!(*seen.lock().unwrap(), vec!["ETH".to_string()]);
+    assert_eq!(bus.lag(BusTopic::MidPrices, "paper").await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn pending_events_are_not_claimed_before_the_idle_time() {
+    let bus = MemoryMarketBus::new();
+    bus.create_group(BusTopic::MidPrices, "api", &StreamOffset::Beginning)
+        .await
+        .unwrap();
+    bus.publish(&mid("BTC", 100.0)).await.unwrap();
+    assert_eq!(
+        bus.read_group(BusTopic::MidPrices, "api", "a", 10)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+
+    let claimed = bus
+        .claim_stale(BusTopic::MidPrices, "api", "b", Duration::from_secs(60), 10)
+        .await
+        .unwrap();
+    assert!(claimed.is_empty());
+}
+
+#[tokio::test]
+async fn topics_replay_from_an_offset() {
+    let bus = MemoryMarketBus::new();
+    let mut ids = Vec::new();
+    for i in 0..5 {
+        ids.push(bus.publish(&mid("BTC", 100.0 + i as f64)).await.unwrap());
+    }
+
+    let all = bus
+        .replay(BusTopic::MidPrices, &StreamOffset::Beginning, 100)
+        .await
+        .unwrap();
+    assert_eq!(all.len(), 5);
+    assert_eq!(all.iter().map(|m| m.id.clone()).collect::<Vec<_>>(), ids);
+
+    let after = bus
+        .replay(BusTopic::MidPrices, &StreamOffset::After(ids[2].clone()), 1)
+        .await
+        .unwrap();
+    assert_eq!(after.iter().map(price).collect::<Vec<_>>(), vec![103.0]);
+    assert!(bus
+        .replay(BusTopic::MidPrices, &StreamOffset::Latest, 100)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn consumer_lag_is_reported() {
+    let bus = Arc::new(MemoryMarketBus::new());
+    let metrics = Arc::new(Metrics::new().unwrap());
+    let consumer = BusConsumer::new(bus.clone(), BusTopic::MidPrices, "api", "a")
+        .with_batch_size(2)
+        .with_metrics(metrics.clone());
+    consumer.join(&StreamOffset::Beginning).await.unwrap();
+    for i in 0..5 {
+        bus.publish(&mid("BTC", 100.0 + i as f64)).await.unwrap();
+    }
+    assert_eq!(bus.lag(BusTopic::MidPrices, "api").await.unwrap(), 5);
+
+    consumer
+        .poll(&|_: BusMessage| async { Ok(()) })
+        .await
+        .unwrap();
+    let lag = metrics
+        .bus_consumer_lag
+        .with_label_values(&["mid_prices", "api"])
+        .get();
+    assert_eq!(lag, 3.0);
+}
@@ -1,6 +1,11 @@
 //! Unit tests for market data provider
 
-use perptrix::services::market_data::{MarketDataProvider, PlaceholderMarketDataProvider};
+use perptrix::models::indicators::Candle;
+use perptrix::services::market_data::{
+    split_symbol, venue_symbol, MarketDataProvider, MarketDataRegistry,
+    PlaceholderMarketDataProvider, DEFAULT_VENUE,
+};
+use std::sync::Arc;
 
 #[tokio::test]
 async fn test_placeholder_provider() {
@@ -10,5 +15,55 @@ async fn test_placeholder_provider() {
     assert!(provider.subscribe("BTC").await.is_ok());
 }
 
+#[test]
+fn symbols_are_namespaced_by_venue() {
+    assert_eq!(split_symbol("BTC"), (DEFAULT_VENUE.to_string(), "BTC"));
+    assert_eq!(
+        split_symbol("Binance:BTCUSDT"),
+        ("binance".to_string(), "BTCUSDT")
+    );
+    assert_eq!(venue_symbol("binance", "BTCUSDT"), "binance:BTCUSDT");
+    assert_eq!(venue_symbol(DEFAULT_VENUE, "BTC"), "BTC");
+}
+
+/// Provider answering every price request with a fixed price
+struct FixedPrice(f64);
+
+#[async_trait::async_trait]
+impl MarketDataProvider for FixedPrice {
+    async fn get_candles(
+        &self,
+        _symbol: &str,
+        _limit: usize,
+    ) -> Result<Vec<Candle>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Vec::new())
+    }
+
+    async fn get_latest_price(
+        &self,
+        symbol: &str,
+    ) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
+        // Providers only ever see their venue's native symbol
+        assert!(!symbol.contains(':'));
+        Ok(self.0)
+    }
 
+    async fn subscribe(
+        &self,
+        _symbol: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn registry_routes_symbols_to_their_venue() {
+    let registry = MarketDataRegistry::new(Arc::new(FixedPrice(1.0)))
+        .with_provider("binance", Arc::new(FixedPrice(2.0)));
+    assert_eq!(registry.venues(), vec!["binance", DEFAULT_VENUE]);
 
+    assert_eq!(registry.get_latest_price("BTC").await.unwrap(), 1.0);
+    assert_eq!(registry.get_latest_price("binance:BTCUSDT").await.unwrap(), 2.0);
+    assert!(registry.get_latest_price("okx:BTC-USDT-SWAP").await.is_err());
+    assert!(registry.is_connected().await);
+}
@@ -0,0 +1,37 @@
+//! Unit tests for subscription control commands
+
+use perptrix::services::subscription_sync::{SubscriptionCommand, SubscriptionSource};
+use serde_json::json;
+
+#[test]
+fn commands_are_tagged_by_action() {
+    assert_eq!(
+        serde_json::to_value(SubscriptionCommand::Reload).unwrap(),
+        json!({ "action": "reload" })
+    );
+    assert_eq!(
+        serde_json::to_value(SubscriptionCommand::Unsubscribe {
+            symbol: "binance:BTCUSDT".to_string()
+        })
+        .unwrap(),
+        json!({ "action": "unsubscribe", "symbol": "binance:BTCUSDT" })
+    );
+
+    let command: SubscriptionCommand =
+        serde_json::from_value(json!({ "action": "subscribe", "symbol": "ETH" })).unwrap();
+    assert_eq!(
+        command,
+        SubscriptionCommand::Subscribe {
+            symbol: "ETH".to_string()
+        }
+    );
+    assert!(serde_json::from_value::<SubscriptionCommand>(json!({ "action": "pause" })).is_err());
+}
+
+#[test]
+fn sources_serialize_in_snake_case() {
+    assert_eq!(
+        serde_json::to_value([SubscriptionSource::Static, SubscriptionSource::Manual]).unwrap(),
+        json!(["static", "manual"])
+    );
+}
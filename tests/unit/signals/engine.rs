@@ -37,6 +37,8 @@ fn create_test_strategy(symbol: &str) -> Strategy {
                     short_max: -1,
                 },
             },
+            execution: None,
+            intrabar: false,
         },
         created_at: Utc::now(),
         updated_at: Utc::now(),
@@ -80,5 +82,19 @@ fn test_evaluate_sufficient_data() {
     assert!(signal.confidence <= 1.0);
 }
 
+#[test]
+fn test_forming_bar_is_evaluated_only_intrabar() {
+    let mut candles = create_uptrend_candles(250);
+    let closed_price = candles.last().unwrap().close;
+    let mut forming = Candle::new(300.0, 301.0, 299.0, 300.5, 10.0, Utc::now());
+    forming.is_closed = false;
+    candles.push(forming);
 
+    let mut strategy = create_test_strategy("BTC");
+    let signal = SignalEngine::evaluate(&candles, &strategy).unwrap();
+    assert_eq!(signal.price, closed_price);
 
+    strategy.config.intrabar = true;
+    let signal = SignalEngine::evaluate(&candles, &strategy).unwrap();
+    assert_eq!(signal.price, 300.5);
+}
@@ -37,6 +37,8 @@ fn create_test_strategy(symbol: &str) -> Strategy {
                     short_max: -1,
                 },
             },
+            execution: None,
+            intrabar: false,
         },
         created_at: Utc::now(),
         updated_at: Utc::now(),